@@ -8,7 +8,7 @@ use domain::{
 use reqwest::Client;
 use tokio::{
     fs::File,
-    io::{AsyncSeekExt, AsyncWriteExt},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
     process::Command,
 };
 use tracing::Instrument;
@@ -71,13 +71,13 @@ impl IBackgroundService for FileDownloadRunner {
                                     .join(format!("file-storage/RangelyDownloadFile/{}", task_file.metadata_id.to_string().as_str()).as_str())
                                     .unwrap();
                                     log::trace!("File {} is downloading from url \"{}\" .", task_file.metadata_id, url);
-                                    let file_length = tokio::select! {
+                                    let (file_length, expected_hash) = tokio::select! {
                                         x = http_client.head(url.clone()).send() => {
                                             match x {
                                                 Ok(x) => {
                                                     let headers = x.headers();
                                                     let content_length = headers.get("Content-Length");
-                                                    match content_length {
+                                                    (match content_length {
                                                         Some(x) => {
                                                             match x.to_str() {
                                                                 Ok(x) => {
@@ -92,15 +92,15 @@ impl IBackgroundService for FileDownloadRunner {
                                                             }
                                                         }
                                                         None => 0
-                                                    }
+                                                    }, expected_hash(headers))
                                                 },
                                                 Err(_) => {
-                                                    0
+                                                    (0, None)
                                                 }
                                             }
                                         }
                                         _ = tokio::time::sleep(tokio::time::Duration::from_secs(5)) => {
-                                            0
+                                            (0, None)
                                         }
                                     };
                                     if file_length == 0 {
@@ -113,7 +113,7 @@ impl IBackgroundService for FileDownloadRunner {
                                             }
                                         };
                                         log::trace!("File {} download finished.", task_file.metadata_id);
-                                        let mut file = match tokio::fs::File::create(path).await {
+                                        let mut file = match tokio::fs::File::create(&path).await {
                                             Ok(x) => x,
                                             Err(e) => {
                                                 log::error!("{}", e);
@@ -138,7 +138,7 @@ impl IBackgroundService for FileDownloadRunner {
                                             task_file.file_name
                                         );
                                     } else {
-                                        let file = match tokio::fs::File::create(path).await {
+                                        let file = match tokio::fs::File::create(&path).await {
                                             Ok(x) => x,
                                             Err(e) => {
                                                 log::error!("{}", e);
@@ -187,6 +187,19 @@ impl IBackgroundService for FileDownloadRunner {
                                             }
                                         }
                                     }
+                                    if let Some((hash_algorithm, hash)) = expected_hash {
+                                        let actual_hash = match hash_file(&path, &hash_algorithm).await {
+                                            Ok(x) => x,
+                                            Err(e) => {
+                                                log::error!("{}", e);
+                                                anyhow::bail!(e);
+                                            }
+                                        };
+                                        if actual_hash != hash {
+                                            log::error!("File {} has {hash_algorithm} hash {actual_hash}, but {hash} is expected.", task_file.metadata_id);
+                                            anyhow::bail!("File {} is corrupted after download.", task_file.metadata_id);
+                                        }
+                                    }
                                     Ok(())
                                 };
                                 match task.await {
@@ -413,3 +426,23 @@ async fn download_task(
     }
     Ok(())
 }
+
+fn expected_hash(headers: &reqwest::header::HeaderMap) -> Option<(String, String)> {
+    let hash_algorithm = headers.get("CONTENT-HASH-ALGORITHM")?.to_str().ok()?;
+    let hash = headers.get("CONTENT-HASH")?.to_str().ok()?;
+    Some((hash_algorithm.to_string(), hash.to_string()))
+}
+
+async fn hash_file(path: &std::path::Path, hash_algorithm: &str) -> anyhow::Result<String> {
+    let mut hasher = hash_algorithm.parse::<lib_file_hash::HashAlgorithm>()?.hasher();
+    let mut file = File::open(path).await?;
+    let mut buffer = vec![0; 65536];
+    loop {
+        let n = file.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize())
+}
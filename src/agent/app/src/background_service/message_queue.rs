@@ -6,8 +6,9 @@ use domain::{
     model::entity::{
         file::FileType,
         task::{
-            CollectFrom, CollectRule, CollectTo, FacilityKind, FileInfo, Requirements,
-            SoftwareDeploymentStatus, StdInKind, TaskStatus, TaskType,
+            CollectFrom, CollectRule, CollectTo, FacilityKind, FileInfo, Launcher, OutValidator,
+            Requirements, ScriptKind, ScriptOrigin, SoftwareDeploymentStatus, StdInKind,
+            TaskStatus, TaskType, ValidateRule,
         },
         SubTask, Task,
     },
@@ -101,31 +102,34 @@ impl IBackgroundService for KafkaMessageQueue {
                                                             std_in,
                                                             files,
                                                             requirements,
+                                                            launcher,
+                                                            std_out_validator,
+                                                            std_err_validator,
+                                                            validators,
                                                         } => {
                                                             sub_task.facility_kind = match facility_kind.clone() {
                                                                 crate::dto::FacilityKind::Spack { name, argument_list } => FacilityKind::Spack { name, argument_list },
                                                                 crate::dto::FacilityKind::Singularity { image, tag } => FacilityKind::Singularity { image, tag },
                                                             };
-                                                            sub_task.requirements = requirements.clone().map(|x| {
-                                                                Requirements { cpu_cores: x.cpu_cores, node_count: x.node_count, max_wall_time: x.max_wall_time, max_cpu_time: x.max_cpu_time, stop_time: x.stop_time }
-                                                            });
+                                                            sub_task.requirements = requirements.clone().map(requirements_from_dto);
                                                             sub_task.task_type = TaskType::UsecaseExecution { name: name.clone(), arguments: arguments.clone(), environments: environments.clone(), std_in: match std_in {
                                                                 crate::dto::StdInKind::Text { text } => StdInKind::Text { text: text.clone() },
                                                                 crate::dto::StdInKind::File { path } => StdInKind::File { path: path.clone() },
                                                                 crate::dto::StdInKind::None => StdInKind::Unknown,
-                                                            }, files: files.iter().map(|x| match x.clone() {
-                                                                crate::dto::FileInfo::Input { path, is_package, form } => match form {
-                                                                    crate::dto::InFileForm::Id(id) => FileInfo {id: uuid::Uuid::new_v4(), metadata_id: id, path, is_package, optional: false, file_type: FileType::IN, is_generated: false, ..Default::default() },
-                                                                    crate::dto::InFileForm::Content(text) => FileInfo { id: uuid::Uuid::new_v4(), metadata_id: uuid::Uuid::new_v4(), path, is_package, optional: false, file_type: FileType::IN, is_generated: true, text }
-                                                                },
-                                                                crate::dto::FileInfo::Output { id, path, is_package, optional } => FileInfo {id: uuid::Uuid::new_v4(), metadata_id: id, path, is_package, optional, file_type: FileType::OUT, ..Default::default() },
-                                                            }).collect::<Vec<FileInfo>>() }
+                                                            }, files: files.iter().cloned().map(file_info_from_dto).collect::<Vec<FileInfo>>(), launcher: match launcher.clone() {
+                                                                crate::dto::Launcher::None => Launcher::None,
+                                                                crate::dto::Launcher::Mpirun => Launcher::Mpirun,
+                                                                crate::dto::Launcher::Srun => Launcher::Srun,
+                                                                crate::dto::Launcher::Mpiexec => Launcher::Mpiexec,
+                                                                crate::dto::Launcher::Custom(template) => Launcher::Custom(template),
+                                                            }, std_out_validator: std_out_validator.clone().map(out_validator_from_dto), std_err_validator: std_err_validator.clone().map(out_validator_from_dto), validators: validators.iter().map(|(path, x)| (path.clone(), out_validator_from_dto(x.clone()))).collect() }
                                                         },
                                                         crate::dto::TaskBody::CollectedOut {
                                                             from,
                                                             rule,
                                                             to,
                                                             optional,
+                                                            validators,
                                                         } => {
                                                             sub_task.task_type = TaskType::CollectedOut { from: match from {
                                                                 crate::dto::CollectFrom::FileOut { path } => CollectFrom::FileOut { path: path.clone() },
@@ -138,7 +142,34 @@ impl IBackgroundService for KafkaMessageQueue {
                                                             }, to: match to.clone() {
                                                                 crate::dto::CollectTo::File { id, path } => CollectTo::File { id, path },
                                                                 crate::dto::CollectTo::Text { id } => CollectTo::Text { id },
-                                                            }, optional: *optional }
+                                                            }, optional: *optional, validators: validators.iter().cloned().map(out_validator_from_dto).collect() }
+                                                        },
+                                                        crate::dto::TaskBody::ExecuteScript {
+                                                            kind,
+                                                            origin,
+                                                            files,
+                                                            validators,
+                                                            requirements,
+                                                        } => {
+                                                            let crate::dto::ScriptKind::Python { environment } = kind;
+                                                            let mut prefix = None;
+                                                            match environment.clone() {
+                                                                crate::dto::PythonEnvironment::System => {}
+                                                                crate::dto::PythonEnvironment::Prefix { path } => prefix = Some(path),
+                                                                crate::dto::PythonEnvironment::Spack { name, argument_list } => sub_task.facility_kind = FacilityKind::Spack { name, argument_list },
+                                                                crate::dto::PythonEnvironment::Singularity { image, tag } => sub_task.facility_kind = FacilityKind::Singularity { image, tag },
+                                                            }
+                                                            sub_task.requirements = requirements.clone().map(requirements_from_dto);
+                                                            let origin = match origin {
+                                                                crate::dto::ScriptOriginKind::Git { url } => ScriptOrigin::git(url),
+                                                                crate::dto::ScriptOriginKind::Edit { content } => ScriptOrigin::Edit { content: content.clone() },
+                                                            };
+                                                            let mut files = files.iter().cloned().map(file_info_from_dto).collect::<Vec<FileInfo>>();
+                                                            // 编辑的脚本作为生成的输入文件写入工作目录
+                                                            if let ScriptOrigin::Edit { content } = &origin {
+                                                                files.push(FileInfo { id: uuid::Uuid::new_v4(), metadata_id: uuid::Uuid::new_v4(), path: ScriptOrigin::EDITED_SCRIPT.to_string(), file_type: FileType::IN, is_generated: true, text: content.clone(), ..Default::default() });
+                                                            }
+                                                            sub_task.task_type = TaskType::ExecuteScript { kind: ScriptKind::Python { prefix }, origin, files, validators: validators.iter().map(|(path, x)| (path.clone(), out_validator_from_dto(x.clone()))).collect() }
                                                         },
                                                     }
                                                     sub_task
@@ -210,3 +241,81 @@ impl KafkaMessageQueue {
         }
     }
 }
+
+fn requirements_from_dto(x: crate::dto::Requirements) -> Requirements {
+    Requirements {
+        cpu_cores: x.cpu_cores,
+        node_count: x.node_count,
+        max_wall_time: x.max_wall_time,
+        max_cpu_time: x.max_cpu_time,
+        stop_time: x.stop_time,
+        partition: x.partition,
+        account: x.account,
+        qos: x.qos,
+        memory_per_node: x.memory_per_node,
+        gpus_per_node: x.gpus_per_node,
+        exclusive: x.exclusive,
+    }
+}
+
+fn out_validator_from_dto(x: crate::dto::OutValidator) -> OutValidator {
+    OutValidator {
+        rule: match x.validate_rules {
+            crate::dto::ValidateRule::Regex(exp) => ValidateRule::Regex { exp },
+            crate::dto::ValidateRule::IsEmpty(expected) => ValidateRule::IsEmpty { expected },
+        },
+        pass_is_success: matches!(
+            x.pass_operation,
+            crate::dto::ValidatedOperation::ReportSuccess
+        ),
+        failure_is_success: matches!(
+            x.failure_operation,
+            crate::dto::ValidatedOperation::ReportSuccess
+        ),
+    }
+}
+
+fn file_info_from_dto(x: crate::dto::FileInfo) -> FileInfo {
+    match x {
+        crate::dto::FileInfo::Input {
+            path,
+            is_package,
+            form,
+        } => match form {
+            crate::dto::InFileForm::Id(id) => FileInfo {
+                id: uuid::Uuid::new_v4(),
+                metadata_id: id,
+                path,
+                is_package,
+                optional: false,
+                file_type: FileType::IN,
+                is_generated: false,
+                ..Default::default()
+            },
+            crate::dto::InFileForm::Content(text) => FileInfo {
+                id: uuid::Uuid::new_v4(),
+                metadata_id: uuid::Uuid::new_v4(),
+                path,
+                is_package,
+                optional: false,
+                file_type: FileType::IN,
+                is_generated: true,
+                text,
+            },
+        },
+        crate::dto::FileInfo::Output {
+            id,
+            path,
+            is_package,
+            optional,
+        } => FileInfo {
+            id: uuid::Uuid::new_v4(),
+            metadata_id: id,
+            path,
+            is_package,
+            optional,
+            file_type: FileType::OUT,
+            ..Default::default()
+        },
+    }
+}
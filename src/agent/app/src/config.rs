@@ -32,6 +32,12 @@ pub struct SchedulerConfig {
     pub r#type: String,
     #[serde(default = "Default::default")]
     pub queue: Option<String>,
+    /// Grid Engine 多槽位作业使用的并行环境
+    #[serde(default = "SchedulerConfig::default_parallel_environment")]
+    pub parallel_environment: String,
+    /// 本地执行器可用的 cgroup v2 目录，未设置时仅通过 rlimit 限制作业资源
+    #[serde(default = "Default::default")]
+    pub cgroup: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +100,8 @@ impl Default for SchedulerConfig {
         Self {
             r#type: Self::default_type(),
             queue: None,
+            parallel_environment: Self::default_parallel_environment(),
+            cgroup: None,
         }
     }
 }
@@ -102,6 +110,10 @@ impl SchedulerConfig {
     pub fn default_type() -> String {
         "slurm".to_string()
     }
+
+    pub fn default_parallel_environment() -> String {
+        "mpi".to_string()
+    }
 }
 
 impl Default for SshProxyConfig {
@@ -52,6 +52,18 @@ pub enum TaskBody {
         files: Vec<FileInfo>,
         /// 计算资源配置
         requirements: Option<Requirements>,
+        /// 并行启动器
+        #[serde(default)]
+        launcher: Launcher,
+        /// 标准输出的校验规则
+        #[serde(default)]
+        std_out_validator: Option<OutValidator>,
+        /// 标准错误输出的校验规则
+        #[serde(default)]
+        std_err_validator: Option<OutValidator>,
+        /// 输出文件的校验规则，键为输出文件路径
+        #[serde(default)]
+        validators: HashMap<String, OutValidator>,
     },
     /// 输出收集
     CollectedOut {
@@ -63,6 +75,23 @@ pub enum TaskBody {
         to: CollectTo,
         /// 如果收集不到是否报错（true 时不报错）
         optional: bool,
+        /// 收集结果的校验规则
+        #[serde(default)]
+        validators: Vec<OutValidator>,
+    },
+    /// 执行脚本
+    ExecuteScript {
+        /// 脚本类型
+        kind: ScriptKind,
+        /// 脚本来源
+        origin: ScriptOriginKind,
+        /// 文件信息列表
+        files: Vec<FileInfo>,
+        /// 输出文件的校验规则，键为输出文件路径
+        #[serde(default)]
+        validators: HashMap<String, OutValidator>,
+        /// 计算资源配置
+        requirements: Option<Requirements>,
     },
 }
 
@@ -79,6 +108,19 @@ pub struct Requirements {
     pub max_cpu_time: Option<usize>,
     /// 定时终止（utc 0 时区 时间戳）
     pub stop_time: Option<usize>,
+    /// 分区（Slurm）或队列（PBS），未指定时使用 agent 配置的默认队列
+    pub partition: Option<String>,
+    /// 记账账户
+    pub account: Option<String>,
+    /// 服务质量（QOS）
+    pub qos: Option<String>,
+    /// 每个节点的内存（MB）
+    pub memory_per_node: Option<usize>,
+    /// 每个节点的 GPU 数
+    pub gpus_per_node: Option<usize>,
+    /// 是否独占节点
+    #[serde(default)]
+    pub exclusive: bool,
 }
 
 /// 从哪里收集
@@ -182,6 +224,22 @@ pub enum FacilityKind {
         tag: String,
     },
 }
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+/// 并行启动器
+pub enum Launcher {
+    /// 直接执行
+    None,
+    /// mpirun
+    #[default]
+    Mpirun,
+    /// srun
+    Srun,
+    /// mpiexec
+    Mpiexec,
+    /// 自定义启动命令模板
+    Custom(String),
+}
 /// 任务结果
 #[derive(Default, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
@@ -233,6 +291,8 @@ pub struct TaskUsedResource {
     pub start_time: i64,
     /// 结束时间
     pub end_time: i64,
+    /// 作业实际所在的分区或队列
+    pub partition: Option<String>,
 }
 
 #[derive(Default, Deserialize, Serialize, Clone, Debug)]
@@ -290,3 +350,103 @@ pub enum Via {
     #[default]
     Unkonwn,
 }
+
+/// 脚本类型
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum ScriptKind {
+    /// Python 脚本
+    Python {
+        /// 运行脚本的 Python 环境
+        #[serde(default)]
+        environment: PythonEnvironment,
+    },
+}
+
+/// Python 环境
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(tag = "type")]
+pub enum PythonEnvironment {
+    /// 集群上 PATH 中的 python3
+    #[default]
+    System,
+    /// 集群上已有的 venv 或 conda 环境
+    Prefix {
+        /// 环境目录
+        path: String,
+    },
+    /// 由 spack 安装的 Python
+    #[serde(rename_all = "camelCase")]
+    Spack {
+        /// 软件名称
+        name: String,
+        /// 安装参数
+        argument_list: Vec<String>,
+    },
+    /// 容器镜像中的 Python
+    Singularity {
+        /// 镜像名
+        image: String,
+        /// 镜像 tag
+        tag: String,
+    },
+}
+
+/// 脚本来源
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum ScriptOriginKind {
+    /// 从 git 拉取
+    Git {
+        /// 仓库链接，可以用 `#<路径>` 指定仓库中的脚本，默认为 `main.py`
+        url: String,
+    },
+    /// 从工作流编辑
+    Edit {
+        /// 内容
+        content: String,
+    },
+}
+
+/// 输出校验
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct OutValidator {
+    /// 校验规则
+    pub validate_rules: ValidateRule,
+    /// 校验成功时的操作
+    #[serde(default = "ValidatedOperation::success")]
+    pub pass_operation: ValidatedOperation,
+    /// 校验失败时的操作
+    #[serde(default = "ValidatedOperation::failure")]
+    pub failure_operation: ValidatedOperation,
+}
+
+/// 校验规则
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidateRule {
+    /// 匹配正则
+    Regex(String),
+    /// 是否为空
+    IsEmpty(bool),
+}
+
+/// 校验后的操作
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidatedOperation {
+    /// 报告成功
+    ReportSuccess,
+    /// 报告失败
+    ReportFailure,
+}
+
+impl ValidatedOperation {
+    fn success() -> Self {
+        Self::ReportSuccess
+    }
+    fn failure() -> Self {
+        Self::ReportFailure
+    }
+}
@@ -44,6 +44,7 @@ impl TaskReportService for HttpClient {
                     node: used_resources.node,
                     start_time: used_resources.start_time,
                     end_time: used_resources.end_time,
+                    partition: used_resources.partition,
                 }),
                 ..Default::default()
             },
@@ -148,7 +148,9 @@ impl IMutableRepository<SubTask> for JSONRepository {
         if let Some(x) = sub_tasks.iter().position(|x| x.id == entity.id) {
             sub_tasks.remove(x);
         }
-        if let TaskType::UsecaseExecution { files, .. } = entity.task_type.clone() {
+        if let TaskType::UsecaseExecution { files, .. } | TaskType::ExecuteScript { files, .. } =
+            entity.task_type.clone()
+        {
             for file in files {
                 self.insert(File {
                     id: file.id,
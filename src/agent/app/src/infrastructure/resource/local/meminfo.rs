@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MemInfo {
+    /// Total memory (unit: byte)
+    total: u64,
+    /// Memory available for starting new applications (unit: byte)
+    available: u64,
+}
+
+impl MemInfo {
+    pub const PATH: &'static str = "/proc/meminfo";
+
+    pub fn new(s: &str) -> anyhow::Result<Self> {
+        let fields = s
+            .lines()
+            .filter_map(|l| {
+                let (key, value) = l.split_once(':')?;
+                // values are in kB
+                let kb = value.trim().trim_end_matches("kB").trim().parse::<u64>().ok()?;
+                Some((key, kb << 10))
+            })
+            .collect::<HashMap<_, _>>();
+        let total = *fields.get("MemTotal").ok_or(anyhow::anyhow!("MemTotal not found"))?;
+        let available = fields
+            .get("MemAvailable")
+            .or(fields.get("MemFree"))
+            .copied()
+            .unwrap_or_default();
+        Ok(Self { total, available })
+    }
+
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    #[inline]
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemInfo;
+    use indoc::indoc;
+
+    #[test]
+    fn test_meminfo() {
+        let info = MemInfo::new(indoc! {"
+            MemTotal:       16303428 kB
+            MemFree:         1203948 kB
+            MemAvailable:    9613956 kB
+            Buffers:          412756 kB
+        "})
+        .unwrap();
+        assert_eq!(info.total(), 16303428 << 10);
+        assert_eq!(info.used(), (16303428 - 9613956) << 10);
+    }
+}
@@ -0,0 +1,52 @@
+mod meminfo;
+mod procs;
+
+use anyhow::Context;
+use async_trait::async_trait;
+
+use super::{SchedulerStat, SchedulerTotalResources, SchedulerUsedResources};
+use crate::infrastructure::ssh_proxy::SshProxy;
+
+/// Resources of the agent host itself, used with the local executor
+pub struct Local;
+
+impl Local {
+    async fn meminfo() -> anyhow::Result<meminfo::MemInfo> {
+        let s = tokio::fs::read_to_string(meminfo::MemInfo::PATH)
+            .await
+            .context(meminfo::MemInfo::PATH)?;
+        meminfo::MemInfo::new(&s)
+    }
+
+    #[inline]
+    fn cpus() -> usize {
+        std::thread::available_parallelism().map(usize::from).unwrap_or(1)
+    }
+}
+
+#[async_trait]
+impl SchedulerStat for Local {
+    async fn total(&self, _proxy: &SshProxy) -> anyhow::Result<SchedulerTotalResources> {
+        Ok(SchedulerTotalResources {
+            memory: Self::meminfo().await?.total(),
+            core_number: Self::cpus(),
+            node_number: 1,
+        })
+    }
+
+    async fn used(&self, _proxy: &SshProxy) -> anyhow::Result<SchedulerUsedResources> {
+        let meminfo = Self::meminfo().await?;
+        let (stopped, running) = tokio::task::spawn_blocking(procs::jobs_count)
+            .await?
+            .context("/proc")?;
+        // there is no accounting of allocated cores, one core per job is assumed
+        let allocated_cpu_count = (stopped + running).min(Self::cpus());
+        Ok(SchedulerUsedResources {
+            allocated_memory: meminfo.used(),
+            allocated_cpu_count,
+            queuing_task_count: 0,
+            running_task_count: running,
+            used_node_count: usize::from(allocated_cpu_count > 0),
+        })
+    }
+}
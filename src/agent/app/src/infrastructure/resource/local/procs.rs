@@ -0,0 +1,77 @@
+/// State of a process read from `/proc/[pid]/stat`
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProcStat {
+    state: char,
+    ppid: u32,
+    pgrp: u32,
+}
+
+impl ProcStat {
+    pub fn new(s: &str) -> Option<Self> {
+        // the command name may contain spaces and parentheses
+        let (_, rest) = s.rsplit_once(')')?;
+        let mut fields = rest.split_whitespace();
+        Some(Self {
+            state: fields.next()?.chars().next()?,
+            ppid: fields.next()?.parse().ok()?,
+            pgrp: fields.next()?.parse().ok()?,
+        })
+    }
+
+    /// whether it is a job spawned by the local executor of the agent,
+    /// i.e. a direct child leading its own process group
+    #[inline]
+    pub fn is_job_of(&self, pid: u32, parent: u32) -> bool {
+        self.ppid == parent && self.pgrp == pid
+    }
+
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        matches!(self.state, 'T' | 't')
+    }
+}
+
+/// get the count of stopped and running local jobs separately: `(stopped, runnings)`
+pub fn jobs_count() -> std::io::Result<(usize, usize)> {
+    let parent = std::process::id();
+    let mut count = (0, 0);
+    for entry in std::fs::read_dir("/proc")? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Some(pid) = entry.file_name().to_str().and_then(|el| el.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        match ProcStat::new(&stat) {
+            Some(stat) if stat.is_job_of(pid, parent) => {
+                if stat.is_stopped() {
+                    count.0 += 1;
+                } else {
+                    count.1 += 1;
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProcStat;
+
+    #[test]
+    fn test_proc_stat() {
+        let stat =
+            ProcStat::new("4321 (bash (job)) T 1000 4321 1000 0 -1 4194560 97 0 0 0").unwrap();
+        assert!(stat.is_job_of(4321, 1000));
+        assert!(!stat.is_job_of(4321, 1));
+        assert!(stat.is_stopped());
+        let stat = ProcStat::new("4322 (sleep) S 4321 4321 1000 0 -1 4194304 77 0 0 0").unwrap();
+        assert!(!stat.is_job_of(4322, 4321));
+        assert!(!stat.is_stopped());
+    }
+}
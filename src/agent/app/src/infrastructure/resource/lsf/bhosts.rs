@@ -0,0 +1,54 @@
+use super::super::table::parse_table;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hosts {
+    hosts: Vec<Host>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Host {
+    name: String,
+    /// Slots occupied by jobs
+    njobs: usize,
+}
+
+impl Hosts {
+    pub const ARGS: &[&'static str] = &["-w"];
+
+    pub fn new(s: &[u8]) -> Self {
+        let s = String::from_utf8_lossy(s);
+        let hosts = parse_table(&s)
+            .into_iter()
+            .filter_map(|row| {
+                Some(Host {
+                    name: row.get("HOST_NAME")?.to_string(),
+                    njobs: row.get("NJOBS")?.parse().ok()?,
+                })
+            })
+            .collect();
+        Self { hosts }
+    }
+
+    #[inline]
+    pub fn alloc_cpus(&self) -> usize {
+        self.hosts.iter().map(|h| h.njobs).sum()
+    }
+
+    /// count of hosts running at least one job
+    #[inline]
+    pub fn alloc_nodes(&self) -> usize {
+        self.hosts.iter().filter(|h| h.njobs > 0).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hosts;
+
+    #[test]
+    fn test_hosts() {
+        let hosts = Hosts::new(include_bytes!("fixtures/bhosts.txt"));
+        assert_eq!(hosts.alloc_cpus(), 24);
+        assert_eq!(hosts.alloc_nodes(), 2);
+    }
+}
@@ -0,0 +1,60 @@
+#[derive(Debug, PartialEq, Eq)]
+pub struct Status {
+    jobs: Vec<JobState>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Other,
+}
+
+impl Status {
+    pub const ARGS: &[&'static str] = &["-u", "all", "-o", "stat", "-noheader"];
+
+    pub fn new(s: &[u8]) -> Self {
+        let jobs = String::from_utf8_lossy(s)
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|l| match l {
+                "PEND" => JobState::Queued,
+                "RUN" => JobState::Running,
+                _ => JobState::Other,
+            })
+            .collect();
+        Self { jobs }
+    }
+
+    /// `bjobs` exits with failure and this message when there is no job at all
+    #[inline]
+    pub fn is_empty_message(s: &[u8]) -> bool {
+        String::from_utf8_lossy(s).contains("No unfinished job found")
+    }
+
+    /// get the count of queued and running jobs separately: `(queueds, runnings)`
+    pub fn qr_count(&self) -> (usize, usize) {
+        self.jobs.iter().fold((0, 0), |(mut queued, mut running), j| {
+            match j {
+                JobState::Queued => queued += 1,
+                JobState::Running => running += 1,
+                JobState::Other => (),
+            }
+
+            (queued, running)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn test_status() {
+        let status = Status::new(include_bytes!("fixtures/bjobs_stat.txt"));
+        assert_eq!(status.qr_count(), (2, 2));
+        assert!(Status::is_empty_message(b"No unfinished job found\n"));
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use super::super::table::{parse_memory, parse_table};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hosts {
+    hosts: Vec<Host>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Host {
+    name: String,
+    ncpus: usize,
+    /// Maximum memory (unit: byte)
+    maxmem: u64,
+}
+
+impl Hosts {
+    pub const ARGS: &[&'static str] = &["-w"];
+
+    pub fn new(s: &[u8]) -> Self {
+        let s = String::from_utf8_lossy(s);
+        let hosts = parse_table(&s)
+            .into_iter()
+            .filter_map(|row| {
+                Some(Host {
+                    name: row.get("HOST_NAME")?.to_string(),
+                    // dynamic hosts like the master report `-`
+                    ncpus: row.get("ncpus")?.parse().ok()?,
+                    maxmem: row.get("maxmem").and_then(|m| parse_memory(m)).unwrap_or_default(),
+                })
+            })
+            .collect();
+        Self { hosts }
+    }
+
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.hosts.len()
+    }
+
+    #[inline]
+    pub fn cpus(&self) -> usize {
+        self.hosts.iter().map(|h| h.ncpus).sum()
+    }
+
+    #[inline]
+    pub fn memory(&self) -> u64 {
+        self.hosts.iter().map(|h| h.maxmem).sum()
+    }
+
+    /// maximum memory of every host, keyed by host name
+    pub fn memory_by_host(&self) -> HashMap<&str, u64> {
+        self.hosts.iter().map(|h| (h.name.as_str(), h.maxmem)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hosts;
+
+    #[test]
+    fn test_hosts() {
+        let hosts = Hosts::new(include_bytes!("fixtures/lshosts.txt"));
+        assert_eq!(hosts.node_count(), 3);
+        assert_eq!(hosts.cpus(), 64);
+        assert_eq!(
+            hosts.memory_by_host()["node03"],
+            (125.9 * (1u64 << 30) as f64) as u64
+        );
+    }
+}
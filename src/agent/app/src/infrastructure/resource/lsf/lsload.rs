@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use super::super::table::{parse_memory, parse_table};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Load {
+    /// Available memory of the hosts reporting load (unit: byte)
+    free_memory: HashMap<String, u64>,
+}
+
+impl Load {
+    pub const ARGS: &[&'static str] = &["-w"];
+
+    pub fn new(s: &[u8]) -> Self {
+        let s = String::from_utf8_lossy(s);
+        let free_memory = parse_table(&s)
+            .into_iter()
+            .filter_map(|row| {
+                Some((
+                    row.get("HOST_NAME")?.to_string(),
+                    parse_memory(row.get("mem")?)?,
+                ))
+            })
+            .collect();
+        Self { free_memory }
+    }
+
+    /// memory in use, i.e. the maximum memory minus the available memory of every reporting host
+    pub fn used_memory(&self, memory_by_host: &HashMap<&str, u64>) -> u64 {
+        self.free_memory
+            .iter()
+            .filter_map(|(host, free)| {
+                Some(memory_by_host.get(host.as_str())?.saturating_sub(*free))
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Load;
+
+    #[test]
+    fn test_load() {
+        let load = Load::new(include_bytes!("fixtures/lsload.txt"));
+        assert_eq!(load.free_memory.len(), 2);
+        let memory_by_host = HashMap::from([
+            ("node01", 64 << 30),
+            ("node02", 64 << 30),
+            ("node03", 128 << 30),
+        ]);
+        let used = (64u64 << 31)
+            - (59.7 * (1u64 << 30) as f64) as u64
+            - (33.6 * (1u64 << 30) as f64) as u64;
+        assert_eq!(load.used_memory(&memory_by_host), used);
+    }
+}
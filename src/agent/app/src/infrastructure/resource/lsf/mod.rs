@@ -0,0 +1,101 @@
+mod bhosts;
+mod bjobs;
+mod lshosts;
+mod lsload;
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+
+use super::{SchedulerStat, SchedulerTotalResources, SchedulerUsedResources};
+use crate::infrastructure::ssh_proxy::SshProxy;
+
+pub struct Lsf;
+
+impl Lsf {
+    async fn hosts(proxy: &SshProxy) -> anyhow::Result<lshosts::Hosts> {
+        let output = proxy
+            .command("lshosts")
+            .args(lshosts::Hosts::ARGS)
+            .output()
+            .await
+            .context("lshosts")?;
+        if !output.status.success() {
+            bail!(
+                "lshosts terminated with an exception. Exit status: {}",
+                output.status
+            );
+        }
+        Ok(lshosts::Hosts::new(&output.stdout))
+    }
+}
+
+#[async_trait]
+impl SchedulerStat for Lsf {
+    async fn total(&self, proxy: &SshProxy) -> anyhow::Result<SchedulerTotalResources> {
+        let hosts = Self::hosts(proxy).await?;
+        Ok(SchedulerTotalResources {
+            memory: hosts.memory(),
+            core_number: hosts.cpus(),
+            node_number: hosts.node_count(),
+        })
+    }
+
+    async fn used(&self, proxy: &SshProxy) -> anyhow::Result<SchedulerUsedResources> {
+        let hosts = Self::hosts(proxy).await?;
+
+        let output = proxy
+            .command("lsload")
+            .args(lsload::Load::ARGS)
+            .output()
+            .await
+            .context("lsload")?;
+        if !output.status.success() {
+            bail!(
+                "lsload terminated with an exception. Exit status: {}",
+                output.status
+            );
+        }
+        let load = lsload::Load::new(&output.stdout);
+
+        let output = proxy
+            .command("bhosts")
+            .args(bhosts::Hosts::ARGS)
+            .output()
+            .await
+            .context("bhosts")?;
+        if !output.status.success() {
+            bail!(
+                "bhosts terminated with an exception. Exit status: {}",
+                output.status
+            );
+        }
+        let batch_hosts = bhosts::Hosts::new(&output.stdout);
+
+        let output = proxy
+            .command("bjobs")
+            .args(bjobs::Status::ARGS)
+            .output()
+            .await
+            .context("bjobs")?;
+        let (queuing_task_count, running_task_count) = if output.status.success() {
+            bjobs::Status::new(&output.stdout).qr_count()
+        } else if bjobs::Status::is_empty_message(&output.stdout)
+            || bjobs::Status::is_empty_message(&output.stderr)
+        {
+            (0, 0)
+        } else {
+            bail!(
+                "bjobs terminated with an exception. Exit status: {}",
+                output.status
+            );
+        };
+
+        Ok(SchedulerUsedResources {
+            allocated_memory: load.used_memory(&hosts.memory_by_host()),
+            allocated_cpu_count: batch_hosts.alloc_cpus(),
+            queuing_task_count,
+            running_task_count,
+            used_node_count: batch_hosts.alloc_nodes(),
+        })
+    }
+}
@@ -1,12 +1,19 @@
+mod local;
+mod lsf;
 mod pbs;
+mod sge;
 mod slurm;
 mod storage;
+mod table;
 
 use std::sync::Arc;
 
 use serde::Serialize;
 
+use self::local::Local;
+use self::lsf::Lsf;
 use self::pbs::Pbs;
+use self::sge::Sge;
 use self::slurm::Slurm;
 use self::storage::stat;
 
@@ -24,6 +31,9 @@ impl ResourceStat {
             scheduler: match scheduler {
                 "slurm" => Box::new(Slurm),
                 "pbs" => Box::new(Pbs),
+                "sge" => Box::new(Sge),
+                "lsf" => Box::new(Lsf),
+                "local" => Box::new(Local),
                 _ => return None,
             },
         })
@@ -0,0 +1,62 @@
+mod qhost;
+mod qstat;
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+
+use super::{SchedulerStat, SchedulerTotalResources, SchedulerUsedResources};
+use crate::infrastructure::ssh_proxy::SshProxy;
+
+pub struct Sge;
+
+impl Sge {
+    async fn hosts(proxy: &SshProxy) -> anyhow::Result<qhost::Hosts> {
+        let output = proxy.command("qhost").output().await.context("qhost")?;
+        if !output.status.success() {
+            bail!(
+                "qhost terminated with an exception. Exit status: {}",
+                output.status
+            );
+        }
+        Ok(qhost::Hosts::new(&output.stdout))
+    }
+}
+
+#[async_trait]
+impl SchedulerStat for Sge {
+    async fn total(&self, proxy: &SshProxy) -> anyhow::Result<SchedulerTotalResources> {
+        let hosts = Self::hosts(proxy).await?;
+        Ok(SchedulerTotalResources {
+            memory: hosts.memory(),
+            core_number: hosts.cpus(),
+            node_number: hosts.node_count(),
+        })
+    }
+
+    async fn used(&self, proxy: &SshProxy) -> anyhow::Result<SchedulerUsedResources> {
+        let hosts = Self::hosts(proxy).await?;
+
+        let output = proxy
+            .command("qstat")
+            .args(qstat::Status::args(proxy.is_proxy()))
+            .output()
+            .await
+            .context("qstat")?;
+        if !output.status.success() {
+            bail!(
+                "qstat terminated with an exception. Exit status: {}",
+                output.status
+            );
+        }
+        let status = qstat::Status::new(&output.stdout);
+
+        let (queuing_task_count, running_task_count) = status.qr_count();
+        Ok(SchedulerUsedResources {
+            allocated_memory: hosts.used_memory(),
+            allocated_cpu_count: status.used_slots(),
+            queuing_task_count,
+            running_task_count,
+            used_node_count: status.used_node_count(),
+        })
+    }
+}
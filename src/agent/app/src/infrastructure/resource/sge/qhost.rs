@@ -0,0 +1,77 @@
+use super::super::table::{parse_memory, parse_table};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hosts {
+    hosts: Vec<Host>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Host {
+    name: String,
+    ncpu: usize,
+    /// Total memory (unit: byte)
+    mem_total: u64,
+    /// Used memory (unit: byte), `0` if the host is not reporting
+    mem_used: u64,
+}
+
+impl Hosts {
+    pub fn new(s: &[u8]) -> Self {
+        let s = String::from_utf8_lossy(s);
+        let hosts = parse_table(&s)
+            .into_iter()
+            .filter_map(|row| {
+                Some(Host {
+                    name: row.get("HOSTNAME")?.to_string(),
+                    // the pseudo host `global` has no cpu
+                    ncpu: row.get("NCPU")?.parse().ok()?,
+                    mem_total: parse_memory(row.get("MEMTOT")?).unwrap_or_default(),
+                    mem_used: row.get("MEMUSE").and_then(|m| parse_memory(m)).unwrap_or_default(),
+                })
+            })
+            .collect();
+        Self { hosts }
+    }
+
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.hosts.len()
+    }
+
+    #[inline]
+    pub fn cpus(&self) -> usize {
+        self.hosts.iter().map(|h| h.ncpu).sum()
+    }
+
+    #[inline]
+    pub fn memory(&self) -> u64 {
+        self.hosts.iter().map(|h| h.mem_total).sum()
+    }
+
+    #[inline]
+    pub fn used_memory(&self) -> u64 {
+        self.hosts.iter().map(|h| h.mem_used).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Host, Hosts};
+
+    #[test]
+    fn test_hosts() {
+        let hosts = Hosts::new(include_bytes!("fixtures/qhost.txt"));
+        assert_eq!(hosts.node_count(), 3);
+        assert_eq!(hosts.cpus(), 64);
+        assert_eq!(
+            hosts.hosts[2],
+            Host {
+                name: "node03".to_string(),
+                ncpu: 32,
+                mem_total: 135184095641,
+                mem_used: 0,
+            }
+        );
+        assert_eq!(hosts.used_memory(), 4402341478 + 32427003084);
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Status {
+    queues: Vec<QueueInstance>,
+    jobs: Vec<JobState>,
+}
+
+/// A queue instance like `all.q@node01`
+#[derive(Debug, PartialEq, Eq)]
+struct QueueInstance {
+    host: String,
+    used_slots: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Other,
+}
+
+impl Status {
+    /// `qstat -f` of all users, `*` has to be quoted when running through the ssh shell
+    pub fn args(is_proxy: bool) -> [&'static str; 3] {
+        ["-f", "-u", if is_proxy { "'*'" } else { "*" }]
+    }
+
+    pub fn new(s: &[u8]) -> Self {
+        let s = String::from_utf8_lossy(s);
+        let mut queues = vec![];
+        let mut jobs = vec![];
+        for line in s.lines() {
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            match columns.as_slice() {
+                [queue, _, slots, ..] if queue.contains('@') => {
+                    // `resv/used/tot.` or `used/tot` in older versions
+                    let slots = slots.split('/').collect::<Vec<_>>();
+                    let used = if slots.len() == 3 { slots[1] } else { slots[0] };
+                    queues.push(QueueInstance {
+                        host: queue.split_once('@').unwrap_or_default().1.to_string(),
+                        used_slots: used.parse().unwrap_or_default(),
+                    });
+                }
+                [id, _, _, _, state, ..] if id.chars().all(|c| c.is_ascii_digit()) => {
+                    jobs.push(state.parse().unwrap());
+                }
+                _ => (),
+            }
+        }
+        Self { queues, jobs }
+    }
+
+    #[inline]
+    pub fn used_slots(&self) -> usize {
+        self.queues.iter().map(|q| q.used_slots).sum()
+    }
+
+    /// count of hosts with at least one used slot
+    pub fn used_node_count(&self) -> usize {
+        self.queues
+            .iter()
+            .filter(|q| q.used_slots > 0)
+            .map(|q| q.host.as_str())
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// get the count of queued and running jobs separately: `(queueds, runnings)`
+    pub fn qr_count(&self) -> (usize, usize) {
+        self.jobs.iter().fold((0, 0), |(mut queued, mut running), j| {
+            match j {
+                JobState::Queued => queued += 1,
+                JobState::Running => running += 1,
+                JobState::Other => (),
+            }
+
+            (queued, running)
+        })
+    }
+}
+
+impl std::str::FromStr for JobState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if s.contains(['E', 'h', 's', 'S', 'T', 'd']) {
+            Self::Other
+        } else if s.contains(['r', 't', 'R']) {
+            Self::Running
+        } else if s.contains(['q', 'w']) {
+            Self::Queued
+        } else {
+            Self::Other
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Status;
+
+    #[test]
+    fn test_status() {
+        let status = Status::new(include_bytes!("fixtures/qstat_f.txt"));
+        assert_eq!(status.used_slots(), 24);
+        assert_eq!(status.used_node_count(), 2);
+        assert_eq!(status.qr_count(), (1, 2));
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+/// Parse the whitespace aligned table printed by `qhost`, `lshosts`, `bhosts`, etc.
+///
+/// The first line is the header, separator lines made of `-` are skipped,
+/// and every row maps the header names to its columns.
+pub fn parse_table(s: &str) -> Vec<HashMap<&str, &str>> {
+    let mut lines = s.lines().filter(|l| !l.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return vec![];
+    };
+    let header = header.split_whitespace().collect::<Vec<_>>();
+    lines
+        .filter(|l| !l.trim().chars().all(|c| c == '-'))
+        .map(|l| header.iter().copied().zip(l.split_whitespace()).collect())
+        .collect()
+}
+
+/// Parse memory like `62.8G` or `512M` into bytes, `None` if not available (e.g. `-`).
+///
+/// Numbers without unit are in MiB, which is the default unit of LSF.
+pub fn parse_memory(s: &str) -> Option<u64> {
+    let number = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let value = number.parse::<f64>().ok()?;
+    let multiplier = match s[number.len()..].to_ascii_uppercase().trim_end_matches('B') {
+        "K" => 1u64 << 10,
+        "" | "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return None,
+    };
+    Some((value * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_memory, parse_table};
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_table() {
+        let s = indoc! {"
+            HOST_NAME  STATUS  MAX
+            ---------------------
+            node01     ok      16
+            node02     unavail
+        "};
+        let rows = parse_table(s);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["MAX"], "16");
+        assert_eq!(rows[1].get("MAX"), None);
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        assert_eq!(parse_memory("512M"), Some(512 << 20));
+        assert_eq!(parse_memory("1.5G"), Some(3 << 29));
+        assert_eq!(parse_memory("2T"), Some(2 << 40));
+        assert_eq!(parse_memory("64"), Some(64 << 20));
+        assert_eq!(parse_memory("-"), None);
+    }
+}
@@ -0,0 +1,573 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+use domain::{
+    model::{
+        entity::task::{Requirements, StdInKind, TaskUsedResource},
+        vo::{
+            job::{JobState, ScriptInfo},
+            Job,
+        },
+    },
+    service::JobSchedulerService,
+};
+use indoc::formatdoc;
+
+/// 在 agent 所在主机上直接以子进程运行作业，用于没有批处理调度器的工作站或测试环境
+///
+/// 作业 id 为作业进程（同时也是进程组）的 pid。作业记录只保存在内存中，agent 重启后
+/// 已提交的作业不再被跟踪，查询时会返回作业不存在，仍在运行的进程也不会被终止。
+pub struct LocalClient {
+    base_path: String,
+    include_env: String,
+    /// 委派给 agent 的 cgroup v2 目录，设置后通过 cgroup 限制作业的内存和核心数，
+    /// 否则只能通过 rlimit 限制
+    cgroup: Option<String>,
+    jobs: Arc<Mutex<HashMap<String, LocalJob>>>,
+}
+
+#[derive(Debug, Clone)]
+struct LocalJob {
+    name: String,
+    owner: String,
+    state: JobState,
+    scheduler_state: String,
+    exit_status_code: i32,
+    work_dir: PathBuf,
+    cpu: u64,
+    /// 最大常驻内存（byte）
+    max_memory: u64,
+    /// 用户态与内核态时间之和（s）
+    cpu_time: u64,
+    start_time: i64,
+    end_time: i64,
+    /// 作业进程是否已被回收，与回收操作在同一把锁内更新，
+    /// 未回收时 pid 不会被复用，可以安全地向进程组发送信号
+    reaped: bool,
+}
+
+/// 作业的资源限制
+#[derive(Default, Debug, Clone, Copy)]
+struct Limits {
+    cpu_cores: Option<usize>,
+    /// 内存（MB）
+    memory: Option<usize>,
+    /// 墙钟时间（s）
+    wall_time: Option<usize>,
+    /// 核心时间（s），rlimit 对作业中的每个进程分别生效
+    cpu_time: Option<usize>,
+}
+
+#[async_trait::async_trait]
+impl JobSchedulerService for LocalClient {
+    async fn get_jobs(&self) -> anyhow::Result<Vec<Job>> {
+        let jobs = self.jobs.lock().unwrap().clone();
+        Ok(jobs.into_iter().map(|(id, job)| Self::to_job(id, job)).collect())
+    }
+
+    async fn get_job(&self, id: &str) -> anyhow::Result<Job> {
+        let job = self.jobs.lock().unwrap().get(id).cloned();
+        match job {
+            Some(job) => Ok(Self::to_job(id.to_string(), job)),
+            None => anyhow::bail!("No such job id."),
+        }
+    }
+
+    async fn submit_job(&self, script_path: &str) -> anyhow::Result<String> {
+        self.spawn(script_path, Limits::default())
+    }
+
+    async fn submit_job_script(&self, script_info: ScriptInfo) -> anyhow::Result<String> {
+        let mut path = PathBuf::new();
+        path.push(self.base_path.as_str());
+        if !path.exists() {
+            tokio::fs::create_dir_all(path.as_path()).await?;
+        }
+        path.push(script_info.path.as_str());
+        tokio::fs::write(
+            path,
+            Self::gen_script(&self.base_path, &self.include_env, script_info.clone()),
+        )
+        .await?;
+        let limits = script_info.requirements.as_ref().map(Limits::from).unwrap_or_default();
+        self.spawn(script_info.path.as_str(), limits)
+    }
+
+    async fn delete_job(&self, job_id: &str) -> anyhow::Result<()> {
+        self.signal_job(job_id, libc::SIGKILL, |job| {
+            job.scheduler_state = "CANCELLED".to_string();
+        })
+    }
+
+    async fn pause_job(&self, job_id: &str) -> anyhow::Result<()> {
+        self.signal_job(job_id, libc::SIGSTOP, |job| {
+            job.state = JobState::Suspended;
+            job.scheduler_state = "SUSPENDED".to_string();
+        })
+    }
+
+    async fn continue_job(&self, job_id: &str) -> anyhow::Result<()> {
+        self.signal_job(job_id, libc::SIGCONT, |job| {
+            job.state = JobState::Running;
+            job.scheduler_state = "RUNNING".to_string();
+        })
+    }
+}
+
+impl LocalClient {
+    fn spawn(&self, script_path: &str, limits: Limits) -> anyhow::Result<String> {
+        let mut path = PathBuf::new();
+        path.push(self.base_path.as_str());
+        path.push(script_path);
+        let work_dir = path.parent().context("Script has no parent directory")?.to_path_buf();
+        let name = work_dir
+            .file_name()
+            .map(|el| el.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let stdout = std::fs::File::create(work_dir.join("STDOUT"))?;
+        let stderr = std::fs::File::create(work_dir.join("STDERR"))?;
+
+        let cgroup = match self.cgroup.as_deref() {
+            Some(root) => Some(create_cgroup(root, &name, &limits).context("cgroup")?),
+            None => None,
+        };
+        let cgroup_procs = cgroup
+            .as_ref()
+            .map(|el| CString::new(el.join("cgroup.procs").as_os_str().as_bytes()))
+            .transpose()?;
+        // 没有 cgroup 时只能退而限制虚拟内存
+        let memory_rlimit = match cgroup {
+            Some(_) => None,
+            None => limits.memory.map(|el| (el as u64) << 20),
+        };
+        let cpu_time_rlimit = limits.cpu_time.map(|el| el as u64);
+
+        let mut command = std::process::Command::new("bash");
+        command
+            .arg(&path)
+            .current_dir(&work_dir)
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
+            .process_group(0);
+        // SAFETY: 子进程 fork 后只调用异步信号安全的函数
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(seconds) = cpu_time_rlimit {
+                    let limit = libc::rlimit {
+                        rlim_cur: seconds,
+                        rlim_max: seconds,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_CPU, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(bytes) = memory_rlimit {
+                    let limit = libc::rlimit {
+                        rlim_cur: bytes,
+                        rlim_max: bytes,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(procs) = &cgroup_procs {
+                    // 写入 0 表示将当前进程移入该 cgroup
+                    let fd = libc::open(procs.as_ptr(), libc::O_WRONLY);
+                    if fd < 0 || libc::write(fd, b"0".as_ptr().cast(), 1) != 1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    libc::close(fd);
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn().context("bash")?;
+        let pid = child.id() as libc::pid_t;
+        let id = pid.to_string();
+
+        self.jobs.lock().unwrap().insert(
+            id.clone(),
+            LocalJob {
+                name,
+                owner: std::env::var("USER").unwrap_or_default(),
+                state: JobState::Running,
+                scheduler_state: "RUNNING".to_string(),
+                exit_status_code: 0,
+                work_dir,
+                cpu: limits.cpu_cores.unwrap_or(1) as u64,
+                max_memory: 0,
+                cpu_time: 0,
+                start_time: chrono::Utc::now().timestamp(),
+                end_time: 0,
+                reaped: false,
+            },
+        );
+
+        let jobs = self.jobs.clone();
+        let job_id = id.clone();
+        tokio::task::spawn_blocking(move || {
+            // 先等待作业退出但不回收，持锁后再回收，避免信号发往被复用的 pid
+            let exited = wait_exited(pid);
+            let mut jobs = jobs.lock().unwrap();
+            let result = exited.and_then(|_| reap(pid));
+            let Some(job) = jobs.get_mut(&job_id) else {
+                return;
+            };
+            // 等待失败时无法确认 pid 是否仍有效，同样视为已回收
+            job.reaped = true;
+            job.end_time = chrono::Utc::now().timestamp();
+            match result {
+                Ok((code, usage)) => {
+                    job.exit_status_code = code;
+                    job.max_memory = usage.ru_maxrss as u64 * 1024;
+                    job.cpu_time = (usage.ru_utime.tv_sec + usage.ru_stime.tv_sec) as u64;
+                    job.state = if code == 0 {
+                        JobState::Completed
+                    } else {
+                        JobState::Failed
+                    };
+                    // 被取消或超时的作业保留原因
+                    if matches!(job.scheduler_state.as_str(), "RUNNING" | "SUSPENDED") {
+                        job.scheduler_state =
+                            if code == 0 { "COMPLETED" } else { "FAILED" }.to_string();
+                    }
+                }
+                Err(e) => {
+                    log::error!("Unable to wait for local job {job_id}: {e}");
+                    job.state = JobState::Unknown;
+                }
+            }
+            if let Some(cgroup) = cgroup {
+                let _ = std::fs::remove_dir(cgroup);
+            }
+        });
+
+        if let Some(wall_time) = limits.wall_time {
+            let jobs = self.jobs.clone();
+            let job_id = id.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(wall_time as u64)).await;
+                let mut jobs = jobs.lock().unwrap();
+                let Some(job) = jobs.get_mut(&job_id) else {
+                    return;
+                };
+                if !job.reaped {
+                    job.scheduler_state = "TIMEOUT".to_string();
+                    // SAFETY: 持锁期间作业进程未被回收，pid 不会被复用
+                    unsafe { libc::killpg(pid, libc::SIGKILL) };
+                }
+            });
+        }
+
+        Ok(id)
+    }
+
+    fn signal_job(
+        &self,
+        job_id: &str,
+        signal: libc::c_int,
+        update: impl FnOnce(&mut LocalJob),
+    ) -> anyhow::Result<()> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let job = jobs.get_mut(job_id).context("No such job id.")?;
+        if job.reaped || !matches!(job.state, JobState::Running | JobState::Suspended) {
+            anyhow::bail!("Job {job_id} has already finished.")
+        }
+        let pid = job_id.parse::<libc::pid_t>()?;
+        // SAFETY: 持锁期间作业进程未被回收，pid 不会被复用
+        if unsafe { libc::killpg(pid, signal) } != 0 {
+            return Err(std::io::Error::last_os_error()).context("killpg");
+        }
+        update(job);
+        Ok(())
+    }
+
+    fn to_job(id: String, job: LocalJob) -> Job {
+        let end_time = match job.end_time {
+            0 => chrono::Utc::now().timestamp(),
+            x => x,
+        };
+        Job {
+            id,
+            name: job.name,
+            owner: job.owner,
+            state: job.state,
+            scheduler_state: job.scheduler_state,
+            exit_status_code: job.exit_status_code,
+            error_output: std::fs::read_to_string(job.work_dir.join("STDERR")).unwrap_or_default(),
+            resource_used: TaskUsedResource {
+                cpu: job.cpu,
+                avg_memory: job.max_memory,
+                max_memory: job.max_memory,
+                storage: directory_size(&job.work_dir).unwrap_or(0),
+                wall_time: (end_time - job.start_time).max(0) as u64,
+                cpu_time: job.cpu_time,
+                node: 1,
+                start_time: job.start_time,
+                end_time: job.end_time,
+                partition: None,
+            },
+        }
+    }
+
+    fn gen_script(base_path: &str, include_env: &str, script_info: ScriptInfo) -> String {
+        let header = "#!/bin/bash";
+        let id = script_info.parent_id.clone();
+        let env: Vec<String> = script_info
+            .environments
+            .iter()
+            .map(|(k, v)| format!("export {}={}", k, v))
+            .collect();
+        let env_string = env.join("\n");
+        let touch = format!("echo -n \"{}\" > {base_path}/{id}/.co.sig", script_info.id);
+        let script = format!("{} {}", script_info.name, script_info.arguments.join(" "));
+        let script = match script_info.std_in {
+            StdInKind::Text { text } => {
+                format!("{script} << EOF\n{text}\nEOF")
+            }
+            StdInKind::File { path } => {
+                format!("{script} < {path}")
+            }
+            StdInKind::Unknown => script,
+        };
+        let script = script_info.launcher.command_line(&script, "$NP", "${OMP_NUM_THREADS:-1}");
+        let load_software = script_info.load_software.clone();
+        let np = script_info.requirements.and_then(|el| el.cpu_cores).unwrap_or(1);
+        formatdoc! {r#"
+            {header}
+            NP={np}
+            {env_string}
+            {include_env}
+            {load_software}
+            {script}
+            result=$?
+            {touch}
+            exit $result
+        "#}
+    }
+
+    pub fn new(base_path: String, include_env: String, cgroup: Option<String>) -> Self {
+        Self {
+            base_path,
+            include_env,
+            cgroup,
+            jobs: Arc::default(),
+        }
+    }
+}
+
+impl From<&Requirements> for Limits {
+    fn from(requirements: &Requirements) -> Self {
+        // 定时终止换算为剩余的墙钟时间
+        let until_stop = requirements
+            .stop_time
+            .map(|el| (el as i64 - chrono::Utc::now().timestamp()).max(0) as usize);
+        let wall_time = match (requirements.max_wall_time, until_stop) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Self {
+            cpu_cores: requirements.cpu_cores,
+            memory: requirements.memory_per_node,
+            wall_time,
+            cpu_time: requirements.max_cpu_time,
+        }
+    }
+}
+
+/// 在 `root` 下创建作业的 cgroup 并写入内存与 CPU 限制
+fn create_cgroup(root: &str, name: &str, limits: &Limits) -> std::io::Result<PathBuf> {
+    let path = Path::new(root).join(format!("co-{name}"));
+    std::fs::create_dir_all(&path)?;
+    if let Some(memory) = limits.memory {
+        std::fs::write(path.join("memory.max"), ((memory as u64) << 20).to_string())?;
+    }
+    if let Some(cores) = limits.cpu_cores {
+        std::fs::write(path.join("cpu.max"), format!("{} 100000", cores * 100000))?;
+    }
+    Ok(path)
+}
+
+/// 阻塞直到子进程退出，但不回收，保留的僵尸进程使 pid 在回收前不会被复用
+fn wait_exited(pid: libc::pid_t) -> std::io::Result<()> {
+    // SAFETY: siginfo_t 是纯数据结构，全零是合法值
+    let mut info = unsafe { std::mem::zeroed::<libc::siginfo_t>() };
+    loop {
+        // SAFETY: pid 是本进程创建且尚未回收的子进程
+        let ret = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if ret == 0 {
+            return Ok(());
+        }
+        let e = std::io::Error::last_os_error();
+        if e.kind() != std::io::ErrorKind::Interrupted {
+            return Err(e);
+        }
+    }
+}
+
+/// 回收已退出的子进程，返回退出码与资源使用情况
+fn reap(pid: libc::pid_t) -> std::io::Result<(i32, libc::rusage)> {
+    let mut status = 0;
+    // SAFETY: rusage 是纯数据结构，全零是合法值
+    let mut usage = unsafe { std::mem::zeroed::<libc::rusage>() };
+    loop {
+        // SAFETY: pid 是本进程创建且尚未回收的子进程
+        if unsafe { libc::wait4(pid, &mut status, 0, &mut usage) } == pid {
+            break;
+        }
+        let e = std::io::Error::last_os_error();
+        if e.kind() != std::io::ErrorKind::Interrupted {
+            return Err(e);
+        }
+    }
+    let code = if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else if libc::WIFSIGNALED(status) {
+        128 + libc::WTERMSIG(status)
+    } else {
+        -1
+    };
+    Ok((code, usage))
+}
+
+fn directory_size(path: impl AsRef<std::path::Path>) -> std::io::Result<u64> {
+    let mut total_size = 0;
+
+    let entries = std::fs::read_dir(path)?;
+
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+
+        let metadata = entry.metadata()?;
+        if metadata.is_symlink() {
+            continue;
+        } else if metadata.is_file() {
+            total_size += metadata.len();
+        } else if metadata.is_dir() {
+            total_size += directory_size(entry.path())?;
+        }
+    }
+
+    Ok(total_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use domain::{
+        model::{
+            entity::task::{Launcher, Requirements},
+            vo::job::{JobState, ScriptInfo},
+        },
+        service::JobSchedulerService,
+    };
+
+    use super::LocalClient;
+
+    fn script_info(
+        name: &str,
+        arguments: &[&str],
+        requirements: Option<Requirements>,
+    ) -> ScriptInfo {
+        ScriptInfo {
+            id: "t1".to_string(),
+            parent_id: "p1".to_string(),
+            name: name.to_string(),
+            path: "p1/run.sh".to_string(),
+            arguments: arguments.iter().map(|el| el.to_string()).collect(),
+            requirements,
+            launcher: Launcher::None,
+            ..Default::default()
+        }
+    }
+
+    async fn run(script_info: ScriptInfo) -> (PathBuf, domain::model::vo::Job) {
+        let base_path = std::env::temp_dir().join(format!("co-local-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(base_path.join("p1")).unwrap();
+        let client = LocalClient::new(base_path.to_string_lossy().to_string(), String::new(), None);
+        let id = client.submit_job_script(script_info).await.unwrap();
+        for _ in 0..100 {
+            let job = client.get_job(&id).await.unwrap();
+            if !matches!(job.state, JobState::Running) {
+                return (base_path, job);
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("local job {id} did not finish");
+    }
+
+    #[tokio::test]
+    async fn test_completed_job() {
+        let (base_path, job) = run(script_info("echo", &["hello"], None)).await;
+        assert_eq!(job.state, JobState::Completed);
+        assert_eq!(job.exit_status_code, 0);
+        assert_eq!(job.resource_used.node, 1);
+        let stdout = std::fs::read_to_string(base_path.join("p1/STDOUT")).unwrap();
+        assert_eq!(stdout, "hello\n");
+        let sig = std::fs::read_to_string(base_path.join("p1/.co.sig")).unwrap();
+        assert_eq!(sig, "t1");
+        std::fs::remove_dir_all(base_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_job() {
+        let (base_path, job) = run(script_info("ls", &["/nonexistent"], None)).await;
+        assert_eq!(job.state, JobState::Failed);
+        assert_ne!(job.exit_status_code, 0);
+        assert!(!job.error_output.is_empty());
+        std::fs::remove_dir_all(base_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wall_time_limit() {
+        let requirements = Requirements {
+            max_wall_time: Some(1),
+            ..Default::default()
+        };
+        let (base_path, job) = run(script_info("sleep", &["30"], Some(requirements))).await;
+        assert_eq!(job.state, JobState::Failed);
+        assert_eq!(job.scheduler_state, "TIMEOUT");
+        assert_eq!(job.exit_status_code, 128 + libc::SIGKILL);
+        assert!(!base_path.join("p1/.co.sig").exists());
+        std::fs::remove_dir_all(base_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_signal_reaped_job() {
+        let base_path = std::env::temp_dir().join(format!("co-local-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(base_path.join("p1")).unwrap();
+        let client = LocalClient::new(base_path.to_string_lossy().to_string(), String::new(), None);
+        let id = client.submit_job_script(script_info("true", &[], None)).await.unwrap();
+        for _ in 0..100 {
+            if client.jobs.lock().unwrap()[&id].reaped {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(client.delete_job(&id).await.is_err());
+        assert_eq!(
+            client.get_job(&id).await.unwrap().scheduler_state,
+            "COMPLETED"
+        );
+        std::fs::remove_dir_all(base_path).unwrap();
+    }
+}
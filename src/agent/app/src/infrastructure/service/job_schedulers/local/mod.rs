@@ -0,0 +1,2 @@
+pub mod local_client;
+pub use self::local_client::*;
@@ -0,0 +1,475 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::{Datelike, Local, NaiveDateTime, TimeZone};
+use domain::{
+    model::{
+        entity::task::{StdInKind, TaskUsedResource},
+        vo::job::{Job, JobState, ScriptInfo},
+    },
+    service::JobSchedulerService,
+};
+use indoc::formatdoc;
+use tokio::process::Command;
+
+use super::{LsfJob, LsfJobs, BJOBS_FIELDS};
+use crate::infrastructure::ssh_proxy::SshProxy;
+
+/// IBM Spectrum LSF 客户端
+pub struct LsfClient {
+    base_path: String,
+    include_env: String,
+    /// 未指定队列时使用的默认队列
+    queue: Option<String>,
+    ssh_proxy: Arc<SshProxy>,
+}
+
+#[async_trait::async_trait]
+impl JobSchedulerService for LsfClient {
+    async fn get_jobs(&self) -> anyhow::Result<Vec<Job>> {
+        let out = self
+            .ssh_proxy
+            .command("bjobs")
+            .args(["-a", "-json", "-o", &self.output_fields()])
+            .output()
+            .await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for get_jobs. real: {}", out.status)
+        }
+        let result: LsfJobs = serde_json::from_slice(&out.stdout)?;
+        Ok(result
+            .records
+            .into_iter()
+            .filter(|el| el.error.is_none())
+            .map(Self::job)
+            .collect())
+    }
+
+    async fn get_job(&self, id: &str) -> anyhow::Result<Job> {
+        let out = self
+            .ssh_proxy
+            .command("bjobs")
+            .args(["-json", "-o", &self.output_fields(), id])
+            .output()
+            .await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for get_job. real: {}", out.status)
+        }
+        let result: LsfJobs = serde_json::from_slice(&out.stdout)?;
+        let record = result.records.into_iter().next().context("No such id")?;
+        if let Some(error) = record.error {
+            anyhow::bail!("No such id: {error}")
+        }
+        Ok(Self::job(record))
+    }
+
+    async fn submit_job(&self, script_path: &str) -> anyhow::Result<String> {
+        let out = 'block: {
+            let mut path = PathBuf::new();
+            path.push(self.base_path.as_str());
+            path.push(script_path);
+
+            let Some(ssh_config) = self.ssh_proxy.config() else {
+                // bsub 只会从标准输入读取的脚本中解析 #BSUB 指令
+                let out = Command::new("bsub")
+                    .stdin(std::fs::File::open(&path)?)
+                    .current_dir(path.parent().unwrap())
+                    .output()
+                    .await?;
+                if !out.status.success() {
+                    anyhow::bail!("Exit Status not 0 for submit_job. real: {}", out.status)
+                }
+                break 'block out;
+            };
+
+            let mut remote_path = PathBuf::new();
+            remote_path.extend([&ssh_config.home_dir, &ssh_config.save_dir, script_path]);
+            let out = self
+                .ssh_proxy
+                .command("mkdir")
+                .arg("-p")
+                .arg(remote_path.parent().unwrap().to_string_lossy().as_ref())
+                .output()
+                .await;
+            match out {
+                Ok(out) => {
+                    if !out.status.success() {
+                        log::error!(
+                            "Unable to create directory {} on for lsf script.",
+                            remote_path.parent().unwrap().to_string_lossy(),
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                }
+            }
+            let _ = Command::new("scp")
+                .arg("-P")
+                .arg(&ssh_config.port)
+                .arg(path)
+                .arg(format!(
+                    "{}:{}",
+                    ssh_config.username_host,
+                    remote_path.to_str().unwrap()
+                ))
+                .output()
+                .await?;
+            let out = self
+                .ssh_proxy
+                .command("cd")
+                .arg(remote_path.parent().unwrap())
+                .arg(";")
+                .arg("bsub")
+                .arg("<")
+                .arg(remote_path)
+                .output()
+                .await?;
+            if !out.status.success() {
+                anyhow::bail!("Exit Status not 0 for submit_job. real: {}", out.status)
+            }
+            out
+        };
+
+        parse_submitted_id(&String::from_utf8_lossy(&out.stdout))
+    }
+
+    async fn submit_job_script(&self, script_info: ScriptInfo) -> anyhow::Result<String> {
+        if let Some(requirements) = &script_info.requirements {
+            requirements.validate_scheduler_names()?;
+        }
+        let mut path = PathBuf::new();
+        path.push(self.base_path.as_str());
+        if !path.exists() {
+            tokio::fs::create_dir_all(path.as_path()).await?;
+        }
+        path.push(script_info.path.as_str());
+        tokio::fs::write(
+            path,
+            Self::gen_script(
+                &self.base_path,
+                &self.include_env,
+                self.queue.as_deref(),
+                script_info.clone(),
+            ),
+        )
+        .await?;
+        self.submit_job(script_info.path.as_str()).await
+    }
+
+    async fn delete_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("bkill").arg(job_id).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for delete_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+
+    async fn pause_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("bstop").arg(job_id).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for pause_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+
+    async fn continue_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("bresume").arg(job_id).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for continue_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+}
+
+impl LsfClient {
+    pub fn new(
+        base_path: String,
+        include_env: String,
+        queue: Option<String>,
+        ssh_proxy: Arc<SshProxy>,
+    ) -> Self {
+        Self {
+            base_path,
+            include_env,
+            queue,
+            ssh_proxy,
+        }
+    }
+
+    /// `bjobs -o` 的参数，经过 ssh 时需要加引号以免被远程 shell 拆分
+    fn output_fields(&self) -> String {
+        if self.ssh_proxy.is_proxy() {
+            format!("'{BJOBS_FIELDS}'")
+        } else {
+            BJOBS_FIELDS.to_string()
+        }
+    }
+
+    fn job(record: LsfJob) -> Job {
+        let state = match record.stat.as_str() {
+            "PEND" | "WAIT" => JobState::Queuing,
+            "RUN" => JobState::Running,
+            "PSUSP" | "USUSP" | "SSUSP" => JobState::Suspended,
+            "DONE" => JobState::Completed,
+            "EXIT" => JobState::Failed,
+            _ => JobState::Unknown,
+        };
+        let is_finished = matches!(state, JobState::Completed | JobState::Failed);
+        Job {
+            id: record.job_id,
+            name: record.job_name,
+            owner: record.user,
+            exit_status_code: record.exit_code.parse().unwrap_or_default(),
+            error_output: if is_finished {
+                std::fs::read_to_string(&record.error_file).unwrap_or_default()
+            } else {
+                String::default()
+            },
+            resource_used: TaskUsedResource {
+                cpu: record.nalloc_slot.parse().unwrap_or_default(),
+                avg_memory: parse_memory(&record.avg_mem),
+                max_memory: parse_memory(&record.max_mem),
+                storage: 0,
+                wall_time: parse_seconds(&record.run_time),
+                cpu_time: parse_seconds(&record.cpu_used),
+                start_time: parse_time(&record.start_time),
+                // 未结束作业的结束时间为预计值
+                end_time: if is_finished {
+                    parse_time(&record.finish_time)
+                } else {
+                    0
+                },
+                node: record.nexec_host.parse().unwrap_or_default(),
+                partition: Some(record.queue).filter(|el| !el.is_empty()),
+            },
+            state,
+            scheduler_state: record.stat,
+        }
+    }
+
+    fn gen_script(
+        base_path: &str,
+        include_env: &str,
+        default_queue: Option<&str>,
+        script_info: ScriptInfo,
+    ) -> String {
+        let header = "#!/bin/bash";
+        let id = script_info.parent_id.clone();
+        let env: Vec<String> = script_info
+            .environments
+            .iter()
+            .map(|(k, v)| format!("export {}={}", k, v))
+            .collect();
+        let env_string = env.join("\n");
+        let touch = format!("echo -n \"{}\" > $LS_SUBCWD/.co.sig", script_info.id);
+        let script = format!("{} {}", script_info.name, script_info.arguments.join(" "));
+        let script = match script_info.std_in {
+            StdInKind::Text { text } => {
+                format!("{script} << EOF\n{text}\nEOF")
+            }
+            StdInKind::File { path } => {
+                format!("{script} < {path}")
+            }
+            StdInKind::Unknown => script,
+        };
+        let script = script_info.launcher.command_line(
+            &script,
+            "$LSB_DJOB_NUMPROC",
+            "${OMP_NUM_THREADS:-1}",
+        );
+        let load_software = script_info.load_software;
+        let mut resource_header = String::default();
+        let queue = script_info
+            .requirements
+            .as_ref()
+            .and_then(|el| el.partition.as_deref())
+            .or(default_queue);
+        if let Some(queue) = queue {
+            resource_header += format!("#BSUB -q {queue}\n").as_str();
+        }
+        if let Some(x) = script_info.requirements {
+            let nodes = x.node_count.unwrap_or(1).max(1) as usize;
+            let cores = x.cpu_cores.unwrap_or(1).max(1);
+            resource_header += format!("#BSUB -n {}\n", nodes * cores).as_str();
+            resource_header += format!("#BSUB -R \"span[ptile={cores}]\"\n").as_str();
+            if let Some(wall_time) = x.max_wall_time {
+                resource_header += format!("#BSUB -W {}\n", format_minutes(wall_time)).as_str();
+            }
+            if let Some(cpu_time) = x.max_cpu_time {
+                resource_header += format!("#BSUB -c {}\n", format_minutes(cpu_time)).as_str();
+            }
+            if let Some(account) = x.account {
+                resource_header += format!("#BSUB -P {account}\n").as_str();
+            }
+            // LSF 的内存预留按槽位计算
+            if let Some(memory) = x.memory_per_node {
+                resource_header +=
+                    format!("#BSUB -R \"rusage[mem={}MB]\"\n", memory.div_ceil(cores)).as_str();
+            }
+            if let Some(gpus) = x.gpus_per_node {
+                resource_header += format!("#BSUB -gpu \"num={gpus}\"\n").as_str();
+            }
+            if x.exclusive {
+                resource_header += "#BSUB -x\n";
+            }
+        }
+        formatdoc! {r#"
+            {header}
+            #BSUB -o {base_path}/{id}/STDOUT
+            #BSUB -e {base_path}/{id}/STDERR
+            {resource_header}
+            cd $LS_SUBCWD
+            {env_string}
+            {include_env}
+            {load_software}
+            {script}
+            ec=$?
+            {touch}
+            exit $ec
+        "#}
+    }
+}
+
+/// 解析 `Job <2105> is submitted to queue <normal>.`
+fn parse_submitted_id(out: &str) -> anyhow::Result<String> {
+    out.split_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(id, _)| id.to_string())
+        .with_context(|| format!("Id parse error. stdout: {out}"))
+}
+
+/// 解析形如 `607 second(s)` 的时长
+fn parse_seconds(value: &str) -> u64 {
+    value
+        .split_whitespace()
+        .next()
+        .and_then(|el| el.parse::<f64>().ok())
+        .map(|el| el.round() as u64)
+        .unwrap_or(0)
+}
+
+/// 解析形如 `1.5 Gbytes` 的内存大小
+fn parse_memory(value: &str) -> u64 {
+    let mut parts = value.split_whitespace();
+    let size = parts.next().and_then(|el| el.parse::<f64>().ok()).unwrap_or(0.0);
+    let multiplier = match parts.next().unwrap_or_default() {
+        "Kbytes" => 1024u64,
+        "Mbytes" => 1024 * 1024,
+        "Gbytes" => 1024 * 1024 * 1024,
+        "Tbytes" => 1024 * 1024 * 1024 * 1024,
+        _ => 1,
+    };
+    (size * multiplier as f64) as u64
+}
+
+/// 解析 bjobs 的时间，未显示年份时按当前年份计算
+fn parse_time(time: &str) -> i64 {
+    let time = time.trim_end_matches([' ', 'L', 'E', 'X']);
+    let with_year = format!("{time} {}", Local::now().year());
+    [time, with_year.as_str()]
+        .iter()
+        .find_map(|time| {
+            ["%b %d %H:%M:%S %Y", "%b %d %H:%M %Y"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(time, format).ok())
+        })
+        .and_then(|el| Local.from_local_datetime(&el).single())
+        .map(|el| el.timestamp())
+        .unwrap_or(0)
+}
+
+/// 将秒数向上取整为 LSF 的 `[HH:]MM` 格式
+fn format_minutes(duration: usize) -> String {
+    let minutes = duration.div_ceil(60);
+    format!("{}:{:0>2}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::model::entity::task::{Launcher, Requirements};
+
+    #[test]
+    fn test_jobs() {
+        let result: LsfJobs = serde_json::from_str(include_str!("fixtures/bjobs.json")).unwrap();
+        let jobs = result.records.into_iter().map(LsfClient::job).collect::<Vec<_>>();
+        let states = jobs.iter().map(|el| (el.id.as_str(), el.state.clone())).collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                ("2101", JobState::Completed),
+                ("2102", JobState::Failed),
+                ("2103", JobState::Running),
+                ("2104", JobState::Queuing),
+            ]
+        );
+
+        let done = &jobs[0].resource_used;
+        assert_eq!(done.cpu, 8);
+        assert_eq!(done.wall_time, 607);
+        assert_eq!(done.cpu_time, 4813);
+        assert_eq!(done.avg_memory, 1024 * 1024 * 1024);
+        assert_eq!(done.max_memory, 2 * 1024 * 1024 * 1024);
+        assert_eq!(done.end_time - done.start_time, 607);
+        assert_eq!(done.partition.as_deref(), Some("normal"));
+
+        assert_eq!(jobs[1].exit_status_code, 137);
+        assert_eq!(jobs[1].resource_used.max_memory, 512 * 1024 * 1024);
+
+        let running = &jobs[2].resource_used;
+        assert_eq!(running.node, 2);
+        assert_ne!(running.start_time, 0);
+        assert_eq!(running.end_time, 0);
+    }
+
+    #[test]
+    fn test_parse_submitted_id() {
+        assert_eq!(
+            parse_submitted_id(include_str!("fixtures/bsub.txt")).unwrap(),
+            "2105"
+        );
+        assert!(parse_submitted_id("Request aborted by esub.").is_err());
+    }
+
+    #[test]
+    fn test_gen_script() {
+        let script_info = ScriptInfo {
+            id: "task".to_string(),
+            parent_id: "parent".to_string(),
+            name: "vasp".to_string(),
+            requirements: Some(Requirements {
+                cpu_cores: Some(8),
+                node_count: Some(2),
+                max_wall_time: Some(5400),
+                partition: Some("short".to_string()),
+                account: Some("chem".to_string()),
+                memory_per_node: Some(4096),
+                gpus_per_node: Some(2),
+                exclusive: true,
+                ..Default::default()
+            }),
+            launcher: Launcher::Mpiexec,
+            ..Default::default()
+        };
+        let script = LsfClient::gen_script("/tasks", "", Some("normal"), script_info);
+        for directive in [
+            "#BSUB -e /tasks/parent/STDERR",
+            "#BSUB -q short",
+            "#BSUB -n 16",
+            "#BSUB -R \"span[ptile=8]\"",
+            "#BSUB -W 1:30",
+            "#BSUB -P chem",
+            "#BSUB -R \"rusage[mem=512MB]\"",
+            "#BSUB -gpu \"num=2\"",
+            "#BSUB -x",
+            "mpiexec -n $LSB_DJOB_NUMPROC vasp",
+        ] {
+            assert!(
+                script.contains(directive),
+                "{directive} is missing in {script}"
+            );
+        }
+    }
+}
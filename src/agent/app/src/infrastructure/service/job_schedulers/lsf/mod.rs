@@ -0,0 +1,4 @@
+pub mod lsf_client;
+pub mod models;
+pub use self::lsf_client::*;
+pub use self::models::*;
@@ -0,0 +1,38 @@
+use serde::*;
+
+/// `bjobs -o` 查询的字段
+pub const BJOBS_FIELDS: &str = "jobid job_name user stat queue exit_code exec_cwd error_file cpu_used run_time nalloc_slot avg_mem max_mem nexec_host start_time finish_time";
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LsfJobs {
+    #[serde(rename = "RECORDS", default)]
+    pub records: Vec<LsfJob>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct LsfJob {
+    #[serde(rename = "JOBID")]
+    pub job_id: String,
+    pub job_name: String,
+    pub user: String,
+    pub stat: String,
+    pub queue: String,
+    pub exit_code: String,
+    pub exec_cwd: String,
+    pub error_file: String,
+    /// 形如 `4812.6 second(s)`
+    pub cpu_used: String,
+    /// 形如 `607 second(s)`
+    pub run_time: String,
+    pub nalloc_slot: String,
+    /// 形如 `1 Gbytes`
+    pub avg_mem: String,
+    pub max_mem: String,
+    pub nexec_host: String,
+    /// 形如 `Mar 12 10:21:33 2024`，可能带有 `L`（实际）或 `E`（预计）后缀
+    pub start_time: String,
+    pub finish_time: String,
+    /// 作业不存在等错误信息
+    pub error: Option<String>,
+}
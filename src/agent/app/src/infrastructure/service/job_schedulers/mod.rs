@@ -1,4 +1,10 @@
+pub mod local;
+pub mod lsf;
 pub mod pbs;
+pub mod sge;
 pub mod slurm;
+pub use self::local::*;
+pub use self::lsf::*;
 pub use self::pbs::*;
+pub use self::sge::*;
 pub use self::slurm::*;
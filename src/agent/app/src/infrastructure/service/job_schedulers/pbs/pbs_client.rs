@@ -22,6 +22,8 @@ use crate::infrastructure::ssh_proxy::SshProxy;
 pub struct PBSClient {
     base_path: String,
     include_env: String,
+    /// 未指定队列时使用的默认队列
+    queue: Option<String>,
     ssh_proxy: Arc<SshProxy>,
 }
 
@@ -116,6 +118,9 @@ impl JobSchedulerService for PBSClient {
     }
 
     async fn submit_job_script(&self, script_info: ScriptInfo) -> anyhow::Result<String> {
+        if let Some(requirements) = &script_info.requirements {
+            requirements.validate_scheduler_names()?;
+        }
         let mut path = PathBuf::new();
         path.push(self.base_path.as_str());
         if !path.exists() {
@@ -124,7 +129,12 @@ impl JobSchedulerService for PBSClient {
         path.push(script_info.path.as_str());
         tokio::fs::write(
             path,
-            Self::gen_script(&self.base_path, &self.include_env, script_info.clone()),
+            Self::gen_script(
+                &self.base_path,
+                &self.include_env,
+                self.queue.as_deref(),
+                script_info.clone(),
+            ),
         )
         .await?;
         self.submit_job(script_info.path.as_str()).await
@@ -189,6 +199,7 @@ impl PBSClient {
                     "H" => JobState::Suspended,
                     _ => JobState::Unknown,
                 },
+                scheduler_state: item.job_state.clone(),
                 exit_status_code: item.exit_status,
                 error_output: std::fs::read_to_string(
                     item.error_path.split_once(':').unwrap_or_default().1,
@@ -214,6 +225,7 @@ impl PBSClient {
                         _ => 0,
                     },
                     node: item.resource_list.nodect as u64,
+                    partition: Some(item.queue.clone()).filter(|el| !el.is_empty()),
                 },
             })
             .collect())
@@ -245,6 +257,7 @@ impl PBSClient {
                 let line = line.trim();
                 if line.starts_with("job_state = ") {
                     let state = line.replace("job_state = ", "");
+                    temp.scheduler_state = state.clone();
                     temp.state = match state.as_str() {
                         "R" => JobState::Running,
                         "E" => {
@@ -351,6 +364,7 @@ impl PBSClient {
                     "H" => JobState::Suspended,
                     _ => JobState::Unknown,
                 },
+                scheduler_state: item.job_state.clone(),
                 exit_status_code: item.exit_status,
                 error_output: std::fs::read_to_string(
                     item.error_path.split_once(':').unwrap_or_default().1,
@@ -376,6 +390,7 @@ impl PBSClient {
                         _ => 0,
                     },
                     node: item.resource_list.nodect as u64,
+                    partition: Some(item.queue.clone()).filter(|el| !el.is_empty()),
                 },
             })
             .next()
@@ -408,6 +423,7 @@ impl PBSClient {
                 let line = line.trim();
                 if line.starts_with("job_state = ") {
                     let state = line.replace("job_state = ", "");
+                    temp.scheduler_state = state.clone();
                     temp.state = match state.as_str() {
                         "R" => JobState::Running,
                         "E" => {
@@ -481,7 +497,12 @@ impl PBSClient {
         }
     }
 
-    fn gen_script(base_path: &str, include_env: &str, script_info: ScriptInfo) -> String {
+    fn gen_script(
+        base_path: &str,
+        include_env: &str,
+        default_queue: Option<&str>,
+        script_info: ScriptInfo,
+    ) -> String {
         let header = "#!/bin/bash";
         let id = script_info.parent_id.clone();
         let env: Vec<String> = script_info
@@ -501,9 +522,13 @@ impl PBSClient {
             }
             StdInKind::Unknown => script,
         };
+        let script = script_info.launcher.command_line(&script, "$NP", "${OMP_NUM_THREADS:-1}");
         let load_software = script_info.load_software.clone();
         let resource_header = match script_info.requirements {
-            None => String::default(),
+            None => match default_queue {
+                Some(queue) => format!("#PBS -q {queue}\n"),
+                None => String::default(),
+            },
             Some(x) => {
                 let mut header = String::default();
                 header += match x.node_count {
@@ -518,8 +543,13 @@ impl PBSClient {
                 }
                 .as_str();
                 header += match x.cpu_cores {
-                    Some(x) => format!("ppn={x}\n"),
-                    None => "ppn=1\n".to_string(),
+                    Some(x) => format!("ppn={x}"),
+                    None => "ppn=1".to_string(),
+                }
+                .as_str();
+                header += match x.gpus_per_node {
+                    Some(x) => format!(":gpus={x}\n"),
+                    None => "\n".to_string(),
                 }
                 .as_str();
                 header += match x.max_wall_time {
@@ -532,6 +562,22 @@ impl PBSClient {
                     None => String::default(),
                 }
                 .as_str();
+                if let Some(queue) = x.partition.as_deref().or(default_queue) {
+                    header += format!("#PBS -q {queue}\n").as_str();
+                }
+                if let Some(account) = x.account {
+                    header += format!("#PBS -A {account}\n").as_str();
+                }
+                if let Some(qos) = x.qos {
+                    header += format!("#PBS -l qos={qos}\n").as_str();
+                }
+                if let Some(memory) = x.memory_per_node {
+                    let nodes = x.node_count.unwrap_or(1).max(1) as usize;
+                    header += format!("#PBS -l mem={}mb\n", memory * nodes).as_str();
+                }
+                if x.exclusive {
+                    header += "#PBS -n\n";
+                }
                 header
             }
         };
@@ -545,17 +591,23 @@ impl PBSClient {
             {env_string}
             {include_env}
             {load_software}
-            mpirun -np $NP {script}
+            {script}
             result = $?
             {touch}
             $(exit $result)
         "#}
     }
 
-    pub fn new(base_path: String, include_env: String, ssh_proxy: Arc<SshProxy>) -> Self {
+    pub fn new(
+        base_path: String,
+        include_env: String,
+        queue: Option<String>,
+        ssh_proxy: Arc<SshProxy>,
+    ) -> Self {
         Self {
             base_path,
             include_env,
+            queue,
             ssh_proxy,
         }
     }
@@ -0,0 +1,4 @@
+pub mod models;
+pub mod sge_client;
+pub use self::models::*;
+pub use self::sge_client::*;
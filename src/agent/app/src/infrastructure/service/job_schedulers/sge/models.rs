@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+/// `qstat` 列出的未结束作业
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SgeJob {
+    pub job_id: String,
+    pub name: String,
+    pub owner: String,
+    /// 状态字母组合，如 `r`、`qw`、`Eqw`
+    pub state: String,
+    /// 提交或开始时间，格式为 `%m/%d/%Y %H:%M:%S`
+    pub submit_or_start: String,
+    /// 运行中作业所在的队列，排队中的作业为空
+    pub queue: String,
+    pub slots: u64,
+}
+
+impl SgeJob {
+    /// 解析 `qstat` 的默认表格输出
+    pub fn parse_list(out: &str) -> Vec<Self> {
+        out.lines()
+            .filter_map(|line| {
+                let columns = line.split_whitespace().collect::<Vec<_>>();
+                if columns.len() < 8 || !columns[0].chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                // 排队中的作业没有 queue 列，slots 总在 queue 列之后
+                let slots_index = if columns[7].contains('@') { 8 } else { 7 };
+                Some(Self {
+                    job_id: columns[0].to_string(),
+                    name: columns[2].to_string(),
+                    owner: columns[3].to_string(),
+                    state: columns[4].to_string(),
+                    submit_or_start: format!("{} {}", columns[5], columns[6]),
+                    queue: match slots_index {
+                        8 => columns[7].split('@').next().unwrap_or_default().to_string(),
+                        _ => String::default(),
+                    },
+                    slots: columns.get(slots_index).and_then(|el| el.parse().ok()).unwrap_or(1),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `qacct -j` 记录的已结束作业
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct SgeAccounting {
+    pub job_number: String,
+    pub job_name: String,
+    pub owner: String,
+    /// 作业所在的队列
+    pub qname: String,
+    pub hostname: String,
+    /// 非 0 时表示作业未能正常启动或被系统终止
+    pub failed: i32,
+    pub exit_status: i32,
+    pub slots: u64,
+    /// 墙钟时间（s）
+    pub wallclock: u64,
+    /// 核心时间（s）
+    pub cpu: u64,
+    /// 内存时间积分（GB·s）
+    pub mem: f64,
+    /// 最大虚拟内存（byte）
+    pub maxvmem: u64,
+    pub start_time: String,
+    pub end_time: String,
+}
+
+impl SgeAccounting {
+    /// 解析 `qacct -j` 的输出，每条记录以一行 `=` 分隔
+    pub fn parse_records(out: &str) -> Vec<Self> {
+        out.split("==============================================================")
+            .filter_map(|block| {
+                let fields = block
+                    .lines()
+                    .filter_map(|line| {
+                        let line = line.trim();
+                        let (key, value) = line.split_once(char::is_whitespace)?;
+                        Some((key, value.trim()))
+                    })
+                    .collect::<HashMap<_, _>>();
+                let job_number = fields.get("jobnumber")?.to_string();
+                let field = |key: &str| fields.get(key).copied().unwrap_or_default();
+                let leading_number = |key: &str| {
+                    field(key).split_whitespace().next().unwrap_or("0").parse().unwrap_or(0)
+                };
+                Some(Self {
+                    job_number,
+                    job_name: field("jobname").to_string(),
+                    owner: field("owner").to_string(),
+                    qname: field("qname").to_string(),
+                    hostname: field("hostname").to_string(),
+                    failed: leading_number("failed"),
+                    exit_status: leading_number("exit_status"),
+                    slots: field("slots").parse().unwrap_or(1),
+                    wallclock: parse_seconds(field("ru_wallclock")),
+                    cpu: parse_seconds(field("cpu")),
+                    mem: field("mem").trim_end_matches("GBs").parse().unwrap_or(0.0),
+                    maxvmem: parse_memory(field("maxvmem")),
+                    start_time: field("start_time").to_string(),
+                    end_time: field("end_time").to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// 解析秒数，新版本的 Grid Engine 会带有 `s` 后缀
+fn parse_seconds(value: &str) -> u64 {
+    value
+        .trim_end_matches('s')
+        .parse::<f64>()
+        .map(|el| el.round() as u64)
+        .unwrap_or(0)
+}
+
+/// 解析带有 `K`、`M`、`G`、`T` 单位后缀的内存大小
+pub fn parse_memory(value: &str) -> u64 {
+    let unit = value.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let size = value.trim_end_matches(char::is_alphabetic).parse::<f64>().unwrap_or(0.0);
+    let multiplier = match unit {
+        "K" | "k" => 1024u64,
+        "M" | "m" => 1024 * 1024,
+        "G" | "g" => 1024 * 1024 * 1024,
+        "T" | "t" => 1024 * 1024 * 1024 * 1024,
+        _ => 1,
+    };
+    (size * multiplier as f64) as u64
+}
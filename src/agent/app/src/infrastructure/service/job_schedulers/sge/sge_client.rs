@@ -0,0 +1,506 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use domain::{
+    model::{
+        entity::task::{StdInKind, TaskUsedResource},
+        vo::job::{Job, JobState, ScriptInfo},
+    },
+    service::JobSchedulerService,
+};
+use indoc::formatdoc;
+use tokio::process::Command;
+
+use super::{SgeAccounting, SgeJob};
+use crate::infrastructure::ssh_proxy::SshProxy;
+
+/// 作业名前缀，作业名为 `co-<任务目录名>`，用于在 `qacct` 记录中找回作业目录
+const JOB_NAME_PREFIX: &str = "co-";
+
+/// Grid Engine（SGE、OGS、UGE）客户端
+pub struct SgeClient {
+    base_path: String,
+    include_env: String,
+    /// 未指定队列时使用的默认队列
+    queue: Option<String>,
+    /// 多槽位作业使用的并行环境
+    parallel_environment: String,
+    ssh_proxy: Arc<SshProxy>,
+}
+
+#[async_trait::async_trait]
+impl JobSchedulerService for SgeClient {
+    async fn get_jobs(&self) -> anyhow::Result<Vec<Job>> {
+        let out = self.ssh_proxy.command("qstat").output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for get_jobs. real: {}", out.status)
+        }
+        Ok(SgeJob::parse_list(&String::from_utf8_lossy(&out.stdout))
+            .into_iter()
+            .map(Self::active_job)
+            .collect())
+    }
+
+    /// 未结束的作业从 `qstat` 中获取，已结束的作业从 `qacct` 中获取
+    async fn get_job(&self, id: &str) -> anyhow::Result<Job> {
+        if let Some(job) = self.get_jobs().await?.into_iter().find(|el| el.id.eq(id)) {
+            return Ok(job);
+        }
+        let out = self.ssh_proxy.command("qacct").args(["-j", id]).output().await?;
+        if !out.status.success() {
+            // 作业刚结束时记账信息可能尚未写入，等待下次刷新
+            return Ok(Job {
+                id: id.to_string(),
+                state: JobState::Completing,
+                ..Default::default()
+            });
+        }
+        let record = SgeAccounting::parse_records(&String::from_utf8_lossy(&out.stdout))
+            .pop()
+            .context("No such id")?;
+        Ok(self.finished_job(record))
+    }
+
+    async fn submit_job(&self, script_path: &str) -> anyhow::Result<String> {
+        let out = 'block: {
+            let mut path = PathBuf::new();
+            path.push(self.base_path.as_str());
+            path.push(script_path);
+
+            let Some(ssh_config) = self.ssh_proxy.config() else {
+                let out = Command::new("qsub")
+                    .arg("-terse")
+                    .arg(&path)
+                    .current_dir(path.parent().unwrap())
+                    .output()
+                    .await?;
+                if !out.status.success() {
+                    anyhow::bail!("Exit Status not 0 for submit_job. real: {}", out.status)
+                }
+                break 'block out;
+            };
+
+            let mut remote_path = PathBuf::new();
+            remote_path.extend([&ssh_config.home_dir, &ssh_config.save_dir, script_path]);
+            let out = self
+                .ssh_proxy
+                .command("mkdir")
+                .arg("-p")
+                .arg(remote_path.parent().unwrap().to_string_lossy().as_ref())
+                .output()
+                .await;
+            match out {
+                Ok(out) => {
+                    if !out.status.success() {
+                        log::error!(
+                            "Unable to create directory {} on for sge script.",
+                            remote_path.parent().unwrap().to_string_lossy(),
+                        );
+                    }
+                }
+                Err(e) => {
+                    log::error!("{e}");
+                }
+            }
+            let _ = Command::new("scp")
+                .arg("-P")
+                .arg(&ssh_config.port)
+                .arg(path)
+                .arg(format!(
+                    "{}:{}",
+                    ssh_config.username_host,
+                    remote_path.to_str().unwrap()
+                ))
+                .output()
+                .await?;
+            let out = self
+                .ssh_proxy
+                .command("cd")
+                .arg(remote_path.parent().unwrap())
+                .arg(";")
+                .arg("qsub")
+                .arg("-terse")
+                .arg(remote_path)
+                .output()
+                .await?;
+            if !out.status.success() {
+                anyhow::bail!("Exit Status not 0 for submit_job. real: {}", out.status)
+            }
+            out
+        };
+
+        // 数组作业的 id 形如 `101.1-10:1`
+        Ok(String::from_utf8_lossy(&out.stdout)
+            .split('.')
+            .next()
+            .context("Id parse error")?
+            .trim()
+            .to_owned())
+    }
+
+    async fn submit_job_script(&self, script_info: ScriptInfo) -> anyhow::Result<String> {
+        if let Some(requirements) = &script_info.requirements {
+            requirements.validate_scheduler_names()?;
+        }
+        let mut path = PathBuf::new();
+        path.push(self.base_path.as_str());
+        if !path.exists() {
+            tokio::fs::create_dir_all(path.as_path()).await?;
+        }
+        path.push(script_info.path.as_str());
+        tokio::fs::write(
+            path,
+            Self::gen_script(
+                &self.base_path,
+                &self.include_env,
+                self.queue.as_deref(),
+                &self.parallel_environment,
+                script_info.clone(),
+            ),
+        )
+        .await?;
+        self.submit_job(script_info.path.as_str()).await
+    }
+
+    async fn delete_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("qdel").arg(job_id).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for delete_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+
+    async fn pause_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("qmod").args(["-sj", job_id]).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for pause_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+
+    async fn continue_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("qmod").args(["-usj", job_id]).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for continue_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+}
+
+impl SgeClient {
+    pub fn new(
+        base_path: String,
+        include_env: String,
+        queue: Option<String>,
+        parallel_environment: String,
+        ssh_proxy: Arc<SshProxy>,
+    ) -> Self {
+        Self {
+            base_path,
+            include_env,
+            queue,
+            parallel_environment,
+            ssh_proxy,
+        }
+    }
+
+    fn active_job(job: SgeJob) -> Job {
+        let state = job_state(&job.state);
+        Job {
+            id: job.job_id,
+            name: job.name,
+            owner: job.owner,
+            resource_used: TaskUsedResource {
+                cpu: job.slots,
+                start_time: match state {
+                    JobState::Queuing => 0,
+                    _ => parse_time(&job.submit_or_start),
+                },
+                partition: Some(job.queue).filter(|el| !el.is_empty()),
+                ..Default::default()
+            },
+            state,
+            scheduler_state: job.state,
+            ..Default::default()
+        }
+    }
+
+    fn finished_job(&self, record: SgeAccounting) -> Job {
+        let work_dir = match record.job_name.strip_prefix(JOB_NAME_PREFIX) {
+            Some(dir) => format!("{}/{dir}", self.base_path),
+            None => self.base_path.clone(),
+        };
+        Job {
+            state: if record.failed != 0 || record.exit_status != 0 {
+                JobState::Failed
+            } else {
+                JobState::Completed
+            },
+            scheduler_state: format!(
+                "failed={} exit_status={}",
+                record.failed, record.exit_status
+            ),
+            exit_status_code: record.exit_status,
+            error_output: std::fs::read_to_string(format!("{work_dir}/STDERR")).unwrap_or_default(),
+            resource_used: TaskUsedResource {
+                cpu: record.slots,
+                // qacct 只记录内存对时间的积分，以此估算平均内存
+                avg_memory: if record.wallclock == 0 {
+                    0
+                } else {
+                    (record.mem * 1024f64.powi(3) / record.wallclock as f64) as u64
+                },
+                max_memory: record.maxvmem,
+                storage: 0,
+                wall_time: record.wallclock,
+                cpu_time: record.cpu,
+                start_time: parse_time(&record.start_time),
+                end_time: parse_time(&record.end_time),
+                // qacct 只记录主节点
+                node: 1,
+                partition: Some(record.qname).filter(|el| !el.is_empty()),
+            },
+            id: record.job_number,
+            name: record.job_name,
+            owner: record.owner,
+        }
+    }
+
+    fn gen_script(
+        base_path: &str,
+        include_env: &str,
+        default_queue: Option<&str>,
+        parallel_environment: &str,
+        script_info: ScriptInfo,
+    ) -> String {
+        let header = "#!/bin/bash";
+        let id = script_info.parent_id.clone();
+        let env: Vec<String> = script_info
+            .environments
+            .iter()
+            .map(|(k, v)| format!("export {}={}", k, v))
+            .collect();
+        let env_string = env.join("\n");
+        let touch = format!("echo -n \"{}\" > $SGE_O_WORKDIR/.co.sig", script_info.id);
+        let script = format!("{} {}", script_info.name, script_info.arguments.join(" "));
+        let script = match script_info.std_in {
+            StdInKind::Text { text } => {
+                format!("{script} << EOF\n{text}\nEOF")
+            }
+            StdInKind::File { path } => {
+                format!("{script} < {path}")
+            }
+            StdInKind::Unknown => script,
+        };
+        let script = script_info.launcher.command_line(&script, "$NSLOTS", "${OMP_NUM_THREADS:-1}");
+        let load_software = script_info.load_software;
+        let mut resource_header = String::default();
+        let queue = script_info
+            .requirements
+            .as_ref()
+            .and_then(|el| el.partition.as_deref())
+            .or(default_queue);
+        if let Some(queue) = queue {
+            resource_header += format!("#$ -q {queue}\n").as_str();
+        }
+        if let Some(x) = script_info.requirements {
+            let nodes = x.node_count.unwrap_or(1).max(1) as usize;
+            let cores = x.cpu_cores.unwrap_or(1).max(1);
+            let slots = nodes * cores;
+            if slots > 1 {
+                resource_header += format!("#$ -pe {parallel_environment} {slots}\n").as_str();
+            }
+            if let Some(wall_time) = x.max_wall_time {
+                resource_header += format!("#$ -l h_rt={}\n", format_duration(wall_time)).as_str();
+            }
+            if let Some(cpu_time) = x.max_cpu_time {
+                resource_header +=
+                    format!("#$ -l h_cpu={}\n", format_duration(cpu_time / slots)).as_str();
+            }
+            if let Some(account) = x.account {
+                resource_header += format!("#$ -A {account}\n").as_str();
+            }
+            // Grid Engine 的内存限制按槽位计算
+            if let Some(memory) = x.memory_per_node {
+                resource_header += format!("#$ -l h_vmem={}M\n", memory.div_ceil(cores)).as_str();
+            }
+            if let Some(gpus) = x.gpus_per_node {
+                resource_header += format!("#$ -l gpu={gpus}\n").as_str();
+            }
+            if x.exclusive {
+                resource_header += "#$ -l exclusive=true\n";
+            }
+        }
+        formatdoc! {r#"
+            {header}
+            #$ -N {JOB_NAME_PREFIX}{id}
+            #$ -o {base_path}/{id}/STDOUT
+            #$ -e {base_path}/{id}/STDERR
+            #$ -S /bin/bash
+            #$ -cwd
+            {resource_header}
+            {env_string}
+            {include_env}
+            {load_software}
+            {script}
+            ec=$?
+            {touch}
+            exit $ec
+        "#}
+    }
+}
+
+/// 将 qstat 的状态字母组合映射为作业状态
+fn job_state(state: &str) -> JobState {
+    if state.contains('E') {
+        JobState::Failed
+    } else if state.contains('d') {
+        JobState::Completing
+    } else if state.contains(['h', 's', 'S', 'T']) {
+        JobState::Suspended
+    } else if state.contains(['r', 't', 'R']) {
+        JobState::Running
+    } else if state.contains(['q', 'w']) {
+        JobState::Queuing
+    } else {
+        JobState::Unknown
+    }
+}
+
+fn parse_time(time: &str) -> i64 {
+    [
+        "%a %b %d %T %Y",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%m/%d/%Y %H:%M:%S",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(time, format).ok())
+    .and_then(|el| Local.from_local_datetime(&el).single())
+    .map(|el| el.timestamp())
+    .unwrap_or(0)
+}
+
+fn format_duration(duration: usize) -> String {
+    let hours = duration / 3600;
+    let minutes = duration % 3600 / 60;
+    let seconds = duration % 3600 % 60;
+
+    format!(
+        "{}:{}:{}",
+        format_args!("{hours:0>2}"),
+        format_args!("{minutes:0>2}"),
+        format_args!("{seconds:0>2}")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::model::entity::task::Requirements;
+
+    fn client() -> SgeClient {
+        SgeClient::new(
+            "/tasks".to_string(),
+            String::default(),
+            None,
+            "mpi".to_string(),
+            Arc::new(SshProxy::new(&None)),
+        )
+    }
+
+    #[test]
+    fn test_active_jobs() {
+        let jobs = SgeJob::parse_list(include_str!("fixtures/qstat.txt"))
+            .into_iter()
+            .map(SgeClient::active_job)
+            .collect::<Vec<_>>();
+        let states = jobs.iter().map(|el| (el.id.as_str(), el.state.clone())).collect::<Vec<_>>();
+        assert_eq!(
+            states,
+            vec![
+                ("101", JobState::Running),
+                ("102", JobState::Running),
+                ("103", JobState::Queuing),
+                ("104", JobState::Suspended),
+                ("105", JobState::Failed),
+                ("106", JobState::Completing),
+            ]
+        );
+        assert_eq!(jobs[0].resource_used.cpu, 8);
+        assert_eq!(jobs[2].resource_used.cpu, 4);
+        assert_eq!(jobs[2].resource_used.start_time, 0);
+        assert_ne!(jobs[0].resource_used.start_time, 0);
+        assert_eq!(jobs[0].resource_used.partition.as_deref(), Some("all.q"));
+        assert_eq!(jobs[2].resource_used.partition, None);
+    }
+
+    #[test]
+    fn test_finished_job() {
+        let record =
+            SgeAccounting::parse_records(include_str!("fixtures/qacct.txt")).pop().unwrap();
+        let job = client().finished_job(record);
+        assert_eq!(job.id, "101");
+        assert_eq!(job.owner, "alice");
+        assert_eq!(job.state, JobState::Completed);
+        assert_eq!(job.resource_used.cpu, 8);
+        assert_eq!(job.resource_used.wall_time, 607);
+        assert_eq!(job.resource_used.cpu_time, 4813);
+        assert_eq!(job.resource_used.max_memory, 2 * 1024 * 1024 * 1024);
+        assert_eq!(job.resource_used.avg_memory, 1024 * 1024 * 1024);
+        assert_eq!(job.resource_used.partition.as_deref(), Some("all.q"));
+        assert_eq!(
+            job.resource_used.end_time - job.resource_used.start_time,
+            607
+        );
+
+        let record = SgeAccounting::parse_records(include_str!("fixtures/qacct_failed.txt"))
+            .pop()
+            .unwrap();
+        let job = client().finished_job(record);
+        assert_eq!(job.state, JobState::Failed);
+        assert_eq!(job.exit_status_code, 137);
+        assert_eq!(job.resource_used.max_memory, 512 * 1024 * 1024);
+        assert_eq!(
+            job.resource_used.end_time - job.resource_used.start_time,
+            38
+        );
+    }
+
+    #[test]
+    fn test_gen_script() {
+        let script_info = ScriptInfo {
+            id: "task".to_string(),
+            parent_id: "parent".to_string(),
+            name: "vasp".to_string(),
+            requirements: Some(Requirements {
+                cpu_cores: Some(8),
+                node_count: Some(2),
+                max_wall_time: Some(3600),
+                account: Some("chem".to_string()),
+                memory_per_node: Some(4096),
+                exclusive: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let script = SgeClient::gen_script("/tasks", "", Some("all.q"), "mpi", script_info);
+        for directive in [
+            "#$ -N co-parent",
+            "#$ -e /tasks/parent/STDERR",
+            "#$ -q all.q",
+            "#$ -pe mpi 16",
+            "#$ -l h_rt=01:00:00",
+            "#$ -A chem",
+            "#$ -l h_vmem=512M",
+            "#$ -l exclusive=true",
+            "mpirun -np $NSLOTS vasp",
+        ] {
+            assert!(
+                script.contains(directive),
+                "{directive} is missing in {script}"
+            );
+        }
+    }
+}
@@ -30,4 +30,6 @@ pub struct SlurmJob {
     pub end: String,
     #[serde(rename = "NNodes")]
     pub nnodes: u64,
+    #[serde(rename = "Partition", default)]
+    pub partition: String,
 }
@@ -18,6 +18,8 @@ use crate::infrastructure::ssh_proxy::SshProxy;
 pub struct SlurmClient {
     base_path: String,
     include_env: String,
+    /// 未指定分区时使用的默认分区
+    queue: Option<String>,
     ssh_proxy: Arc<SshProxy>,
 }
 
@@ -27,7 +29,7 @@ impl JobSchedulerService for SlurmClient {
         let out = self.ssh_proxy.command("sacct")
             .args([
                 "-PXo",
-                "JobID,JobName,User,State,ExitCode,WorkDir,CPUTimeRaw,ElapsedRaw,NCPUS,AveRSS,MaxRSS,NNodes,Start,End",
+                "JobID,JobName,User,State,ExitCode,WorkDir,CPUTimeRaw,ElapsedRaw,NCPUS,AveRSS,MaxRSS,NNodes,Start,End,Partition",
                 "-j",
                 id,
             ])
@@ -48,8 +50,8 @@ impl JobSchedulerService for SlurmClient {
                 name: record.job_name,
                 owner: record.user,
                 state: match record.state.as_str() {
-                    "BOOT_FAIL" | "FAILED" | "NODE_FAIL" | "OUT_OF_MEMORY" | "TIMEOUT"
-                    | "DEADLINE" => JobState::Failed,
+                    "BOOT_FAIL" | "FAILED" | "NODE_FAIL" | "OUT_OF_MEMORY" | "PREEMPTED"
+                    | "TIMEOUT" | "DEADLINE" => JobState::Failed,
                     "CANCELLED" => JobState::Suspended,
                     "COMPLETED" => JobState::Completed,
                     "PENDING" => JobState::Queuing,
@@ -57,6 +59,7 @@ impl JobSchedulerService for SlurmClient {
                     "RUNNING" => JobState::Running,
                     _ => JobState::Unknown,
                 },
+                scheduler_state: record.state,
                 exit_status_code: record.exit_code.split(':').next().unwrap_or("0").parse()?,
                 error_output: tokio::fs::read_to_string(format!("{}/STDERR", record.work_dir))
                     .await
@@ -71,6 +74,7 @@ impl JobSchedulerService for SlurmClient {
                     start_time: parse_time(&record.start),
                     end_time: parse_time(&record.end),
                     node: record.nnodes,
+                    partition: Some(record.partition).filter(|el| !el.is_empty()),
                 },
             })
         }
@@ -102,8 +106,8 @@ impl JobSchedulerService for SlurmClient {
                 name: record.job_name,
                 owner: record.user,
                 state: match record.state.as_str() {
-                    "BOOT_FAIL" | "FAILED" | "NODE_FAIL" | "OUT_OF_MEMORY" | "TIMEOUT"
-                    | "DEADLINE" => JobState::Failed,
+                    "BOOT_FAIL" | "FAILED" | "NODE_FAIL" | "OUT_OF_MEMORY" | "PREEMPTED"
+                    | "TIMEOUT" | "DEADLINE" => JobState::Failed,
                     "CANCELLED" => JobState::Suspended,
                     "COMPLETED" => JobState::Completed,
                     "PENDING" => JobState::Queuing,
@@ -111,6 +115,7 @@ impl JobSchedulerService for SlurmClient {
                     "RUNNING" => JobState::Running,
                     _ => JobState::Unknown,
                 },
+                scheduler_state: record.state,
                 exit_status_code: record.exit_code.split(':').next().unwrap_or("0").parse()?,
                 error_output: tokio::fs::read_to_string(format!("{}/STDERR", record.work_dir))
                     .await
@@ -125,6 +130,7 @@ impl JobSchedulerService for SlurmClient {
                     start_time: parse_time(&record.start),
                     end_time: parse_time(&record.end),
                     node: record.nnodes,
+                    partition: Some(record.partition).filter(|el| !el.is_empty()),
                 },
             })
         }
@@ -132,6 +138,66 @@ impl JobSchedulerService for SlurmClient {
     }
 
     async fn submit_job(&self, script_path: &str) -> anyhow::Result<String> {
+        self.submit(script_path, self.queue.as_deref()).await
+    }
+
+    async fn submit_job_script(&self, script_info: ScriptInfo) -> anyhow::Result<String> {
+        if let Some(requirements) = &script_info.requirements {
+            requirements.validate_scheduler_names()?;
+        }
+        let mut path = PathBuf::new();
+        path.push(self.base_path.as_str());
+        if !path.exists() {
+            tokio::fs::create_dir_all(path.as_path()).await?;
+        }
+        path.push(script_info.path.as_str());
+        let partition = script_info
+            .requirements
+            .as_ref()
+            .and_then(|el| el.partition.clone())
+            .or_else(|| self.queue.clone());
+        tokio::fs::write(
+            path,
+            Self::gen_script(
+                &self.base_path,
+                &self.include_env,
+                partition.as_deref(),
+                script_info.clone(),
+            ),
+        )
+        .await?;
+        self.submit(script_info.path.as_str(), partition.as_deref()).await
+    }
+
+    async fn delete_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("scancel").arg(job_id).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for delete_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+
+    async fn pause_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("scontrol").args(["suspend", job_id]).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for pause_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+
+    async fn continue_job(&self, job_id: &str) -> anyhow::Result<()> {
+        let out = self.ssh_proxy.command("scontrol").args(["resume", job_id]).output().await?;
+        if !out.status.success() {
+            anyhow::bail!("Exit Status not 0 for continue_job. real: {}", out.status)
+        }
+        Ok(())
+    }
+}
+
+impl SlurmClient {
+    /// 以指定分区提交作业脚本，未指定分区时使用 sinfo 列出的第一个分区
+    async fn submit(&self, script_path: &str, partition: Option<&str>) -> anyhow::Result<String> {
+        let partition = self.resolve_partition(partition).await?;
         let out = {
             let mut path = PathBuf::new();
             path.push(self.base_path.as_str());
@@ -171,20 +237,6 @@ impl JobSchedulerService for SlurmClient {
                     ))
                     .output()
                     .await?;
-                let sinfo_out_bytes =
-                    self.ssh_proxy.command("sinfo").arg("-h").output().await?.stdout;
-                let sinfo_out = String::from_utf8(sinfo_out_bytes)?;
-                let partition = sinfo_out
-                    .lines()
-                    .next()
-                    .with_context(|| {
-                        format!("Unable to get sinfo from sinfo -h. stdout: {sinfo_out}")
-                    })?
-                    .split_whitespace()
-                    .next()
-                    .with_context(|| {
-                        format!("Unable to get partition from sinfo -h. stdout: {sinfo_out}")
-                    })?;
                 let out = self
                     .ssh_proxy
                     .command("cd")
@@ -200,19 +252,6 @@ impl JobSchedulerService for SlurmClient {
                 }
                 out
             } else {
-                let sinfo_out_bytes = Command::new("sinfo").arg("-h").output().await?.stdout;
-                let sinfo_out = String::from_utf8(sinfo_out_bytes)?;
-                let partition = sinfo_out
-                    .lines()
-                    .next()
-                    .with_context(|| {
-                        format!("Unable to get sinfo from sinfo -h. stdout: {sinfo_out}")
-                    })?
-                    .split_whitespace()
-                    .next()
-                    .with_context(|| {
-                        format!("Unable to get partition from sinfo -h. stdout: {sinfo_out}")
-                    })?;
                 let out = Command::new("sbatch")
                     .arg(format!("--partition={partition}"))
                     .arg(&path)
@@ -231,48 +270,37 @@ impl JobSchedulerService for SlurmClient {
             .to_string())
     }
 
-    async fn submit_job_script(&self, script_info: ScriptInfo) -> anyhow::Result<String> {
-        let mut path = PathBuf::new();
-        path.push(self.base_path.as_str());
-        if !path.exists() {
-            tokio::fs::create_dir_all(path.as_path()).await?;
-        }
-        path.push(script_info.path.as_str());
-        tokio::fs::write(
-            path,
-            Self::gen_script(&self.base_path, &self.include_env, script_info.clone()),
-        )
-        .await?;
-        self.submit_job(script_info.path.as_str()).await
-    }
-
-    async fn delete_job(&self, job_id: &str) -> anyhow::Result<()> {
-        let out = self.ssh_proxy.command("scancel").arg(job_id).output().await?;
-        if !out.status.success() {
-            anyhow::bail!("Exit Status not 0 for delete_job. real: {}", out.status)
-        }
-        Ok(())
-    }
-
-    async fn pause_job(&self, job_id: &str) -> anyhow::Result<()> {
-        let out = self.ssh_proxy.command("scontrol").args(["suspend", job_id]).output().await?;
+    /// 确定提交使用的分区
+    /// 指定的分区必须在 sinfo 列出的分区中，未指定时使用 sinfo 列出的第一个分区
+    async fn resolve_partition(&self, partition: Option<&str>) -> anyhow::Result<String> {
+        let out = self.ssh_proxy.command("sinfo").args(["-h", "-o", "%P"]).output().await?;
         if !out.status.success() {
-            anyhow::bail!("Exit Status not 0 for pause_job. real: {}", out.status)
+            anyhow::bail!("Exit Status not 0 for sinfo. real: {}", out.status)
         }
-        Ok(())
-    }
-
-    async fn continue_job(&self, job_id: &str) -> anyhow::Result<()> {
-        let out = self.ssh_proxy.command("scontrol").args(["resume", job_id]).output().await?;
-        if !out.status.success() {
-            anyhow::bail!("Exit Status not 0 for continue_job. real: {}", out.status)
+        let sinfo_out = String::from_utf8(out.stdout)?;
+        let partitions = parse_partitions(&sinfo_out);
+        match partition {
+            Some(partition) => {
+                if !partitions.iter().any(|el| el.eq(partition)) {
+                    anyhow::bail!(
+                        "Partition {partition} is not listed by sinfo. Available partitions: {}",
+                        partitions.join(", ")
+                    )
+                }
+                Ok(partition.to_string())
+            }
+            None => partitions.into_iter().next().with_context(|| {
+                format!("Unable to get partition from sinfo -h. stdout: {sinfo_out}")
+            }),
         }
-        Ok(())
     }
-}
 
-impl SlurmClient {
-    fn gen_script(base_path: &str, include_env: &str, script_info: ScriptInfo) -> String {
+    fn gen_script(
+        base_path: &str,
+        include_env: &str,
+        partition: Option<&str>,
+        script_info: ScriptInfo,
+    ) -> String {
         let header = "#!/bin/bash";
         let id = script_info.id.clone();
         let env: Vec<String> = script_info
@@ -292,6 +320,11 @@ impl SlurmClient {
             }
             StdInKind::Unknown => script,
         };
+        let script = script_info.launcher.command_line(
+            &script,
+            "$SLURM_NPROCS",
+            "${SLURM_CPUS_PER_TASK:-1}",
+        );
         let load_software = script_info.load_software;
         let resource_header = match script_info.requirements {
             None => String::default(),
@@ -328,29 +361,55 @@ impl SlurmClient {
                     None => String::default(),
                 }
                 .as_str();
+                if let Some(account) = x.account {
+                    header += format!("#SBATCH --account={account}\n").as_str();
+                }
+                if let Some(qos) = x.qos {
+                    header += format!("#SBATCH --qos={qos}\n").as_str();
+                }
+                if let Some(memory) = x.memory_per_node {
+                    header += format!("#SBATCH --mem={memory}M\n").as_str();
+                }
+                if let Some(gpus) = x.gpus_per_node {
+                    header += format!("#SBATCH --gres=gpu:{gpus}\n").as_str();
+                }
+                if x.exclusive {
+                    header += "#SBATCH --exclusive\n";
+                }
                 header
             }
         };
+        let partition_header = match partition {
+            Some(partition) => format!("#SBATCH --partition={partition}"),
+            None => String::default(),
+        };
         formatdoc! {r#"
             {header}
             #SBATCH --output={base_path}/{id}/STDOUT
             #SBATCH --error={base_path}/{id}/STDERR
-            cd $SLURM_SUBMIT_DIR
+            {partition_header}
             {resource_header}
+            cd $SLURM_SUBMIT_DIR
             {env_string}
             {include_env}
             {load_software}
-            mpirun -np $SLURM_NPROCS {script}
+            {script}
             ec=$?
             {touch}
             exit $ec
         "#}
     }
 
-    pub fn new(base_path: String, include_env: String, ssh_proxy: Arc<SshProxy>) -> Self {
+    pub fn new(
+        base_path: String,
+        include_env: String,
+        queue: Option<String>,
+        ssh_proxy: Arc<SshProxy>,
+    ) -> Self {
         Self {
             base_path,
             include_env,
+            queue,
             ssh_proxy,
         }
     }
@@ -365,3 +424,96 @@ fn parse_time(time: &str) -> i64 {
         Err(_) => 0,
     }
 }
+
+/// 解析 `sinfo -h -o %P` 的输出，默认分区带有 `*` 后缀
+fn parse_partitions(sinfo_out: &str) -> Vec<String> {
+    let mut partitions = Vec::<String>::new();
+    for partition in sinfo_out.lines().map(|el| el.trim().trim_end_matches('*')) {
+        if !partition.is_empty() && !partitions.iter().any(|el| el.eq(partition)) {
+            partitions.push(partition.to_string());
+        }
+    }
+    partitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::model::entity::task::{Launcher, Requirements};
+
+    #[test]
+    fn test_parse_partitions() {
+        assert_eq!(
+            parse_partitions("debug*\ncompute\ncompute\ngpu\n"),
+            vec!["debug", "compute", "gpu"]
+        );
+        assert!(parse_partitions("").is_empty());
+    }
+
+    #[test]
+    fn test_gen_script_directives() {
+        let script_info = ScriptInfo {
+            id: "task".to_string(),
+            name: "vasp".to_string(),
+            requirements: Some(Requirements {
+                cpu_cores: Some(8),
+                node_count: Some(2),
+                account: Some("chem".to_string()),
+                qos: Some("high".to_string()),
+                memory_per_node: Some(4096),
+                gpus_per_node: Some(2),
+                exclusive: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let script = SlurmClient::gen_script("/tmp", "", Some("gpu"), script_info);
+        for directive in [
+            "#SBATCH --partition=gpu",
+            "#SBATCH --nodes=2",
+            "#SBATCH --ntasks-per-node=8",
+            "#SBATCH --account=chem",
+            "#SBATCH --qos=high",
+            "#SBATCH --mem=4096M",
+            "#SBATCH --gres=gpu:2",
+            "#SBATCH --exclusive",
+        ] {
+            assert!(
+                script.contains(directive),
+                "{directive} is missing in {script}"
+            );
+        }
+        // sbatch 忽略第一条命令之后的 #SBATCH 指令
+        let first_command =
+            script.lines().position(|el| !el.is_empty() && !el.starts_with('#')).unwrap();
+        assert!(
+            script.lines().skip(first_command).all(|el| !el.starts_with("#SBATCH")),
+            "#SBATCH directives after the first command in {script}"
+        );
+    }
+
+    #[test]
+    fn test_gen_script_launcher() {
+        let gen_script = |launcher: Launcher| {
+            let script_info = ScriptInfo {
+                name: "vasp".to_string(),
+                arguments: vec!["-v".to_string()],
+                launcher,
+                ..Default::default()
+            };
+            SlurmClient::gen_script("/tmp", "", None, script_info)
+        };
+        assert!(gen_script(Launcher::default()).contains("\nmpirun -np $SLURM_NPROCS vasp -v\n"));
+        assert!(gen_script(Launcher::None).contains("\nvasp -v\n"));
+        assert!(gen_script(Launcher::Srun).contains("\nsrun -n $SLURM_NPROCS vasp -v\n"));
+        assert!(gen_script(Launcher::Mpiexec).contains("\nmpiexec -n $SLURM_NPROCS vasp -v\n"));
+        let script = gen_script(Launcher::Custom(
+            "OMP_NUM_THREADS={{threads}} mpiexec.hydra -n {{ranks}}".to_string(),
+        ));
+        assert!(script.contains(
+            "\nOMP_NUM_THREADS=${SLURM_CPUS_PER_TASK:-1} mpiexec.hydra -n $SLURM_NPROCS vasp -v\n"
+        ));
+        let script = gen_script(Launcher::Custom("time {{command}} > log".to_string()));
+        assert!(script.contains("\ntime vasp -v > log\n"));
+    }
+}
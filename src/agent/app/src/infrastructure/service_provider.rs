@@ -18,7 +18,7 @@ use super::{
     resource::ResourceStat,
     service::{
         file_load_service::FileLoadServiceImpl,
-        job_schedulers::{PBSClient, SlurmClient},
+        job_schedulers::{LocalClient, LsfClient, PBSClient, SgeClient, SlurmClient},
         software_deployers::{apptainer::ApptainerDeployer, spack::SpackDeployer},
     },
     ssh_proxy::SshProxy,
@@ -67,8 +67,11 @@ build_container! {
                 agent_config.include_env_script.clone()
             };
             let result: Arc<dyn JobSchedulerService> = match agent_config.scheduler.r#type.to_lowercase().as_str() {
-                "pbs" => Arc::new(PBSClient::new(agent_config.save_path.clone(), include_env, ssh_proxy.clone())),
-                "slurm" => Arc::new(SlurmClient::new(agent_config.save_path.clone(), include_env, ssh_proxy.clone())),
+                "pbs" => Arc::new(PBSClient::new(agent_config.save_path.clone(), include_env, agent_config.scheduler.queue.clone(), ssh_proxy.clone())),
+                "slurm" => Arc::new(SlurmClient::new(agent_config.save_path.clone(), include_env, agent_config.scheduler.queue.clone(), ssh_proxy.clone())),
+                "sge" => Arc::new(SgeClient::new(agent_config.save_path.clone(), include_env, agent_config.scheduler.queue.clone(), agent_config.scheduler.parallel_environment.clone(), ssh_proxy.clone())),
+                "lsf" => Arc::new(LsfClient::new(agent_config.save_path.clone(), include_env, agent_config.scheduler.queue.clone(), ssh_proxy.clone())),
+                "local" => Arc::new(LocalClient::new(agent_config.save_path.clone(), include_env, agent_config.scheduler.cgroup.clone())),
                 _ => {
                     anyhow::bail!("job.scheduler.type hasn't been configured.")
                 }
@@ -125,6 +128,17 @@ build_container! {
             deployers
         }
     }
+    file_load_service: Arc<dyn FileLoadService> {
+        build {
+            Arc::new(
+                FileLoadServiceImpl::new(
+                    agent_config.save_path.clone(),
+                    http_client.clone(),
+                    agent_config.upload_base_url.clone(),
+                    agent_config.ssh_proxy.clone(),
+            ))
+        }
+    }
     run_task_service: Arc<dyn RunJobService> {
         build {
             Arc::new(RunJobServiceImpl::new(
@@ -134,7 +148,8 @@ build_container! {
                 download_sender.clone(),
                 upload_sender.clone(),
                 sub_task_report_service.clone(),
-                deployers.clone()
+                deployers.clone(),
+                file_load_service.clone()
             ))
         }
     }
@@ -148,17 +163,6 @@ build_container! {
             Arc::new(SoftwareDeploymentRunner::new(deploy_sender.get_receiver(), deploy_software_service.clone()))
         }
     }
-    file_load_service: Arc<dyn FileLoadService> {
-        build {
-            Arc::new(
-                FileLoadServiceImpl::new(
-                    agent_config.save_path.clone(),
-                    http_client.clone(),
-                    agent_config.upload_base_url.clone(),
-                    agent_config.ssh_proxy.clone(),
-            ))
-        }
-    }
     collection_task_service: Arc<CollectionTaskServiceImpl> {
         build {
             Arc::new(CollectionTaskServiceImpl::new(repository.clone(), sub_task_report_service.clone(), file_load_service.clone()))
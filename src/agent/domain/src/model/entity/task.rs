@@ -57,6 +57,18 @@ pub enum TaskType {
         std_in: StdInKind,
         /// 文件信息列表
         files: Vec<FileInfo>,
+        /// 并行启动器
+        #[serde(default)]
+        launcher: Launcher,
+        /// 标准输出的校验规则
+        #[serde(default)]
+        std_out_validator: Option<OutValidator>,
+        /// 标准错误输出的校验规则
+        #[serde(default)]
+        std_err_validator: Option<OutValidator>,
+        /// 输出文件的校验规则，键为输出文件路径
+        #[serde(default)]
+        validators: HashMap<String, OutValidator>,
     },
     /// 输出收集
     CollectedOut {
@@ -68,6 +80,20 @@ pub enum TaskType {
         to: CollectTo,
         /// 如果收集不到是否报错（true 时不报错）
         optional: bool,
+        /// 收集结果的校验规则
+        #[serde(default)]
+        validators: Vec<OutValidator>,
+    },
+    /// 脚本执行
+    ExecuteScript {
+        /// 脚本类型
+        kind: ScriptKind,
+        /// 脚本来源
+        origin: ScriptOrigin,
+        /// 文件信息列表
+        files: Vec<FileInfo>,
+        /// 输出文件的校验规则，键为输出文件路径
+        validators: HashMap<String, OutValidator>,
     },
     #[default]
     Unknown,
@@ -86,6 +112,43 @@ pub struct Requirements {
     pub max_cpu_time: Option<usize>,
     /// 定时终止（utc 0 时区 时间戳）
     pub stop_time: Option<usize>,
+    /// 分区（Slurm）或队列（PBS），未指定时使用 agent 配置的默认队列
+    pub partition: Option<String>,
+    /// 记账账户
+    pub account: Option<String>,
+    /// 服务质量（QOS）
+    pub qos: Option<String>,
+    /// 每个节点的内存（MB）
+    pub memory_per_node: Option<usize>,
+    /// 每个节点的 GPU 数
+    pub gpus_per_node: Option<usize>,
+    /// 是否独占节点
+    #[serde(default)]
+    pub exclusive: bool,
+}
+
+impl Requirements {
+    /// 校验分区、记账账户与 QOS 名称
+    ///
+    /// 它们会原样写入作业脚本的调度指令，只接受 `[A-Za-z0-9_.-]+`，
+    /// 避免换行等字符注入额外的指令或命令。
+    pub fn validate_scheduler_names(&self) -> anyhow::Result<()> {
+        for (kind, value) in [
+            ("partition", &self.partition),
+            ("account", &self.account),
+            ("qos", &self.qos),
+        ] {
+            let Some(value) = value else {
+                continue;
+            };
+            if value.is_empty()
+                || !value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+            {
+                anyhow::bail!("Invalid {kind} name {value:?}, only [A-Za-z0-9_.-] is allowed.");
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
@@ -142,6 +205,209 @@ pub enum StdInKind {
     Unknown,
 }
 
+/// 并行启动器
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub enum Launcher {
+    /// 直接执行，适用于串行或仅使用 OpenMP 的程序
+    None,
+    /// mpirun
+    #[default]
+    Mpirun,
+    /// srun
+    Srun,
+    /// mpiexec
+    Mpiexec,
+    /// 自定义启动命令模板，支持 `{{ranks}}`、`{{threads}}` 与 `{{command}}` 占位符
+    Custom(String),
+}
+
+impl Launcher {
+    /// 生成作业脚本中的启动命令
+    /// `ranks` 与 `threads` 为作业脚本中表示进程数与每进程线程数的表达式
+    pub fn command_line(&self, command: &str, ranks: &str, threads: &str) -> String {
+        match self {
+            Launcher::None => command.to_string(),
+            Launcher::Mpirun => format!("mpirun -np {ranks} {command}"),
+            Launcher::Srun => format!("srun -n {ranks} {command}"),
+            Launcher::Mpiexec => format!("mpiexec -n {ranks} {command}"),
+            Launcher::Custom(template) => {
+                let template = template.replace("{{ranks}}", ranks).replace("{{threads}}", threads);
+                if template.contains("{{command}}") {
+                    template.replace("{{command}}", command)
+                } else {
+                    format!("{template} {command}")
+                }
+            }
+        }
+    }
+}
+
+/// 脚本类型
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum ScriptKind {
+    /// Python 脚本
+    Python {
+        /// 使用的 venv 或 conda 环境目录，为空时使用 PATH 中的 python3
+        prefix: Option<String>,
+    },
+}
+
+impl ScriptKind {
+    /// 运行脚本的命令，环境目录由用户提供，已按 shell 参数转义
+    pub fn interpreter(&self) -> String {
+        match self {
+            ScriptKind::Python {
+                prefix: Some(prefix),
+            } => shell_quote(&format!("{}/bin/python", prefix.trim_end_matches('/'))),
+            ScriptKind::Python { prefix: None } => "python3".to_string(),
+        }
+    }
+}
+
+/// 脚本来源
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum ScriptOrigin {
+    /// 从 git 仓库拉取，`path` 为脚本在仓库中的路径
+    Git { url: String, path: String },
+    /// 脚本内容
+    Edit { content: String },
+}
+
+impl ScriptOrigin {
+    /// 编辑的脚本保存的文件名
+    pub const EDITED_SCRIPT: &'static str = "co_script.py";
+    /// git 仓库克隆到的目录
+    pub const CLONE_DIR: &'static str = "co_script";
+
+    /// 解析形如 `<仓库链接>#<路径>` 的链接
+    pub fn git(url: &str) -> Self {
+        let (url, path) = match url.rsplit_once('#') {
+            Some((url, path)) if !path.is_empty() => (url, path),
+            _ => (url.trim_end_matches('#'), "main.py"),
+        };
+        Self::Git {
+            url: url.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    /// 脚本在工作目录中的路径，已按 shell 参数转义
+    pub fn script_path(&self) -> String {
+        match self {
+            ScriptOrigin::Git { path, .. } => shell_quote(&format!("{}/{path}", Self::CLONE_DIR)),
+            ScriptOrigin::Edit { .. } => Self::EDITED_SCRIPT.to_string(),
+        }
+    }
+
+    /// 作业开始前获取脚本的命令，编辑的脚本作为输入文件写入，不需要额外命令
+    ///
+    /// 链接由用户提供，转义后放在 `--` 之后，避免被 shell 解释或被 git 当作选项。
+    pub fn fetch_command(&self) -> Option<String> {
+        match self {
+            ScriptOrigin::Git { url, .. } => Some(format!(
+                "rm -rf {dir} && git clone --depth 1 -- {url} {dir}",
+                url = shell_quote(url),
+                dir = Self::CLONE_DIR
+            )),
+            ScriptOrigin::Edit { .. } => None,
+        }
+    }
+}
+
+/// 用单引号包裹参数，使其在 shell 中按字面值传递
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 输出校验
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct OutValidator {
+    /// 校验规则
+    pub rule: ValidateRule,
+    /// 符合规则时是否报告成功
+    pub pass_is_success: bool,
+    /// 不符合规则时是否报告成功
+    pub failure_is_success: bool,
+}
+
+/// 校验规则
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum ValidateRule {
+    /// 匹配正则
+    Regex { exp: String },
+    /// 是否为空
+    IsEmpty { expected: bool },
+}
+
+impl OutValidator {
+    /// 校验内容，返回是否应报告成功
+    pub fn validate(&self, content: &str) -> anyhow::Result<bool> {
+        let passed = match &self.rule {
+            ValidateRule::Regex { exp } => regex::Regex::new(exp)?.is_match(content),
+            ValidateRule::IsEmpty { expected } => content.trim().is_empty() == *expected,
+        };
+        Ok(if passed {
+            self.pass_is_success
+        } else {
+            self.failure_is_success
+        })
+    }
+}
+
+impl std::fmt::Display for ValidateRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidateRule::Regex { exp } => write!(f, "regex `{exp}`"),
+            ValidateRule::IsEmpty { expected: true } => f.write_str("is empty"),
+            ValidateRule::IsEmpty { expected: false } => f.write_str("is not empty"),
+        }
+    }
+}
+
+impl TaskType {
+    /// 作业完成后需要校验的输出及其校验规则
+    pub fn output_validators(&self) -> Vec<(CollectFrom, OutValidator)> {
+        let (std_out_validator, std_err_validator, validators) = match self {
+            TaskType::UsecaseExecution {
+                std_out_validator,
+                std_err_validator,
+                validators,
+                ..
+            } => (
+                std_out_validator.as_ref(),
+                std_err_validator.as_ref(),
+                validators,
+            ),
+            TaskType::ExecuteScript { validators, .. } => (None, None, validators),
+            _ => return vec![],
+        };
+        let mut validators = validators
+            .iter()
+            .map(|(path, validator)| {
+                (
+                    CollectFrom::FileOut { path: path.clone() },
+                    validator.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        // 按路径排序，使每次报告的失败原因一致
+        validators.sort_by_key(|x| x.0.to_string());
+        validators.extend(std_out_validator.map(|x| (CollectFrom::Stdout, x.clone())));
+        validators.extend(std_err_validator.map(|x| (CollectFrom::Stderr, x.clone())));
+        validators
+    }
+}
+
+impl std::fmt::Display for CollectFrom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollectFrom::FileOut { path } => write!(f, "Output {path}"),
+            CollectFrom::Stdout => f.write_str("Standard output"),
+            CollectFrom::Stderr => f.write_str("Standard error output"),
+        }
+    }
+}
+
 #[derive(Default, Clone, Serialize, Deserialize, Debug)]
 /// 文件信息
 pub struct FileInfo {
@@ -208,4 +474,122 @@ pub struct TaskUsedResource {
     pub start_time: i64,
     /// 结束时间
     pub end_time: i64,
+    /// 作业实际所在的分区或队列，调度器不区分分区时为空
+    #[serde(default)]
+    pub partition: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{
+        CollectFrom, OutValidator, Requirements, ScriptKind, ScriptOrigin,
+        SoftwareDeploymentStatus, StdInKind, TaskType, ValidateRule,
+    };
+
+    fn validator(expected: bool) -> OutValidator {
+        OutValidator {
+            rule: ValidateRule::IsEmpty { expected },
+            pass_is_success: true,
+            failure_is_success: false,
+        }
+    }
+
+    fn sources(task_type: &TaskType) -> Vec<String> {
+        task_type.output_validators().iter().map(|(from, _)| from.to_string()).collect()
+    }
+
+    #[test]
+    fn test_usecase_output_validators() {
+        let task_type = TaskType::UsecaseExecution {
+            name: "vasp".to_string(),
+            arguments: vec![],
+            environments: HashMap::new(),
+            std_in: StdInKind::Unknown,
+            files: vec![],
+            launcher: Default::default(),
+            std_out_validator: Some(validator(false)),
+            std_err_validator: Some(validator(true)),
+            validators: HashMap::from([
+                ("OUTCAR".to_string(), validator(false)),
+                ("CONTCAR".to_string(), validator(false)),
+            ]),
+        };
+        assert_eq!(
+            sources(&task_type),
+            [
+                "Output CONTCAR",
+                "Output OUTCAR",
+                "Standard output",
+                "Standard error output"
+            ]
+        );
+        let (from, stderr) = task_type.output_validators().pop().unwrap();
+        assert!(matches!(from, CollectFrom::Stderr));
+        assert!(matches!(
+            stderr.rule,
+            ValidateRule::IsEmpty { expected: true }
+        ));
+    }
+
+    #[test]
+    fn test_script_and_other_output_validators() {
+        let task_type = TaskType::ExecuteScript {
+            kind: ScriptKind::Python { prefix: None },
+            origin: ScriptOrigin::Edit {
+                content: String::new(),
+            },
+            files: vec![],
+            validators: HashMap::from([("result.json".to_string(), validator(false))]),
+        };
+        assert_eq!(sources(&task_type), ["Output result.json"]);
+        let task_type = TaskType::SoftwareDeployment {
+            status: SoftwareDeploymentStatus::default(),
+        };
+        assert!(task_type.output_validators().is_empty());
+    }
+
+    #[test]
+    fn test_git_fetch_command_is_quoted() {
+        let origin = ScriptOrigin::git("https://example.com/a.git; touch pwned'#dir/$(id).py");
+        assert_eq!(
+            origin.fetch_command().unwrap(),
+            r"rm -rf co_script && git clone --depth 1 -- 'https://example.com/a.git; touch pwned'\''' co_script"
+        );
+        assert_eq!(origin.script_path(), "'co_script/dir/$(id).py'");
+    }
+
+    #[test]
+    fn test_validate_scheduler_names() {
+        let requirements = Requirements {
+            partition: Some("gpu-a100".to_string()),
+            account: Some("chem_01".to_string()),
+            qos: Some("high.1".to_string()),
+            ..Default::default()
+        };
+        assert!(requirements.validate_scheduler_names().is_ok());
+        for (partition, account, qos) in [
+            ("gpu\n#SBATCH --uid=0", "chem", "high"),
+            ("gpu", "chem; rm -rf ~", "high"),
+            ("gpu", "chem", ""),
+        ] {
+            let requirements = Requirements {
+                partition: Some(partition.to_string()),
+                account: Some(account.to_string()),
+                qos: Some(qos.to_string()),
+                ..Default::default()
+            };
+            assert!(requirements.validate_scheduler_names().is_err());
+        }
+    }
+
+    #[test]
+    fn test_python_prefix_is_quoted() {
+        let kind = ScriptKind::Python {
+            prefix: Some("/opt/envs/$(id); rm -rf ~/".to_string()),
+        };
+        assert_eq!(kind.interpreter(), "'/opt/envs/$(id); rm -rf ~/bin/python'");
+        assert_eq!(ScriptKind::Python { prefix: None }.interpreter(), "python3");
+    }
 }
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-use crate::model::entity::task::{Requirements, StdInKind, TaskUsedResource};
+use crate::model::entity::task::{Launcher, Requirements, StdInKind, TaskUsedResource};
 
 #[derive(Default, Deserialize, Serialize, Debug, Clone, Ord, Eq, PartialOrd)]
 pub struct Job {
@@ -10,6 +10,8 @@ pub struct Job {
     pub name: String,
     pub owner: String,
     pub state: JobState,
+    /// 调度器报告的原始作业状态
+    pub scheduler_state: String,
     pub exit_status_code: i32,
     pub error_output: String,
     pub resource_used: TaskUsedResource,
@@ -48,4 +50,5 @@ pub struct ScriptInfo {
     pub environments: HashMap<String, String>,
     pub std_in: StdInKind,
     pub requirements: Option<Requirements>,
+    pub launcher: Launcher,
 }
@@ -15,7 +15,9 @@ impl From<TaskType> for TaskDisplayType {
     fn from(f: TaskType) -> Self {
         match f {
             TaskType::SoftwareDeployment { .. } => Self::SoftwareDeployment,
-            TaskType::UsecaseExecution { .. } => Self::UsecaseExecution,
+            TaskType::UsecaseExecution { .. } | TaskType::ExecuteScript { .. } => {
+                Self::UsecaseExecution
+            }
             TaskType::CollectedOut { .. } => Self::CollectedOut,
             TaskType::Unknown => Self::Unknown,
         }
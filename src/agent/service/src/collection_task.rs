@@ -1,7 +1,7 @@
 use domain::{
     model::{
         entity::{
-            task::{CollectRule, TaskStatus, TaskType},
+            task::{CollectFrom, CollectRule, OutValidator, TaskStatus, TaskType},
             SubTask,
         },
         vo::TaskDisplayType,
@@ -87,6 +87,7 @@ impl CollectionTaskServiceImpl {
                 rule,
                 to,
                 optional,
+                validators,
             } => {
                 let input = match self
                     .file_load_service
@@ -102,20 +103,7 @@ impl CollectionTaskServiceImpl {
                         }
                     }
                 };
-                let output = match rule {
-                    CollectRule::Regex { exp } => regex::Regex::new(exp.as_str())?
-                        .captures_iter(input.as_str())
-                        .filter_map(|x| x.get(0).map(|x| x.as_str()))
-                        .collect::<Vec<&str>>()
-                        .join("\n"),
-                    CollectRule::BottomLines { n } => {
-                        let lines = input.lines().count();
-                        input.lines().skip(lines - n).take(n).collect::<Vec<&str>>().join("\n")
-                    }
-                    CollectRule::TopLines { n } => {
-                        input.lines().take(n).collect::<Vec<&str>>().join("\n")
-                    }
-                };
+                let output = collect_output(&from, &input, &rule, &validators)?;
                 self.file_load_service
                     .save_file(sub_task.parent_id, output.as_str(), &to)
                     .await?;
@@ -125,3 +113,79 @@ impl CollectionTaskServiceImpl {
         Ok(())
     }
 }
+
+/// 按收集规则从内容中收集输出，并依次校验，任一校验未通过时返回错误
+fn collect_output(
+    from: &CollectFrom,
+    input: &str,
+    rule: &CollectRule,
+    validators: &[OutValidator],
+) -> anyhow::Result<String> {
+    let output = match rule {
+        CollectRule::Regex { exp } => regex::Regex::new(exp.as_str())?
+            .captures_iter(input)
+            .filter_map(|x| x.get(0).map(|x| x.as_str()))
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        CollectRule::BottomLines { n } => {
+            let lines = input.lines().count();
+            input.lines().skip(lines.saturating_sub(*n)).collect::<Vec<&str>>().join("\n")
+        }
+        CollectRule::TopLines { n } => input.lines().take(*n).collect::<Vec<&str>>().join("\n"),
+    };
+    for validator in validators.iter() {
+        if !validator.validate(&output)? {
+            anyhow::bail!(
+                "Collected {from} failed the validator ({}).",
+                validator.rule
+            )
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use domain::model::entity::task::{CollectFrom, CollectRule, OutValidator, ValidateRule};
+
+    use super::collect_output;
+
+    fn validator(exp: &str) -> OutValidator {
+        OutValidator {
+            rule: ValidateRule::Regex {
+                exp: exp.to_string(),
+            },
+            pass_is_success: true,
+            failure_is_success: false,
+        }
+    }
+
+    #[test]
+    fn test_collect_output_passes_validator() {
+        let rule = CollectRule::BottomLines { n: 1 };
+        let output = collect_output(
+            &CollectFrom::Stdout,
+            "step 1\nenergy = -1.5",
+            &rule,
+            &[validator(r"^energy")],
+        );
+        assert_eq!(output.unwrap(), "energy = -1.5");
+    }
+
+    #[test]
+    fn test_collect_output_fails_validator() {
+        let rule = CollectRule::TopLines { n: 1 };
+        let validators = [validator(".*"), validator(r"^energy")];
+        let err = collect_output(
+            &CollectFrom::Stdout,
+            "step 1\nenergy = -1.5",
+            &rule,
+            &validators,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Collected Standard output failed the validator (regex `^energy`)."
+        );
+    }
+}
@@ -6,7 +6,11 @@ use domain::{
     model::{
         entity::{
             file::{FileStatus, FileType},
-            task::{DeployerType, FacilityKind, TaskStatus, TaskType},
+            task::{
+                CollectFrom, DeployerType, FacilityKind, Launcher, OutValidator, StdInKind,
+                TaskStatus, TaskType,
+            },
+            SubTask,
         },
         vo::{
             job::{JobState, ScriptInfo},
@@ -15,7 +19,10 @@ use domain::{
     },
     repository::{IFileRepository, ISubTaskRepository},
     sender::{IDownloadSender, ISubTaskReportService, IUploadSender},
-    service::{JobSchedulerService, RunJobService, SoftwareDeployerService, SubTaskService},
+    service::{
+        FileLoadService, JobSchedulerService, RunJobService, SoftwareDeployerService,
+        SubTaskService,
+    },
 };
 
 pub struct RunJobServiceImpl {
@@ -26,6 +33,7 @@ pub struct RunJobServiceImpl {
     upload_sender: Arc<dyn IUploadSender + Send + Sync>,
     report_service: Arc<dyn ISubTaskReportService>,
     deployers: HashMap<DeployerType, Arc<dyn SoftwareDeployerService>>,
+    file_load_service: Arc<dyn FileLoadService>,
 }
 
 #[async_trait::async_trait]
@@ -139,10 +147,22 @@ impl SubTaskService for RunJobServiceImpl {
         if task.status == TaskStatus::Running {
             return Ok(());
         }
+        if task.status == TaskStatus::Completing {
+            let validators = task.task_type.output_validators();
+            if !validators.is_empty() {
+                if let Some(reason) = self.validate_outputs(&task, &validators).await? {
+                    task.status = TaskStatus::Failed;
+                    task.failed_reason = reason;
+                    self.task_repo.update(task).await?;
+                    self.task_repo.save_changed().await?;
+                    return self.report_service.report_failed_task(id).await;
+                }
+            }
+        }
         if task.status == TaskStatus::Failed {
             task.failed_reason = format!(
-                "Job exit with {}\nError Output:\n{}",
-                job.exit_status_code, job.error_output
+                "Scheduler state: {}\nJob exit with {}\nError Output:\n{}",
+                job.scheduler_state, job.exit_status_code, job.error_output
             );
             self.task_repo.update(task).await?;
             self.task_repo.save_changed().await?;
@@ -185,6 +205,7 @@ impl RunJobServiceImpl {
         upload_sender: Arc<dyn IUploadSender + Send + Sync>,
         report_service: Arc<dyn ISubTaskReportService>,
         deployers: HashMap<DeployerType, Arc<dyn SoftwareDeployerService>>,
+        file_load_service: Arc<dyn FileLoadService>,
     ) -> Self {
         Self {
             job_scheduler,
@@ -194,6 +215,7 @@ impl RunJobServiceImpl {
             upload_sender,
             report_service,
             deployers,
+            file_load_service,
         }
     }
     async fn internal_run_job(&self, id: &str) -> anyhow::Result<()> {
@@ -271,6 +293,7 @@ impl RunJobServiceImpl {
                     environments,
                     std_in,
                     name,
+                    launcher,
                     ..
                 } => {
                     let info = ScriptInfo {
@@ -284,6 +307,29 @@ impl RunJobServiceImpl {
                         parent_id: task.parent_id.to_string(),
                         requirements: task.requirements.clone(),
                         is_mpi_before_loader,
+                        launcher,
+                    };
+                    let job_id = self.job_scheduler.submit_job_script(info).await?;
+                    task.job_id = job_id;
+                    let _ = self.task_repo.update(task).await?;
+                }
+                TaskType::ExecuteScript { kind, origin, .. } => {
+                    let load_software = match origin.fetch_command() {
+                        Some(fetch) => format!("{fetch}\n{load_software}"),
+                        None => load_software,
+                    };
+                    let info = ScriptInfo {
+                        id: task.id.to_string(),
+                        name: kind.interpreter(),
+                        path: format!("{}/{}", task.parent_id.to_string().as_str(), "run.sh"),
+                        load_software,
+                        arguments: vec![origin.script_path()],
+                        environments: HashMap::new(),
+                        std_in: StdInKind::Unknown,
+                        parent_id: task.parent_id.to_string(),
+                        requirements: task.requirements.clone(),
+                        is_mpi_before_loader,
+                        launcher: Launcher::None,
                     };
                     let job_id = self.job_scheduler.submit_job_script(info).await?;
                     task.job_id = job_id;
@@ -295,6 +341,36 @@ impl RunJobServiceImpl {
         self.task_file_repo.save_changed().await?;
         Ok(())
     }
+    /// 校验作业的输出，返回第一个未通过的校验的原因，不存在的输出按空内容校验
+    async fn validate_outputs(
+        &self,
+        task: &SubTask,
+        validators: &[(CollectFrom, OutValidator)],
+    ) -> anyhow::Result<Option<String>> {
+        for (from, validator) in validators {
+            let content = match self
+                .file_load_service
+                .load_file(task.parent_id.to_string().as_str(), from)
+                .await
+            {
+                Ok(content) => content,
+                Err(e)
+                    if e.downcast_ref::<std::io::Error>()
+                        .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) =>
+                {
+                    String::new()
+                }
+                Err(e) => return Err(e.context(format!("Unable to load {from} for validation."))),
+            };
+            if !validator.validate(&content)? {
+                return Ok(Some(format!(
+                    "{from} failed the validator ({}).",
+                    validator.rule
+                )));
+            }
+        }
+        Ok(None)
+    }
     async fn internal_complete_job(&self, id: &str) -> anyhow::Result<()> {
         let can_run = self
             .task_file_repo
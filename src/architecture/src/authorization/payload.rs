@@ -6,16 +6,32 @@ pub struct UserInfo {
     pub user_id: String,
     pub preferred_username: String,
     pub resource_access: HashMap<String, ResourceAccessItem>,
+    /// 本服务在 Keycloak 中的客户端 id，管理员角色只在该客户端上认定
+    #[serde(default)]
+    pub client_id: Option<String>,
 }
 
 impl UserInfo {
-    pub fn new(payload: Payload) -> Self {
+    /// 管理员角色名
+    pub const ADMIN_ROLE: &'static str = "admin";
+
+    pub fn new(payload: Payload, client_id: Option<String>) -> Self {
         Self {
             user_id: payload.sub,
             preferred_username: payload.preferred_username,
             resource_access: payload.resource_access,
+            client_id,
         }
     }
+
+    /// 是否在本服务的客户端上拥有管理员角色，
+    /// 未配置客户端 id 时不认定任何管理员
+    pub fn is_admin(&self) -> bool {
+        self.client_id
+            .as_ref()
+            .and_then(|client_id| self.resource_access.get(client_id))
+            .is_some_and(|el| el.roles.iter().any(|role| role == Self::ADMIN_ROLE))
+    }
 }
 
 /// 携带用户信息的 Payload，
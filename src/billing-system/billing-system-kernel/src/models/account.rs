@@ -0,0 +1,14 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+impl alice_architecture::model::IAggregateRoot for Account {}
+
+/// 用户预付费账户
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct Account {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 余额
+    pub balance: Decimal,
+}
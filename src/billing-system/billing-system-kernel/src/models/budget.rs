@@ -0,0 +1,137 @@
+use hmac::{Hmac, Mac};
+use num_derive::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// 预算检查请求的签名时间戳请求头
+pub const BUDGET_CHECK_TIMESTAMP_HEADER: &str = "X-Kuintessence-Timestamp";
+/// 预算检查请求的签名请求头，值为 `sha256=` 加上签名的十六进制
+pub const BUDGET_CHECK_SIGNATURE_HEADER: &str = "X-Kuintessence-Signature";
+/// 预算检查请求的有效时间（秒），超过后拒绝，避免请求被重放
+pub const BUDGET_CHECK_MAX_AGE_SECS: i64 = 300;
+
+impl alice_architecture::model::IAggregateRoot for Budget {}
+
+/// 预算，限制用户或某个工作流实例累计的花费
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct Budget {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 预算范围
+    pub scope: BudgetScope,
+    /// 预算上限
+    pub limit: Decimal,
+    /// 已花费
+    pub spent: Decimal,
+    /// 超出预算时的处理方式
+    pub action: BudgetAction,
+}
+
+/// 预算范围
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+pub enum BudgetScope {
+    /// 用户的全部工作流
+    #[default]
+    User,
+    /// 单个工作流实例
+    Workflow(Uuid),
+}
+
+/// 超出预算时的处理方式
+#[derive(
+    FromPrimitive, ToPrimitive, Clone, Copy, Serialize, Deserialize, Default, Debug, PartialEq, Eq,
+)]
+pub enum BudgetAction {
+    /// 暂停工作流，充值或调整预算后可继续
+    #[default]
+    Pause,
+    /// 拒绝运行节点
+    Refuse,
+}
+
+/// 节点预计使用的资源，与计费公式中的 n_* 变量对应
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct ResourceEstimate {
+    pub cpu: u64,
+    pub memory: u64,
+    pub storage: u64,
+    pub cpu_time: u64,
+    pub wall_time: u64,
+}
+
+/// 节点分发前的预算检查结果
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum BudgetDecision {
+    /// 允许运行
+    Allowed,
+    /// 暂停工作流
+    Paused(String),
+    /// 拒绝运行
+    Refused(String),
+}
+
+/// 预算检查请求签名，即以与 co 共享的密钥对 `{timestamp}.{body}` 计算的 HMAC-SHA256 的十六进制
+///
+/// 预算检查由 co 在节点分发前调用，不携带用户令牌，以签名认证调用方
+pub fn budget_check_signature(secret: &str, timestamp: i64, body: &[u8]) -> anyhow::Result<String> {
+    Ok(hex::encode(
+        budget_check_mac(secret, timestamp, body)?.finalize().into_bytes(),
+    ))
+}
+
+/// 校验预算检查请求的签名与时间戳
+///
+/// # 参数
+///
+/// * `signature` - 签名请求头的值
+/// * `now` - 当前时间戳（秒）
+pub fn verify_budget_check(
+    secret: &str,
+    timestamp: i64,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+) -> anyhow::Result<()> {
+    if (now - timestamp).abs() > BUDGET_CHECK_MAX_AGE_SECS {
+        anyhow::bail!("Budget check request signed at {timestamp} is expired.");
+    }
+    let signature = signature.strip_prefix("sha256=").ok_or(anyhow::anyhow!(
+        "Budget check signature isn't a sha256 signature."
+    ))?;
+    budget_check_mac(secret, timestamp, body)?
+        .verify_slice(&hex::decode(signature)?)
+        .map_err(|_| anyhow::anyhow!("Budget check signature doesn't match."))
+}
+
+fn budget_check_mac(secret: &str, timestamp: i64, body: &[u8]) -> anyhow::Result<Hmac<Sha256>> {
+    if secret.is_empty() {
+        anyhow::bail!("Budget check secret isn't configured.");
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+    Ok(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_budget_check() {
+        // echo -n '1680000000.{}' | openssl dgst -sha256 -hmac secret
+        let signature = "2a02091ea43e6c03406451ee70ff733cc457a182f40a5951ff17ffcfdafd0235";
+        assert_eq!(
+            budget_check_signature("secret", 1680000000, b"{}").unwrap(),
+            signature
+        );
+        let signature = format!("sha256={signature}");
+        verify_budget_check("secret", 1680000000, b"{}", &signature, 1680000060).unwrap();
+        assert!(verify_budget_check("secret", 1680000000, b"{ }", &signature, 1680000060).is_err());
+        assert!(verify_budget_check("other", 1680000000, b"{}", &signature, 1680000060).is_err());
+        assert!(verify_budget_check("secret", 1680000000, b"{}", &signature, 1680001000).is_err());
+        assert!(verify_budget_check("", 1680000000, b"{}", &signature, 1680000060).is_err());
+    }
+}
@@ -1,5 +1,7 @@
+use crate::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 impl alice_architecture::model::IAggregateRoot for ClusterIdSettings {}
@@ -14,3 +16,31 @@ pub struct ClusterIdSettings {
     pub wall_time: Decimal,
     pub formula: String,
 }
+
+impl ClusterIdSettings {
+    /// 校验计费公式
+    pub fn validate(&self) -> anyhow::Result<PricingFormula> {
+        PricingFormula::parse(&self.formula)
+    }
+
+    /// 按集群的计费公式计算资源用量的价格明细
+    ///
+    /// # 参数
+    ///
+    /// * `usage` - 资源用量
+    pub fn evaluate(&self, usage: &ResourceEstimate) -> anyhow::Result<PriceBreakdown> {
+        let variables = BTreeMap::from([
+            ("n_cpu".to_string(), Decimal::from(usage.cpu)),
+            ("n_memory".to_string(), Decimal::from(usage.memory)),
+            ("n_storage".to_string(), Decimal::from(usage.storage)),
+            ("n_cpu_time".to_string(), Decimal::from(usage.cpu_time)),
+            ("n_wall_time".to_string(), Decimal::from(usage.wall_time)),
+            ("u_cpu".to_string(), self.cpu),
+            ("u_memory".to_string(), self.memory),
+            ("u_storage".to_string(), self.storage),
+            ("u_cpu_time".to_string(), self.cpu_time),
+            ("u_wall_time".to_string(), self.wall_time),
+        ]);
+        self.validate()?.evaluate(variables)
+    }
+}
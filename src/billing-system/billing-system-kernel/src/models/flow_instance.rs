@@ -4,8 +4,10 @@ use uuid::Uuid;
 
 impl IAggregateRoot for FlowInstance {}
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct FlowInstance {
     pub id: Uuid,
     pub user_id: Uuid,
+    /// 工作流实例已完成
+    pub finished: bool,
 }
@@ -1,4 +1,7 @@
-use serde::Serialize;
+use num_traits::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Serialize)]
 pub struct Formula {
@@ -9,3 +12,580 @@ pub struct Formula {
     pub p_wall_time: String,
     pub p_total: String,
 }
+
+/// 计费公式中可用的资源种类，`n_{kind}` 为用量，`u_{kind}` 为单价
+pub const RESOURCE_KINDS: [&str; 5] = ["cpu", "memory", "storage", "cpu_time", "wall_time"];
+
+/// 节点总价对应的价格项
+pub const TOTAL_PRICE_ITEM: &str = "p_node";
+
+/// 账单金额保留的小数位数，与数据库中价格字段的精度一致
+pub const PRICE_SCALE: u32 = 10;
+
+/// 经过校验的计费公式
+///
+/// 公式以 JSON 对象保存，键为 `p_*` 价格项，值为表达式。
+/// 表达式支持十进制数字、`+ - * /`、括号，变量 `n_*`、`u_*` 与其他价格项 `p_*`，
+/// 以及函数 `min`、`max`、`ceil`、`floor`、`round(x, dp)` 与阶梯计价
+/// `tiered(quantity, limit_1, rate_1, ..., limit_n, rate_n, rate_rest)`。
+/// 必须包含总价 `p_node`，价格项之间不能循环引用。
+#[derive(Clone, Debug)]
+pub struct PricingFormula {
+    /// 按依赖顺序排列的价格项
+    items: Vec<(String, String, Expr)>,
+}
+
+/// 带入数值后的价格明细，保存了重新计算所需的全部输入
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct PriceBreakdown {
+    /// 用量与单价
+    pub variables: BTreeMap<String, Decimal>,
+    /// 按计算顺序排列的价格项
+    pub items: Vec<LineItem>,
+    /// 总价
+    pub total: Decimal,
+}
+
+/// 价格项
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct LineItem {
+    pub name: String,
+    pub expression: String,
+    pub amount: Decimal,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Number(Decimal),
+    Variable(String),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Min,
+    Max,
+    Ceil,
+    Floor,
+    Round,
+    Tiered,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "ceil" => Self::Ceil,
+            "floor" => Self::Floor,
+            "round" => Self::Round,
+            "tiered" => Self::Tiered,
+            _ => return None,
+        })
+    }
+
+    /// 检查参数个数
+    fn check_arity(&self, name: &str, count: usize) -> anyhow::Result<()> {
+        let valid = match self {
+            Self::Min | Self::Max => count >= 2,
+            Self::Ceil | Self::Floor => count == 1,
+            Self::Round => count == 2,
+            Self::Tiered => count >= 2 && count.is_multiple_of(2),
+        };
+        if !valid {
+            anyhow::bail!("Function {name} can not take {count} arguments.");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(Decimal),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(source: &str) -> anyhow::Result<Vec<Token>> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let literal = chars[start..i].iter().collect::<String>();
+                let number = Decimal::from_str_exact(&literal)
+                    .map_err(|_| anyhow::anyhow!("Invalid number {literal} at {start}."))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => {
+                tokens.push(match c {
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    '*' => Token::Star,
+                    '/' => Token::Slash,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => anyhow::bail!("Unexpected character '{c}' at {i}."),
+                });
+                i += 1;
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// 递归下降解析器
+///
+/// expr    := term (('+' | '-') term)*
+/// term    := unary (('*' | '/') unary)*
+/// unary   := '-' unary | primary
+/// primary := number | ident | ident '(' expr (',' expr)* ')' | '(' expr ')'
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn parse(source: &str) -> anyhow::Result<Expr> {
+        let mut parser = Self {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let expr = parser.expr()?;
+        if let Some(token) = parser.peek() {
+            anyhow::bail!("Unexpected token {token:?}.");
+        }
+        Ok(expr)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => anyhow::bail!("Expected {expected:?}, found {token:?}."),
+            None => anyhow::bail!("Expected {expected:?}, found the end of the expression."),
+        }
+    }
+
+    fn expr(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Plus) => BinaryOp::Add,
+                Some(Token::Minus) => BinaryOp::Sub,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Star) => BinaryOp::Mul,
+                Some(Token::Slash) => BinaryOp::Div,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        if let Some(Token::Minus) = self.peek() {
+            self.next();
+            return Ok(Expr::Negate(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> anyhow::Result<Expr> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::Ident(name)) => {
+                if self.peek() != Some(&Token::LParen) {
+                    return Ok(Expr::Variable(name));
+                }
+                let function = Function::from_name(&name)
+                    .ok_or(anyhow::anyhow!("Unknown function {name}."))?;
+                self.next();
+                let mut args = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.next();
+                    args.push(self.expr()?);
+                }
+                self.expect(Token::RParen)?;
+                function.check_arity(&name, args.len())?;
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::LParen) => {
+                let expr = self.expr()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(token) => anyhow::bail!("Unexpected token {token:?}."),
+            None => anyhow::bail!("Unexpected end of the expression."),
+        }
+    }
+}
+
+impl Expr {
+    /// 表达式引用的变量
+    fn variables<'a>(&'a self, variables: &mut Vec<&'a str>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Variable(name) => variables.push(name),
+            Expr::Negate(expr) => expr.variables(variables),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.variables(variables);
+                rhs.variables(variables);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|el| el.variables(variables)),
+        }
+    }
+
+    fn evaluate(&self, variables: &BTreeMap<String, Decimal>) -> anyhow::Result<Decimal> {
+        let overflow = || anyhow::anyhow!("Arithmetic overflow.");
+        Ok(match self {
+            Expr::Number(number) => *number,
+            Expr::Variable(name) => {
+                *variables.get(name).ok_or(anyhow::anyhow!("Variable {name} is not defined."))?
+            }
+            Expr::Negate(expr) => -expr.evaluate(variables)?,
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(variables)?, rhs.evaluate(variables)?);
+                match op {
+                    BinaryOp::Add => lhs.checked_add(rhs).ok_or_else(overflow)?,
+                    BinaryOp::Sub => lhs.checked_sub(rhs).ok_or_else(overflow)?,
+                    BinaryOp::Mul => lhs.checked_mul(rhs).ok_or_else(overflow)?,
+                    BinaryOp::Div => {
+                        if rhs.is_zero() {
+                            anyhow::bail!("Division by zero.");
+                        }
+                        lhs.checked_div(rhs).ok_or_else(overflow)?
+                    }
+                }
+            }
+            Expr::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|el| el.evaluate(variables))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                match function {
+                    Function::Min => args.into_iter().min().unwrap_or_default(),
+                    Function::Max => args.into_iter().max().unwrap_or_default(),
+                    Function::Ceil => args[0].ceil(),
+                    Function::Floor => args[0].floor(),
+                    Function::Round => {
+                        let dp = match args[1].to_u32() {
+                            Some(dp) if args[1].fract().is_zero() && dp <= 28 => dp,
+                            _ => anyhow::bail!("Invalid number of decimal places {}.", args[1]),
+                        };
+                        args[0].round_dp_with_strategy(dp, RoundingStrategy::MidpointAwayFromZero)
+                    }
+                    Function::Tiered => tiered(&args)?,
+                }
+            }
+        })
+    }
+}
+
+/// 阶梯计价，`args` 为用量、各阶梯上限与单价，最后为超出所有阶梯部分的单价
+fn tiered(args: &[Decimal]) -> anyhow::Result<Decimal> {
+    let overflow = || anyhow::anyhow!("Arithmetic overflow.");
+    let quantity = args[0].max(Decimal::ZERO);
+    let mut total = Decimal::ZERO;
+    let mut floor = Decimal::ZERO;
+    for tier in args[1..args.len() - 1].chunks(2) {
+        let (limit, rate) = (tier[0], tier[1]);
+        if limit < floor {
+            anyhow::bail!("Tier limits must be ascending, {limit} follows {floor}.");
+        }
+        let portion = quantity.min(limit) - floor;
+        if portion.is_sign_positive() && !portion.is_zero() {
+            total = portion
+                .checked_mul(rate)
+                .and_then(|el| total.checked_add(el))
+                .ok_or_else(overflow)?;
+        }
+        floor = limit;
+    }
+    if quantity > floor {
+        let rate = args[args.len() - 1];
+        total = (quantity - floor)
+            .checked_mul(rate)
+            .and_then(|el| total.checked_add(el))
+            .ok_or_else(overflow)?;
+    }
+    Ok(total)
+}
+
+impl PricingFormula {
+    /// 解析并校验计费公式
+    ///
+    /// # 参数
+    ///
+    /// * `source` - JSON 对象，键为价格项，值为表达式
+    pub fn parse(source: &str) -> anyhow::Result<Self> {
+        let source = serde_json::from_str::<BTreeMap<String, String>>(source)
+            .map_err(|e| anyhow::anyhow!("Formula must be a JSON object of strings: {e}"))?;
+        if !source.contains_key(TOTAL_PRICE_ITEM) {
+            anyhow::bail!("Formula has no {TOTAL_PRICE_ITEM}.");
+        }
+        let mut parsed = BTreeMap::new();
+        for (name, expression) in source.iter() {
+            if !name.starts_with("p_") {
+                anyhow::bail!("Price item {name} must start with 'p_'.");
+            }
+            let expr = Parser::parse(expression)
+                .map_err(|e| anyhow::anyhow!("Invalid expression of {name}: {e}"))?;
+            let mut variables = vec![];
+            expr.variables(&mut variables);
+            for variable in variables {
+                let known = match variable.split_once('_') {
+                    Some(("n" | "u", kind)) => RESOURCE_KINDS.contains(&kind),
+                    Some(("p", _)) => source.contains_key(variable),
+                    _ => false,
+                };
+                if !known {
+                    anyhow::bail!("Unknown variable {variable} in {name}.");
+                }
+            }
+            parsed.insert(name.to_owned(), (expression.to_owned(), expr));
+        }
+
+        // 按依赖顺序排列价格项，同时检查循环引用
+        let mut items = vec![];
+        let mut visiting = vec![];
+        fn visit(
+            name: &str,
+            parsed: &BTreeMap<String, (String, Expr)>,
+            visiting: &mut Vec<String>,
+            items: &mut Vec<(String, String, Expr)>,
+        ) -> anyhow::Result<()> {
+            if items.iter().any(|(el, _, _)| el == name) {
+                return Ok(());
+            }
+            if visiting.iter().any(|el| el == name) {
+                anyhow::bail!("Price item {name} references itself.");
+            }
+            visiting.push(name.to_owned());
+            let (expression, expr) = &parsed[name];
+            let mut variables = vec![];
+            expr.variables(&mut variables);
+            for variable in variables.into_iter().filter(|el| el.starts_with("p_")) {
+                visit(variable, parsed, visiting, items)?;
+            }
+            visiting.pop();
+            items.push((name.to_owned(), expression.to_owned(), expr.to_owned()));
+            Ok(())
+        }
+        for name in parsed.keys() {
+            visit(name, &parsed, &mut visiting, &mut items)?;
+        }
+        Ok(Self { items })
+    }
+
+    /// 计算价格明细
+    ///
+    /// # 参数
+    ///
+    /// * `variables` - `n_*` 用量与 `u_*` 单价
+    pub fn evaluate(&self, variables: BTreeMap<String, Decimal>) -> anyhow::Result<PriceBreakdown> {
+        let mut context = variables.to_owned();
+        let mut items = vec![];
+        for (name, expression, expr) in self.items.iter() {
+            let amount = expr
+                .evaluate(&context)
+                .map_err(|e| anyhow::anyhow!("Failed to evaluate {name}: {e}"))?
+                .normalize();
+            context.insert(name.to_owned(), amount);
+            items.push(LineItem {
+                name: name.to_owned(),
+                expression: expression.to_owned(),
+                amount,
+            });
+        }
+        Ok(PriceBreakdown {
+            total: context[TOTAL_PRICE_ITEM],
+            variables,
+            items,
+        })
+    }
+}
+
+impl PriceBreakdown {
+    /// 按明细中的输入重新计算，用于核对账单
+    ///
+    /// # 参数
+    ///
+    /// * `formula` - 计费公式
+    pub fn reproduce(&self, formula: &str) -> anyhow::Result<Self> {
+        PricingFormula::parse(formula)?.evaluate(self.variables.to_owned())
+    }
+
+    /// 按账单精度舍入的总价
+    pub fn price(&self) -> Decimal {
+        self.total
+            .round_dp_with_strategy(PRICE_SCALE, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    /// 各价格项金额
+    pub fn amounts(&self) -> HashMap<&str, Decimal> {
+        self.items.iter().map(|el| (el.name.as_str(), el.amount)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn dec(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn variables(pairs: &[(&str, &str)]) -> BTreeMap<String, Decimal> {
+        pairs.iter().map(|(k, v)| (k.to_string(), dec(v))).collect()
+    }
+
+    #[test]
+    fn test_exact_decimal_evaluation() {
+        let formula = PricingFormula::parse(
+            &json!({
+                "p_cpu": "n_cpu * u_cpu",
+                "p_wall_time": "ceil(n_wall_time / 3600) * u_wall_time",
+                "p_node": "p_cpu + p_wall_time",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let breakdown = formula
+            .evaluate(variables(&[
+                ("n_cpu", "3"),
+                ("u_cpu", "0.1"),
+                ("n_wall_time", "3601"),
+                ("u_wall_time", "0.0000000001"),
+            ]))
+            .unwrap();
+        let amounts = breakdown.amounts();
+        assert_eq!(amounts["p_cpu"], dec("0.3"));
+        assert_eq!(amounts["p_wall_time"], dec("0.0000000002"));
+        assert_eq!(breakdown.total, dec("0.3000000002"));
+        // 被引用的价格项先于引用者计算
+        assert_eq!(breakdown.items.last().unwrap().name, "p_node");
+    }
+
+    #[test]
+    fn test_functions() {
+        let formula = PricingFormula::parse(
+            &json!({
+                "p_cpu": "tiered(n_cpu, 10, 1, 20, 0.5, 0.25)",
+                "p_memory": "max(min(n_memory, 8), 2) * -u_memory",
+                "p_node": "round(p_cpu + p_memory + floor(1.9), 1)",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let breakdown = formula
+            .evaluate(variables(&[
+                ("n_cpu", "25"),
+                ("n_memory", "1"),
+                ("u_memory", "0.125"),
+            ]))
+            .unwrap();
+        let amounts = breakdown.amounts();
+        // 10 * 1 + 10 * 0.5 + 5 * 0.25
+        assert_eq!(amounts["p_cpu"], dec("16.25"));
+        assert_eq!(amounts["p_memory"], dec("-0.25"));
+        assert_eq!(breakdown.total, dec("17.0"));
+    }
+
+    #[test]
+    fn test_invalid_formulas_are_rejected() {
+        let invalid = [
+            json!({ "p_cpu": "n_cpu * u_cpu" }),
+            json!({ "p_node": "n_gpu * u_cpu" }),
+            json!({ "p_node": "n_cpu * " }),
+            json!({ "p_node": "pow(n_cpu, 2)" }),
+            json!({ "p_node": "tiered(n_cpu, 10, 1)" }),
+            json!({ "p_node": "p_cpu", "p_cpu": "p_node" }),
+            json!({ "p_node": "n_cpu # 2" }),
+            json!({ "cpu": "n_cpu", "p_node": "1" }),
+            json!({ "p_node": 1 }),
+        ];
+        for formula in invalid {
+            assert!(
+                PricingFormula::parse(&formula.to_string()).is_err(),
+                "{formula}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluation_errors() {
+        let formula =
+            PricingFormula::parse(&json!({ "p_node": "u_cpu / n_cpu" }).to_string()).unwrap();
+        assert!(formula.evaluate(variables(&[("u_cpu", "1"), ("n_cpu", "0")])).is_err());
+        let formula = PricingFormula::parse(
+            &json!({ "p_node": "tiered(n_cpu, 10, 1, 5, 1, 1)" }).to_string(),
+        )
+        .unwrap();
+        assert!(formula.evaluate(variables(&[("n_cpu", "1")])).is_err());
+    }
+
+    #[test]
+    fn test_reproduce() {
+        let formula = json!({ "p_node": "n_cpu * u_cpu" }).to_string();
+        let breakdown = PricingFormula::parse(&formula)
+            .unwrap()
+            .evaluate(variables(&[("n_cpu", "2"), ("u_cpu", "1.5")]))
+            .unwrap();
+        let serialized = serde_json::to_string(&breakdown).unwrap();
+        let breakdown: PriceBreakdown = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(breakdown.reproduce(&formula).unwrap(), breakdown);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::prelude::*;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Write};
+use uuid::Uuid;
+
+/// 无法归入资源种类的金额，包括早于价格明细功能的账单与公式中的附加费用
+pub const OTHER_RESOURCE: &str = "other";
+
+impl alice_architecture::model::IAggregateRoot for Invoice {}
+
+/// 节点账单及其所属的用户、集群与记账时间
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct BillingRecord {
+    pub user_id: Uuid,
+    pub cluster_id: Uuid,
+    pub created_time: DateTime<Utc>,
+    pub bill: NodeInstanceBilling,
+}
+
+/// 对账单范围
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+pub enum StatementScope {
+    /// 用户的全部工作流
+    #[default]
+    User,
+    /// 单个工作流实例，即一个项目
+    Workflow(Uuid),
+}
+
+/// 对账单中按集群与资源种类汇总的一行
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct StatementLine {
+    pub cluster_id: Uuid,
+    /// 资源种类，见 [`RESOURCE_KINDS`] 与 [`OTHER_RESOURCE`]
+    pub resource: String,
+    /// 用量
+    pub quantity: i64,
+    pub amount: Decimal,
+}
+
+/// 账期内的对账单
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct Statement {
+    pub user_id: Uuid,
+    pub scope: StatementScope,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// 计入的节点账单数
+    pub node_count: i64,
+    pub lines: Vec<StatementLine>,
+    pub total: Decimal,
+}
+
+/// 账期关闭后开具的发票，开具后不再随计费公式或账单变化
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct Invoice {
+    pub id: Uuid,
+    pub statement: Statement,
+    pub issued_time: DateTime<Utc>,
+}
+
+/// 自然月账期 `[start, end)`，按 UTC 划分
+///
+/// # 参数
+///
+/// * `year` - 年
+/// * `month` - 月，从 1 开始
+pub fn month_period(year: i32, month: u32) -> anyhow::Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let first_day = |year: i32, month: u32| {
+        NaiveDate::from_ymd_opt(year, month, 1)
+            .and_then(|el| el.and_hms_opt(0, 0, 0))
+            .map(|el| el.and_utc())
+            .ok_or(anyhow::anyhow!("Invalid billing period {year}-{month}."))
+    };
+    let start = first_day(year, month)?;
+    let end = if month == 12 {
+        first_day(year + 1, 1)?
+    } else {
+        first_day(year, month + 1)?
+    };
+    Ok((start, end))
+}
+
+/// 节点账单中各资源种类的金额
+///
+/// 按计费时段的权重累加各时段明细中的 `p_{kind}` 价格项，
+/// 总价与各资源金额之差计入 [`OTHER_RESOURCE`]，保证各项之和等于账单总价。
+fn resource_amounts(bill: &NodeInstanceBilling) -> Vec<(&'static str, i64, Decimal)> {
+    let item_amount = |breakdown: &PriceBreakdown, kind: &str| {
+        let name = format!("p_{kind}");
+        breakdown.items.iter().find(|el| el.name == name).map(|el| el.amount)
+    };
+    let quantities = [
+        bill.cpu,
+        bill.memory,
+        bill.storage,
+        bill.cpu_time,
+        bill.wall_time,
+    ];
+    let mut result = vec![];
+    let mut allocated = Decimal::ZERO;
+    for (kind, quantity) in RESOURCE_KINDS.into_iter().zip(quantities) {
+        let amount = if bill.segments.is_empty() {
+            item_amount(&bill.breakdown, kind)
+        } else {
+            bill.segments
+                .iter()
+                .map(|el| item_amount(&el.breakdown, kind).map(|amount| amount * el.weight))
+                .sum()
+        };
+        let amount = amount
+            .unwrap_or_default()
+            .round_dp_with_strategy(PRICE_SCALE, RoundingStrategy::MidpointAwayFromZero);
+        allocated += amount;
+        result.push((kind, quantity, amount));
+    }
+    result.push((OTHER_RESOURCE, 0, bill.price - allocated));
+    result
+}
+
+impl Statement {
+    /// 汇总账期内的节点账单
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `scope` - 对账单范围，不在范围内的账单将被忽略
+    /// * `period` - 账期 `[start, end)`
+    /// * `records` - 节点账单
+    pub fn aggregate(
+        user_id: Uuid,
+        scope: StatementScope,
+        period: (DateTime<Utc>, DateTime<Utc>),
+        records: &[BillingRecord],
+    ) -> Self {
+        let (period_start, period_end) = period;
+        let mut lines = BTreeMap::<(Uuid, &str), (i64, Decimal)>::new();
+        let mut node_count = 0;
+        let mut total = Decimal::ZERO;
+        for record in records.iter().filter(|el| {
+            el.user_id == user_id
+                && el.created_time >= period_start
+                && el.created_time < period_end
+                && match scope {
+                    StatementScope::User => true,
+                    StatementScope::Workflow(id) => el.bill.flow_instance_id == id,
+                }
+        }) {
+            node_count += 1;
+            total += record.bill.price;
+            for (kind, quantity, amount) in resource_amounts(&record.bill) {
+                if kind == OTHER_RESOURCE && amount.is_zero() {
+                    continue;
+                }
+                let line = lines.entry((record.cluster_id, kind)).or_default();
+                line.0 += quantity;
+                line.1 += amount;
+            }
+        }
+        Self {
+            user_id,
+            scope,
+            period_start,
+            period_end,
+            node_count,
+            lines: lines
+                .into_iter()
+                .map(
+                    |((cluster_id, resource), (quantity, amount))| StatementLine {
+                        cluster_id,
+                        resource: resource.to_owned(),
+                        quantity,
+                        amount,
+                    },
+                )
+                .collect(),
+            total,
+        }
+    }
+
+    /// 导出为 CSV，末行为合计
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("cluster_id,resource,quantity,amount\n");
+        for line in self.lines.iter() {
+            // 写入 String 不会失败
+            let _ = writeln!(
+                csv,
+                "{},{},{},{}",
+                line.cluster_id, line.resource, line.quantity, line.amount
+            );
+        }
+        let _ = writeln!(csv, ",total,,{}", self.total);
+        csv
+    }
+}
+
+impl Invoice {
+    /// 以对账单开具发票
+    pub fn issue(statement: Statement) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            statement,
+            issued_time: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn breakdown(items: &[(&str, Decimal)]) -> PriceBreakdown {
+        PriceBreakdown {
+            variables: BTreeMap::new(),
+            items: items
+                .iter()
+                .map(|(name, amount)| LineItem {
+                    name: name.to_string(),
+                    expression: String::new(),
+                    amount: *amount,
+                })
+                .collect(),
+            total: items.iter().map(|el| el.1).sum(),
+        }
+    }
+
+    #[test]
+    fn test_month_period() {
+        let (start, end) = month_period(2023, 12).unwrap();
+        assert_eq!(start, Utc.with_ymd_and_hms(2023, 12, 1, 0, 0, 0).unwrap());
+        assert_eq!(end, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        assert!(month_period(2023, 13).is_err());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let user_id = Uuid::new_v4();
+        let cluster_id = Uuid::new_v4();
+        let flow_instance_id = Uuid::new_v4();
+        let period = month_period(2023, 4).unwrap();
+        let created_time = Utc.with_ymd_and_hms(2023, 4, 10, 0, 0, 0).unwrap();
+        let record = |bill: NodeInstanceBilling| BillingRecord {
+            user_id,
+            cluster_id,
+            created_time,
+            bill: NodeInstanceBilling {
+                flow_instance_id,
+                ..bill
+            },
+        };
+        let records = vec![
+            // 跨越两个计费时段，各占一半
+            record(NodeInstanceBilling {
+                cpu: 2,
+                cpu_time: 100,
+                price: Decimal::new(35, 1),
+                segments: vec![
+                    PricingSegment {
+                        weight: Decimal::new(5, 1),
+                        breakdown: breakdown(&[
+                            ("p_cpu", Decimal::from(2)),
+                            ("p_cpu_time", Decimal::from(1)),
+                            ("p_node", Decimal::from(3)),
+                        ]),
+                        ..Default::default()
+                    },
+                    PricingSegment {
+                        weight: Decimal::new(5, 1),
+                        breakdown: breakdown(&[
+                            ("p_cpu", Decimal::from(2)),
+                            ("p_cpu_time", Decimal::from(2)),
+                            ("p_node", Decimal::from(4)),
+                        ]),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }),
+            // 早于价格明细功能的账单
+            record(NodeInstanceBilling {
+                cpu: 1,
+                price: Decimal::from(5),
+                ..Default::default()
+            }),
+            // 不在账期内
+            BillingRecord {
+                created_time: period.1,
+                ..record(NodeInstanceBilling {
+                    price: Decimal::from(100),
+                    ..Default::default()
+                })
+            },
+        ];
+        let statement = Statement::aggregate(user_id, StatementScope::User, period, &records);
+        assert_eq!(statement.node_count, 2);
+        assert_eq!(statement.total, Decimal::new(85, 1));
+        let line = |resource: &str| {
+            statement.lines.iter().find(|el| el.resource == resource).cloned().unwrap()
+        };
+        assert_eq!(line("cpu").quantity, 3);
+        assert_eq!(line("cpu").amount, Decimal::from(2));
+        assert_eq!(line("cpu_time").amount, Decimal::new(15, 1));
+        assert_eq!(line(OTHER_RESOURCE).amount, Decimal::from(5));
+        assert_eq!(
+            statement.lines.iter().map(|el| el.amount).sum::<Decimal>(),
+            statement.total
+        );
+
+        let statement = Statement::aggregate(
+            user_id,
+            StatementScope::Workflow(Uuid::new_v4()),
+            period,
+            &records,
+        );
+        assert_eq!(statement.node_count, 0);
+        assert!(statement.lines.is_empty());
+        assert!(statement.to_csv().ends_with(",total,,0\n"));
+    }
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use num_derive::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+impl alice_architecture::model::IAggregateRoot for LedgerEntry {}
+
+/// 账户流水
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LedgerEntry {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 借记或贷记
+    pub kind: LedgerEntryKind,
+    /// 金额
+    pub amount: Decimal,
+    /// 记账后的余额，用户没有预付费账户时为 None
+    pub balance: Option<Decimal>,
+    /// 扣费对应的工作流实例
+    pub flow_instance_id: Option<Uuid>,
+    /// 扣费对应的节点实例
+    pub node_instance_id: Option<Uuid>,
+    pub description: String,
+    pub created_time: DateTime<Utc>,
+}
+
+/// 流水类型
+#[derive(FromPrimitive, ToPrimitive, Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum LedgerEntryKind {
+    /// 扣费
+    Debit,
+    /// 充值
+    Credit,
+}
@@ -1,17 +1,31 @@
+pub mod account;
+pub mod budget;
 pub mod cluster_id_settings;
 pub mod flow_instance;
 pub mod flow_instance_billing;
 pub mod formula;
+pub mod invoice;
+pub mod ledger_entry;
 pub mod node_instance;
 pub mod node_instance_billing;
+pub mod pricing_rule;
+pub mod user_group;
 pub mod user_webhook;
+pub mod webhook_delivery;
 
 pub mod prelude {
+    pub use super::account::*;
+    pub use super::budget::*;
     pub use super::cluster_id_settings::*;
     pub use super::flow_instance::*;
     pub use super::flow_instance_billing::*;
     pub use super::formula::*;
+    pub use super::invoice::*;
+    pub use super::ledger_entry::*;
     pub use super::node_instance::*;
     pub use super::node_instance_billing::*;
+    pub use super::pricing_rule::*;
+    pub use super::user_group::*;
     pub use super::user_webhook::*;
+    pub use super::webhook_delivery::*;
 }
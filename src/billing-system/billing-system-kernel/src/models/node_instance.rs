@@ -32,4 +32,7 @@ pub struct TaskUsedResource {
     pub start_time: i64,
     /// 结束时间
     pub end_time: i64,
+    /// 调度器实际使用的分区或队列
+    #[serde(default)]
+    pub partition: Option<String>,
 }
@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -14,5 +15,10 @@ pub struct NodeInstanceBilling {
     pub cpu_time: i64,
     pub wall_time: i64,
     pub price: Decimal,
+    /// 计费时使用的公式，跨越多个计费时段时为首个时段的公式
     pub formula: String,
+    /// 价格明细，跨越多个计费时段时为首个时段的明细
+    pub breakdown: PriceBreakdown,
+    /// 按计费规则拆分的计费时段
+    pub segments: Vec<PricingSegment>,
 }
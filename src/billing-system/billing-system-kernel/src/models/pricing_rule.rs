@@ -0,0 +1,355 @@
+use crate::prelude::*;
+use chrono::{DateTime, Duration, FixedOffset, NaiveTime, TimeZone, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+impl alice_architecture::model::IAggregateRoot for PricingRule {}
+
+/// 集群的计费规则，按分区、用户组与时段覆盖集群的统一单价
+///
+/// 规则创建后单价与公式不再修改，调价时令旧规则失效并新增规则，以便按账单时间找回当时的单价
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct PricingRule {
+    pub id: Uuid,
+    pub cluster_id: Uuid,
+    /// 分区（Slurm）或队列（PBS），为空时适用于所有分区
+    pub partition: Option<String>,
+    /// 用户组，为空时适用于所有用户
+    pub user_group: Option<String>,
+    /// 每日生效时段，为空时全天生效
+    pub time_window: Option<TimeWindow>,
+    /// 生效时间
+    pub effective_from: DateTime<Utc>,
+    /// 失效时间，为空时一直有效
+    pub effective_to: Option<DateTime<Utc>>,
+    pub cpu: Decimal,
+    pub memory: Decimal,
+    pub storage: Decimal,
+    pub cpu_time: Decimal,
+    pub wall_time: Decimal,
+    pub formula: String,
+}
+
+/// 每日时段，结束时间早于开始时间时跨越午夜
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeWindow {
+    /// 开始时间（含）
+    pub start: NaiveTime,
+    /// 结束时间（不含）
+    pub end: NaiveTime,
+    /// 时段所在时区相对 UTC 的偏移（秒）
+    #[serde(default)]
+    pub utc_offset: i32,
+}
+
+/// 按计费规则拆分的计费时段
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct PricingSegment {
+    /// 使用的计费规则，为空时使用集群的统一单价
+    pub rule_id: Option<Uuid>,
+    /// 开始时间（时间戳，秒）
+    pub start_time: i64,
+    /// 结束时间（时间戳，秒）
+    pub end_time: i64,
+    /// 时段占任务运行时长的比例
+    pub weight: Decimal,
+    /// 计费时使用的公式
+    pub formula: String,
+    /// 完整用量按该时段的单价计算的价格明细
+    pub breakdown: PriceBreakdown,
+    /// 时段的价格，即明细总价乘以比例
+    pub amount: Decimal,
+}
+
+/// 计价的上下文
+#[derive(Clone, Debug)]
+pub struct PricingContext {
+    pub cluster_id: Uuid,
+    pub user_id: Uuid,
+    /// 任务使用的分区或队列
+    pub partition: Option<String>,
+    /// 开始时间（时间戳，秒）
+    pub start_time: i64,
+    /// 结束时间（时间戳，秒）
+    pub end_time: i64,
+}
+
+impl TimeWindow {
+    fn offset(&self) -> anyhow::Result<FixedOffset> {
+        FixedOffset::east_opt(self.utc_offset)
+            .ok_or(anyhow::anyhow!("Invalid utc offset {}.", self.utc_offset))
+    }
+
+    /// 校验时段
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.offset()?;
+        if self.start == self.end {
+            anyhow::bail!("Time window can not start and end at the same time.");
+        }
+        Ok(())
+    }
+
+    /// 时段是否包含某一时刻
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let Ok(offset) = self.offset() else {
+            return false;
+        };
+        let time = at.with_timezone(&offset).time();
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// 时段在 (`from`, `to`) 内的开始与结束时刻
+    pub fn boundaries(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let Ok(offset) = self.offset() else {
+            return vec![];
+        };
+        let mut result = vec![];
+        let last = to.with_timezone(&offset).date_naive();
+        let mut date = from.with_timezone(&offset).date_naive();
+        while date <= last {
+            for time in [self.start, self.end] {
+                let Some(at) = offset.from_local_datetime(&date.and_time(time)).single() else {
+                    continue;
+                };
+                let at = at.with_timezone(&Utc);
+                if from < at && at < to {
+                    result.push(at);
+                }
+            }
+            date += Duration::days(1);
+        }
+        result
+    }
+}
+
+impl PricingRule {
+    /// 规则的单价，用于按集群统一单价的方式计算价格
+    pub fn settings(&self) -> ClusterIdSettings {
+        ClusterIdSettings {
+            id: self.id,
+            cluster_id: self.cluster_id,
+            cpu: self.cpu,
+            memory: self.memory,
+            storage: self.storage,
+            cpu_time: self.cpu_time,
+            wall_time: self.wall_time,
+            formula: self.formula.to_owned(),
+        }
+    }
+
+    /// 校验计费公式、时段与有效期
+    pub fn validate(&self) -> anyhow::Result<()> {
+        self.settings().validate()?;
+        if let Some(time_window) = &self.time_window {
+            time_window.validate()?;
+        }
+        if let Some(effective_to) = self.effective_to {
+            if effective_to <= self.effective_from {
+                anyhow::bail!("Pricing rule must expire after it takes effect.");
+            }
+        }
+        Ok(())
+    }
+
+    /// 规则是否适用于分区与用户组
+    pub fn applies_to(&self, partition: Option<&str>, user_group: Option<&str>) -> bool {
+        let matches = |expected: &Option<String>, actual: Option<&str>| match expected {
+            Some(expected) => actual == Some(expected.as_str()),
+            None => true,
+        };
+        matches(&self.partition, partition) && matches(&self.user_group, user_group)
+    }
+
+    /// 规则在某一时刻是否生效
+    pub fn is_effective_at(&self, at: DateTime<Utc>) -> bool {
+        self.effective_from <= at
+            && self.effective_to.map(|el| at < el).unwrap_or(true)
+            && self.time_window.as_ref().map(|el| el.contains(at)).unwrap_or(true)
+    }
+
+    /// 规则指定的条件数，多条规则同时生效时使用条件最多的规则
+    fn specificity(&self) -> usize {
+        [
+            self.partition.is_some(),
+            self.user_group.is_some(),
+            self.time_window.is_some(),
+        ]
+        .into_iter()
+        .filter(|el| *el)
+        .count()
+    }
+
+    /// 规则在 (`from`, `to`) 内开始或停止生效的时刻
+    fn boundaries(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut result =
+            self.time_window.as_ref().map(|el| el.boundaries(from, to)).unwrap_or_default();
+        for at in [Some(self.effective_from), self.effective_to].into_iter().flatten() {
+            if from < at && at < to {
+                result.push(at);
+            }
+        }
+        result
+    }
+}
+
+/// 某一时刻生效的规则中条件最多的规则，条件数相同时使用生效时间最晚的规则
+fn select_rule<'a>(rules: &[&'a PricingRule], at: DateTime<Utc>) -> Option<&'a PricingRule> {
+    rules
+        .iter()
+        .filter(|el| el.is_effective_at(at))
+        .max_by_key(|el| (el.specificity(), el.effective_from))
+        .copied()
+}
+
+/// 按计费规则拆分任务的运行时段
+///
+/// 每个时段使用该时段内生效的规则，没有规则生效的时段使用集群的统一单价（规则为 None），
+/// 运行时长为 0 的任务按开始时刻的规则计价。
+///
+/// # 参数
+///
+/// * `rules` - 集群的计费规则
+/// * `partition` - 任务使用的分区或队列
+/// * `user_group` - 用户所属的用户组
+/// * `start` - 开始时间
+/// * `end` - 结束时间
+pub fn split_by_rules<'a>(
+    rules: &'a [PricingRule],
+    partition: Option<&str>,
+    user_group: Option<&str>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<(Option<&'a PricingRule>, DateTime<Utc>, DateTime<Utc>)> {
+    let rules = rules
+        .iter()
+        .filter(|el| el.applies_to(partition, user_group))
+        .collect::<Vec<_>>();
+    if end <= start {
+        return vec![(select_rule(&rules, start), start, start)];
+    }
+    let mut points = vec![start, end];
+    for rule in rules.iter() {
+        points.append(&mut rule.boundaries(start, end));
+    }
+    points.sort();
+    points.dedup();
+
+    let mut result: Vec<(Option<&PricingRule>, DateTime<Utc>, DateTime<Utc>)> = vec![];
+    for window in points.windows(2) {
+        let rule = select_rule(&rules, window[0]);
+        match result.last_mut() {
+            Some(last) if last.0.map(|el| el.id) == rule.map(|el| el.id) => last.2 = window[1],
+            _ => result.push((rule, window[0], window[1])),
+        }
+    }
+    result
+}
+
+/// 各计费时段的总价，按账单精度舍入
+pub fn segments_price(segments: &[PricingSegment]) -> Decimal {
+    segments
+        .iter()
+        .map(|el| el.amount)
+        .sum::<Decimal>()
+        .round_dp_with_strategy(PRICE_SCALE, RoundingStrategy::MidpointAwayFromZero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 4, 3, hour, minute, 0).unwrap()
+    }
+
+    fn rule(time_window: Option<TimeWindow>) -> PricingRule {
+        PricingRule {
+            id: Uuid::new_v4(),
+            time_window,
+            effective_from: at(0, 0) - Duration::days(30),
+            formula: json!({ "p_cpu": "n_cpu * u_cpu", "p_node": "p_cpu" }).to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn window(start: u32, end: u32) -> TimeWindow {
+        TimeWindow {
+            start: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+            utc_offset: 0,
+        }
+    }
+
+    #[test]
+    fn test_time_window() {
+        let peak = window(8, 20);
+        assert!(peak.contains(at(8, 0)));
+        assert!(!peak.contains(at(20, 0)));
+        let night = window(22, 6);
+        assert!(night.contains(at(23, 0)));
+        assert!(night.contains(at(5, 59)));
+        assert!(!night.contains(at(12, 0)));
+        let shifted = TimeWindow {
+            utc_offset: 8 * 3600,
+            ..peak
+        };
+        assert!(shifted.contains(at(0, 0)));
+        assert!(!shifted.contains(at(12, 0)));
+        assert_eq!(
+            peak.boundaries(at(6, 0), at(21, 0)),
+            vec![at(8, 0), at(20, 0)]
+        );
+    }
+
+    #[test]
+    fn test_split_by_rules() {
+        let base = rule(None);
+        let peak = rule(Some(window(8, 20)));
+        let rules = vec![base.to_owned(), peak.to_owned()];
+        let segments = split_by_rules(&rules, None, None, at(6, 0), at(21, 0));
+        let segments = segments
+            .into_iter()
+            .map(|(rule, from, to)| (rule.map(|el| el.id), from, to))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            segments,
+            vec![
+                (Some(base.id), at(6, 0), at(8, 0)),
+                (Some(peak.id), at(8, 0), at(20, 0)),
+                (Some(base.id), at(20, 0), at(21, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_by_rules_respects_effective_dates_and_conditions() {
+        let old = PricingRule {
+            effective_to: Some(at(12, 0)),
+            ..rule(None)
+        };
+        let gpu = PricingRule {
+            partition: Some("gpu".to_string()),
+            ..rule(None)
+        };
+        let rules = vec![old.to_owned(), gpu.to_owned()];
+        let segments = split_by_rules(&rules, None, None, at(10, 0), at(14, 0));
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].0.map(|el| el.id), Some(old.id));
+        assert!(segments[1].0.is_none());
+        assert_eq!(segments[1].1, at(12, 0));
+
+        let segments = split_by_rules(&rules, Some("gpu"), None, at(10, 0), at(14, 0));
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].0.map(|el| el.id), Some(gpu.id));
+
+        let segments = split_by_rules(&rules, None, None, at(10, 0), at(10, 0));
+        assert_eq!(segments[0].0.map(|el| el.id), Some(old.id));
+    }
+}
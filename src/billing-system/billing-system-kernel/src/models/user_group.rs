@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+impl alice_architecture::model::IAggregateRoot for UserGroup {}
+
+/// 用户所属的计费用户组
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+pub struct UserGroup {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 用户组名称
+    pub name: String,
+}
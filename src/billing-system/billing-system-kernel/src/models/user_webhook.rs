@@ -2,9 +2,33 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 impl alice_architecture::model::IAggregateRoot for UserWebhook {}
+
+/// 用户订阅计费事件的地址，一个用户可以有多个订阅
 #[derive(Clone, Serialize, Deserialize, Default, Debug)]
 pub struct UserWebhook {
     pub id: Uuid,
     pub user_id: Uuid,
     pub url: String,
+    /// 签名密钥
+    pub secret: String,
+    /// 订阅的事件类型，为空时订阅所有事件
+    pub event_types: Vec<WebhookEventType>,
+}
+
+/// 计费事件类型
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum WebhookEventType {
+    /// 节点计费完成
+    NodeBilled,
+    /// 工作流账单更新，每个节点计费后发送工作流的累计账单
+    WorkflowBilled,
+    /// 预算花费达到上限
+    BudgetThresholdReached,
+}
+
+impl UserWebhook {
+    /// 是否订阅了某类事件
+    pub fn subscribes(&self, event_type: WebhookEventType) -> bool {
+        self.event_types.is_empty() || self.event_types.contains(&event_type)
+    }
 }
@@ -0,0 +1,203 @@
+use crate::prelude::*;
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// 事件类型请求头
+pub const WEBHOOK_EVENT_HEADER: &str = "X-Kuintessence-Event";
+/// 推送记录 id 请求头，接收方可据此去重
+pub const WEBHOOK_DELIVERY_HEADER: &str = "X-Kuintessence-Delivery";
+/// 签名时间戳请求头
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-Kuintessence-Timestamp";
+/// 签名请求头，值为 `sha256=` 加上签名的十六进制
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Kuintessence-Signature";
+/// 最多尝试推送的次数
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 8;
+
+impl alice_architecture::model::IAggregateRoot for WebhookDelivery {}
+
+/// 计费事件的推送记录，同时作为待推送队列
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: WebhookEventType,
+    /// 请求体
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    /// 已尝试推送的次数
+    pub attempts: u32,
+    /// 下次尝试推送的时间
+    pub next_attempt_time: DateTime<Utc>,
+    /// 最近一次推送失败的原因
+    pub last_error: Option<String>,
+    pub created_time: DateTime<Utc>,
+    /// 推送成功的时间
+    pub delivered_time: Option<DateTime<Utc>>,
+}
+
+/// 推送状态
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum WebhookDeliveryStatus {
+    /// 等待推送或重试
+    #[default]
+    Pending,
+    /// 推送成功
+    Delivered,
+    /// 超过最大尝试次数，不再重试
+    Failed,
+}
+
+/// 推送的请求体
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEvent {
+    /// 推送记录 id
+    pub id: Uuid,
+    pub event_type: WebhookEventType,
+    pub created_time: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+/// 第 `attempts` 次推送失败后等待的时间，从 30 秒开始翻倍，最长 6 小时
+pub fn webhook_backoff(attempts: u32) -> Duration {
+    let seconds = 30i64.saturating_mul(1 << attempts.saturating_sub(1).min(20));
+    Duration::seconds(seconds.min(6 * 3600))
+}
+
+impl WebhookDelivery {
+    /// 为订阅创建待推送的事件
+    ///
+    /// # 参数
+    ///
+    /// * `webhook` - 订阅
+    /// * `event_type` - 事件类型
+    /// * `data` - 事件内容
+    pub fn create(
+        webhook: &UserWebhook,
+        event_type: WebhookEventType,
+        data: serde_json::Value,
+    ) -> anyhow::Result<Self> {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        let payload = serde_json::to_string(&WebhookEvent {
+            id,
+            event_type,
+            created_time: now,
+            data,
+        })?;
+        Ok(Self {
+            id,
+            webhook_id: webhook.id,
+            user_id: webhook.user_id,
+            event_type,
+            payload,
+            status: WebhookDeliveryStatus::Pending,
+            attempts: 0,
+            next_attempt_time: now,
+            last_error: None,
+            created_time: now,
+            delivered_time: None,
+        })
+    }
+
+    /// 请求签名，即以订阅的密钥对 `{timestamp}.{payload}` 计算的 HMAC-SHA256 的十六进制
+    ///
+    /// # 参数
+    ///
+    /// * `secret` - 订阅的签名密钥
+    /// * `timestamp` - 签名时间戳（秒）
+    pub fn signature(&self, secret: &str, timestamp: i64) -> anyhow::Result<String> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())?;
+        mac.update(format!("{timestamp}.").as_bytes());
+        mac.update(self.payload.as_bytes());
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+
+    /// 记录推送成功
+    pub fn succeed(&mut self, now: DateTime<Utc>) {
+        self.attempts += 1;
+        self.status = WebhookDeliveryStatus::Delivered;
+        self.delivered_time = Some(now);
+        self.last_error = None;
+    }
+
+    /// 记录推送失败，按指数退避安排重试，达到最大尝试次数后不再重试
+    pub fn fail(&mut self, error: String, now: DateTime<Utc>) {
+        self.attempts += 1;
+        self.last_error = Some(error);
+        if self.attempts >= WEBHOOK_MAX_ATTEMPTS {
+            self.status = WebhookDeliveryStatus::Failed;
+        } else {
+            self.next_attempt_time = now + webhook_backoff(self.attempts);
+        }
+    }
+
+    /// 重新推送，重置尝试次数
+    pub fn replay(&mut self, now: DateTime<Utc>) {
+        self.status = WebhookDeliveryStatus::Pending;
+        self.attempts = 0;
+        self.next_attempt_time = now;
+        self.delivered_time = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn delivery() -> WebhookDelivery {
+        WebhookDelivery::create(
+            &UserWebhook {
+                id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                url: "http://localhost/webhook".to_string(),
+                secret: "secret".to_string(),
+                event_types: vec![],
+            },
+            WebhookEventType::NodeBilled,
+            json!({ "price": "0.1" }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_signature() {
+        let delivery = WebhookDelivery {
+            payload: "{}".to_string(),
+            ..delivery()
+        };
+        // echo -n '1680000000.{}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            delivery.signature("secret", 1680000000).unwrap(),
+            "2a02091ea43e6c03406451ee70ff733cc457a182f40a5951ff17ffcfdafd0235"
+        );
+    }
+
+    #[test]
+    fn test_retry_with_backoff() {
+        assert_eq!(webhook_backoff(1), Duration::seconds(30));
+        assert_eq!(webhook_backoff(3), Duration::seconds(120));
+        assert_eq!(webhook_backoff(30), Duration::hours(6));
+
+        let mut delivery = delivery();
+        let now = Utc::now();
+        delivery.fail("timeout".to_string(), now);
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.next_attempt_time, now + Duration::seconds(30));
+        for _ in 1..WEBHOOK_MAX_ATTEMPTS {
+            delivery.fail("timeout".to_string(), now);
+        }
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Failed);
+
+        delivery.replay(now);
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 0);
+        delivery.succeed(now);
+        assert_eq!(delivery.status, WebhookDeliveryStatus::Delivered);
+    }
+}
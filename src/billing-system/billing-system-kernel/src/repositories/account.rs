@@ -0,0 +1,13 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+use rust_decimal::Decimal;
+
+#[async_trait::async_trait]
+pub trait IAccountRepository: IDBRepository<Account> {
+    /// 获取用户的预付费账户，用户没有账户时返回 None
+    async fn get_by_user_id(&self, id: &str) -> anyhow::Result<Option<Account>>;
+    /// 在数据库中原子地增加余额，用户没有账户时开通，随 `save_changed` 提交
+    async fn credit(&self, user_id: &str, amount: Decimal) -> anyhow::Result<()>;
+    /// 在数据库中原子地扣减余额，用户没有账户时不做修改，随 `save_changed` 提交
+    async fn debit(&self, user_id: &str, amount: Decimal) -> anyhow::Result<()>;
+}
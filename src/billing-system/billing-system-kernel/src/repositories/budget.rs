@@ -0,0 +1,16 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+pub trait IBudgetRepository: IDBRepository<Budget> {
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<Budget>>;
+    /// 在数据库中原子地累加适用于工作流实例的预算花费，随 `save_changed` 提交
+    async fn add_spent(
+        &self,
+        user_id: &str,
+        flow_instance_id: Uuid,
+        amount: Decimal,
+    ) -> anyhow::Result<()>;
+}
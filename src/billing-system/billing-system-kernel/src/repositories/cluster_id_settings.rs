@@ -3,4 +3,6 @@ use crate::prelude::*;
 #[async_trait::async_trait]
 pub trait IClusterIdSettingsRepository {
     async fn get_by_cluster_id(&self, id: &str) -> anyhow::Result<ClusterIdSettings>;
+    /// 按集群 id 插入或更新计费设置
+    async fn insert_or_update(&self, entity: ClusterIdSettings) -> anyhow::Result<()>;
 }
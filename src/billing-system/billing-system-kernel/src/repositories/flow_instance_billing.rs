@@ -5,4 +5,6 @@ use alice_architecture::repository::IDBRepository;
 pub trait IFlowInstanceBillingRepository: IDBRepository<FlowInstanceBilling> {
     async fn get_by_flow_instance_id(&self, id: &str) -> anyhow::Result<FlowInstanceBilling>;
     async fn insert_or_update(&self, entity: FlowInstanceBilling) -> anyhow::Result<()>;
+    /// 立即标记工作流账单已推送，已被标记过时返回 false
+    async fn settle(&self, flow_instance_id: &str) -> anyhow::Result<bool>;
 }
@@ -0,0 +1,16 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+use chrono::{DateTime, Utc};
+
+#[async_trait::async_trait]
+pub trait IInvoiceRepository: IDBRepository<Invoice> {
+    /// 按账期倒序获取用户的发票
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<Invoice>>;
+    /// 获取某一账期已开具的发票
+    async fn get_by_period(
+        &self,
+        user_id: &str,
+        scope: &StatementScope,
+        period_start: DateTime<Utc>,
+    ) -> anyhow::Result<Option<Invoice>>;
+}
@@ -0,0 +1,10 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+
+#[async_trait::async_trait]
+pub trait ILedgerEntryRepository: IDBRepository<LedgerEntry> {
+    /// 按记账时间倒序获取用户的流水
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<LedgerEntry>>;
+    /// 记录流水，余额取同一事务中此前修改后的账户余额，随 `save_changed` 提交
+    async fn insert_with_current_balance(&self, entity: LedgerEntry) -> anyhow::Result<()>;
+}
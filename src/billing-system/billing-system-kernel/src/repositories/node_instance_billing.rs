@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use alice_architecture::repository::IDBRepository;
+use chrono::{DateTime, Utc};
 
 #[async_trait::async_trait]
 pub trait INodeInstanceBillingRepository: IDBRepository<NodeInstanceBilling> {
@@ -7,4 +8,18 @@ pub trait INodeInstanceBillingRepository: IDBRepository<NodeInstanceBilling> {
         &self,
         id: &str,
     ) -> anyhow::Result<Vec<NodeInstanceBilling>>;
+    /// 工作流实例中成功结束但尚未计费的节点数
+    async fn count_unbilled(&self, flow_instance_id: &str) -> anyhow::Result<usize>;
+    /// 获取节点实例的账单，节点尚未计费时返回 None
+    async fn get_by_node_instance_id(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<Option<NodeInstanceBilling>>;
+    /// 获取用户在 `[start, end)` 内记账的节点账单
+    async fn get_all_records_by_user_id(
+        &self,
+        id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<BillingRecord>>;
 }
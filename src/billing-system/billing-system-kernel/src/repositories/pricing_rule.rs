@@ -0,0 +1,8 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+
+#[async_trait::async_trait]
+pub trait IPricingRuleRepository: IDBRepository<PricingRule> {
+    /// 获取集群的所有计费规则，包括已失效的规则
+    async fn get_all_by_cluster_id(&self, id: &str) -> anyhow::Result<Vec<PricingRule>>;
+}
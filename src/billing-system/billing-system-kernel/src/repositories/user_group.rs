@@ -0,0 +1,8 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+
+#[async_trait::async_trait]
+pub trait IUserGroupRepository: IDBRepository<UserGroup> {
+    /// 获取用户所属的用户组，用户不属于任何用户组时返回 None
+    async fn get_by_user_id(&self, id: &str) -> anyhow::Result<Option<UserGroup>>;
+}
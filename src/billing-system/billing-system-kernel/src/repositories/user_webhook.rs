@@ -3,5 +3,6 @@ use alice_architecture::repository::IDBRepository;
 
 #[async_trait::async_trait]
 pub trait IUserWebhookRepository: IDBRepository<UserWebhook> {
-    async fn get_url_by_user_id(&self, id: &str) -> anyhow::Result<String>;
+    /// 获取用户的所有订阅
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<UserWebhook>>;
 }
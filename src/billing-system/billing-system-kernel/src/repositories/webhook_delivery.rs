@@ -0,0 +1,15 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+use chrono::{DateTime, Utc};
+
+#[async_trait::async_trait]
+pub trait IWebhookDeliveryRepository: IDBRepository<WebhookDelivery> {
+    /// 立即领取到期待推送的记录，领取的记录在 `lease_until` 之前不会被再次领取
+    async fn claim_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<WebhookDelivery>>;
+    /// 获取用户的推送记录，按创建时间倒序
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<WebhookDelivery>>;
+}
@@ -0,0 +1,40 @@
+use crate::prelude::*;
+use rust_decimal::Decimal;
+
+#[async_trait::async_trait]
+pub trait IBudgetService {
+    /// 节点分发前按集群计费规则估算花费，检查工作流实例所属用户的账户余额与预算
+    async fn check_budget(
+        &self,
+        flow_instance_id: &str,
+        cluster_id: &str,
+        partition: Option<&str>,
+        estimate: &ResourceEstimate,
+    ) -> anyhow::Result<BudgetDecision>;
+    /// 扣费，记录流水并累计预算花费
+    ///
+    /// 修改不会立即提交，由调用方与节点账单在同一事务中提交，
+    /// 返回本次扣费后达到上限的预算，提交后由调用方发送通知
+    async fn charge(
+        &self,
+        user_id: &str,
+        flow_instance_id: &str,
+        node_instance_id: &str,
+        amount: Decimal,
+    ) -> anyhow::Result<Vec<Budget>>;
+    /// 充值，用户没有账户时开通
+    async fn top_up(
+        &self,
+        user_id: &str,
+        amount: Decimal,
+        description: &str,
+    ) -> anyhow::Result<Account>;
+    /// 设置预算，相同范围的预算已存在时更新上限与处理方式
+    async fn set_budget(&self, budget: Budget) -> anyhow::Result<Budget>;
+    /// 获取账户
+    async fn get_account(&self, user_id: &str) -> anyhow::Result<Option<Account>>;
+    /// 获取用户的所有预算
+    async fn get_budgets(&self, user_id: &str) -> anyhow::Result<Vec<Budget>>;
+    /// 获取账户流水
+    async fn get_ledger(&self, user_id: &str) -> anyhow::Result<Vec<LedgerEntry>>;
+}
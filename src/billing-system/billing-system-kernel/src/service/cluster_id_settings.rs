@@ -0,0 +1,9 @@
+use crate::prelude::*;
+
+#[async_trait::async_trait]
+pub trait IClusterIdSettingsService {
+    /// 获取集群计费设置
+    async fn get_settings(&self, cluster_id: &str) -> anyhow::Result<ClusterIdSettings>;
+    /// 校验计费公式后保存集群计费设置
+    async fn set_settings(&self, settings: ClusterIdSettings) -> anyhow::Result<()>;
+}
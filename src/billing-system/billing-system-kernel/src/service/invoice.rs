@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+#[async_trait::async_trait]
+pub trait IInvoiceService {
+    /// 获取自然月账期的对账单，账期已开具发票时返回发票中的对账单
+    async fn get_statement(
+        &self,
+        user_id: &str,
+        scope: StatementScope,
+        year: i32,
+        month: u32,
+    ) -> anyhow::Result<Statement>;
+    /// 关闭已结束的账期并开具发票，已开具时返回原发票
+    async fn close_period(
+        &self,
+        user_id: &str,
+        scope: StatementScope,
+        year: i32,
+        month: u32,
+    ) -> anyhow::Result<Invoice>;
+    /// 获取用户的所有发票
+    async fn get_invoices(&self, user_id: &str) -> anyhow::Result<Vec<Invoice>>;
+    /// 获取用户的发票
+    async fn get_invoice(&self, user_id: &str, invoice_id: &str) -> anyhow::Result<Invoice>;
+}
@@ -1,7 +1,15 @@
+pub mod budget;
+pub mod cluster_id_settings;
 pub mod flow_node_billing;
+pub mod invoice;
+pub mod pricing;
 pub mod user_webhook;
 
 pub mod prelude {
+    pub use super::budget::*;
+    pub use super::cluster_id_settings::*;
     pub use super::flow_node_billing::*;
+    pub use super::invoice::*;
+    pub use super::pricing::*;
     pub use super::user_webhook::*;
 }
@@ -0,0 +1,24 @@
+use crate::prelude::*;
+use chrono::{DateTime, Utc};
+
+#[async_trait::async_trait]
+pub trait IPricingService {
+    /// 获取集群的所有计费规则
+    async fn get_rules(&self, cluster_id: &str) -> anyhow::Result<Vec<PricingRule>>;
+    /// 校验后新增计费规则，生效时间早于当前时间时从当前时间开始生效
+    async fn add_rule(&self, rule: PricingRule) -> anyhow::Result<PricingRule>;
+    /// 设置计费规则的失效时间，失效时间早于当前时间时立即失效，不影响已出账单的价格
+    async fn expire_rule(
+        &self,
+        rule_id: &str,
+        effective_to: DateTime<Utc>,
+    ) -> anyhow::Result<PricingRule>;
+    /// 设置用户所属的用户组
+    async fn set_user_group(&self, user_id: &str, name: &str) -> anyhow::Result<UserGroup>;
+    /// 计算用量的价格，任务跨越多个计费规则的时段时按运行时长拆分
+    async fn price(
+        &self,
+        context: &PricingContext,
+        usage: &ResourceEstimate,
+    ) -> anyhow::Result<Vec<PricingSegment>>;
+}
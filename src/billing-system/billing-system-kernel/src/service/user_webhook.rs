@@ -1,7 +1,29 @@
+use crate::prelude::*;
+
 #[async_trait::async_trait]
 pub trait IUserWebhookService {
-    /// 注册
-    async fn register_webhook(&self, user_id: &str, url: &str) -> anyhow::Result<()>;
-    /// 发布消息
-    async fn send_message(&self, user_id: &str, message: &str) -> anyhow::Result<()>;
+    /// 订阅计费事件，事件类型为空时订阅所有事件，返回带签名密钥的订阅
+    async fn register_webhook(
+        &self,
+        user_id: &str,
+        url: &str,
+        event_types: Vec<WebhookEventType>,
+    ) -> anyhow::Result<UserWebhook>;
+    /// 取消订阅
+    async fn remove_webhook(&self, user_id: &str, webhook_id: &str) -> anyhow::Result<()>;
+    /// 获取用户的所有订阅
+    async fn get_webhooks(&self, user_id: &str) -> anyhow::Result<Vec<UserWebhook>>;
+    /// 发布事件，为订阅了该事件的每个地址加入待推送队列
+    async fn publish(
+        &self,
+        user_id: &str,
+        event_type: WebhookEventType,
+        data: serde_json::Value,
+    ) -> anyhow::Result<()>;
+    /// 推送队列中到期的事件
+    async fn deliver_due(&self) -> anyhow::Result<()>;
+    /// 获取用户的推送记录
+    async fn get_deliveries(&self, user_id: &str) -> anyhow::Result<Vec<WebhookDelivery>>;
+    /// 重新推送
+    async fn replay(&self, user_id: &str, delivery_id: &str) -> anyhow::Result<WebhookDelivery>;
 }
@@ -0,0 +1,632 @@
+use crate::prelude::*;
+use alice_architecture::repository::IReadOnlyRepository;
+use chrono::Utc;
+use rust_decimal::Decimal;
+use std::{str::FromStr, sync::Arc};
+use uuid::Uuid;
+
+pub struct BudgetService {
+    account_repo: Arc<dyn IAccountRepository + Send + Sync>,
+    budget_repo: Arc<dyn IBudgetRepository + Send + Sync>,
+    ledger_repo: Arc<dyn ILedgerEntryRepository + Send + Sync>,
+    pricing_service: Arc<dyn IPricingService + Send + Sync>,
+    flow_instance_repo: Arc<dyn IReadOnlyRepository<FlowInstance> + Send + Sync>,
+}
+
+impl BudgetService {
+    pub fn new(
+        account_repo: Arc<dyn IAccountRepository + Send + Sync>,
+        budget_repo: Arc<dyn IBudgetRepository + Send + Sync>,
+        ledger_repo: Arc<dyn ILedgerEntryRepository + Send + Sync>,
+        pricing_service: Arc<dyn IPricingService + Send + Sync>,
+        flow_instance_repo: Arc<dyn IReadOnlyRepository<FlowInstance> + Send + Sync>,
+    ) -> Self {
+        Self {
+            account_repo,
+            budget_repo,
+            ledger_repo,
+            pricing_service,
+            flow_instance_repo,
+        }
+    }
+
+    /// 适用于工作流实例的预算
+    async fn applicable_budgets(
+        &self,
+        user_id: &str,
+        flow_instance_id: Uuid,
+    ) -> anyhow::Result<Vec<Budget>> {
+        Ok(self
+            .budget_repo
+            .get_all_by_user_id(user_id)
+            .await?
+            .into_iter()
+            .filter(|el| match el.scope {
+                BudgetScope::User => true,
+                BudgetScope::Workflow(id) => id == flow_instance_id,
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl IBudgetService for BudgetService {
+    async fn check_budget(
+        &self,
+        flow_instance_id: &str,
+        cluster_id: &str,
+        partition: Option<&str>,
+        estimate: &ResourceEstimate,
+    ) -> anyhow::Result<BudgetDecision> {
+        let user_id = self.flow_instance_repo.get_by_id(flow_instance_id).await?.user_id;
+        // 按从当前时刻起运行最长时间估算，跨越的各计费时段分别计价
+        let now = Utc::now().timestamp();
+        let segments = self
+            .pricing_service
+            .price(
+                &PricingContext {
+                    cluster_id: Uuid::from_str(cluster_id)?,
+                    user_id,
+                    partition: partition.map(ToOwned::to_owned),
+                    start_time: now,
+                    end_time: now + estimate.wall_time as i64,
+                },
+                estimate,
+            )
+            .await?;
+        let cost = segments_price(&segments);
+
+        let mut paused = vec![];
+        let mut refused = vec![];
+        let user_id = user_id.to_string();
+        if let Some(account) = self.account_repo.get_by_user_id(&user_id).await? {
+            if account.balance < cost {
+                paused.push(format!(
+                    "estimated cost {cost} exceeds the balance {}",
+                    account.balance
+                ));
+            }
+        }
+        for budget in self.applicable_budgets(&user_id, Uuid::from_str(flow_instance_id)?).await? {
+            if budget.spent + cost <= budget.limit {
+                continue;
+            }
+            let scope = match budget.scope {
+                BudgetScope::User => "user".to_string(),
+                BudgetScope::Workflow(id) => format!("workflow {id}"),
+            };
+            let reason = format!(
+                "estimated cost {cost} exceeds the {scope} budget ({} of {} spent)",
+                budget.spent, budget.limit
+            );
+            match budget.action {
+                BudgetAction::Pause => paused.push(reason),
+                BudgetAction::Refuse => refused.push(reason),
+            }
+        }
+
+        Ok(if !refused.is_empty() {
+            refused.append(&mut paused);
+            BudgetDecision::Refused(format!("Budget check failed: {}.", refused.join("; ")))
+        } else if !paused.is_empty() {
+            BudgetDecision::Paused(format!("Budget check failed: {}.", paused.join("; ")))
+        } else {
+            BudgetDecision::Allowed
+        })
+    }
+
+    async fn charge(
+        &self,
+        user_id: &str,
+        flow_instance_id: &str,
+        node_instance_id: &str,
+        amount: Decimal,
+    ) -> anyhow::Result<Vec<Budget>> {
+        let flow_instance_id = Uuid::from_str(flow_instance_id)?;
+        // 余额与预算花费在数据库中原子地修改，此处的读取仅用于判断是否达到上限
+        let reached = self
+            .applicable_budgets(user_id, flow_instance_id)
+            .await?
+            .into_iter()
+            .filter(|el| el.spent < el.limit && el.spent + amount >= el.limit)
+            .map(|el| Budget {
+                spent: el.spent + amount,
+                ..el
+            })
+            .collect();
+        self.account_repo.debit(user_id, amount).await?;
+        self.budget_repo.add_spent(user_id, flow_instance_id, amount).await?;
+        self.ledger_repo
+            .insert_with_current_balance(LedgerEntry {
+                id: Uuid::new_v4(),
+                user_id: Uuid::from_str(user_id)?,
+                kind: LedgerEntryKind::Debit,
+                amount,
+                balance: None,
+                flow_instance_id: Some(flow_instance_id),
+                node_instance_id: Some(Uuid::from_str(node_instance_id)?),
+                description: format!("Node instance {node_instance_id} billed."),
+                created_time: Utc::now(),
+            })
+            .await?;
+        Ok(reached)
+    }
+
+    async fn top_up(
+        &self,
+        user_id: &str,
+        amount: Decimal,
+        description: &str,
+    ) -> anyhow::Result<Account> {
+        if amount <= Decimal::ZERO {
+            anyhow::bail!("Top up amount must be positive, got {amount}.");
+        }
+        self.account_repo.credit(user_id, amount).await?;
+        self.ledger_repo
+            .insert_with_current_balance(LedgerEntry {
+                id: Uuid::new_v4(),
+                user_id: Uuid::from_str(user_id)?,
+                kind: LedgerEntryKind::Credit,
+                amount,
+                balance: None,
+                flow_instance_id: None,
+                node_instance_id: None,
+                description: description.to_owned(),
+                created_time: Utc::now(),
+            })
+            .await?;
+        self.ledger_repo.save_changed().await?;
+        self.account_repo.get_by_user_id(user_id).await?.ok_or(anyhow::anyhow!(
+            "Account of user {user_id} is missing after top up."
+        ))
+    }
+
+    async fn set_budget(&self, budget: Budget) -> anyhow::Result<Budget> {
+        if budget.limit < Decimal::ZERO {
+            anyhow::bail!("Budget limit must not be negative, got {}.", budget.limit);
+        }
+        let existing = self
+            .budget_repo
+            .get_all_by_user_id(&budget.user_id.to_string())
+            .await?
+            .into_iter()
+            .find(|el| el.scope == budget.scope);
+        let budget = match existing {
+            Some(existing) => {
+                self.budget_repo
+                    .update(Budget {
+                        limit: budget.limit,
+                        action: budget.action,
+                        ..existing
+                    })
+                    .await?
+            }
+            None => {
+                self.budget_repo
+                    .insert(Budget {
+                        id: Uuid::new_v4(),
+                        spent: Decimal::ZERO,
+                        ..budget
+                    })
+                    .await?
+            }
+        };
+        self.budget_repo.save_changed().await?;
+        Ok(budget)
+    }
+
+    async fn get_account(&self, user_id: &str) -> anyhow::Result<Option<Account>> {
+        self.account_repo.get_by_user_id(user_id).await
+    }
+
+    async fn get_budgets(&self, user_id: &str) -> anyhow::Result<Vec<Budget>> {
+        self.budget_repo.get_all_by_user_id(user_id).await
+    }
+
+    async fn get_ledger(&self, user_id: &str) -> anyhow::Result<Vec<LedgerEntry>> {
+        self.ledger_repo.get_all_by_user_id(user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// 内存中的账户、预算与流水
+    #[derive(Default)]
+    struct InMemoryRepository {
+        accounts: Mutex<Vec<Account>>,
+        budgets: Mutex<Vec<Budget>>,
+        ledger: Mutex<Vec<LedgerEntry>>,
+        rules: Mutex<Vec<PricingRule>>,
+        user_groups: Mutex<Vec<UserGroup>>,
+        flow_instances: Mutex<Vec<FlowInstance>>,
+    }
+
+    macro_rules! impl_in_memory {
+        ($entity:ty, $field:ident) => {
+            #[async_trait::async_trait]
+            impl IReadOnlyRepository<$entity> for InMemoryRepository {
+                async fn get_by_id(&self, uuid: &str) -> anyhow::Result<$entity> {
+                    self.$field
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .find(|el| el.id.to_string() == uuid)
+                        .cloned()
+                        .ok_or(anyhow::anyhow!("No such row {uuid}"))
+                }
+                async fn get_all(&self) -> anyhow::Result<Vec<$entity>> {
+                    Ok(self.$field.lock().unwrap().clone())
+                }
+            }
+
+            #[async_trait::async_trait]
+            impl IMutableRepository<$entity> for InMemoryRepository {
+                async fn update(&self, entity: $entity) -> anyhow::Result<$entity> {
+                    let mut rows = self.$field.lock().unwrap();
+                    let row = rows
+                        .iter_mut()
+                        .find(|el| el.id == entity.id)
+                        .ok_or(anyhow::anyhow!("No such row {}", entity.id))?;
+                    *row = entity.to_owned();
+                    Ok(entity)
+                }
+                async fn insert(&self, entity: $entity) -> anyhow::Result<$entity> {
+                    self.$field.lock().unwrap().push(entity.to_owned());
+                    Ok(entity)
+                }
+                async fn delete(&self, _entity: $entity) -> anyhow::Result<bool> {
+                    unimplemented!()
+                }
+                async fn delete_by_id(
+                    &self,
+                    _uuid: &str,
+                    _entity: Option<$entity>,
+                ) -> anyhow::Result<bool> {
+                    unimplemented!()
+                }
+                async fn save_changed(&self) -> anyhow::Result<bool> {
+                    Ok(true)
+                }
+            }
+
+            impl IDBRepository<$entity> for InMemoryRepository {}
+        };
+    }
+
+    impl_in_memory!(Account, accounts);
+    impl_in_memory!(Budget, budgets);
+    impl_in_memory!(LedgerEntry, ledger);
+    impl_in_memory!(PricingRule, rules);
+    impl_in_memory!(UserGroup, user_groups);
+    impl_in_memory!(FlowInstance, flow_instances);
+
+    #[async_trait::async_trait]
+    impl IAccountRepository for InMemoryRepository {
+        async fn get_by_user_id(&self, id: &str) -> anyhow::Result<Option<Account>> {
+            Ok(self
+                .accounts
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|el| el.user_id.to_string() == id)
+                .cloned())
+        }
+        async fn credit(&self, user_id: &str, amount: Decimal) -> anyhow::Result<()> {
+            let mut accounts = self.accounts.lock().unwrap();
+            match accounts.iter_mut().find(|el| el.user_id.to_string() == user_id) {
+                Some(account) => account.balance += amount,
+                None => accounts.push(Account {
+                    id: Uuid::new_v4(),
+                    user_id: Uuid::from_str(user_id)?,
+                    balance: amount,
+                }),
+            }
+            Ok(())
+        }
+        async fn debit(&self, user_id: &str, amount: Decimal) -> anyhow::Result<()> {
+            if let Some(account) = self
+                .accounts
+                .lock()
+                .unwrap()
+                .iter_mut()
+                .find(|el| el.user_id.to_string() == user_id)
+            {
+                account.balance -= amount;
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl IBudgetRepository for InMemoryRepository {
+        async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<Budget>> {
+            Ok(self
+                .budgets
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|el| el.user_id.to_string() == id)
+                .cloned()
+                .collect())
+        }
+        async fn add_spent(
+            &self,
+            user_id: &str,
+            flow_instance_id: Uuid,
+            amount: Decimal,
+        ) -> anyhow::Result<()> {
+            for budget in self.budgets.lock().unwrap().iter_mut().filter(|el| {
+                el.user_id.to_string() == user_id
+                    && match el.scope {
+                        BudgetScope::User => true,
+                        BudgetScope::Workflow(id) => id == flow_instance_id,
+                    }
+            }) {
+                budget.spent += amount;
+            }
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ILedgerEntryRepository for InMemoryRepository {
+        async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<LedgerEntry>> {
+            let mut entries = self
+                .ledger
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|el| el.user_id.to_string() == id)
+                .cloned()
+                .collect::<Vec<_>>();
+            entries.sort_by_key(|el| std::cmp::Reverse(el.created_time));
+            Ok(entries)
+        }
+        async fn insert_with_current_balance(&self, entity: LedgerEntry) -> anyhow::Result<()> {
+            let balance = IAccountRepository::get_by_user_id(self, &entity.user_id.to_string())
+                .await?
+                .map(|el| el.balance);
+            self.ledger.lock().unwrap().push(LedgerEntry { balance, ..entity });
+            Ok(())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl IPricingRuleRepository for InMemoryRepository {
+        async fn get_all_by_cluster_id(&self, id: &str) -> anyhow::Result<Vec<PricingRule>> {
+            Ok(self
+                .rules
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|el| el.cluster_id.to_string() == id)
+                .cloned()
+                .collect())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl IUserGroupRepository for InMemoryRepository {
+        async fn get_by_user_id(&self, id: &str) -> anyhow::Result<Option<UserGroup>> {
+            Ok(self
+                .user_groups
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|el| el.user_id.to_string() == id)
+                .cloned())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl IClusterIdSettingsRepository for InMemoryRepository {
+        async fn get_by_cluster_id(&self, _id: &str) -> anyhow::Result<ClusterIdSettings> {
+            Ok(ClusterIdSettings {
+                cpu: Decimal::new(1, 10),
+                formula: json!({
+                    "p_cpu": "n_cpu * u_cpu",
+                    "p_node": "p_cpu",
+                })
+                .to_string(),
+                ..Default::default()
+            })
+        }
+        async fn insert_or_update(&self, _entity: ClusterIdSettings) -> anyhow::Result<()> {
+            unimplemented!()
+        }
+    }
+
+    fn service() -> (BudgetService, Arc<InMemoryRepository>) {
+        let repo = Arc::new(InMemoryRepository::default());
+        (
+            BudgetService::new(
+                repo.clone(),
+                repo.clone(),
+                repo.clone(),
+                Arc::new(PricingService::new(
+                    repo.clone(),
+                    repo.clone(),
+                    repo.clone(),
+                )),
+                repo.clone(),
+            ),
+            repo,
+        )
+    }
+
+    /// 用户的工作流实例 id
+    fn flow_instance(repo: &InMemoryRepository, user_id: Uuid) -> Uuid {
+        let id = Uuid::new_v4();
+        repo.flow_instances.lock().unwrap().push(FlowInstance {
+            id,
+            user_id,
+            finished: false,
+        });
+        id
+    }
+
+    /// 按测试集群的计费公式，4 核的节点预计花费为 Decimal::new(4, 10)
+    fn estimate() -> ResourceEstimate {
+        ResourceEstimate {
+            cpu: 4,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_budget_without_account_or_budget() {
+        let (service, repo) = service();
+        let decision = service
+            .check_budget(
+                &flow_instance(&repo, Uuid::new_v4()).to_string(),
+                &Uuid::new_v4().to_string(),
+                None,
+                &estimate(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(decision, BudgetDecision::Allowed);
+    }
+
+    #[tokio::test]
+    async fn test_check_budget_follows_budget_action() {
+        let (service, repo) = service();
+        let user_id = Uuid::new_v4();
+        let flow_instance_id = flow_instance(&repo, user_id);
+        service
+            .top_up(&user_id.to_string(), Decimal::new(10, 10), "Top up")
+            .await
+            .unwrap();
+        service
+            .set_budget(Budget {
+                user_id,
+                scope: BudgetScope::Workflow(flow_instance_id),
+                limit: Decimal::new(3, 10),
+                action: BudgetAction::Pause,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let decision = service
+            .check_budget(
+                &flow_instance_id.to_string(),
+                &Uuid::new_v4().to_string(),
+                None,
+                &estimate(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(decision, BudgetDecision::Paused(_)));
+
+        // 其他工作流不受该预算限制
+        let decision = service
+            .check_budget(
+                &flow_instance(&repo, user_id).to_string(),
+                &Uuid::new_v4().to_string(),
+                None,
+                &estimate(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(decision, BudgetDecision::Allowed);
+
+        service
+            .set_budget(Budget {
+                user_id,
+                scope: BudgetScope::User,
+                limit: Decimal::new(3, 10),
+                action: BudgetAction::Refuse,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let decision = service
+            .check_budget(
+                &flow_instance(&repo, user_id).to_string(),
+                &Uuid::new_v4().to_string(),
+                None,
+                &estimate(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(decision, BudgetDecision::Refused(_)));
+    }
+
+    #[tokio::test]
+    async fn test_check_budget_pauses_on_insufficient_balance() {
+        let (service, repo) = service();
+        let user_id = Uuid::new_v4();
+        service
+            .top_up(&user_id.to_string(), Decimal::new(3, 10), "Top up")
+            .await
+            .unwrap();
+        let decision = service
+            .check_budget(
+                &flow_instance(&repo, user_id).to_string(),
+                &Uuid::new_v4().to_string(),
+                None,
+                &estimate(),
+            )
+            .await
+            .unwrap();
+        assert!(matches!(decision, BudgetDecision::Paused(_)));
+    }
+
+    #[tokio::test]
+    async fn test_charge_writes_ledger() {
+        let (service, repo) = service();
+        let user_id = Uuid::new_v4();
+        let flow_instance_id = Uuid::new_v4();
+        service
+            .top_up(&user_id.to_string(), Decimal::new(10, 0), "Top up")
+            .await
+            .unwrap();
+        service
+            .set_budget(Budget {
+                user_id,
+                scope: BudgetScope::Workflow(flow_instance_id),
+                limit: Decimal::new(5, 0),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let reached = service
+            .charge(
+                &user_id.to_string(),
+                &flow_instance_id.to_string(),
+                &Uuid::new_v4().to_string(),
+                Decimal::new(4, 0),
+            )
+            .await
+            .unwrap();
+        assert!(reached.is_empty());
+
+        let account = service.get_account(&user_id.to_string()).await.unwrap().unwrap();
+        assert_eq!(account.balance, Decimal::new(6, 0));
+        assert_eq!(repo.budgets.lock().unwrap()[0].spent, Decimal::new(4, 0));
+        let ledger = repo.ledger.lock().unwrap();
+        assert_eq!(ledger.len(), 2);
+        assert_eq!(ledger[0].kind, LedgerEntryKind::Credit);
+        assert_eq!(ledger[1].kind, LedgerEntryKind::Debit);
+        assert_eq!(ledger[1].amount, Decimal::new(4, 0));
+        assert_eq!(ledger[1].balance, Some(Decimal::new(6, 0)));
+        drop(ledger);
+
+        let reached = service
+            .charge(
+                &user_id.to_string(),
+                &flow_instance_id.to_string(),
+                &Uuid::new_v4().to_string(),
+                Decimal::new(1, 0),
+            )
+            .await
+            .unwrap();
+        assert_eq!(reached.len(), 1);
+        assert_eq!(reached[0].spent, Decimal::new(5, 0));
+    }
+}
@@ -0,0 +1,26 @@
+use crate::prelude::*;
+use std::sync::Arc;
+
+pub struct ClusterIdSettingsService {
+    cluster_setting_repo: Arc<dyn IClusterIdSettingsRepository + Send + Sync>,
+}
+
+impl ClusterIdSettingsService {
+    pub fn new(cluster_setting_repo: Arc<dyn IClusterIdSettingsRepository + Send + Sync>) -> Self {
+        Self {
+            cluster_setting_repo,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl IClusterIdSettingsService for ClusterIdSettingsService {
+    async fn get_settings(&self, cluster_id: &str) -> anyhow::Result<ClusterIdSettings> {
+        self.cluster_setting_repo.get_by_cluster_id(cluster_id).await
+    }
+
+    async fn set_settings(&self, settings: ClusterIdSettings) -> anyhow::Result<()> {
+        settings.validate()?;
+        self.cluster_setting_repo.insert_or_update(settings).await
+    }
+}
@@ -1,17 +1,17 @@
 use crate::prelude::*;
 use alice_architecture::repository::IReadOnlyRepository;
-use evalexpr::{eval_float_with_context, Context, ContextWithMutableVariables};
 use rust_decimal::Decimal;
-use std::{collections::HashMap, str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc};
 use uuid::Uuid;
 
 pub struct FlowNodeBillingService {
     flow_bill_repo: Arc<dyn IFlowInstanceBillingRepository + Send + Sync>,
     node_bill_repo: Arc<dyn INodeInstanceBillingRepository + Send + Sync>,
     node_instance_repo: Arc<dyn IReadOnlyRepository<NodeInstance> + Send + Sync>,
-    cluster_setting_repo: Arc<dyn IClusterIdSettingsRepository + Send + Sync>,
+    pricing_service: Arc<dyn IPricingService + Send + Sync>,
     flow_instance_repo: Arc<dyn IReadOnlyRepository<FlowInstance> + Send + Sync>,
     user_webhook_service: Arc<dyn IUserWebhookService + Send + Sync>,
+    budget_service: Arc<dyn IBudgetService + Send + Sync>,
 }
 
 impl FlowNodeBillingService {
@@ -19,17 +19,19 @@ impl FlowNodeBillingService {
         flow_bill_repo: Arc<dyn IFlowInstanceBillingRepository + Send + Sync>,
         node_bill_repo: Arc<dyn INodeInstanceBillingRepository + Send + Sync>,
         node_instance_repo: Arc<dyn IReadOnlyRepository<NodeInstance> + Send + Sync>,
-        cluster_setting_repo: Arc<dyn IClusterIdSettingsRepository + Send + Sync>,
+        pricing_service: Arc<dyn IPricingService + Send + Sync>,
         flow_instance_repo: Arc<dyn IReadOnlyRepository<FlowInstance> + Send + Sync>,
         user_webhook_service: Arc<dyn IUserWebhookService + Send + Sync>,
+        budget_service: Arc<dyn IBudgetService + Send + Sync>,
     ) -> Self {
         Self {
             flow_bill_repo,
             node_bill_repo,
             node_instance_repo,
-            cluster_setting_repo,
+            pricing_service,
             flow_instance_repo,
             user_webhook_service,
+            budget_service,
         }
     }
 }
@@ -46,6 +48,10 @@ impl IFlowNodeBillingService for FlowNodeBillingService {
     }
 
     async fn record_bill(&self, node_instance_id: &str) -> anyhow::Result<()> {
+        // 消息可能重复投递，节点已计费时不再重复扣费
+        if self.node_bill_repo.get_by_node_instance_id(node_instance_id).await?.is_some() {
+            return Ok(());
+        }
         let node_instance = self.node_instance_repo.get_by_id(node_instance_id).await?;
         let resource_meter = node_instance.resource_meter;
         let cluster_id = node_instance.cluster_id;
@@ -53,79 +59,41 @@ impl IFlowNodeBillingService for FlowNodeBillingService {
         let flow_instance =
             self.flow_instance_repo.get_by_id(flow_instance_id.to_string().as_str()).await?;
         let user_id = flow_instance.user_id;
-        let cluster_settings =
-            self.cluster_setting_repo.get_by_cluster_id(&cluster_id.to_string()).await?;
-        println!("CS:\n\n{cluster_settings:#?}");
-        let formula = cluster_settings.formula;
-
-        let (n_cpu, n_memory, n_storage, n_cpu_time, n_wall_time) = (
-            resource_meter.cpu as f64,
-            resource_meter.max_memory as f64,
-            resource_meter.storage as f64,
-            resource_meter.cpu_time as f64,
-            resource_meter.wall_time as f64,
-        );
-
-        let (u_cpu, u_memory, u_storage, u_cpu_time, u_wall_time) = (
-            cluster_settings.cpu.mantissa() as f64,
-            cluster_settings.memory.mantissa() as f64,
-            cluster_settings.storage.mantissa() as f64,
-            cluster_settings.cpu_time.mantissa() as f64,
-            cluster_settings.wall_time.mantissa() as f64,
-        );
-
-        let mut context = evalexpr::context_map! {
-            "n_cpu" => n_cpu,
-            "n_memory" => n_memory,
-            "n_storage" => n_storage,
-            "n_cpu_time" => n_cpu_time,
-            "n_wall_time"=> n_wall_time,
-            "u_cpu" => u_cpu,
-            "u_memory" => u_memory,
-            "u_storage" => u_storage,
-            "u_cpu_time" => u_cpu_time,
-            "u_wall_time"=> u_wall_time,
-        }?;
-        let mut prices = serde_json::from_str::<HashMap<String, String>>(&formula)?;
-        for (arg, txt) in prices.iter_mut().filter(|(k, _)| k.ne(&"p_node")) {
-            let result = eval_float_with_context(txt, &context)?;
-            context.set_value(arg.into(), result.into())?;
-            let result = Decimal::new(result as i64, 10);
-            let var = arg
-                .strip_prefix("p_")
-                .ok_or(anyhow::anyhow!("prefix error: not start with 'p_'"))?;
-            let n_var_context = context
-                .get_value(&format!("n_{var}"))
-                .ok_or(anyhow::anyhow!("No n_{var} context"))?
-                .to_string();
-            let u_var_context = context
-                .get_value(&format!("u_{var}"))
-                .ok_or(anyhow::anyhow!("No u_{var} context"))?
-                .as_float()?;
-            println!("{u_var_context}");
-            let u_var_context = Decimal::new(u_var_context as i64, 10);
-            println!("{u_var_context}");
-            println!("{}", u_var_context);
-            let value_txt = txt
-                .replace(&format!("u_{var}"), &format!("u_{var}: ({u_var_context})"))
-                .replace(&format!("n_{var}"), &format!("n_{var}: ({n_var_context})"));
-            *txt = format!("{value_txt} = {result}");
-        }
-        let p_node_txt = prices.get_mut("p_node").ok_or(anyhow::anyhow!("No p_node in formula"))?;
-        let p_node = eval_float_with_context(p_node_txt, &context)?;
-        let p_node = Decimal::new(p_node as i64, 10);
-        *p_node_txt = format!("{p_node_txt} = {p_node}");
+        let usage = ResourceEstimate {
+            cpu: resource_meter.cpu,
+            memory: resource_meter.max_memory,
+            storage: resource_meter.storage,
+            cpu_time: resource_meter.cpu_time,
+            wall_time: resource_meter.wall_time,
+        };
+        let segments = self
+            .pricing_service
+            .price(
+                &PricingContext {
+                    cluster_id,
+                    user_id,
+                    partition: resource_meter.partition.to_owned(),
+                    start_time: resource_meter.start_time,
+                    end_time: resource_meter.end_time,
+                },
+                &usage,
+            )
+            .await?;
+        let p_node = segments_price(&segments);
+        let first = segments.first().cloned().unwrap_or_default();
         let node_bill = NodeInstanceBilling {
             id: Uuid::new_v4(),
             node_instance_id: Uuid::from_str(node_instance_id)?,
             flow_instance_id,
-            cpu: n_cpu as i64,
-            memory: n_memory as i64,
-            storage: n_storage as i64,
-            cpu_time: n_cpu_time as i64,
-            wall_time: n_wall_time as i64,
+            cpu: usage.cpu as i64,
+            memory: usage.memory as i64,
+            storage: usage.storage as i64,
+            cpu_time: usage.cpu_time as i64,
+            wall_time: usage.wall_time as i64,
             price: p_node,
-            formula: serde_json::to_string(&prices)?,
+            formula: first.formula,
+            breakdown: first.breakdown,
+            segments,
         };
         let mut flow_bill = match self
             .flow_bill_repo
@@ -150,27 +118,68 @@ impl IFlowNodeBillingService for FlowNodeBillingService {
             }
         };
 
-        flow_bill.cpu += n_cpu as i64;
-        flow_bill.memory += n_memory as i64;
-        flow_bill.storage += n_storage as i64;
-        flow_bill.cpu_time += n_cpu_time as i64;
-        flow_bill.wall_time += n_wall_time as i64;
+        flow_bill.cpu += usage.cpu as i64;
+        flow_bill.memory += usage.memory as i64;
+        flow_bill.storage += usage.storage as i64;
+        flow_bill.cpu_time += usage.cpu_time as i64;
+        flow_bill.wall_time += usage.wall_time as i64;
         flow_bill.total_price += p_node;
 
-        self.node_bill_repo.insert(node_bill).await?;
+        // 节点账单、工作流账单与扣费在同一事务中提交，
+        // 节点账单按节点实例唯一，并发重复计费时只有一次能够提交
+        self.node_bill_repo.insert(node_bill.to_owned()).await?;
         self.flow_bill_repo.insert_or_update(flow_bill).await?;
-        self.flow_bill_repo.save_changed().await?;
+        let reached = self
+            .budget_service
+            .charge(
+                &user_id.to_string(),
+                &flow_instance_id.to_string(),
+                node_instance_id,
+                p_node,
+            )
+            .await?;
+        if let Err(e) = self.flow_bill_repo.save_changed().await {
+            if self.node_bill_repo.get_by_node_instance_id(node_instance_id).await?.is_some() {
+                return Ok(());
+            }
+            return Err(e);
+        }
 
-        let flow_bill = self
-            .flow_bill_repo
-            .get_by_flow_instance_id(flow_instance_id.to_string().as_str())
+        let user_id = user_id.to_string();
+        for budget in reached {
+            self.user_webhook_service
+                .publish(
+                    &user_id,
+                    WebhookEventType::BudgetThresholdReached,
+                    serde_json::to_value(budget)?,
+                )
+                .await?;
+        }
+        self.user_webhook_service
+            .publish(
+                &user_id,
+                WebhookEventType::NodeBilled,
+                serde_json::to_value(node_bill)?,
+            )
             .await?;
-        let node_bills = self
-            .node_bill_repo
-            .get_all_by_flow_instance_id(flow_instance_id.to_string().as_str())
+
+        // 工作流完成且所有节点都已计费后推送一次工作流账单，
+        // 并发计费的最后几个节点中只有一个能够标记成功
+        let flow_instance_id = flow_instance_id.to_string();
+        if !flow_instance.finished
+            || self.node_bill_repo.count_unbilled(&flow_instance_id).await? > 0
+            || !self.flow_bill_repo.settle(&flow_instance_id).await?
+        {
+            return Ok(());
+        }
+        let (flow_bill, node_bills) = self.get_bill(&flow_instance_id).await?;
+        self.user_webhook_service
+            .publish(
+                &user_id,
+                WebhookEventType::WorkflowBilled,
+                serde_json::json!({ "flow_bill": flow_bill, "node_bills": node_bills }),
+            )
             .await?;
-        let message = serde_json::to_string(&(flow_bill, node_bills))?;
-        self.user_webhook_service.send_message(&user_id.to_string(), &message).await?;
         Ok(())
     }
 }
@@ -192,14 +201,15 @@ mod tests {
             node: 1,
             start_time: 1235,
             end_time: 1425,
+            partition: None,
+        };
+        let usage = ResourceEstimate {
+            cpu: resource_meter.cpu,
+            memory: resource_meter.max_memory,
+            storage: resource_meter.storage,
+            cpu_time: resource_meter.cpu_time,
+            wall_time: resource_meter.wall_time,
         };
-        let (cpu, memory, storage, cpu_time, wall_time) = (
-            resource_meter.cpu as f64,
-            resource_meter.max_memory as f64,
-            resource_meter.storage as f64,
-            resource_meter.cpu_time as f64,
-            resource_meter.wall_time as f64,
-        );
         let cluster_settings = ClusterIdSettings {
             id: Uuid::default(),
             cluster_id: Uuid::default(),
@@ -218,60 +228,15 @@ mod tests {
             })
             .to_string(),
         };
-        let (u_cpu, u_memory, u_storage, u_cpu_time, u_wall_time) = (
-            cluster_settings.cpu.mantissa() as f64,
-            cluster_settings.memory.mantissa() as f64,
-            cluster_settings.storage.mantissa() as f64,
-            cluster_settings.cpu_time.mantissa() as f64,
-            cluster_settings.wall_time.mantissa() as f64,
+        let breakdown = cluster_settings.evaluate(&usage).unwrap();
+        assert_eq!(breakdown.items.len(), 6);
+        assert!(breakdown.items[..5].iter().all(|el| el.amount == Decimal::new(1, 10)));
+        assert_eq!(breakdown.price(), Decimal::new(5, 10));
+        assert_eq!(breakdown.variables["u_cpu"], Decimal::new(1, 10));
+        assert_eq!(
+            breakdown.reproduce(&cluster_settings.formula).unwrap(),
+            breakdown
         );
-        let formula = cluster_settings.formula;
-        let mut prices = serde_json::from_str::<HashMap<String, String>>(&formula).unwrap();
-        let mut context = evalexpr::context_map! {
-            "n_cpu" => cpu,
-            "n_memory" => memory,
-            "n_storage" => storage,
-            "n_cpu_time" => cpu_time,
-            "n_wall_time"=> wall_time,
-            "u_cpu" => u_cpu,
-            "u_memory" => u_memory,
-            "u_storage" => u_storage,
-            "u_cpu_time" => u_cpu_time,
-            "u_wall_time"=> u_wall_time,
-        }
-        .unwrap();
-
-        for (arg, txt) in prices.iter_mut().filter(|(k, _)| k.ne(&"p_node")) {
-            let result = eval_float_with_context(txt, &context).unwrap();
-            context.set_value(arg.into(), result.into()).unwrap();
-            let result = Decimal::new(result as i64, 10);
-            let var = arg.strip_prefix("p_").ok_or("prefix error: not start with 'p_'").unwrap();
-            let n_var_context = context
-                .get_value(&format!("n_{var}"))
-                .ok_or(anyhow::anyhow!("No n_{var} context"))
-                .unwrap()
-                .to_string();
-            let u_var_context = context
-                .get_value(&format!("u_{var}"))
-                .ok_or(anyhow::anyhow!("No u_{var} context"))
-                .unwrap()
-                .as_float()
-                .unwrap();
-            println!("{u_var_context}");
-            let u_var_context = Decimal::new(u_var_context as i64, 10);
-            println!("{u_var_context}");
-            println!("{}", u_var_context);
-            let value_txt = txt
-                .replace(&format!("u_{var}"), &format!("u_{var}: ({u_var_context})"))
-                .replace(&format!("n_{var}"), &format!("n_{var}: ({n_var_context})"));
-            *txt = format!("{value_txt} = {result}");
-        }
-        let p_node_txt = prices.get_mut("p_node").unwrap();
-        let p_node = eval_float_with_context(p_node_txt, &context).unwrap();
-        let p_node = Decimal::new(p_node as i64, 10);
-        *p_node_txt = format!("{p_node_txt} = {p_node}");
-
-        println!("{prices:#?}");
     }
 
     #[test]
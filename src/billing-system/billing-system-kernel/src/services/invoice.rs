@@ -0,0 +1,93 @@
+use crate::prelude::*;
+use chrono::Utc;
+use std::{str::FromStr, sync::Arc};
+use uuid::Uuid;
+
+pub struct InvoiceService {
+    node_bill_repo: Arc<dyn INodeInstanceBillingRepository + Send + Sync>,
+    invoice_repo: Arc<dyn IInvoiceRepository + Send + Sync>,
+}
+
+impl InvoiceService {
+    pub fn new(
+        node_bill_repo: Arc<dyn INodeInstanceBillingRepository + Send + Sync>,
+        invoice_repo: Arc<dyn IInvoiceRepository + Send + Sync>,
+    ) -> Self {
+        Self {
+            node_bill_repo,
+            invoice_repo,
+        }
+    }
+
+    async fn aggregate(
+        &self,
+        user_id: &str,
+        scope: StatementScope,
+        year: i32,
+        month: u32,
+    ) -> anyhow::Result<Statement> {
+        let (start, end) = month_period(year, month)?;
+        let records = self.node_bill_repo.get_all_records_by_user_id(user_id, start, end).await?;
+        Ok(Statement::aggregate(
+            Uuid::from_str(user_id)?,
+            scope,
+            (start, end),
+            &records,
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl IInvoiceService for InvoiceService {
+    async fn get_statement(
+        &self,
+        user_id: &str,
+        scope: StatementScope,
+        year: i32,
+        month: u32,
+    ) -> anyhow::Result<Statement> {
+        let (start, _) = month_period(year, month)?;
+        match self.invoice_repo.get_by_period(user_id, &scope, start).await? {
+            Some(invoice) => Ok(invoice.statement),
+            None => self.aggregate(user_id, scope, year, month).await,
+        }
+    }
+
+    async fn close_period(
+        &self,
+        user_id: &str,
+        scope: StatementScope,
+        year: i32,
+        month: u32,
+    ) -> anyhow::Result<Invoice> {
+        let (start, end) = month_period(year, month)?;
+        if end > Utc::now() {
+            anyhow::bail!("Billing period {year}-{month} has not ended yet.");
+        }
+        if let Some(invoice) = self.invoice_repo.get_by_period(user_id, &scope, start).await? {
+            return Ok(invoice);
+        }
+        let statement = self.aggregate(user_id, scope.to_owned(), year, month).await?;
+        let invoice = self.invoice_repo.insert(Invoice::issue(statement)).await?;
+        if let Err(e) = self.invoice_repo.save_changed().await {
+            // 账期被并发关闭时唯一索引拒绝插入，返回已开具的发票
+            return match self.invoice_repo.get_by_period(user_id, &scope, start).await? {
+                Some(invoice) => Ok(invoice),
+                None => Err(e),
+            };
+        }
+        Ok(invoice)
+    }
+
+    async fn get_invoices(&self, user_id: &str) -> anyhow::Result<Vec<Invoice>> {
+        self.invoice_repo.get_all_by_user_id(user_id).await
+    }
+
+    async fn get_invoice(&self, user_id: &str, invoice_id: &str) -> anyhow::Result<Invoice> {
+        let invoice = self.invoice_repo.get_by_id(invoice_id).await?;
+        if invoice.statement.user_id != Uuid::from_str(user_id)? {
+            anyhow::bail!("Invoice {invoice_id} doesn't belong to user {user_id}.");
+        }
+        Ok(invoice)
+    }
+}
@@ -1,5 +1,13 @@
+pub mod budget;
+pub mod cluster_id_settings;
 pub mod flow_node_billing;
+pub mod invoice;
+pub mod pricing;
 
 pub mod prelude {
+    pub use super::budget::*;
+    pub use super::cluster_id_settings::*;
     pub use super::flow_node_billing::*;
+    pub use super::invoice::*;
+    pub use super::pricing::*;
 }
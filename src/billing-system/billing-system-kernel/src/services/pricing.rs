@@ -0,0 +1,164 @@
+use crate::prelude::*;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::{str::FromStr, sync::Arc};
+use uuid::Uuid;
+
+pub struct PricingService {
+    rule_repo: Arc<dyn IPricingRuleRepository + Send + Sync>,
+    user_group_repo: Arc<dyn IUserGroupRepository + Send + Sync>,
+    cluster_setting_repo: Arc<dyn IClusterIdSettingsRepository + Send + Sync>,
+}
+
+impl PricingService {
+    pub fn new(
+        rule_repo: Arc<dyn IPricingRuleRepository + Send + Sync>,
+        user_group_repo: Arc<dyn IUserGroupRepository + Send + Sync>,
+        cluster_setting_repo: Arc<dyn IClusterIdSettingsRepository + Send + Sync>,
+    ) -> Self {
+        Self {
+            rule_repo,
+            user_group_repo,
+            cluster_setting_repo,
+        }
+    }
+}
+
+/// 任务的运行时段，时间未知时按结束时刻（或当前时刻）计价
+fn running_period(context: &PricingContext) -> anyhow::Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let timestamp = |secs: i64| {
+        DateTime::<Utc>::from_timestamp(secs, 0).ok_or(anyhow::anyhow!("Invalid timestamp {secs}."))
+    };
+    let end = if context.end_time > 0 {
+        timestamp(context.end_time)?
+    } else {
+        Utc::now()
+    };
+    let start = if context.start_time > 0 && context.start_time <= context.end_time {
+        timestamp(context.start_time)?
+    } else {
+        end
+    };
+    Ok((start, end))
+}
+
+#[async_trait::async_trait]
+impl IPricingService for PricingService {
+    async fn get_rules(&self, cluster_id: &str) -> anyhow::Result<Vec<PricingRule>> {
+        self.rule_repo.get_all_by_cluster_id(cluster_id).await
+    }
+
+    async fn add_rule(&self, rule: PricingRule) -> anyhow::Result<PricingRule> {
+        let rule = PricingRule {
+            id: Uuid::new_v4(),
+            effective_from: rule.effective_from.max(Utc::now()),
+            ..rule
+        };
+        rule.validate()?;
+        let rule = self.rule_repo.insert(rule).await?;
+        self.rule_repo.save_changed().await?;
+        Ok(rule)
+    }
+
+    async fn expire_rule(
+        &self,
+        rule_id: &str,
+        effective_to: DateTime<Utc>,
+    ) -> anyhow::Result<PricingRule> {
+        let rule = self.rule_repo.get_by_id(rule_id).await?;
+        let now = Utc::now();
+        if rule.effective_to.map(|el| el <= now).unwrap_or(false) {
+            anyhow::bail!("Pricing rule {rule_id} has already expired.");
+        }
+        let rule = PricingRule {
+            effective_to: Some(effective_to.max(now).max(rule.effective_from)),
+            ..rule
+        };
+        let rule = self.rule_repo.update(rule).await?;
+        self.rule_repo.save_changed().await?;
+        Ok(rule)
+    }
+
+    async fn set_user_group(&self, user_id: &str, name: &str) -> anyhow::Result<UserGroup> {
+        let user_group = match self.user_group_repo.get_by_user_id(user_id).await? {
+            Some(user_group) => {
+                self.user_group_repo
+                    .update(UserGroup {
+                        name: name.to_owned(),
+                        ..user_group
+                    })
+                    .await?
+            }
+            None => {
+                self.user_group_repo
+                    .insert(UserGroup {
+                        id: Uuid::new_v4(),
+                        user_id: Uuid::from_str(user_id)?,
+                        name: name.to_owned(),
+                    })
+                    .await?
+            }
+        };
+        self.user_group_repo.save_changed().await?;
+        Ok(user_group)
+    }
+
+    async fn price(
+        &self,
+        context: &PricingContext,
+        usage: &ResourceEstimate,
+    ) -> anyhow::Result<Vec<PricingSegment>> {
+        let cluster_id = context.cluster_id.to_string();
+        let rules = self.rule_repo.get_all_by_cluster_id(&cluster_id).await?;
+        let user_group = self
+            .user_group_repo
+            .get_by_user_id(&context.user_id.to_string())
+            .await?
+            .map(|el| el.name);
+        let (start, end) = running_period(context)?;
+        let duration = (end - start).num_seconds();
+
+        let mut cluster_settings: Option<ClusterIdSettings> = None;
+        let mut result = vec![];
+        for (rule, from, to) in split_by_rules(
+            &rules,
+            context.partition.as_deref(),
+            user_group.as_deref(),
+            start,
+            end,
+        ) {
+            let settings = match (rule, &cluster_settings) {
+                (Some(rule), _) => rule.settings(),
+                (None, Some(settings)) => settings.to_owned(),
+                (None, None) => {
+                    let settings = self.cluster_setting_repo.get_by_cluster_id(&cluster_id).await?;
+                    cluster_settings = Some(settings.to_owned());
+                    settings
+                }
+            };
+            let breakdown = settings.evaluate(usage)?;
+            let (weight, amount) = if duration > 0 {
+                let seconds = Decimal::from((to - from).num_seconds());
+                let duration = Decimal::from(duration);
+                let amount = breakdown
+                    .total
+                    .checked_mul(seconds)
+                    .and_then(|el| el.checked_div(duration))
+                    .ok_or(anyhow::anyhow!("Price of segment overflowed."))?;
+                (seconds / duration, amount)
+            } else {
+                (Decimal::ONE, breakdown.total)
+            };
+            result.push(PricingSegment {
+                rule_id: rule.map(|el| el.id),
+                start_time: from.timestamp(),
+                end_time: to.timestamp(),
+                weight,
+                formula: settings.formula,
+                breakdown,
+                amount,
+            });
+        }
+        Ok(result)
+    }
+}
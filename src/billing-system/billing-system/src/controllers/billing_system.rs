@@ -1,12 +1,14 @@
-use crate::infrastructure::ServiceProvider;
+use crate::infrastructure::{BillingConfig, ServiceProvider};
 use actix_web::web::Path;
-use actix_web::{get, post, web};
+use actix_web::{get, http::header, post, web, HttpMessage, HttpRequest, HttpResponse};
 use alice_architecture::base_dto::ResponseBase;
 use alice_di::{actix_auto_inject, IServiceProvider};
 use billing_system_kernel::prelude::*;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use uuid::Uuid;
 
 #[actix_auto_inject(ServiceProvider, scoped = "None")]
 #[alice_web_macro::http_request]
@@ -15,17 +17,89 @@ use std::sync::Arc;
 pub async fn webhook_subscribe(
     url: web::Json<Url>,
     #[inject] service: Arc<dyn IUserWebhookService + Send + Sync>,
-) -> web::Json<ResponseBase<String>> {
-    // let user_id = match user_info {
-    //     Some(el) => el.user_id,
-    //     None => return web::Json(ResponseBase::err(500, "Interval error")),
-    // };
+) -> web::Json<ResponseBase<UserWebhookDto>> {
+    let user_id = user_info.unwrap().user_id;
     let url = url.0;
-    let user_id = url.user_id;
-    let url = url.url;
-    match service.register_webhook(&user_id, &url).await {
+    match service.register_webhook(&user_id, &url.url, url.event_types).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(UserWebhookDto::with_secret(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetWebhooks")]
+pub async fn get_webhooks(
+    #[inject] service: Arc<dyn IUserWebhookService + Send + Sync>,
+) -> web::Json<ResponseBase<Vec<UserWebhookDto>>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.get_webhooks(&user_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(
+            el.into_iter().map(UserWebhookDto::from).collect(),
+        ))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/RemoveWebhook")]
+pub async fn remove_webhook(
+    request: web::Json<RemoveWebhookRequest>,
+    #[inject] service: Arc<dyn IUserWebhookService + Send + Sync>,
+) -> web::Json<ResponseBase<String>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.remove_webhook(&user_id, &request.id).await {
         Ok(_) => web::Json(ResponseBase::ok(None)),
-        Err(_) => web::Json(ResponseBase::err(500, "Interval error")),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetWebhookDeliveries")]
+pub async fn get_webhook_deliveries(
+    #[inject] service: Arc<dyn IUserWebhookService + Send + Sync>,
+) -> web::Json<ResponseBase<Vec<WebhookDeliveryDto>>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.get_deliveries(&user_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(
+            el.into_iter().map(WebhookDeliveryDto::from).collect(),
+        ))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/ReplayWebhookDelivery")]
+pub async fn replay_webhook_delivery(
+    request: web::Json<ReplayWebhookDeliveryRequest>,
+    #[inject] service: Arc<dyn IUserWebhookService + Send + Sync>,
+) -> web::Json<ResponseBase<WebhookDeliveryDto>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.replay(&user_id, &request.id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(WebhookDeliveryDto::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
     }
 }
 
@@ -49,6 +123,447 @@ pub async fn get_flow_nodes_bill(
     }
 }
 
+/// 由 co 在节点分发前调用，以共享密钥签名认证，不使用用户令牌
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[post("billing-system/CheckBudget")]
+pub async fn check_budget(
+    body: web::Bytes,
+    #[inject] service: Arc<dyn IBudgetService + Send + Sync>,
+    #[inject] billing_config: BillingConfig,
+) -> web::Json<ResponseBase<BudgetDecision>> {
+    let header = |name: &str| raw_req.headers().get(name).and_then(|el| el.to_str().ok());
+    let timestamp = header(BUDGET_CHECK_TIMESTAMP_HEADER).and_then(|el| el.parse::<i64>().ok());
+    let signature = header(BUDGET_CHECK_SIGNATURE_HEADER);
+    let verified = match (timestamp, signature) {
+        (Some(timestamp), Some(signature)) => verify_budget_check(
+            billing_config.budget().secret(),
+            timestamp,
+            &body,
+            signature,
+            Utc::now().timestamp(),
+        ),
+        _ => Err(anyhow::anyhow!("Budget check request isn't signed.")),
+    };
+    if let Err(e) = verified {
+        log::error!("{e}");
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let request: CheckBudgetRequest = match serde_json::from_slice(&body) {
+        Ok(el) => el,
+        Err(e) => {
+            return web::Json(ResponseBase::err(400, &e.to_string()));
+        }
+    };
+    match service
+        .check_budget(
+            &request.flow_instance_id,
+            &request.cluster_id,
+            request.partition.as_deref(),
+            &request.estimate,
+        )
+        .await
+    {
+        Ok(el) => web::Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/TopUp")]
+pub async fn top_up(
+    request: web::Json<TopUpRequest>,
+    #[inject] service: Arc<dyn IBudgetService + Send + Sync>,
+) -> web::Json<ResponseBase<AccountResponse>> {
+    if !user_info.unwrap().is_admin() {
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let request = request.0;
+    match service.top_up(&request.user_id, request.amount, &request.description).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(AccountResponse::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/SetBudget")]
+pub async fn set_budget(
+    request: web::Json<SetBudgetRequest>,
+    #[inject] service: Arc<dyn IBudgetService + Send + Sync>,
+) -> web::Json<ResponseBase<BudgetResponse>> {
+    if !user_info.unwrap().is_admin() {
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let request = request.0;
+    let budget = Budget {
+        id: Uuid::default(),
+        user_id: request.user_id,
+        scope: match request.flow_instance_id {
+            Some(id) => BudgetScope::Workflow(id),
+            None => BudgetScope::User,
+        },
+        limit: request.limit,
+        spent: Decimal::ZERO,
+        action: request.action,
+    };
+    match service.set_budget(budget).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(BudgetResponse::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetAccount")]
+pub async fn get_account(
+    #[inject] service: Arc<dyn IBudgetService + Send + Sync>,
+) -> web::Json<ResponseBase<AccountResponse>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.get_account(&user_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(el.map(AccountResponse::from))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetBudgets")]
+pub async fn get_budgets(
+    #[inject] service: Arc<dyn IBudgetService + Send + Sync>,
+) -> web::Json<ResponseBase<Vec<BudgetResponse>>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.get_budgets(&user_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(
+            el.into_iter().map(BudgetResponse::from).collect(),
+        ))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetLedger")]
+pub async fn get_ledger(
+    #[inject] service: Arc<dyn IBudgetService + Send + Sync>,
+) -> web::Json<ResponseBase<Vec<LedgerEntryResponse>>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.get_ledger(&user_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(
+            el.into_iter().map(LedgerEntryResponse::from).collect(),
+        ))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetStatement")]
+pub async fn get_statement(
+    request: web::Query<StatementRequest>,
+    #[inject] service: Arc<dyn IInvoiceService + Send + Sync>,
+) -> web::Json<ResponseBase<StatementDto>> {
+    let user_id = user_info.unwrap().user_id;
+    let request = request.into_inner();
+    match service
+        .get_statement(&user_id, request.scope(), request.year, request.month)
+        .await
+    {
+        Ok(el) => web::Json(ResponseBase::ok(Some(StatementDto::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[get("billing-system/ExportStatement")]
+pub async fn export_statement(
+    request: web::Query<StatementRequest>,
+    #[inject] service: Arc<dyn IInvoiceService + Send + Sync>,
+) -> HttpResponse {
+    let user_id = match authorized_user_id(&raw_req) {
+        Some(el) => el,
+        None => return HttpResponse::Unauthorized().finish(),
+    };
+    let request = request.into_inner();
+    match service
+        .get_statement(&user_id, request.scope(), request.year, request.month)
+        .await
+    {
+        Ok(el) => csv_response(
+            &format!("statement-{}-{:02}", request.year, request.month),
+            el.to_csv(),
+        ),
+        Err(e) => {
+            log::error!("{e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/CloseInvoicePeriod")]
+pub async fn close_invoice_period(
+    request: web::Json<CloseInvoicePeriodRequest>,
+    #[inject] service: Arc<dyn IInvoiceService + Send + Sync>,
+) -> web::Json<ResponseBase<InvoiceDto>> {
+    if !user_info.unwrap().is_admin() {
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let request = request.0;
+    match service
+        .close_period(
+            &request.user_id,
+            request.period.scope(),
+            request.period.year,
+            request.period.month,
+        )
+        .await
+    {
+        Ok(el) => web::Json(ResponseBase::ok(Some(InvoiceDto::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetInvoices")]
+pub async fn get_invoices(
+    #[inject] service: Arc<dyn IInvoiceService + Send + Sync>,
+) -> web::Json<ResponseBase<Vec<InvoiceDto>>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.get_invoices(&user_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(
+            el.into_iter().map(InvoiceDto::from).collect(),
+        ))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetInvoice")]
+pub async fn get_invoice(
+    request: web::Query<InvoiceRequest>,
+    #[inject] service: Arc<dyn IInvoiceService + Send + Sync>,
+) -> web::Json<ResponseBase<InvoiceDto>> {
+    let user_id = user_info.unwrap().user_id;
+    match service.get_invoice(&user_id, &request.id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(InvoiceDto::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[get("billing-system/ExportInvoice")]
+pub async fn export_invoice(
+    request: web::Query<InvoiceRequest>,
+    #[inject] service: Arc<dyn IInvoiceService + Send + Sync>,
+) -> HttpResponse {
+    let user_id = match authorized_user_id(&raw_req) {
+        Some(el) => el,
+        None => return HttpResponse::Unauthorized().finish(),
+    };
+    match service.get_invoice(&user_id, &request.id).await {
+        Ok(el) => csv_response(&format!("invoice-{}", el.id), el.statement.to_csv()),
+        Err(e) => {
+            log::error!("{e}");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// 导出文件的接口不返回 JSON，无法使用 `authorize` 宏，在此取得登录用户
+fn authorized_user_id(raw_req: &HttpRequest) -> Option<String> {
+    raw_req
+        .extensions()
+        .get::<alice_architecture::authorization::UserInfo>()
+        .map(|el| el.user_id.to_owned())
+}
+
+fn csv_response(name: &str, content: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{name}.csv\""),
+        ))
+        .body(content)
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetClusterSettings/{cluster_id}")]
+pub async fn get_cluster_settings(
+    cluster_id: Path<String>,
+    #[inject] service: Arc<dyn IClusterIdSettingsService + Send + Sync>,
+) -> web::Json<ResponseBase<ClusterSettingsDto>> {
+    match service.get_settings(&cluster_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(ClusterSettingsDto::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/SetClusterSettings")]
+pub async fn set_cluster_settings(
+    request: web::Json<ClusterSettingsDto>,
+    #[inject] service: Arc<dyn IClusterIdSettingsService + Send + Sync>,
+) -> web::Json<ResponseBase<String>> {
+    if !user_info.unwrap().is_admin() {
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let settings = ClusterIdSettings::from(request.0);
+    if let Err(e) = settings.validate() {
+        log::error!("{e}");
+        return web::Json(ResponseBase::err(400, &e.to_string()));
+    }
+    match service.set_settings(settings).await {
+        Ok(_) => web::Json(ResponseBase::ok(None)),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("billing-system/GetPricingRules/{cluster_id}")]
+pub async fn get_pricing_rules(
+    cluster_id: Path<String>,
+    #[inject] service: Arc<dyn IPricingService + Send + Sync>,
+) -> web::Json<ResponseBase<Vec<PricingRuleDto>>> {
+    match service.get_rules(&cluster_id).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(
+            el.into_iter().map(PricingRuleDto::from).collect(),
+        ))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/AddPricingRule")]
+pub async fn add_pricing_rule(
+    request: web::Json<PricingRuleDto>,
+    #[inject] service: Arc<dyn IPricingService + Send + Sync>,
+) -> web::Json<ResponseBase<PricingRuleDto>> {
+    if !user_info.unwrap().is_admin() {
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let rule = PricingRule::from(request.0);
+    if let Err(e) = rule.validate() {
+        log::error!("{e}");
+        return web::Json(ResponseBase::err(400, &e.to_string()));
+    }
+    match service.add_rule(rule).await {
+        Ok(el) => web::Json(ResponseBase::ok(Some(PricingRuleDto::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/ExpirePricingRule")]
+pub async fn expire_pricing_rule(
+    request: web::Json<ExpirePricingRuleRequest>,
+    #[inject] service: Arc<dyn IPricingService + Send + Sync>,
+) -> web::Json<ResponseBase<PricingRuleDto>> {
+    if !user_info.unwrap().is_admin() {
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let request = request.0;
+    match service
+        .expire_rule(&request.id, request.effective_to.unwrap_or_else(Utc::now))
+        .await
+    {
+        Ok(el) => web::Json(ResponseBase::ok(Some(PricingRuleDto::from(el)))),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("billing-system/SetUserGroup")]
+pub async fn set_user_group(
+    request: web::Json<SetUserGroupRequest>,
+    #[inject] service: Arc<dyn IPricingService + Send + Sync>,
+) -> web::Json<ResponseBase<String>> {
+    if !user_info.unwrap().is_admin() {
+        return web::Json(ResponseBase::err(403, "Forbidden"));
+    }
+    let request = request.0;
+    match service.set_user_group(&request.user_id, &request.name).await {
+        Ok(_) => web::Json(ResponseBase::ok(None)),
+        Err(e) => {
+            log::error!("{e}");
+            web::Json(ResponseBase::err(500, "Interval error"))
+        }
+    }
+}
+
 #[alice_di::auto_inject(ServiceProvider, scoped = "None")]
 #[alice_web_macro::message_consumer]
 pub async fn bill_consumer(
@@ -66,8 +581,80 @@ pub struct NodeInstanceId {
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Url {
-    pub user_id: String,
     pub url: String,
+    /// 为空时订阅所有事件
+    #[serde(default)]
+    pub event_types: Vec<WebhookEventType>,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserWebhookDto {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    /// 签名密钥，只在订阅时返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub event_types: Vec<WebhookEventType>,
+}
+impl From<UserWebhook> for UserWebhookDto {
+    fn from(value: UserWebhook) -> Self {
+        Self {
+            id: value.id,
+            user_id: value.user_id,
+            url: value.url,
+            secret: None,
+            event_types: value.event_types,
+        }
+    }
+}
+impl UserWebhookDto {
+    fn with_secret(value: UserWebhook) -> Self {
+        Self {
+            secret: Some(value.secret.to_owned()),
+            ..Self::from(value)
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveWebhookRequest {
+    pub id: String,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryDto {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub payload: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_time: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_time: DateTime<Utc>,
+    pub delivered_time: Option<DateTime<Utc>>,
+}
+impl From<WebhookDelivery> for WebhookDeliveryDto {
+    fn from(value: WebhookDelivery) -> Self {
+        Self {
+            id: value.id,
+            webhook_id: value.webhook_id,
+            event_type: value.event_type,
+            payload: value.payload,
+            status: value.status,
+            attempts: value.attempts,
+            next_attempt_time: value.next_attempt_time,
+            last_error: value.last_error,
+            created_time: value.created_time,
+            delivered_time: value.delivered_time,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayWebhookDeliveryRequest {
+    pub id: String,
 }
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -98,6 +685,8 @@ impl From<(FlowInstanceBilling, Vec<NodeInstanceBilling>)> for FlowBillResponse
                     wall_time: el.wall_time,
                     price: el.price,
                     formula: el.formula,
+                    breakdown: el.breakdown,
+                    segments: el.segments,
                 })
                 .collect::<Vec<_>>(),
         }
@@ -124,4 +713,295 @@ pub struct NodeBill {
     pub wall_time: i64,
     pub price: Decimal,
     pub formula: String,
+    pub breakdown: PriceBreakdown,
+    pub segments: Vec<PricingSegment>,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckBudgetRequest {
+    pub flow_instance_id: String,
+    pub cluster_id: String,
+    /// 任务使用的分区或队列
+    #[serde(default)]
+    pub partition: Option<String>,
+    pub estimate: ResourceEstimate,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopUpRequest {
+    pub user_id: String,
+    pub amount: Decimal,
+    #[serde(default)]
+    pub description: String,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBudgetRequest {
+    pub user_id: Uuid,
+    /// 为空时设置用户的全部工作流的预算
+    pub flow_instance_id: Option<Uuid>,
+    pub limit: Decimal,
+    #[serde(default)]
+    pub action: BudgetAction,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountResponse {
+    pub user_id: String,
+    pub balance: Decimal,
+}
+impl From<Account> for AccountResponse {
+    fn from(value: Account) -> Self {
+        Self {
+            user_id: value.user_id.to_string(),
+            balance: value.balance,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetResponse {
+    pub id: String,
+    pub flow_instance_id: Option<String>,
+    pub limit: Decimal,
+    pub spent: Decimal,
+    pub action: BudgetAction,
+}
+impl From<Budget> for BudgetResponse {
+    fn from(value: Budget) -> Self {
+        Self {
+            id: value.id.to_string(),
+            flow_instance_id: match value.scope {
+                BudgetScope::User => None,
+                BudgetScope::Workflow(id) => Some(id.to_string()),
+            },
+            limit: value.limit,
+            spent: value.spent,
+            action: value.action,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntryResponse {
+    pub id: String,
+    pub kind: LedgerEntryKind,
+    pub amount: Decimal,
+    pub balance: Option<Decimal>,
+    pub flow_instance_id: Option<String>,
+    pub node_instance_id: Option<String>,
+    pub description: String,
+    pub created_time: i64,
+}
+impl From<LedgerEntry> for LedgerEntryResponse {
+    fn from(value: LedgerEntry) -> Self {
+        Self {
+            id: value.id.to_string(),
+            kind: value.kind,
+            amount: value.amount,
+            balance: value.balance,
+            flow_instance_id: value.flow_instance_id.map(|el| el.to_string()),
+            node_instance_id: value.node_instance_id.map(|el| el.to_string()),
+            description: value.description,
+            created_time: value.created_time.timestamp(),
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterSettingsDto {
+    pub cluster_id: Uuid,
+    pub cpu: Decimal,
+    pub memory: Decimal,
+    pub storage: Decimal,
+    pub cpu_time: Decimal,
+    pub wall_time: Decimal,
+    pub formula: String,
+}
+impl From<ClusterIdSettings> for ClusterSettingsDto {
+    fn from(value: ClusterIdSettings) -> Self {
+        Self {
+            cluster_id: value.cluster_id,
+            cpu: value.cpu,
+            memory: value.memory,
+            storage: value.storage,
+            cpu_time: value.cpu_time,
+            wall_time: value.wall_time,
+            formula: value.formula,
+        }
+    }
+}
+impl From<ClusterSettingsDto> for ClusterIdSettings {
+    fn from(value: ClusterSettingsDto) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            cluster_id: value.cluster_id,
+            cpu: value.cpu,
+            memory: value.memory,
+            storage: value.storage,
+            cpu_time: value.cpu_time,
+            wall_time: value.wall_time,
+            formula: value.formula,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingRuleDto {
+    #[serde(default)]
+    pub id: Uuid,
+    pub cluster_id: Uuid,
+    pub partition: Option<String>,
+    pub user_group: Option<String>,
+    pub time_window: Option<TimeWindow>,
+    /// 为空时从当前时间开始生效
+    pub effective_from: Option<DateTime<Utc>>,
+    pub effective_to: Option<DateTime<Utc>>,
+    pub cpu: Decimal,
+    pub memory: Decimal,
+    pub storage: Decimal,
+    pub cpu_time: Decimal,
+    pub wall_time: Decimal,
+    pub formula: String,
+}
+impl From<PricingRule> for PricingRuleDto {
+    fn from(value: PricingRule) -> Self {
+        Self {
+            id: value.id,
+            cluster_id: value.cluster_id,
+            partition: value.partition,
+            user_group: value.user_group,
+            time_window: value.time_window,
+            effective_from: Some(value.effective_from),
+            effective_to: value.effective_to,
+            cpu: value.cpu,
+            memory: value.memory,
+            storage: value.storage,
+            cpu_time: value.cpu_time,
+            wall_time: value.wall_time,
+            formula: value.formula,
+        }
+    }
+}
+impl From<PricingRuleDto> for PricingRule {
+    fn from(value: PricingRuleDto) -> Self {
+        Self {
+            id: value.id,
+            cluster_id: value.cluster_id,
+            partition: value.partition,
+            user_group: value.user_group,
+            time_window: value.time_window,
+            effective_from: value.effective_from.unwrap_or_else(Utc::now),
+            effective_to: value.effective_to,
+            cpu: value.cpu,
+            memory: value.memory,
+            storage: value.storage,
+            cpu_time: value.cpu_time,
+            wall_time: value.wall_time,
+            formula: value.formula,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpirePricingRuleRequest {
+    pub id: String,
+    /// 为空时立即失效
+    pub effective_to: Option<DateTime<Utc>>,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetUserGroupRequest {
+    pub user_id: String,
+    pub name: String,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementRequest {
+    /// 为空时为用户的全部工作流
+    pub flow_instance_id: Option<Uuid>,
+    pub year: i32,
+    pub month: u32,
+}
+impl StatementRequest {
+    fn scope(&self) -> StatementScope {
+        match self.flow_instance_id {
+            Some(id) => StatementScope::Workflow(id),
+            None => StatementScope::User,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceRequest {
+    pub id: String,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseInvoicePeriodRequest {
+    pub user_id: String,
+    #[serde(flatten)]
+    pub period: StatementRequest,
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementLineDto {
+    pub cluster_id: Uuid,
+    pub resource: String,
+    pub quantity: i64,
+    pub amount: Decimal,
+}
+impl From<StatementLine> for StatementLineDto {
+    fn from(value: StatementLine) -> Self {
+        Self {
+            cluster_id: value.cluster_id,
+            resource: value.resource,
+            quantity: value.quantity,
+            amount: value.amount,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementDto {
+    pub user_id: Uuid,
+    pub flow_instance_id: Option<Uuid>,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub node_count: i64,
+    pub lines: Vec<StatementLineDto>,
+    pub total: Decimal,
+}
+impl From<Statement> for StatementDto {
+    fn from(value: Statement) -> Self {
+        Self {
+            user_id: value.user_id,
+            flow_instance_id: match value.scope {
+                StatementScope::User => None,
+                StatementScope::Workflow(id) => Some(id),
+            },
+            period_start: value.period_start,
+            period_end: value.period_end,
+            node_count: value.node_count,
+            lines: value.lines.into_iter().map(StatementLineDto::from).collect(),
+            total: value.total,
+        }
+    }
+}
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceDto {
+    pub id: Uuid,
+    pub issued_time: DateTime<Utc>,
+    #[serde(flatten)]
+    pub statement: StatementDto,
+}
+impl From<Invoice> for InvoiceDto {
+    fn from(value: Invoice) -> Self {
+        Self {
+            id: value.id,
+            issued_time: value.issued_time,
+            statement: StatementDto::from(value.statement),
+        }
+    }
 }
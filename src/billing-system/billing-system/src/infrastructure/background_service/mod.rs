@@ -0,0 +1,2 @@
+pub mod webhook_delivery_runner;
+pub use webhook_delivery_runner::*;
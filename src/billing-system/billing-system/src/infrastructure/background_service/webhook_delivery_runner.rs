@@ -0,0 +1,39 @@
+use crate::infrastructure::ServiceProvider;
+use alice_architecture::hosting::IBackgroundService;
+use alice_di::IServiceProvider;
+use billing_system_kernel::prelude::*;
+use std::{sync::Arc, time::Duration};
+use tokio::time::interval;
+
+/// 检查待推送事件的间隔
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 定时推送队列中到期的 webhook 事件
+pub struct WebhookDeliveryRunner {
+    sp: Arc<ServiceProvider>,
+}
+
+impl WebhookDeliveryRunner {
+    pub fn new(sp: Arc<ServiceProvider>) -> Self {
+        Self { sp }
+    }
+
+    async fn deliver_due(&self) -> anyhow::Result<()> {
+        let sp = self.sp.create_scoped(None)?;
+        let service: Arc<dyn IUserWebhookService + Send + Sync> = sp.provide();
+        service.deliver_due().await
+    }
+}
+
+#[async_trait::async_trait]
+impl IBackgroundService for WebhookDeliveryRunner {
+    async fn run(&self) {
+        let mut interval = interval(DELIVERY_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.deliver_due().await {
+                log::error!("{e}");
+            }
+        }
+    }
+}
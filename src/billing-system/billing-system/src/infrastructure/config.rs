@@ -0,0 +1,17 @@
+use getset::Getters;
+use serde::Deserialize;
+
+#[derive(Default, Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct BillingConfig {
+    #[serde(default)]
+    budget: BudgetConfig,
+}
+
+#[derive(Default, Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct BudgetConfig {
+    /// 与 co 共享的预算检查请求签名密钥，未配置时拒绝所有预算检查请求
+    #[serde(default)]
+    secret: String,
+}
@@ -1,43 +1,162 @@
 use billing_system_kernel::prelude::*;
-use std::{str::FromStr, sync::Arc};
+use chrono::Utc;
+use rand::{distributions::Alphanumeric, Rng};
+use std::{str::FromStr, sync::Arc, time::Duration};
 use uuid::Uuid;
 
+/// 签名密钥长度
+const SECRET_LENGTH: usize = 32;
+/// 单次推送的超时时间
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// 领取的推送记录的保留时间，超过后未完成的记录可被再次领取
+const CLAIM_LEASE_MINUTES: i64 = 30;
+
 pub struct UserWebhookService {
     user_webhook_repo: Arc<dyn IUserWebhookRepository + Send + Sync>,
+    delivery_repo: Arc<dyn IWebhookDeliveryRepository + Send + Sync>,
     client: Arc<reqwest::Client>,
 }
 
 impl UserWebhookService {
     pub fn new(
         user_webhook_repo: Arc<dyn IUserWebhookRepository + Send + Sync>,
+        delivery_repo: Arc<dyn IWebhookDeliveryRepository + Send + Sync>,
         client: Arc<reqwest::Client>,
     ) -> Self {
         Self {
             user_webhook_repo,
+            delivery_repo,
             client,
         }
     }
+
+    async fn get_user_webhook(
+        &self,
+        user_id: &str,
+        webhook_id: &str,
+    ) -> anyhow::Result<UserWebhook> {
+        let webhook = self.user_webhook_repo.get_by_id(webhook_id).await?;
+        if webhook.user_id != Uuid::from_str(user_id)? {
+            anyhow::bail!("Webhook {webhook_id} doesn't belong to user {user_id}.");
+        }
+        Ok(webhook)
+    }
+
+    async fn send(&self, webhook: &UserWebhook, delivery: &WebhookDelivery) -> anyhow::Result<()> {
+        let timestamp = Utc::now().timestamp();
+        let signature = delivery.signature(&webhook.secret, timestamp)?;
+        self.client
+            .post(&webhook.url)
+            .timeout(DELIVERY_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(WEBHOOK_EVENT_HEADER, format!("{:?}", delivery.event_type))
+            .header(WEBHOOK_DELIVERY_HEADER, delivery.id.to_string())
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(WEBHOOK_SIGNATURE_HEADER, format!("sha256={signature}"))
+            .body(delivery.payload.to_owned())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
 impl IUserWebhookService for UserWebhookService {
-    async fn register_webhook(&self, user_id: &str, url: &str) -> anyhow::Result<()> {
+    async fn register_webhook(
+        &self,
+        user_id: &str,
+        url: &str,
+        event_types: Vec<WebhookEventType>,
+    ) -> anyhow::Result<UserWebhook> {
+        reqwest::Url::parse(url)?;
+        let secret = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(SECRET_LENGTH)
+            .map(char::from)
+            .collect();
         let user_webhook = UserWebhook {
             id: Uuid::new_v4(),
             user_id: Uuid::from_str(user_id)?,
             url: url.to_owned(),
+            secret,
+            event_types,
         };
-        self.user_webhook_repo.insert(user_webhook).await?;
+        let user_webhook = self.user_webhook_repo.insert(user_webhook).await?;
+        self.user_webhook_repo.save_changed().await?;
+        Ok(user_webhook)
+    }
+
+    async fn remove_webhook(&self, user_id: &str, webhook_id: &str) -> anyhow::Result<()> {
+        let webhook = self.get_user_webhook(user_id, webhook_id).await?;
+        self.user_webhook_repo.delete_by_id(webhook_id, Some(webhook)).await?;
         self.user_webhook_repo.save_changed().await?;
         Ok(())
     }
-    async fn send_message(&self, user_id: &str, message: &str) -> anyhow::Result<()> {
-        let url = self.user_webhook_repo.get_url_by_user_id(user_id).await?;
 
-        let response = self.client.post(url).json(message).send().await?;
-        let response_text = response.text().await?;
-        println!("finish send");
-        println!("{response_text}");
+    async fn get_webhooks(&self, user_id: &str) -> anyhow::Result<Vec<UserWebhook>> {
+        self.user_webhook_repo.get_all_by_user_id(user_id).await
+    }
+
+    async fn publish(
+        &self,
+        user_id: &str,
+        event_type: WebhookEventType,
+        data: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let webhooks = self.user_webhook_repo.get_all_by_user_id(user_id).await?;
+        let mut published = false;
+        for webhook in webhooks.iter().filter(|el| el.subscribes(event_type)) {
+            let delivery = WebhookDelivery::create(webhook, event_type, data.to_owned())?;
+            self.delivery_repo.insert(delivery).await?;
+            published = true;
+        }
+        if published {
+            self.delivery_repo.save_changed().await?;
+        }
+        Ok(())
+    }
+
+    async fn deliver_due(&self) -> anyhow::Result<()> {
+        let now = Utc::now();
+        let deliveries = self
+            .delivery_repo
+            .claim_due(now, now + chrono::Duration::minutes(CLAIM_LEASE_MINUTES))
+            .await?;
+        for mut delivery in deliveries {
+            match self.user_webhook_repo.get_by_id(&delivery.webhook_id.to_string()).await {
+                Ok(webhook) => match self.send(&webhook, &delivery).await {
+                    Ok(()) => delivery.succeed(Utc::now()),
+                    Err(e) => {
+                        log::warn!("Webhook delivery {} failed: {e}", delivery.id);
+                        delivery.fail(e.to_string(), Utc::now());
+                    }
+                },
+                Err(_) => {
+                    // 订阅已被取消，不再重试
+                    delivery.fail("Webhook has been removed.".to_owned(), Utc::now());
+                    delivery.status = WebhookDeliveryStatus::Failed;
+                }
+            }
+            // 逐条提交，避免中途退出后已推送的记录被再次推送
+            self.delivery_repo.update(delivery).await?;
+            self.delivery_repo.save_changed().await?;
+        }
         Ok(())
     }
+
+    async fn get_deliveries(&self, user_id: &str) -> anyhow::Result<Vec<WebhookDelivery>> {
+        self.delivery_repo.get_all_by_user_id(user_id).await
+    }
+
+    async fn replay(&self, user_id: &str, delivery_id: &str) -> anyhow::Result<WebhookDelivery> {
+        let mut delivery = self.delivery_repo.get_by_id(delivery_id).await?;
+        if delivery.user_id != Uuid::from_str(user_id)? {
+            anyhow::bail!("Webhook delivery {delivery_id} doesn't belong to user {user_id}.");
+        }
+        delivery.replay(Utc::now());
+        let delivery = self.delivery_repo.update(delivery).await?;
+        self.delivery_repo.save_changed().await?;
+        Ok(delivery)
+    }
 }
@@ -4,10 +4,14 @@ use alice_di::IServiceProvider;
 use alice_infrastructure::config::build_config;
 use std::sync::Arc;
 
+pub mod background_service;
+pub mod config;
 pub mod external_services;
 pub mod repositories;
 pub mod service_provider;
 pub mod web_server;
+pub use self::background_service::*;
+pub use self::config::*;
 pub use self::external_services::*;
 pub use self::repositories::*;
 pub use self::service_provider::*;
@@ -0,0 +1,117 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use billing_system_kernel::prelude::*;
+use chrono::Utc;
+use database_model::{
+    sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
+    system::prelude::*,
+    utils::WithDecimalFileds,
+};
+use rust_decimal::Decimal;
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, QueryFilter,
+};
+use std::{str::FromStr, sync::atomic::Ordering};
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<Account> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<Account> {
+        let mut entity = AccountEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
+        entity.rescale_all_to(10);
+        entity.try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<Account>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<Account> for SeaOrmDbRepository {
+    async fn update(&self, entity: Account) -> anyhow::Result<Account> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = AccountModel::try_from(entity.to_owned())?.into_set();
+        let stmt = AccountEntity::update(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: Account) -> anyhow::Result<Account> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = AccountModel::try_from(entity.to_owned())?.into_set();
+        let stmt = AccountEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: Account) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(&self, _uuid: &str, _entity: Option<Account>) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<Account> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl IAccountRepository for SeaOrmDbRepository {
+    async fn get_by_user_id(&self, id: &str) -> anyhow::Result<Option<Account>> {
+        let model = AccountEntity::find()
+            .filter(AccountColumn::UserId.eq(Uuid::from_str(id)?))
+            .one(self.db.get_connection())
+            .await?;
+        model
+            .map(|mut el| {
+                el.rescale_all_to(10);
+                el.try_into()
+            })
+            .transpose()
+    }
+    async fn credit(&self, user_id: &str, amount: Decimal) -> anyhow::Result<()> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = AccountModel::try_from(Account {
+            id: Uuid::new_v4(),
+            user_id: Uuid::from_str(user_id)?,
+            balance: amount,
+        })?
+        .into_set();
+        let stmt = AccountEntity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(AccountColumn::UserId)
+                    .value(
+                        AccountColumn::Balance,
+                        Expr::col((AccountEntity, AccountColumn::Balance)).add(amount),
+                    )
+                    .update_column(AccountColumn::ModifiedTime)
+                    .to_owned(),
+            )
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+    async fn debit(&self, user_id: &str, amount: Decimal) -> anyhow::Result<()> {
+        let mut stmts = self.statements.lock().await;
+        let stmt = AccountEntity::update_many()
+            .col_expr(
+                AccountColumn::Balance,
+                Expr::col(AccountColumn::Balance).sub(amount),
+            )
+            .col_expr(AccountColumn::ModifiedTime, Expr::value(Utc::now()))
+            .filter(AccountColumn::UserId.eq(Uuid::from_str(user_id)?))
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use billing_system_kernel::prelude::*;
+use chrono::Utc;
+use database_model::{
+    sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
+    system::prelude::*,
+    utils::WithDecimalFileds,
+};
+use rust_decimal::Decimal;
+use sea_orm::{sea_query::Expr, ColumnTrait, Condition, QueryFilter};
+use std::{str::FromStr, sync::atomic::Ordering};
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<Budget> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<Budget> {
+        let mut entity = BudgetEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
+        entity.rescale_all_to(10);
+        entity.try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<Budget>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<Budget> for SeaOrmDbRepository {
+    async fn update(&self, entity: Budget) -> anyhow::Result<Budget> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = BudgetModel::try_from(entity.to_owned())?.into_set();
+        let stmt = BudgetEntity::update(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: Budget) -> anyhow::Result<Budget> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = BudgetModel::try_from(entity.to_owned())?.into_set();
+        let stmt = BudgetEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: Budget) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(&self, _uuid: &str, _entity: Option<Budget>) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<Budget> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl IBudgetRepository for SeaOrmDbRepository {
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<Budget>> {
+        let res = BudgetEntity::find()
+            .filter(BudgetColumn::UserId.eq(Uuid::from_str(id)?))
+            .all(self.db.get_connection())
+            .await?;
+        let mut r = vec![];
+        for mut el in res.into_iter() {
+            el.rescale_all_to(10);
+            r.push(el.try_into()?);
+        }
+        Ok(r)
+    }
+    async fn add_spent(
+        &self,
+        user_id: &str,
+        flow_instance_id: Uuid,
+        amount: Decimal,
+    ) -> anyhow::Result<()> {
+        let mut stmts = self.statements.lock().await;
+        let stmt = BudgetEntity::update_many()
+            .col_expr(
+                BudgetColumn::Spent,
+                Expr::col(BudgetColumn::Spent).add(amount),
+            )
+            .col_expr(BudgetColumn::ModifiedTime, Expr::value(Utc::now()))
+            .filter(BudgetColumn::UserId.eq(Uuid::from_str(user_id)?))
+            .filter(
+                Condition::any()
+                    .add(BudgetColumn::FlowInstanceId.is_null())
+                    .add(BudgetColumn::FlowInstanceId.eq(flow_instance_id)),
+            )
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use super::SeaOrmDbRepository;
 use billing_system_kernel::prelude::*;
 use database_model::{system::prelude::*, utils::WithDecimalFileds};
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use sea_orm::{sea_query::OnConflict, ColumnTrait, EntityTrait, QueryFilter};
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -17,4 +17,24 @@ impl IClusterIdSettingsRepository for SeaOrmDbRepository {
         log::debug!("{model:#?}");
         model.try_into()
     }
+
+    async fn insert_or_update(&self, entity: ClusterIdSettings) -> anyhow::Result<()> {
+        ClusterIdSettingsEntity::insert(ClusterIdSettingsModel::try_from(entity)?.into_set())
+            .on_conflict(
+                OnConflict::column(ClusterIdSettingsColumn::ClusterId)
+                    .update_columns([
+                        ClusterIdSettingsColumn::Cpu,
+                        ClusterIdSettingsColumn::Memory,
+                        ClusterIdSettingsColumn::Storage,
+                        ClusterIdSettingsColumn::CpuTime,
+                        ClusterIdSettingsColumn::WallTime,
+                        ClusterIdSettingsColumn::Formula,
+                        ClusterIdSettingsColumn::ModifiedTime,
+                    ])
+                    .to_owned(),
+            )
+            .exec(self.db.get_connection())
+            .await?;
+        Ok(())
+    }
 }
@@ -1,12 +1,17 @@
 use super::SeaOrmDbRepository;
 use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
 use billing_system_kernel::prelude::*;
+use chrono::Utc;
 use database_model::{
     sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
     system::prelude::*,
     utils::WithDecimalFileds,
 };
-use sea_orm::{prelude::Uuid, sea_query::OnConflict, ColumnTrait, QueryFilter};
+use sea_orm::{
+    prelude::Uuid,
+    sea_query::{Expr, OnConflict},
+    ColumnTrait, QueryFilter,
+};
 use std::{str::FromStr, sync::atomic::Ordering};
 
 #[async_trait::async_trait]
@@ -16,7 +21,7 @@ impl IReadOnlyRepository<FlowInstanceBilling> for SeaOrmDbRepository {
             .one(self.db.get_connection())
             .await?
             .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
-        entity.rescale_all_to(2);
+        entity.rescale_all_to(10);
         entity.try_into()
     }
     async fn get_all(&self) -> anyhow::Result<Vec<FlowInstanceBilling>> {
@@ -73,7 +78,7 @@ impl IFlowInstanceBillingRepository for SeaOrmDbRepository {
             .one(self.db.get_connection())
             .await?
             .ok_or(anyhow::anyhow!("No such Flow Instence"))?;
-        model.rescale_all_to(2);
+        model.rescale_all_to(10);
         model.try_into()
     }
     async fn insert_or_update(&self, entity: FlowInstanceBilling) -> anyhow::Result<()> {
@@ -97,4 +102,16 @@ impl IFlowInstanceBillingRepository for SeaOrmDbRepository {
         self.can_drop.store(false, Ordering::Relaxed);
         Ok(())
     }
+    async fn settle(&self, flow_instance_id: &str) -> anyhow::Result<bool> {
+        let res = FlowInstanceBillingEntity::update_many()
+            .col_expr(
+                FlowInstanceBillingColumn::SettledTime,
+                Expr::value(Utc::now()),
+            )
+            .filter(FlowInstanceBillingColumn::FlowInstanceId.eq(Uuid::from_str(flow_instance_id)?))
+            .filter(FlowInstanceBillingColumn::SettledTime.is_null())
+            .exec(self.db.get_connection())
+            .await?;
+        Ok(res.rows_affected == 1)
+    }
 }
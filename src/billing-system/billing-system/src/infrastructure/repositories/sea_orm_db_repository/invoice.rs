@@ -0,0 +1,92 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use billing_system_kernel::prelude::*;
+use chrono::{DateTime, Utc};
+use database_model::{
+    sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
+    system::prelude::*,
+    utils::WithDecimalFileds,
+};
+use sea_orm::{ColumnTrait, QueryFilter, QueryOrder};
+use std::{str::FromStr, sync::atomic::Ordering};
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<Invoice> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<Invoice> {
+        let mut entity = InvoiceEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
+        entity.rescale_all_to(10);
+        entity.try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<Invoice>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<Invoice> for SeaOrmDbRepository {
+    async fn update(&self, _entity: Invoice) -> anyhow::Result<Invoice> {
+        anyhow::bail!("Issued invoices can't be modified.")
+    }
+    async fn insert(&self, entity: Invoice) -> anyhow::Result<Invoice> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = InvoiceModel::try_from(entity.to_owned())?.into_set();
+        let stmt = InvoiceEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: Invoice) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(&self, _uuid: &str, _entity: Option<Invoice>) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<Invoice> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl IInvoiceRepository for SeaOrmDbRepository {
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<Invoice>> {
+        let res = InvoiceEntity::find()
+            .filter(InvoiceColumn::UserId.eq(Uuid::from_str(id)?))
+            .order_by_desc(InvoiceColumn::PeriodStart)
+            .all(self.db.get_connection())
+            .await?;
+        let mut r = vec![];
+        for mut el in res.into_iter() {
+            el.rescale_all_to(10);
+            r.push(el.try_into()?);
+        }
+        Ok(r)
+    }
+    async fn get_by_period(
+        &self,
+        user_id: &str,
+        scope: &StatementScope,
+        period_start: DateTime<Utc>,
+    ) -> anyhow::Result<Option<Invoice>> {
+        let query = InvoiceEntity::find()
+            .filter(InvoiceColumn::UserId.eq(Uuid::from_str(user_id)?))
+            .filter(InvoiceColumn::PeriodStart.eq(period_start));
+        let query = match scope {
+            StatementScope::User => query.filter(InvoiceColumn::FlowInstanceId.is_null()),
+            StatementScope::Workflow(id) => query.filter(InvoiceColumn::FlowInstanceId.eq(*id)),
+        };
+        match query.one(self.db.get_connection()).await? {
+            Some(mut entity) => {
+                entity.rescale_all_to(10);
+                Ok(Some(entity.try_into()?))
+            }
+            None => Ok(None),
+        }
+    }
+}
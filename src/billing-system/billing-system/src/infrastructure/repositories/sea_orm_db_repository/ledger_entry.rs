@@ -0,0 +1,108 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use billing_system_kernel::prelude::*;
+use database_model::{
+    sea_orm::{ConnectionTrait, EntityTrait, QueryTrait, Statement},
+    system::prelude::*,
+    utils::WithDecimalFileds,
+};
+use sea_orm::{ColumnTrait, QueryFilter, QueryOrder};
+use std::{str::FromStr, sync::atomic::Ordering};
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<LedgerEntry> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<LedgerEntry> {
+        let mut entity = LedgerEntryEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
+        entity.rescale_all_to(10);
+        entity.try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<LedgerEntry>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<LedgerEntry> for SeaOrmDbRepository {
+    async fn update(&self, entity: LedgerEntry) -> anyhow::Result<LedgerEntry> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = LedgerEntryModel::try_from(entity.to_owned())?.into_set();
+        let stmt = LedgerEntryEntity::update(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: LedgerEntry) -> anyhow::Result<LedgerEntry> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = LedgerEntryModel::try_from(entity.to_owned())?.into_set();
+        let stmt = LedgerEntryEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: LedgerEntry) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(
+        &self,
+        _uuid: &str,
+        _entity: Option<LedgerEntry>,
+    ) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<LedgerEntry> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl ILedgerEntryRepository for SeaOrmDbRepository {
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<LedgerEntry>> {
+        let res = LedgerEntryEntity::find()
+            .filter(LedgerEntryColumn::UserId.eq(Uuid::from_str(id)?))
+            .order_by_desc(LedgerEntryColumn::CreatedTime)
+            .all(self.db.get_connection())
+            .await?;
+        let mut r = vec![];
+        for mut el in res.into_iter() {
+            el.rescale_all_to(10);
+            r.push(el.try_into()?);
+        }
+        Ok(r)
+    }
+    async fn insert_with_current_balance(&self, entity: LedgerEntry) -> anyhow::Result<()> {
+        let mut stmts = self.statements.lock().await;
+        let model = LedgerEntryModel::try_from(entity)?;
+        // 余额在同一事务中读取，包含此前排队的余额修改
+        let stmt = Statement::from_sql_and_values(
+            self.db.get_connection().get_database_backend(),
+            &[
+                r#"INSERT INTO "ledger_entry" ("id", "user_id", "kind", "amount", "balance","#,
+                r#""flow_instance_id", "node_instance_id", "description", "created_time")"#,
+                r#"VALUES ($1, $2, $3, $4, (SELECT "balance" FROM "account" WHERE "user_id" = $2),"#,
+                r#"$5, $6, $7, $8)"#,
+            ]
+            .join(" "),
+            vec![
+                model.id.into(),
+                model.user_id.into(),
+                model.kind.into(),
+                model.amount.into(),
+                model.flow_instance_id.into(),
+                model.node_instance_id.into(),
+                model.description.into(),
+                model.created_time.into(),
+            ],
+        );
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
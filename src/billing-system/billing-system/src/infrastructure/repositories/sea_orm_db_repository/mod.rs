@@ -6,12 +6,19 @@ use std::sync::{
 };
 use tokio::sync::Mutex;
 
+mod account;
+mod budget;
 mod cluster_id_settings;
 mod flow_instance;
 mod flow_instance_billing;
+mod invoice;
+mod ledger_entry;
 mod node_instance;
 mod node_instance_billing;
+mod pricing_rule;
+mod user_group;
 mod user_webhook;
+mod webhook_delivery;
 
 pub struct SeaOrmDbRepository {
     pub(self) db: Arc<Database>,
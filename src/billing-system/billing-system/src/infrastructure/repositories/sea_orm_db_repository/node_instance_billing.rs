@@ -1,13 +1,14 @@
 use super::SeaOrmDbRepository;
 use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
 use billing_system_kernel::prelude::*;
+use chrono::{DateTime, Utc};
 use database_model::{
     sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
     system::prelude::*,
     utils::WithDecimalFileds,
 };
-use sea_orm::{prelude::Uuid, sea_query::OnConflict, ColumnTrait, QueryFilter};
-use std::{str::FromStr, sync::atomic::Ordering};
+use sea_orm::{prelude::Uuid, sea_query::Query, ColumnTrait, QueryFilter, QueryOrder};
+use std::{collections::HashSet, str::FromStr, sync::atomic::Ordering};
 
 #[async_trait::async_trait]
 impl IReadOnlyRepository<NodeInstanceBilling> for SeaOrmDbRepository {
@@ -16,7 +17,7 @@ impl IReadOnlyRepository<NodeInstanceBilling> for SeaOrmDbRepository {
             .one(self.db.get_connection())
             .await?
             .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
-        entity.rescale_all_to(2);
+        entity.rescale_all_to(10);
         entity.try_into()
     }
     async fn get_all(&self) -> anyhow::Result<Vec<NodeInstanceBilling>> {
@@ -37,16 +38,13 @@ impl IMutableRepository<NodeInstanceBilling> for SeaOrmDbRepository {
     }
     async fn insert(&self, entity: NodeInstanceBilling) -> anyhow::Result<NodeInstanceBilling> {
         log::debug!("nb: {entity:#?}");
-        NodeInstanceBillingEntity::insert(
-            NodeInstanceBillingModel::try_from(entity.to_owned())?.into_set(),
-        )
-        .on_conflict(
-            OnConflict::column(NodeInstanceBillingColumn::NodeInstanceId)
-                .do_nothing()
-                .to_owned(),
-        )
-        .exec(self.db.get_connection())
-        .await?;
+        // 节点实例已计费时违反唯一约束，整个事务回滚，避免重复扣费
+        let mut stmts = self.statements.lock().await;
+        let active_model = NodeInstanceBillingModel::try_from(entity.to_owned())?.into_set();
+        let stmt = NodeInstanceBillingEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
         Ok(entity)
     }
     async fn delete(&self, _entity: NodeInstanceBilling) -> anyhow::Result<bool> {
@@ -79,9 +77,77 @@ impl INodeInstanceBillingRepository for SeaOrmDbRepository {
 
         let mut r = vec![];
         for mut el in res.into_iter() {
-            el.rescale_all_to(2);
+            el.rescale_all_to(10);
             r.push(el.try_into()?);
         }
         Ok(r)
     }
+    async fn count_unbilled(&self, flow_instance_id: &str) -> anyhow::Result<usize> {
+        let flow_instance_id = Uuid::from_str(flow_instance_id)?;
+        let billed = NodeInstanceBillingEntity::find()
+            .filter(NodeInstanceBillingColumn::FlowInstanceId.eq(flow_instance_id))
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.node_instance_id)
+            .collect::<HashSet<_>>();
+        Ok(NodeInstanceEntity::find()
+            .filter(NodeInstanceColumn::FlowInstanceId.eq(flow_instance_id))
+            .all(self.db.get_connection())
+            .await?
+            .iter()
+            .filter(|el| el.is_billable() && !billed.contains(&el.id))
+            .count())
+    }
+    async fn get_by_node_instance_id(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<Option<NodeInstanceBilling>> {
+        let model = NodeInstanceBillingEntity::find()
+            .filter(NodeInstanceBillingColumn::NodeInstanceId.eq(Uuid::from_str(id)?))
+            .one(self.db.get_connection())
+            .await?;
+        model
+            .map(|mut el| {
+                el.rescale_all_to(10);
+                el.try_into()
+            })
+            .transpose()
+    }
+    async fn get_all_records_by_user_id(
+        &self,
+        id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<BillingRecord>> {
+        let user_id = Uuid::from_str(id)?;
+        let res = NodeInstanceBillingEntity::find()
+            .filter(
+                NodeInstanceBillingColumn::FlowInstanceId.in_subquery(
+                    Query::select()
+                        .column(FlowInstanceColumn::Id)
+                        .from(FlowInstanceEntity)
+                        .and_where(FlowInstanceColumn::UserId.eq(user_id))
+                        .to_owned(),
+                ),
+            )
+            .filter(NodeInstanceBillingColumn::CreatedTime.gte(start))
+            .filter(NodeInstanceBillingColumn::CreatedTime.lt(end))
+            .order_by_asc(NodeInstanceBillingColumn::CreatedTime)
+            .find_also_related(NodeInstanceEntity)
+            .all(self.db.get_connection())
+            .await?;
+
+        let mut r = vec![];
+        for (mut el, node_instance) in res.into_iter() {
+            el.rescale_all_to(10);
+            r.push(BillingRecord {
+                user_id,
+                cluster_id: node_instance.and_then(|el| el.cluster_id).unwrap_or_default(),
+                created_time: el.created_time,
+                bill: el.try_into()?,
+            });
+        }
+        Ok(r)
+    }
 }
@@ -0,0 +1,79 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use billing_system_kernel::prelude::*;
+use database_model::{
+    sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
+    system::prelude::*,
+    utils::WithDecimalFileds,
+};
+use sea_orm::{ColumnTrait, QueryFilter};
+use std::{str::FromStr, sync::atomic::Ordering};
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<PricingRule> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<PricingRule> {
+        let mut entity = PricingRuleEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
+        entity.rescale_all_to(10);
+        entity.try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<PricingRule>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<PricingRule> for SeaOrmDbRepository {
+    async fn update(&self, entity: PricingRule) -> anyhow::Result<PricingRule> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = PricingRuleModel::try_from(entity.to_owned())?.into_set();
+        let stmt = PricingRuleEntity::update(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: PricingRule) -> anyhow::Result<PricingRule> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = PricingRuleModel::try_from(entity.to_owned())?.into_set();
+        let stmt = PricingRuleEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: PricingRule) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(
+        &self,
+        _uuid: &str,
+        _entity: Option<PricingRule>,
+    ) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<PricingRule> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl IPricingRuleRepository for SeaOrmDbRepository {
+    async fn get_all_by_cluster_id(&self, id: &str) -> anyhow::Result<Vec<PricingRule>> {
+        let res = PricingRuleEntity::find()
+            .filter(PricingRuleColumn::ClusterId.eq(Uuid::from_str(id)?))
+            .all(self.db.get_connection())
+            .await?;
+        let mut r = vec![];
+        for mut el in res.into_iter() {
+            el.rescale_all_to(10);
+            r.push(el.try_into()?);
+        }
+        Ok(r)
+    }
+}
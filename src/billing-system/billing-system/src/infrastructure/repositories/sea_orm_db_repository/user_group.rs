@@ -0,0 +1,68 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use billing_system_kernel::prelude::*;
+use database_model::{
+    sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
+    system::prelude::*,
+};
+use sea_orm::{ColumnTrait, QueryFilter};
+use std::{str::FromStr, sync::atomic::Ordering};
+use uuid::Uuid;
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<UserGroup> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<UserGroup> {
+        let entity = UserGroupEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
+        entity.try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<UserGroup>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<UserGroup> for SeaOrmDbRepository {
+    async fn update(&self, entity: UserGroup) -> anyhow::Result<UserGroup> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = UserGroupModel::try_from(entity.to_owned())?.into_set();
+        let stmt = UserGroupEntity::update(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: UserGroup) -> anyhow::Result<UserGroup> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = UserGroupModel::try_from(entity.to_owned())?.into_set();
+        let stmt = UserGroupEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: UserGroup) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(&self, _uuid: &str, _entity: Option<UserGroup>) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<UserGroup> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl IUserGroupRepository for SeaOrmDbRepository {
+    async fn get_by_user_id(&self, id: &str) -> anyhow::Result<Option<UserGroup>> {
+        let model = UserGroupEntity::find()
+            .filter(UserGroupColumn::UserId.eq(Uuid::from_str(id)?))
+            .one(self.db.get_connection())
+            .await?;
+        model.map(|el| el.try_into()).transpose()
+    }
+}
@@ -5,15 +5,14 @@ use database_model::{
     sea_orm::{ConnectionTrait, EntityTrait, QueryTrait},
     system::prelude::*,
 };
-use sea_orm::{sea_query::OnConflict, ColumnTrait, QueryFilter};
+use sea_orm::{ColumnTrait, QueryFilter};
 use std::{str::FromStr, sync::atomic::Ordering};
 use uuid::Uuid;
 
 #[async_trait::async_trait]
 impl IReadOnlyRepository<UserWebhook> for SeaOrmDbRepository {
     async fn get_by_id(&self, uuid: &str) -> anyhow::Result<UserWebhook> {
-        let entity = UserWebhookEntity::find()
-            .filter(UserWebhookColumn::UserId.eq(Uuid::from_str(uuid)?))
+        let entity = UserWebhookEntity::find_by_id(Uuid::from_str(uuid)?)
             .one(self.db.get_connection())
             .await?
             .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
@@ -35,25 +34,24 @@ impl IMutableRepository<UserWebhook> for SeaOrmDbRepository {
         Ok(entity)
     }
     async fn insert(&self, entity: UserWebhook) -> anyhow::Result<UserWebhook> {
-        UserWebhookEntity::insert(UserWebhookModel::try_from(entity.to_owned())?.into_set())
-            .on_conflict(
-                OnConflict::column(UserWebhookColumn::UserId)
-                    .update_column(UserWebhookColumn::Url)
-                    .to_owned(),
-            )
-            .exec(self.db.get_connection())
-            .await?;
+        let mut stmts = self.statements.lock().await;
+        let active_model = UserWebhookModel::try_from(entity.to_owned())?.into_set();
+        let stmt = UserWebhookEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
         Ok(entity)
     }
     async fn delete(&self, _entity: UserWebhook) -> anyhow::Result<bool> {
         unimplemented!()
     }
-    async fn delete_by_id(
-        &self,
-        _uuid: &str,
-        _entity: Option<UserWebhook>,
-    ) -> anyhow::Result<bool> {
-        unimplemented!()
+    async fn delete_by_id(&self, uuid: &str, _entity: Option<UserWebhook>) -> anyhow::Result<bool> {
+        let mut stmts = self.statements.lock().await;
+        let stmt = UserWebhookEntity::delete_by_id(Uuid::from_str(uuid)?)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(true)
     }
     async fn save_changed(&self) -> anyhow::Result<bool> {
         self.save_changed().await
@@ -62,12 +60,11 @@ impl IMutableRepository<UserWebhook> for SeaOrmDbRepository {
 impl IDBRepository<UserWebhook> for SeaOrmDbRepository {}
 #[async_trait::async_trait]
 impl IUserWebhookRepository for SeaOrmDbRepository {
-    async fn get_url_by_user_id(&self, id: &str) -> anyhow::Result<String> {
-        let model = UserWebhookEntity::find()
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<UserWebhook>> {
+        let res = UserWebhookEntity::find()
             .filter(UserWebhookColumn::UserId.eq(Uuid::from_str(id)?))
-            .one(self.db.get_connection())
-            .await?
-            .ok_or(anyhow::anyhow!("No such subscribe"))?;
-        Ok(model.url)
+            .all(self.db.get_connection())
+            .await?;
+        res.into_iter().map(|el| el.try_into()).collect()
     }
 }
@@ -0,0 +1,105 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use billing_system_kernel::prelude::*;
+use chrono::{DateTime, Utc};
+use database_model::{
+    sea_orm::{ConnectionTrait, EntityTrait, QueryTrait, Statement},
+    system::prelude::*,
+};
+use sea_orm::{ColumnTrait, QueryFilter, QueryOrder};
+use std::{str::FromStr, sync::atomic::Ordering};
+use uuid::Uuid;
+
+/// 每次推送的最大记录数
+const DUE_LIMIT: i64 = 100;
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<WebhookDelivery> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<WebhookDelivery> {
+        let entity = WebhookDeliveryEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("there is no such row with key {uuid}"))?;
+        entity.try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<WebhookDelivery>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<WebhookDelivery> for SeaOrmDbRepository {
+    async fn update(&self, entity: WebhookDelivery) -> anyhow::Result<WebhookDelivery> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = WebhookDeliveryModel::try_from(entity.to_owned())?.into_set();
+        let stmt = WebhookDeliveryEntity::update(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: WebhookDelivery) -> anyhow::Result<WebhookDelivery> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = WebhookDeliveryModel::try_from(entity.to_owned())?.into_set();
+        let stmt = WebhookDeliveryEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: WebhookDelivery) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(
+        &self,
+        _uuid: &str,
+        _entity: Option<WebhookDelivery>,
+    ) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<WebhookDelivery> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl IWebhookDeliveryRepository for SeaOrmDbRepository {
+    async fn claim_due(
+        &self,
+        now: DateTime<Utc>,
+        lease_until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<WebhookDelivery>> {
+        // 推迟下次推送时间作为领取标记，其他实例跳过已被锁定的记录
+        let res = WebhookDeliveryEntity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                self.db.get_connection().get_database_backend(),
+                &[
+                    r#"UPDATE "webhook_delivery" SET "next_attempt_time" = $1"#,
+                    r#"WHERE "id" IN (SELECT "id" FROM "webhook_delivery""#,
+                    r#"WHERE "status" = $2 AND "next_attempt_time" <= $3"#,
+                    r#"ORDER BY "next_attempt_time" LIMIT $4 FOR UPDATE SKIP LOCKED)"#,
+                    r#"RETURNING *"#,
+                ]
+                .join(" "),
+                vec![
+                    lease_until.into(),
+                    (WebhookDeliveryStatus::Pending as i32).into(),
+                    now.into(),
+                    DUE_LIMIT.into(),
+                ],
+            ))
+            .all(self.db.get_connection())
+            .await?;
+        res.into_iter().map(|el| el.try_into()).collect()
+    }
+    async fn get_all_by_user_id(&self, id: &str) -> anyhow::Result<Vec<WebhookDelivery>> {
+        let res = WebhookDeliveryEntity::find()
+            .filter(WebhookDeliveryColumn::UserId.eq(Uuid::from_str(id)?))
+            .order_by_desc(WebhookDeliveryColumn::CreatedTime)
+            .all(self.db.get_connection())
+            .await?;
+        res.into_iter().map(|el| el.try_into()).collect()
+    }
+}
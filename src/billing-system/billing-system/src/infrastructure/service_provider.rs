@@ -1,4 +1,7 @@
-use super::{sea_orm_db_repository::SeaOrmDbRepository, user_webhook::UserWebhookService};
+use super::{
+    sea_orm_db_repository::SeaOrmDbRepository, user_webhook::UserWebhookService, BillingConfig,
+    WebhookDeliveryRunner,
+};
 use crate::controllers;
 use alice_architecture::hosting::IBackgroundService;
 use alice_di::{build_container, IServiceProvider};
@@ -7,10 +10,11 @@ use alice_infrastructure::{
     message_queue::{
         InternalMessageQueueProducer, KafkaMessageQueue, KafkaSingleTopicMessageQueueConsumer,
     },
+    middleware::authorization::{IKeyStorage, KeyStorage},
     ConsumerFn,
 };
 use billing_system_kernel::prelude::*;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 build_container! {
     #[derive(Clone)]
@@ -23,6 +27,12 @@ build_container! {
             common_config
         }
     }
+    billing_config: BillingConfig {
+        build {
+            let billing_config: BillingConfig = config.clone().try_deserialize()?;
+            billing_config
+        }
+    }
     database: Arc<Database> {
         build async {
             Arc::new(Database::new(common_config.db().url()).await)
@@ -42,23 +52,52 @@ build_container! {
             result
         }
     }
-    scoped http_client: Arc<reqwest::Client> {
+    http_client: Arc<reqwest::Client> {
         build {
             Arc::new(reqwest::Client::builder().connect_timeout(std::time::Duration::from_secs(2)).build()?)
         }
     }
+    key_storage: Arc<dyn IKeyStorage + Send + Sync> {
+        build {
+            Arc::new(KeyStorage::new(Arc::new(std::sync::Mutex::new(HashMap::new()))))
+        }
+    }
 
     scoped user_webhook_service: Arc<dyn IUserWebhookService + Send +Sync>{
         build {
             let repo = sea_orm_repository.clone();
-            Arc::new(UserWebhookService::new(repo,http_client.clone()))
+            Arc::new(UserWebhookService::new(repo.clone(), repo, self.http_client.clone()))
+        }
+    }
+    scoped pricing_service: Arc<dyn IPricingService + Send + Sync>{
+        build {
+            let repo = sea_orm_repository.clone();
+            Arc::new(PricingService::new(repo.clone(), repo.clone(), repo))
+        }
+    }
+    scoped budget_service: Arc<dyn IBudgetService + Send + Sync>{
+        build {
+            let repo = sea_orm_repository.clone();
+            Arc::new(BudgetService::new(repo.clone(), repo.clone(), repo.clone(), pricing_service.clone(), repo))
+        }
+    }
+    scoped cluster_id_settings_service: Arc<dyn IClusterIdSettingsService + Send + Sync>{
+        build {
+            let repo = sea_orm_repository.clone();
+            Arc::new(ClusterIdSettingsService::new(repo))
+        }
+    }
+    scoped invoice_service: Arc<dyn IInvoiceService + Send + Sync>{
+        build {
+            let repo = sea_orm_repository.clone();
+            Arc::new(InvoiceService::new(repo.clone(), repo))
         }
     }
     scoped billing_service: Arc<dyn IFlowNodeBillingService + Send +Sync>{
         build {
             let repo = sea_orm_repository.clone();
             let service = user_webhook_service.clone();
-            Arc::new(FlowNodeBillingService::new(repo.clone(), repo.clone(), repo.clone(),repo.clone(),repo,service))
+            Arc::new(FlowNodeBillingService::new(repo.clone(), repo.clone(), repo.clone(),pricing_service.clone(),repo,service,budget_service.clone()))
         }
     }
 
@@ -80,7 +119,8 @@ build_container! {
         let config: alice_infrastructure::config::CommonConfig = arc_sp.provide();
         let client_options = config.mq().client_options().clone();
         let topics = config.mq().topics();
-        let message_queue = Arc::new(KafkaSingleTopicMessageQueueConsumer::new(topics,client_options, arc_sp, consumers));
-        sp.background_services.push(message_queue)
+        let message_queue = Arc::new(KafkaSingleTopicMessageQueueConsumer::new(topics,client_options, arc_sp.clone(), consumers));
+        sp.background_services.push(message_queue);
+        sp.background_services.push(Arc::new(WebhookDeliveryRunner::new(arc_sp)));
     }
 }
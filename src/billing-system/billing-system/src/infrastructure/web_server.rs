@@ -2,11 +2,12 @@ use super::ServiceProvider;
 use crate::controllers;
 use actix_easy_multipart::MultipartFormConfig;
 use alice_di::IServiceProvider;
-use alice_infrastructure::config::CommonConfig;
+use alice_infrastructure::{config::CommonConfig, middleware};
 use std::sync::Arc;
 
 pub async fn initialize_web_host(sp: Arc<ServiceProvider>) {
     let common_config: CommonConfig = sp.provide();
+    let jwt = common_config.jwt().clone();
     match actix_web::HttpServer::new(move || {
         let cors = actix_cors::Cors::default()
             .allow_any_origin()
@@ -19,8 +20,39 @@ pub async fn initialize_web_host(sp: Arc<ServiceProvider>) {
             .app_data(MultipartFormConfig::default().total_limit(100 * 1024 * 1024))
             .app_data(actix_web::web::Data::from(sp.clone()))
             .wrap(tracing_actix_web::TracingLogger::default())
+            .wrap(
+                middleware::authorization::AddUserInfo::new(
+                    sp.provide(),
+                    sp.provide(),
+                    jwt.clone(),
+                )
+                .not_validate()
+                .all_controllers(),
+            )
             .service(controllers::billing_system::get_flow_nodes_bill)
             .service(controllers::billing_system::webhook_subscribe)
+            .service(controllers::billing_system::get_webhooks)
+            .service(controllers::billing_system::remove_webhook)
+            .service(controllers::billing_system::get_webhook_deliveries)
+            .service(controllers::billing_system::replay_webhook_delivery)
+            .service(controllers::billing_system::check_budget)
+            .service(controllers::billing_system::top_up)
+            .service(controllers::billing_system::set_budget)
+            .service(controllers::billing_system::get_account)
+            .service(controllers::billing_system::get_budgets)
+            .service(controllers::billing_system::get_ledger)
+            .service(controllers::billing_system::get_statement)
+            .service(controllers::billing_system::export_statement)
+            .service(controllers::billing_system::close_invoice_period)
+            .service(controllers::billing_system::get_invoices)
+            .service(controllers::billing_system::get_invoice)
+            .service(controllers::billing_system::export_invoice)
+            .service(controllers::billing_system::get_cluster_settings)
+            .service(controllers::billing_system::set_cluster_settings)
+            .service(controllers::billing_system::get_pricing_rules)
+            .service(controllers::billing_system::add_pricing_rule)
+            .service(controllers::billing_system::expire_pricing_rule)
+            .service(controllers::billing_system::set_user_group)
     })
     .bind((
         common_config.host().bind_address().to_owned(),
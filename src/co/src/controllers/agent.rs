@@ -0,0 +1,27 @@
+use crate::infrastructure::ServiceProvider;
+use actix_web::web::Json;
+use actix_web::{post, web};
+use alice_architecture::base_dto::ResponseBase;
+use alice_di::{actix_auto_inject, IServiceProvider};
+use kernel::prelude::*;
+use std::sync::Arc;
+
+/// 接收 agent 定时上报的集群资源使用量，
+/// agent 订阅的消息队列主题即其用户名，据此找到对应集群
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("agent/UpdateUsedResource")]
+pub async fn update_used_resource(
+    #[inject] service: Arc<dyn IResourcesService + Send + Sync>,
+    data: web::Json<ClusterUsedResource>,
+) -> Json<ResponseBase<String>> {
+    let topic_name = user_info.unwrap().preferred_username;
+    match service.update_cluster_used_resource(&topic_name, data.into_inner()).await {
+        Ok(()) => Json(ResponseBase::ok(None)),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
@@ -9,6 +9,7 @@ use actix_web::{get, head, post, web, Error, HttpResponse};
 use alice_architecture::base_dto::ResponseBase;
 use alice_architecture::exceptions::GenericError;
 use alice_di::{actix_auto_inject, IServiceProvider};
+use futures::TryStreamExt;
 use kernel::prelude::*;
 use serde::Deserialize;
 use serde::Serialize;
@@ -674,6 +675,7 @@ pub async fn get_file_download_urls(
 #[alice_web_macro::http_request]
 pub async fn head_rangely_download_file(
     #[inject] dispatcher_service: Arc<dyn IStorageServerDownloadDispatcherService + Send + Sync>,
+    #[inject] meta_storage_service: Arc<dyn IMetaStorageService + Send + Sync>,
     id: Path<String>,
 ) -> HttpResponse {
     let id = match Uuid::from_str(&id) {
@@ -692,8 +694,19 @@ pub async fn head_rangely_download_file(
         }
     };
 
+    let meta = match meta_storage_service.get_meta(id).await {
+        Ok(meta) => meta,
+        Err(e) => {
+            log::error!("head_rangely_download_file get_meta error: {e}");
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    // 下载方据此校验下载后的文件
     let mut response = HttpResponse::Ok()
         .insert_header((header::ACCEPT_RANGES, "bytes"))
+        .insert_header(("CONTENT-HASH", meta.hash))
+        .insert_header(("CONTENT-HASH-ALGORITHM", meta.hash_algorithm.to_string()))
         // .insert_header(("CONTENT-SIZE", size))
         .finish();
     response.headers_mut().insert(header::CONTENT_LENGTH, HeaderValue::from(size));
@@ -825,6 +838,131 @@ pub async fn get_rangely_download_file(
     }
 }
 
+#[get("file-storage/LocalFile/{storage_server_id}/{meta_id}")]
+#[actix_auto_inject(ServiceProvider, scoped = "None")]
+#[alice_web_macro::http_request]
+pub async fn get_local_file(
+    #[inject] resources_service: Arc<dyn IResourcesService + Send + Sync>,
+    #[inject] broker_service: Arc<FileSystemServerBrokerService>,
+    path: Path<(String, String)>,
+    query: web::Query<LocalFileQuery>,
+) -> HttpResponse {
+    let (storage_server_id, meta_id) = path.into_inner();
+    // Only uuids are accepted, so that the path can't be used to walk out of storage root.
+    let (storage_server_id, meta_id) =
+        match (Uuid::from_str(&storage_server_id), Uuid::from_str(&meta_id)) {
+            (Ok(storage_server_id), Ok(meta_id)) => (storage_server_id, meta_id),
+            _ => return HttpResponse::BadRequest().body("Invalid file path."),
+        };
+    let storage_server = match resources_service.get_storage_server(storage_server_id).await {
+        Ok(el) => el,
+        Err(e) => {
+            log::error!("get_local_file get storage server error: {e}");
+            return HttpResponse::NotFound().finish();
+        }
+    };
+    if let Err(e) = FileSystemServerBrokerService::verify_download_url(
+        &storage_server,
+        meta_id,
+        query.expires,
+        &query.signature,
+    ) {
+        log::error!("get_local_file verify url error: {e}");
+        return HttpResponse::Forbidden().finish();
+    }
+    let file = match broker_service.read_stream(&storage_server, meta_id).await {
+        Ok(el) => el,
+        Err(e) => {
+            log::error!("get_local_file read stream error: {e}");
+            return HttpResponse::NotFound().finish();
+        }
+    };
+    HttpResponse::Ok()
+        .content_type("application/octet-stream")
+        .no_chunking(file.size)
+        .streaming(file.content.map_err(actix_web::error::ErrorInternalServerError))
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("file-storage/SetFilePinned")]
+pub async fn set_file_pinned(
+    #[inject] service: Arc<dyn IFileLifecycleService + Send + Sync>,
+    data: web::Json<SetFilePinnedRequest>,
+) -> Json<ResponseBase<String>> {
+    if !user_info.unwrap().is_admin() {
+        return Json(ResponseBase::err(403, "Forbidden"));
+    }
+    match service.set_pinned(data.meta_id, data.pinned).await {
+        Ok(()) => Json(ResponseBase::ok(None)),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
+
+/// 生成文件生命周期策略的试运行报告，不实际删除或迁移文件
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("file-storage/LifecycleReport")]
+pub async fn get_lifecycle_report(
+    #[inject] service: Arc<dyn IFileLifecycleService + Send + Sync>,
+) -> Json<ResponseBase<LifecycleReport>> {
+    if !user_info.unwrap().is_admin() {
+        return Json(ResponseBase::err(403, "Forbidden"));
+    }
+    match service.apply(true).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
+
+/// 生成未被引用文件的回收试运行报告，不实际删除文件
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("file-storage/GarbageCollectionReport")]
+pub async fn get_garbage_collection_report(
+    #[inject] service: Arc<dyn IFileReferenceService + Send + Sync>,
+) -> Json<ResponseBase<GarbageCollectionReport>> {
+    if !user_info.unwrap().is_admin() {
+        return Json(ResponseBase::err(403, "Forbidden"));
+    }
+    match service.collect_garbage(true).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
+
+/// 统计相同哈希文件去重节省的存储空间
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("file-storage/DeduplicationReport")]
+pub async fn get_deduplication_report(
+    #[inject] service: Arc<dyn IFileReferenceService + Send + Sync>,
+) -> Json<ResponseBase<DeduplicationReport>> {
+    if !user_info.unwrap().is_admin() {
+        return Json(ResponseBase::err(403, "Forbidden"));
+    }
+    match service.deduplication_report().await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
+
 #[actix_auto_inject(ServiceProvider, scoped = "None")]
 #[alice_web_macro::http_request]
 // #[alice_web_macro::authorize]
@@ -978,6 +1116,19 @@ pub struct WholeFileUploadRequest {
     pub size: Text<usize>,
 }
 
+#[derive(Deserialize)]
+pub struct LocalFileQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFilePinnedRequest {
+    pub meta_id: Uuid,
+    pub pinned: bool,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadRealtimeFileRequest {
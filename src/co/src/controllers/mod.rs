@@ -3,12 +3,14 @@ use std::{
     error::Error,
     marker::{Send, Sync},
 };
+pub mod agent;
 pub mod file_storage;
 pub mod snapshot;
 pub mod text_storage;
 pub mod usecase_editor;
 pub mod workflow_editor;
 pub mod workflow_engine;
+pub mod workflow_template;
 pub mod ws;
 
 pub fn handle_error<E: Error + Send + Sync + 'static, R>(e: anyhow::Error) -> HandleResult<E, R> {
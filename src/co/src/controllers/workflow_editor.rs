@@ -1,8 +1,8 @@
 use crate::infrastructure::ServiceProvider;
 use actix_http::header::LanguageTag;
 use actix_web::{
-    get,
-    web::{Json, Path, Query},
+    get, post,
+    web::{self, Json, Path, Query},
 };
 use alice_architecture::{base_dto::ResponseBase, GenericError};
 use alice_di::{actix_auto_inject, IServiceProvider};
@@ -10,6 +10,9 @@ use kernel::prelude::*;
 use lib_co_repo::{client::*, dtos::prelude::NodeDraft};
 use std::str::FromStr;
 
+/// 自定义节点分类 id
+const CUSTOM_NODE_CATEGORY_ID: &str = "6f0c5a3e-8d2b-4c1e-9a47-3b5d2e8f1c90";
+
 #[derive(Debug, Deserialize)]
 pub struct GetWorkflowComponentRequest {
     usecase_version_id: String,
@@ -114,6 +117,12 @@ pub async fn get_workflow_component_categories(
                     id: "1109cbbb-4830-4e1d-ab66-1f019b3b321b".to_string(),
                     is_active: false,
                 },
+                GetWorkflowComponentCategoriesResponse {
+                    name: "custom_nodes".to_string(),
+                    display_name: "自訂節點".to_string(),
+                    id: CUSTOM_NODE_CATEGORY_ID.to_string(),
+                    is_active: true,
+                },
             ]))),
             _ => Json(ResponseBase::ok(Some(vec![
                 GetWorkflowComponentCategoriesResponse {
@@ -134,6 +143,12 @@ pub async fn get_workflow_component_categories(
                     id: "1109cbbb-4830-4e1d-ab66-1f019b3b321b".to_string(),
                     is_active: false,
                 },
+                GetWorkflowComponentCategoriesResponse {
+                    name: "custom_nodes".to_string(),
+                    display_name: "自定义节点".to_string(),
+                    id: CUSTOM_NODE_CATEGORY_ID.to_string(),
+                    is_active: true,
+                },
             ]))),
         },
         _ => Json(ResponseBase::ok(Some(vec![
@@ -155,6 +170,110 @@ pub async fn get_workflow_component_categories(
                 id: "1109cbbb-4830-4e1d-ab66-1f019b3b321b".to_string(),
                 is_active: false,
             },
+            GetWorkflowComponentCategoriesResponse {
+                name: "custom_nodes".to_string(),
+                display_name: "Custom Nodes".to_string(),
+                id: CUSTOM_NODE_CATEGORY_ID.to_string(),
+                is_active: true,
+            },
         ]))),
     }
 }
+
+/// 获取用户的自定义节点，对应分类 custom_nodes
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("workflow-editor/GetCustomNodes")]
+pub async fn get_custom_nodes(
+    #[inject] service: Arc<dyn ICustomNodeService + Send + Sync>,
+) -> Json<ResponseBase<Vec<CustomNode>>> {
+    let user_id = match Uuid::from_str(&user_info.unwrap().user_id) {
+        Ok(el) => el,
+        Err(e) => {
+            log::error!("{e}");
+            return Json(ResponseBase::err(400, "Invalid user id."));
+        }
+    };
+    match service.get_custom_nodes(user_id).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
+
+/// 获取自定义节点对应的节点草稿
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("workflow-editor/GetCustomNodeDraft/{id}")]
+pub async fn get_custom_node_draft(
+    #[inject] service: Arc<dyn ICustomNodeService + Send + Sync>,
+    id: Path<String>,
+) -> Json<ResponseBase<kernel::prelude::NodeDraft>> {
+    let (user_id, id) = match (
+        Uuid::from_str(&user_info.unwrap().user_id),
+        Uuid::from_str(&id),
+    ) {
+        (Ok(user_id), Ok(id)) => (user_id, id),
+        _ => return Json(ResponseBase::err(400, "Invalid id.")),
+    };
+    match service.get_node_draft(user_id, id).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(400, &e.to_string()))
+        }
+    }
+}
+
+/// 将工作流草稿中的一组节点打包为自定义节点
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("workflow-editor/PackageCustomNode")]
+pub async fn package_custom_node(
+    #[inject] service: Arc<dyn ICustomNodeService + Send + Sync>,
+    data: web::Json<PackageCustomNodeCommand>,
+) -> Json<ResponseBase<CustomNode>> {
+    let user_id = match Uuid::from_str(&user_info.unwrap().user_id) {
+        Ok(el) => el,
+        Err(e) => {
+            log::error!("{e}");
+            return Json(ResponseBase::err(400, "Invalid user id."));
+        }
+    };
+    match service.package(user_id, data.0).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(400, &e.to_string()))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("workflow-editor/RemoveCustomNode/{id}")]
+pub async fn remove_custom_node(
+    #[inject] service: Arc<dyn ICustomNodeService + Send + Sync>,
+    id: Path<String>,
+) -> Json<ResponseBase<()>> {
+    let (user_id, id) = match (
+        Uuid::from_str(&user_info.unwrap().user_id),
+        Uuid::from_str(&id),
+    ) {
+        (Ok(user_id), Ok(id)) => (user_id, id),
+        _ => return Json(ResponseBase::err(400, "Invalid id.")),
+    };
+    match service.remove(user_id, id).await {
+        Ok(()) => Json(ResponseBase::ok(None)),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(400, &e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::infrastructure::ServiceProvider;
+use actix_web::{
+    get, post,
+    web::{self, Json, Path, Query},
+};
+use alice_architecture::base_dto::ResponseBase;
+use alice_di::{actix_auto_inject, IServiceProvider};
+use kernel::prelude::*;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+#[derive(Debug, Deserialize)]
+pub struct SearchWorkflowTemplatesRequest {
+    pub keyword: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstantiateWorkflowTemplateRequest {
+    pub id: Uuid,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: HashMap<String, TemplateArgument>,
+}
+
+/// 将工作流草稿发布为模板，指定 familyId 时发布为该模板的新版本
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("workflow-template/PublishWorkflowTemplate")]
+pub async fn publish_workflow_template(
+    #[inject] service: Arc<dyn IWorkflowTemplateService + Send + Sync>,
+    data: web::Json<PublishTemplateCommand>,
+) -> Json<ResponseBase<WorkflowTemplate>> {
+    let user_id = match Uuid::from_str(&user_info.unwrap().user_id) {
+        Ok(el) => el,
+        Err(e) => {
+            log::error!("{e}");
+            return Json(ResponseBase::err(400, "Invalid user id."));
+        }
+    };
+    match service.publish(user_id, data.0).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(400, &e.to_string()))
+        }
+    }
+}
+
+/// 搜索用户可见的模板，每个模板只返回最新版本
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("workflow-template/SearchWorkflowTemplates")]
+pub async fn search_workflow_templates(
+    #[inject] service: Arc<dyn IWorkflowTemplateService + Send + Sync>,
+    request: Query<SearchWorkflowTemplatesRequest>,
+) -> Json<ResponseBase<Vec<WorkflowTemplate>>> {
+    let user_id = match Uuid::from_str(&user_info.unwrap().user_id) {
+        Ok(el) => el,
+        Err(e) => {
+            log::error!("{e}");
+            return Json(ResponseBase::err(400, "Invalid user id."));
+        }
+    };
+    match service.search(user_id, request.0.keyword).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
+
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("workflow-template/GetWorkflowTemplate/{id}")]
+pub async fn get_workflow_template(
+    #[inject] service: Arc<dyn IWorkflowTemplateService + Send + Sync>,
+    id: Path<String>,
+) -> Json<ResponseBase<WorkflowTemplate>> {
+    let (user_id, id) = match (
+        Uuid::from_str(&user_info.unwrap().user_id),
+        Uuid::from_str(&id),
+    ) {
+        (Ok(user_id), Ok(id)) => (user_id, id),
+        _ => return Json(ResponseBase::err(400, "Invalid id.")),
+    };
+    match service.get_template(user_id, id).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(400, &e.to_string()))
+        }
+    }
+}
+
+/// 获取模板的全部可见版本，按版本号倒序
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[get("workflow-template/GetWorkflowTemplateVersions/{family_id}")]
+pub async fn get_workflow_template_versions(
+    #[inject] service: Arc<dyn IWorkflowTemplateService + Send + Sync>,
+    family_id: Path<String>,
+) -> Json<ResponseBase<Vec<WorkflowTemplate>>> {
+    let (user_id, family_id) = match (
+        Uuid::from_str(&user_info.unwrap().user_id),
+        Uuid::from_str(&family_id),
+    ) {
+        (Ok(user_id), Ok(family_id)) => (user_id, family_id),
+        _ => return Json(ResponseBase::err(400, "Invalid id.")),
+    };
+    match service.get_versions(user_id, family_id).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(500, "Interval Error."))
+        }
+    }
+}
+
+/// 以参数值填充模板，创建新的工作流草稿，返回草稿 id
+#[actix_auto_inject(ServiceProvider, scoped = "user_info.clone()")]
+#[alice_web_macro::http_request]
+#[alice_web_macro::authorize]
+#[post("workflow-template/InstantiateWorkflowTemplate")]
+pub async fn instantiate_workflow_template(
+    #[inject] service: Arc<dyn IWorkflowTemplateService + Send + Sync>,
+    data: web::Json<InstantiateWorkflowTemplateRequest>,
+) -> Json<ResponseBase<Uuid>> {
+    let user_id = match Uuid::from_str(&user_info.unwrap().user_id) {
+        Ok(el) => el,
+        Err(e) => {
+            log::error!("{e}");
+            return Json(ResponseBase::err(400, "Invalid user id."));
+        }
+    };
+    let data = data.0;
+    match service.instantiate(user_id, data.id, data.name, data.arguments).await {
+        Ok(el) => Json(ResponseBase::ok(Some(el))),
+        Err(e) => {
+            log::error!("{e}");
+            Json(ResponseBase::err(400, &e.to_string()))
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::infrastructure::ServiceProvider;
+use alice_architecture::hosting::IBackgroundService;
+use alice_di::IServiceProvider;
+use kernel::prelude::*;
+use std::time::Duration;
+use tokio::time::interval;
+
+/// 定时回收未被引用的文件
+pub struct FileGarbageCollectionRunner {
+    sp: Arc<ServiceProvider>,
+    period: Duration,
+    dry_run: bool,
+}
+
+impl FileGarbageCollectionRunner {
+    pub fn new(sp: Arc<ServiceProvider>, period: Duration, dry_run: bool) -> Self {
+        Self {
+            sp,
+            period,
+            dry_run,
+        }
+    }
+
+    async fn collect(&self) -> anyhow::Result<()> {
+        let sp = self.sp.create_scoped(None)?;
+        let service: Arc<dyn IFileReferenceService + Send + Sync> = sp.provide();
+        let report = service.collect_garbage(self.dry_run).await?;
+        log::info!(
+            "File garbage collection (dry run: {}): {} files, {} bytes.",
+            report.dry_run,
+            report.meta_ids.len(),
+            report.freed_size
+        );
+        for failure in report.failures.iter() {
+            log::error!(
+                "File garbage collection of {} failed: {}",
+                failure.meta_id,
+                failure.reason
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl IBackgroundService for FileGarbageCollectionRunner {
+    async fn run(&self) {
+        let mut interval = interval(self.period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.collect().await {
+                log::error!("{e}");
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::infrastructure::ServiceProvider;
+use alice_architecture::hosting::IBackgroundService;
+use alice_di::IServiceProvider;
+use kernel::prelude::*;
+use std::time::Duration;
+use tokio::time::interval;
+
+/// 定时执行文件生命周期策略
+pub struct FileLifecycleRunner {
+    sp: Arc<ServiceProvider>,
+    period: Duration,
+    dry_run: bool,
+}
+
+impl FileLifecycleRunner {
+    pub fn new(sp: Arc<ServiceProvider>, period: Duration, dry_run: bool) -> Self {
+        Self {
+            sp,
+            period,
+            dry_run,
+        }
+    }
+
+    async fn apply(&self) -> anyhow::Result<()> {
+        let sp = self.sp.create_scoped(None)?;
+        let service: Arc<dyn IFileLifecycleService + Send + Sync> = sp.provide();
+        let report = service.apply(self.dry_run).await?;
+        for action in report.actions.iter() {
+            log::info!("File lifecycle (dry run: {}): {action:?}", report.dry_run);
+        }
+        for failure in report.failures.iter() {
+            log::error!(
+                "File lifecycle {:?} failed: {}",
+                failure.action,
+                failure.reason
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl IBackgroundService for FileLifecycleRunner {
+    async fn run(&self) {
+        let mut interval = interval(self.period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.apply().await {
+                log::error!("{e}");
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::infrastructure::ServiceProvider;
+use alice_architecture::hosting::IBackgroundService;
+use alice_di::IServiceProvider;
+use kernel::prelude::*;
+use std::time::Duration;
+use tokio::time::interval;
+
+/// 定时重新计算已存储文件的哈希值，报告损坏的文件
+pub struct FileScrubRunner {
+    sp: Arc<ServiceProvider>,
+    period: Duration,
+}
+
+impl FileScrubRunner {
+    pub fn new(sp: Arc<ServiceProvider>, period: Duration) -> Self {
+        Self { sp, period }
+    }
+
+    async fn scrub(&self) -> anyhow::Result<()> {
+        let sp = self.sp.create_scoped(None)?;
+        let service: Arc<dyn IFileScrubService + Send + Sync> = sp.provide();
+        let report = service.scrub().await?;
+        log::info!(
+            "File scrub verified {} copies, {} corrupted.",
+            report.verified_count,
+            report.corruptions.len()
+        );
+        for corruption in report.corruptions.iter() {
+            log::error!(
+                "File {} on storage server {} is corrupted, expected hash {}, actual hash {}.",
+                corruption.meta_id,
+                corruption.storage_server_id,
+                corruption.expected_hash,
+                corruption.actual_hash
+            );
+        }
+        for failure in report.failures.iter() {
+            log::error!(
+                "File scrub of {} on storage server {} failed: {}",
+                failure.meta_id,
+                failure.storage_server_id,
+                failure.reason
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl IBackgroundService for FileScrubRunner {
+    async fn run(&self) {
+        let mut interval = interval(self.period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.scrub().await {
+                log::error!("{e}");
+            }
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod file_garbage_collection_runner;
+pub mod file_lifecycle_runner;
+pub mod file_scrub_runner;
+pub mod node_retry_runner;
+pub use file_garbage_collection_runner::*;
+pub use file_lifecycle_runner::*;
+pub use file_scrub_runner::*;
+pub use node_retry_runner::*;
@@ -0,0 +1,37 @@
+use crate::infrastructure::ServiceProvider;
+use alice_architecture::hosting::IBackgroundService;
+use alice_di::IServiceProvider;
+use kernel::prelude::*;
+use std::time::Duration;
+use tokio::time::interval;
+
+/// 定时重新提交到达重试时间的节点
+pub struct NodeRetryRunner {
+    sp: Arc<ServiceProvider>,
+    period: Duration,
+}
+
+impl NodeRetryRunner {
+    pub fn new(sp: Arc<ServiceProvider>, period: Duration) -> Self {
+        Self { sp, period }
+    }
+
+    async fn retry(&self) -> anyhow::Result<()> {
+        let sp = self.sp.create_scoped(None)?;
+        let service: Arc<dyn IWorkflowScheduleService + Send + Sync> = sp.provide();
+        service.retry_due_nodes().await
+    }
+}
+
+#[async_trait::async_trait]
+impl IBackgroundService for NodeRetryRunner {
+    async fn run(&self) {
+        let mut interval = interval(self.period);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.retry().await {
+                log::error!("{e}");
+            }
+        }
+    }
+}
@@ -16,6 +16,12 @@ pub struct CoConfig {
     default_storage_server_id: Uuid,
     #[serde(default = "default_bill_topic")]
     bill_topic: String,
+    #[serde(default)]
+    quota: QuotaConfig,
+    #[serde(default)]
+    budget: BudgetConfig,
+    #[serde(default)]
+    retry: RetryConfig,
     co_repo_domain: String,
 }
 
@@ -23,6 +29,64 @@ fn default_bill_topic() -> String {
     "bill-dev".to_string()
 }
 
+#[derive(Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct QuotaConfig {
+    /// 用户配额不足时节点排队还是直接出错
+    #[serde(default)]
+    policy: QuotaPolicy,
+    #[serde(default = "QuotaConfig::default_alert_topic")]
+    alert_topic: String,
+}
+
+impl QuotaConfig {
+    fn default_alert_topic() -> String {
+        "quota-alert".to_string()
+    }
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            policy: QuotaPolicy::default(),
+            alert_topic: Self::default_alert_topic(),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct RetryConfig {
+    /// 检查到达重试时间的节点的间隔，单位秒
+    #[serde(default = "RetryConfig::default_interval_secs")]
+    interval_secs: u64,
+}
+
+impl RetryConfig {
+    fn default_interval_secs() -> u64 {
+        10
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: Self::default_interval_secs(),
+        }
+    }
+}
+
+#[derive(Default, Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct BudgetConfig {
+    /// 计费系统地址，未配置时节点分发前不检查预算
+    #[serde(default)]
+    billing_url: Option<String>,
+    /// 与计费系统共享的预算检查请求签名密钥
+    #[serde(default)]
+    secret: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Getters)]
 #[getset(get = "pub")]
 pub struct HttpClientConfig {
@@ -59,6 +123,53 @@ pub struct FileSystemConfig {
     file_move: FileMoveConfig,
     #[serde(default)]
     multipart: MultipartConfig,
+    #[serde(default)]
+    lifecycle: LifecycleConfig,
+    #[serde(default)]
+    garbage_collection: GarbageCollectionConfig,
+    #[serde(default)]
+    scrub: ScrubConfig,
+}
+
+#[derive(Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct LifecycleConfig {
+    /// 生命周期策略执行间隔，单位秒
+    #[serde(default = "LifecycleConfig::default_interval_secs")]
+    interval_secs: u64,
+    /// 仅生成报告，不实际删除或迁移文件
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    policy: LifecyclePolicy,
+}
+
+#[derive(Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct GarbageCollectionConfig {
+    /// 是否定时回收未被引用的文件
+    #[serde(default)]
+    enabled: bool,
+    /// 回收间隔，单位秒
+    #[serde(default = "GarbageCollectionConfig::default_interval_secs")]
+    interval_secs: u64,
+    /// 文件创建后至少保留的时间，单位秒，避免回收尚未记录到网盘的上传
+    #[serde(default = "GarbageCollectionConfig::default_grace_secs")]
+    grace_secs: i64,
+    /// 仅生成报告，不实际删除文件
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Deserialize, Debug, Getters)]
+#[getset(get = "pub")]
+pub struct ScrubConfig {
+    /// 是否定时重新计算已存储文件的哈希值，检查文件是否损坏
+    #[serde(default)]
+    enabled: bool,
+    /// 检查间隔，单位秒
+    #[serde(default = "ScrubConfig::default_interval_secs")]
+    interval_secs: u64,
 }
 
 #[derive(Clone, Deserialize, Debug, Getters)]
@@ -121,6 +232,57 @@ impl MultipartConfig {
     }
 }
 
+impl LifecycleConfig {
+    fn default_interval_secs() -> u64 {
+        24 * 60 * 60
+    }
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: Self::default_interval_secs(),
+            dry_run: false,
+            policy: LifecyclePolicy::default(),
+        }
+    }
+}
+
+impl GarbageCollectionConfig {
+    fn default_interval_secs() -> u64 {
+        24 * 60 * 60
+    }
+    fn default_grace_secs() -> i64 {
+        24 * 60 * 60
+    }
+}
+
+impl Default for GarbageCollectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: Self::default_interval_secs(),
+            grace_secs: Self::default_grace_secs(),
+            dry_run: false,
+        }
+    }
+}
+
+impl ScrubConfig {
+    fn default_interval_secs() -> u64 {
+        7 * 24 * 60 * 60
+    }
+}
+
+impl Default for ScrubConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: Self::default_interval_secs(),
+        }
+    }
+}
+
 impl Default for MultipartConfig {
     fn default() -> Self {
         Self {
@@ -177,6 +339,7 @@ impl Default for FileSystemConfig {
             snapshot: SnapshotConfig::default(),
             file_move: FileMoveConfig::default(),
             multipart: MultipartConfig::default(),
+            lifecycle: LifecycleConfig::default(),
         }
     }
 }
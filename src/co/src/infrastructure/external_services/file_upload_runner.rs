@@ -1,5 +1,4 @@
 use kernel::prelude::*;
-use CacheOperateCommand::*;
 use CacheReadCommand::*;
 
 #[async_trait]
@@ -46,18 +45,23 @@ impl IFileUploadRunner for FileUploadRunner {
                 _ => bail!("Unreachable destination when run file upload."),
             },
         );
-        let content = self.cache_service.read(ReadNormal { meta_id }).await?;
-        let server_url = match self.upload_service.upload(meta_id, &content).await {
-            Ok(el) => el,
-            Err(e) => {
-                move_info.is_upload_failed = true;
-                move_info.failed_reason = Some(e.to_string());
-                self.file_move_service.set_move_as_failed(move_id, &e.to_string()).await?;
-                anyhow::bail!(format!(
-                    "Error when upload file meta id: {meta_id} to server. - source: {e}"
-                ))
-            }
-        };
+        let part_count = self.multipart_service.info(meta_id).await?.parts.len();
+        let mut parts = Vec::with_capacity(part_count);
+        for nth in 0..part_count {
+            parts.push(self.cache_service.read_stream(ReadPart { meta_id, nth }).await?);
+        }
+        let server_url =
+            match self.upload_service.upload(meta_id, &hash, &hash_algorithm, parts).await {
+                Ok(el) => el,
+                Err(e) => {
+                    move_info.is_upload_failed = true;
+                    move_info.failed_reason = Some(e.to_string());
+                    self.file_move_service.set_move_as_failed(move_id, &e.to_string()).await?;
+                    anyhow::bail!(format!(
+                        "Error when upload file meta id: {meta_id} to server. - source: {e}"
+                    ))
+                }
+            };
 
         let (storage_server_id, server_url) =
             (server_url.storage_server_id, server_url.server_url());
@@ -90,7 +94,6 @@ impl IFileUploadRunner for FileUploadRunner {
                 .await?;
         }
 
-        self.cache_service.operate(RemoveNormal { meta_id }).await?;
         self.multipart_service.remove(meta_id).await?;
         self.file_move_service.remove_all_with_meta_id(meta_id).await?;
         Ok(())
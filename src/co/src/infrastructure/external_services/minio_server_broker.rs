@@ -1,9 +1,20 @@
+use futures::{StreamExt, TryStreamExt};
 use kernel::prelude::*;
+use md5::{Digest, Md5};
 use rusoto_core::{credential::StaticProvider, ByteStream, HttpClient, Region};
-use rusoto_s3::{GetObjectRequest, HeadObjectRequest, PutObjectRequest, S3Client, S3};
+use rusoto_s3::{
+    AbortMultipartUploadRequest, CompleteMultipartUploadRequest, CompletedMultipartUpload,
+    CompletedPart, CreateMultipartUploadRequest, DeleteObjectRequest, GetObjectRequest,
+    HeadObjectRequest, PutObjectRequest, S3Client, UploadPartRequest, S3,
+};
 use std::ops::Range;
 use tokio::io::AsyncReadExt;
 
+/// S3 requires each part except the last one to be at least 5 MiB.
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+/// S3 allows at most 10000 parts in a multipart upload.
+const MAX_PART_COUNT: usize = 10000;
+
 #[derive(Builder)]
 pub struct MinioServerBrokerService {
     meta_storage_service: Arc<dyn IMetaStorageService + Send + Sync>,
@@ -15,7 +26,7 @@ impl IStorageServerBrokerService for MinioServerBrokerService {
         &self,
         storage_server: &StorageServer,
         meta_id: Uuid,
-        content: &[u8],
+        parts: Vec<FileStream>,
     ) -> AnyhowResult<ServerUrl> {
         match &storage_server.storage_type {
             StorageType::ObjectStorage { options } => {
@@ -28,17 +39,87 @@ impl IStorageServerBrokerService for MinioServerBrokerService {
                     meta_id,
                 };
                 let key = server_url.key();
+                let parts = if is_multipart_ready(&parts) {
+                    parts
+                } else {
+                    rechunk(parts)
+                };
+                if parts.len() == 1 {
+                    let part = parts.into_iter().next().unwrap();
+                    let md5 = Arc::new(std::sync::Mutex::new(Md5::new()));
+                    let e_tag = client
+                        .put_object(PutObjectRequest {
+                            body: Some(s3_body(
+                                md5_inspected(part.content, vec![md5.clone()]),
+                                part.size,
+                            )),
+                            bucket: bucket.to_owned(),
+                            key: key.to_owned(),
+                            content_length: Some(part.size as i64),
+                            ..Default::default()
+                        })
+                        .await?
+                        .e_tag;
+                    let md5 = md5_hex(&md5);
+                    self.verify_upload(storage_server, meta_id, vec![(e_tag, md5.clone())], &md5)
+                        .await?;
+                    return Ok(server_url);
+                }
+
+                let upload_id = client
+                    .create_multipart_upload(CreateMultipartUploadRequest {
+                        bucket: bucket.to_owned(),
+                        key: key.to_owned(),
+                        ..Default::default()
+                    })
+                    .await?
+                    .upload_id
+                    .ok_or(anyhow!(
+                        "Create multipart upload of {key} returns no upload id."
+                    ))?;
+                let md5 = Arc::new(std::sync::Mutex::new(Md5::new()));
+                let (completed_parts, part_md5s) =
+                    match upload_parts(&client, &bucket, &key, &upload_id, parts, &md5).await {
+                        Ok(el) => el,
+                        Err(e) => {
+                            if let Err(abort_error) = client
+                                .abort_multipart_upload(AbortMultipartUploadRequest {
+                                    bucket: bucket.to_owned(),
+                                    key: key.to_owned(),
+                                    upload_id: upload_id.to_owned(),
+                                    ..Default::default()
+                                })
+                                .await
+                            {
+                                log::warn!("Abort multipart upload of {key} failed: {abort_error}");
+                            }
+                            return Err(e);
+                        }
+                    };
                 client
-                    .put_object(PutObjectRequest {
-                        body: Some(ByteStream::from(content.to_owned())),
+                    .complete_multipart_upload(CompleteMultipartUploadRequest {
                         bucket: bucket.to_owned(),
                         key: key.to_owned(),
+                        upload_id,
+                        multipart_upload: Some(CompletedMultipartUpload {
+                            parts: Some(completed_parts.clone()),
+                        }),
                         ..Default::default()
                     })
                     .await?;
+                let checks = completed_parts
+                    .into_iter()
+                    .map(|el| el.e_tag)
+                    .zip(part_md5s)
+                    .collect::<Vec<_>>();
+                self.verify_upload(storage_server, meta_id, checks, &md5_hex(&md5)).await?;
 
                 Ok(server_url)
             }
+            _ => bail!(
+                "Storage server {} is not an object storage.",
+                storage_server.id
+            ),
         }
     }
 
@@ -80,6 +161,64 @@ impl IStorageServerBrokerService for MinioServerBrokerService {
                 };
                 return Ok(content_length as u64);
             }
+            _ => bail!(
+                "Storage server {} is not an object storage.",
+                storage_server.id
+            ),
+        }
+    }
+
+    async fn read_stream(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<FileStream> {
+        match &storage_server.storage_type {
+            StorageType::ObjectStorage { options } => {
+                let client = create_s3_client(options)?;
+                let bucket = options.default_bucket.to_owned();
+                let key = format!("storage-{}/{}", storage_server.id, meta_id);
+
+                let output = client
+                    .get_object(GetObjectRequest {
+                        bucket,
+                        key: key.to_owned(),
+                        ..Default::default()
+                    })
+                    .await?;
+                let size = output
+                    .content_length
+                    .ok_or(anyhow!("Get object: {key} returns no content length."))?;
+                let body = output.body.ok_or(anyhow!("Get object: {key} return empty body!"))?;
+                Ok(FileStream {
+                    size: size as u64,
+                    content: Box::pin(body.map_err(anyhow::Error::from)),
+                })
+            }
+            _ => bail!(
+                "Storage server {} is not an object storage.",
+                storage_server.id
+            ),
+        }
+    }
+
+    async fn delete(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow {
+        match &storage_server.storage_type {
+            StorageType::ObjectStorage { options } => {
+                let client = create_s3_client(options)?;
+                client
+                    .delete_object(DeleteObjectRequest {
+                        bucket: options.default_bucket.to_owned(),
+                        key: format!("storage-{}/{}", storage_server.id, meta_id),
+                        ..Default::default()
+                    })
+                    .await?;
+                Ok(())
+            }
+            _ => bail!(
+                "Storage server {} is not an object storage.",
+                storage_server.id
+            ),
         }
     }
 
@@ -100,6 +239,10 @@ impl IStorageServerBrokerService for MinioServerBrokerService {
                 let download_endpoint = &options.download_endpoint;
                 format!("{download_endpoint}/{server_url}")
             }
+            _ => bail!(
+                "Storage server {} is not an object storage.",
+                storage_server.id
+            ),
         })
     }
 
@@ -122,6 +265,45 @@ impl IStorageServerBrokerService for MinioServerBrokerService {
 }
 
 impl MinioServerBrokerService {
+    /// S3 returns the MD5 digest of the content it received as ETag, so it is compared with the
+    /// digest of the content sent. ETags of objects encrypted by KMS or customer keys are not
+    /// MD5 digests, such objects are read back instead. Objects failed to be verified are deleted.
+    async fn verify_upload(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+        checks: Vec<(Option<String>, String)>,
+        md5: &str,
+    ) -> Anyhow {
+        let e_tags_matched = checks
+            .iter()
+            .map(|(e_tag, md5)| {
+                let e_tag = e_tag.as_deref()?.trim_matches('"');
+                (e_tag.len() == 32 && e_tag.chars().all(|el| el.is_ascii_hexdigit()))
+                    .then(|| e_tag.eq_ignore_ascii_case(md5))
+            })
+            .collect::<Option<Vec<_>>>();
+        let is_matched = match e_tags_matched {
+            Some(el) => el.into_iter().all(|el| el),
+            None => {
+                let mut stream = self.read_stream(storage_server, meta_id).await?;
+                let mut hasher = Md5::new();
+                while let Some(chunk) = stream.content.try_next().await? {
+                    hasher.update(&chunk);
+                }
+                format!("{:x}", hasher.finalize()).eq(md5)
+            }
+        };
+        if !is_matched {
+            self.delete(storage_server, meta_id).await?;
+            bail!(
+                "File {meta_id} on storage server {} is corrupted during upload.",
+                storage_server.id
+            );
+        }
+        Ok(())
+    }
+
     async fn get_content(
         &self,
         storage_server: &StorageServer,
@@ -156,10 +338,100 @@ impl MinioServerBrokerService {
                     }
                 }
             }
+            _ => bail!(
+                "Storage server {} is not an object storage.",
+                storage_server.id
+            ),
         }
     }
 }
 
+/// Whether parts can be uploaded as they are, each part except the last one must be at least
+/// 5 MiB, and there must be no more than 10000 parts. A single part is always rechunked, so a
+/// large one isn't put in one request.
+fn is_multipart_ready(parts: &[FileStream]) -> bool {
+    parts.len() > 1
+        && parts.len() <= MAX_PART_COUNT
+        && parts[..parts.len() - 1].iter().all(|el| el.size >= MIN_PART_SIZE)
+}
+
+/// Part size to split content of `size` bytes into, large enough to keep the part count
+/// within the limit.
+fn rechunked_part_size(size: u64) -> u64 {
+    MIN_PART_SIZE.max(size.div_ceil(MAX_PART_COUNT as u64))
+}
+
+/// Chain parts into one stream, still without reading them into memory, then split it into
+/// parts acceptable by S3 multipart upload.
+fn rechunk(parts: Vec<FileStream>) -> Vec<FileStream> {
+    let size = parts.iter().map(|el| el.size).sum::<u64>();
+    let content = futures::stream::iter(parts).flat_map(|el| el.content);
+    split_stream(
+        FileStream {
+            size,
+            content: Box::pin(content),
+        },
+        rechunked_part_size(size),
+    )
+}
+
+/// Upload each cached part as a part of S3 multipart upload, part number starts from 1.
+///
+/// Returns completed parts with the MD5 digests of their content, and the digest of the whole
+/// content is computed by `md5`.
+async fn upload_parts(
+    client: &S3Client,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    parts: Vec<FileStream>,
+    md5: &Arc<std::sync::Mutex<Md5>>,
+) -> AnyhowResult<(Vec<CompletedPart>, Vec<String>)> {
+    let mut completed_parts = Vec::with_capacity(parts.len());
+    let mut part_md5s = Vec::with_capacity(parts.len());
+    for (nth, part) in parts.into_iter().enumerate() {
+        let part_number = nth as i64 + 1;
+        let part_md5 = Arc::new(std::sync::Mutex::new(Md5::new()));
+        let content = md5_inspected(part.content, vec![part_md5.clone(), md5.clone()]);
+        let output = client
+            .upload_part(UploadPartRequest {
+                body: Some(s3_body(content, part.size)),
+                bucket: bucket.to_owned(),
+                key: key.to_owned(),
+                upload_id: upload_id.to_owned(),
+                part_number,
+                content_length: Some(part.size as i64),
+                ..Default::default()
+            })
+            .await?;
+        completed_parts.push(CompletedPart {
+            e_tag: output.e_tag,
+            part_number: Some(part_number),
+        });
+        part_md5s.push(md5_hex(&part_md5));
+    }
+    Ok((completed_parts, part_md5s))
+}
+
+/// Feed content to MD5 hashers while it is read.
+fn md5_inspected(
+    content: impl futures::Stream<Item = AnyhowResult<bytes::Bytes>> + Send + Sync + 'static,
+    md5s: Vec<Arc<std::sync::Mutex<Md5>>>,
+) -> impl futures::Stream<Item = AnyhowResult<bytes::Bytes>> + Send + Sync + 'static {
+    content.inspect_ok(move |chunk| md5s.iter().for_each(|el| el.lock().unwrap().update(chunk)))
+}
+
+fn md5_hex(md5: &std::sync::Mutex<Md5>) -> String {
+    format!("{:x}", md5.lock().unwrap().clone().finalize())
+}
+
+fn s3_body(
+    content: impl futures::Stream<Item = AnyhowResult<bytes::Bytes>> + Send + Sync + 'static,
+    size: u64,
+) -> ByteStream {
+    ByteStream::new_with_size(content.map_err(std::io::Error::other), size as usize)
+}
+
 fn create_s3_client(options: &ObjectServerOption) -> anyhow::Result<S3Client> {
     let endpoint = options.endpoint.to_owned();
     let access_key_id = options.access_key_id.to_owned();
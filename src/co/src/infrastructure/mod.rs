@@ -8,6 +8,7 @@ pub use self::host::*;
 pub use self::http_client::*;
 pub use self::service_provider::*;
 pub use self::web_server::*;
+pub mod background_service;
 pub mod external_services;
 pub mod repositories;
 pub mod ws;
@@ -3,7 +3,10 @@ use alice_architecture::repository::IReadOnlyRepository;
 use database_model::system::prelude::*;
 use kernel::prelude::*;
 use rand::Rng;
-use sea_orm::{prelude::Uuid, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QuerySelect};
+use sea_orm::{
+    prelude::Uuid, sea_query::Expr, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter,
+    QuerySelect,
+};
 use std::str::FromStr;
 
 #[async_trait::async_trait]
@@ -16,7 +19,12 @@ impl IReadOnlyRepository<Cluster> for SeaOrmDbRepository {
             .try_into()
     }
     async fn get_all(&self) -> anyhow::Result<Vec<Cluster>> {
-        unimplemented!()
+        ClusterEntity::find()
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
     }
 }
 
@@ -37,4 +45,60 @@ impl IClusterRepository for SeaOrmDbRepository {
             .ok_or(anyhow::anyhow!("No such cluster id!"))?
             .id)
     }
+
+    async fn get_all_clusters_with_resource(
+        &self,
+    ) -> anyhow::Result<Vec<(Cluster, Option<ClusterResource>)>> {
+        let clusters = ClusterEntity::find()
+            .find_also_related(ClusterResourceEntity)
+            .all(self.db.get_connection())
+            .await?;
+        let mut r = vec![];
+        for (cluster, resource) in clusters.into_iter() {
+            r.push((cluster.try_into()?, resource.map(ClusterResource::from)));
+        }
+        Ok(r)
+    }
+
+    async fn get_by_topic_name(&self, topic_name: &str) -> anyhow::Result<Cluster> {
+        ClusterEntity::find()
+            .filter(ClusterColumn::TopicName.eq(topic_name))
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!(
+                "there is no cluster with topic {topic_name}"
+            ))?
+            .try_into()
+    }
+
+    async fn update_used_resource(
+        &self,
+        cluster_id: Uuid,
+        used: &ClusterUsedResource,
+    ) -> anyhow::Result<()> {
+        let result = ClusterResourceEntity::update_many()
+            .col_expr(
+                ClusterResourceColumn::AllocatedMemory,
+                Expr::value(used.allocated_memory as i64),
+            )
+            .col_expr(
+                ClusterResourceColumn::AllocatedCpuCount,
+                Expr::value(used.allocated_cpu_count as i64),
+            )
+            .col_expr(
+                ClusterResourceColumn::QueuingTaskCount,
+                Expr::value(used.queuing_task_count as i64),
+            )
+            .col_expr(
+                ClusterResourceColumn::RunningTaskCount,
+                Expr::value(used.running_task_count as i64),
+            )
+            .filter(ClusterResourceColumn::ClusterId.eq(cluster_id))
+            .exec(self.db.get_connection())
+            .await?;
+        if result.rows_affected == 0 {
+            anyhow::bail!("there is no cluster_resource with cluster id {cluster_id}");
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,76 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use database_model::system::prelude::*;
+use kernel::prelude::*;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QueryTrait};
+use std::{str::FromStr, sync::atomic::Ordering};
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<CustomNode> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<CustomNode> {
+        CustomNodeEntity::find_by_id(Uuid::from_str(uuid)?)
+            .filter(CustomNodeColumn::UserId.eq(self.user_id(None)?))
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!(
+                "There is no such custom_node with user_id: {}, id: {uuid}",
+                self.user_id(None)?
+            ))?
+            .try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<CustomNode>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<CustomNode> for SeaOrmDbRepository {
+    async fn update(&self, entity: CustomNode) -> anyhow::Result<CustomNode> {
+        let mut stmts = self.statements.lock().await;
+        let model = CustomNodeModel::try_from(entity.to_owned())?;
+        let stmt = CustomNodeEntity::update(model.into_set())
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: CustomNode) -> anyhow::Result<CustomNode> {
+        let mut stmts = self.statements.lock().await;
+        let model = CustomNodeModel::try_from(entity.to_owned())?;
+        let stmt = CustomNodeEntity::insert(model.into_set())
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, entity: CustomNode) -> anyhow::Result<bool> {
+        self.delete_by_id(&entity.id.to_string(), Some(entity)).await
+    }
+    async fn delete_by_id(&self, uuid: &str, _entity: Option<CustomNode>) -> anyhow::Result<bool> {
+        let mut stmts = self.statements.lock().await;
+        let stmt = CustomNodeEntity::delete_by_id(Uuid::from_str(uuid)?)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(true)
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<CustomNode> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl ICustomNodeRepository for SeaOrmDbRepository {
+    async fn get_all_by_user_id(&self, user_id: Uuid) -> anyhow::Result<Vec<CustomNode>> {
+        CustomNodeEntity::find()
+            .filter(CustomNodeColumn::UserId.eq(user_id))
+            .order_by_desc(CustomNodeColumn::CreatedTime)
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
+    }
+}
@@ -3,7 +3,8 @@ use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnl
 use database_model::system::prelude::*;
 use kernel::prelude::*;
 use sea_orm::{
-    prelude::Uuid, ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter, QueryTrait,
+    prelude::Uuid, ColumnTrait, Condition, ConnectionTrait, DatabaseBackend, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect, QueryTrait, Set, Statement,
 };
 use std::{str::FromStr, sync::atomic::Ordering};
 
@@ -29,6 +30,58 @@ impl IFileMetaRepo for SeaOrmDbRepository {
             },
         )
     }
+
+    async fn get_all_unreferenced_before(
+        &self,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<FileMeta>> {
+        FileMetadataEntity::find()
+            .filter(FileMetadataColumn::ReferenceCount.lte(0))
+            .filter(FileMetadataColumn::CreatedTime.lt(time))
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
+    }
+
+    async fn get_deduplication_report(&self) -> anyhow::Result<DeduplicationReport> {
+        let mut sql = String::from("SELECT COUNT(*) AS file_count");
+        sql.push_str(", COALESCE(SUM(reference_count), 0)::bigint AS reference_count");
+        sql.push_str(", COALESCE(SUM(size), 0)::bigint AS stored_size");
+        sql.push_str(", COALESCE(SUM(size * reference_count), 0)::bigint AS referenced_size");
+        sql.push_str(" FROM file_metadata WHERE reference_count > 0");
+        let result = self
+            .db
+            .get_connection()
+            .query_one(Statement::from_string(DatabaseBackend::Postgres, sql))
+            .await?
+            .ok_or(anyhow!("No result of deduplication report."))?;
+        let stored_size: i64 = result.try_get("", "stored_size")?;
+        let referenced_size: i64 = result.try_get("", "referenced_size")?;
+        Ok(DeduplicationReport {
+            file_count: result.try_get("", "file_count")?,
+            reference_count: result.try_get("", "reference_count")?,
+            stored_size,
+            referenced_size,
+            saved_size: referenced_size - stored_size,
+        })
+    }
+
+    async fn get_all_after(&self, id: Option<Uuid>, count: u64) -> anyhow::Result<Vec<FileMeta>> {
+        let mut query = FileMetadataEntity::find();
+        if let Some(id) = id {
+            query = query.filter(FileMetadataColumn::Id.gt(id));
+        }
+        query
+            .order_by_asc(FileMetadataColumn::Id)
+            .limit(count)
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
+    }
 }
 
 #[async_trait::async_trait]
@@ -41,14 +94,30 @@ impl IReadOnlyRepository<FileMeta> for SeaOrmDbRepository {
         Ok(model.try_into()?)
     }
     async fn get_all(&self) -> anyhow::Result<Vec<FileMeta>> {
-        unimplemented!()
+        FileMetadataEntity::find()
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
     }
 }
 
 #[async_trait::async_trait]
 impl IMutableRepository<FileMeta> for SeaOrmDbRepository {
-    async fn update(&self, _entity: FileMeta) -> anyhow::Result<FileMeta> {
-        unimplemented!();
+    async fn update(&self, entity: FileMeta) -> anyhow::Result<FileMeta> {
+        let mut stmts = self.statements.lock().await;
+        let active_model = FileMetadataActiveModel {
+            id: Set(entity.id),
+            name: Set(entity.name.to_owned()),
+            pinned: Set(entity.pinned),
+            ..Default::default()
+        };
+        let stmt = FileMetadataEntity::update(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
     }
     async fn insert(&self, entity: FileMeta) -> anyhow::Result<FileMeta> {
         let mut stmts = self.statements.lock().await;
@@ -62,8 +131,13 @@ impl IMutableRepository<FileMeta> for SeaOrmDbRepository {
     async fn delete(&self, _entity: FileMeta) -> anyhow::Result<bool> {
         unimplemented!();
     }
-    async fn delete_by_id(&self, _uuid: &str, _entity: Option<FileMeta>) -> anyhow::Result<bool> {
-        unimplemented!();
+    async fn delete_by_id(&self, uuid: &str, _entity: Option<FileMeta>) -> anyhow::Result<bool> {
+        let mut stmts = self.statements.lock().await;
+        let stmt = FileMetadataEntity::delete_by_id(Uuid::from_str(uuid)?)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(true)
     }
     async fn save_changed(&self) -> anyhow::Result<bool> {
         self.save_changed().await
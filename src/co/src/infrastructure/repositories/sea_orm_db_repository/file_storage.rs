@@ -2,7 +2,9 @@ use super::SeaOrmDbRepository;
 use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
 use database_model::system::prelude::*;
 use kernel::prelude::*;
-use sea_orm::{ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter, QueryTrait};
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter, QueryTrait,
+};
 use std::sync::atomic::Ordering;
 
 #[async_trait::async_trait]
@@ -91,4 +93,66 @@ impl IFileStorageRepo for SeaOrmDbRepository {
             )))?;
         Ok(x.server_url)
     }
+
+    async fn change_storage_server(
+        &self,
+        from_storage_server_id: Uuid,
+        entity: FileStorage,
+    ) -> Anyhow {
+        let mut stmts = self.statements.lock().await;
+        let stmt = FileStorageEntity::update_many()
+            .col_expr(
+                FileStorageColumn::StorageServerId,
+                Expr::value(entity.storage_server_id),
+            )
+            .col_expr(FileStorageColumn::ServerUrl, Expr::value(entity.server_url))
+            .col_expr(FileStorageColumn::Corrupted, Expr::value(entity.corrupted))
+            .filter(
+                Condition::all()
+                    .add(FileStorageColumn::StorageServerId.eq(from_storage_server_id))
+                    .add(FileStorageColumn::FileMetadataId.eq(entity.meta_id)),
+            )
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn delete_by_storage_server_id_and_meta_id(
+        &self,
+        storage_server_id: Uuid,
+        meta_id: Uuid,
+    ) -> Anyhow {
+        let mut stmts = self.statements.lock().await;
+        let stmt = FileStorageEntity::delete_many()
+            .filter(
+                Condition::all()
+                    .add(FileStorageColumn::StorageServerId.eq(storage_server_id))
+                    .add(FileStorageColumn::FileMetadataId.eq(meta_id)),
+            )
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn set_corrupted(
+        &self,
+        storage_server_id: Uuid,
+        meta_id: Uuid,
+        corrupted: bool,
+    ) -> Anyhow {
+        let mut stmts = self.statements.lock().await;
+        let stmt = FileStorageEntity::update_many()
+            .col_expr(FileStorageColumn::Corrupted, Expr::value(corrupted))
+            .filter(
+                Condition::all()
+                    .add(FileStorageColumn::StorageServerId.eq(storage_server_id))
+                    .add(FileStorageColumn::FileMetadataId.eq(meta_id)),
+            )
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
 }
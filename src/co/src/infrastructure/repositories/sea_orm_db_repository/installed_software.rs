@@ -1,7 +1,7 @@
 use super::SeaOrmDbRepository;
 use database_model::agent::prelude::{InstalledSoftwareColumn, InstalledSoftwareEntity};
 use kernel::prelude::*;
-use sea_orm::{sea_query::Expr, EntityTrait, QueryFilter};
+use sea_orm::{sea_query::Expr, Condition, EntityTrait, QueryFilter};
 
 #[async_trait::async_trait]
 impl IInstalledSoftwareRepository for SeaOrmDbRepository {
@@ -27,4 +27,29 @@ impl IInstalledSoftwareRepository for SeaOrmDbRepository {
         }
         Ok(r)
     }
+
+    async fn is_software_installed_on_cluster(
+        &self,
+        cluster_id: Uuid,
+        software_name: &str,
+        required_install_arguments: &[String],
+    ) -> anyhow::Result<bool> {
+        let installed_software_list = InstalledSoftwareEntity::find()
+            .filter(Expr::col(InstalledSoftwareColumn::SoftwareName).eq(software_name))
+            .filter(
+                Condition::any()
+                    .add(Expr::col(InstalledSoftwareColumn::ClusterId).eq(cluster_id))
+                    .add(Expr::col(InstalledSoftwareColumn::ClusterId).is_null()),
+            )
+            .all(self.db.get_connection())
+            .await?;
+        for installed_software in installed_software_list {
+            let repo_install_arguments =
+                serde_json::from_value::<Vec<String>>(installed_software.install_argument)?;
+            if required_install_arguments.iter().all(|el| repo_install_arguments.contains(el)) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
 }
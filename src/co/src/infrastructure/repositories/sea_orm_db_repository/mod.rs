@@ -8,6 +8,7 @@ use std::sync::{
 use tokio::sync::Mutex;
 use uuid::Uuid;
 mod cluster;
+mod custom_node;
 mod file_meta;
 mod file_storage;
 mod installed_software;
@@ -15,8 +16,10 @@ mod net_disk;
 mod node_instance;
 mod software_block_list;
 mod storage_server;
+mod user_resource;
 mod workflow_draft;
 mod workflow_instance;
+mod workflow_template;
 
 #[derive(Builder)]
 pub struct SeaOrmDbRepository {
@@ -220,4 +220,24 @@ impl INetDiskRepo for SeaOrmDbRepository {
             .await?
             .map(|el| el.id))
     }
+
+    async fn get_all_by_meta_id(&self, meta_id: Uuid) -> AnyhowResult<Vec<NetDisk>> {
+        FileSystemEntity::find()
+            .filter(FileSystemColumn::FileMetadataId.eq(meta_id))
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
+    }
+
+    async fn delete_all_by_meta_id(&self, meta_id: Uuid) -> Anyhow {
+        let mut stmts = self.statements.lock().await;
+        let stmt = FileSystemEntity::delete_many()
+            .filter(FileSystemColumn::FileMetadataId.eq(meta_id))
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(())
+    }
 }
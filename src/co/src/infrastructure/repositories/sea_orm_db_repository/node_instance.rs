@@ -2,7 +2,10 @@ use super::SeaOrmDbRepository;
 use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
 use database_model::system::prelude::*;
 use kernel::prelude::*;
-use sea_orm::{prelude::Uuid, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryTrait};
+use sea_orm::{
+    prelude::Uuid, ColumnTrait, ConnectionTrait, DatabaseBackend, EntityTrait, QueryFilter,
+    QueryOrder, QueryTrait, Statement,
+};
 use std::{str::FromStr, sync::atomic::Ordering};
 
 #[async_trait::async_trait]
@@ -67,6 +70,7 @@ impl INodeInstanceRepository for SeaOrmDbRepository {
         let res = NodeInstanceEntity::find()
             .filter(NodeInstanceColumn::BatchParentId.is_not_null())
             .filter(NodeInstanceColumn::BatchParentId.eq(batch_parent_id))
+            .order_by_asc(NodeInstanceColumn::BatchIndex)
             .all(self.db.get_connection())
             .await?;
         let mut r = vec![];
@@ -92,6 +96,7 @@ impl INodeInstanceRepository for SeaOrmDbRepository {
         Ok(res.iter().all(|el| {
             el.status.eq(&(NodeInstanceStatus::Finished as i32))
                 || el.status.eq(&(NodeInstanceStatus::Standby as i32))
+                || el.status.eq(&(NodeInstanceStatus::Skipped as i32))
         }))
     }
 
@@ -127,22 +132,71 @@ impl INodeInstanceRepository for SeaOrmDbRepository {
     }
 
     async fn get_nth_of_batch_tasks(&self, sub_node_id: Uuid) -> anyhow::Result<usize> {
-        let batch_parent_id = NodeInstanceEntity::find()
+        let batch_index = NodeInstanceEntity::find()
             .filter(NodeInstanceColumn::Id.eq(sub_node_id))
             .one(self.db.get_connection())
             .await?
             .ok_or(anyhow::anyhow!("No such node!"))?
-            .id;
-        let sub_nodes = NodeInstanceEntity::find()
-            .filter(NodeInstanceColumn::BatchParentId.eq(batch_parent_id))
+            .batch_index;
+        Ok(batch_index.ok_or(anyhow::anyhow!("No such sub node!"))? as usize)
+    }
+
+    async fn get_user_queued_node_instances(
+        &self,
+        user_id: Uuid,
+    ) -> anyhow::Result<Vec<NodeInstance>> {
+        let res = NodeInstanceEntity::find()
+            .inner_join(FlowInstanceEntity)
+            .filter(FlowInstanceColumn::UserId.eq(user_id))
+            .filter(NodeInstanceColumn::Status.eq(NodeInstanceStatus::Queued as i32))
+            .all(self.db.get_connection())
+            .await?;
+        let mut r = vec![];
+        for el in res.into_iter() {
+            r.push(el.try_into()?);
+        }
+        Ok(r)
+    }
+
+    async fn get_all_due_retry_node_instances(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<NodeInstance>> {
+        let res = NodeInstanceEntity::find()
+            .filter(NodeInstanceColumn::Status.eq(NodeInstanceStatus::Pending as i32))
+            .filter(NodeInstanceColumn::RetryAt.lte(now))
             .all(self.db.get_connection())
             .await?;
-        let mut nth = None;
-        for (i, sub_node) in sub_nodes.iter().enumerate() {
-            if sub_node.id.eq(&sub_node_id) {
-                nth = Some(i)
-            }
+        let mut r = vec![];
+        for el in res.into_iter() {
+            r.push(el.try_into()?);
         }
-        Ok(nth.ok_or(anyhow::anyhow!("No such sub node!"))?)
+        Ok(r)
+    }
+
+    async fn take_quota_reservation(
+        &self,
+        node_instance_id: Uuid,
+    ) -> anyhow::Result<Option<QuotaReservation>> {
+        // 锁定原行后置空，并返回置空前的值
+        let mut sql = String::from("UPDATE node_instance AS n SET quota_reservation = NULL");
+        sql.push_str(" FROM (SELECT id, quota_reservation FROM node_instance");
+        sql.push_str(" WHERE id = $1 FOR UPDATE) AS old");
+        sql.push_str(" WHERE n.id = old.id AND old.quota_reservation IS NOT NULL");
+        sql.push_str(" RETURNING old.quota_reservation");
+        let Some(result) = self
+            .db
+            .get_connection()
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                &sql,
+                vec![node_instance_id.into()],
+            ))
+            .await?
+        else {
+            return Ok(None);
+        };
+        let reservation: serde_json::Value = result.try_get("", "quota_reservation")?;
+        Ok(Some(serde_json::from_value(reservation)?))
     }
 }
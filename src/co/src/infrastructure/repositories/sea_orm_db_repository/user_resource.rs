@@ -0,0 +1,117 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::IMutableRepository;
+use database_model::system::prelude::*;
+use kernel::prelude::*;
+use sea_orm::{
+    prelude::Uuid, ColumnTrait, ConnectionTrait, DatabaseBackend, EntityTrait, QueryFilter,
+    QueryTrait, Statement,
+};
+use std::sync::atomic::Ordering;
+
+#[async_trait::async_trait]
+impl IMutableRepository<UserResource> for SeaOrmDbRepository {
+    async fn update(&self, entity: UserResource) -> anyhow::Result<UserResource> {
+        let mut stmts = self.statements.lock().await;
+        let stmt =
+            UserResourceEntity::update(UserResourceModel::from(entity.to_owned()).into_set())
+                .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn insert(&self, entity: UserResource) -> anyhow::Result<UserResource> {
+        let mut stmts = self.statements.lock().await;
+        let stmt =
+            UserResourceEntity::insert(UserResourceModel::from(entity.to_owned()).into_set())
+                .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: UserResource) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(
+        &self,
+        _uuid: &str,
+        _entity: Option<UserResource>,
+    ) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+#[async_trait::async_trait]
+impl IUserResourceRepository for SeaOrmDbRepository {
+    async fn get_by_user_and_cluster(
+        &self,
+        user_id: Uuid,
+        cluster_id: Uuid,
+    ) -> anyhow::Result<Option<UserResource>> {
+        Ok(UserResourceEntity::find()
+            .filter(UserResourceColumn::UserId.eq(user_id))
+            .filter(UserResourceColumn::ClusterId.eq(cluster_id))
+            .one(self.db.get_connection())
+            .await?
+            .map(UserResource::from))
+    }
+
+    async fn try_reserve(
+        &self,
+        id: Uuid,
+        reservation: &QuotaReservation,
+    ) -> anyhow::Result<Option<UserResource>> {
+        // 上限为 0 时不限制
+        let mut sql = String::from("UPDATE user_resource");
+        sql.push_str(" SET core_number = core_number + $2, memory = memory + $3");
+        sql.push_str(" WHERE id = $1");
+        sql.push_str(" AND (core_number_max = 0 OR core_number + $2 <= core_number_max)");
+        sql.push_str(" AND (memory_max = 0 OR memory + $3 <= memory_max)");
+        sql.push_str(" RETURNING *");
+        Ok(UserResourceEntity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                &sql,
+                vec![
+                    id.into(),
+                    (reservation.cores as i64).into(),
+                    (reservation.memory as i64).into(),
+                ],
+            ))
+            .one(self.db.get_connection())
+            .await?
+            .map(UserResource::from))
+    }
+
+    async fn release_reserved(
+        &self,
+        id: Uuid,
+        reservation: &QuotaReservation,
+        used_storage: u64,
+    ) -> anyhow::Result<UserResource> {
+        let mut sql = String::from("UPDATE user_resource");
+        sql.push_str(" SET core_number = GREATEST(core_number - $2, 0)");
+        sql.push_str(", memory = GREATEST(memory - $3, 0)");
+        sql.push_str(", storage_capacity = storage_capacity + $4");
+        sql.push_str(" WHERE id = $1 RETURNING *");
+        Ok(UserResourceEntity::find()
+            .from_raw_sql(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                &sql,
+                vec![
+                    id.into(),
+                    (reservation.cores as i64).into(),
+                    (reservation.memory as i64).into(),
+                    (used_storage as i64).into(),
+                ],
+            ))
+            .one(self.db.get_connection())
+            .await?
+            .map(UserResource::from)
+            .ok_or(anyhow::anyhow!(
+                "There is no such user_resource with id: {id}"
+            ))?)
+    }
+}
@@ -1,9 +1,12 @@
 use super::SeaOrmDbRepository;
-use alice_architecture::repository::IReadOnlyRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use chrono::Utc;
 use database_model::system::prelude::*;
 use kernel::prelude::*;
-use sea_orm::{prelude::Uuid, ColumnTrait, EntityTrait, QueryFilter};
-use std::str::FromStr;
+use sea_orm::{
+    prelude::Uuid, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryTrait, Set,
+};
+use std::{str::FromStr, sync::atomic::Ordering};
 
 #[async_trait::async_trait]
 impl IReadOnlyRepository<WorkflowDraft> for SeaOrmDbRepository {
@@ -22,3 +25,44 @@ impl IReadOnlyRepository<WorkflowDraft> for SeaOrmDbRepository {
         unimplemented!()
     }
 }
+
+#[async_trait::async_trait]
+impl IMutableRepository<WorkflowDraft> for SeaOrmDbRepository {
+    async fn update(&self, _entity: WorkflowDraft) -> anyhow::Result<WorkflowDraft> {
+        unimplemented!()
+    }
+    async fn insert(&self, entity: WorkflowDraft) -> anyhow::Result<WorkflowDraft> {
+        let mut stmts = self.statements.lock().await;
+        let now = Utc::now();
+        let active_model = FlowDraftActiveModel {
+            id: Set(entity.id),
+            name: Set(entity.name.to_owned()),
+            description: Set(entity.description.to_owned()),
+            logo: Set(entity.logo.to_owned()),
+            spec: Set(serde_json::to_value(&entity.spec)?),
+            user_id: Set(self.user_id(None)?),
+            created_time: Set(now),
+            last_modified_time: Set(now),
+        };
+        let stmt = FlowDraftEntity::insert(active_model)
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: WorkflowDraft) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(
+        &self,
+        _uuid: &str,
+        _entity: Option<WorkflowDraft>,
+    ) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<WorkflowDraft> for SeaOrmDbRepository {}
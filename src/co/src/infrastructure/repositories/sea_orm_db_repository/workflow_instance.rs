@@ -91,4 +91,22 @@ impl IWorkflowInstanceRepository for SeaOrmDbRepository {
         self.update(entity).await?;
         Ok(())
     }
+
+    async fn get_all_finished_before(
+        &self,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<WorkflowInstance>> {
+        FlowInstanceEntity::find()
+            .filter(FlowInstanceColumn::Status.is_in([
+                WorkflowInstanceStatus::Finished as i32,
+                WorkflowInstanceStatus::Error as i32,
+                WorkflowInstanceStatus::Stopped as i32,
+            ]))
+            .filter(FlowInstanceColumn::LastModifiedTime.lt(time))
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
+    }
 }
@@ -0,0 +1,115 @@
+use super::SeaOrmDbRepository;
+use alice_architecture::repository::{IDBRepository, IMutableRepository, IReadOnlyRepository};
+use database_model::system::prelude::*;
+use kernel::prelude::*;
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, QueryTrait,
+};
+use std::{str::FromStr, sync::atomic::Ordering};
+
+#[async_trait::async_trait]
+impl IReadOnlyRepository<WorkflowTemplate> for SeaOrmDbRepository {
+    async fn get_by_id(&self, uuid: &str) -> anyhow::Result<WorkflowTemplate> {
+        FlowTemplateEntity::find_by_id(Uuid::from_str(uuid)?)
+            .one(self.db.get_connection())
+            .await?
+            .ok_or(anyhow::anyhow!("There is no such flow_template id: {uuid}"))?
+            .try_into()
+    }
+    async fn get_all(&self) -> anyhow::Result<Vec<WorkflowTemplate>> {
+        unimplemented!()
+    }
+}
+
+#[async_trait::async_trait]
+impl IMutableRepository<WorkflowTemplate> for SeaOrmDbRepository {
+    async fn update(&self, _entity: WorkflowTemplate) -> anyhow::Result<WorkflowTemplate> {
+        anyhow::bail!("Published workflow templates can't be modified.")
+    }
+    async fn insert(&self, entity: WorkflowTemplate) -> anyhow::Result<WorkflowTemplate> {
+        let mut stmts = self.statements.lock().await;
+        let model = FlowTemplateModel::try_from(entity.to_owned())?;
+        let stmt = FlowTemplateEntity::insert(model.into_set())
+            .build(self.db.get_connection().get_database_backend());
+        stmts.push(stmt);
+        self.can_drop.store(false, Ordering::Relaxed);
+        Ok(entity)
+    }
+    async fn delete(&self, _entity: WorkflowTemplate) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn delete_by_id(
+        &self,
+        _uuid: &str,
+        _entity: Option<WorkflowTemplate>,
+    ) -> anyhow::Result<bool> {
+        unimplemented!()
+    }
+    async fn save_changed(&self) -> anyhow::Result<bool> {
+        self.save_changed().await
+    }
+}
+
+impl IDBRepository<WorkflowTemplate> for SeaOrmDbRepository {}
+
+#[async_trait::async_trait]
+impl IWorkflowTemplateRepository for SeaOrmDbRepository {
+    async fn search(
+        &self,
+        user_id: Uuid,
+        groups: Vec<String>,
+        keyword: Option<String>,
+    ) -> anyhow::Result<Vec<WorkflowTemplate>> {
+        let mut visible = Condition::any()
+            .add(FlowTemplateColumn::UserId.eq(user_id))
+            .add(FlowTemplateColumn::Visibility.eq(2));
+        if !groups.is_empty() {
+            visible = visible.add(
+                Condition::all()
+                    .add(FlowTemplateColumn::Visibility.eq(1))
+                    .add(FlowTemplateColumn::ShareGroup.is_in(groups)),
+            );
+        }
+        let mut condition = Condition::all().add(visible);
+        if let Some(keyword) = keyword.filter(|el| !el.is_empty()) {
+            condition = condition.add(
+                Condition::any()
+                    .add(FlowTemplateColumn::Name.contains(&keyword))
+                    .add(FlowTemplateColumn::Description.contains(&keyword)),
+            );
+        }
+        FlowTemplateEntity::find()
+            .filter(condition)
+            .order_by_desc(FlowTemplateColumn::CreatedTime)
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
+    }
+
+    async fn get_all_by_family_id(&self, family_id: Uuid) -> anyhow::Result<Vec<WorkflowTemplate>> {
+        FlowTemplateEntity::find()
+            .filter(
+                Condition::any()
+                    .add(FlowTemplateColumn::FamilyId.eq(family_id))
+                    .add(FlowTemplateColumn::Id.eq(family_id)),
+            )
+            .order_by_desc(FlowTemplateColumn::Version)
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.try_into())
+            .collect()
+    }
+
+    async fn get_user_groups(&self, user_id: Uuid) -> anyhow::Result<Vec<String>> {
+        Ok(TemplateShareGroupMemberEntity::find()
+            .filter(TemplateShareGroupMemberColumn::UserId.eq(user_id))
+            .all(self.db.get_connection())
+            .await?
+            .into_iter()
+            .map(|el| el.group_name)
+            .collect())
+    }
+}
@@ -1,4 +1,7 @@
 use super::{
+    background_service::{
+        FileGarbageCollectionRunner, FileLifecycleRunner, FileScrubRunner, NodeRetryRunner,
+    },
     external_services::{
         FileUploadRunnerBuilder, IFileUploadRunner, InnerUsecaseSelectServiceBuilder,
         MinioServerBrokerServiceBuilder,
@@ -141,7 +144,7 @@ build_container! {
             )
         }
     }
-    scoped meta_storage_service: Arc<dyn IMetaStorageService> {
+    scoped meta_storage_service: Arc<dyn IMetaStorageService + Send + Sync> {
         build {
             Arc::new(
                 MetaStorageServiceBuilder::default()
@@ -151,21 +154,78 @@ build_container! {
             )
         }
     }
+    scoped file_system_server_broker_service: Arc<FileSystemServerBrokerService> {
+        build {
+            Arc::new(
+                FileSystemServerBrokerServiceBuilder::default()
+                .cache_service(cache_service.clone())
+                .build()?
+            )
+        }
+    }
     scoped storage_server_broker_service: Arc<dyn IStorageServerBrokerService> {
         build {
             Arc::new(
-                MinioServerBrokerServiceBuilder::default()
-                .meta_storage_service(meta_storage_service.clone())
+                StorageServerBrokerServiceBuilder::default()
+                .object_storage_broker_service(Arc::new(
+                    MinioServerBrokerServiceBuilder::default()
+                    .meta_storage_service(meta_storage_service.clone())
+                    .build()?
+                ))
+                .file_system_broker_service(file_system_server_broker_service.clone())
                 .build()?
             )
         }
     }
-    scoped resources_service: Arc<dyn IResourcesService> {
+    scoped resources_service: Arc<dyn IResourcesService + Send + Sync> {
         build {
             Arc::new(
                 ResourcesServiceBuilder::default()
                 .default_storage_server_id(*self.co_config.default_storage_server_id())
                 .storage_server_repo(sea_orm_repository.clone())
+                .cluster_repo(sea_orm_repository.clone())
+                .build()?
+            )
+        }
+    }
+    scoped file_reference_service: Arc<dyn IFileReferenceService + Send + Sync> {
+        build {
+            Arc::new(
+                FileReferenceServiceBuilder::default()
+                .grace_period(chrono::Duration::seconds(*self.file_system_config.garbage_collection().grace_secs()))
+                .meta_repo(sea_orm_repository.clone())
+                .storage_repo(sea_orm_repository.clone())
+                .resources_service(resources_service.clone())
+                .storage_server_broker_service(storage_server_broker_service.clone())
+                .snapshot_service(snapshot_service.clone())
+                .build()?
+            )
+        }
+    }
+    scoped file_scrub_service: Arc<dyn IFileScrubService + Send + Sync> {
+        build {
+            Arc::new(
+                FileScrubServiceBuilder::default()
+                .meta_repo(sea_orm_repository.clone())
+                .storage_repo(sea_orm_repository.clone())
+                .resources_service(resources_service.clone())
+                .storage_server_broker_service(storage_server_broker_service.clone())
+                .build()?
+            )
+        }
+    }
+    scoped file_lifecycle_service: Arc<dyn IFileLifecycleService + Send + Sync> {
+        build {
+            Arc::new(
+                FileLifecycleServiceBuilder::default()
+                .policy(self.file_system_config.lifecycle().policy().to_owned())
+                .workflow_instance_repo(sea_orm_repository.clone())
+                .net_disk_repo(sea_orm_repository.clone())
+                .meta_repo(sea_orm_repository.clone())
+                .storage_repo(sea_orm_repository.clone())
+                .resources_service(resources_service.clone())
+                .storage_server_broker_service(storage_server_broker_service.clone())
+                .snapshot_service(snapshot_service.clone())
                 .build()?
             )
         }
@@ -268,6 +328,55 @@ build_container! {
             )
         }
     }
+    scoped cluster_selection_service: Arc<dyn IClusterSelectionService + Send + Sync> {
+        build {
+            Arc::new(
+                ClusterSelectionServiceBuilder::default()
+                .cluster_repository(sea_orm_repository.clone())
+                .installed_software_repository(sea_orm_repository.clone())
+                .workflow_instance_repository(sea_orm_repository.clone())
+                .build()?
+            )
+        }
+    }
+    scoped quota_service: Arc<dyn IQuotaService + Send + Sync> {
+        build {
+            Arc::new(
+                QuotaServiceBuilder::default()
+                .user_resource_repository(sea_orm_repository.clone())
+                .workflow_instance_repository(sea_orm_repository.clone())
+                .mq_producer(self.kafka_mq_producer.to_owned())
+                .alert_topic(self.co_config.quota().alert_topic().to_owned())
+                .policy(self.co_config.quota().policy().to_owned())
+                .build()?
+            )
+        }
+    }
+    scoped budget_service: Arc<dyn IBudgetService + Send + Sync> {
+        build {
+            Arc::new(
+                BudgetServiceBuilder::default()
+                .http_client(self.http_client.clone())
+                .billing_url(self.co_config.budget().billing_url().to_owned())
+                .secret(self.co_config.budget().secret().to_owned())
+                .build()?
+            )
+        }
+    }
+    scoped task_dispatch_service: Arc<dyn ITaskDispatchService + Send + Sync> {
+        build {
+            Arc::new(
+                TaskDispatchServiceBuilder::default()
+                .cluster_selection_service(cluster_selection_service.clone())
+                .budget_service(budget_service.clone())
+                .quota_service(quota_service.clone())
+                .task_distribution_service(task_distribution_service.clone())
+                .node_instance_repository(sea_orm_repository.clone())
+                .workflow_instance_repository(sea_orm_repository.clone())
+                .build()?
+            )
+        }
+    }
     scoped software_computing_usecase_service: Arc<SoftwareComputingUsecaseService>{
         build{
             Arc::new(
@@ -277,7 +386,7 @@ build_container! {
                 .task_distribution_service(task_distribution_service.clone())
                 .software_block_list_repository(sea_orm_repository.clone())
                 .installed_software_repository(sea_orm_repository.clone())
-                .cluster_repository(sea_orm_repository.clone())
+                .task_dispatch_service(task_dispatch_service.clone())
                 .node_instance_repository(sea_orm_repository.clone())
                 .workflow_instance_repository(sea_orm_repository.clone())
                 .build()?
@@ -290,12 +399,22 @@ build_container! {
             Arc::new(NoActionUsecaseService::new(internal_message_queue_producer))
         }
     }
+    scoped condition_usecase_service: Arc<ConditionUsecaseService> {
+        build {
+            let internal_message_queue_producer: Arc<InternalMessageQueueProducer> = sp.provide();
+            Arc::new(ConditionUsecaseService::new(
+                redis_repository.clone(),
+                internal_message_queue_producer,
+            ))
+        }
+    }
     scoped script_usecase_service: Arc<ScriptUsecaseService> {
         build {
             Arc::new(ScriptUsecaseService::new(
                 task_distribution_service.clone(),
+                task_dispatch_service.clone(),
                 sea_orm_repository.clone(),
-                sea_orm_repository.clone(),
+                redis_repository.clone(),
             ))
         }
     }
@@ -312,6 +431,7 @@ build_container! {
             map.insert(no_action_usecase_service.get_service_type(), no_action_usecase_service.clone());
             map.insert(software_computing_usecase_service.get_service_type(), software_computing_usecase_service.clone());
             map.insert(script_usecase_service.get_service_type(), script_usecase_service.clone());
+            map.insert(condition_usecase_service.get_service_type(), condition_usecase_service.clone());
             Arc::new(
                 InnerUsecaseSelectServiceBuilder::default()
                 .usecases(map)
@@ -340,6 +460,7 @@ build_container! {
                 .node_instance_repository(sea_orm_repository.clone())
                 .workflow_instance_repository(sea_orm_repository.clone())
                 .schedule_service(workflow_schedule_service.clone())
+                .quota_service(quota_service.clone())
                 .mq_producer(self.kafka_mq_producer.to_owned())
                 .bill_topic(self.co_config.bill_topic().to_owned())
                 .build()?
@@ -355,6 +476,28 @@ build_container! {
                 .node_instance_repository(sea_orm_repository.clone())
                 .file_metadata_repository(sea_orm_repository.clone())
                 .workflow_schedule_service(workflow_schedule_service.clone())
+                .cluster_repository(sea_orm_repository.clone())
+                .custom_node_repository(sea_orm_repository.clone())
+                .build()?
+            )
+        }
+    }
+    scoped custom_node_service: Arc<dyn ICustomNodeService + Send + Sync> {
+        build{
+            Arc::new(
+                CustomNodeServiceBuilder::default()
+                .custom_node_repository(sea_orm_repository.clone())
+                .workflow_draft_repository(sea_orm_repository.clone())
+                .build()?
+            )
+        }
+    }
+    scoped workflow_template_service: Arc<dyn IWorkflowTemplateService + Send + Sync> {
+        build{
+            Arc::new(
+                WorkflowTemplateServiceBuilder::default()
+                .workflow_template_repository(sea_orm_repository.clone())
+                .workflow_draft_repository(sea_orm_repository.clone())
                 .build()?
             )
         }
@@ -413,7 +556,25 @@ build_container! {
         fn_mapper.insert(realtime_request_topic, internal_message_consumers::realtime_file_consumer);
         fn_mapper.insert(ws_server_topic, internal_message_consumers::ws_server_file_consumer);
         let internal_message_queue_producer: Arc<InternalMessageQueueProducer> = arc_sp.provide();
-        let mq = Arc::new(InternalMessageQueueConsumer::new(internal_message_queue_producer.get_receiver(), arc_sp, fn_mapper));
+        let mq = Arc::new(InternalMessageQueueConsumer::new(internal_message_queue_producer.get_receiver(), arc_sp.clone(), fn_mapper));
         sp.background_services.push(mq);
+        let lifecycle = arc_sp.file_system_config.lifecycle();
+        let policy = lifecycle.policy();
+        if policy.intermediate_output_expire_days.is_some() || policy.cold_storage.is_some() {
+            let period = std::time::Duration::from_secs(*lifecycle.interval_secs());
+            sp.background_services.push(Arc::new(FileLifecycleRunner::new(arc_sp.clone(), period, *lifecycle.dry_run())));
+        }
+        let garbage_collection = arc_sp.file_system_config.garbage_collection();
+        if *garbage_collection.enabled() {
+            let period = std::time::Duration::from_secs(*garbage_collection.interval_secs());
+            sp.background_services.push(Arc::new(FileGarbageCollectionRunner::new(arc_sp.clone(), period, *garbage_collection.dry_run())));
+        }
+        let scrub = arc_sp.file_system_config.scrub();
+        if *scrub.enabled() {
+            let period = std::time::Duration::from_secs(*scrub.interval_secs());
+            sp.background_services.push(Arc::new(FileScrubRunner::new(arc_sp.clone(), period)));
+        }
+        let period = std::time::Duration::from_secs(*arc_sp.co_config.retry().interval_secs());
+        sp.background_services.push(Arc::new(NodeRetryRunner::new(arc_sp.clone(), period)));
     }
 }
@@ -34,6 +34,10 @@ pub async fn initialize_web_host(sp: Arc<ServiceProvider>) {
             .service(controllers::workflow_editor::get_node_draft)
             .service(controllers::workflow_editor::get_workflow_component_categories)
             .service(controllers::workflow_editor::validate_workflow_draft)
+            .service(controllers::workflow_editor::get_custom_nodes)
+            .service(controllers::workflow_editor::get_custom_node_draft)
+            .service(controllers::workflow_editor::package_custom_node)
+            .service(controllers::workflow_editor::remove_custom_node)
             .service(controllers::workflow_engine::start_workflow)
             .service(controllers::workflow_engine::submit_workflow)
             .service(controllers::workflow_engine::receive_node_status)
@@ -41,6 +45,11 @@ pub async fn initialize_web_host(sp: Arc<ServiceProvider>) {
             .service(controllers::workflow_engine::continue_workflow)
             .service(controllers::workflow_engine::terminate_workflow)
             .service(controllers::workflow_engine::get_node_cmd)
+            .service(controllers::workflow_template::publish_workflow_template)
+            .service(controllers::workflow_template::search_workflow_templates)
+            .service(controllers::workflow_template::get_workflow_template)
+            .service(controllers::workflow_template::get_workflow_template_versions)
+            .service(controllers::workflow_template::instantiate_workflow_template)
             .service(controllers::text_storage::upload)
             .service(controllers::text_storage::get_by_ids)
             .service(controllers::file_storage::create_multipart_from_flow_editor)
@@ -56,6 +65,11 @@ pub async fn initialize_web_host(sp: Arc<ServiceProvider>) {
             .service(controllers::usecase_editor::package_validate)
             .service(controllers::file_storage::head_rangely_download_file)
             .service(controllers::file_storage::get_rangely_download_file)
+            .service(controllers::file_storage::get_local_file)
+            .service(controllers::file_storage::set_file_pinned)
+            .service(controllers::file_storage::get_lifecycle_report)
+            .service(controllers::file_storage::get_garbage_collection_report)
+            .service(controllers::file_storage::get_deduplication_report)
             .service(controllers::file_storage::cancel_partial_upload)
             .service(controllers::file_storage::upload_realtime_file)
             .service(controllers::file_storage::retry_partial_upload)
@@ -63,6 +77,7 @@ pub async fn initialize_web_host(sp: Arc<ServiceProvider>) {
             .service(controllers::snapshot::get_snapshots_infos)
             .service(controllers::snapshot::get_snapshot)
             .service(controllers::snapshot::del_snapshot)
+            .service(controllers::agent::update_used_resource)
     })
     .bind((
         common_config.host().bind_address().to_owned(),
@@ -0,0 +1,76 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230320_1047_add_cluster_selection"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ClusterResourceEntity)
+                    .add_column(
+                        ColumnDef::new(ClusterResourceColumn::AllocatedMemory)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(ClusterResourceColumn::AllocatedCpuCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(ClusterResourceColumn::QueuingTaskCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(
+                        ColumnDef::new(ClusterResourceColumn::RunningTaskCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .add_column(ColumnDef::new(NodeInstanceColumn::ScheduleReason).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .drop_column(NodeInstanceColumn::ScheduleReason)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ClusterResourceEntity)
+                    .drop_column(ClusterResourceColumn::AllocatedMemory)
+                    .drop_column(ClusterResourceColumn::AllocatedCpuCount)
+                    .drop_column(ClusterResourceColumn::QueuingTaskCount)
+                    .drop_column(ClusterResourceColumn::RunningTaskCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
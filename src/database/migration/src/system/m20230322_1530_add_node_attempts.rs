@@ -0,0 +1,39 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230322_1530_add_node_attempts"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .add_column(
+                        ColumnDef::new(NodeInstanceColumn::Attempts)
+                            .json()
+                            .not_null()
+                            .default("[]"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .drop_column(NodeInstanceColumn::Attempts)
+                    .to_owned(),
+            )
+            .await
+    }
+}
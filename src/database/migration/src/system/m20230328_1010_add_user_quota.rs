@@ -0,0 +1,34 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230328_1010_add_user_quota"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .add_column(ColumnDef::new(NodeInstanceColumn::QuotaReservation).json().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .drop_column(NodeInstanceColumn::QuotaReservation)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,58 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230401_0930_add_bill_breakdown"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceBillingEntity)
+                    .add_column(ColumnDef::new(NodeInstanceBillingColumn::Breakdown).json().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FlowInstanceBillingEntity)
+                    .modify_column(
+                        ColumnDef::new(FlowInstanceBillingColumn::TotalPrice)
+                            .decimal_len(20, 10)
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FlowInstanceBillingEntity)
+                    .modify_column(
+                        ColumnDef::new(FlowInstanceBillingColumn::TotalPrice)
+                            .decimal_len(12, 2)
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceBillingEntity)
+                    .drop_column(NodeInstanceBillingColumn::Breakdown)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,62 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{DbBackend, EntityTrait, Schema},
+};
+pub struct Migration;
+
+fn get_seaorm_create_stmt<E: EntityTrait>(e: E) -> TableCreateStatement {
+    let schema = Schema::new(DbBackend::Postgres);
+    schema.create_table_from_entity(e).if_not_exists().to_owned()
+}
+
+fn get_seaorm_drop_stmt<E: EntityTrait>(e: E) -> TableDropStatement {
+    Table::drop().table(e).if_exists().to_owned()
+}
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230403_1100_add_pricing_rules"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let stmts = vec![
+            get_seaorm_create_stmt(PricingRuleEntity),
+            get_seaorm_create_stmt(UserGroupEntity),
+        ];
+        for stmt in stmts {
+            manager.create_table(stmt.to_owned()).await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceBillingEntity)
+                    .add_column(ColumnDef::new(NodeInstanceBillingColumn::Segments).json().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceBillingEntity)
+                    .drop_column(NodeInstanceBillingColumn::Segments)
+                    .to_owned(),
+            )
+            .await?;
+        let stmts = vec![
+            get_seaorm_drop_stmt(PricingRuleEntity),
+            get_seaorm_drop_stmt(UserGroupEntity),
+        ];
+        for stmt in stmts {
+            manager.drop_table(stmt.to_owned()).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{DbBackend, Schema},
+};
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230405_1400_add_webhook_delivery"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserWebhookEntity)
+                    .add_column(
+                        ColumnDef::new(UserWebhookColumn::Secret).string().not_null().default(""),
+                    )
+                    .add_column(ColumnDef::new(UserWebhookColumn::EventTypes).json().null())
+                    .to_owned(),
+            )
+            .await?;
+        let schema = Schema::new(DbBackend::Postgres);
+        manager
+            .create_table(
+                schema
+                    .create_table_from_entity(WebhookDeliveryEntity)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_status_next_attempt_time")
+                    .table(WebhookDeliveryEntity)
+                    .col(WebhookDeliveryColumn::Status)
+                    .col(WebhookDeliveryColumn::NextAttemptTime)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDeliveryEntity).if_exists().to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserWebhookEntity)
+                    .drop_column(UserWebhookColumn::Secret)
+                    .drop_column(UserWebhookColumn::EventTypes)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,68 @@
+use database_model::{
+    sea_orm::{ConnectionTrait, Statement},
+    system::prelude::*,
+};
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{DbBackend, Schema},
+};
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230407_1000_add_invoice"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let schema = Schema::new(DbBackend::Postgres);
+        manager
+            .create_table(schema.create_table_from_entity(InvoiceEntity).if_not_exists().to_owned())
+            .await?;
+        // 每个账期只开具一张发票，用户范围的发票没有工作流实例，按空 uuid 参与唯一约束
+        let statement = Statement::from_string(
+            DbBackend::Postgres,
+            vec![
+                r#"CREATE UNIQUE INDEX "idx_invoice_user_id_flow_instance_id_period_start""#,
+                r#"ON "public"."invoice" ("user_id","#,
+                r#"COALESCE("flow_instance_id", '00000000-0000-0000-0000-000000000000'::uuid),"#,
+                r#""period_start")"#,
+            ]
+            .join(" "),
+        );
+        manager.get_connection().execute(statement).await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_node_instance_billing_created_time")
+                    .table(NodeInstanceBillingEntity)
+                    .col(NodeInstanceBillingColumn::CreatedTime)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_invoice_user_id_flow_instance_id_period_start")
+                    .table(InvoiceEntity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_node_instance_billing_created_time")
+                    .table(NodeInstanceBillingEntity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(InvoiceEntity).if_exists().to_owned())
+            .await
+    }
+}
@@ -0,0 +1,68 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230409_1000_add_template_versions"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FlowTemplateEntity)
+                    .add_column(ColumnDef::new(FlowTemplateColumn::FamilyId).uuid().null())
+                    .add_column(
+                        ColumnDef::new(FlowTemplateColumn::Version).integer().not_null().default(1),
+                    )
+                    .add_column(
+                        ColumnDef::new(FlowTemplateColumn::Visibility)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(FlowTemplateColumn::ShareGroup).string().null())
+                    .add_column(ColumnDef::new(FlowTemplateColumn::Parameters).json().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_flow_template_family_id_version")
+                    .table(FlowTemplateEntity)
+                    .col(FlowTemplateColumn::FamilyId)
+                    .col(FlowTemplateColumn::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_flow_template_family_id_version")
+                    .table(FlowTemplateEntity)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FlowTemplateEntity)
+                    .drop_column(FlowTemplateColumn::FamilyId)
+                    .drop_column(FlowTemplateColumn::Version)
+                    .drop_column(FlowTemplateColumn::Visibility)
+                    .drop_column(FlowTemplateColumn::ShareGroup)
+                    .drop_column(FlowTemplateColumn::Parameters)
+                    .to_owned(),
+            )
+            .await
+    }
+}
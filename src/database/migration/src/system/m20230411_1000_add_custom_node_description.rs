@@ -0,0 +1,36 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230411_1000_add_custom_node_description"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CustomNodeEntity)
+                    .add_column(
+                        ColumnDef::new(CustomNodeColumn::Description).text().not_null().default(""),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CustomNodeEntity)
+                    .drop_column(CustomNodeColumn::Description)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,142 @@
+use database_model::{
+    sea_orm::{ConnectionTrait, Statement},
+    system::prelude::*,
+};
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230415_1000_add_file_metadata_reference_count"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileMetadataEntity)
+                    .add_column(
+                        ColumnDef::new(FileMetadataColumn::ReferenceCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // 由数据库在网盘文件、工作流草稿、模板与实例变化时维护引用计数，其他服务直接修改这些表时也不会漏记
+        // 草稿、模板与实例的引用为节点输入中的文件与调度时预分配的节点输出文件
+        let statements = [
+            vec![
+                r#"CREATE OR REPLACE FUNCTION "spec_file_references"("spec" jsonb)"#,
+                r#"RETURNS TABLE ("meta_id" uuid, "count" bigint) AS $$"#,
+                r#"SELECT ("el" #>> '{}')::uuid, COUNT(*) FROM ("#,
+                r#"SELECT jsonb_path_query("spec", 'strict $.** ? (exists (@.fileMetadataId)).fileMetadataId') AS "el""#,
+                r#"UNION ALL"#,
+                r#"SELECT jsonb_path_query("spec","#,
+                r#"'strict $.** ? (exists (@.allTasksPreparedContentIds)).allTasksPreparedContentIds[*]')"#,
+                r#") AS "references" GROUP BY 1"#,
+                r#"$$ LANGUAGE sql IMMUTABLE"#,
+            ],
+            vec![
+                r#"CREATE OR REPLACE FUNCTION "count_spec_file_references"() RETURNS trigger AS $$"#,
+                r#"BEGIN"#,
+                r#"IF TG_OP <> 'INSERT' THEN"#,
+                r#"UPDATE "public"."file_metadata" SET "reference_count" = "reference_count" - "r"."count""#,
+                r#"FROM "spec_file_references"(OLD."spec"::jsonb) AS "r""#,
+                r#"WHERE "file_metadata"."id" = "r"."meta_id";"#,
+                r#"END IF;"#,
+                r#"IF TG_OP <> 'DELETE' THEN"#,
+                r#"UPDATE "public"."file_metadata" SET "reference_count" = "reference_count" + "r"."count""#,
+                r#"FROM "spec_file_references"(NEW."spec"::jsonb) AS "r""#,
+                r#"WHERE "file_metadata"."id" = "r"."meta_id";"#,
+                r#"END IF;"#,
+                r#"RETURN NULL;"#,
+                r#"END"#,
+                r#"$$ LANGUAGE plpgsql"#,
+            ],
+            vec![
+                r#"CREATE OR REPLACE FUNCTION "count_net_disk_file_references"() RETURNS trigger AS $$"#,
+                r#"BEGIN"#,
+                r#"IF TG_OP <> 'INSERT' AND OLD."file_metadata_id" IS NOT NULL THEN"#,
+                r#"UPDATE "public"."file_metadata" SET "reference_count" = "reference_count" - 1"#,
+                r#"WHERE "id" = OLD."file_metadata_id";"#,
+                r#"END IF;"#,
+                r#"IF TG_OP <> 'DELETE' AND NEW."file_metadata_id" IS NOT NULL THEN"#,
+                r#"UPDATE "public"."file_metadata" SET "reference_count" = "reference_count" + 1"#,
+                r#"WHERE "id" = NEW."file_metadata_id";"#,
+                r#"END IF;"#,
+                r#"RETURN NULL;"#,
+                r#"END"#,
+                r#"$$ LANGUAGE plpgsql"#,
+            ],
+            vec![
+                r#"CREATE TRIGGER "count_file_references""#,
+                r#"AFTER INSERT OR DELETE OR UPDATE OF "file_metadata_id" ON "public"."file_system""#,
+                r#"FOR EACH ROW EXECUTE FUNCTION "count_net_disk_file_references"()"#,
+            ],
+            vec![
+                r#"CREATE TRIGGER "count_file_references""#,
+                r#"AFTER INSERT OR DELETE OR UPDATE OF "spec" ON "public"."flow_draft""#,
+                r#"FOR EACH ROW EXECUTE FUNCTION "count_spec_file_references"()"#,
+            ],
+            vec![
+                r#"CREATE TRIGGER "count_file_references""#,
+                r#"AFTER INSERT OR DELETE OR UPDATE OF "spec" ON "public"."flow_template""#,
+                r#"FOR EACH ROW EXECUTE FUNCTION "count_spec_file_references"()"#,
+            ],
+            vec![
+                r#"CREATE TRIGGER "count_file_references""#,
+                r#"AFTER INSERT OR DELETE OR UPDATE OF "spec" ON "public"."flow_instance""#,
+                r#"FOR EACH ROW EXECUTE FUNCTION "count_spec_file_references"()"#,
+            ],
+            // 统计已有的引用
+            vec![
+                r#"UPDATE "public"."file_metadata" SET "reference_count" = "r"."count" FROM ("#,
+                r#"SELECT "meta_id", SUM("count")::bigint AS "count" FROM ("#,
+                r#"SELECT "file_metadata_id" AS "meta_id", 1::bigint AS "count""#,
+                r#"FROM "public"."file_system" WHERE "file_metadata_id" IS NOT NULL"#,
+                r#"UNION ALL SELECT "r".* FROM "public"."flow_draft","#,
+                r#""spec_file_references"("flow_draft"."spec"::jsonb) AS "r""#,
+                r#"UNION ALL SELECT "r".* FROM "public"."flow_template","#,
+                r#""spec_file_references"("flow_template"."spec"::jsonb) AS "r""#,
+                r#"UNION ALL SELECT "r".* FROM "public"."flow_instance","#,
+                r#""spec_file_references"("flow_instance"."spec"::jsonb) AS "r""#,
+                r#") AS "references" GROUP BY "meta_id""#,
+                r#") AS "r" WHERE "file_metadata"."id" = "r"."meta_id""#,
+            ],
+        ];
+        for statement in statements {
+            let statement = Statement::from_string(DbBackend::Postgres, statement.join(" "));
+            manager.get_connection().execute(statement).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let statements = [
+            r#"DROP TRIGGER IF EXISTS "count_file_references" ON "public"."file_system""#,
+            r#"DROP TRIGGER IF EXISTS "count_file_references" ON "public"."flow_draft""#,
+            r#"DROP TRIGGER IF EXISTS "count_file_references" ON "public"."flow_template""#,
+            r#"DROP TRIGGER IF EXISTS "count_file_references" ON "public"."flow_instance""#,
+            r#"DROP FUNCTION IF EXISTS "count_net_disk_file_references"()"#,
+            r#"DROP FUNCTION IF EXISTS "count_spec_file_references"()"#,
+            r#"DROP FUNCTION IF EXISTS "spec_file_references"(jsonb)"#,
+        ];
+        for statement in statements {
+            let statement = Statement::from_string(DbBackend::Postgres, statement.to_string());
+            manager.get_connection().execute(statement).await?;
+        }
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileMetadataEntity)
+                    .drop_column(FileMetadataColumn::ReferenceCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,35 @@
+use database_model::agent::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230417_1000_add_installed_software_cluster"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 已有记录的集群未知，保持为空，视为所有集群均已安装
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledSoftwareEntity)
+                    .add_column(ColumnDef::new(InstalledSoftwareColumn::ClusterId).uuid().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(InstalledSoftwareEntity)
+                    .drop_column(InstalledSoftwareColumn::ClusterId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,49 @@
+use database_model::{
+    sea_orm::{ConnectionTrait, Statement},
+    system::prelude::*,
+};
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230417_1200_add_node_batch_index"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .add_column(ColumnDef::new(NodeInstanceColumn::BatchIndex).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+        // 已有的批量子节点按名称 {父节点名称}_sub_task_{次序} 得到次序
+        let statement = Statement::from_string(
+            DbBackend::Postgres,
+            vec![
+                r#"UPDATE "public"."node_instance" SET "batch_index" ="#,
+                r#"substring("name" from '_sub_task_(\d+)$')::integer"#,
+                r#"WHERE "batch_parent_id" IS NOT NULL"#,
+            ]
+            .join(" "),
+        );
+        manager.get_connection().execute(statement).await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NodeInstanceEntity)
+                    .drop_column(NodeInstanceColumn::BatchIndex)
+                    .to_owned(),
+            )
+            .await
+    }
+}
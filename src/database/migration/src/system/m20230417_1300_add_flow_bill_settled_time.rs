@@ -0,0 +1,38 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::prelude::*;
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230417_1300_add_flow_bill_settled_time"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FlowInstanceBillingEntity)
+                    .add_column(
+                        ColumnDef::new(FlowInstanceBillingColumn::SettledTime)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FlowInstanceBillingEntity)
+                    .drop_column(FlowInstanceBillingColumn::SettledTime)
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,44 @@
+use database_model::system::prelude::*;
+use sea_orm_migration::{
+    prelude::*,
+    sea_orm::{DbBackend, Schema},
+};
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m20230419_1000_add_template_share_group_member"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let schema = Schema::new(DbBackend::Postgres);
+        manager
+            .create_table(
+                schema
+                    .create_table_from_entity(TemplateShareGroupMemberEntity)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_template_share_group_member_user_id_group_name")
+                    .table(TemplateShareGroupMemberEntity)
+                    .col(TemplateShareGroupMemberColumn::UserId)
+                    .col(TemplateShareGroupMemberColumn::GroupName)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TemplateShareGroupMemberEntity).if_exists().to_owned())
+            .await
+    }
+}
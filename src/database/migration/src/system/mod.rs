@@ -2,6 +2,24 @@ use sea_orm_migration::{sea_orm::Database, *};
 mod m20220705_1439_create_table;
 mod m20230213_1401_add_billing_system;
 mod m20230217_1522_add_user_webhook;
+mod m20230320_1047_add_cluster_selection;
+mod m20230322_1530_add_node_attempts;
+mod m20230328_1010_add_user_quota;
+mod m20230330_1020_add_budget_and_ledger;
+mod m20230401_0930_add_bill_breakdown;
+mod m20230403_1100_add_pricing_rules;
+mod m20230405_1400_add_webhook_delivery;
+mod m20230407_1000_add_invoice;
+mod m20230409_1000_add_template_versions;
+mod m20230411_1000_add_custom_node_description;
+mod m20230413_1000_add_file_metadata_pinned;
+mod m20230415_1000_add_file_metadata_reference_count;
+mod m20230417_1000_add_installed_software_cluster;
+mod m20230417_1100_add_node_retry_at;
+mod m20230417_1200_add_node_batch_index;
+mod m20230417_1300_add_flow_bill_settled_time;
+mod m20230417_1400_add_file_storage_corrupted;
+mod m20230419_1000_add_template_share_group_member;
 pub struct Migrator;
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
@@ -10,6 +28,24 @@ impl MigratorTrait for Migrator {
             Box::new(m20220705_1439_create_table::Migration),
             Box::new(m20230213_1401_add_billing_system::Migration),
             Box::new(m20230217_1522_add_user_webhook::Migration),
+            Box::new(m20230320_1047_add_cluster_selection::Migration),
+            Box::new(m20230322_1530_add_node_attempts::Migration),
+            Box::new(m20230328_1010_add_user_quota::Migration),
+            Box::new(m20230330_1020_add_budget_and_ledger::Migration),
+            Box::new(m20230401_0930_add_bill_breakdown::Migration),
+            Box::new(m20230403_1100_add_pricing_rules::Migration),
+            Box::new(m20230405_1400_add_webhook_delivery::Migration),
+            Box::new(m20230407_1000_add_invoice::Migration),
+            Box::new(m20230409_1000_add_template_versions::Migration),
+            Box::new(m20230411_1000_add_custom_node_description::Migration),
+            Box::new(m20230413_1000_add_file_metadata_pinned::Migration),
+            Box::new(m20230415_1000_add_file_metadata_reference_count::Migration),
+            Box::new(m20230417_1000_add_installed_software_cluster::Migration),
+            Box::new(m20230417_1100_add_node_retry_at::Migration),
+            Box::new(m20230417_1200_add_node_batch_index::Migration),
+            Box::new(m20230417_1300_add_flow_bill_settled_time::Migration),
+            Box::new(m20230417_1400_add_file_storage_corrupted::Migration),
+            Box::new(m20230419_1000_add_template_share_group_member::Migration),
         ]
     }
 }
@@ -12,6 +12,8 @@ pub struct Model {
     pub install_argument: Json,
     pub installed_time: DateTimeUtc,
     pub installed_user_id: Uuid,
+    /// 安装到的集群，为空时视为所有集群均已安装
+    pub cluster_id: Option<Uuid>,
 }
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
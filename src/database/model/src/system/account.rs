@@ -0,0 +1,67 @@
+//! 用户预付费账户
+use crate::utils::WithDecimalFileds;
+use billing_system_kernel::prelude::*;
+use chrono::Utc;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "account")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub user_id: Uuid,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub balance: Decimal,
+    pub created_time: DateTimeUtc,
+    pub modified_time: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<Account> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<Account, Self::Error> {
+        Ok(Account {
+            id: self.id,
+            user_id: self.user_id,
+            balance: self.balance,
+        })
+    }
+}
+
+impl TryFrom<Account> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: Account) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: l.id,
+            user_id: l.user_id,
+            balance: l.balance,
+            created_time: Utc::now(),
+            modified_time: Utc::now(),
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            user_id: Set(self.user_id),
+            balance: Set(self.balance),
+            created_time: Set(self.created_time),
+            modified_time: Set(self.modified_time),
+        }
+    }
+}
+
+impl WithDecimalFileds for Model {
+    fn rescale_all_to(&mut self, n: u32) {
+        self.balance.rescale(n);
+    }
+}
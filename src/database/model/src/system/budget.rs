@@ -0,0 +1,89 @@
+//! 用户预算
+use crate::utils::WithDecimalFileds;
+use billing_system_kernel::prelude::*;
+use chrono::Utc;
+use num_traits::FromPrimitive;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "budget")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 预算限制的工作流实例，为空时限制用户的全部工作流
+    pub flow_instance_id: Option<Uuid>,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub limit: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub spent: Decimal,
+    pub action: i32,
+    pub created_time: DateTimeUtc,
+    pub modified_time: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<Budget> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<Budget, Self::Error> {
+        Ok(Budget {
+            id: self.id,
+            user_id: self.user_id,
+            scope: match self.flow_instance_id {
+                Some(id) => BudgetScope::Workflow(id),
+                None => BudgetScope::User,
+            },
+            limit: self.limit,
+            spent: self.spent,
+            action: FromPrimitive::from_i32(self.action)
+                .ok_or(anyhow::anyhow!("Budget action is invalid."))?,
+        })
+    }
+}
+
+impl TryFrom<Budget> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: Budget) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: l.id,
+            user_id: l.user_id,
+            flow_instance_id: match l.scope {
+                BudgetScope::User => None,
+                BudgetScope::Workflow(id) => Some(id),
+            },
+            limit: l.limit,
+            spent: l.spent,
+            action: l.action as i32,
+            created_time: Utc::now(),
+            modified_time: Utc::now(),
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            user_id: Set(self.user_id),
+            flow_instance_id: Set(self.flow_instance_id),
+            limit: Set(self.limit),
+            spent: Set(self.spent),
+            action: Set(self.action),
+            created_time: Set(self.created_time),
+            modified_time: Set(self.modified_time),
+        }
+    }
+}
+
+impl WithDecimalFileds for Model {
+    fn rescale_all_to(&mut self, n: u32) {
+        self.limit.rescale(n);
+        self.spent.rescale(n);
+    }
+}
@@ -1,5 +1,6 @@
 //! 集群资源
 use crate::system::prelude::*;
+use kernel::models::prelude::ClusterResource;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
@@ -17,6 +18,14 @@ pub struct Model {
     /// 存储空间大小，单位为字节
     pub storage_capacity: i64,
     pub storage_capacity_alert: i64,
+    /// agent 上报的已分配内存，单位为字节
+    pub allocated_memory: i64,
+    /// agent 上报的已分配核心数
+    pub allocated_cpu_count: i64,
+    /// agent 上报的排队中任务数
+    pub queuing_task_count: i64,
+    /// agent 上报的运行中任务数
+    pub running_task_count: i64,
     pub cluster_id: Uuid,
 }
 
@@ -37,3 +46,17 @@ impl Related<ClusterEntity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl From<Model> for ClusterResource {
+    fn from(l: Model) -> Self {
+        Self {
+            cluster_id: l.cluster_id,
+            memory: l.memory.max(0) as u64,
+            core_number: l.core_number.max(0) as u64,
+            allocated_memory: l.allocated_memory.max(0) as u64,
+            allocated_cpu_count: l.allocated_cpu_count.max(0) as u64,
+            queuing_task_count: l.queuing_task_count.max(0) as u64,
+            running_task_count: l.running_task_count.max(0) as u64,
+        }
+    }
+}
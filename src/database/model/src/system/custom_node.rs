@@ -1,5 +1,6 @@
 //! 自定义组件
-use sea_orm::entity::prelude::*;
+use kernel::models::prelude::CustomNode;
+use sea_orm::{entity::prelude::*, Set};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "custom_node")]
@@ -10,9 +11,53 @@ pub struct Model {
     pub created_time: DateTime,
     pub user_id: Uuid,
     pub spec: Json,
+    pub description: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<CustomNode> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<CustomNode, Self::Error> {
+        Ok(CustomNode {
+            id: self.id,
+            name: self.name,
+            description: self.description,
+            user_id: self.user_id,
+            spec: serde_json::from_value(self.spec)?,
+            created_time: self.created_time.and_utc(),
+        })
+    }
+}
+
+impl TryFrom<CustomNode> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: CustomNode) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: l.id,
+            name: l.name,
+            created_time: l.created_time.naive_utc(),
+            user_id: l.user_id,
+            spec: serde_json::to_value(l.spec)?,
+            description: l.description,
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            name: Set(self.name),
+            created_time: Set(self.created_time),
+            user_id: Set(self.user_id),
+            spec: Set(self.spec),
+            description: Set(self.description),
+        }
+    }
+}
@@ -18,6 +18,10 @@ pub struct Model {
     pub hash_algorithm: String,
     pub size: i64,
     pub created_time: DateTimeUtc,
+    /// 是否固定，固定的文件不受生命周期策略影响
+    pub pinned: bool,
+    /// 引用计数，为网盘文件与节点输入引用该文件的次数
+    pub reference_count: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -72,6 +76,8 @@ impl TryInto<FileMeta> for Model {
             hash: self.hash,
             hash_algorithm: HashAlgorithm::from_str(&self.hash_algorithm)?,
             size: self.size as usize,
+            pinned: self.pinned,
+            reference_count: self.reference_count,
         })
     }
 }
@@ -85,6 +91,8 @@ impl From<FileMeta> for Model {
             hash_algorithm: l.hash_algorithm.to_string(),
             size: l.size as i64,
             created_time: Utc::now(),
+            pinned: l.pinned,
+            reference_count: l.reference_count,
         }
     }
 }
@@ -98,6 +106,8 @@ impl Model {
             hash_algorithm: Set(self.hash_algorithm),
             size: Set(self.size),
             created_time: Set(self.created_time),
+            pinned: Set(self.pinned),
+            reference_count: Set(self.reference_count),
         }
     }
 }
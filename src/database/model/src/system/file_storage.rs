@@ -14,6 +14,8 @@ pub struct Model {
     pub file_metadata_id: Uuid,
     /// 文件副本在存储服务器中的 uri
     pub server_url: String,
+    /// 最近一次校验时文件副本内容与哈希不符
+    pub corrupted: bool,
     pub created_time: DateTimeUtc,
     pub created_user_id: Uuid,
 }
@@ -44,6 +46,7 @@ impl TryFrom<FileStorage> for Model {
             storage_server_id: l.storage_server_id,
             file_metadata_id: l.meta_id,
             server_url: l.server_url,
+            corrupted: l.corrupted,
             created_time: Utc::now(),
             created_user_id: Default::default(),
         })
@@ -56,6 +59,7 @@ impl From<Model> for FileStorage {
             storage_server_id: val.storage_server_id,
             meta_id: val.file_metadata_id,
             server_url: val.server_url,
+            corrupted: val.corrupted,
         }
     }
 }
@@ -67,6 +71,7 @@ impl Model {
             storage_server_id: Set(self.storage_server_id),
             file_metadata_id: Set(self.file_metadata_id),
             server_url: Set(self.server_url),
+            corrupted: Set(self.corrupted),
             created_time: Set(self.created_time),
             created_user_id: Set(self.created_user_id),
         }
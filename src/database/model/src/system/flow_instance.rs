@@ -2,7 +2,7 @@
 use crate::system::prelude::*;
 use billing_system_kernel::prelude::*;
 use chrono::Utc;
-use kernel::models::prelude::WorkflowInstance;
+use kernel::models::prelude::{WorkflowInstance, WorkflowInstanceStatus};
 use num_traits::FromPrimitive;
 use sea_orm::entity::prelude::*;
 
@@ -68,6 +68,7 @@ impl TryInto<FlowInstance> for Model {
         Ok(FlowInstance {
             id: self.id,
             user_id: self.user_id,
+            finished: self.status == WorkflowInstanceStatus::Finished as i32,
         })
     }
 }
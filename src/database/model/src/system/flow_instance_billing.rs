@@ -16,11 +16,13 @@ pub struct Model {
     pub storage: i64,
     pub cpu_time: i64,
     pub wall_time: i64,
-    #[sea_orm(column_type = "Decimal(Some((12, 2)))")]
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
     pub total_price: Decimal,
     pub user_id: Uuid,
     pub created_time: DateTimeUtc,
     pub modified_time: DateTimeUtc,
+    /// 推送工作流账单的时间，工作流完成且所有节点都已计费后记录
+    pub settled_time: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -74,6 +76,7 @@ impl TryFrom<FlowInstanceBilling> for Model {
             user_id: l.user_id,
             created_time: Utc::now(),
             modified_time: Utc::now(),
+            settled_time: None,
         })
     }
 }
@@ -92,6 +95,7 @@ impl Model {
             user_id: Set(self.user_id),
             created_time: Set(self.created_time),
             modified_time: Set(self.modified_time),
+            settled_time: Set(self.settled_time),
         }
     }
 }
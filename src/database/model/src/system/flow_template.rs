@@ -1,5 +1,6 @@
 //! 工作流模板
-use sea_orm::entity::prelude::*;
+use kernel::models::prelude::{TemplateVisibility, WorkflowTemplate};
+use sea_orm::{entity::prelude::*, Set};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "flow_template")]
@@ -13,6 +14,15 @@ pub struct Model {
     pub spec: Json,
     pub user_id: Uuid,
     pub created_time: DateTimeUtc,
+    /// 模板系列 id，为空时为模板自身的 id
+    pub family_id: Option<Uuid>,
+    pub version: i32,
+    /// 共享范围，0 为仅发布者可见，1 为组内可见，2 为公开
+    pub visibility: i32,
+    /// 组内可见时的组名
+    pub share_group: Option<String>,
+    /// 使用模板时需要填写的参数
+    pub parameters: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -20,6 +30,80 @@ pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl TryInto<WorkflowTemplate> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<WorkflowTemplate, Self::Error> {
+        Ok(WorkflowTemplate {
+            id: self.id,
+            family_id: self.family_id.unwrap_or(self.id),
+            version: u32::try_from(self.version)?,
+            name: self.name,
+            description: self.description,
+            logo: Some(self.logo).filter(|el| !el.is_empty()),
+            user_id: self.user_id,
+            visibility: match (self.visibility, self.share_group) {
+                (0, _) => TemplateVisibility::Private,
+                (1, Some(name)) => TemplateVisibility::Group { name },
+                (2, _) => TemplateVisibility::Public,
+                _ => anyhow::bail!("Template visibility is invalid."),
+            },
+            parameters: self
+                .parameters
+                .map(serde_json::from_value)
+                .transpose()?
+                .unwrap_or_default(),
+            spec: serde_json::from_value(self.spec)?,
+            created_time: self.created_time,
+        })
+    }
+}
+
+impl TryFrom<WorkflowTemplate> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: WorkflowTemplate) -> Result<Self, Self::Error> {
+        let (visibility, share_group) = match l.visibility {
+            TemplateVisibility::Private => (0, None),
+            TemplateVisibility::Group { name } => (1, Some(name)),
+            TemplateVisibility::Public => (2, None),
+        };
+        Ok(Self {
+            id: l.id,
+            name: l.name,
+            description: l.description,
+            logo: l.logo.unwrap_or_default(),
+            spec: serde_json::to_value(l.spec)?,
+            user_id: l.user_id,
+            created_time: l.created_time,
+            family_id: Some(l.family_id),
+            version: i32::try_from(l.version)?,
+            visibility,
+            share_group,
+            parameters: Some(serde_json::to_value(l.parameters)?),
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            name: Set(self.name),
+            description: Set(self.description),
+            logo: Set(self.logo),
+            spec: Set(self.spec),
+            user_id: Set(self.user_id),
+            created_time: Set(self.created_time),
+            family_id: Set(self.family_id),
+            version: Set(self.version),
+            visibility: Set(self.visibility),
+            share_group: Set(self.share_group),
+            parameters: Set(self.parameters),
+        }
+    }
+}
+
 pub use {
     ActiveModel as FlowTemplateActiveModel, Column as FlowTemplateColumn,
     Entity as FlowTemplateEntity, Model as FlowTemplateModel, PrimaryKey as FlowTemplatePrimaryKey,
@@ -0,0 +1,94 @@
+//! 发票
+use crate::utils::WithDecimalFileds;
+use billing_system_kernel::prelude::*;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "invoice")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// 发票对应的工作流实例，为空时为用户的全部工作流
+    pub flow_instance_id: Option<Uuid>,
+    pub period_start: DateTimeUtc,
+    pub period_end: DateTimeUtc,
+    pub node_count: i64,
+    /// 按集群与资源种类汇总的明细
+    pub lines: Json,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub total: Decimal,
+    pub issued_time: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<Invoice> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<Invoice, Self::Error> {
+        Ok(Invoice {
+            id: self.id,
+            statement: Statement {
+                user_id: self.user_id,
+                scope: match self.flow_instance_id {
+                    Some(id) => StatementScope::Workflow(id),
+                    None => StatementScope::User,
+                },
+                period_start: self.period_start,
+                period_end: self.period_end,
+                node_count: self.node_count,
+                lines: serde_json::from_value(self.lines)?,
+                total: self.total,
+            },
+            issued_time: self.issued_time,
+        })
+    }
+}
+
+impl TryFrom<Invoice> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: Invoice) -> Result<Self, Self::Error> {
+        let statement = l.statement;
+        Ok(Self {
+            id: l.id,
+            user_id: statement.user_id,
+            flow_instance_id: match statement.scope {
+                StatementScope::User => None,
+                StatementScope::Workflow(id) => Some(id),
+            },
+            period_start: statement.period_start,
+            period_end: statement.period_end,
+            node_count: statement.node_count,
+            lines: serde_json::to_value(statement.lines)?,
+            total: statement.total,
+            issued_time: l.issued_time,
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            user_id: Set(self.user_id),
+            flow_instance_id: Set(self.flow_instance_id),
+            period_start: Set(self.period_start),
+            period_end: Set(self.period_end),
+            node_count: Set(self.node_count),
+            lines: Set(self.lines),
+            total: Set(self.total),
+            issued_time: Set(self.issued_time),
+        }
+    }
+}
+
+impl WithDecimalFileds for Model {
+    fn rescale_all_to(&mut self, n: u32) {
+        self.total.rescale(n);
+    }
+}
@@ -0,0 +1,89 @@
+//! 账户流水
+use crate::utils::WithDecimalFileds;
+use billing_system_kernel::prelude::*;
+use num_traits::FromPrimitive;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "ledger_entry")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: i32,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub amount: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))", nullable)]
+    pub balance: Option<Decimal>,
+    pub flow_instance_id: Option<Uuid>,
+    pub node_instance_id: Option<Uuid>,
+    pub description: String,
+    pub created_time: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<LedgerEntry> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<LedgerEntry, Self::Error> {
+        Ok(LedgerEntry {
+            id: self.id,
+            user_id: self.user_id,
+            kind: FromPrimitive::from_i32(self.kind)
+                .ok_or(anyhow::anyhow!("Ledger entry kind is invalid."))?,
+            amount: self.amount,
+            balance: self.balance,
+            flow_instance_id: self.flow_instance_id,
+            node_instance_id: self.node_instance_id,
+            description: self.description,
+            created_time: self.created_time,
+        })
+    }
+}
+
+impl TryFrom<LedgerEntry> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: LedgerEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: l.id,
+            user_id: l.user_id,
+            kind: l.kind as i32,
+            amount: l.amount,
+            balance: l.balance,
+            flow_instance_id: l.flow_instance_id,
+            node_instance_id: l.node_instance_id,
+            description: l.description,
+            created_time: l.created_time,
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            user_id: Set(self.user_id),
+            kind: Set(self.kind),
+            amount: Set(self.amount),
+            balance: Set(self.balance),
+            flow_instance_id: Set(self.flow_instance_id),
+            node_instance_id: Set(self.node_instance_id),
+            description: Set(self.description),
+            created_time: Set(self.created_time),
+        }
+    }
+}
+
+impl WithDecimalFileds for Model {
+    fn rescale_all_to(&mut self, n: u32) {
+        self.amount.rescale(n);
+        if let Some(balance) = self.balance.as_mut() {
+            balance.rescale(n);
+        }
+    }
+}
@@ -1,4 +1,6 @@
+mod account;
 mod available_zone;
+mod budget;
 mod chat;
 mod cluster;
 mod cluster_id_settings;
@@ -13,6 +15,8 @@ mod flow_draft;
 mod flow_instance;
 mod flow_instance_billing;
 mod flow_template;
+mod invoice;
+mod ledger_entry;
 mod message;
 mod net_disk;
 mod node_draft_file;
@@ -20,20 +24,32 @@ mod node_instance;
 mod node_instance_billing;
 mod node_instance_file;
 mod notification;
+mod pricing_rule;
 mod region;
 mod storage_server;
+mod template_share_group_member;
+mod user_group;
 mod user_log;
 mod user_resource;
 mod user_webhook;
+mod webhook_delivery;
 mod work_order;
 
 pub mod prelude {
     pub use super::{
+        account::{
+            ActiveModel as AccountActiveModel, Column as AccountColumn, Entity as AccountEntity,
+            Model as AccountModel, PrimaryKey as AccountPrimaryKey, Relation as AccountRelation,
+        },
         available_zone::{
             ActiveModel as AvailableZoneActiveModel, Column as AvailableZoneColumn,
             Entity as AvailableZoneEntity, Model as AvailableZoneModel,
             PrimaryKey as AvailableZonePrimaryKey, Relation as AvailableZoneRelation,
         },
+        budget::{
+            ActiveModel as BudgetActiveModel, Column as BudgetColumn, Entity as BudgetEntity,
+            Model as BudgetModel, PrimaryKey as BudgetPrimaryKey, Relation as BudgetRelation,
+        },
         cluster::{
             ActiveModel as ClusterActiveModel, Column as ClusterColumn, Entity as ClusterEntity,
             Model as ClusterModel, PrimaryKey as ClusterPrimaryKey, Relation as ClusterRelation,
@@ -98,6 +114,14 @@ pub mod prelude {
             Entity as FlowTemplateEntity, Model as FlowTemplateModel,
             PrimaryKey as FlowTemplatePrimaryKey, Relation as FlowTemplateRelation,
         },
+        invoice::{
+            ActiveModel as InvoiceActiveModel, Column as InvoiceColumn, Entity as InvoiceEntity,
+            Model as InvoiceModel, PrimaryKey as InvoicePrimaryKey, Relation as InvoiceRelation,
+        },
+        ledger_entry::{
+            ActiveModel as LedgerEntryActiveModel, Column as LedgerEntryColumn, Entity as LedgerEntryEntity,
+            Model as LedgerEntryModel, PrimaryKey as LedgerEntryPrimaryKey, Relation as LedgerEntryRelation,
+        },
         net_disk::{
             ActiveModel as FileSystemActiveModel, Column as FileSystemColumn,
             Entity as FileSystemEntity, Model as FileSystemModel,
@@ -128,6 +152,11 @@ pub mod prelude {
             Entity as NotificationEntity, Model as NotificationModel,
             PrimaryKey as NotificationPrimaryKey, Relation as NotificationRelation,
         },
+        pricing_rule::{
+            ActiveModel as PricingRuleActiveModel, Column as PricingRuleColumn,
+            Entity as PricingRuleEntity, Model as PricingRuleModel,
+            PrimaryKey as PricingRulePrimaryKey, Relation as PricingRuleRelation,
+        },
         region::{
             ActiveModel as RegionActiveModel, Column as RegionColumn, Entity as RegionEntity,
             Model as RegionModel, PrimaryKey as RegionPrimaryKey, Relation as RegionRelation,
@@ -137,6 +166,18 @@ pub mod prelude {
             Entity as StorageServerEntity, Model as StorageServerModel,
             PrimaryKey as StorageServerPrimaryKey, Relation as StorageServerRelation,
         },
+        template_share_group_member::{
+            ActiveModel as TemplateShareGroupMemberActiveModel,
+            Column as TemplateShareGroupMemberColumn, Entity as TemplateShareGroupMemberEntity,
+            Model as TemplateShareGroupMemberModel,
+            PrimaryKey as TemplateShareGroupMemberPrimaryKey,
+            Relation as TemplateShareGroupMemberRelation,
+        },
+        user_group::{
+            ActiveModel as UserGroupActiveModel, Column as UserGroupColumn,
+            Entity as UserGroupEntity, Model as UserGroupModel,
+            PrimaryKey as UserGroupPrimaryKey, Relation as UserGroupRelation,
+        },
         user_log::{
             ActiveModel as UserLogActiveModel, Column as UserLogColumn, Entity as UserLogEntity,
             Model as UserLogModel, PrimaryKey as UserLogPrimaryKey, Relation as UserLogRelation,
@@ -151,6 +192,11 @@ pub mod prelude {
             Entity as UserWebhookEntity, Model as UserWebhookModel,
             PrimaryKey as UserWebhookPrimaryKey, Relation as UserWebhookRelation,
         },
+        webhook_delivery::{
+            ActiveModel as WebhookDeliveryActiveModel, Column as WebhookDeliveryColumn,
+            Entity as WebhookDeliveryEntity, Model as WebhookDeliveryModel,
+            PrimaryKey as WebhookDeliveryPrimaryKey, Relation as WebhookDeliveryRelation,
+        },
         work_order::{
             ActiveModel as WorkOrderActiveModel, Column as WorkOrderColumn,
             Entity as WorkOrderEntity, Model as WorkOrderModel, PrimaryKey as WorkOrderPrimaryKey,
@@ -2,7 +2,7 @@
 use crate::system::prelude::*;
 use anyhow::anyhow;
 use chrono::Utc;
-use kernel::models::prelude::NodeInstance as KernelNodeInstance;
+use kernel::models::prelude::{NodeInstance as KernelNodeInstance, NodeInstanceStatus};
 use num_traits::FromPrimitive;
 use sea_orm::{entity::prelude::*, Set};
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
@@ -14,10 +14,20 @@ pub struct Model {
     pub kind: i32,
     pub is_parent: bool,
     pub batch_parent_id: Option<Uuid>,
+    /// 批量子节点是第几个子任务
+    pub batch_index: Option<i32>,
     pub status: i32,
     pub resource_meter: Option<Json>,
     pub log: Option<String>,
     pub cluster_id: Option<Uuid>,
+    /// 选择集群的原因
+    pub schedule_reason: Option<String>,
+    /// 历次执行记录
+    pub attempts: Json,
+    /// 失败后等待重试时，重新提交的时间
+    pub retry_at: Option<DateTimeUtc>,
+    /// 占用的用户配额
+    pub quota_reservation: Option<Json>,
     pub flow_instance_id: Uuid,
     pub created_time: DateTimeUtc,
     pub last_modified_time: DateTimeUtc,
@@ -93,6 +103,7 @@ impl TryFrom<KernelNodeInstance> for Model {
             kind: l.kind as i32,
             is_parent: l.is_parent,
             batch_parent_id: l.batch_parent_id,
+            batch_index: l.batch_index.map(|el| el as i32),
             status: l.status as i32,
             resource_meter: match l.resource_meter {
                 Some(el) => Some(serde_json::to_value(el)?),
@@ -100,6 +111,13 @@ impl TryFrom<KernelNodeInstance> for Model {
             },
             log: l.log,
             cluster_id: l.cluster_id,
+            schedule_reason: l.schedule_reason,
+            attempts: serde_json::to_value(l.attempts)?,
+            retry_at: l.retry_at,
+            quota_reservation: match l.quota_reservation {
+                Some(el) => Some(serde_json::to_value(el)?),
+                None => None,
+            },
             flow_instance_id: l.flow_instance_id,
             created_time: Utc::now(),
             last_modified_time: Utc::now(),
@@ -118,15 +136,23 @@ impl TryInto<KernelNodeInstance> for Model {
             name: self.name,
             is_parent: self.is_parent,
             batch_parent_id: self.batch_parent_id,
+            batch_index: self.batch_index.map(|el| el as usize),
             flow_instance_id: self.flow_instance_id,
             status: FromPrimitive::from_i32(self.status)
                 .ok_or(anyhow::anyhow!("Wrong status type."))?,
             cluster_id: self.cluster_id,
+            schedule_reason: self.schedule_reason,
             log: self.log,
             resource_meter: match self.resource_meter {
                 Some(x) => Some(serde_json::from_value(x)?),
                 None => None,
             },
+            attempts: serde_json::from_value(self.attempts)?,
+            retry_at: self.retry_at,
+            quota_reservation: match self.quota_reservation {
+                Some(x) => Some(serde_json::from_value(x)?),
+                None => None,
+            },
         })
     }
 }
@@ -149,6 +175,11 @@ impl TryInto<billing_system_kernel::prelude::NodeInstance> for Model {
 }
 
 impl Model {
+    /// 节点成功结束并记录了资源使用，需要计费
+    pub fn is_billable(&self) -> bool {
+        self.status == NodeInstanceStatus::Finished as i32 && self.resource_meter.is_some()
+    }
+
     pub fn into_set(self) -> ActiveModel {
         ActiveModel {
             id: Set(self.id),
@@ -156,10 +187,15 @@ impl Model {
             kind: Set(self.kind),
             is_parent: Set(self.is_parent),
             batch_parent_id: Set(self.batch_parent_id),
+            batch_index: Set(self.batch_index),
             status: Set(self.status),
             resource_meter: Set(self.resource_meter),
             log: Set(self.log),
             cluster_id: Set(self.cluster_id),
+            schedule_reason: Set(self.schedule_reason),
+            attempts: Set(self.attempts),
+            retry_at: Set(self.retry_at),
+            quota_reservation: Set(self.quota_reservation),
             flow_instance_id: Set(self.flow_instance_id),
             created_time: sea_orm::ActiveValue::Unchanged(self.created_time),
             last_modified_time: sea_orm::ActiveValue::Unchanged(self.last_modified_time),
@@ -20,6 +20,10 @@ pub struct Model {
     #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
     pub price: Decimal,
     pub formula: String,
+    /// 价格明细，早于明细功能的账单为空
+    pub breakdown: Option<Json>,
+    /// 按计费规则拆分的计费时段，早于计费规则功能的账单为空
+    pub segments: Option<Json>,
     pub created_time: DateTimeUtc,
     pub modified_time: DateTimeUtc,
 }
@@ -54,6 +58,8 @@ impl TryInto<NodeInstanceBilling> for Model {
             wall_time: self.wall_time,
             price: self.price,
             formula: self.formula,
+            breakdown: self.breakdown.map(serde_json::from_value).transpose()?.unwrap_or_default(),
+            segments: self.segments.map(serde_json::from_value).transpose()?.unwrap_or_default(),
         })
     }
 }
@@ -73,6 +79,8 @@ impl TryFrom<NodeInstanceBilling> for Model {
             wall_time: l.wall_time,
             price: l.price,
             formula: l.formula,
+            breakdown: Some(serde_json::to_value(l.breakdown)?),
+            segments: Some(serde_json::to_value(l.segments)?),
             created_time: Utc::now(),
             modified_time: Utc::now(),
         })
@@ -92,6 +100,8 @@ impl Model {
             wall_time: Set(self.wall_time),
             price: Set(self.price),
             formula: Set(self.formula),
+            breakdown: Set(self.breakdown),
+            segments: Set(self.segments),
             created_time: Set(self.created_time),
             modified_time: Set(self.modified_time),
         }
@@ -0,0 +1,128 @@
+//! 集群计费规则
+use crate::system::prelude::*;
+use crate::utils::WithDecimalFileds;
+use billing_system_kernel::prelude::*;
+use chrono::Utc;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "pricing_rule")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub cluster_id: Uuid,
+    /// 分区或队列，为空时适用于所有分区
+    pub partition: Option<String>,
+    /// 用户组，为空时适用于所有用户
+    pub user_group: Option<String>,
+    /// 每日生效时段，为空时全天生效
+    pub time_window: Option<Json>,
+    pub effective_from: DateTimeUtc,
+    pub effective_to: Option<DateTimeUtc>,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub cpu: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub memory: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub storage: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub cpu_time: Decimal,
+    #[sea_orm(column_type = "Decimal(Some((20, 10)))")]
+    pub wall_time: Decimal,
+    pub formula: String,
+    pub created_time: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "ClusterEntity",
+        from = "Column::ClusterId",
+        to = "ClusterColumn::Id"
+    )]
+    Cluster,
+}
+
+impl Related<ClusterEntity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Cluster.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<PricingRule> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<PricingRule, Self::Error> {
+        Ok(PricingRule {
+            id: self.id,
+            cluster_id: self.cluster_id,
+            partition: self.partition,
+            user_group: self.user_group,
+            time_window: self.time_window.map(serde_json::from_value).transpose()?,
+            effective_from: self.effective_from,
+            effective_to: self.effective_to,
+            cpu: self.cpu,
+            memory: self.memory,
+            storage: self.storage,
+            cpu_time: self.cpu_time,
+            wall_time: self.wall_time,
+            formula: self.formula,
+        })
+    }
+}
+
+impl TryFrom<PricingRule> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: PricingRule) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: l.id,
+            cluster_id: l.cluster_id,
+            partition: l.partition,
+            user_group: l.user_group,
+            time_window: l.time_window.map(serde_json::to_value).transpose()?,
+            effective_from: l.effective_from,
+            effective_to: l.effective_to,
+            cpu: l.cpu,
+            memory: l.memory,
+            storage: l.storage,
+            cpu_time: l.cpu_time,
+            wall_time: l.wall_time,
+            formula: l.formula,
+            created_time: Utc::now(),
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            cluster_id: Set(self.cluster_id),
+            partition: Set(self.partition),
+            user_group: Set(self.user_group),
+            time_window: Set(self.time_window),
+            effective_from: Set(self.effective_from),
+            effective_to: Set(self.effective_to),
+            cpu: Set(self.cpu),
+            memory: Set(self.memory),
+            storage: Set(self.storage),
+            cpu_time: Set(self.cpu_time),
+            wall_time: Set(self.wall_time),
+            formula: Set(self.formula),
+            created_time: Set(self.created_time),
+        }
+    }
+}
+
+impl WithDecimalFileds for Model {
+    fn rescale_all_to(&mut self, n: u32) {
+        self.cpu.rescale(n);
+        self.memory.rescale(n);
+        self.storage.rescale(n);
+        self.cpu_time.rescale(n);
+        self.wall_time.rescale(n);
+    }
+}
@@ -60,7 +60,10 @@ impl TryInto<StorageServer> for Model {
                 0 => StorageType::ObjectStorage {
                     options: serde_json::from_value(self.options)?,
                 },
-                _ => unimplemented!(),
+                1 => StorageType::FileSystem {
+                    options: serde_json::from_value(self.options)?,
+                },
+                x => anyhow::bail!("Unknown storage type: {x}."),
             },
             available_zone_id: self.available_zone_id,
         })
@@ -0,0 +1,59 @@
+//! 用户所属的计费用户组
+use billing_system_kernel::prelude::*;
+use chrono::Utc;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "user_group")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_time: DateTimeUtc,
+    pub modified_time: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<UserGroup> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<UserGroup, Self::Error> {
+        Ok(UserGroup {
+            id: self.id,
+            user_id: self.user_id,
+            name: self.name,
+        })
+    }
+}
+
+impl TryFrom<UserGroup> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: UserGroup) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: l.id,
+            user_id: l.user_id,
+            name: l.name,
+            created_time: Utc::now(),
+            modified_time: Utc::now(),
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            user_id: Set(self.user_id),
+            name: Set(self.name),
+            created_time: Set(self.created_time),
+            modified_time: Set(self.modified_time),
+        }
+    }
+}
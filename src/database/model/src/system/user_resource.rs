@@ -1,6 +1,7 @@
 //! 用户集群资源
 use crate::system::prelude::*;
-use sea_orm::entity::prelude::*;
+use kernel::models::prelude::UserResource;
+use sea_orm::{entity::prelude::*, Set};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "user_resource")]
@@ -42,3 +43,60 @@ impl Related<ClusterEntity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl From<Model> for UserResource {
+    fn from(l: Model) -> Self {
+        Self {
+            id: l.id,
+            user_id: l.user_id,
+            cluster_id: l.cluster_id,
+            memory: l.memory.max(0) as u64,
+            memory_max: l.memory_max.max(0) as u64,
+            memory_alert: l.memory_alert.max(0) as u64,
+            core_number: l.core_number.max(0) as u64,
+            core_number_max: l.core_number_max.max(0) as u64,
+            core_number_alert: l.core_number_alert.max(0) as u64,
+            storage_capacity: l.storage_capacity.max(0) as u64,
+            storage_capacity_max: l.storage_capacity_max.max(0) as u64,
+            storage_capacity_alert: l.storage_capacity_alert.max(0) as u64,
+        }
+    }
+}
+
+impl From<UserResource> for Model {
+    fn from(l: UserResource) -> Self {
+        Self {
+            id: l.id,
+            user_id: l.user_id,
+            memory: l.memory as i64,
+            memory_max: l.memory_max as i64,
+            memory_alert: l.memory_alert as i64,
+            core_number: l.core_number as i64,
+            core_number_max: l.core_number_max as i64,
+            core_number_alert: l.core_number_alert as i64,
+            storage_capacity: l.storage_capacity as i64,
+            storage_capacity_max: l.storage_capacity_max as i64,
+            storage_capacity_alert: l.storage_capacity_alert as i64,
+            cluster_id: l.cluster_id,
+        }
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            user_id: Set(self.user_id),
+            memory: Set(self.memory),
+            memory_max: Set(self.memory_max),
+            memory_alert: Set(self.memory_alert),
+            core_number: Set(self.core_number),
+            core_number_max: Set(self.core_number_max),
+            core_number_alert: Set(self.core_number_alert),
+            storage_capacity: Set(self.storage_capacity),
+            storage_capacity_max: Set(self.storage_capacity_max),
+            storage_capacity_alert: Set(self.storage_capacity_alert),
+            cluster_id: Set(self.cluster_id),
+        }
+    }
+}
@@ -8,6 +8,10 @@ pub struct Model {
     pub id: Uuid,
     pub user_id: Uuid,
     pub url: String,
+    /// 签名密钥
+    pub secret: String,
+    /// 订阅的事件类型，为空时订阅所有事件
+    pub event_types: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -23,6 +27,12 @@ impl TryInto<UserWebhook> for Model {
             id: self.id,
             user_id: self.user_id,
             url: self.url,
+            secret: self.secret,
+            event_types: self
+                .event_types
+                .map(serde_json::from_value)
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
@@ -35,6 +45,8 @@ impl TryFrom<UserWebhook> for Model {
             id: l.id,
             user_id: l.user_id,
             url: l.url,
+            secret: l.secret,
+            event_types: Some(serde_json::to_value(l.event_types)?),
         })
     }
 }
@@ -45,6 +57,8 @@ impl Model {
             id: Set(self.id),
             user_id: Set(self.user_id),
             url: Set(self.url),
+            secret: Set(self.secret),
+            event_types: Set(self.event_types),
         }
     }
 }
@@ -0,0 +1,95 @@
+//! 计费事件推送记录
+use billing_system_kernel::prelude::*;
+use sea_orm::{entity::prelude::*, Set};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "webhook_delivery")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub user_id: Uuid,
+    pub event_type: i32,
+    #[sea_orm(column_type = "Text")]
+    pub payload: String,
+    pub status: i32,
+    pub attempts: i32,
+    pub next_attempt_time: DateTimeUtc,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+    pub created_time: DateTimeUtc,
+    pub delivered_time: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl TryInto<WebhookDelivery> for Model {
+    type Error = anyhow::Error;
+
+    fn try_into(self) -> Result<WebhookDelivery, Self::Error> {
+        Ok(WebhookDelivery {
+            id: self.id,
+            webhook_id: self.webhook_id,
+            user_id: self.user_id,
+            event_type: match self.event_type {
+                0 => WebhookEventType::NodeBilled,
+                1 => WebhookEventType::WorkflowBilled,
+                2 => WebhookEventType::BudgetThresholdReached,
+                _ => anyhow::bail!("Webhook event type is invalid."),
+            },
+            payload: self.payload,
+            status: match self.status {
+                0 => WebhookDeliveryStatus::Pending,
+                1 => WebhookDeliveryStatus::Delivered,
+                2 => WebhookDeliveryStatus::Failed,
+                _ => anyhow::bail!("Webhook delivery status is invalid."),
+            },
+            attempts: self.attempts as u32,
+            next_attempt_time: self.next_attempt_time,
+            last_error: self.last_error,
+            created_time: self.created_time,
+            delivered_time: self.delivered_time,
+        })
+    }
+}
+
+impl TryFrom<WebhookDelivery> for Model {
+    type Error = anyhow::Error;
+
+    fn try_from(l: WebhookDelivery) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: l.id,
+            webhook_id: l.webhook_id,
+            user_id: l.user_id,
+            event_type: l.event_type as i32,
+            payload: l.payload,
+            status: l.status as i32,
+            attempts: l.attempts as i32,
+            next_attempt_time: l.next_attempt_time,
+            last_error: l.last_error,
+            created_time: l.created_time,
+            delivered_time: l.delivered_time,
+        })
+    }
+}
+
+impl Model {
+    pub fn into_set(self) -> ActiveModel {
+        ActiveModel {
+            id: Set(self.id),
+            webhook_id: Set(self.webhook_id),
+            user_id: Set(self.user_id),
+            event_type: Set(self.event_type),
+            payload: Set(self.payload),
+            status: Set(self.status),
+            attempts: Set(self.attempts),
+            next_attempt_time: Set(self.next_attempt_time),
+            last_error: Set(self.last_error),
+            created_time: Set(self.created_time),
+            delivered_time: Set(self.delivered_time),
+        }
+    }
+}
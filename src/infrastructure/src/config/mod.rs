@@ -35,6 +35,9 @@ pub struct JwtValidationConfig {
     aud: Option<HashSet<String>>,
     #[serde(default = "JwtValidationConfig::default_iss")]
     iss: Option<HashSet<String>>,
+    /// 本服务在 Keycloak 中的客户端 id，用于从 resource_access 中读取本服务的角色
+    #[serde(default)]
+    client_id: Option<String>,
 }
 
 impl JwtValidationConfig {
@@ -67,6 +70,7 @@ impl Default for JwtValidationConfig {
             validate_nbf: false,
             aud: None,
             iss: None,
+            client_id: None,
         }
     }
 }
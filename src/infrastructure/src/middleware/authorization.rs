@@ -113,6 +113,7 @@ where
         let service = self.service.clone();
         let key_storage = self.key_storage.clone();
         let config = self.config.clone();
+        let client_id = config.client_id().clone();
         let http_client = self.http_client.clone();
         let not_validate = self.not_validate;
         let all_controllers = self.all_controllers;
@@ -135,7 +136,7 @@ where
                         )
                         .await
                         {
-                            Ok(x) => Some(UserInfo::new(x)),
+                            Ok(x) => Some(UserInfo::new(x, client_id)),
                             Err(e) => {
                                 log::debug!("{}", e);
                                 None
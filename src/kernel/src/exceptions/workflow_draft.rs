@@ -73,4 +73,33 @@ pub enum WorkflowDraftException {
     BatchInputNotOffer,
     #[error("Unknown data store error")]
     Unknown,
+    #[error("The node id: {id} is used by more than one node!")]
+    DuplicatedNodeId { id: Uuid },
+    #[error("The nodes: {node_ids:?} depend on each other in a cycle!")]
+    CyclicDependency { node_ids: Vec<Uuid> },
+    #[error("The node: {node_id} can never be scheduled because it depends on nodes in a cycle!")]
+    UnreachableNode { node_id: Uuid },
+    #[error("The required out_slot: {descriptor} in node: {node_id} is not used by any node!")]
+    UnconsumedOutputSlot { node_id: Uuid, descriptor: String },
+    #[error("No enabled cluster can provide {cpu_cores} cpu cores required by node: {node_id}!")]
+    UnsatisfiableRequirements { node_id: Uuid, cpu_cores: u64 },
+    #[error("{}", join_problems(problems))]
+    Problems {
+        problems: Vec<WorkflowDraftException>,
+    },
+}
+
+impl WorkflowDraftException {
+    /// 将多个问题合并为一个异常，只有一个问题时直接返回该问题
+    pub fn from_problems(mut problems: Vec<WorkflowDraftException>) -> Self {
+        if problems.len() == 1 {
+            problems.remove(0)
+        } else {
+            Self::Problems { problems }
+        }
+    }
+}
+
+fn join_problems(problems: &[WorkflowDraftException]) -> String {
+    problems.iter().map(|el| el.to_string()).collect::<Vec<_>>().join("\n")
 }
@@ -21,6 +21,70 @@ mock! {
         ) -> anyhow::Result<bool>;
         async fn save_changed(&self) -> anyhow::Result<bool>;
     }
+    #[async_trait]
+    impl IDBRepository<WorkflowDraft> for WorkflowDraftRepository {}
+}
+mock! {
+    pub CustomNodeRepository {}
+    #[async_trait]
+    impl ICustomNodeRepository for CustomNodeRepository {
+        async fn get_all_by_user_id(&self, user_id: Uuid) -> anyhow::Result<Vec<CustomNode>>;
+    }
+    #[async_trait]
+    impl IReadOnlyRepository<CustomNode> for CustomNodeRepository {
+        async fn get_by_id(&self, uuid: &str) -> anyhow::Result<CustomNode>;
+        async fn get_all(&self) -> anyhow::Result<Vec<CustomNode>>;
+    }
+    #[async_trait]
+    impl IMutableRepository<CustomNode> for CustomNodeRepository {
+        async fn update(&self, entity: CustomNode) -> anyhow::Result<CustomNode>;
+        async fn insert(&self, entity: CustomNode) -> anyhow::Result<CustomNode>;
+        async fn delete(&self, entity: CustomNode) -> anyhow::Result<bool>;
+        async fn delete_by_id(
+            &self,
+            uuid: &str,
+            entity: Option<CustomNode>,
+        ) -> anyhow::Result<bool>;
+        async fn save_changed(&self) -> anyhow::Result<bool>;
+    }
+    #[async_trait]
+    impl IDBRepository<CustomNode> for CustomNodeRepository {}
+}
+mock! {
+    pub WorkflowTemplateRepository {}
+    #[async_trait]
+    impl IWorkflowTemplateRepository for WorkflowTemplateRepository {
+        async fn search(
+            &self,
+            user_id: Uuid,
+            groups: Vec<String>,
+            keyword: Option<String>,
+        ) -> anyhow::Result<Vec<WorkflowTemplate>>;
+        async fn get_all_by_family_id(
+            &self,
+            family_id: Uuid,
+        ) -> anyhow::Result<Vec<WorkflowTemplate>>;
+        async fn get_user_groups(&self, user_id: Uuid) -> anyhow::Result<Vec<String>>;
+    }
+    #[async_trait]
+    impl IReadOnlyRepository<WorkflowTemplate> for WorkflowTemplateRepository {
+        async fn get_by_id(&self, uuid: &str) -> anyhow::Result<WorkflowTemplate>;
+        async fn get_all(&self) -> anyhow::Result<Vec<WorkflowTemplate>>;
+    }
+    #[async_trait]
+    impl IMutableRepository<WorkflowTemplate> for WorkflowTemplateRepository {
+        async fn update(&self, entity: WorkflowTemplate) -> anyhow::Result<WorkflowTemplate>;
+        async fn insert(&self, entity: WorkflowTemplate) -> anyhow::Result<WorkflowTemplate>;
+        async fn delete(&self, entity: WorkflowTemplate) -> anyhow::Result<bool>;
+        async fn delete_by_id(
+            &self,
+            uuid: &str,
+            entity: Option<WorkflowTemplate>,
+        ) -> anyhow::Result<bool>;
+        async fn save_changed(&self) -> anyhow::Result<bool>;
+    }
+    #[async_trait]
+    impl IDBRepository<WorkflowTemplate> for WorkflowTemplateRepository {}
 }
 mock! {
     pub WorkflowInstanceRepository {}
@@ -33,6 +97,10 @@ mock! {
             new_id: Uuid,
             node_instance_id: Uuid,
         ) -> anyhow::Result<()>;
+        async fn get_all_finished_before(
+            &self,
+            time: chrono::DateTime<chrono::Utc>,
+        ) -> anyhow::Result<Vec<WorkflowInstance>>;
     }
     #[async_trait]
     impl IReadOnlyRepository<WorkflowInstance> for WorkflowInstanceRepository {
@@ -82,6 +150,24 @@ mock! {
 
         /// 获取批量任务是第几个
         async fn get_nth_of_batch_tasks(&self, sub_node_id: Uuid) -> anyhow::Result<usize>;
+
+        /// 获取某用户因配额不足正在排队的节点
+        async fn get_user_queued_node_instances(
+            &self,
+            user_id: Uuid,
+        ) -> anyhow::Result<Vec<NodeInstance>>;
+
+        /// 获取等待重试且已到重新提交时间的节点
+        async fn get_all_due_retry_node_instances(
+            &self,
+            now: chrono::DateTime<chrono::Utc>,
+        ) -> anyhow::Result<Vec<NodeInstance>>;
+
+        /// 原子地取出并清空节点占用的配额
+        async fn take_quota_reservation(
+            &self,
+            node_instance_id: Uuid,
+        ) -> anyhow::Result<Option<QuotaReservation>>;
     }
     #[async_trait]
     impl IReadOnlyRepository<NodeInstance> for NodeInstanceRepository {
@@ -176,6 +262,12 @@ mock! {
             software_name: &str,
             required_install_arguments: &[String],
         ) -> anyhow::Result<bool>;
+        async fn is_software_installed_on_cluster(
+            &self,
+            cluster_id: Uuid,
+            software_name: &str,
+            required_install_arguments: &[String],
+        ) -> anyhow::Result<bool>;
     }
 }
 
@@ -184,6 +276,15 @@ mock! {
     #[async_trait]
     impl IClusterRepository for ClusterRepository{
         async fn get_random_cluster(&self) -> anyhow::Result<Uuid>;
+        async fn get_all_clusters_with_resource(
+            &self,
+        ) -> anyhow::Result<Vec<(Cluster, Option<ClusterResource>)>>;
+        async fn get_by_topic_name(&self, topic_name: &str) -> anyhow::Result<Cluster>;
+        async fn update_used_resource(
+            &self,
+            cluster_id: Uuid,
+            used: &ClusterUsedResource,
+        ) -> anyhow::Result<()>;
     }
     #[async_trait]
     impl IReadOnlyRepository<Cluster> for ClusterRepository {
@@ -191,3 +292,173 @@ mock! {
         async fn get_all(&self) -> anyhow::Result<Vec<Cluster>>;
     }
 }
+
+mock! {
+    pub UserResourceRepository{}
+    #[async_trait]
+    impl IUserResourceRepository for UserResourceRepository {
+        async fn get_by_user_and_cluster(
+            &self,
+            user_id: Uuid,
+            cluster_id: Uuid,
+        ) -> anyhow::Result<Option<UserResource>>;
+        async fn try_reserve(
+            &self,
+            id: Uuid,
+            reservation: &QuotaReservation,
+        ) -> anyhow::Result<Option<UserResource>>;
+        async fn release_reserved(
+            &self,
+            id: Uuid,
+            reservation: &QuotaReservation,
+            used_storage: u64,
+        ) -> anyhow::Result<UserResource>;
+    }
+    #[async_trait]
+    impl IMutableRepository<UserResource> for UserResourceRepository {
+        async fn update(&self, entity: UserResource) -> anyhow::Result<UserResource>;
+        async fn insert(&self, entity: UserResource) -> anyhow::Result<UserResource>;
+        async fn delete(&self, entity: UserResource) -> anyhow::Result<bool>;
+        async fn delete_by_id(
+            &self,
+            uuid: &str,
+            entity: Option<UserResource>,
+        ) -> anyhow::Result<bool>;
+        async fn save_changed(&self) -> anyhow::Result<bool>;
+    }
+}
+
+mock! {
+    pub FileMetaRepo {}
+    #[async_trait]
+    impl IFileMetaRepo for FileMetaRepo {
+        async fn get_by_hash_and_algorithm(
+            &self,
+            hash: &str,
+            hash_algorithm: &HashAlgorithm,
+        ) -> anyhow::Result<Option<FileMeta>>;
+        async fn get_all_unreferenced_before(
+            &self,
+            time: chrono::DateTime<chrono::Utc>,
+        ) -> anyhow::Result<Vec<FileMeta>>;
+        async fn get_deduplication_report(&self) -> anyhow::Result<DeduplicationReport>;
+        async fn get_all_after(
+            &self,
+            id: Option<Uuid>,
+            count: u64,
+        ) -> anyhow::Result<Vec<FileMeta>>;
+    }
+    #[async_trait]
+    impl IReadOnlyRepository<FileMeta> for FileMetaRepo {
+        async fn get_by_id(&self, uuid: &str) -> anyhow::Result<FileMeta>;
+        async fn get_all(&self) -> anyhow::Result<Vec<FileMeta>>;
+    }
+    #[async_trait]
+    impl IMutableRepository<FileMeta> for FileMetaRepo {
+        async fn update(&self, entity: FileMeta) -> anyhow::Result<FileMeta>;
+        async fn insert(&self, entity: FileMeta) -> anyhow::Result<FileMeta>;
+        async fn delete(&self, entity: FileMeta) -> anyhow::Result<bool>;
+        async fn delete_by_id(
+            &self,
+            uuid: &str,
+            entity: Option<FileMeta>,
+        ) -> anyhow::Result<bool>;
+        async fn save_changed(&self) -> anyhow::Result<bool>;
+    }
+    #[async_trait]
+    impl IDBRepository<FileMeta> for FileMetaRepo {}
+}
+
+mock! {
+    pub FileStorageRepo {}
+    #[async_trait]
+    impl IFileStorageRepo for FileStorageRepo {
+        async fn get_all_by_meta_id(&self, meta_id: Uuid) -> anyhow::Result<Vec<FileStorage>>;
+        async fn get_by_storage_server_id_and_meta_id(
+            &self,
+            storage_server_id: Uuid,
+            meta_id: Uuid,
+        ) -> anyhow::Result<String>;
+        async fn insert_with_custom_user_id(
+            &self,
+            entity: FileStorage,
+            user_id: Uuid,
+        ) -> anyhow::Result<()>;
+        async fn change_storage_server(
+            &self,
+            from_storage_server_id: Uuid,
+            entity: FileStorage,
+        ) -> anyhow::Result<()>;
+        async fn delete_by_storage_server_id_and_meta_id(
+            &self,
+            storage_server_id: Uuid,
+            meta_id: Uuid,
+        ) -> anyhow::Result<()>;
+        async fn set_corrupted(
+            &self,
+            storage_server_id: Uuid,
+            meta_id: Uuid,
+            corrupted: bool,
+        ) -> anyhow::Result<()>;
+    }
+    #[async_trait]
+    impl IReadOnlyRepository<FileStorage> for FileStorageRepo {
+        async fn get_by_id(&self, uuid: &str) -> anyhow::Result<FileStorage>;
+        async fn get_all(&self) -> anyhow::Result<Vec<FileStorage>>;
+    }
+    #[async_trait]
+    impl IMutableRepository<FileStorage> for FileStorageRepo {
+        async fn update(&self, entity: FileStorage) -> anyhow::Result<FileStorage>;
+        async fn insert(&self, entity: FileStorage) -> anyhow::Result<FileStorage>;
+        async fn delete(&self, entity: FileStorage) -> anyhow::Result<bool>;
+        async fn delete_by_id(
+            &self,
+            uuid: &str,
+            entity: Option<FileStorage>,
+        ) -> anyhow::Result<bool>;
+        async fn save_changed(&self) -> anyhow::Result<bool>;
+    }
+    #[async_trait]
+    impl IDBRepository<FileStorage> for FileStorageRepo {}
+}
+
+mock! {
+    pub NetDiskRepo {}
+    #[async_trait]
+    impl INetDiskRepo for NetDiskRepo {
+        async fn get_root_id(&self, user_id: Option<Uuid>) -> anyhow::Result<Option<Uuid>>;
+        async fn get_flow_draft_dir_id(&self, flow_draft_id: Uuid) -> anyhow::Result<Option<Uuid>>;
+        async fn get_node_instance_dir_id(&self, node_instance: Uuid) -> anyhow::Result<Option<Uuid>>;
+        async fn get_flow_instance_dir_id(&self, flow_instance: Uuid) -> anyhow::Result<Option<Uuid>>;
+        async fn get_flow_draft_root_id(&self) -> anyhow::Result<Option<Uuid>>;
+        async fn get_flow_instance_root_id(&self, user_id: Option<Uuid>) -> anyhow::Result<Option<Uuid>>;
+        async fn is_same_pid_fname_exists(
+            &self,
+            parent_id: Option<Uuid>,
+            file_name: &str,
+            user_id: Option<Uuid>,
+        ) -> anyhow::Result<bool>;
+        async fn create_root(&self) -> anyhow::Result<Uuid>;
+        async fn get_all_by_meta_id(&self, meta_id: Uuid) -> anyhow::Result<Vec<NetDisk>>;
+        async fn delete_all_by_meta_id(&self, meta_id: Uuid) -> anyhow::Result<()>;
+    }
+    #[async_trait]
+    impl IReadOnlyRepository<NetDisk> for NetDiskRepo {
+        async fn get_by_id(&self, uuid: &str) -> anyhow::Result<NetDisk>;
+        async fn get_all(&self) -> anyhow::Result<Vec<NetDisk>>;
+    }
+    #[async_trait]
+    impl IMutableRepository<NetDisk> for NetDiskRepo {
+        async fn update(&self, entity: NetDisk) -> anyhow::Result<NetDisk>;
+        async fn insert(&self, entity: NetDisk) -> anyhow::Result<NetDisk>;
+        async fn delete(&self, entity: NetDisk) -> anyhow::Result<bool>;
+        async fn delete_by_id(
+            &self,
+            uuid: &str,
+            entity: Option<NetDisk>,
+        ) -> anyhow::Result<bool>;
+        async fn save_changed(&self) -> anyhow::Result<bool>;
+    }
+    #[async_trait]
+    impl IDBRepository<NetDisk> for NetDiskRepo {}
+}
@@ -22,6 +22,8 @@ mock! {
         async fn pause_workflow(&self, id: Uuid) -> anyhow::Result<()>;
         async fn continue_workflow(&self, id: Uuid) -> anyhow::Result<()>;
         async fn terminate_workflow(&self, id: Uuid) -> anyhow::Result<()>;
+        async fn retry_node(&self, node_instance_id: Uuid) -> anyhow::Result<()>;
+        async fn retry_due_nodes(&self) -> anyhow::Result<()>;
         async fn debatch(
             &self,
             node_relations: &[NodeRelation],
@@ -81,6 +83,55 @@ mock! {
     }
 }
 
+mock! {
+    pub TaskDispatchService {}
+    #[async_trait]
+    impl ITaskDispatchService for TaskDispatchService {
+        async fn dispatch(&self, node_spec: &NodeSpec, task: &Task) -> anyhow::Result<()>;
+    }
+}
+
+mock! {
+    pub ClusterSelectionService {}
+    #[async_trait]
+    impl IClusterSelectionService for ClusterSelectionService{
+        async fn select_cluster(&self, node_spec: &NodeSpec, task: &Task)
+            -> anyhow::Result<ClusterDecision>;
+    }
+}
+
+mock! {
+    pub QuotaService {}
+    #[async_trait]
+    impl IQuotaService for QuotaService {
+        async fn acquire(
+            &self,
+            node_instance: &NodeInstance,
+            cluster_id: Uuid,
+            task: &Task,
+        ) -> anyhow::Result<QuotaDecision>;
+        async fn release(
+            &self,
+            node_instance: &NodeInstance,
+            reservation: &QuotaReservation,
+            used_storage: u64,
+        ) -> anyhow::Result<()>;
+    }
+}
+
+mock! {
+    pub BudgetService {}
+    #[async_trait]
+    impl IBudgetService for BudgetService {
+        async fn check(
+            &self,
+            node_instance: &NodeInstance,
+            cluster_id: Uuid,
+            task: &Task,
+        ) -> anyhow::Result<BudgetDecision>;
+    }
+}
+
 mock! {
     pub ComputingUsecaseGetter {}
     #[async_trait]
@@ -155,3 +206,91 @@ mock! {
 //         async fn file_download_url(&self, meta_id: Uuid) -> AnyhowResult<String>;
 //     }
 // }
+
+mock! {
+    pub ResourcesService {}
+    #[async_trait]
+    impl IResourcesService for ResourcesService {
+        async fn default_file_storage_server(&self) -> AnyhowResult<StorageServer>;
+        async fn get_storage_server(&self, id: Uuid) -> AnyhowResult<StorageServer>;
+        async fn update_cluster_used_resource(
+            &self,
+            topic_name: &str,
+            used: ClusterUsedResource,
+        ) -> Anyhow;
+    }
+}
+
+mock! {
+    pub StorageServerBrokerService {}
+    #[async_trait]
+    impl IStorageServerBrokerService for StorageServerBrokerService {
+        async fn upload(
+            &self,
+            storage_server: &StorageServer,
+            meta_id: Uuid,
+            parts: Vec<FileStream>,
+        ) -> AnyhowResult<ServerUrl>;
+        async fn read_stream(
+            &self,
+            storage_server: &StorageServer,
+            meta_id: Uuid,
+        ) -> AnyhowResult<FileStream>;
+        async fn delete(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow;
+        async fn download(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow;
+        async fn get_download_url(
+            &self,
+            storage_server: &StorageServer,
+            meta_id: Uuid,
+        ) -> AnyhowResult<String>;
+        async fn get_bytes(
+            &self,
+            storage_server: &StorageServer,
+            meta_id: Uuid,
+        ) -> AnyhowResult<Vec<u8>>;
+        async fn get_text(&self, storage_server: &StorageServer, meta_id: Uuid)
+            -> AnyhowResult<String>;
+        async fn rangely_get_file(
+            &self,
+            storage_server: &StorageServer,
+            meta_id: Uuid,
+            ranges: &[Range<u64>],
+        ) -> AnyhowResult<Vec<Vec<u8>>>;
+        async fn get_file_size(
+            &self,
+            storage_server: &StorageServer,
+            meta_id: Uuid,
+        ) -> AnyhowResult<u64>;
+    }
+}
+
+mock! {
+    pub SnapshotService {}
+    #[async_trait]
+    impl ISnapshotService for SnapshotService {
+        async fn request(&self, info: RequestSnapshotCommand) -> Anyhow;
+        async fn create(&self, snapshot: Snapshot) -> Anyhow;
+        async fn create_record(&self, snapshot: Snapshot) -> Anyhow;
+        async fn remove(&self, id: Uuid) -> anyhow::Result<()>;
+        async fn read(&self, id: Uuid) -> anyhow::Result<Vec<u8>>;
+        async fn get_all_by_nid_and_fid(
+            &self,
+            node_id: Uuid,
+            meta_id: Uuid,
+        ) -> anyhow::Result<Vec<Snapshot>>;
+        async fn satisfy_flash_upload(
+            &self,
+            hash: &str,
+            hash_algorithm: &HashAlgorithm,
+        ) -> AnyhowResult<Option<Uuid>>;
+    }
+}
+
+mock! {
+    pub FileReferenceService {}
+    #[async_trait]
+    impl IFileReferenceService for FileReferenceService {
+        async fn collect_garbage(&self, dry_run: bool) -> AnyhowResult<GarbageCollectionReport>;
+        async fn deduplication_report(&self) -> AnyhowResult<DeduplicationReport>;
+    }
+}
@@ -1,6 +1,7 @@
 use alice_architecture::model::IAggregateRoot;
 use num_derive::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 impl IAggregateRoot for Cluster {}
 
@@ -28,3 +29,50 @@ pub enum ClusterTech {
     Slurm,
     Pbs,
 }
+
+/// 集群资源情况
+/// 总量来自集群注册信息，使用量来自 agent 定时上报
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterResource {
+    /// 集群 id
+    pub cluster_id: Uuid,
+    /// 内存大小，单位为字节
+    pub memory: u64,
+    /// 核心个数
+    pub core_number: u64,
+    /// 已分配内存，单位为字节
+    pub allocated_memory: u64,
+    /// 已分配核心数
+    pub allocated_cpu_count: u64,
+    /// 排队中的任务数
+    pub queuing_task_count: u64,
+    /// 运行中的任务数
+    pub running_task_count: u64,
+}
+
+impl ClusterResource {
+    /// 空闲核心数
+    pub fn free_cores(&self) -> u64 {
+        self.core_number.saturating_sub(self.allocated_cpu_count)
+    }
+
+    /// 空闲内存，单位为字节
+    pub fn free_memory(&self) -> u64 {
+        self.memory.saturating_sub(self.allocated_memory)
+    }
+}
+
+/// agent 定时上报的集群资源使用量
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ClusterUsedResource {
+    /// 已分配内存，单位为字节
+    pub allocated_memory: u64,
+    /// 已分配核心数
+    pub allocated_cpu_count: u64,
+    /// 排队中的任务数
+    pub queuing_task_count: u64,
+    /// 运行中的任务数
+    pub running_task_count: u64,
+}
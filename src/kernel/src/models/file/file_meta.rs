@@ -19,4 +19,11 @@ pub struct FileMeta {
     pub hash_algorithm: HashAlgorithm,
     /// Size of these files.
     pub size: usize,
+    /// Pinned files are kept by lifecycle policies.
+    pub pinned: bool,
+    /// Count of net disk files, and node inputs and prepared node outputs of workflow drafts,
+    /// templates and instances referring to these files.
+    ///
+    /// Unreferenced files are collected as garbage.
+    pub reference_count: i64,
 }
@@ -12,4 +12,6 @@ pub struct FileStorage {
     pub meta_id: Uuid,
     /// The relative url on the server.
     pub server_url: String,
+    /// Whether the last scrub found the content doesn't match the hash of file meta.
+    pub corrupted: bool,
 }
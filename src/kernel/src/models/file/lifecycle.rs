@@ -0,0 +1,75 @@
+use crate::prelude::*;
+
+/// Lifecycle policy applied to stored files.
+///
+/// Pinned files are never deleted or moved by any policy.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecyclePolicy {
+    /// Delete workflow intermediate outputs this many days after the instance finishes.
+    #[serde(default)]
+    pub intermediate_output_expire_days: Option<i64>,
+    /// Move workflow results to a cold storage server.
+    #[serde(default)]
+    pub cold_storage: Option<ColdStoragePolicy>,
+}
+
+/// Move workflow results to a cold storage server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColdStoragePolicy {
+    /// Cold storage server id.
+    pub storage_server_id: Uuid,
+    /// Move results this many days after the instance finishes.
+    pub after_days: i64,
+}
+
+/// A file output of a workflow node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputFile {
+    /// Node instance id.
+    pub node_id: Uuid,
+    /// Output file meta id.
+    pub meta_id: Uuid,
+    /// Whether the output is consumed by another node, otherwise it is a workflow result.
+    pub is_intermediate: bool,
+}
+
+/// A change applied, or would be applied in dry run, to a stored file copy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LifecycleAction {
+    /// Delete file copy from storage server.
+    #[serde(rename_all = "camelCase")]
+    Delete {
+        meta_id: Uuid,
+        storage_server_id: Uuid,
+    },
+    /// Move file copy from one storage server to another.
+    #[serde(rename_all = "camelCase")]
+    Move {
+        meta_id: Uuid,
+        from_storage_server_id: Uuid,
+        to_storage_server_id: Uuid,
+    },
+}
+
+/// An action that failed to be applied.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleFailure {
+    pub action: LifecycleAction,
+    pub reason: String,
+}
+
+/// Report of a lifecycle policy run.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleReport {
+    /// Whether actions are only planned but not applied.
+    pub dry_run: bool,
+    /// Actions applied, or would be applied in dry run.
+    pub actions: Vec<LifecycleAction>,
+    /// Actions failed to be applied.
+    pub failures: Vec<LifecycleFailure>,
+}
@@ -1,9 +1,12 @@
 pub mod common;
 pub mod file_meta;
 pub mod file_storage;
+pub mod lifecycle;
 pub mod move_registration;
 pub mod multipart;
 pub mod net_disk;
+pub mod reference;
+pub mod scrub;
 pub mod snapshot;
 pub mod storage_server;
 pub mod ws_req_info;
@@ -12,9 +15,12 @@ pub mod prelude {
     pub use super::common::*;
     pub use super::file_meta::*;
     pub use super::file_storage::*;
+    pub use super::lifecycle::*;
     pub use super::move_registration::*;
     pub use super::multipart::*;
     pub use super::net_disk::*;
+    pub use super::reference::*;
+    pub use super::scrub::*;
     pub use super::snapshot::*;
     pub use super::storage_server::*;
     pub use super::ws_req_info::*;
@@ -0,0 +1,39 @@
+use crate::prelude::*;
+
+/// Report of a garbage collection run over unreferenced files.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GarbageCollectionReport {
+    /// Whether files are only listed but not deleted.
+    pub dry_run: bool,
+    /// Files deleted, or would be deleted in dry run.
+    pub meta_ids: Vec<Uuid>,
+    /// Total size of the files, copies on different storage servers are counted once.
+    pub freed_size: usize,
+    /// Files failed to be deleted.
+    pub failures: Vec<GarbageCollectionFailure>,
+}
+
+/// A file that failed to be collected.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GarbageCollectionFailure {
+    pub meta_id: Uuid,
+    pub reason: String,
+}
+
+/// Storage saved by deduplicating files with the same hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeduplicationReport {
+    /// Count of referenced files.
+    pub file_count: i64,
+    /// Total references of these files.
+    pub reference_count: i64,
+    /// Size of these files, each stored once.
+    pub stored_size: i64,
+    /// Size these files would take if every reference stored its own copy.
+    pub referenced_size: i64,
+    /// Size saved by deduplication.
+    pub saved_size: i64,
+}
@@ -0,0 +1,32 @@
+use crate::prelude::*;
+
+/// Report of re-hashing all stored file copies.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrubReport {
+    /// Count of copies whose hash matches.
+    pub verified_count: usize,
+    /// Copies whose hash doesn't match.
+    pub corruptions: Vec<FileCorruption>,
+    /// Copies failed to be read.
+    pub failures: Vec<ScrubFailure>,
+}
+
+/// A stored file copy whose content doesn't match its hash.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileCorruption {
+    pub meta_id: Uuid,
+    pub storage_server_id: Uuid,
+    pub expected_hash: String,
+    pub actual_hash: String,
+}
+
+/// A stored file copy failed to be scrubbed.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrubFailure {
+    pub meta_id: Uuid,
+    pub storage_server_id: Uuid,
+    pub reason: String,
+}
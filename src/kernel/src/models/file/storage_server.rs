@@ -26,6 +26,11 @@ pub enum StorageType {
         #[serde(flatten)]
         options: ObjectServerOption,
     },
+    /// Local or POSIX parallel filesystem mounted on co.
+    FileSystem {
+        #[serde(flatten)]
+        options: FileSystemServerOption,
+    },
 }
 
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
@@ -38,3 +43,24 @@ pub struct ObjectServerOption {
     pub secret_access_key: String,
     pub region: String,
 }
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSystemServerOption {
+    /// Root directory that every stored file is sandboxed in.
+    pub root: String,
+    /// Endpoint of co web server that serves the stored files.
+    pub download_endpoint: String,
+    /// Key to sign download urls with, downloads are refused while it is empty.
+    #[serde(default)]
+    pub signing_key: String,
+    /// Seconds that a signed download url stays valid.
+    #[serde(default = "FileSystemServerOption::default_url_expire_secs")]
+    pub url_expire_secs: i64,
+}
+
+impl FileSystemServerOption {
+    fn default_url_expire_secs() -> i64 {
+        3600
+    }
+}
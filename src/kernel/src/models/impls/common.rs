@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Value};
 use rand::Rng;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -105,3 +106,149 @@ impl NodeInputSlotKind {
         })
     }
 }
+
+impl Requirements {
+    /// 需要的核心总数，未指定的核心数与节点数按 1 计算
+    pub fn required_cores(&self) -> u64 {
+        let cpu_cores = self.cpu_cores.unwrap_or(1) as u64;
+        let node_count = self.node_count.filter(|el| *el > 0).unwrap_or(1) as u64;
+        cpu_cores * node_count
+    }
+
+    /// 需要的内存总量（字节），未指定每个节点的内存时为 0
+    pub fn required_memory(&self) -> u64 {
+        let memory_per_node = self.memory_per_node.unwrap_or_default() as u64;
+        let node_count = self.node_count.filter(|el| *el > 0).unwrap_or(1) as u64;
+        memory_per_node * node_count * 1024 * 1024
+    }
+}
+
+impl RetryPolicy {
+    /// 判断第 `attempt` 次尝试以 `kind` 失败后是否应该重试
+    ///
+    /// # 参数
+    ///
+    /// * `attempt` - 已经完成的尝试次数（从 1 开始）
+    /// * `kind` - 失败种类
+    pub fn should_retry(&self, attempt: usize, kind: &TaskFailureKind) -> bool {
+        attempt < self.max_attempts && (self.retry_on.is_empty() || self.retry_on.contains(kind))
+    }
+
+    /// 第 `attempt` 次尝试失败后，下一次重试前需要等待的秒数
+    ///
+    /// # 参数
+    ///
+    /// * `attempt` - 已经完成的尝试次数（从 1 开始）
+    pub fn backoff_seconds(&self, attempt: usize) -> u64 {
+        match &self.backoff {
+            RetryBackoff::Immediate => 0,
+            RetryBackoff::Fixed { seconds } => *seconds,
+            RetryBackoff::Exponential {
+                initial_seconds,
+                multiplier,
+                max_seconds,
+            } => {
+                let exponent = attempt.saturating_sub(1).min(u32::MAX as usize) as u32;
+                let seconds = (*multiplier as u64)
+                    .checked_pow(exponent)
+                    .and_then(|el| el.checked_mul(*initial_seconds))
+                    .unwrap_or(u64::MAX);
+                match max_seconds {
+                    Some(max_seconds) => seconds.min(*max_seconds),
+                    None => seconds,
+                }
+            }
+        }
+    }
+}
+
+impl BatchFailurePolicy {
+    /// 判断在 `total` 个子任务中失败 `failed` 个时是否仍可继续
+    ///
+    /// # 参数
+    ///
+    /// * `failed` - 失败的子任务个数
+    /// * `total` - 子任务总数
+    pub fn tolerates(&self, failed: usize, total: usize) -> bool {
+        match self {
+            BatchFailurePolicy::FailFast => failed == 0,
+            BatchFailurePolicy::TolerateCount { count } => failed <= *count,
+            BatchFailurePolicy::ToleratePercentage { percentage } => {
+                total == 0 || failed as f64 * 100.0 <= *percentage * total as f64
+            }
+            BatchFailurePolicy::IgnoreFailures => true,
+        }
+    }
+}
+
+impl Condition {
+    /// 条件节点保存求值结果（`true` 或 `false`）的文本输出插槽描述符
+    pub const RESULT_SLOT: &'static str = "result";
+
+    /// 求值条件表达式
+    ///
+    /// # 参数
+    ///
+    /// * `variables` - 变量名与变量文本，文本依次尝试解析为整数、浮点数、布尔值，都失败时作为字符串
+    pub fn evaluate(&self, variables: &HashMap<String, String>) -> anyhow::Result<bool> {
+        let mut context = HashMapContext::new();
+        for (name, text) in variables.iter() {
+            let text = text.trim();
+            let value = if let Ok(value) = text.parse::<i64>() {
+                Value::Int(value)
+            } else if let Ok(value) = text.parse::<f64>() {
+                Value::Float(value)
+            } else if let Ok(value) = text.parse::<bool>() {
+                Value::Boolean(value)
+            } else {
+                Value::String(text.to_string())
+            };
+            context.set_value(name.to_owned(), value)?;
+        }
+        Ok(evalexpr::eval_boolean_with_context(
+            &self.expression,
+            &context,
+        )?)
+    }
+}
+
+impl NodeLoop {
+    /// 改变循环结构中的旧节点 id 为新 id
+    ///
+    /// # 参数
+    ///
+    /// * `id_map` - 旧 id 与新 id 对照 map
+    pub fn update_id(&mut self, id_map: &HashMap<Uuid, Uuid>) {
+        for node_id in self.node_ids.iter_mut() {
+            if let Some(new_id) = id_map.get(node_id) {
+                *node_id = new_id.to_owned();
+            }
+        }
+        for carried_relation in self.carried_relations.iter_mut() {
+            carried_relation.update_id(id_map);
+        }
+        if let Some(new_id) = id_map.get(&self.until.node_id) {
+            self.until.node_id = new_id.to_owned();
+        }
+    }
+
+    /// 当前迭代的节点 id，与 node_ids 一一对应
+    pub fn current_iteration(&self) -> &[Uuid] {
+        self.iterations.last().unwrap_or(&self.node_ids)
+    }
+
+    /// 已实例化的迭代次数
+    pub fn current_iteration_count(&self) -> usize {
+        self.iterations.len().max(1)
+    }
+
+    /// 循环体内的节点在当前迭代中的 id
+    ///
+    /// # 参数
+    ///
+    /// * `node_id` - 第一次迭代中的节点 id
+    pub fn current_node_id(&self, node_id: Uuid) -> Option<Uuid> {
+        let index = self.node_ids.iter().position(|el| el.eq(&node_id))?;
+        self.current_iteration().get(index).cloned()
+    }
+}
@@ -32,16 +32,39 @@ impl From<WorkflowDraftSpec> for WorkflowInstanceSpec {
                 el
             })
             .collect::<Vec<_>>();
+        let loops = l
+            .loops
+            .into_iter()
+            .map(|mut el| {
+                el.update_id(&old_new_id_map);
+                el.iterations = vec![el.node_ids.to_owned()];
+                el
+            })
+            .collect::<Vec<_>>();
         Self {
             scheduling_strategy: l.scheduling_strategy,
             node_specs,
             node_relations,
+            loops,
         }
     }
 }
 
 impl From<NodeDraft> for NodeSpec {
     fn from(l: NodeDraft) -> Self {
+        let mut output_slots =
+            l.output_slots.into_iter().map(NodeSpecOutputSlot::from).collect::<Vec<_>>();
+        // 条件节点的求值结果保存在专用的文本输出插槽上，草稿未声明时补充
+        if matches!(l.kind, NodeKind::Condition { .. })
+            && !output_slots.iter().any(|el| el.descriptor.eq(Condition::RESULT_SLOT))
+        {
+            output_slots.push(NodeSpecOutputSlot::from(NodeDraftOutputSlot {
+                kind: NodeDraftOutputSlotKind::Text,
+                descriptor: Condition::RESULT_SLOT.to_string(),
+                description: None,
+                optional: true,
+            }));
+        }
         Self {
             id: Uuid::new_v4(),
             name: l.name,
@@ -51,10 +74,11 @@ impl From<NodeDraft> for NodeSpec {
                 None => vec![],
             },
             input_slots: l.input_slots,
-            output_slots: l.output_slots.into_iter().map(NodeSpecOutputSlot::from).collect(),
+            output_slots,
             scheduling_strategy: l.scheduling_strategy,
             kind: l.kind,
             requirements: l.requirements,
+            retry_policy: l.retry_policy,
             additional_datas: l.additional_datas,
         }
     }
@@ -94,6 +118,9 @@ impl From<NodeKind> for NodeInstanceKind {
             NodeKind::NoAction => Self::NoAction,
             NodeKind::Script { .. } => Self::Script,
             NodeKind::Milestone { .. } => Self::Milestone,
+            NodeKind::Condition { .. } => Self::Condition,
+            // 自定义节点在提交时已展开，不会生成节点实例
+            NodeKind::CustomNode { .. } => Self::NoAction,
         }
     }
 }
@@ -1 +1,37 @@
+use crate::prelude::*;
 
+impl TaskFailureKind {
+    /// 任务失败信息中记录调度器作业状态的行前缀
+    pub const SCHEDULER_STATE_PREFIX: &'static str = "Scheduler state: ";
+
+    /// 根据任务失败信息中的调度器作业状态判断失败种类
+    ///
+    /// # 参数
+    ///
+    /// * `message` - 任务失败信息
+    pub fn from_message(message: &str) -> Self {
+        let state = message
+            .lines()
+            .find_map(|el| el.trim().strip_prefix(Self::SCHEDULER_STATE_PREFIX))
+            .map(|el| el.trim().to_uppercase());
+        match state.as_deref() {
+            Some("NODE_FAIL") => Self::NodeFail,
+            Some("BOOT_FAIL") => Self::BootFail,
+            Some("PREEMPTED") => Self::Preempted,
+            Some("TIMEOUT") | Some("DEADLINE") => Self::Timeout,
+            Some("OUT_OF_MEMORY") => Self::OutOfMemory,
+            _ => Self::Error,
+        }
+    }
+}
+
+impl Task {
+    /// 任务中用例执行或脚本执行的资源需求
+    pub fn requirements(&self) -> Option<&Requirements> {
+        self.body.iter().find_map(|el| match el {
+            TaskBody::UsecaseExecution { requirements, .. }
+            | TaskBody::ExecuteScript { requirements, .. } => requirements.as_ref(),
+            _ => None,
+        })
+    }
+}
@@ -1,6 +1,10 @@
 use crate::prelude::*;
 use alice_architecture::repository::IReadOnlyRepository;
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+};
 
 impl NodeDraft {
     /// 根据 id 获取输入插槽
@@ -281,4 +285,168 @@ impl WorkflowDraftSpec {
         }
         Ok(())
     }
+
+    /// 静态分析节点依赖图，返回发现的所有问题
+    ///
+    /// 1. 节点 id 不能重复
+    /// 2. 节点依赖不能成环
+    /// 3. 所有节点都必须能从入口节点到达（依赖环下游的节点永远不会被调度）
+    /// 4. 有下游节点的节点，其必需的输出插槽必须被使用，末端节点的输出作为工作流结果不做要求
+    pub fn analyze_graph(&self) -> Vec<WorkflowDraftException> {
+        let mut problems = vec![];
+        let mut node_ids = vec![];
+        let mut duplicated_ids = vec![];
+        for node_draft in self.node_drafts.iter() {
+            let id = node_draft.external_id;
+            if !node_ids.contains(&id) {
+                node_ids.push(id);
+            } else if !duplicated_ids.contains(&id) {
+                duplicated_ids.push(id);
+            }
+        }
+        problems.extend(
+            duplicated_ids
+                .into_iter()
+                .map(|id| WorkflowDraftException::DuplicatedNodeId { id }),
+        );
+
+        // 不存在的节点由 validate_related_nodes 报告，这里忽略
+        let edges = self
+            .node_relations
+            .iter()
+            .filter(|el| node_ids.contains(&el.from_id) && node_ids.contains(&el.to_id))
+            .map(|el| (el.from_id, el.to_id))
+            .collect::<Vec<_>>();
+
+        // 不断移除入度为零的节点，剩下的节点在依赖环上或在依赖环下游
+        let mut in_degrees = node_ids.iter().map(|el| (*el, 0)).collect::<HashMap<_, _>>();
+        for (_, to_id) in edges.iter() {
+            *in_degrees.get_mut(to_id).unwrap() += 1;
+        }
+        let mut queue =
+            node_ids.iter().filter(|el| in_degrees[*el] == 0).cloned().collect::<Vec<_>>();
+        while let Some(node_id) = queue.pop() {
+            for (_, to_id) in edges.iter().filter(|el| el.0.eq(&node_id)) {
+                let in_degree = in_degrees.get_mut(to_id).unwrap();
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    queue.push(*to_id);
+                }
+            }
+        }
+        let remaining =
+            node_ids.iter().filter(|el| in_degrees[*el] > 0).cloned().collect::<Vec<_>>();
+        let reachable = remaining
+            .iter()
+            .map(|el| (*el, Self::reachable_nodes(*el, &edges)))
+            .collect::<HashMap<_, _>>();
+        let mut cycle_node_ids = HashSet::new();
+        for node_id in remaining.iter() {
+            if cycle_node_ids.contains(node_id) || !reachable[node_id].contains(node_id) {
+                continue;
+            }
+            // 互相可达的节点在同一个依赖环上
+            let cycle = remaining
+                .iter()
+                .filter(|el| reachable[node_id].contains(*el) && reachable[*el].contains(node_id))
+                .cloned()
+                .collect::<Vec<_>>();
+            cycle_node_ids.extend(cycle.iter().cloned());
+            problems.push(WorkflowDraftException::CyclicDependency { node_ids: cycle });
+        }
+        problems.extend(
+            remaining
+                .iter()
+                .filter(|el| !cycle_node_ids.contains(*el))
+                .map(|el| WorkflowDraftException::UnreachableNode { node_id: *el }),
+        );
+
+        let consumed_slots = self
+            .node_relations
+            .iter()
+            .chain(self.loops.iter().flat_map(|el| el.carried_relations.iter()))
+            .flat_map(|el| el.slot_relations.iter().map(|el2| (el.from_id, el2.from_slot.as_str())))
+            .collect::<HashSet<_>>();
+        for node_draft in self.node_drafts.iter() {
+            let id = node_draft.external_id;
+            if !self.node_relations.iter().any(|el| el.from_id.eq(&id)) {
+                continue;
+            }
+            problems.extend(
+                node_draft
+                    .output_slots
+                    .iter()
+                    .filter(|el| {
+                        !el.optional && !consumed_slots.contains(&(id, el.descriptor.as_str()))
+                    })
+                    .map(|el| WorkflowDraftException::UnconsumedOutputSlot {
+                        node_id: id,
+                        descriptor: el.descriptor.to_owned(),
+                    }),
+            );
+        }
+        problems
+    }
+
+    /// 从某节点出发沿依赖关系能到达的所有节点（不包含出发节点本身，除非其在依赖环上）
+    ///
+    /// # 参数
+    ///
+    /// * `node_id` - 出发节点 id
+    /// * `edges` - 依赖关系
+    fn reachable_nodes(node_id: Uuid, edges: &[(Uuid, Uuid)]) -> HashSet<Uuid> {
+        let mut reachable = HashSet::new();
+        let mut stack = vec![node_id];
+        while let Some(from_id) = stack.pop() {
+            for (_, to_id) in edges.iter().filter(|el| el.0.eq(&from_id)) {
+                if reachable.insert(*to_id) {
+                    stack.push(*to_id);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// 检查节点的资源需求是否有启用的集群能够满足
+    /// 没有上报资源的集群无法判断，视为能够满足
+    ///
+    /// # 参数
+    ///
+    /// * `clusters` - 所有集群及其资源情况
+    pub fn validate_requirements(
+        &self,
+        clusters: &[(Cluster, Option<ClusterResource>)],
+    ) -> Vec<WorkflowDraftException> {
+        let mut problems = vec![];
+        for node_draft in self.node_drafts.iter() {
+            let requirements = match &node_draft.requirements {
+                Some(requirements) => requirements,
+                None => continue,
+            };
+            let strategy = match &node_draft.scheduling_strategy {
+                SchedulingStrategy::Auto => &self.scheduling_strategy,
+                el => el,
+            };
+            let cpu_cores = requirements.required_cores();
+            let satisfiable = clusters
+                .iter()
+                .filter(|(cluster, _)| cluster.enabled)
+                .filter(|(cluster, _)| match strategy {
+                    SchedulingStrategy::Manual { clusters } => Uuid::from_str(&cluster.id)
+                        .map(|el| clusters.contains(&el))
+                        .unwrap_or(false),
+                    _ => true,
+                })
+                .any(|(_, resource)| {
+                    resource.as_ref().map(|el| el.core_number >= cpu_cores).unwrap_or(true)
+                });
+            if !satisfiable {
+                problems.push(WorkflowDraftException::UnsatisfiableRequirements {
+                    node_id: node_draft.external_id,
+                    cpu_cores,
+                });
+            }
+        }
+        problems
+    }
 }
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 impl WorkflowInstance {
@@ -184,6 +185,62 @@ impl NodeSpec {
         Ok(sub_nodes)
     }
 
+    /// 用批量父节点预分配的输出恢复子节点的输出插槽
+    /// 重新分批得到的子节点会生成新的输出 id，重试子节点时需要沿用第一次分批时的输出 id
+    ///
+    /// # 参数
+    ///
+    /// * `parent` - 批量父节点
+    /// * `index` - 子节点在父节点所有子节点中的次序
+    pub fn restore_sub_node_output_slots(
+        &mut self,
+        parent: &NodeSpec,
+        index: usize,
+    ) -> anyhow::Result<()> {
+        for output_slot in self.output_slots.iter_mut() {
+            let parent_output_slot = parent.output_slot(&output_slot.descriptor);
+            match &mut output_slot.kind {
+                NodeSpecOutputSlotKind::File {
+                    all_tasks_prepared_content_ids,
+                    ..
+                } => {
+                    *all_tasks_prepared_content_ids = vec![*parent_output_slot
+                        .all_tasks_file_outputs()?
+                        .get(index)
+                        .ok_or(anyhow::anyhow!(
+                            "OutputSlot {} has no output for sub task {index}",
+                            output_slot.descriptor
+                        ))?]
+                }
+                NodeSpecOutputSlotKind::Text {
+                    all_tasks_prepared_text_keys,
+                } => {
+                    *all_tasks_prepared_text_keys = vec![*parent_output_slot
+                        .all_tasks_text_outputs()?
+                        .get(index)
+                        .ok_or(anyhow::anyhow!(
+                            "OutputSlot {} has no output for sub task {index}",
+                            output_slot.descriptor
+                        ))?]
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 判断批量节点在 `total` 个子任务中失败 `failed` 个时是否仍可继续
+    /// 多个批量策略时需要全部策略都能容忍
+    ///
+    /// # 参数
+    ///
+    /// * `failed` - 失败的子任务个数
+    /// * `total` - 子任务总数
+    pub fn tolerates_batch_failures(&self, failed: usize, total: usize) -> bool {
+        self.batch_strategies
+            .iter()
+            .all(|el| el.failure_policy.tolerates(failed, total))
+    }
+
     /// 得到节点上所有输入插槽的所有文本 id（可能为空）
     pub fn text_keys(&self) -> Vec<Uuid> {
         let mut result = vec![];
@@ -214,6 +271,56 @@ impl WorkflowInstanceSpec {
     pub fn node_mut(&mut self, id: Uuid) -> &mut NodeSpec {
         self.node_specs.iter_mut().find(|el| el.id.eq(&id)).unwrap()
     }
+
+    /// 取得所有节点的输出文件，被其他节点使用的输出为中间产物，否则为工作流结果
+    pub fn output_files(&self) -> Vec<OutputFile> {
+        let mut output_files = vec![];
+        for node_spec in self.node_specs.iter() {
+            for output_slot in node_spec.output_slots.iter() {
+                let all_tasks_prepared_content_ids = match &output_slot.kind {
+                    NodeSpecOutputSlotKind::File {
+                        all_tasks_prepared_content_ids,
+                        ..
+                    } => all_tasks_prepared_content_ids,
+                    NodeSpecOutputSlotKind::Text { .. } => continue,
+                };
+                let is_intermediate = self.node_relations.iter().any(|relation| {
+                    relation.from_id.eq(&node_spec.id)
+                        && relation
+                            .slot_relations
+                            .iter()
+                            .any(|el| el.from_slot.eq(&output_slot.descriptor))
+                });
+                output_files.extend(all_tasks_prepared_content_ids.iter().map(|meta_id| {
+                    OutputFile {
+                        node_id: node_spec.id,
+                        meta_id: *meta_id,
+                        is_intermediate,
+                    }
+                }));
+            }
+        }
+        output_files
+    }
+
+    /// 取得所有节点输入中已填写的文件 id 与预分配的输出文件 id，同一文件被多次使用时重复出现
+    pub fn file_ids(&self) -> Vec<Uuid> {
+        let input_file_ids = self
+            .node_specs
+            .iter()
+            .flat_map(|el| el.input_slots.iter())
+            .filter_map(|el| match &el.kind {
+                NodeInputSlotKind::File {
+                    contents: Some(contents),
+                    ..
+                } => Some(contents.iter().map(|el| el.file_metadata_id)),
+                _ => None,
+            })
+            .flatten();
+        input_file_ids
+            .chain(self.output_files().into_iter().map(|el| el.meta_id))
+            .collect()
+    }
 }
 
 impl WorkflowInstance {
@@ -268,34 +375,169 @@ impl WorkflowInstance {
             .spec
             .node_specs
             .iter()
-            .map(|node_spec| {
-                let mut node_instances = vec![];
-                let root_instance = NodeInstance {
-                    kind: NodeInstanceKind::from(node_spec.kind.to_owned()),
-                    id: node_spec.id.to_owned(),
-                    name: node_spec.name.to_owned(),
-                    is_parent: !node_spec.batch_strategies.is_empty(),
-                    flow_instance_id: self.id.to_owned(),
-                    ..Default::default()
-                };
+            .map(|node_spec| self.parse_node_spec_instances(node_spec))
+            .collect::<Vec<_>>();
 
-                node_instances.push(root_instance.to_owned());
-                if root_instance.is_parent {
-                    let count = self.sub_node_count(node_spec.id);
-                    for i in 0..count {
-                        node_instances.push(NodeInstance {
-                            id: Uuid::new_v4(),
-                            name: format!("{}_sub_task_{}", node_spec.name, i),
-                            is_parent: false,
-                            batch_parent_id: Some(root_instance.id.to_owned()),
-                            ..root_instance.to_owned()
-                        })
-                    }
-                }
-                node_instances
+        Ok(node_instances.into_iter().flatten().collect::<Vec<_>>())
+    }
+
+    /// 解析一个根节点得到其节点实例，批量父节点同时得到其所有子节点实例
+    ///
+    /// # 参数
+    ///
+    /// * `node_spec` - 根节点
+    fn parse_node_spec_instances(&self, node_spec: &NodeSpec) -> Vec<NodeInstance> {
+        let mut node_instances = vec![];
+        let root_instance = NodeInstance {
+            kind: NodeInstanceKind::from(node_spec.kind.to_owned()),
+            id: node_spec.id.to_owned(),
+            name: node_spec.name.to_owned(),
+            is_parent: !node_spec.batch_strategies.is_empty(),
+            flow_instance_id: self.id.to_owned(),
+            ..Default::default()
+        };
+
+        node_instances.push(root_instance.to_owned());
+        if root_instance.is_parent {
+            let count = self.sub_node_count(node_spec.id);
+            for i in 0..count {
+                node_instances.push(NodeInstance {
+                    id: Uuid::new_v4(),
+                    name: format!("{}_sub_task_{}", node_spec.name, i),
+                    is_parent: false,
+                    batch_parent_id: Some(root_instance.id.to_owned()),
+                    batch_index: Some(i),
+                    ..root_instance.to_owned()
+                })
+            }
+        }
+        node_instances
+    }
+
+    /// 节点是否在等待未结束的循环
+    ///
+    /// 循环体外部的节点依赖循环体当前迭代的节点时，要等循环结束才能使用其输出
+    ///
+    /// # 参数
+    ///
+    /// * `node_id` - 节点 id
+    pub fn waits_for_unfinished_loop(&self, node_id: Uuid) -> bool {
+        self.spec.loops.iter().filter(|el| el.completion.is_none()).any(|node_loop| {
+            let body = node_loop.current_iteration();
+            !body.contains(&node_id)
+                && self
+                    .spec
+                    .node_relations
+                    .iter()
+                    .any(|el| el.to_id.eq(&node_id) && body.contains(&el.from_id))
+        })
+    }
+
+    /// 以新的节点 id 实例化循环的下一次迭代，返回新迭代的节点实例
+    ///
+    /// 新迭代的节点：
+    /// 1. 循环体内部的依赖关系指向新迭代的节点
+    /// 2. 循环体外部提供的输入沿用，但被传递关系覆盖的输入插槽改由上一次迭代的输出提供
+    /// 3. 循环体外部依赖循环体输出的节点改为依赖新迭代的节点
+    ///
+    /// # 参数
+    ///
+    /// * `loop_index` - 循环在工作流实例中的次序
+    pub fn instantiate_loop_iteration(
+        &mut self,
+        loop_index: usize,
+    ) -> anyhow::Result<Vec<NodeInstance>> {
+        let node_loop = self
+            .spec
+            .loops
+            .get(loop_index)
+            .ok_or(anyhow::anyhow!("No such loop: {loop_index}"))?
+            .to_owned();
+        if node_loop.iterations.is_empty() {
+            self.spec.loops[loop_index].iterations.push(node_loop.node_ids.to_owned());
+        }
+        let iteration = node_loop.current_iteration_count() + 1;
+        let current_ids = node_loop.current_iteration().to_vec();
+        let id_map = current_ids
+            .iter()
+            .map(|el| (el.to_owned(), Uuid::new_v4()))
+            .collect::<HashMap<_, _>>();
+
+        // 被传递关系覆盖的输入插槽
+        let carried_slots = node_loop
+            .carried_relations
+            .iter()
+            .flat_map(|el| {
+                let to_id = node_loop.current_node_id(el.to_id);
+                el.slot_relations.iter().map(move |el2| (to_id, el2.to_slot.to_owned()))
             })
             .collect::<Vec<_>>();
 
-        Ok(node_instances.into_iter().flatten().collect::<Vec<_>>())
+        let mut node_relations = vec![];
+        for node_relation in self.spec.node_relations.iter_mut() {
+            let to_id = node_relation.to_id;
+            match (
+                id_map.get(&node_relation.from_id),
+                id_map.contains_key(&to_id),
+            ) {
+                // 循环体内部的依赖关系与循环体外部提供的输入，被传递关系覆盖的插槽关系不再沿用
+                (_, true) => {
+                    let mut new_relation = node_relation.to_owned();
+                    new_relation.slot_relations.retain(|el| {
+                        !carried_slots.contains(&(Some(to_id), el.to_slot.to_owned()))
+                    });
+                    if new_relation.slot_relations.is_empty()
+                        && !node_relation.slot_relations.is_empty()
+                    {
+                        continue;
+                    }
+                    new_relation.update_id(&id_map);
+                    node_relations.push(new_relation);
+                }
+                // 循环体外部依赖循环体输出
+                (Some(from_id), false) => node_relation.from_id = from_id.to_owned(),
+                (None, false) => {}
+            }
+        }
+        for carried_relation in node_loop.carried_relations.iter() {
+            let from_id = node_loop.current_node_id(carried_relation.from_id);
+            let to_id = node_loop.current_node_id(carried_relation.to_id);
+            let (from_id, to_id) = match (from_id, to_id) {
+                (Some(from_id), Some(to_id)) => (from_id, to_id),
+                _ => anyhow::bail!(
+                    "Carried relation from {} to {} is out of the loop body.",
+                    carried_relation.from_id,
+                    carried_relation.to_id
+                ),
+            };
+            node_relations.push(NodeRelation {
+                from_id,
+                to_id: id_map[&to_id],
+                ..carried_relation.to_owned()
+            });
+        }
+        self.spec.node_relations.extend(node_relations);
+
+        let mut new_ids = vec![];
+        let mut node_specs = vec![];
+        for (node_id, current_id) in node_loop.node_ids.iter().zip(current_ids.iter()) {
+            let mut node_spec = self.spec.node(*current_id).to_owned();
+            node_spec.id = id_map[current_id];
+            node_spec.name = format!("{}_iteration_{iteration}", self.spec.node(*node_id).name);
+            node_spec.update_output_slots();
+            new_ids.push(node_spec.id);
+            node_specs.push(node_spec);
+        }
+        self.spec.node_specs.extend(node_specs.iter().cloned());
+        self.spec.loops[loop_index].iterations.push(new_ids);
+
+        Ok(node_specs
+            .iter()
+            .flat_map(|el| self.parse_node_spec_instances(el))
+            .map(|el| NodeInstance {
+                status: NodeInstanceStatus::Standby,
+                ..el
+            })
+            .collect())
     }
 }
@@ -6,6 +6,7 @@ pub mod snapshot_info;
 pub mod software_deployment;
 pub mod task;
 pub mod text_storage;
+pub mod user_resource;
 pub mod workflow_engine;
 pub mod ws_file_info;
 
@@ -18,6 +19,7 @@ pub mod prelude {
     pub use super::software_deployment::*;
     pub use super::task::*;
     pub use super::text_storage::*;
+    pub use super::user_resource::*;
     pub use super::workflow_engine::prelude::*;
     pub use super::ws_file_info::*;
 }
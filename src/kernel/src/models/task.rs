@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use lib_co_repo::models::prelude::SoftwareSpec;
+use lib_co_repo::models::prelude::{Launcher as RepoLauncher, OutValidator, SoftwareSpec};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -48,6 +48,18 @@ pub enum TaskBody {
         files: Vec<FileInfo>,
         /// 计算资源配置
         requirements: Option<Requirements>,
+        /// 并行启动器
+        #[serde(default)]
+        launcher: Launcher,
+        /// 标准输出的校验规则
+        #[serde(default)]
+        std_out_validator: Option<OutValidator>,
+        /// 标准错误输出的校验规则
+        #[serde(default)]
+        std_err_validator: Option<OutValidator>,
+        /// 输出文件的校验规则，键为输出文件路径
+        #[serde(default)]
+        validators: HashMap<String, OutValidator>,
     },
     /// 输出收集
     CollectedOut {
@@ -59,12 +71,22 @@ pub enum TaskBody {
         to: CollectTo,
         /// 如果收集不到是否报错（true 时不报错）
         optional: bool,
+        /// 收集结果的校验规则（收集器与输出插槽上定义的）
+        #[serde(default)]
+        validators: Vec<OutValidator>,
     },
     /// 执行脚本
     ExecuteScript {
-        /// 脚本信息
-        #[serde(flatten)]
-        script_info: ScriptInfo,
+        /// 脚本类型
+        kind: ScriptKind,
+        /// 脚本来源
+        origin: ScriptOriginKind,
+        /// 文件信息列表
+        files: Vec<FileInfo>,
+        /// 输出文件的校验规则，键为输出文件路径
+        validators: HashMap<String, OutValidator>,
+        /// 计算资源配置
+        requirements: Option<Requirements>,
     },
     /// 文件传输
     FileUpload {
@@ -208,6 +230,33 @@ impl From<SoftwareSpec> for FacilityKind {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+/// 并行启动器
+pub enum Launcher {
+    /// 直接执行
+    None,
+    /// mpirun
+    #[default]
+    Mpirun,
+    /// srun
+    Srun,
+    /// mpiexec
+    Mpiexec,
+    /// 自定义启动命令模板
+    Custom(String),
+}
+impl From<RepoLauncher> for Launcher {
+    fn from(l: RepoLauncher) -> Self {
+        match l {
+            RepoLauncher::None => Launcher::None,
+            RepoLauncher::Mpirun => Launcher::Mpirun,
+            RepoLauncher::Srun => Launcher::Srun,
+            RepoLauncher::Mpiexec => Launcher::Mpiexec,
+            RepoLauncher::Custom(template) => Launcher::Custom(template),
+        }
+    }
+}
+
 /// 任务结果
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TaskResult {
@@ -236,6 +285,23 @@ pub enum TaskResultStatus {
     Deleted,
 }
 
+/// 任务失败种类
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum TaskFailureKind {
+    /// 计算节点故障（NODE_FAIL）
+    NodeFail,
+    /// 节点启动失败（BOOT_FAIL）
+    BootFail,
+    /// 被抢占（PREEMPTED）
+    Preempted,
+    /// 超时（TIMEOUT、DEADLINE）
+    Timeout,
+    /// 内存不足（OUT_OF_MEMORY）
+    OutOfMemory,
+    /// 程序执行出错
+    Error,
+}
+
 /// 资源使用
 #[derive(Default, Deserialize, Serialize, Clone, Debug)]
 pub struct TaskUsedResource {
@@ -257,4 +323,7 @@ pub struct TaskUsedResource {
     pub start_time: i64,
     /// 结束时间
     pub end_time: i64,
+    /// 调度器实际使用的分区或队列
+    #[serde(default)]
+    pub partition: Option<String>,
 }
@@ -0,0 +1,87 @@
+use alice_architecture::model::IAggregateRoot;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+impl IAggregateRoot for UserResource {}
+
+/// 用户在某集群上的资源配额与使用量
+/// 上限或告警阈值为 0 时表示不限制、不告警
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct UserResource {
+    /// id
+    pub id: Uuid,
+    /// 用户 id
+    pub user_id: Uuid,
+    /// 集群 id
+    pub cluster_id: Uuid,
+    /// 正在使用的内存，单位为字节
+    pub memory: u64,
+    /// 内存上限，单位为字节
+    pub memory_max: u64,
+    /// 内存告警阈值，单位为字节
+    pub memory_alert: u64,
+    /// 正在使用的核心数
+    pub core_number: u64,
+    /// 核心数上限
+    pub core_number_max: u64,
+    /// 核心数告警阈值
+    pub core_number_alert: u64,
+    /// 已使用的存储空间，单位为字节
+    pub storage_capacity: u64,
+    /// 存储空间上限，单位为字节
+    pub storage_capacity_max: u64,
+    /// 存储空间告警阈值，单位为字节
+    pub storage_capacity_alert: u64,
+}
+
+/// 配额不足时的处理策略
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaPolicy {
+    /// 节点排队，等待其他任务释放配额后重新提交
+    #[default]
+    Queue,
+    /// 节点直接出错
+    Reject,
+}
+
+/// 节点占用的配额，节点结束时释放
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaReservation {
+    /// 占用的核心数
+    pub cores: u64,
+    /// 占用的内存，单位为字节
+    pub memory: u64,
+}
+
+/// 配额资源种类
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaResourceKind {
+    /// 内存
+    Memory,
+    /// 核心数
+    CoreNumber,
+    /// 存储空间
+    StorageCapacity,
+}
+
+/// 配额告警，使用量越过告警阈值时发送
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaAlert {
+    /// 用户 id
+    pub user_id: Uuid,
+    /// 集群 id
+    pub cluster_id: Uuid,
+    /// 资源种类
+    pub kind: QuotaResourceKind,
+    /// 当前使用量
+    pub used: u64,
+    /// 告警阈值
+    pub alert: u64,
+    /// 上限
+    pub max: u64,
+}
@@ -1,3 +1,4 @@
+use crate::models::task::TaskFailureKind;
 use lib_co_repo::models::prelude::OutValidator;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -16,6 +17,42 @@ pub enum SchedulingStrategy {
     Prefer { clusters: Vec<Uuid> },
 }
 
+/// 节点重试策略
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// 最大尝试次数（包含第一次执行）
+    pub max_attempts: usize,
+    /// 重试间隔
+    #[serde(default)]
+    pub backoff: RetryBackoff,
+    /// 需要重试的失败种类，为空时重试所有失败
+    #[serde(default)]
+    pub retry_on: Vec<TaskFailureKind>,
+}
+
+/// 重试间隔策略
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum RetryBackoff {
+    /// 立即重试
+    #[default]
+    Immediate,
+    /// 固定间隔（s）
+    #[serde(rename_all = "camelCase")]
+    Fixed { seconds: u64 },
+    /// 指数增长的间隔（s）
+    #[serde(rename_all = "camelCase")]
+    Exponential {
+        /// 第一次重试前的间隔
+        initial_seconds: u64,
+        /// 每次重试间隔的倍数
+        multiplier: u32,
+        /// 最长间隔
+        max_seconds: Option<u64>,
+    },
+}
+
 /// 节点依赖关系
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
@@ -26,6 +63,9 @@ pub struct NodeRelation {
     pub to_id: Uuid,
     /// 节点间插槽关系
     pub slot_relations: Vec<SlotRelation>,
+    /// 条件分支，出节点为条件节点时，仅当条件结果与其相同时该关系生效
+    #[serde(default)]
+    pub branch: Option<bool>,
 }
 
 /// 插槽关系
@@ -65,6 +105,19 @@ pub struct Requirements {
     pub max_cpu_time: Option<usize>,
     /// 定时终止 (utc 0 时区 时间戳)
     pub stop_time: Option<usize>,
+    /// 分区（Slurm）或队列（PBS），未指定时使用 agent 配置的默认队列
+    pub partition: Option<String>,
+    /// 记账账户
+    pub account: Option<String>,
+    /// 服务质量（QOS）
+    pub qos: Option<String>,
+    /// 每个节点的内存（MB）
+    pub memory_per_node: Option<usize>,
+    /// 每个节点的 GPU 数
+    pub gpus_per_node: Option<usize>,
+    /// 是否独占节点
+    #[serde(default)]
+    pub exclusive: bool,
 }
 
 /// 批量策略
@@ -78,6 +131,26 @@ pub struct BatchStrategy {
     /// 批量策略种类
     #[serde(flatten)]
     pub kind: BatchStrategyKind,
+    /// 子任务失败时的处理策略
+    #[serde(default)]
+    pub failure_policy: BatchFailurePolicy,
+}
+
+/// 批量子任务失败处理策略
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(tag = "type", deny_unknown_fields)]
+pub enum BatchFailurePolicy {
+    /// 任一子任务失败，工作流即出错
+    #[default]
+    FailFast,
+    /// 最多容忍 count 个子任务失败
+    #[serde(rename_all = "camelCase")]
+    TolerateCount { count: usize },
+    /// 最多容忍 percentage% 的子任务失败
+    #[serde(rename_all = "camelCase")]
+    ToleratePercentage { percentage: f64 },
+    /// 忽略所有子任务的失败
+    IgnoreFailures,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
@@ -247,19 +320,27 @@ pub enum NodeKind {
         #[serde(flatten)]
         data: Milestone,
     },
+    /// 条件节点
+    Condition {
+        #[serde(flatten)]
+        data: Condition,
+    },
+    /// 自定义节点，提交工作流时展开为其子工作流中的节点
+    #[serde(rename_all = "camelCase")]
+    CustomNode { custom_node_id: Uuid },
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct ScriptInfo {
     /// 脚本类型
-    kind: ScriptKind,
+    pub kind: ScriptKind,
     /// 输入插槽文件与路径对应关系
-    input_path: HashMap<String, String>,
+    pub input_path: HashMap<String, String>,
     /// 输出插槽文件与路径、验证规则对应关系
-    output_path: HashMap<String, OutPathAndValidate>,
+    pub output_path: HashMap<String, OutPathAndValidate>,
     /// 脚本来源
-    origin: ScriptOriginKind,
+    pub origin: ScriptOriginKind,
 }
 
 /// 脚本输出路径和校验
@@ -278,7 +359,7 @@ pub struct OutPathAndValidate {
 pub enum ScriptOriginKind {
     /// 从 git 拉取
     Git {
-        /// 链接
+        /// 仓库链接，可以用 `#<路径>` 指定仓库中的脚本，默认为 `main.py`
         url: String,
     },
     /// 从工作流编辑
@@ -293,7 +374,40 @@ pub enum ScriptOriginKind {
 #[serde(tag = "type")]
 pub enum ScriptKind {
     /// Python 脚本
-    Python,
+    Python {
+        /// 运行脚本的 Python 环境
+        #[serde(default)]
+        environment: PythonEnvironment,
+    },
+}
+
+/// Python 环境
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(tag = "type")]
+pub enum PythonEnvironment {
+    /// 集群上 PATH 中的 python3
+    #[default]
+    System,
+    /// 集群上已有的 venv 或 conda 环境
+    Prefix {
+        /// 环境目录
+        path: String,
+    },
+    /// 由 spack 安装的 Python
+    #[serde(rename_all = "camelCase")]
+    Spack {
+        /// 软件名称
+        name: String,
+        /// 安装参数
+        argument_list: Vec<String>,
+    },
+    /// 容器镜像中的 Python
+    Singularity {
+        /// 镜像名
+        image: String,
+        /// 镜像 tag
+        tag: String,
+    },
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -313,6 +427,61 @@ pub struct Milestone {
     pub custom_message: String,
 }
 
+/// 条件节点
+/// 表达式中以输入插槽描述符作为变量名，变量值为上游输出的文本
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    /// 结果为布尔值的表达式
+    pub expression: String,
+}
+
+/// 循环结构
+/// 循环体内的节点在每次迭代时以新的节点 id 重新实例化，历次迭代的节点实例都会保留
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeLoop {
+    /// 循环体内的节点 id
+    pub node_ids: Vec<Uuid>,
+    /// 上一次迭代的输出到下一次迭代的输入的关系，出入节点均为循环体内的节点
+    #[serde(default)]
+    pub carried_relations: Vec<NodeRelation>,
+    /// 终止条件
+    pub until: LoopCondition,
+    /// 最大迭代次数
+    pub max_iterations: usize,
+    /// 历次迭代的节点 id，与 node_ids 一一对应
+    #[serde(default)]
+    pub iterations: Vec<Vec<Uuid>>,
+    /// 循环结束的原因，未结束时为空
+    #[serde(default)]
+    pub completion: Option<LoopCompletion>,
+}
+
+/// 循环终止条件
+/// 表达式中以文本输出插槽描述符作为变量名
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopCondition {
+    /// 提供文本输出的循环体节点 id
+    pub node_id: Uuid,
+    /// 文本输出插槽描述符
+    pub slot: String,
+    /// 结果为 true 时循环结束
+    #[serde(flatten)]
+    pub condition: Condition,
+}
+
+/// 循环结束的原因
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum LoopCompletion {
+    /// 满足终止条件
+    Converged,
+    /// 达到最大迭代次数
+    MaxIterationsReached,
+}
+
 impl Default for NodeKind {
     fn default() -> Self {
         Self::SoftwareUsecaseComputing {
@@ -0,0 +1,396 @@
+use crate::prelude::*;
+use alice_architecture::model::IAggregateRoot;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+impl IAggregateRoot for CustomNode {}
+
+/// 自定义节点
+/// 由工作流草稿中的一组节点打包而来，提交工作流时在原位置展开。
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomNode {
+    /// id
+    pub id: Uuid,
+    /// 名称
+    pub name: String,
+    /// 描述
+    pub description: String,
+    /// 创建者
+    pub user_id: Uuid,
+    /// 自定义节点数据
+    pub spec: CustomNodeSpec,
+    /// 创建时间
+    pub created_time: DateTime<Utc>,
+}
+
+/// 自定义节点数据
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomNodeSpec {
+    /// 节点内部的子工作流
+    pub workflow: WorkflowDraftSpec,
+    /// 对外暴露的输入插槽
+    pub input_slots: Vec<ExposedSlot>,
+    /// 对外暴露的输出插槽
+    pub output_slots: Vec<ExposedSlot>,
+}
+
+/// 对外暴露的插槽，对应子工作流中某个节点的插槽
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExposedSlot {
+    /// 自定义节点上的插槽描述符，在同类插槽中唯一
+    pub descriptor: String,
+    /// 描述
+    pub description: Option<String>,
+    /// 插槽所在节点的外部 id
+    pub node_external_id: Uuid,
+    /// 插槽所在节点上的描述符
+    pub inner_descriptor: String,
+}
+
+impl CustomNode {
+    /// 将工作流草稿中的一组节点打包为自定义节点
+    ///
+    /// 子工作流只保留两端均在组内的依赖关系与完全在组内的循环结构。
+    ///
+    /// # 参数
+    ///
+    /// * `spec` - 工作流草稿数据
+    /// * `node_ids` - 打包的节点外部 id
+    /// * `input_slots` - 对外暴露的输入插槽
+    /// * `output_slots` - 对外暴露的输出插槽
+    pub fn package(
+        spec: &WorkflowDraftSpec,
+        node_ids: &[Uuid],
+        input_slots: Vec<ExposedSlot>,
+        output_slots: Vec<ExposedSlot>,
+    ) -> anyhow::Result<CustomNodeSpec> {
+        let node_ids = node_ids.iter().copied().collect::<HashSet<_>>();
+        if node_ids.is_empty() {
+            bail!("A custom node must contain at least one node.");
+        }
+        if let Some(id) = node_ids.iter().find(|el| spec.get_node(**el).is_none()) {
+            bail!("There is no node {id} in the workflow draft.");
+        }
+        let node_drafts = spec
+            .node_drafts
+            .iter()
+            .filter(|el| node_ids.contains(&el.external_id))
+            .cloned()
+            .collect();
+        let workflow = WorkflowDraftSpec {
+            scheduling_strategy: spec.scheduling_strategy.to_owned(),
+            node_drafts,
+            node_relations: spec
+                .node_relations
+                .iter()
+                .filter(|el| node_ids.contains(&el.from_id) && node_ids.contains(&el.to_id))
+                .cloned()
+                .collect(),
+            loops: spec
+                .loops
+                .iter()
+                .filter(|el| el.node_ids.iter().all(|id| node_ids.contains(id)))
+                .cloned()
+                .collect(),
+        };
+        let custom_node = CustomNodeSpec {
+            workflow,
+            input_slots,
+            output_slots,
+        };
+        custom_node.validate()?;
+        Ok(custom_node)
+    }
+
+    /// 供工作流编辑器使用的节点草稿，插槽与对外暴露的插槽一致
+    pub fn node_draft(&self) -> anyhow::Result<NodeDraft> {
+        let workflow = &self.spec.workflow;
+        let input_slots = self
+            .spec
+            .input_slots
+            .iter()
+            .map(|el| {
+                let mut slot = workflow.exposed_input_slot(el)?.to_owned();
+                slot.descriptor = el.descriptor.to_owned();
+                slot.description = el.description.to_owned().or(slot.description);
+                Ok(slot)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let output_slots = self
+            .spec
+            .output_slots
+            .iter()
+            .map(|el| {
+                let mut slot = workflow.exposed_output_slot(el)?.to_owned();
+                slot.descriptor = el.descriptor.to_owned();
+                slot.description = el.description.to_owned().or(slot.description);
+                Ok(slot)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(NodeDraft {
+            kind: NodeKind::CustomNode {
+                custom_node_id: self.id,
+            },
+            external_id: Uuid::new_v4(),
+            name: self.name.to_owned(),
+            description: self.description.to_owned(),
+            batch_strategies: None,
+            input_slots,
+            output_slots,
+            scheduling_strategy: SchedulingStrategy::default(),
+            requirements: None,
+            retry_policy: None,
+            additional_datas: None,
+        })
+    }
+}
+
+impl CustomNodeSpec {
+    /// 检查对外暴露的插槽描述符唯一且指向子工作流中存在的插槽
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut descriptors = HashSet::new();
+        for slot in self.input_slots.iter() {
+            if !descriptors.insert(slot.descriptor.as_str()) {
+                bail!("Input slot {} is exposed more than once.", slot.descriptor);
+            }
+            self.workflow.exposed_input_slot(slot)?;
+        }
+        let mut descriptors = HashSet::new();
+        for slot in self.output_slots.iter() {
+            if !descriptors.insert(slot.descriptor.as_str()) {
+                bail!("Output slot {} is exposed more than once.", slot.descriptor);
+            }
+            self.workflow.exposed_output_slot(slot)?;
+        }
+        Ok(())
+    }
+}
+
+impl WorkflowDraftSpec {
+    /// 引用的自定义节点 id
+    pub fn custom_node_ids(&self) -> HashSet<Uuid> {
+        self.node_drafts
+            .iter()
+            .filter_map(|el| match el.kind {
+                NodeKind::CustomNode { custom_node_id } => Some(custom_node_id),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 将引用的自定义节点在原位置展开为子工作流中的节点
+    ///
+    /// 子工作流中的节点使用新的外部 id，指向自定义节点插槽的依赖关系改为指向对应的内部节点插槽，
+    /// 不含插槽关系的依赖关系连接到子工作流的全部入口或出口节点。
+    /// 子工作流中嵌套的自定义节点不在此展开，需再次调用。
+    ///
+    /// # 参数
+    ///
+    /// * `custom_nodes` - 自定义节点 id 与自定义节点
+    pub fn expand_custom_nodes(
+        &mut self,
+        custom_nodes: &HashMap<Uuid, CustomNode>,
+    ) -> anyhow::Result<()> {
+        let node_drafts = std::mem::take(&mut self.node_drafts);
+        for node_draft in node_drafts {
+            let NodeKind::CustomNode { custom_node_id } = node_draft.kind else {
+                self.node_drafts.push(node_draft);
+                continue;
+            };
+            let custom_node = custom_nodes
+                .get(&custom_node_id)
+                .ok_or(anyhow!("There is no custom node {custom_node_id}."))?;
+            self.expand_custom_node(node_draft, &custom_node.spec)?;
+        }
+        Ok(())
+    }
+
+    fn expand_custom_node(
+        &mut self,
+        node_draft: NodeDraft,
+        custom_node: &CustomNodeSpec,
+    ) -> anyhow::Result<()> {
+        let outer_id = node_draft.external_id;
+        if node_draft.batch_strategies.as_ref().is_some_and(|el| !el.is_empty()) {
+            bail!("Custom node {outer_id} can't have batch strategies.");
+        }
+        if self.loops.iter().any(|el| {
+            el.node_ids.contains(&outer_id)
+                || el.until.node_id == outer_id
+                || el
+                    .carried_relations
+                    .iter()
+                    .any(|el| el.from_id == outer_id || el.to_id == outer_id)
+        }) {
+            bail!("Custom node {outer_id} can't be used in a loop.");
+        }
+        let workflow = &custom_node.workflow;
+        let id_map = workflow
+            .node_drafts
+            .iter()
+            .map(|el| (el.external_id, Uuid::new_v4()))
+            .collect::<HashMap<_, _>>();
+        let entry_ids = workflow
+            .node_drafts
+            .iter()
+            .map(|el| el.external_id)
+            .filter(|id| workflow.node_relations.iter().all(|el| el.to_id != *id))
+            .map(|id| id_map[&id])
+            .collect::<Vec<_>>();
+        let exit_ids = workflow
+            .node_drafts
+            .iter()
+            .map(|el| el.external_id)
+            .filter(|id| workflow.node_relations.iter().all(|el| el.from_id != *id))
+            .map(|id| id_map[&id])
+            .collect::<Vec<_>>();
+
+        let mut inner_nodes = workflow.node_drafts.to_owned();
+        for inner_node in inner_nodes.iter_mut() {
+            inner_node.external_id = id_map[&inner_node.external_id];
+            inner_node.name = format!("{}/{}", node_draft.name, inner_node.name);
+        }
+        // 自定义节点上填写的输入覆盖子工作流中的输入
+        for exposed in custom_node.input_slots.iter() {
+            let Some(outer_slot) = node_draft.get_input_slot(&exposed.descriptor) else {
+                continue;
+            };
+            let has_contents = match &outer_slot.kind {
+                NodeInputSlotKind::Text { contents, .. } => contents.is_some(),
+                NodeInputSlotKind::File { contents, .. } => contents.is_some(),
+                NodeInputSlotKind::Unknown => false,
+            };
+            if !has_contents {
+                continue;
+            }
+            let inner_id = id_map[&exposed.node_external_id];
+            let inner_slot = inner_nodes
+                .iter_mut()
+                .find(|el| el.external_id == inner_id)
+                .and_then(|el| {
+                    el.input_slots.iter_mut().find(|el| el.descriptor == exposed.inner_descriptor)
+                })
+                .ok_or(anyhow!(
+                    "Input slot {} isn't exposed correctly.",
+                    exposed.descriptor
+                ))?;
+            inner_slot.kind = outer_slot.kind.to_owned();
+        }
+
+        let mut node_relations = vec![];
+        for relation in std::mem::take(&mut self.node_relations) {
+            if relation.to_id == outer_id {
+                node_relations.extend(Self::redirect_relation(
+                    relation,
+                    &custom_node.input_slots,
+                    &id_map,
+                    &entry_ids,
+                    false,
+                )?);
+            } else if relation.from_id == outer_id {
+                node_relations.extend(Self::redirect_relation(
+                    relation,
+                    &custom_node.output_slots,
+                    &id_map,
+                    &exit_ids,
+                    true,
+                )?);
+            } else {
+                node_relations.push(relation);
+            }
+        }
+        node_relations.extend(workflow.node_relations.iter().map(|el| {
+            let mut el = el.to_owned();
+            el.update_id(&id_map);
+            el
+        }));
+        self.node_relations = node_relations;
+        self.loops.extend(workflow.loops.iter().map(|el| {
+            let mut el = el.to_owned();
+            el.update_id(&id_map);
+            el
+        }));
+        self.node_drafts.extend(inner_nodes);
+        Ok(())
+    }
+
+    /// 将连接自定义节点的依赖关系改为连接内部节点，按内部节点拆分
+    ///
+    /// # 参数
+    ///
+    /// * `relation` - 依赖关系
+    /// * `exposed_slots` - 自定义节点对外暴露的插槽
+    /// * `id_map` - 子工作流节点的旧 id 与新 id
+    /// * `boundary_ids` - 子工作流的入口或出口节点
+    /// * `outgoing` - 是否为自定义节点的出依赖
+    fn redirect_relation(
+        relation: NodeRelation,
+        exposed_slots: &[ExposedSlot],
+        id_map: &HashMap<Uuid, Uuid>,
+        boundary_ids: &[Uuid],
+        outgoing: bool,
+    ) -> anyhow::Result<Vec<NodeRelation>> {
+        let with_inner_id = |inner_id: Uuid, slot_relations: Vec<SlotRelation>| {
+            let mut el = NodeRelation {
+                slot_relations,
+                ..relation.to_owned()
+            };
+            if outgoing {
+                el.from_id = inner_id;
+            } else {
+                el.to_id = inner_id;
+            }
+            el
+        };
+        if relation.slot_relations.is_empty() {
+            return Ok(boundary_ids.iter().map(|el| with_inner_id(*el, vec![])).collect());
+        }
+        let mut grouped = Vec::<(Uuid, Vec<SlotRelation>)>::new();
+        for mut slot_relation in relation.slot_relations.iter().cloned() {
+            let descriptor = if outgoing {
+                &mut slot_relation.from_slot
+            } else {
+                &mut slot_relation.to_slot
+            };
+            let exposed = exposed_slots
+                .iter()
+                .find(|el| el.descriptor == *descriptor)
+                .ok_or(anyhow!("Custom node doesn't expose slot {descriptor}."))?;
+            let inner_id = id_map[&exposed.node_external_id];
+            *descriptor = exposed.inner_descriptor.to_owned();
+            match grouped.iter_mut().find(|el| el.0 == inner_id) {
+                Some(el) => el.1.push(slot_relation),
+                None => grouped.push((inner_id, vec![slot_relation])),
+            }
+        }
+        Ok(grouped
+            .into_iter()
+            .map(|(id, slot_relations)| with_inner_id(id, slot_relations))
+            .collect())
+    }
+
+    fn exposed_input_slot(&self, exposed: &ExposedSlot) -> anyhow::Result<&NodeInputSlot> {
+        self.get_node(exposed.node_external_id)
+            .and_then(|el| el.get_input_slot(&exposed.inner_descriptor))
+            .ok_or(anyhow!(
+                "Exposed input slot {} refers to input slot {} in node {}, which doesn't exist.",
+                exposed.descriptor,
+                exposed.inner_descriptor,
+                exposed.node_external_id
+            ))
+    }
+
+    fn exposed_output_slot(&self, exposed: &ExposedSlot) -> anyhow::Result<&NodeDraftOutputSlot> {
+        self.get_node(exposed.node_external_id)
+            .and_then(|el| el.get_output_slot(&exposed.inner_descriptor))
+            .ok_or(anyhow!(
+                "Exposed output slot {} refers to output slot {} in node {}, which doesn't exist.",
+                exposed.descriptor,
+                exposed.inner_descriptor,
+                exposed.node_external_id
+            ))
+    }
+}
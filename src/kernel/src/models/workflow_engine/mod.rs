@@ -1,11 +1,15 @@
 pub mod common;
+pub mod custom_node;
 pub mod node_instance;
 pub mod workflow_draft;
 pub mod workflow_instance;
+pub mod workflow_template;
 
 pub mod prelude {
     pub use super::common::*;
+    pub use super::custom_node::*;
     pub use super::node_instance::*;
     pub use super::workflow_draft::*;
     pub use super::workflow_instance::*;
+    pub use super::workflow_template::*;
 }
@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use alice_architecture::model::IAggregateRoot;
+use chrono::{DateTime, Utc};
 use num_derive::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -19,16 +20,46 @@ pub struct NodeInstance {
     pub is_parent: bool,
     /// 父节点 id
     pub batch_parent_id: Option<Uuid>,
+    /// 批量子节点是第几个子任务，与父节点输出插槽上的输出次序对应
+    pub batch_index: Option<usize>,
     /// 属于的工作流实例 id
     pub flow_instance_id: Uuid,
     /// 节点实例状态
     pub status: NodeInstanceStatus,
     /// 集群 id
     pub cluster_id: Option<Uuid>,
+    /// 选择该集群的原因
+    pub schedule_reason: Option<String>,
     /// 节点日志
     pub log: Option<String>,
     /// 计量
     pub resource_meter: Option<TaskUsedResource>,
+    /// 历次执行记录
+    pub attempts: Vec<NodeAttempt>,
+    /// 失败后等待重试时，重新提交的时间
+    pub retry_at: Option<DateTime<Utc>>,
+    /// 占用的用户配额
+    pub quota_reservation: Option<QuotaReservation>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+/// 节点实例的一次执行记录
+pub struct NodeAttempt {
+    /// 第几次执行（从 1 开始）
+    pub attempt: usize,
+    /// 执行所在的集群 id
+    pub cluster_id: Option<Uuid>,
+    /// 执行结束时的状态
+    pub status: NodeInstanceStatus,
+    /// 失败种类
+    pub failure_kind: Option<TaskFailureKind>,
+    /// 执行日志
+    pub log: Option<String>,
+    /// 计量
+    pub resource_meter: Option<TaskUsedResource>,
+    /// 结束时间
+    pub finished_time: DateTime<Utc>,
 }
 
 #[derive(
@@ -44,6 +75,8 @@ pub enum NodeInstanceKind {
     /// 脚本
     Script,
     Milestone,
+    /// 条件
+    Condition,
 }
 
 #[derive(FromPrimitive, ToPrimitive, Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
@@ -83,4 +116,10 @@ pub enum NodeInstanceStatus {
     /// # 正在恢复
     /// 作业实例的处理过程正在恢复
     Recovering,
+    /// # 已跳过
+    /// 作业实例所在的条件分支未被选中，不会执行
+    Skipped,
+    /// # 排队中
+    /// 用户在集群上的配额不足，等待其他作业释放配额后重新提交
+    Queued,
 }
@@ -31,6 +31,9 @@ pub struct WorkflowDraftSpec {
     pub node_drafts: Vec<NodeDraft>,
     /// 节点草稿关系列表
     pub node_relations: Vec<NodeRelation>,
+    /// 循环结构列表
+    #[serde(default)]
+    pub loops: Vec<NodeLoop>,
 }
 
 /// 节点草稿
@@ -56,6 +59,9 @@ pub struct NodeDraft {
     pub scheduling_strategy: SchedulingStrategy,
     /// 资源需求覆盖（若没有则采取用例包规定的）
     pub requirements: Option<Requirements>,
+    /// 重试策略（若没有则失败时不重试）
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicy>,
     /// 其他字段
     pub additional_datas: Option<HashMap<String, Value>>,
 }
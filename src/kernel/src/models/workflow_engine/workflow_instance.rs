@@ -39,6 +39,9 @@ pub struct WorkflowInstanceSpec {
     pub node_specs: Vec<NodeSpec>,
     /// 节点实例关系列表
     pub node_relations: Vec<NodeRelation>,
+    /// 循环结构列表
+    #[serde(default)]
+    pub loops: Vec<NodeLoop>,
 }
 
 /// 根节点实例
@@ -64,6 +67,9 @@ pub struct NodeSpec {
     pub batch_strategies: Vec<BatchStrategy>,
     /// 资源需求覆盖（若没有则采取用例包规定的）
     pub requirements: Option<Requirements>,
+    /// 重试策略（若没有则失败时不重试）
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicy>,
     /// 其他字段
     pub additional_datas: Option<HashMap<String, Value>>,
 }
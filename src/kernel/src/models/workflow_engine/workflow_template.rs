@@ -0,0 +1,209 @@
+use crate::prelude::*;
+use alice_architecture::model::IAggregateRoot;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+impl IAggregateRoot for WorkflowTemplate {}
+
+/// 工作流模板
+/// 由工作流草稿发布而来，同一模板的各个版本共享系列 id。
+#[derive(Clone, Serialize, Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowTemplate {
+    /// id
+    pub id: Uuid,
+    /// 模板系列 id，即首个版本的 id
+    pub family_id: Uuid,
+    /// 版本号，从 1 开始
+    pub version: u32,
+    /// 名称
+    pub name: String,
+    /// 描述
+    pub description: String,
+    /// 图标
+    pub logo: Option<String>,
+    /// 发布者
+    pub user_id: Uuid,
+    /// 共享范围
+    pub visibility: TemplateVisibility,
+    /// 使用模板时需要填写的参数
+    pub parameters: Vec<TemplateParameter>,
+    /// 工作流草稿数据，发布者的文件与文本 id 不会随模板共享，所有输入插槽内容均为空
+    pub spec: WorkflowDraftSpec,
+    /// 发布时间
+    pub created_time: DateTime<Utc>,
+}
+
+/// 模板共享范围
+#[derive(Clone, Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum TemplateVisibility {
+    /// 仅发布者可见
+    #[default]
+    Private,
+    /// 与发布者在同一模板共享组的用户可见
+    Group {
+        /// 共享组名
+        name: String,
+    },
+    /// 所有用户可见
+    Public,
+}
+
+/// 模板参数，对应一个需要用户填写的输入插槽
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateParameter {
+    /// 参数名，在模板内唯一
+    pub name: String,
+    /// 描述
+    pub description: Option<String>,
+    /// 输入插槽所在节点的外部 id
+    pub node_external_id: Uuid,
+    /// 输入插槽描述符
+    pub descriptor: String,
+    /// 是否可以不填，不填时输入插槽内容为空
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// 使用模板时填写的参数值
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type")]
+pub enum TemplateArgument {
+    /// 文本输入，所有子任务文本 id 列表
+    Text { contents: Vec<Uuid> },
+    /// 文件输入
+    File { contents: Vec<FileInput> },
+}
+
+impl WorkflowTemplate {
+    /// 将工作流草稿发布为模板
+    ///
+    /// # 参数
+    ///
+    /// * `draft` - 工作流草稿
+    /// * `user_id` - 发布者
+    /// * `visibility` - 共享范围
+    /// * `parameters` - 模板参数
+    /// * `previous` - 同一系列的最新版本，首次发布时为 None
+    pub fn publish(
+        draft: WorkflowDraft,
+        user_id: Uuid,
+        visibility: TemplateVisibility,
+        parameters: Vec<TemplateParameter>,
+        previous: Option<&WorkflowTemplate>,
+    ) -> anyhow::Result<Self> {
+        let mut spec = draft.spec;
+        let mut names = HashSet::new();
+        for parameter in parameters.iter() {
+            if !names.insert(parameter.name.as_str()) {
+                bail!(
+                    "Template parameter {} is declared more than once.",
+                    parameter.name
+                );
+            }
+            template_slot(&mut spec, parameter)?;
+        }
+        for slot in spec.node_drafts.iter_mut().flat_map(|el| el.input_slots.iter_mut()) {
+            match &mut slot.kind {
+                NodeInputSlotKind::Text { contents, .. } => *contents = None,
+                NodeInputSlotKind::File { contents, .. } => *contents = None,
+                NodeInputSlotKind::Unknown => {}
+            }
+        }
+        let id = Uuid::new_v4();
+        Ok(Self {
+            id,
+            family_id: previous.map(|el| el.family_id).unwrap_or(id),
+            version: previous.map(|el| el.version + 1).unwrap_or(1),
+            name: draft.name,
+            description: draft.description,
+            logo: draft.logo,
+            user_id,
+            visibility,
+            parameters,
+            spec,
+            created_time: Utc::now(),
+        })
+    }
+
+    /// 用户能否看到模板
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `groups` - 用户所在的模板共享组
+    pub fn visible_to(&self, user_id: Uuid, groups: &[String]) -> bool {
+        self.user_id == user_id
+            || match &self.visibility {
+                TemplateVisibility::Private => false,
+                TemplateVisibility::Group { name } => groups.contains(name),
+                TemplateVisibility::Public => true,
+            }
+    }
+
+    /// 以参数值填充模板，生成新的工作流草稿
+    ///
+    /// # 参数
+    ///
+    /// * `name` - 草稿名称，为空时使用模板名称
+    /// * `arguments` - 参数名与参数值
+    pub fn instantiate(
+        &self,
+        name: Option<String>,
+        mut arguments: HashMap<String, TemplateArgument>,
+    ) -> anyhow::Result<WorkflowDraft> {
+        let mut spec = self.spec.to_owned();
+        for parameter in self.parameters.iter() {
+            let slot = template_slot(&mut spec, parameter)?;
+            let Some(argument) = arguments.remove(&parameter.name) else {
+                if parameter.optional {
+                    continue;
+                }
+                bail!("Template parameter {} is required.", parameter.name);
+            };
+            match (&mut slot.kind, argument) {
+                (
+                    NodeInputSlotKind::Text { contents, .. },
+                    TemplateArgument::Text { contents: el },
+                ) => *contents = Some(el),
+                (
+                    NodeInputSlotKind::File { contents, .. },
+                    TemplateArgument::File { contents: el },
+                ) => *contents = Some(el),
+                _ => bail!(
+                    "Template parameter {} doesn't match the kind of its input slot.",
+                    parameter.name
+                ),
+            }
+        }
+        if let Some(name) = arguments.keys().next() {
+            bail!("There is no template parameter named {name}.");
+        }
+        Ok(WorkflowDraft {
+            id: Uuid::new_v4(),
+            name: name.unwrap_or_else(|| self.name.to_owned()),
+            description: self.description.to_owned(),
+            logo: self.logo.to_owned(),
+            spec,
+        })
+    }
+}
+
+/// 找到模板参数对应的输入插槽
+fn template_slot<'a>(
+    spec: &'a mut WorkflowDraftSpec,
+    parameter: &TemplateParameter,
+) -> anyhow::Result<&'a mut NodeInputSlot> {
+    spec.node_drafts
+        .iter_mut()
+        .find(|el| el.external_id == parameter.node_external_id)
+        .and_then(|el| el.input_slots.iter_mut().find(|el| el.descriptor == parameter.descriptor))
+        .ok_or(anyhow!(
+            "Template parameter {} refers to input slot {} in node {}, which doesn't exist.",
+            parameter.name,
+            parameter.descriptor,
+            parameter.node_external_id
+        ))
+}
@@ -1,9 +1,24 @@
 use crate::prelude::*;
 use alice_architecture::repository::IReadOnlyRepository;
 
-/// 软件黑名单列表
+/// 集群仓储
 #[async_trait]
 pub trait IClusterRepository: IReadOnlyRepository<Cluster> {
     /// 随机获取 Cluster id
     async fn get_random_cluster(&self) -> anyhow::Result<Uuid>;
+
+    /// 获取所有集群及其资源情况，集群没有上报过资源时资源为 None
+    async fn get_all_clusters_with_resource(
+        &self,
+    ) -> anyhow::Result<Vec<(Cluster, Option<ClusterResource>)>>;
+
+    /// 根据订阅的消息队列主题名称获取集群
+    async fn get_by_topic_name(&self, topic_name: &str) -> anyhow::Result<Cluster>;
+
+    /// 写入 agent 上报的集群资源使用量，集群没有资源记录时报错
+    async fn update_used_resource(
+        &self,
+        cluster_id: Uuid,
+        used: &ClusterUsedResource,
+    ) -> anyhow::Result<()>;
 }
@@ -0,0 +1,13 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+
+/// 自定义节点仓储
+#[async_trait]
+pub trait ICustomNodeRepository: IDBRepository<CustomNode> {
+    /// 获取用户创建的所有自定义节点，按创建时间倒序排列
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    async fn get_all_by_user_id(&self, user_id: Uuid) -> anyhow::Result<Vec<CustomNode>>;
+}
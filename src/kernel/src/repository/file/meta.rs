@@ -8,4 +8,13 @@ pub trait IFileMetaRepo: IDBRepository<FileMeta> {
         hash: &str,
         hash_algorithm: &HashAlgorithm,
     ) -> AnyhowResult<Option<FileMeta>>;
+    /// Get all unreferenced file metas created before `time`.
+    async fn get_all_unreferenced_before(
+        &self,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> AnyhowResult<Vec<FileMeta>>;
+    /// Summarize sizes and references of all referenced file metas.
+    async fn get_deduplication_report(&self) -> AnyhowResult<DeduplicationReport>;
+    /// Get at most `count` file metas ordered by id, whose ids are greater than `id`.
+    async fn get_all_after(&self, id: Option<Uuid>, count: u64) -> AnyhowResult<Vec<FileMeta>>;
 }
@@ -16,4 +16,8 @@ pub trait INetDiskRepo: IDBRepository<NetDisk> {
         user_id: Option<Uuid>,
     ) -> AnyhowResult<bool>;
     async fn create_root(&self) -> AnyhowResult<Uuid>;
+    /// Get all net disk files referencing the file meta.
+    async fn get_all_by_meta_id(&self, meta_id: Uuid) -> AnyhowResult<Vec<NetDisk>>;
+    /// Delete all net disk files referencing the file meta.
+    async fn delete_all_by_meta_id(&self, meta_id: Uuid) -> Anyhow;
 }
@@ -12,4 +12,23 @@ pub trait IFileStorageRepo: IDBRepository<FileStorage> {
         meta_id: Uuid,
     ) -> AnyhowResult<String>;
     async fn insert_with_custom_user_id(&self, entity: FileStorage, user_id: Uuid) -> Anyhow;
+    /// Change the storage server of a stored copy, to where `entity` is.
+    async fn change_storage_server(
+        &self,
+        from_storage_server_id: Uuid,
+        entity: FileStorage,
+    ) -> Anyhow;
+    /// Delete the record of a stored copy.
+    async fn delete_by_storage_server_id_and_meta_id(
+        &self,
+        storage_server_id: Uuid,
+        meta_id: Uuid,
+    ) -> Anyhow;
+    /// Mark whether a stored copy is corrupted.
+    async fn set_corrupted(
+        &self,
+        storage_server_id: Uuid,
+        meta_id: Uuid,
+        corrupted: bool,
+    ) -> Anyhow;
 }
@@ -14,4 +14,18 @@ pub trait IInstalledSoftwareRepository {
         software_name: &str,
         required_install_arguments: &[String],
     ) -> anyhow::Result<bool>;
+
+    /// 检查用例执行所需的软件是否已安装在指定集群上
+    ///
+    /// # 参数
+    ///
+    /// * `cluster_id` - 集群 id
+    /// * `software_name` - Spack 安装软件名称
+    /// * `required_install_arguments` - 用例执行需要的 Spack 安装软件参数
+    async fn is_software_installed_on_cluster(
+        &self,
+        cluster_id: Uuid,
+        software_name: &str,
+        required_install_arguments: &[String],
+    ) -> anyhow::Result<bool>;
 }
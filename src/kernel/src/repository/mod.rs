@@ -1,19 +1,25 @@
 pub mod cluster;
+pub mod custom_node;
 pub mod file;
 pub mod installed_software;
 pub mod node_instance;
 pub mod read_only_by_cluster;
 pub mod software_block_list;
 pub mod text_storage;
+pub mod user_resource;
 pub mod workflow_instance;
+pub mod workflow_template;
 
 pub mod prelude {
     pub use super::cluster::*;
+    pub use super::custom_node::*;
     pub use super::file::prelude::*;
     pub use super::installed_software::*;
     pub use super::node_instance::*;
     pub use super::read_only_by_cluster::*;
     pub use super::software_block_list::*;
     pub use super::text_storage::*;
+    pub use super::user_resource::*;
     pub use super::workflow_instance::*;
+    pub use super::workflow_template::*;
 }
@@ -26,4 +26,22 @@ pub trait INodeInstanceRepository: IDBRepository<NodeInstance> {
 
     /// 获取批量任务是第几个
     async fn get_nth_of_batch_tasks(&self, sub_node_id: Uuid) -> anyhow::Result<usize>;
+
+    /// 获取某用户因配额不足正在排队的节点
+    async fn get_user_queued_node_instances(
+        &self,
+        user_id: Uuid,
+    ) -> anyhow::Result<Vec<NodeInstance>>;
+
+    /// 获取等待重试且已到重新提交时间的节点
+    async fn get_all_due_retry_node_instances(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<NodeInstance>>;
+
+    /// 原子地取出并清空节点占用的配额，立即生效，重复调用时返回 None，保证配额只释放一次
+    async fn take_quota_reservation(
+        &self,
+        node_instance_id: Uuid,
+    ) -> anyhow::Result<Option<QuotaReservation>>;
 }
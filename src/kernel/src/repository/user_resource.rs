@@ -0,0 +1,44 @@
+use crate::prelude::*;
+use alice_architecture::repository::IMutableRepository;
+
+/// 用户集群资源配额仓储
+#[async_trait]
+pub trait IUserResourceRepository: IMutableRepository<UserResource> {
+    /// 获取用户在集群上的资源配额，没有配置配额时为 None
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `cluster_id` - 集群 id
+    async fn get_by_user_and_cluster(
+        &self,
+        user_id: Uuid,
+        cluster_id: Uuid,
+    ) -> anyhow::Result<Option<UserResource>>;
+
+    /// 在不超出上限的前提下原子地占用配额，立即生效，超出上限时不做修改并返回 None
+    ///
+    /// # 参数
+    ///
+    /// * `id` - 配额 id
+    /// * `reservation` - 要占用的配额
+    async fn try_reserve(
+        &self,
+        id: Uuid,
+        reservation: &QuotaReservation,
+    ) -> anyhow::Result<Option<UserResource>>;
+
+    /// 原子地释放占用的配额并累计使用的存储空间，立即生效，返回释放后的配额
+    ///
+    /// # 参数
+    ///
+    /// * `id` - 配额 id
+    /// * `reservation` - 要释放的配额
+    /// * `used_storage` - 任务使用的存储空间，单位为字节
+    async fn release_reserved(
+        &self,
+        id: Uuid,
+        reservation: &QuotaReservation,
+        used_storage: u64,
+    ) -> anyhow::Result<UserResource>;
+}
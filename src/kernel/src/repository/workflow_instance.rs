@@ -14,4 +14,9 @@ pub trait IWorkflowInstanceRepository: IDBRepository<WorkflowInstance> {
         new_id: Uuid,
         node_instance_id: Uuid,
     ) -> Anyhow;
+    /// 获取在指定时间前已结束（完成、出错或停止）的工作流实例
+    async fn get_all_finished_before(
+        &self,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<WorkflowInstance>>;
 }
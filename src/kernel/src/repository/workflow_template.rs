@@ -0,0 +1,34 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+
+/// 工作流模板仓储
+#[async_trait]
+pub trait IWorkflowTemplateRepository: IDBRepository<WorkflowTemplate> {
+    /// 搜索用户可见的模板，按发布时间倒序排列
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `groups` - 用户所在的模板共享组
+    /// * `keyword` - 名称或描述中包含的关键字，为空时不筛选
+    async fn search(
+        &self,
+        user_id: Uuid,
+        groups: Vec<String>,
+        keyword: Option<String>,
+    ) -> anyhow::Result<Vec<WorkflowTemplate>>;
+
+    /// 获取模板系列的所有版本，按版本号倒序排列
+    ///
+    /// # 参数
+    ///
+    /// * `family_id` - 模板系列 id
+    async fn get_all_by_family_id(&self, family_id: Uuid) -> anyhow::Result<Vec<WorkflowTemplate>>;
+
+    /// 获取用户所在的所有模板共享组，与计费用户组无关
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    async fn get_user_groups(&self, user_id: Uuid) -> anyhow::Result<Vec<String>>;
+}
@@ -0,0 +1,10 @@
+use crate::prelude::*;
+
+/// Apply lifecycle policies to stored files.
+#[async_trait]
+pub trait IFileLifecycleService {
+    /// Apply lifecycle policy, when `dry_run`, only report what would be deleted or moved.
+    async fn apply(&self, dry_run: bool) -> AnyhowResult<LifecycleReport>;
+    /// Pin or unpin a file, pinned files are kept by lifecycle policies.
+    async fn set_pinned(&self, meta_id: Uuid, pinned: bool) -> Anyhow;
+}
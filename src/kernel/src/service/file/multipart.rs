@@ -19,8 +19,8 @@ pub trait IMultipartService {
 
     /// Complete a part.
     ///
-    /// If the multipart is completed, it will validate hash, if passed, return merged cache file,
-    /// else return None.
+    /// Return the parts not uploaded yet. When all parts are uploaded, validate the hash of
+    /// their concatenation, the parts are kept in cache until the multipart is removed.
     async fn complete_part(&self, part: Part) -> AnyhowResult<Vec<usize>>;
 
     /// Get multipart info.
@@ -0,0 +1,13 @@
+use crate::prelude::*;
+
+/// Collect stored files that nothing references.
+///
+/// References are counted by the database whenever a net disk file, a workflow draft, a
+/// workflow template or a workflow instance is created, changed or removed.
+#[async_trait]
+pub trait IFileReferenceService {
+    /// Delete unreferenced files from all storage servers, when `dry_run`, only report them.
+    async fn collect_garbage(&self, dry_run: bool) -> AnyhowResult<GarbageCollectionReport>;
+    /// Report storage saved by deduplication.
+    async fn deduplication_report(&self) -> AnyhowResult<DeduplicationReport>;
+}
@@ -0,0 +1,8 @@
+use crate::prelude::*;
+
+/// Detect corruption of stored files.
+#[async_trait]
+pub trait IFileScrubService {
+    /// Re-hash every stored copy and compare with its file meta, and record whether it is corrupted.
+    async fn scrub(&self) -> AnyhowResult<ScrubReport>;
+}
@@ -3,6 +3,14 @@ use crate::prelude::*;
 /// Dispatch storage server operations to a certain storage server.
 #[async_trait]
 pub trait IStorageServerUploadDispatcherService {
-    /// Transport file to server, return stored file's url.
-    async fn upload(&self, meta_id: Uuid, content: &[u8]) -> AnyhowResult<ServerUrl>;
+    /// Transport file given as its multipart parts in order to server, return stored file's url.
+    ///
+    /// The stored file is read back to verify its hash, and removed if it is corrupted.
+    async fn upload(
+        &self,
+        meta_id: Uuid,
+        hash: &str,
+        hash_algorithm: &HashAlgorithm,
+        parts: Vec<FileStream>,
+    ) -> AnyhowResult<ServerUrl>;
 }
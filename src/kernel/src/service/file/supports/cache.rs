@@ -1,4 +1,18 @@
 use crate::prelude::*;
+use bytes::Bytes;
+use futures::Stream;
+use std::pin::Pin;
+
+/// Async stream of file content chunks.
+pub type ByteStream = Pin<Box<dyn Stream<Item = AnyhowResult<Bytes>> + Send + Sync>>;
+
+/// A cached file opened for streaming, with its size known in advance.
+pub struct FileStream {
+    /// Size in bytes.
+    pub size: u64,
+    /// Content chunks.
+    pub content: ByteStream,
+}
 
 /// # Local file manager Service.
 ///
@@ -7,6 +21,8 @@ use crate::prelude::*;
 pub trait ICacheService {
     async fn operate(&self, cmd: CacheOperateCommand) -> Anyhow;
     async fn read(&self, cmd: CacheReadCommand) -> AnyhowResult<Vec<u8>>;
+    /// Open a cached file as a stream, without reading it into memory.
+    async fn read_stream(&self, cmd: CacheReadCommand) -> AnyhowResult<FileStream>;
 
     // /// Create a local file.
     // async fn write(&self, cmd: CacheCommand) -> Anyhow;
@@ -21,16 +37,12 @@ pub trait ICacheService {
 }
 
 pub enum CacheOperateCommand {
-    /// Whole file upload or complete multipart.
-    WriteNormal { meta_id: Uuid, content: Vec<u8> },
     /// Complete a part of multipart.
     WritePart(Part),
     /// Remove multipart dir.
     RemoveMultipartDir { meta_id: Uuid },
-    /// Remove normal file.
-    RemoveNormal { meta_id: Uuid },
-    /// Change normal file to snapshot file.
-    ChangeNormalToSnapshot { meta_id: Uuid },
+    /// Concatenate completed multipart's parts to snapshot file, and remove multipart dir.
+    ChangeMultipartToSnapshot { meta_id: Uuid },
     /// Remove snapshot file.
     RemoveSnapshot { meta_id: Uuid },
     /// Ok if exists, else Err
@@ -38,7 +50,6 @@ pub enum CacheOperateCommand {
 }
 
 pub enum CacheReadCommand {
-    ReadNormal { meta_id: Uuid },
     ReadSnapshot { meta_id: Uuid },
     ReadPart { meta_id: Uuid, nth: usize },
 }
@@ -20,6 +20,8 @@ pub trait IMetaStorageService {
     ) -> AnyhowResult<Option<Uuid>>;
     /// Get server_url by storage_server_id and meta_id.
     async fn get_server_url(&self, storage_server_id: Uuid, meta_id: Uuid) -> AnyhowResult<String>;
+    /// Get file_metadata by meta_id.
+    async fn get_meta(&self, meta_id: Uuid) -> AnyhowResult<FileMeta>;
 }
 
 pub struct RecordFileMeta {
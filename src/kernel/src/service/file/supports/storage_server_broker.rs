@@ -7,13 +7,26 @@ use std::ops::Range;
 #[async_trait]
 pub trait IStorageServerBrokerService {
     /// Transport local file to server, return stored file's url.
+    ///
+    /// The file is given as its multipart parts in order, each part is streamed to server
+    /// without reading the whole file into memory.
     async fn upload(
         &self,
         storage_server: &StorageServer,
         meta_id: Uuid,
-        content: &[u8],
+        parts: Vec<FileStream>,
     ) -> AnyhowResult<ServerUrl>;
 
+    /// Open server file as a stream, without reading it into memory.
+    async fn read_stream(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<FileStream>;
+
+    /// Delete server file.
+    async fn delete(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow;
+
     /// Transport server file to local.
     async fn download(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow;
 
@@ -5,4 +5,12 @@ use crate::prelude::*;
 pub trait IResourcesService {
     /// Get co system default storage server.
     async fn default_file_storage_server(&self) -> AnyhowResult<StorageServer>;
+    /// Get a storage server by id.
+    async fn get_storage_server(&self, id: Uuid) -> AnyhowResult<StorageServer>;
+    /// Record the resource usage reported by the agent subscribing to `topic_name`.
+    async fn update_cluster_used_resource(
+        &self,
+        topic_name: &str,
+        used: ClusterUsedResource,
+    ) -> Anyhow;
 }
@@ -0,0 +1,30 @@
+use crate::prelude::*;
+
+/// 预算检查结果
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BudgetDecision {
+    /// 余额与预算充足
+    Allowed,
+    /// 余额或预算不足，暂停工作流，充值或调整预算后可继续
+    Paused(String),
+    /// 超出预算且预算要求拒绝运行
+    Refused(String),
+}
+
+#[async_trait]
+/// 预算服务
+pub trait IBudgetService {
+    /// 节点分发前估算任务在集群上的花费，检查用户的余额与预算
+    ///
+    /// # 参数
+    ///
+    /// * `node_instance` - 任务对应的节点实例
+    /// * `cluster_id` - 选中的集群 id
+    /// * `task` - 节点解析得到的任务，提供资源需求
+    async fn check(
+        &self,
+        node_instance: &NodeInstance,
+        cluster_id: Uuid,
+        task: &Task,
+    ) -> anyhow::Result<BudgetDecision>;
+}
@@ -0,0 +1,26 @@
+use crate::prelude::*;
+
+/// 集群选择结果
+#[derive(Clone, Debug)]
+pub struct ClusterDecision {
+    /// 选中的集群 id
+    pub cluster_id: Uuid,
+    /// 选择该集群的原因
+    pub reason: String,
+}
+
+#[async_trait]
+/// 集群选择服务
+pub trait IClusterSelectionService {
+    /// 根据调度策略、资源需求与集群资源情况为任务选择集群
+    ///
+    /// # 参数
+    ///
+    /// * `node_spec` - 节点信息，提供节点调度策略
+    /// * `task` - 节点解析得到的任务，提供资源需求与软件环境
+    async fn select_cluster(
+        &self,
+        node_spec: &NodeSpec,
+        task: &Task,
+    ) -> anyhow::Result<ClusterDecision>;
+}
@@ -0,0 +1,59 @@
+use crate::prelude::*;
+
+/// 打包自定义节点的参数
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageCustomNodeCommand {
+    /// 工作流草稿 id
+    pub draft_id: Uuid,
+    /// 打包的节点外部 id
+    pub node_ids: Vec<Uuid>,
+    /// 名称
+    pub name: String,
+    /// 描述
+    #[serde(default)]
+    pub description: String,
+    /// 对外暴露的输入插槽
+    pub input_slots: Vec<ExposedSlot>,
+    /// 对外暴露的输出插槽
+    pub output_slots: Vec<ExposedSlot>,
+}
+
+#[async_trait]
+/// 自定义节点服务
+pub trait ICustomNodeService {
+    /// 将工作流草稿中的一组节点打包为自定义节点
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 创建者
+    /// * `command` - 打包参数
+    async fn package(
+        &self,
+        user_id: Uuid,
+        command: PackageCustomNodeCommand,
+    ) -> anyhow::Result<CustomNode>;
+
+    /// 获取用户创建的所有自定义节点
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    async fn get_custom_nodes(&self, user_id: Uuid) -> anyhow::Result<Vec<CustomNode>>;
+
+    /// 获取自定义节点对应的节点草稿，供工作流编辑器放入草稿
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `id` - 自定义节点 id
+    async fn get_node_draft(&self, user_id: Uuid, id: Uuid) -> anyhow::Result<NodeDraft>;
+
+    /// 删除自定义节点，已提交的工作流不受影响
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `id` - 自定义节点 id
+    async fn remove(&self, user_id: Uuid, id: Uuid) -> anyhow::Result<()>;
+}
@@ -0,0 +1,44 @@
+use crate::prelude::*;
+
+/// 配额检查结果
+#[derive(Clone, Debug)]
+pub enum QuotaDecision {
+    /// 配额充足，已占用配额，用户没有配置配额时为 None
+    Granted(Option<QuotaReservation>),
+    /// 配额暂时不足，节点需要排队等待
+    Queued(String),
+    /// 配额不足且无法通过等待满足，或策略为拒绝
+    Rejected(String),
+}
+
+#[async_trait]
+/// 用户配额服务
+pub trait IQuotaService {
+    /// 检查用户在集群上的剩余配额能否满足任务的资源需求，满足时占用配额
+    ///
+    /// # 参数
+    ///
+    /// * `node_instance` - 任务对应的节点实例
+    /// * `cluster_id` - 选中的集群 id
+    /// * `task` - 节点解析得到的任务，提供资源需求
+    async fn acquire(
+        &self,
+        node_instance: &NodeInstance,
+        cluster_id: Uuid,
+        task: &Task,
+    ) -> anyhow::Result<QuotaDecision>;
+
+    /// 释放节点占用的配额，并累计任务使用的存储空间
+    ///
+    /// # 参数
+    ///
+    /// * `node_instance` - 已结束或下发失败的节点实例
+    /// * `reservation` - 节点占用的配额，由节点实例仓储取出，保证只释放一次
+    /// * `used_storage` - 任务使用的存储空间，单位为字节
+    async fn release(
+        &self,
+        node_instance: &NodeInstance,
+        reservation: &QuotaReservation,
+        used_storage: u64,
+    ) -> anyhow::Result<()>;
+}
@@ -34,6 +34,19 @@ pub trait IWorkflowScheduleService {
     /// 输出 成功状态
     /// 错误 数据库、调度失败
     async fn terminate_workflow(&self, id: Uuid) -> anyhow::Result<()>;
+
+    /// 重新提交执行失败或因配额不足排队的节点实例。
+    /// 输入 节点实例 id
+    /// 过程 重新生成节点 spec -> 要求相应的服务重新执行
+    /// 输出 成功状态
+    /// 错误 数据库、调度失败
+    async fn retry_node(&self, node_instance_id: Uuid) -> anyhow::Result<()>;
+
+    /// 重新提交到达重试时间的节点实例。
+    /// 过程 查询等待重试且到达重试时间的节点 -> 逐个重新提交
+    /// 输出 成功状态
+    /// 错误 数据库、任一节点重新提交失败
+    async fn retry_due_nodes(&self) -> anyhow::Result<()>;
     /// 根据节点实例 id，分批节点实例，获取分批节点实例列表。
     /// 输入 节点实例 id
     /// 过程 读取节点实例 spec -> 解析批量信息 -> 形成分批节点实例 spec 列表
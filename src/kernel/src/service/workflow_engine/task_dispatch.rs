@@ -0,0 +1,15 @@
+use crate::prelude::*;
+
+#[async_trait]
+/// 任务派发服务
+pub trait ITaskDispatchService {
+    /// 为节点解析得到的任务选择集群，检查预算与配额后下发到集群
+    ///
+    /// 预算不足时暂停工作流，配额不足时节点排队，下发失败时释放已占用的配额
+    ///
+    /// # 参数
+    ///
+    /// * `node_spec` - 节点信息，提供调度策略
+    /// * `task` - 节点解析得到的任务
+    async fn dispatch(&self, node_spec: &NodeSpec, task: &Task) -> anyhow::Result<()>;
+}
@@ -0,0 +1,82 @@
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// 发布模板的参数
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishTemplateCommand {
+    /// 工作流草稿 id
+    pub draft_id: Uuid,
+    /// 作为已有模板的新版本发布时为模板系列 id
+    pub family_id: Option<Uuid>,
+    /// 共享范围
+    pub visibility: TemplateVisibility,
+    /// 使用模板时需要填写的参数
+    pub parameters: Vec<TemplateParameter>,
+}
+
+#[async_trait]
+/// 工作流模板服务
+pub trait IWorkflowTemplateService {
+    /// 将工作流草稿发布为模板
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 发布者
+    /// * `command` - 发布参数
+    async fn publish(
+        &self,
+        user_id: Uuid,
+        command: PublishTemplateCommand,
+    ) -> anyhow::Result<WorkflowTemplate>;
+
+    /// 搜索用户可见的模板，每个系列只返回最新版本
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `keyword` - 名称或描述中包含的关键字，为空时列出所有模板
+    async fn search(
+        &self,
+        user_id: Uuid,
+        keyword: Option<String>,
+    ) -> anyhow::Result<Vec<WorkflowTemplate>>;
+
+    /// 获取模板
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `id` - 模板 id
+    async fn get_template(&self, user_id: Uuid, id: Uuid) -> anyhow::Result<WorkflowTemplate>;
+
+    /// 获取模板系列中用户可见的所有版本
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `family_id` - 模板系列 id
+    async fn get_versions(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+    ) -> anyhow::Result<Vec<WorkflowTemplate>>;
+
+    /// 以参数值填充模板，为用户创建新的工作流草稿
+    ///
+    /// # 参数
+    ///
+    /// * `user_id` - 用户 id
+    /// * `id` - 模板 id
+    /// * `name` - 草稿名称，为空时使用模板名称
+    /// * `arguments` - 参数名与参数值
+    ///
+    /// 返回新草稿的 id
+    async fn instantiate(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        name: Option<String>,
+        arguments: HashMap<String, TemplateArgument>,
+    ) -> anyhow::Result<Uuid>;
+}
@@ -0,0 +1,518 @@
+use crate::prelude::*;
+use chrono::{Duration, Utc};
+use std::collections::{HashMap, HashSet};
+
+/// Size of parts a stored copy is uploaded in when it is moved to another storage server.
+const MOVE_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+#[derive(Builder)]
+pub struct FileLifecycleService {
+    policy: LifecyclePolicy,
+    workflow_instance_repo: Arc<dyn IWorkflowInstanceRepository + Send + Sync>,
+    net_disk_repo: Arc<dyn INetDiskRepo + Send + Sync>,
+    meta_repo: Arc<dyn IFileMetaRepo + Send + Sync>,
+    storage_repo: Arc<dyn IFileStorageRepo + Send + Sync>,
+    resources_service: Arc<dyn IResourcesService + Send + Sync>,
+    storage_server_broker_service: Arc<dyn IStorageServerBrokerService + Send + Sync>,
+    snapshot_service: Arc<dyn ISnapshotService + Send + Sync>,
+}
+
+/// Workflow instances whose intermediate outputs are expired.
+#[derive(Default)]
+struct Expiring {
+    /// Ids of all nodes in the instances.
+    node_ids: HashSet<Uuid>,
+    /// How many times each file is used as node input or output in the instances.
+    file_counts: HashMap<Uuid, i64>,
+}
+
+#[async_trait]
+impl IFileLifecycleService for FileLifecycleService {
+    async fn apply(&self, dry_run: bool) -> AnyhowResult<LifecycleReport> {
+        let (actions, expiring) = self.plan().await?;
+        let mut failures = vec![];
+        if !dry_run {
+            for action in actions.iter() {
+                if let Err(e) = self.apply_action(action, &expiring).await {
+                    failures.push(LifecycleFailure {
+                        action: action.to_owned(),
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(LifecycleReport {
+            dry_run,
+            actions,
+            failures,
+        })
+    }
+
+    async fn set_pinned(&self, meta_id: Uuid, pinned: bool) -> Anyhow {
+        let mut meta = self.meta_repo.get_by_id(&meta_id.to_string()).await?;
+        meta.pinned = pinned;
+        self.meta_repo.update(meta).await?;
+        self.meta_repo.save_changed().await?;
+        Ok(())
+    }
+}
+
+impl FileLifecycleService {
+    /// Plan actions of all policies.
+    async fn plan(&self) -> AnyhowResult<(Vec<LifecycleAction>, Expiring)> {
+        let now = Utc::now();
+        let mut actions = vec![];
+        let mut expiring = Expiring::default();
+
+        if let Some(days) = self.policy.intermediate_output_expire_days {
+            let instances = self
+                .workflow_instance_repo
+                .get_all_finished_before(now - Duration::days(days))
+                .await?;
+            expiring.node_ids = instances
+                .iter()
+                .flat_map(|el| el.spec.node_specs.iter().map(|el| el.id))
+                .collect();
+            for meta_id in instances.iter().flat_map(|el| el.spec.file_ids()) {
+                *expiring.file_counts.entry(meta_id).or_default() += 1;
+            }
+            let output_files =
+                instances.iter().flat_map(|el| el.spec.output_files()).collect::<Vec<_>>();
+            // A flash uploaded output might be an intermediate output and a result at the same time.
+            let results = output_files
+                .iter()
+                .filter(|el| !el.is_intermediate)
+                .map(|el| el.meta_id)
+                .collect::<HashSet<_>>();
+            let mut planned = HashSet::new();
+            for output_file in output_files.iter().filter(|el| el.is_intermediate) {
+                let meta_id = output_file.meta_id;
+                if results.contains(&meta_id) || !planned.insert(meta_id) {
+                    continue;
+                }
+                let storages = self.storage_repo.get_all_by_meta_id(meta_id).await?;
+                if storages.is_empty() || !self.is_expirable(meta_id, &expiring).await? {
+                    continue;
+                }
+                actions.extend(storages.into_iter().map(|el| LifecycleAction::Delete {
+                    meta_id,
+                    storage_server_id: el.storage_server_id,
+                }));
+            }
+        }
+
+        if let Some(cold_storage) = &self.policy.cold_storage {
+            let instances = self
+                .workflow_instance_repo
+                .get_all_finished_before(now - Duration::days(cold_storage.after_days))
+                .await?;
+            let mut planned = HashSet::new();
+            for output_file in instances
+                .iter()
+                .flat_map(|el| el.spec.output_files())
+                .filter(|el| !el.is_intermediate)
+            {
+                let meta_id = output_file.meta_id;
+                if !planned.insert(meta_id) {
+                    continue;
+                }
+                let storages = self
+                    .storage_repo
+                    .get_all_by_meta_id(meta_id)
+                    .await?
+                    .into_iter()
+                    .filter(|el| el.storage_server_id != cold_storage.storage_server_id)
+                    .collect::<Vec<_>>();
+                if storages.is_empty() || self.is_pinned(meta_id).await? {
+                    continue;
+                }
+                actions.extend(storages.into_iter().map(|el| LifecycleAction::Move {
+                    meta_id,
+                    from_storage_server_id: el.storage_server_id,
+                    to_storage_server_id: cold_storage.storage_server_id,
+                }));
+            }
+        }
+
+        Ok((actions, expiring))
+    }
+
+    async fn is_pinned(&self, meta_id: Uuid) -> AnyhowResult<bool> {
+        Ok(self.meta_repo.get_by_id(&meta_id.to_string()).await?.pinned)
+    }
+
+    /// A file is expirable when it isn't pinned, no snapshot shares its content, and it is only
+    /// referenced by the expiring instances.
+    ///
+    /// Net disk files must all be outputs of the expiring nodes. Other holders, such as drafts
+    /// or flash uploads reusing the file, are found by a reference count exceeding the references
+    /// of the expiring instances.
+    async fn is_expirable(&self, meta_id: Uuid, expiring: &Expiring) -> AnyhowResult<bool> {
+        let meta = self.meta_repo.get_by_id(&meta_id.to_string()).await?;
+        if meta.pinned
+            || self
+                .snapshot_service
+                .satisfy_flash_upload(&meta.hash, &meta.hash_algorithm)
+                .await?
+                .is_some()
+        {
+            return Ok(false);
+        }
+        let net_disks = self.net_disk_repo.get_all_by_meta_id(meta_id).await?;
+        if !net_disks.iter().all(|el| {
+            el.meta
+                .as_ref()
+                .and_then(|el| el.node_instance_id)
+                .is_some_and(|el| expiring.node_ids.contains(&el))
+        }) {
+            return Ok(false);
+        }
+        let held = net_disks.len() as i64
+            + expiring.file_counts.get(&meta_id).copied().unwrap_or_default();
+        Ok(meta.reference_count <= held)
+    }
+
+    async fn apply_action(&self, action: &LifecycleAction, expiring: &Expiring) -> Anyhow {
+        match *action {
+            LifecycleAction::Delete {
+                meta_id,
+                storage_server_id,
+            } => {
+                // The file might be referenced again by a flash upload since planned.
+                if !self.is_expirable(meta_id, expiring).await? {
+                    bail!("File {meta_id} is referenced again.");
+                }
+                let storage_server =
+                    self.resources_service.get_storage_server(storage_server_id).await?;
+                self.storage_server_broker_service.delete(&storage_server, meta_id).await?;
+                self.storage_repo
+                    .delete_by_storage_server_id_and_meta_id(storage_server_id, meta_id)
+                    .await?;
+                let is_last_copy = self
+                    .storage_repo
+                    .get_all_by_meta_id(meta_id)
+                    .await?
+                    .iter()
+                    .all(|el| el.storage_server_id == storage_server_id);
+                if is_last_copy {
+                    // Nothing can be downloaded any more, remove the records of the file.
+                    self.net_disk_repo.delete_all_by_meta_id(meta_id).await?;
+                    self.meta_repo.delete_by_id(&meta_id.to_string(), None).await?;
+                }
+                self.storage_repo.save_changed().await?;
+            }
+            LifecycleAction::Move {
+                meta_id,
+                from_storage_server_id,
+                to_storage_server_id,
+            } => {
+                let from =
+                    self.resources_service.get_storage_server(from_storage_server_id).await?;
+                let to = self.resources_service.get_storage_server(to_storage_server_id).await?;
+                let is_copied = self
+                    .storage_repo
+                    .get_all_by_meta_id(meta_id)
+                    .await?
+                    .iter()
+                    .any(|el| el.storage_server_id == to_storage_server_id);
+                if is_copied {
+                    self.storage_repo
+                        .delete_by_storage_server_id_and_meta_id(from_storage_server_id, meta_id)
+                        .await?;
+                } else {
+                    let meta = self.meta_repo.get_by_id(&meta_id.to_string()).await?;
+                    let content =
+                        self.storage_server_broker_service.read_stream(&from, meta_id).await?;
+                    let server_url = upload_verified(
+                        self.storage_server_broker_service.as_ref(),
+                        &to,
+                        meta_id,
+                        &meta.hash,
+                        &meta.hash_algorithm,
+                        split_stream(content, MOVE_PART_SIZE),
+                    )
+                    .await?;
+                    self.storage_repo
+                        .change_storage_server(
+                            from_storage_server_id,
+                            FileStorage {
+                                storage_server_id: to_storage_server_id,
+                                meta_id,
+                                server_url: server_url.server_url(),
+                                corrupted: false,
+                            },
+                        )
+                        .await?;
+                }
+                self.storage_repo.save_changed().await?;
+                // Source copy is removed only after the record points to the new one.
+                self.storage_server_broker_service.delete(&from, meta_id).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+
+    fn output_slot(descriptor: &str, meta_id: Uuid) -> NodeSpecOutputSlot {
+        NodeSpecOutputSlot {
+            kind: NodeSpecOutputSlotKind::File {
+                origin: FileOutOrigin::UsecaseOut,
+                is_batch: false,
+                all_tasks_prepared_content_ids: vec![meta_id],
+            },
+            descriptor: descriptor.to_string(),
+            description: None,
+            optional: false,
+        }
+    }
+
+    fn net_disk(meta_id: Uuid, node_instance_id: Option<Uuid>) -> NetDisk {
+        NetDisk {
+            id: Uuid::new_v4(),
+            parent_id: None,
+            name: "out".to_string(),
+            is_dict: false,
+            kind: FileType::Text,
+            file_metadata_id: Some(meta_id),
+            meta: Some(NetDiskMeta {
+                node_instance_id,
+                ..Default::default()
+            }),
+            user_id: None,
+        }
+    }
+
+    fn file_meta(id: Uuid, reference_count: i64) -> FileMeta {
+        FileMeta {
+            id,
+            name: "out".to_string(),
+            hash: String::new(),
+            hash_algorithm: HashAlgorithm::default(),
+            size: 0,
+            pinned: false,
+            reference_count,
+        }
+    }
+
+    fn snapshot_service() -> MockSnapshotService {
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service.expect_satisfy_flash_upload().returning(|_, _| Ok(None));
+        snapshot_service
+    }
+
+    #[tokio::test]
+    async fn test_plan() {
+        let (hot, cold) = (Uuid::new_v4(), Uuid::new_v4());
+        let (node_a, node_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let (intermediate, shared, reused, result) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let workflow_instance = WorkflowInstance {
+            status: WorkflowInstanceStatus::Finished,
+            spec: WorkflowInstanceSpec {
+                node_specs: vec![
+                    NodeSpec {
+                        id: node_a,
+                        output_slots: vec![
+                            output_slot("a", intermediate),
+                            output_slot("b", shared),
+                            output_slot("d", reused),
+                        ],
+                        ..Default::default()
+                    },
+                    NodeSpec {
+                        id: node_b,
+                        output_slots: vec![output_slot("c", result)],
+                        ..Default::default()
+                    },
+                ],
+                node_relations: vec![NodeRelation {
+                    from_id: node_a,
+                    to_id: node_b,
+                    slot_relations: ["a", "b", "d"]
+                        .map(|el| SlotRelation {
+                            from_slot: el.to_string(),
+                            to_slot: el.to_string(),
+                            ..Default::default()
+                        })
+                        .to_vec(),
+                    branch: None,
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut workflow_instance_repo = MockWorkflowInstanceRepository::new();
+        workflow_instance_repo
+            .expect_get_all_finished_before()
+            .returning(move |_| Ok(vec![workflow_instance.clone()]));
+        let mut net_disk_repo = MockNetDiskRepo::new();
+        net_disk_repo.expect_get_all_by_meta_id().returning(move |meta_id| {
+            Ok(if meta_id == shared {
+                // Also uploaded by user.
+                vec![net_disk(meta_id, Some(node_a)), net_disk(meta_id, None)]
+            } else {
+                vec![net_disk(meta_id, Some(node_a))]
+            })
+        });
+        let mut meta_repo = MockFileMetaRepo::new();
+        meta_repo.expect_get_by_id().returning(move |id| {
+            let id = Uuid::parse_str(id).unwrap();
+            // Referenced by a net disk file and the output slot, the reused one is also used by a
+            // draft through flash upload.
+            Ok(file_meta(id, if id == reused { 3 } else { 2 }))
+        });
+        let mut storage_repo = MockFileStorageRepo::new();
+        storage_repo.expect_get_all_by_meta_id().returning(move |meta_id| {
+            Ok(vec![FileStorage {
+                storage_server_id: hot,
+                meta_id,
+                server_url: String::new(),
+                corrupted: false,
+            }])
+        });
+
+        let service = FileLifecycleServiceBuilder::default()
+            .policy(LifecyclePolicy {
+                intermediate_output_expire_days: Some(7),
+                cold_storage: Some(ColdStoragePolicy {
+                    storage_server_id: cold,
+                    after_days: 30,
+                }),
+            })
+            .workflow_instance_repo(Arc::new(workflow_instance_repo))
+            .net_disk_repo(Arc::new(net_disk_repo))
+            .meta_repo(Arc::new(meta_repo))
+            .storage_repo(Arc::new(storage_repo))
+            .resources_service(Arc::new(MockResourcesService::new()))
+            .storage_server_broker_service(Arc::new(MockStorageServerBrokerService::new()))
+            .snapshot_service(Arc::new(snapshot_service()))
+            .build()
+            .unwrap();
+
+        let report = service.apply(true).await.unwrap();
+        assert!(report.dry_run);
+        assert_eq!(
+            report.actions,
+            vec![
+                LifecycleAction::Delete {
+                    meta_id: intermediate,
+                    storage_server_id: hot,
+                },
+                LifecycleAction::Move {
+                    meta_id: result,
+                    from_storage_server_id: hot,
+                    to_storage_server_id: cold,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_last_copy() {
+        let storage_server_id = Uuid::new_v4();
+        let node_id = Uuid::new_v4();
+        let intermediate = Uuid::new_v4();
+        let workflow_instance = WorkflowInstance {
+            status: WorkflowInstanceStatus::Finished,
+            spec: WorkflowInstanceSpec {
+                node_specs: vec![NodeSpec {
+                    id: node_id,
+                    output_slots: vec![output_slot("a", intermediate)],
+                    ..Default::default()
+                }],
+                node_relations: vec![NodeRelation {
+                    from_id: node_id,
+                    to_id: Uuid::new_v4(),
+                    slot_relations: vec![SlotRelation {
+                        from_slot: "a".to_string(),
+                        to_slot: "a".to_string(),
+                        ..Default::default()
+                    }],
+                    branch: None,
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut workflow_instance_repo = MockWorkflowInstanceRepository::new();
+        workflow_instance_repo
+            .expect_get_all_finished_before()
+            .returning(move |_| Ok(vec![workflow_instance.clone()]));
+        let mut net_disk_repo = MockNetDiskRepo::new();
+        net_disk_repo
+            .expect_get_all_by_meta_id()
+            .returning(move |meta_id| Ok(vec![net_disk(meta_id, Some(node_id))]));
+        net_disk_repo
+            .expect_delete_all_by_meta_id()
+            .withf(move |meta_id| *meta_id == intermediate)
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut meta_repo = MockFileMetaRepo::new();
+        meta_repo
+            .expect_get_by_id()
+            .returning(|id| Ok(file_meta(Uuid::parse_str(id).unwrap(), 2)));
+        meta_repo
+            .expect_delete_by_id()
+            .withf(move |id, _| id == intermediate.to_string())
+            .times(1)
+            .returning(|_, _| Ok(true));
+        let mut storage_repo = MockFileStorageRepo::new();
+        storage_repo.expect_get_all_by_meta_id().returning(move |meta_id| {
+            Ok(vec![FileStorage {
+                storage_server_id,
+                meta_id,
+                server_url: String::new(),
+                corrupted: false,
+            }])
+        });
+        storage_repo
+            .expect_delete_by_storage_server_id_and_meta_id()
+            .times(1)
+            .returning(|_, _| Ok(()));
+        storage_repo.expect_save_changed().returning(|| Ok(true));
+        let mut resources_service = MockResourcesService::new();
+        resources_service.expect_get_storage_server().returning(|id| {
+            Ok(StorageServer {
+                id,
+                name: "storage".to_string(),
+                capacity: 0,
+                storage_type: StorageType::ObjectStorage {
+                    options: Default::default(),
+                },
+                available_zone_id: Uuid::new_v4(),
+            })
+        });
+        let mut storage_server_broker_service = MockStorageServerBrokerService::new();
+        storage_server_broker_service.expect_delete().times(1).returning(|_, _| Ok(()));
+
+        let service = FileLifecycleServiceBuilder::default()
+            .policy(LifecyclePolicy {
+                intermediate_output_expire_days: Some(7),
+                cold_storage: None,
+            })
+            .workflow_instance_repo(Arc::new(workflow_instance_repo))
+            .net_disk_repo(Arc::new(net_disk_repo))
+            .meta_repo(Arc::new(meta_repo))
+            .storage_repo(Arc::new(storage_repo))
+            .resources_service(Arc::new(resources_service))
+            .storage_server_broker_service(Arc::new(storage_server_broker_service))
+            .snapshot_service(Arc::new(snapshot_service()))
+            .build()
+            .unwrap();
+
+        let report = service.apply(false).await.unwrap();
+        assert_eq!(report.actions.len(), 1);
+        assert!(report.failures.is_empty());
+    }
+}
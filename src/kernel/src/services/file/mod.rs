@@ -1,14 +1,20 @@
+pub mod lifecycle;
 pub mod mover;
 pub mod multipart;
 pub mod realtime;
+pub mod reference;
+pub mod scrub;
 pub mod storage_server_download_dispatcher;
 pub mod storage_server_upload_dispatcher;
 pub mod supports;
 
 pub mod prelude {
+    pub use super::lifecycle::*;
     pub use super::mover::*;
     pub use super::multipart::*;
     pub use super::realtime::*;
+    pub use super::reference::*;
+    pub use super::scrub::*;
     pub use super::storage_server_download_dispatcher::*;
     pub use super::storage_server_upload_dispatcher::*;
     pub use super::supports::prelude::*;
@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use alice_architecture::GenericError;
+use futures::TryStreamExt;
 use CacheOperateCommand::*;
 use CacheReadCommand::*;
 use MultipartException::*;
@@ -105,17 +106,16 @@ impl IMultipartService for MultipartService {
             return Ok(unfinished_parts);
         }
 
-        // If all parts are uploaded, merge them and delete multipart dir.
-        let mut completed_content = vec![];
+        // If all parts are uploaded, validate hash of their concatenation. Parts are kept in
+        // cache so that they can be streamed to storage server one by one.
+        let mut hasher = hash_algorithm.hasher();
         for nth in 0..parts_len {
-            let nth_content = self.cache_service.read(ReadPart { meta_id, nth }).await?;
-            completed_content.extend(nth_content);
-        }
-        let completed_content_hash = match hash_algorithm {
-            HashAlgorithm::Blake3 => {
-                blake3::hash(completed_content.as_slice()).to_string().to_uppercase()
+            let mut nth_content = self.cache_service.read_stream(ReadPart { meta_id, nth }).await?;
+            while let Some(chunk) = nth_content.content.try_next().await? {
+                hasher.update(&chunk);
             }
-        };
+        }
+        let completed_content_hash = hasher.finalize();
         if completed_content_hash.ne(&hash) {
             bail!(Exception::Specific(DifferentHashs(
                 meta_id,
@@ -123,14 +123,6 @@ impl IMultipartService for MultipartService {
                 completed_content_hash
             )))
         }
-
-        self.cache_service
-            .operate(WriteNormal {
-                meta_id,
-                content: completed_content,
-            })
-            .await?;
-        self.cache_service.operate(RemoveMultipartDir { meta_id }).await?;
         Ok(vec![])
     }
 
@@ -0,0 +1,185 @@
+use crate::prelude::*;
+use chrono::{Duration, Utc};
+
+#[derive(Builder)]
+pub struct FileReferenceService {
+    /// Files are kept for a while after created, so that uploads not yet recorded aren't collected.
+    grace_period: Duration,
+    meta_repo: Arc<dyn IFileMetaRepo + Send + Sync>,
+    storage_repo: Arc<dyn IFileStorageRepo + Send + Sync>,
+    resources_service: Arc<dyn IResourcesService + Send + Sync>,
+    storage_server_broker_service: Arc<dyn IStorageServerBrokerService + Send + Sync>,
+    snapshot_service: Arc<dyn ISnapshotService + Send + Sync>,
+}
+
+#[async_trait]
+impl IFileReferenceService for FileReferenceService {
+    async fn collect_garbage(&self, dry_run: bool) -> AnyhowResult<GarbageCollectionReport> {
+        let metas = self
+            .meta_repo
+            .get_all_unreferenced_before(Utc::now() - self.grace_period)
+            .await?
+            .into_iter()
+            .filter(|el| !el.pinned)
+            .collect::<Vec<_>>();
+        let mut report = GarbageCollectionReport {
+            dry_run,
+            ..Default::default()
+        };
+        for meta in metas {
+            if self.is_snapshotted(&meta).await? {
+                continue;
+            }
+            if !dry_run {
+                if let Err(e) = self.collect(meta.id).await {
+                    report.failures.push(GarbageCollectionFailure {
+                        meta_id: meta.id,
+                        reason: e.to_string(),
+                    });
+                    continue;
+                }
+            }
+            report.meta_ids.push(meta.id);
+            report.freed_size += meta.size;
+        }
+        Ok(report)
+    }
+
+    async fn deduplication_report(&self) -> AnyhowResult<DeduplicationReport> {
+        self.meta_repo.get_deduplication_report().await
+    }
+}
+
+impl FileReferenceService {
+    /// Snapshots aren't counted as references, but are uploaded by flash upload of the file
+    /// with the same hash.
+    async fn is_snapshotted(&self, meta: &FileMeta) -> AnyhowResult<bool> {
+        Ok(self
+            .snapshot_service
+            .satisfy_flash_upload(&meta.hash, &meta.hash_algorithm)
+            .await?
+            .is_some())
+    }
+
+    /// Delete all copies of a file, then its meta.
+    async fn collect(&self, meta_id: Uuid) -> Anyhow {
+        // The file might be referenced again by a draft, an instance or a flash upload since listed.
+        let meta = self.meta_repo.get_by_id(&meta_id.to_string()).await?;
+        if meta.reference_count > 0 || meta.pinned || self.is_snapshotted(&meta).await? {
+            bail!("File {meta_id} is referenced again.");
+        }
+        for storage in self.storage_repo.get_all_by_meta_id(meta_id).await? {
+            let storage_server =
+                self.resources_service.get_storage_server(storage.storage_server_id).await?;
+            self.storage_server_broker_service.delete(&storage_server, meta_id).await?;
+            self.storage_repo
+                .delete_by_storage_server_id_and_meta_id(storage.storage_server_id, meta_id)
+                .await?;
+        }
+        self.meta_repo.delete_by_id(&meta_id.to_string(), None).await?;
+        self.meta_repo.save_changed().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+
+    fn file_meta(id: Uuid, size: usize, pinned: bool) -> FileMeta {
+        FileMeta {
+            id,
+            name: "file".to_string(),
+            hash: String::new(),
+            hash_algorithm: HashAlgorithm::default(),
+            size,
+            pinned,
+            reference_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collect_garbage() {
+        let storage_server_ids = [Uuid::new_v4(), Uuid::new_v4()];
+        let (unreferenced, pinned, snapshotted) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let mut meta_repo = MockFileMetaRepo::new();
+        meta_repo.expect_get_all_unreferenced_before().returning(move |_| {
+            Ok(vec![
+                file_meta(unreferenced, 3, false),
+                file_meta(pinned, 5, true),
+                FileMeta {
+                    hash: "snapshot".to_string(),
+                    ..file_meta(snapshotted, 7, false)
+                },
+            ])
+        });
+        meta_repo
+            .expect_get_by_id()
+            .returning(|id| Ok(file_meta(Uuid::parse_str(id).unwrap(), 3, false)));
+        meta_repo
+            .expect_delete_by_id()
+            .withf(move |id, _| id == unreferenced.to_string())
+            .times(1)
+            .returning(|_, _| Ok(true));
+        meta_repo.expect_save_changed().returning(|| Ok(true));
+        let mut storage_repo = MockFileStorageRepo::new();
+        storage_repo.expect_get_all_by_meta_id().returning(move |meta_id| {
+            Ok(storage_server_ids
+                .iter()
+                .map(|el| FileStorage {
+                    storage_server_id: *el,
+                    meta_id,
+                    server_url: String::new(),
+                    corrupted: false,
+                })
+                .collect())
+        });
+        storage_repo
+            .expect_delete_by_storage_server_id_and_meta_id()
+            .times(2)
+            .returning(|_, _| Ok(()));
+        let mut resources_service = MockResourcesService::new();
+        resources_service.expect_get_storage_server().returning(|id| {
+            Ok(StorageServer {
+                id,
+                name: "storage".to_string(),
+                capacity: 0,
+                storage_type: StorageType::ObjectStorage {
+                    options: Default::default(),
+                },
+                available_zone_id: Uuid::new_v4(),
+            })
+        });
+        let mut storage_server_broker_service = MockStorageServerBrokerService::new();
+        storage_server_broker_service
+            .expect_delete()
+            .withf(move |storage_server, meta_id| {
+                storage_server_ids.contains(&storage_server.id) && *meta_id == unreferenced
+            })
+            .times(2)
+            .returning(|_, _| Ok(()));
+
+        let mut snapshot_service = MockSnapshotService::new();
+        snapshot_service
+            .expect_satisfy_flash_upload()
+            .returning(|hash, _| Ok((hash == "snapshot").then(Uuid::new_v4)));
+
+        let service = FileReferenceServiceBuilder::default()
+            .grace_period(Duration::days(1))
+            .meta_repo(Arc::new(meta_repo))
+            .storage_repo(Arc::new(storage_repo))
+            .resources_service(Arc::new(resources_service))
+            .storage_server_broker_service(Arc::new(storage_server_broker_service))
+            .snapshot_service(Arc::new(snapshot_service))
+            .build()
+            .unwrap();
+
+        let report = service.collect_garbage(false).await.unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(report.meta_ids, vec![unreferenced]);
+        assert_eq!(report.freed_size, 3);
+        assert!(report.failures.is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::prelude::*;
+
+#[derive(Builder)]
+pub struct FileScrubService {
+    meta_repo: Arc<dyn IFileMetaRepo + Send + Sync>,
+    storage_repo: Arc<dyn IFileStorageRepo + Send + Sync>,
+    resources_service: Arc<dyn IResourcesService + Send + Sync>,
+    storage_server_broker_service: Arc<dyn IStorageServerBrokerService + Send + Sync>,
+}
+
+/// Count of file metas scrubbed in one page.
+const SCRUB_PAGE_SIZE: u64 = 100;
+
+#[async_trait]
+impl IFileScrubService for FileScrubService {
+    async fn scrub(&self) -> AnyhowResult<ScrubReport> {
+        let mut report = ScrubReport::default();
+        let mut last_id = None;
+        loop {
+            let metas = self.meta_repo.get_all_after(last_id, SCRUB_PAGE_SIZE).await?;
+            let Some(last) = metas.last() else {
+                break;
+            };
+            last_id = Some(last.id);
+            for meta in metas.iter() {
+                for storage in self.storage_repo.get_all_by_meta_id(meta.id).await? {
+                    let storage_server_id = storage.storage_server_id;
+                    let corrupted = match self.hash(meta, storage_server_id).await {
+                        Ok(actual_hash) if actual_hash.eq(&meta.hash) => {
+                            report.verified_count += 1;
+                            false
+                        }
+                        Ok(actual_hash) => {
+                            report.corruptions.push(FileCorruption {
+                                meta_id: meta.id,
+                                storage_server_id,
+                                expected_hash: meta.hash.to_owned(),
+                                actual_hash,
+                            });
+                            true
+                        }
+                        Err(e) => {
+                            report.failures.push(ScrubFailure {
+                                meta_id: meta.id,
+                                storage_server_id,
+                                reason: e.to_string(),
+                            });
+                            continue;
+                        }
+                    };
+                    if corrupted != storage.corrupted {
+                        self.storage_repo
+                            .set_corrupted(storage_server_id, meta.id, corrupted)
+                            .await?;
+                    }
+                }
+            }
+            self.storage_repo.save_changed().await?;
+        }
+        Ok(report)
+    }
+}
+
+impl FileScrubService {
+    async fn hash(&self, meta: &FileMeta, storage_server_id: Uuid) -> AnyhowResult<String> {
+        let storage_server = self.resources_service.get_storage_server(storage_server_id).await?;
+        let stream =
+            self.storage_server_broker_service.read_stream(&storage_server, meta.id).await?;
+        hash_stream(&meta.hash_algorithm, stream).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+    use mockall::predicate::eq;
+
+    #[tokio::test]
+    async fn test_scrub() {
+        let (healthy, corrupted, repaired) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut meta_repo = MockFileMetaRepo::new();
+        meta_repo.expect_get_all_after().returning(move |id, count| {
+            // One meta in each page.
+            assert_eq!(count, SCRUB_PAGE_SIZE);
+            let ids = [healthy, corrupted, repaired];
+            let next = match id {
+                None => Some(healthy),
+                Some(id) => {
+                    ids.iter().position(|el| *el == id).and_then(|el| ids.get(el + 1).cloned())
+                }
+            };
+            Ok(next
+                .map(|id| FileMeta {
+                    id,
+                    name: "file".to_string(),
+                    hash: blake3::hash(b"content").to_hex().to_uppercase(),
+                    hash_algorithm: HashAlgorithm::Blake3,
+                    size: 7,
+                    pinned: false,
+                    reference_count: 1,
+                })
+                .into_iter()
+                .collect())
+        });
+        let storage_server_id = Uuid::new_v4();
+        let mut storage_repo = MockFileStorageRepo::new();
+        storage_repo.expect_get_all_by_meta_id().returning(move |meta_id| {
+            Ok(vec![FileStorage {
+                storage_server_id,
+                meta_id,
+                server_url: String::new(),
+                corrupted: meta_id == repaired,
+            }])
+        });
+        storage_repo
+            .expect_set_corrupted()
+            .with(eq(storage_server_id), eq(corrupted), eq(true))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        storage_repo
+            .expect_set_corrupted()
+            .with(eq(storage_server_id), eq(repaired), eq(false))
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        storage_repo.expect_save_changed().times(3).returning(|| Ok(true));
+        let mut resources_service = MockResourcesService::new();
+        resources_service.expect_get_storage_server().returning(|id| {
+            Ok(StorageServer {
+                id,
+                name: "storage".to_string(),
+                capacity: 0,
+                storage_type: StorageType::ObjectStorage {
+                    options: Default::default(),
+                },
+                available_zone_id: Uuid::new_v4(),
+            })
+        });
+        let mut storage_server_broker_service = MockStorageServerBrokerService::new();
+        storage_server_broker_service.expect_read_stream().returning(move |_, meta_id| {
+            let content: &'static [u8] = if meta_id != corrupted {
+                b"content"
+            } else {
+                b"c0ntent"
+            };
+            Ok(FileStream {
+                size: content.len() as u64,
+                content: Box::pin(futures::stream::once(async move {
+                    Ok(bytes::Bytes::from_static(content))
+                })),
+            })
+        });
+
+        let service = FileScrubServiceBuilder::default()
+            .meta_repo(Arc::new(meta_repo))
+            .storage_repo(Arc::new(storage_repo))
+            .resources_service(Arc::new(resources_service))
+            .storage_server_broker_service(Arc::new(storage_server_broker_service))
+            .build()
+            .unwrap();
+
+        let report = service.scrub().await.unwrap();
+        assert_eq!(report.verified_count, 2);
+        assert!(report.failures.is_empty());
+        assert_eq!(
+            report.corruptions,
+            vec![FileCorruption {
+                meta_id: corrupted,
+                storage_server_id,
+                expected_hash: blake3::hash(b"content").to_hex().to_uppercase(),
+                actual_hash: blake3::hash(b"c0ntent").to_hex().to_uppercase(),
+            }]
+        );
+    }
+}
@@ -8,11 +8,23 @@ pub struct StorageServerUploadDispatcherService {
 
 #[async_trait]
 impl IStorageServerUploadDispatcherService for StorageServerUploadDispatcherService {
-    async fn upload(&self, meta_id: Uuid, content: &[u8]) -> AnyhowResult<ServerUrl> {
+    async fn upload(
+        &self,
+        meta_id: Uuid,
+        hash: &str,
+        hash_algorithm: &HashAlgorithm,
+        parts: Vec<FileStream>,
+    ) -> AnyhowResult<ServerUrl> {
         let storage_server = &self.resources_service.default_file_storage_server().await?;
 
-        self.storage_server_broker_service
-            .upload(storage_server, meta_id, content)
-            .await
+        upload_verified(
+            self.storage_server_broker_service.as_ref(),
+            storage_server,
+            meta_id,
+            hash,
+            hash_algorithm,
+            parts,
+        )
+        .await
     }
 }
@@ -1,5 +1,7 @@
 use crate::prelude::*;
+use bytes::BytesMut;
 use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
 use CacheOperateCommand::*;
 use CacheReadCommand::*;
 
@@ -13,10 +15,6 @@ impl LocalCacheService {
     fn default_base() -> String {
         "base_dir".to_string()
     }
-    fn normal_path(&self, meta_id: Uuid) -> PathBuf {
-        self.base.join(format!("normal/{meta_id}"))
-    }
-
     fn part_path(&self, meta_id: Uuid, nth: usize) -> PathBuf {
         self.base.join(format!("multipart/{meta_id}/{nth}"))
     }
@@ -33,14 +31,33 @@ async fn create_parent_and_write(path: &Path, content: &[u8]) -> Anyhow {
     tokio::fs::write(path, content).await?;
     Ok(())
 }
+/// Size of chunks read from a streaming cached file.
+const STREAM_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+pub(crate) async fn open_stream(path: &Path) -> AnyhowResult<FileStream> {
+    let file = tokio::fs::File::open(path).await?;
+    let size = file.metadata().await?.len();
+    let content = futures::stream::try_unfold(file, |mut file| async move {
+        let mut buffer = BytesMut::with_capacity(STREAM_CHUNK_SIZE);
+        while buffer.len() < STREAM_CHUNK_SIZE {
+            if file.read_buf(&mut buffer).await? == 0 {
+                break;
+            }
+        }
+        if buffer.is_empty() {
+            return Ok(None);
+        }
+        anyhow::Ok(Some((buffer.freeze(), file)))
+    });
+    Ok(FileStream {
+        size,
+        content: Box::pin(content),
+    })
+}
 #[async_trait]
 impl ICacheService for LocalCacheService {
     async fn operate(&self, cmd: CacheOperateCommand) -> Anyhow {
         match cmd {
-            WriteNormal { meta_id, content } => {
-                let path = self.normal_path(meta_id);
-                create_parent_and_write(&path, &content).await?;
-            }
             WritePart(part) => {
                 let path = self.part_path(part.meta_id, part.nth);
                 create_parent_and_write(&path, &part.content).await?;
@@ -49,12 +66,7 @@ impl ICacheService for LocalCacheService {
                 let dir = self.multipart_dir(meta_id);
                 tokio::fs::remove_dir_all(dir).await?;
             }
-            RemoveNormal { meta_id } => {
-                let path = self.normal_path(meta_id);
-                tokio::fs::remove_file(path).await?;
-            }
-            ChangeNormalToSnapshot { meta_id } => {
-                let normal_path = self.normal_path(meta_id);
+            ChangeMultipartToSnapshot { meta_id } => {
                 let snapshot_path = self.snapshot_path(meta_id);
                 tokio::fs::create_dir_all(
                     &snapshot_path
@@ -62,7 +74,14 @@ impl ICacheService for LocalCacheService {
                         .ok_or(anyhow!("path: {snapshot_path:?} doesn't has parent."))?,
                 )
                 .await?;
-                tokio::fs::rename(normal_path, snapshot_path).await?;
+                let mut snapshot = tokio::fs::File::create(&snapshot_path).await?;
+                let mut nth = 0;
+                while tokio::fs::try_exists(self.part_path(meta_id, nth)).await? {
+                    let mut part = tokio::fs::File::open(self.part_path(meta_id, nth)).await?;
+                    tokio::io::copy(&mut part, &mut snapshot).await?;
+                    nth += 1;
+                }
+                tokio::fs::remove_dir_all(self.multipart_dir(meta_id)).await?;
             }
             RemoveSnapshot { meta_id } => {
                 let path = self.snapshot_path(meta_id);
@@ -88,17 +107,22 @@ impl ICacheService for LocalCacheService {
                 let path = self.part_path(meta_id, nth);
                 tokio::fs::read(path).await?
             }
-            ReadNormal { meta_id } => {
-                let path = self.normal_path(meta_id);
-                tokio::fs::read(path).await?
-            }
         })
     }
+
+    async fn read_stream(&self, cmd: CacheReadCommand) -> AnyhowResult<FileStream> {
+        let path = match cmd {
+            ReadSnapshot { meta_id } => self.snapshot_path(meta_id),
+            ReadPart { meta_id, nth } => self.part_path(meta_id, nth),
+        };
+        open_stream(&path).await
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::TryStreamExt;
 
     fn load() -> LocalCacheService {
         LocalCacheServiceBuilder::default().build().unwrap()
@@ -129,15 +153,24 @@ mod tests {
     async fn snapshot() {
         let service = load();
         let meta_id = Uuid::new_v4();
-        service
-            .operate(WriteNormal {
-                meta_id,
-                content: b"789".to_vec(),
-            })
-            .await
-            .unwrap();
-        service.operate(ChangeNormalToSnapshot { meta_id }).await.unwrap();
+        for (nth, content) in [b"78".to_vec(), b"9".to_vec()].into_iter().enumerate() {
+            service
+                .operate(WritePart(Part {
+                    meta_id,
+                    content,
+                    nth,
+                }))
+                .await
+                .unwrap();
+        }
+        service.operate(ChangeMultipartToSnapshot { meta_id }).await.unwrap();
         service.operate(IsSnapshotExists { meta_id }).await.unwrap();
+        let content = service.read(ReadSnapshot { meta_id }).await.unwrap();
+        assert_eq!(b"789", content.as_slice());
+        let stream = service.read_stream(ReadSnapshot { meta_id }).await.unwrap();
+        assert_eq!(stream.size, 3);
+        let chunks = stream.content.try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(chunks.concat(), b"789");
         service.operate(RemoveSnapshot { meta_id }).await.unwrap();
     }
 }
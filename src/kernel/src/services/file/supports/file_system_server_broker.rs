@@ -0,0 +1,397 @@
+use super::cache::open_stream;
+use crate::prelude::*;
+use chrono::Utc;
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    io::SeekFrom,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+/// Storage server broker that stores files in a local or POSIX parallel filesystem.
+///
+/// Each storage server's files are sandboxed in its `root` directory, with the same
+/// `storage-{server id}/{meta id}` layout as object storage keys.
+#[derive(Builder)]
+pub struct FileSystemServerBrokerService {
+    cache_service: Arc<dyn ICacheService + Send + Sync>,
+}
+
+/// Join `relative` to `root`, rejecting any path that may escape `root`.
+///
+/// `relative` must only consist of normal components, and if the joined path exists, it
+/// is canonicalized to make sure no symbolic link points it outside of `root`.
+pub async fn sandboxed_path(root: &Path, relative: &str) -> AnyhowResult<PathBuf> {
+    let relative = Path::new(relative);
+    if relative.as_os_str().is_empty()
+        || relative.components().any(|el| !matches!(el, Component::Normal(_)))
+    {
+        bail!("Path {} escapes storage root.", relative.display());
+    }
+    let root = tokio::fs::canonicalize(root)
+        .await
+        .map_err(|e| anyhow!("Storage root {} is unavailable: {e}", root.display()))?;
+    let path = root.join(relative);
+    // Check the deepest existing ancestor, the rest components are normal ones.
+    let mut existing = path.as_path();
+    while !tokio::fs::try_exists(existing).await? {
+        existing = match existing.parent() {
+            Some(el) => el,
+            None => break,
+        };
+    }
+    if !tokio::fs::canonicalize(existing).await?.starts_with(&root) {
+        bail!("Path {} escapes storage root.", relative.display());
+    }
+    Ok(path)
+}
+
+impl FileSystemServerBrokerService {
+    fn options(storage_server: &StorageServer) -> AnyhowResult<&FileSystemServerOption> {
+        match &storage_server.storage_type {
+            StorageType::FileSystem { options } => Ok(options),
+            _ => bail!(
+                "Storage server {} is not a filesystem storage server.",
+                storage_server.id
+            ),
+        }
+    }
+
+    fn server_url(storage_server: &StorageServer, meta_id: Uuid) -> AnyhowResult<ServerUrl> {
+        Ok(ServerUrl {
+            bucket: Self::options(storage_server)?.root.to_owned(),
+            storage_server_id: storage_server.id,
+            meta_id,
+        })
+    }
+
+    async fn file_path(storage_server: &StorageServer, meta_id: Uuid) -> AnyhowResult<PathBuf> {
+        let server_url = Self::server_url(storage_server, meta_id)?;
+        sandboxed_path(Path::new(&server_url.bucket), &server_url.key()).await
+    }
+
+    /// HMAC-SHA256 of `{storage server id}/{meta id}/{expires}` keyed by the signing key.
+    fn url_mac(
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+        expires: i64,
+    ) -> AnyhowResult<Hmac<Sha256>> {
+        let signing_key = &Self::options(storage_server)?.signing_key;
+        if signing_key.is_empty() {
+            bail!(
+                "Storage server {} has no key to sign download urls.",
+                storage_server.id
+            );
+        }
+        let mut mac = Hmac::<Sha256>::new_from_slice(signing_key.as_bytes())?;
+        mac.update(format!("{}/{meta_id}/{expires}", storage_server.id).as_bytes());
+        Ok(mac)
+    }
+
+    /// Verify a download url generated by `get_download_url`.
+    ///
+    /// # Arguments
+    ///
+    /// * `storage_server` - Storage server that the file is stored in.
+    /// * `meta_id` - Meta id of the file.
+    /// * `expires` - Unix timestamp in seconds after which the url is invalid.
+    /// * `signature` - Hex encoded signature of the url.
+    pub fn verify_download_url(
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+        expires: i64,
+        signature: &str,
+    ) -> Anyhow {
+        if expires < Utc::now().timestamp() {
+            bail!("Download url of file {meta_id} has expired.");
+        }
+        Self::url_mac(storage_server, meta_id, expires)?
+            .verify_slice(&hex::decode(signature)?)
+            .map_err(|_| anyhow!("Download url of file {meta_id} has an invalid signature."))
+    }
+}
+
+#[async_trait]
+impl IStorageServerBrokerService for FileSystemServerBrokerService {
+    async fn upload(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+        parts: Vec<FileStream>,
+    ) -> AnyhowResult<ServerUrl> {
+        let server_url = Self::server_url(storage_server, meta_id)?;
+        let path = Self::file_path(storage_server, meta_id).await?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Write to a temporary file first, so that readers never see a partial file.
+        let uploading_path = path.with_extension("uploading");
+        let mut file = tokio::fs::File::create(&uploading_path).await?;
+        for mut part in parts {
+            while let Some(chunk) = part.content.next().await {
+                file.write_all(&chunk?).await?;
+            }
+        }
+        file.sync_all().await?;
+        tokio::fs::rename(&uploading_path, &path).await?;
+        Ok(server_url)
+    }
+
+    async fn read_stream(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<FileStream> {
+        open_stream(&Self::file_path(storage_server, meta_id).await?).await
+    }
+
+    async fn delete(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow {
+        let path = Self::file_path(storage_server, meta_id).await?;
+        match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn download(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow {
+        let mut stream = self.read_stream(storage_server, meta_id).await?;
+        let mut nth = 0;
+        while let Some(chunk) = stream.content.next().await {
+            self.cache_service
+                .operate(CacheOperateCommand::WritePart(Part {
+                    meta_id,
+                    content: chunk?.to_vec(),
+                    nth,
+                }))
+                .await?;
+            nth += 1;
+        }
+        if nth == 0 {
+            // Empty file still needs a part to be changed to snapshot.
+            self.cache_service
+                .operate(CacheOperateCommand::WritePart(Part {
+                    meta_id,
+                    content: vec![],
+                    nth,
+                }))
+                .await?;
+        }
+        self.cache_service
+            .operate(CacheOperateCommand::ChangeMultipartToSnapshot { meta_id })
+            .await
+    }
+
+    async fn get_download_url(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<String> {
+        let options = Self::options(storage_server)?;
+        let download_endpoint = &options.download_endpoint;
+        let storage_server_id = storage_server.id;
+        let expires = Utc::now().timestamp() + options.url_expire_secs;
+        let signature =
+            hex::encode(Self::url_mac(storage_server, meta_id, expires)?.finalize().into_bytes());
+        Ok(format!(
+            "{download_endpoint}/file-storage/LocalFile/{storage_server_id}/{meta_id}?expires={expires}&signature={signature}"
+        ))
+    }
+
+    async fn get_bytes(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<Vec<u8>> {
+        Ok(tokio::fs::read(Self::file_path(storage_server, meta_id).await?).await?)
+    }
+
+    async fn get_text(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<String> {
+        Ok(tokio::fs::read_to_string(Self::file_path(storage_server, meta_id).await?).await?)
+    }
+
+    async fn rangely_get_file(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+        ranges: &[Range<u64>],
+    ) -> AnyhowResult<Vec<Vec<u8>>> {
+        let path = Self::file_path(storage_server, meta_id).await?;
+        let mut file = tokio::fs::File::open(path).await?;
+        let size = file.metadata().await?.len();
+        let mut contents = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            // Ranges are inclusive as http range headers.
+            if range.start > range.end || range.start >= size {
+                bail!(
+                    "Range {}-{} is not satisfiable for size {size}.",
+                    range.start,
+                    range.end
+                );
+            }
+            let end = range.end.min(size - 1);
+            let mut buffer = vec![0; (end - range.start + 1) as usize];
+            file.seek(SeekFrom::Start(range.start)).await?;
+            file.read_exact(&mut buffer).await?;
+            contents.push(buffer);
+        }
+        Ok(contents)
+    }
+
+    async fn get_file_size(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<u64> {
+        let path = Self::file_path(storage_server, meta_id).await?;
+        Ok(tokio::fs::metadata(path).await?.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_server(root: &Path) -> StorageServer {
+        StorageServer {
+            id: Uuid::new_v4(),
+            name: "local".to_string(),
+            capacity: 0,
+            storage_type: StorageType::FileSystem {
+                options: FileSystemServerOption {
+                    root: root.to_string_lossy().to_string(),
+                    download_endpoint: "http://localhost".to_string(),
+                    signing_key: "key".to_string(),
+                    url_expire_secs: 60,
+                },
+            },
+            available_zone_id: Uuid::new_v4(),
+        }
+    }
+
+    fn part(content: &'static [u8]) -> FileStream {
+        FileStream {
+            size: content.len() as u64,
+            content: Box::pin(futures::stream::once(async move {
+                Ok(bytes::Bytes::from_static(content))
+            })),
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_and_read() {
+        let root = std::env::temp_dir().join(format!("fs-broker-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&root).await.unwrap();
+        let service = FileSystemServerBrokerServiceBuilder::default()
+            .cache_service(Arc::new(
+                LocalCacheServiceBuilder::default().build().unwrap(),
+            ))
+            .build()
+            .unwrap();
+        let storage_server = storage_server(&root);
+        let meta_id = Uuid::new_v4();
+
+        service
+            .upload(
+                &storage_server,
+                meta_id,
+                vec![part(b"0123"), part(b"456789")],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            service.get_file_size(&storage_server, meta_id).await.unwrap(),
+            10
+        );
+        assert_eq!(
+            service.get_text(&storage_server, meta_id).await.unwrap(),
+            "0123456789"
+        );
+        assert_eq!(
+            service
+                .rangely_get_file(&storage_server, meta_id, &[0..1, 8..20])
+                .await
+                .unwrap(),
+            vec![b"01".to_vec(), b"89".to_vec()]
+        );
+        service.delete(&storage_server, meta_id).await.unwrap();
+        assert!(service.get_file_size(&storage_server, meta_id).await.is_err());
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn signed_download_url() {
+        let service = FileSystemServerBrokerServiceBuilder::default()
+            .cache_service(Arc::new(
+                LocalCacheServiceBuilder::default().build().unwrap(),
+            ))
+            .build()
+            .unwrap();
+        let storage_server = storage_server(&std::env::temp_dir());
+        let meta_id = Uuid::new_v4();
+        let url = service.get_download_url(&storage_server, meta_id).await.unwrap();
+        let url = url::Url::parse(&url).unwrap();
+        let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+        let expires: i64 = query["expires"].parse().unwrap();
+        let signature = &query["signature"];
+
+        assert!(FileSystemServerBrokerService::verify_download_url(
+            &storage_server,
+            meta_id,
+            expires,
+            signature
+        )
+        .is_ok());
+        assert!(FileSystemServerBrokerService::verify_download_url(
+            &storage_server,
+            Uuid::new_v4(),
+            expires,
+            signature
+        )
+        .is_err());
+        assert!(FileSystemServerBrokerService::verify_download_url(
+            &storage_server,
+            meta_id,
+            expires + 1,
+            signature
+        )
+        .is_err());
+        let expired = Utc::now().timestamp() - 1;
+        let signature = hex::encode(
+            FileSystemServerBrokerService::url_mac(&storage_server, meta_id, expired)
+                .unwrap()
+                .finalize()
+                .into_bytes(),
+        );
+        assert!(FileSystemServerBrokerService::verify_download_url(
+            &storage_server,
+            meta_id,
+            expired,
+            &signature
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn sandbox() {
+        let root = std::env::temp_dir().join(format!("fs-broker-{}", Uuid::new_v4()));
+        tokio::fs::create_dir_all(&root).await.unwrap();
+        assert!(sandboxed_path(&root, "a/b").await.is_ok());
+        assert!(sandboxed_path(&root, "../a").await.is_err());
+        assert!(sandboxed_path(&root, "a/../../b").await.is_err());
+        assert!(sandboxed_path(&root, "/etc/passwd").await.is_err());
+        assert!(sandboxed_path(&root, "").await.is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::env::temp_dir(), root.join("link")).unwrap();
+            assert!(sandboxed_path(&root, "link/a").await.is_err());
+        }
+        tokio::fs::remove_dir_all(&root).await.unwrap();
+    }
+}
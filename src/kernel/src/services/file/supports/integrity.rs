@@ -0,0 +1,257 @@
+use crate::prelude::*;
+use futures::TryStreamExt;
+use std::task::Poll;
+
+/// Hash the whole content of a file stream.
+pub async fn hash_stream(
+    hash_algorithm: &HashAlgorithm,
+    mut stream: FileStream,
+) -> AnyhowResult<String> {
+    let mut hasher = hash_algorithm.hasher();
+    while let Some(chunk) = stream.content.try_next().await? {
+        hasher.update(&chunk);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Split a file stream into parts of `part_size` bytes, the last part may be smaller.
+///
+/// Parts share the source stream, so they must be read one after another in order, a part
+/// polled before the previous ones are exhausted fails instead of taking their bytes.
+pub fn split_stream(stream: FileStream, part_size: u64) -> Vec<FileStream> {
+    if stream.size <= part_size || part_size == 0 {
+        return vec![stream];
+    }
+    struct Source {
+        content: ByteStream,
+        leftover: Option<bytes::Bytes>,
+        next_part: usize,
+    }
+    let source = Arc::new(std::sync::Mutex::new(Source {
+        content: stream.content,
+        leftover: None,
+        next_part: 0,
+    }));
+    let part_count = stream.size.div_ceil(part_size);
+    (0..part_count as usize)
+        .map(|nth| {
+            let size = part_size.min(stream.size - nth as u64 * part_size);
+            let source = source.clone();
+            let mut remaining = size;
+            let content = futures::stream::poll_fn(move |cx| {
+                if remaining == 0 {
+                    return Poll::Ready(None);
+                }
+                let mut source = source.lock().unwrap();
+                if source.next_part != nth {
+                    return Poll::Ready(Some(Err(anyhow!(
+                        "Part {nth} is read before part {} is finished.",
+                        source.next_part
+                    ))));
+                }
+                let mut chunk = match source.leftover.take() {
+                    Some(el) => el,
+                    None => match futures::ready!(source.content.as_mut().poll_next(cx)) {
+                        Some(Ok(el)) => el,
+                        Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                        None => {
+                            return Poll::Ready(Some(Err(anyhow!(
+                                "Stream ends {remaining} bytes before the end of part {nth}."
+                            ))))
+                        }
+                    },
+                };
+                if chunk.len() as u64 > remaining {
+                    source.leftover = Some(chunk.split_off(remaining as usize));
+                }
+                remaining -= chunk.len() as u64;
+                if remaining == 0 {
+                    source.next_part += 1;
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            });
+            FileStream {
+                size,
+                content: Box::pin(content),
+            }
+        })
+        .collect()
+}
+
+/// Read a stored copy back from storage server, and make sure its hash is the expected one.
+pub async fn verify_stored_copy(
+    storage_server_broker_service: &(dyn IStorageServerBrokerService + Send + Sync),
+    storage_server: &StorageServer,
+    meta_id: Uuid,
+    hash: &str,
+    hash_algorithm: &HashAlgorithm,
+) -> Anyhow {
+    let stream = storage_server_broker_service.read_stream(storage_server, meta_id).await?;
+    let actual_hash = hash_stream(hash_algorithm, stream).await?;
+    if actual_hash.ne(hash) {
+        bail!(
+            "File {meta_id} on storage server {} has {hash_algorithm} hash {actual_hash}, but {hash} is expected.",
+            storage_server.id
+        );
+    }
+    Ok(())
+}
+
+/// Upload parts to a storage server, and make sure the stored copy has the expected hash.
+///
+/// Parts are hashed while being uploaded in order. Object storages check the transfer by
+/// themselves, so only copies on file systems are read back. Copies failed to be verified are
+/// deleted.
+pub async fn upload_verified(
+    storage_server_broker_service: &(dyn IStorageServerBrokerService + Send + Sync),
+    storage_server: &StorageServer,
+    meta_id: Uuid,
+    hash: &str,
+    hash_algorithm: &HashAlgorithm,
+    parts: Vec<FileStream>,
+) -> AnyhowResult<ServerUrl> {
+    let hasher = Arc::new(std::sync::Mutex::new(hash_algorithm.hasher()));
+    let parts = parts
+        .into_iter()
+        .map(|el| {
+            let hasher = hasher.clone();
+            FileStream {
+                size: el.size,
+                content: Box::pin(
+                    el.content.inspect_ok(move |chunk| hasher.lock().unwrap().update(chunk)),
+                ),
+            }
+        })
+        .collect();
+    let server_url = storage_server_broker_service.upload(storage_server, meta_id, parts).await?;
+    let actual_hash =
+        std::mem::replace(&mut *hasher.lock().unwrap(), hash_algorithm.hasher()).finalize();
+    let verified = if actual_hash.ne(hash) {
+        Err(anyhow!(
+            "File {meta_id} uploaded to storage server {} has {hash_algorithm} hash {actual_hash}, but {hash} is expected.",
+            storage_server.id
+        ))
+    } else if let StorageType::FileSystem { .. } = storage_server.storage_type {
+        verify_stored_copy(
+            storage_server_broker_service,
+            storage_server,
+            meta_id,
+            hash,
+            hash_algorithm,
+        )
+        .await
+    } else {
+        Ok(())
+    };
+    if let Err(e) = verified {
+        storage_server_broker_service.delete(storage_server, meta_id).await?;
+        return Err(e);
+    }
+    Ok(server_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+
+    fn stream(chunks: &[&'static [u8]]) -> FileStream {
+        FileStream {
+            size: chunks.iter().map(|el| el.len() as u64).sum(),
+            content: Box::pin(futures::stream::iter(
+                chunks.iter().map(|el| Ok(bytes::Bytes::from_static(el))).collect::<Vec<_>>(),
+            )),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_stream() {
+        let chunks: [&'static [u8]; 2] = [b"a", b"bc"];
+        assert_eq!(
+            hash_stream(&HashAlgorithm::Sha256, stream(&chunks)).await.unwrap(),
+            "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD"
+        );
+        assert_eq!(
+            hash_stream(&HashAlgorithm::Xxh3, stream(&chunks)).await.unwrap(),
+            format!("{:016X}", xxhash_rust::xxh3::xxh3_64(b"abc"))
+        );
+        assert_eq!(
+            hash_stream(&HashAlgorithm::Blake3, stream(&chunks)).await.unwrap(),
+            blake3::hash(b"abc").to_hex().to_uppercase()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_split_stream() {
+        let chunks: [&'static [u8]; 3] = [b"abcd", b"e", b"fgh"];
+        let parts = split_stream(stream(&chunks), 3);
+        assert_eq!(
+            parts.iter().map(|el| el.size).collect::<Vec<_>>(),
+            vec![3, 3, 2]
+        );
+        let mut contents = vec![];
+        for part in parts {
+            let chunks = part.content.try_collect::<Vec<_>>().await.unwrap();
+            contents.push(String::from_utf8(chunks.concat()).unwrap());
+        }
+        assert_eq!(contents, vec!["abc", "def", "gh"]);
+
+        let mut parts = split_stream(stream(&chunks), 3);
+        assert!(parts[1].content.try_next().await.is_err());
+        assert_eq!(split_stream(stream(&chunks), 8).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_upload_verified() {
+        let mut storage_server_broker_service = MockStorageServerBrokerService::new();
+        storage_server_broker_service.expect_upload().returning(
+            |storage_server, meta_id, parts| {
+                futures::executor::block_on(async {
+                    for mut part in parts {
+                        while part.content.try_next().await?.is_some() {}
+                    }
+                    Ok(ServerUrl {
+                        bucket: String::new(),
+                        storage_server_id: storage_server.id,
+                        meta_id,
+                    })
+                })
+            },
+        );
+        // Copies on object storages are not read back.
+        storage_server_broker_service.expect_read_stream().never();
+        storage_server_broker_service.expect_delete().times(1).returning(|_, _| Ok(()));
+        let storage_server = StorageServer {
+            id: Uuid::new_v4(),
+            name: "storage".to_string(),
+            capacity: 0,
+            storage_type: StorageType::ObjectStorage {
+                options: Default::default(),
+            },
+            available_zone_id: Uuid::new_v4(),
+        };
+        let hash = blake3::hash(b"abc").to_hex().to_uppercase();
+        let chunks: [&'static [u8]; 2] = [b"a", b"bc"];
+
+        upload_verified(
+            &storage_server_broker_service,
+            &storage_server,
+            Uuid::new_v4(),
+            &hash,
+            &HashAlgorithm::Blake3,
+            vec![stream(&chunks[..1]), stream(&chunks[1..])],
+        )
+        .await
+        .unwrap();
+        assert!(upload_verified(
+            &storage_server_broker_service,
+            &storage_server,
+            Uuid::new_v4(),
+            &hash,
+            &HashAlgorithm::Blake3,
+            vec![stream(&[b"abd"])],
+        )
+        .await
+        .is_err());
+    }
+}
@@ -21,11 +21,15 @@ impl IMetaStorageService for MetaStorageService {
             hash: file_meta_info.hash,
             hash_algorithm: file_meta_info.hash_algorithm,
             size: file_meta_info.size,
+            pinned: false,
+            // Referenced when it is recorded in net disk or used as node input.
+            reference_count: 0,
         };
         let file_storage = FileStorage {
             storage_server_id: file_storage_info.storage_server_id,
             meta_id,
             server_url: file_storage_info.server_url,
+            corrupted: false,
         };
 
         self.meta_repo.insert(file_meta).await?;
@@ -48,7 +52,10 @@ impl IMetaStorageService for MetaStorageService {
         hash_algorithm: &HashAlgorithm,
     ) -> AnyhowResult<Option<Uuid>> {
         if let Some(meta) = self.meta_repo.get_by_hash_and_algorithm(hash, hash_algorithm).await? {
-            return Ok(Some(meta.id));
+            // Copies might be all removed by lifecycle policies.
+            if !self.storage_repo.get_all_by_meta_id(meta.id).await?.is_empty() {
+                return Ok(Some(meta.id));
+            }
         }
         Ok(None)
     }
@@ -58,4 +65,8 @@ impl IMetaStorageService for MetaStorageService {
             .get_by_storage_server_id_and_meta_id(storage_server_id, meta_id)
             .await
     }
+
+    async fn get_meta(&self, meta_id: Uuid) -> AnyhowResult<FileMeta> {
+        self.meta_repo.get_by_id(&meta_id.to_string()).await
+    }
 }
@@ -1,13 +1,19 @@
 pub mod cache;
 pub mod content_extractor;
+pub mod file_system_server_broker;
+pub mod integrity;
 pub mod meta_storage;
 pub mod net_disk;
 pub mod snapshot;
+pub mod storage_server_broker;
 
 pub mod prelude {
     pub use super::cache::*;
     pub use super::content_extractor::*;
+    pub use super::file_system_server_broker::*;
+    pub use super::integrity::*;
     pub use super::meta_storage::*;
     pub use super::net_disk::*;
     pub use super::snapshot::*;
+    pub use super::storage_server_broker::*;
 }
@@ -45,7 +45,7 @@ impl ISnapshotService for SnapshotService {
 
     async fn create(&self, snapshot: Snapshot) -> Anyhow {
         self.cache_service
-            .operate(ChangeNormalToSnapshot {
+            .operate(ChangeMultipartToSnapshot {
                 meta_id: snapshot.meta_id,
             })
             .await?;
@@ -0,0 +1,92 @@
+use crate::prelude::*;
+use std::ops::Range;
+
+/// Route storage server operations to the broker of the storage server's type.
+#[derive(Builder)]
+pub struct StorageServerBrokerService {
+    object_storage_broker_service: Arc<dyn IStorageServerBrokerService + Send + Sync>,
+    file_system_broker_service: Arc<dyn IStorageServerBrokerService + Send + Sync>,
+}
+
+impl StorageServerBrokerService {
+    fn broker(
+        &self,
+        storage_server: &StorageServer,
+    ) -> &(dyn IStorageServerBrokerService + Send + Sync) {
+        match storage_server.storage_type {
+            StorageType::ObjectStorage { .. } => self.object_storage_broker_service.as_ref(),
+            StorageType::FileSystem { .. } => self.file_system_broker_service.as_ref(),
+        }
+    }
+}
+
+#[async_trait]
+impl IStorageServerBrokerService for StorageServerBrokerService {
+    async fn upload(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+        parts: Vec<FileStream>,
+    ) -> AnyhowResult<ServerUrl> {
+        self.broker(storage_server).upload(storage_server, meta_id, parts).await
+    }
+
+    async fn read_stream(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<FileStream> {
+        self.broker(storage_server).read_stream(storage_server, meta_id).await
+    }
+
+    async fn delete(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow {
+        self.broker(storage_server).delete(storage_server, meta_id).await
+    }
+
+    async fn download(&self, storage_server: &StorageServer, meta_id: Uuid) -> Anyhow {
+        self.broker(storage_server).download(storage_server, meta_id).await
+    }
+
+    async fn get_download_url(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<String> {
+        self.broker(storage_server).get_download_url(storage_server, meta_id).await
+    }
+
+    async fn get_bytes(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<Vec<u8>> {
+        self.broker(storage_server).get_bytes(storage_server, meta_id).await
+    }
+
+    async fn get_text(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<String> {
+        self.broker(storage_server).get_text(storage_server, meta_id).await
+    }
+
+    async fn rangely_get_file(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+        ranges: &[Range<u64>],
+    ) -> AnyhowResult<Vec<Vec<u8>>> {
+        self.broker(storage_server)
+            .rangely_get_file(storage_server, meta_id, ranges)
+            .await
+    }
+
+    async fn get_file_size(
+        &self,
+        storage_server: &StorageServer,
+        meta_id: Uuid,
+    ) -> AnyhowResult<u64> {
+        self.broker(storage_server).get_file_size(storage_server, meta_id).await
+    }
+}
@@ -349,7 +349,7 @@ impl INodeInstanceRepository for JSONRepository {
         batch_parent_id: Uuid,
     ) -> anyhow::Result<Vec<NodeInstance>> {
         let node_instances = self.node_instances.lock().await;
-        let sub_nodes = node_instances
+        let mut sub_nodes = node_instances
             .clone()
             .into_iter()
             .filter(|el| {
@@ -357,6 +357,7 @@ impl INodeInstanceRepository for JSONRepository {
                     && el.batch_parent_id.as_ref().unwrap().eq(&batch_parent_id)
             })
             .collect::<Vec<_>>();
+        sub_nodes.sort_by_key(|el| el.batch_index);
         Ok(sub_nodes)
     }
 
@@ -374,6 +375,7 @@ impl INodeInstanceRepository for JSONRepository {
             .all(|el| {
                 el.status.eq(&NodeInstanceStatus::Finished)
                     || el.status.eq(&NodeInstanceStatus::Standby)
+                    || el.status.eq(&NodeInstanceStatus::Skipped)
             }))
     }
 
@@ -406,20 +408,60 @@ impl INodeInstanceRepository for JSONRepository {
 
     async fn get_nth_of_batch_tasks(&self, sub_node_id: Uuid) -> anyhow::Result<usize> {
         let node_instances = self.node_instances.lock().await;
-        let batch_parent_id = node_instances
+        node_instances
             .iter()
             .find(|el| el.id.eq(&sub_node_id))
-            .ok_or(anyhow::anyhow!("No such sub node!"))?
-            .batch_parent_id
-            .to_owned();
-        let sub_nodes = node_instances
+            .and_then(|el| el.batch_index)
+            .ok_or(anyhow::anyhow!("No such sub node!"))
+    }
+
+    async fn get_user_queued_node_instances(
+        &self,
+        user_id: Uuid,
+    ) -> anyhow::Result<Vec<NodeInstance>> {
+        let workflow_instance_ids = self
+            .workflow_instances
+            .lock()
+            .await
             .iter()
-            .filter(|el| el.batch_parent_id.eq(&batch_parent_id))
+            .filter(|el| el.user_id.eq(&user_id))
+            .map(|el| el.id)
             .collect::<Vec<_>>();
-        Ok(sub_nodes
+        let node_instances = self.node_instances.lock().await;
+        Ok(node_instances
+            .clone()
+            .into_iter()
+            .filter(|el| {
+                workflow_instance_ids.contains(&el.flow_instance_id)
+                    && el.status.eq(&NodeInstanceStatus::Queued)
+            })
+            .collect::<Vec<_>>())
+    }
+
+    async fn get_all_due_retry_node_instances(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<NodeInstance>> {
+        let node_instances = self.node_instances.lock().await;
+        Ok(node_instances
             .iter()
-            .position(|el| el.id.eq(&sub_node_id))
-            .ok_or(anyhow::anyhow!("No such sub node!"))?)
+            .filter(|el| {
+                el.status.eq(&NodeInstanceStatus::Pending)
+                    && el.retry_at.is_some_and(|el| el <= now)
+            })
+            .cloned()
+            .collect::<Vec<_>>())
+    }
+
+    async fn take_quota_reservation(
+        &self,
+        node_instance_id: Uuid,
+    ) -> anyhow::Result<Option<QuotaReservation>> {
+        let mut node_instances = self.node_instances.lock().await;
+        Ok(node_instances
+            .iter_mut()
+            .find(|el| el.id.eq(&node_instance_id))
+            .and_then(|el| el.quota_reservation.take()))
     }
 }
 
@@ -462,6 +504,25 @@ impl IWorkflowInstanceRepository for JSONRepository {
             .ok_or(anyhow!("No such id."))? = flow_instance.to_owned();
         Ok(())
     }
+
+    async fn get_all_finished_before(
+        &self,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<WorkflowInstance>> {
+        let workflow_instances = self.workflow_instances.lock().await;
+        Ok(workflow_instances
+            .iter()
+            .filter(|el| {
+                matches!(
+                    el.status,
+                    WorkflowInstanceStatus::Finished
+                        | WorkflowInstanceStatus::Error
+                        | WorkflowInstanceStatus::Stopped
+                ) && el.last_modified_time < time
+            })
+            .cloned()
+            .collect())
+    }
 }
 
 impl JSONRepository {
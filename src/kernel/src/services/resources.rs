@@ -1,10 +1,12 @@
 use crate::prelude::*;
 use alice_architecture::IReadOnlyRepository;
+use std::str::FromStr;
 
 #[derive(Builder)]
 pub struct ResourcesService {
     default_storage_server_id: Uuid,
     storage_server_repo: Arc<dyn IReadOnlyRepository<StorageServer> + Send + Sync>,
+    cluster_repo: Arc<dyn IClusterRepository + Send + Sync>,
 }
 
 #[async_trait]
@@ -14,4 +16,19 @@ impl IResourcesService for ResourcesService {
             .get_by_id(&self.default_storage_server_id.to_string())
             .await
     }
+
+    async fn get_storage_server(&self, id: Uuid) -> AnyhowResult<StorageServer> {
+        self.storage_server_repo.get_by_id(&id.to_string()).await
+    }
+
+    async fn update_cluster_used_resource(
+        &self,
+        topic_name: &str,
+        used: ClusterUsedResource,
+    ) -> Anyhow {
+        let cluster = self.cluster_repo.get_by_topic_name(topic_name).await?;
+        self.cluster_repo
+            .update_used_resource(Uuid::from_str(&cluster.id)?, &used)
+            .await
+    }
 }
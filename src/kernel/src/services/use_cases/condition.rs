@@ -0,0 +1,214 @@
+use crate::prelude::*;
+use alice_architecture::IMessageQueueProducerTemplate;
+use std::collections::HashMap;
+
+/// 条件节点用例服务
+/// 求值条件表达式，将 `true` 或 `false` 保存到结果输出插槽后上报任务完成
+pub struct ConditionUsecaseService {
+    text_storage_repository: Arc<dyn ITextStorageRepository + Send + Sync>,
+    message_producer: Arc<dyn IMessageQueueProducerTemplate<TaskResult> + Send + Sync>,
+}
+
+impl ConditionUsecaseService {
+    pub fn new(
+        text_storage_repository: Arc<dyn ITextStorageRepository + Send + Sync>,
+        message_producer: Arc<dyn IMessageQueueProducerTemplate<TaskResult> + Send + Sync>,
+    ) -> Self {
+        Self {
+            text_storage_repository,
+            message_producer,
+        }
+    }
+
+    /// 以输入插槽的文本为变量，求值条件表达式
+    async fn evaluate(&self, node_spec: &NodeSpec) -> anyhow::Result<bool> {
+        let condition = match &node_spec.kind {
+            NodeKind::Condition { data } => data,
+            _ => anyhow::bail!("Node {} is not a condition node.", node_spec.id),
+        };
+        let mut variables = HashMap::new();
+        for input_slot in node_spec.input_slots.iter() {
+            let key = match &input_slot.kind {
+                NodeInputSlotKind::Text {
+                    contents: Some(contents),
+                    ..
+                } => contents.first(),
+                _ => None,
+            };
+            if let Some(key) = key {
+                let text = self.text_storage_repository.get_by_id(&key.to_string()).await?;
+                variables.insert(input_slot.descriptor.to_owned(), text.value);
+            }
+        }
+        let result = condition.evaluate(&variables)?;
+        let key = node_spec
+            .output_slots
+            .iter()
+            .find(|el| el.descriptor.eq(Condition::RESULT_SLOT))
+            .ok_or(anyhow::anyhow!(
+                "Condition node {} has no output slot {}.",
+                node_spec.id,
+                Condition::RESULT_SLOT
+            ))?
+            .all_tasks_text_outputs()?
+            .first()
+            .ok_or(anyhow::anyhow!(
+                "Condition node {} has no text output on slot {}.",
+                node_spec.id,
+                Condition::RESULT_SLOT
+            ))?
+            .to_owned();
+        self.text_storage_repository
+            .insert(TextStorage {
+                key: Some(key),
+                value: result.to_string(),
+            })
+            .await?;
+        self.text_storage_repository.save_changed().await?;
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl IUsecaseService for ConditionUsecaseService {
+    /// 处理用例
+    /// 输入 节点信息
+    /// 输出 Ok
+    async fn handle_usecase(&self, node_spec: NodeSpec) -> anyhow::Result<()> {
+        let task_result = match self.evaluate(&node_spec).await {
+            Ok(result) => TaskResult {
+                id: node_spec.id,
+                status: TaskResultStatus::Success,
+                message: result.to_string(),
+                used_resources: None,
+            },
+            Err(e) => TaskResult {
+                id: node_spec.id,
+                status: TaskResultStatus::Failed,
+                message: format!("Failed to evaluate condition: {e}"),
+                used_resources: None,
+            },
+        };
+        self.message_producer.send_object(&task_result, Some("node_status")).await?;
+
+        Ok(())
+    }
+
+    /// 操作条件节点任务
+    async fn operate_task(&self, operate: Operation) -> anyhow::Result<()> {
+        let task_result: TaskResult = TaskResult {
+            id: operate.task_id,
+            status: match operate.command {
+                TaskCommand::Start => TaskResultStatus::Success,
+                TaskCommand::Pause => TaskResultStatus::Paused,
+                TaskCommand::Continue => TaskResultStatus::Success,
+                TaskCommand::Delete => TaskResultStatus::Deleted,
+            },
+            message: "".to_string(),
+            used_resources: None,
+        };
+        self.message_producer.send_object(&task_result, Some("node_status")).await?;
+
+        Ok(())
+    }
+    fn get_service_type(&self) -> NodeInstanceKind {
+        NodeInstanceKind::Condition
+    }
+    async fn get_cmd(&self, _node_id: Uuid) -> anyhow::Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingProducer {
+        results: Mutex<Vec<TaskResult>>,
+    }
+
+    #[async_trait]
+    impl IMessageQueueProducerTemplate<TaskResult> for RecordingProducer {
+        async fn send_object(
+            &self,
+            content: &TaskResult,
+            _topic: Option<&str>,
+        ) -> anyhow::Result<()> {
+            self.results.lock().unwrap().push(content.to_owned());
+            Ok(())
+        }
+    }
+
+    fn node_spec(expression: &str, key: Uuid) -> NodeSpec {
+        NodeSpec {
+            kind: NodeKind::Condition {
+                data: Condition {
+                    expression: expression.to_string(),
+                },
+            },
+            input_slots: vec![NodeInputSlot {
+                kind: NodeInputSlotKind::Text {
+                    contents: Some(vec![key]),
+                    rule: TextInputSlotRule::AnyString,
+                },
+                descriptor: "energy".to_string(),
+                ..Default::default()
+            }],
+            output_slots: vec![NodeSpecOutputSlot {
+                kind: NodeSpecOutputSlotKind::Text {
+                    all_tasks_prepared_text_keys: vec![Uuid::new_v4()],
+                },
+                descriptor: Condition::RESULT_SLOT.to_string(),
+                description: None,
+                optional: true,
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// 返回上报的任务结果与保存到结果输出插槽的文本
+    async fn evaluate(expression: &str, text: &'static str) -> (TaskResult, Option<String>) {
+        let saved = Arc::new(Mutex::new(None));
+        let mut text_storage_repository = MockTextStorageRepository::new();
+        text_storage_repository.expect_get_by_id().returning(|key| {
+            Ok(TextStorage {
+                key: Some(Uuid::parse_str(key).unwrap()),
+                value: text.to_string(),
+            })
+        });
+        let saved_clone = saved.clone();
+        text_storage_repository.expect_insert().returning(move |el| {
+            *saved_clone.lock().unwrap() = Some(el.value.to_owned());
+            Ok(el)
+        });
+        text_storage_repository.expect_save_changed().returning(|| Ok(true));
+        let producer = Arc::new(RecordingProducer::default());
+        let service =
+            ConditionUsecaseService::new(Arc::new(text_storage_repository), producer.clone());
+        service.handle_usecase(node_spec(expression, Uuid::new_v4())).await.unwrap();
+        let result = producer.results.lock().unwrap().pop().unwrap();
+        let saved = saved.lock().unwrap().to_owned();
+        (result, saved)
+    }
+
+    #[tokio::test]
+    async fn test_condition_result() {
+        let (result, saved) = evaluate("energy < -1.5", " -2.25\n").await;
+        assert!(matches!(result.status, TaskResultStatus::Success));
+        assert_eq!(saved.as_deref(), Some("true"));
+
+        let (result, saved) = evaluate("energy < -1.5", "3").await;
+        assert!(matches!(result.status, TaskResultStatus::Success));
+        assert_eq!(saved.as_deref(), Some("false"));
+    }
+
+    #[tokio::test]
+    async fn test_condition_evaluation_error() {
+        let (result, saved) = evaluate("energy + 1", "3").await;
+        assert!(matches!(result.status, TaskResultStatus::Failed));
+        assert!(saved.is_none());
+    }
+}
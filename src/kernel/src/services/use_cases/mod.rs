@@ -1,9 +1,11 @@
+pub mod condition;
 pub mod milestone;
 pub mod no_action;
 pub mod script;
 pub mod software_computing;
 
 pub mod prelude {
+    pub use super::condition::*;
     pub use super::milestone::*;
     pub use super::no_action::*;
     pub use super::script::*;
@@ -1,27 +1,148 @@
 use crate::prelude::*;
+use std::collections::HashMap;
 
 /// 脚本用例解析微服务
 pub struct ScriptUsecaseService {
     /// 任务分发服务
     task_distribution_service: Arc<dyn ITaskDistributionService + Send + Sync>,
-    /// 集群仓储
-    cluster_repository: Arc<dyn IClusterRepository + Send + Sync>,
+    /// 任务派发服务
+    task_dispatch_service: Arc<dyn ITaskDispatchService + Send + Sync>,
     /// 节点实例仓储
     node_instance_repository: Arc<dyn INodeInstanceRepository + Send + Sync>,
+    /// 文本仓储
+    text_storage_repository: Arc<dyn ITextStorageRepository + Send + Sync>,
 }
 
 impl ScriptUsecaseService {
     pub fn new(
         task_distribution_service: Arc<dyn ITaskDistributionService + Send + Sync>,
-        cluster_repository: Arc<dyn IClusterRepository + Send + Sync>,
+        task_dispatch_service: Arc<dyn ITaskDispatchService + Send + Sync>,
         node_instance_repository: Arc<dyn INodeInstanceRepository + Send + Sync>,
+        text_storage_repository: Arc<dyn ITextStorageRepository + Send + Sync>,
     ) -> Self {
         Self {
             task_distribution_service,
-            cluster_repository,
+            task_dispatch_service,
             node_instance_repository,
+            text_storage_repository,
         }
     }
+
+    /// 根据脚本信息生成任务
+    ///
+    /// 输入插槽的文件与文本按 `input_path` 放置到工作目录，
+    /// 输出插槽按 `output_path` 上传为文件或收集为文本
+    async fn gen_task(
+        &self,
+        node_spec: &NodeSpec,
+        script_info: ScriptInfo,
+    ) -> anyhow::Result<Task> {
+        let mut files = vec![];
+        let mut collects = vec![];
+        for (descriptor, path) in script_info.input_path.iter() {
+            let input_slot = node_spec
+                .input_slots
+                .iter()
+                .find(|el| el.descriptor.eq(descriptor))
+                .ok_or(anyhow::anyhow!("No such input slot {descriptor}."))?;
+            match &input_slot.kind {
+                NodeInputSlotKind::File {
+                    contents, is_batch, ..
+                } => {
+                    for content in contents.iter().flatten() {
+                        files.push(FileInfo::Input {
+                            path: path.to_owned(),
+                            is_package: *is_batch,
+                            form: InFileForm::Id(content.file_metadata_id),
+                        });
+                    }
+                }
+                NodeInputSlotKind::Text { contents, .. } => {
+                    for content in contents.iter().flatten() {
+                        let text =
+                            self.text_storage_repository.get_by_id(&content.to_string()).await?;
+                        files.push(FileInfo::Input {
+                            path: path.to_owned(),
+                            is_package: false,
+                            form: InFileForm::Content(text.value),
+                        });
+                    }
+                }
+                NodeInputSlotKind::Unknown => unreachable!(),
+            }
+        }
+
+        let mut validators = HashMap::new();
+        for (descriptor, out) in script_info.output_path.iter() {
+            let output_slot = node_spec
+                .output_slots
+                .iter()
+                .find(|el| el.descriptor.eq(descriptor))
+                .ok_or(anyhow::anyhow!("No such output slot {descriptor}."))?;
+            match &output_slot.kind {
+                NodeSpecOutputSlotKind::File { is_batch, .. } => {
+                    let id = output_slot.all_tasks_file_outputs()?.first().ok_or(
+                        anyhow::anyhow!("Output slot {descriptor} has no prepared file id."),
+                    )?;
+                    files.push(FileInfo::Output {
+                        id: id.to_owned(),
+                        path: out.path.to_owned(),
+                        is_package: *is_batch,
+                        optional: output_slot.optional,
+                    });
+                }
+                NodeSpecOutputSlotKind::Text { .. } => {
+                    let id = output_slot.all_tasks_text_outputs()?.first().ok_or(
+                        anyhow::anyhow!("Output slot {descriptor} has no prepared text key."),
+                    )?;
+                    collects.push(TaskBody::CollectedOut {
+                        from: CollectFrom::FileOut {
+                            path: out.path.to_owned(),
+                        },
+                        rule: CollectRule::TopLines(usize::MAX),
+                        to: CollectTo::Text { id: id.to_owned() },
+                        optional: output_slot.optional,
+                        validators: vec![],
+                    });
+                }
+            }
+            if let Some(validator) = &out.validator {
+                validators.insert(out.path.to_owned(), validator.to_owned());
+            }
+        }
+
+        let ScriptKind::Python { environment } = &script_info.kind;
+        let facility_kind = match environment.to_owned() {
+            PythonEnvironment::Spack {
+                name,
+                argument_list,
+            } => Some(FacilityKind::Spack {
+                name,
+                argument_list,
+            }),
+            PythonEnvironment::Singularity { image, tag } => {
+                Some(FacilityKind::Singularity { image, tag })
+            }
+            PythonEnvironment::System | PythonEnvironment::Prefix { .. } => None,
+        };
+        let mut body = vec![];
+        if let Some(facility_kind) = facility_kind {
+            body.push(TaskBody::SoftwareDeployment { facility_kind });
+        }
+        body.push(TaskBody::ExecuteScript {
+            kind: script_info.kind,
+            origin: script_info.origin,
+            files,
+            validators,
+            requirements: node_spec.requirements.to_owned(),
+        });
+        body.extend(collects);
+        Ok(Task {
+            id: node_spec.id.to_owned(),
+            command: TaskCommand::Start,
+            body,
+        })
+    }
 }
 
 #[async_trait]
@@ -30,25 +151,12 @@ impl IUsecaseService for ScriptUsecaseService {
     /// 输入 节点信息
     /// 输出 Ok
     async fn handle_usecase(&self, node_spec: NodeSpec) -> anyhow::Result<()> {
-        let task = if let NodeKind::Script { script_info } = node_spec.kind {
-            let mut task = Task {
-                id: node_spec.id.to_owned(),
-                command: TaskCommand::Start,
-                body: vec![],
-            };
-            task.body.push(TaskBody::ExecuteScript { script_info });
-            task
-        } else {
+        let NodeKind::Script { script_info } = node_spec.kind.to_owned() else {
             anyhow::bail!("Unreachable node kind.");
         };
+        let task = self.gen_task(&node_spec, script_info).await?;
 
-        let cluster_id = self.cluster_repository.get_random_cluster().await?;
-        let mut node_instance =
-            self.node_instance_repository.get_by_id(&task.id.to_string()).await?;
-        node_instance.cluster_id = Some(cluster_id);
-        self.node_instance_repository.update(node_instance).await?;
-        self.node_instance_repository.save_changed().await?;
-        self.task_distribution_service.send_task(&task, cluster_id).await
+        self.task_dispatch_service.dispatch(&node_spec, &task).await
     }
 
     /// 操作软件计算任务
@@ -74,3 +182,125 @@ impl IUsecaseService for ScriptUsecaseService {
         unimplemented!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+
+    fn service() -> ScriptUsecaseService {
+        let mut text_storage_repository = MockTextStorageRepository::new();
+        text_storage_repository.expect_get_by_id().returning(|key| {
+            Ok(TextStorage {
+                key: Some(Uuid::parse_str(key).unwrap()),
+                value: "0.01".to_string(),
+            })
+        });
+        ScriptUsecaseService::new(
+            Arc::new(MockTaskDistributionService::new()),
+            Arc::new(MockTaskDispatchService::new()),
+            Arc::new(MockNodeInstanceRepository::new()),
+            Arc::new(text_storage_repository),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_gen_task() {
+        let script_info: ScriptInfo = serde_json::from_value(serde_json::json!({
+            "kind": {
+                "type": "Python",
+                "environment": { "type": "Spack", "name": "py-numpy", "argumentList": [] }
+            },
+            "inputPath": { "structure": "POSCAR", "tolerance": "tolerance.txt" },
+            "outputPath": {
+                "report": {
+                    "path": "report.json",
+                    "validator": { "validateRules": { "isEmpty": false } }
+                },
+                "energy": { "path": "energy.txt" }
+            },
+            "origin": { "type": "Git", "url": "https://example.com/scripts.git#analyse.py" }
+        }))
+        .unwrap();
+        let (file_id, report_id, energy_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let node_spec = NodeSpec {
+            kind: NodeKind::Script {
+                script_info: script_info.to_owned(),
+            },
+            input_slots: vec![
+                NodeInputSlot {
+                    kind: NodeInputSlotKind::File {
+                        contents: Some(vec![FileInput {
+                            file_metadata_id: file_id,
+                            file_metadata_name: "structure.vasp".to_string(),
+                            ..Default::default()
+                        }]),
+                        expected_file_name: None,
+                        is_batch: false,
+                    },
+                    descriptor: "structure".to_string(),
+                    ..Default::default()
+                },
+                NodeInputSlot {
+                    kind: NodeInputSlotKind::Text {
+                        contents: Some(vec![Uuid::new_v4()]),
+                        rule: TextInputSlotRule::AnyString,
+                    },
+                    descriptor: "tolerance".to_string(),
+                    ..Default::default()
+                },
+            ],
+            output_slots: vec![
+                NodeSpecOutputSlot {
+                    kind: NodeSpecOutputSlotKind::File {
+                        origin: FileOutOrigin::UsecaseOut,
+                        is_batch: false,
+                        all_tasks_prepared_content_ids: vec![report_id],
+                    },
+                    descriptor: "report".to_string(),
+                    description: None,
+                    optional: false,
+                },
+                NodeSpecOutputSlot {
+                    kind: NodeSpecOutputSlotKind::Text {
+                        all_tasks_prepared_text_keys: vec![energy_id],
+                    },
+                    descriptor: "energy".to_string(),
+                    description: None,
+                    optional: true,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let task = service().gen_task(&node_spec, script_info).await.unwrap();
+        assert_eq!(task.body.len(), 3);
+        assert!(matches!(
+            &task.body[0],
+            TaskBody::SoftwareDeployment { facility_kind: FacilityKind::Spack { name, .. } } if name == "py-numpy"
+        ));
+        let TaskBody::ExecuteScript {
+            files, validators, ..
+        } = &task.body[1]
+        else {
+            panic!("unexpected task body {:?}", task.body[1]);
+        };
+        assert!(files.iter().any(|el| matches!(
+            el,
+            FileInfo::Input { path, form: InFileForm::Id(id), .. } if path == "POSCAR" && *id == file_id
+        )));
+        assert!(files.iter().any(|el| matches!(
+            el,
+            FileInfo::Input { path, form: InFileForm::Content(text), .. } if path == "tolerance.txt" && text == "0.01"
+        )));
+        assert!(files.iter().any(|el| matches!(
+            el,
+            FileInfo::Output { path, id, .. } if path == "report.json" && *id == report_id
+        )));
+        assert!(validators.contains_key("report.json"));
+        assert!(matches!(
+            &task.body[2],
+            TaskBody::CollectedOut { to: CollectTo::Text { id }, optional: true, .. } if *id == energy_id
+        ));
+    }
+}
@@ -42,8 +42,8 @@ pub struct SoftwareComputingUsecaseService {
     software_block_list_repository: Arc<dyn ISoftwareBlockListRepository + Send + Sync>,
     /// 已安装软件仓储
     installed_software_repository: Arc<dyn IInstalledSoftwareRepository + Send + Sync>,
-    /// 集群仓储
-    cluster_repository: Arc<dyn IClusterRepository + Send + Sync>,
+    /// 任务派发服务
+    task_dispatch_service: Arc<dyn ITaskDispatchService + Send + Sync>,
     /// 节点实例仓储
     node_instance_repository: Arc<dyn INodeInstanceRepository + Send + Sync>,
     workflow_instance_repository: Arc<dyn IWorkflowInstanceRepository + Send + Sync>,
@@ -52,14 +52,8 @@ pub struct SoftwareComputingUsecaseService {
 #[async_trait]
 impl IUsecaseService for SoftwareComputingUsecaseService {
     async fn handle_usecase(&self, node_spec: NodeSpec) -> anyhow::Result<()> {
-        let task = self.parse_task(node_spec).await?;
-        let cluster_id = self.cluster_repository.get_random_cluster().await?;
-        let mut node_instance =
-            self.node_instance_repository.get_by_id(&task.id.to_string()).await?;
-        node_instance.cluster_id = Some(cluster_id.to_owned());
-        self.node_instance_repository.update(node_instance).await?;
-        self.node_instance_repository.save_changed().await?;
-        self.task_distribution_service.send_task(&task, cluster_id).await
+        let task = self.parse_task(node_spec.to_owned()).await?;
+        self.task_dispatch_service.dispatch(&node_spec, &task).await
     }
 
     async fn operate_task(&self, operate: Operation) -> anyhow::Result<()> {
@@ -151,6 +145,8 @@ impl SoftwareComputingUsecaseService {
         let mut environment_formats_map = HashMap::<String, FormatFill>::new();
         let mut files = vec![];
         let mut std_in = StdInKind::default();
+        // 输出文件的校验规则，批量输出为通配符无法按单个文件校验
+        let mut validators = HashMap::new();
 
         // 模板描述符及其键填充值的对应关系集合
         let mut templates_kv_json = HashMap::<String, HashMap<String, Option<String>>>::new();
@@ -462,6 +458,7 @@ impl SoftwareComputingUsecaseService {
                     collected_out_descriptor,
                     optional,
                     descriptor,
+                    validator,
                     ..
                 } => {
                     let collected_out = collected_outs
@@ -577,12 +574,19 @@ impl SoftwareComputingUsecaseService {
                         rule,
                         to,
                         optional: *optional,
+                        validators: collected_out
+                            .validator
+                            .iter()
+                            .chain(validator.iter())
+                            .cloned()
+                            .collect(),
                     });
                 }
                 OutputSlot::File {
                     descriptor: usecase_outslot_descriptor,
                     origin,
                     optional,
+                    validator,
                     ..
                 } => {
                     let task_output_slot = node_spec.output_slot(usecase_outslot_descriptor);
@@ -684,6 +688,12 @@ impl SoftwareComputingUsecaseService {
                                 rule,
                                 to,
                                 optional: *optional,
+                                validators: collected_out
+                                    .validator
+                                    .iter()
+                                    .chain(validator.iter())
+                                    .cloned()
+                                    .collect(),
                             });
                         }
                         FileOutOrigin::UsecaseOut(file_out_and_appointed_by) => {
@@ -720,14 +730,24 @@ impl SoftwareComputingUsecaseService {
                                 FileKind::Normal(file_name) => {
                                     let out_file_id =
                                         task_output_slot.all_tasks_file_outputs()?.get(0).unwrap();
+                                    let path = out_path_alter.unwrap_or(file_name.to_owned());
+                                    if let Some(validator) = validator {
+                                        validators.insert(path.to_owned(), validator.to_owned());
+                                    }
                                     files.push(FileInfo::Output {
                                         id: out_file_id.to_owned(),
-                                        path: out_path_alter.unwrap_or(file_name.to_owned()),
+                                        path,
                                         is_package: false,
                                         optional: *optional,
                                     });
                                 }
                                 FileKind::Batched(wild_card) => {
+                                    // 批量输出会被打包上传，无法逐个文件校验
+                                    if validator.is_some() {
+                                        anyhow::bail!(
+                                            "Output slot {usecase_outslot_descriptor} is batched and does not support validators."
+                                        );
+                                    }
                                     let out_file_or_zip_id =
                                         task_output_slot.all_tasks_file_outputs()?.get(0).unwrap();
                                     files.push(FileInfo::Output {
@@ -803,6 +823,10 @@ impl SoftwareComputingUsecaseService {
                 >(
                     &serde_json::to_string(&requirements)?,
                 )?),
+                launcher: Launcher::from(usecase_spec.launcher.to_owned()),
+                std_out_validator: usecase_spec.std_out_validator.to_owned(),
+                std_err_validator: usecase_spec.std_err_validator.to_owned(),
+                validators,
             },
         );
 
@@ -1017,23 +1041,17 @@ mod tests {
             .returning(|_, _| Ok(true));
         let installed_software_repository = Arc::new(installed_software_repository);
 
-        let mut cluster_repository = MockClusterRepository::new();
-        cluster_repository
-            .expect_get_random_cluster()
-            .returning(|| Ok(uuid::Uuid::new_v4()));
-        let cluster_repository = Arc::new(cluster_repository);
+        let mut task_dispatch_service = MockTaskDispatchService::new();
+        task_dispatch_service.expect_dispatch().returning(|_, _| Ok(()));
+        let task_dispatch_service = Arc::new(task_dispatch_service);
 
         let mut node_instance_repository = MockNodeInstanceRepository::new();
         node_instance_repository
             .expect_get_by_id()
             .returning(|_| Ok(NodeInstance::default()));
-        node_instance_repository.expect_update().returning(Ok);
-        node_instance_repository.expect_save_changed().returning(|| Ok(true));
         let node_instance_repository = Arc::new(node_instance_repository);
 
-        let mut task_distribution_service = MockTaskDistributionService::new();
-        task_distribution_service.expect_send_task().returning(|_, _| Ok(()));
-        let task_distribution_service = Arc::new(task_distribution_service);
+        let task_distribution_service = Arc::new(MockTaskDistributionService::new());
 
         (
             Arc::new(
@@ -1043,7 +1061,7 @@ mod tests {
                     .task_distribution_service(task_distribution_service)
                     .software_block_list_repository(software_block_list_repository)
                     .installed_software_repository(installed_software_repository)
-                    .cluster_repository(cluster_repository)
+                    .task_dispatch_service(task_dispatch_service)
                     .node_instance_repository(node_instance_repository)
                     .build()
                     .unwrap(),
@@ -0,0 +1,181 @@
+use crate::prelude::*;
+use alice_architecture::base_dto::ResponseBase;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::Arc;
+
+/// 签名时间戳请求头
+const TIMESTAMP_HEADER: &str = "X-Kuintessence-Timestamp";
+/// 签名请求头，值为 `sha256=` 加上以共享密钥对 `{timestamp}.{body}` 计算的 HMAC-SHA256 的十六进制
+const SIGNATURE_HEADER: &str = "X-Kuintessence-Signature";
+
+/// 通过计费系统检查预算的服务
+#[derive(Builder)]
+pub struct BudgetService {
+    http_client: Arc<reqwest::Client>,
+    /// 计费系统地址，未配置时不检查预算
+    #[builder(default)]
+    billing_url: Option<String>,
+    /// 与计费系统共享的请求签名密钥
+    #[builder(default)]
+    secret: String,
+}
+
+/// 节点预计使用的资源，与计费公式中的 n_* 变量对应
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct ResourceEstimate {
+    cpu: u64,
+    memory: u64,
+    storage: u64,
+    cpu_time: u64,
+    wall_time: u64,
+}
+
+impl From<Option<&Requirements>> for ResourceEstimate {
+    /// 按资源需求估算，墙钟时间取最长运行时间，未指定最大核时时按核心数与墙钟时间计算
+    fn from(requirements: Option<&Requirements>) -> Self {
+        let Some(requirements) = requirements else {
+            return Self {
+                cpu: 1,
+                memory: 0,
+                storage: 0,
+                cpu_time: 0,
+                wall_time: 0,
+            };
+        };
+        let cpu = requirements.required_cores();
+        let wall_time = requirements.max_wall_time.unwrap_or_default() as u64;
+        Self {
+            cpu,
+            memory: requirements.required_memory(),
+            storage: 0,
+            cpu_time: requirements.max_cpu_time.map(|el| el as u64).unwrap_or(cpu * wall_time),
+            wall_time,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckBudgetRequest {
+    flow_instance_id: String,
+    cluster_id: String,
+    partition: Option<String>,
+    estimate: ResourceEstimate,
+}
+
+impl BudgetService {
+    fn signature(&self, timestamp: i64, body: &[u8]) -> anyhow::Result<String> {
+        if self.secret.is_empty() {
+            anyhow::bail!("Budget check secret isn't configured.");
+        }
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())?;
+        mac.update(format!("{timestamp}.").as_bytes());
+        mac.update(body);
+        Ok(hex::encode(mac.finalize().into_bytes()))
+    }
+}
+
+#[async_trait]
+impl IBudgetService for BudgetService {
+    async fn check(
+        &self,
+        node_instance: &NodeInstance,
+        cluster_id: Uuid,
+        task: &Task,
+    ) -> anyhow::Result<BudgetDecision> {
+        let Some(billing_url) = &self.billing_url else {
+            return Ok(BudgetDecision::Allowed);
+        };
+        let url = url::Url::parse(billing_url)?.join("billing-system/CheckBudget")?;
+        // 计费系统按工作流实例确定用户，请求以共享密钥签名而非用户令牌认证
+        let body = serde_json::to_vec(&CheckBudgetRequest {
+            flow_instance_id: node_instance.flow_instance_id.to_string(),
+            cluster_id: cluster_id.to_string(),
+            partition: task.requirements().and_then(|el| el.partition.to_owned()),
+            estimate: task.requirements().into(),
+        })?;
+        let timestamp = chrono::Utc::now().timestamp();
+        let response = self
+            .http_client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                format!("sha256={}", self.signature(timestamp, &body)?),
+            )
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ResponseBase<BudgetDecision>>()
+            .await?;
+        if response.status != 200 {
+            anyhow::bail!("Budget check failed: {}", response.message);
+        }
+        response.content.ok_or(anyhow::anyhow!("Budget check returned no decision."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_estimate() {
+        let requirements = Requirements {
+            cpu_cores: Some(4),
+            node_count: Some(2),
+            memory_per_node: Some(1024),
+            max_wall_time: Some(3600),
+            ..Default::default()
+        };
+        assert_eq!(
+            ResourceEstimate::from(Some(&requirements)),
+            ResourceEstimate {
+                cpu: 8,
+                memory: 2 << 30,
+                storage: 0,
+                cpu_time: 8 * 3600,
+                wall_time: 3600,
+            }
+        );
+        let requirements = Requirements {
+            max_cpu_time: Some(100),
+            ..requirements
+        };
+        assert_eq!(ResourceEstimate::from(Some(&requirements)).cpu_time, 100);
+        assert_eq!(ResourceEstimate::from(None).cpu, 1);
+    }
+
+    #[test]
+    fn test_signature() {
+        let service = BudgetServiceBuilder::default()
+            .http_client(Arc::new(reqwest::Client::new()))
+            .secret("secret".to_string())
+            .build()
+            .unwrap();
+        // echo -n '1680000000.{}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            service.signature(1680000000, b"{}").unwrap(),
+            "2a02091ea43e6c03406451ee70ff733cc457a182f40a5951ff17ffcfdafd0235"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_without_billing_url() {
+        let service = BudgetServiceBuilder::default()
+            .http_client(Arc::new(reqwest::Client::new()))
+            .build()
+            .unwrap();
+        let task = Task {
+            id: Uuid::new_v4(),
+            command: TaskCommand::Start,
+            body: vec![],
+        };
+        let decision =
+            service.check(&NodeInstance::default(), Uuid::new_v4(), &task).await.unwrap();
+        assert_eq!(decision, BudgetDecision::Allowed);
+    }
+}
@@ -0,0 +1,422 @@
+use crate::prelude::*;
+use std::{cmp::Ordering, collections::HashSet, str::FromStr, sync::Arc};
+
+/// 集群选择服务
+///
+/// 选择流程：
+/// 1. 跳过未启用的集群
+/// 2. 按调度策略（节点策略优先，节点为 Auto 时使用工作流策略）确定候选集群
+/// 3. 有候选集群已安装所需软件时，跳过未安装的集群；都未安装时软件将在执行前部署
+/// 4. 按资源（核心与内存）是否满足、是否优先、排队任务数、空闲核心数、空闲内存对候选集群排序
+#[derive(Builder)]
+pub struct ClusterSelectionService {
+    cluster_repository: Arc<dyn IClusterRepository + Send + Sync>,
+    installed_software_repository: Arc<dyn IInstalledSoftwareRepository + Send + Sync>,
+    workflow_instance_repository: Arc<dyn IWorkflowInstanceRepository + Send + Sync>,
+}
+
+/// 参与排序的候选集群
+struct Candidate {
+    cluster: Cluster,
+    id: Uuid,
+    resource: Option<ClusterResource>,
+    preferred: bool,
+}
+
+impl Candidate {
+    /// 空闲核心与空闲内存是否都满足需求，没有上报资源时视为不满足
+    fn fits(&self, cores: u64, memory: u64) -> bool {
+        self.resource
+            .as_ref()
+            .map(|el| el.free_cores() >= cores && el.free_memory() >= memory)
+            .unwrap_or(false)
+    }
+
+    fn describe(&self) -> String {
+        match &self.resource {
+            Some(resource) => format!(
+                "{} ({}): {} free cores, {} bytes free memory, {} queuing tasks",
+                self.cluster.name,
+                self.id,
+                resource.free_cores(),
+                resource.free_memory(),
+                resource.queuing_task_count
+            ),
+            None => format!("{} ({}): no resource report", self.cluster.name, self.id),
+        }
+    }
+}
+
+#[async_trait]
+impl IClusterSelectionService for ClusterSelectionService {
+    async fn select_cluster(
+        &self,
+        node_spec: &NodeSpec,
+        task: &Task,
+    ) -> anyhow::Result<ClusterDecision> {
+        let strategy = match &node_spec.scheduling_strategy {
+            SchedulingStrategy::Auto => {
+                self.workflow_instance_repository
+                    .get_by_node_id(node_spec.id)
+                    .await?
+                    .spec
+                    .scheduling_strategy
+            }
+            el => el.to_owned(),
+        };
+        let (requirements, facility_kind) = Self::task_requirements(task);
+        let cores = Self::required_cores(requirements.as_ref());
+        let memory = Self::required_memory(requirements.as_ref());
+
+        let clusters = self.cluster_repository.get_all_clusters_with_resource().await?;
+        let total_count = clusters.len();
+        let mut candidates = vec![];
+        for (cluster, resource) in clusters.into_iter().filter(|(el, _)| el.enabled) {
+            let id = Uuid::from_str(&cluster.id)?;
+            candidates.push(Candidate {
+                cluster,
+                id,
+                resource,
+                preferred: false,
+            });
+        }
+        let disabled_count = total_count - candidates.len();
+
+        let strategy_name = match &strategy {
+            SchedulingStrategy::Manual { clusters } => {
+                let manual = clusters.iter().collect::<HashSet<_>>();
+                candidates.retain(|el| manual.contains(&el.id));
+                if candidates.is_empty() {
+                    anyhow::bail!(
+                        "None of the manually selected clusters {clusters:?} is enabled."
+                    );
+                }
+                "Manual"
+            }
+            SchedulingStrategy::Prefer { clusters } => {
+                let preferred = clusters.iter().collect::<HashSet<_>>();
+                for candidate in candidates.iter_mut() {
+                    candidate.preferred = preferred.contains(&candidate.id);
+                }
+                "Prefer"
+            }
+            SchedulingStrategy::Auto => "Auto",
+        };
+        if candidates.is_empty() {
+            anyhow::bail!(
+                "There is no enabled cluster to schedule node: {}.",
+                node_spec.id
+            );
+        }
+
+        let mut software_reason = None;
+        if let Some(FacilityKind::Spack {
+            name,
+            argument_list,
+        }) = facility_kind
+        {
+            let mut installed = vec![];
+            for candidate in candidates.iter() {
+                installed.push(
+                    self.installed_software_repository
+                        .is_software_installed_on_cluster(candidate.id, name, argument_list)
+                        .await?,
+                );
+            }
+            software_reason = Some(if installed.contains(&true) {
+                let candidate_count = candidates.len();
+                let mut installed = installed.into_iter();
+                candidates.retain(|_| installed.next().unwrap_or_default());
+                format!(
+                    "software {name} is installed, {} clusters without it skipped",
+                    candidate_count - candidates.len()
+                )
+            } else {
+                format!("software {name} will be deployed before execution")
+            });
+        }
+
+        Self::rank(&mut candidates, cores, memory);
+        let chosen = candidates.first().unwrap();
+
+        let mut reason = vec![
+            format!("strategy: {strategy_name}"),
+            format!("requested cores: {cores}"),
+            format!("requested memory: {memory} bytes"),
+            format!(
+                "{} candidate clusters, {disabled_count} disabled clusters skipped",
+                candidates.len()
+            ),
+            format!("selected {}", chosen.describe()),
+        ];
+        if chosen.preferred {
+            reason.push("selected cluster is preferred".to_string());
+        } else if candidates.iter().any(|el| el.preferred) {
+            reason.push("no preferred cluster has enough free cores and memory".to_string());
+        }
+        if !chosen.fits(cores, memory) {
+            reason.push(
+                "no candidate cluster has enough free cores and memory, task may queue".to_string(),
+            );
+        }
+        reason.extend(software_reason);
+
+        Ok(ClusterDecision {
+            cluster_id: chosen.id,
+            reason: reason.join("; "),
+        })
+    }
+}
+
+impl ClusterSelectionService {
+    /// 从任务中取出用例执行的资源需求与软件环境
+    fn task_requirements(task: &Task) -> (Option<Requirements>, Option<&FacilityKind>) {
+        task.body
+            .iter()
+            .find_map(|el| match el {
+                TaskBody::UsecaseExecution {
+                    requirements,
+                    facility_kind,
+                    ..
+                } => Some((requirements.to_owned(), Some(facility_kind))),
+                TaskBody::ExecuteScript { requirements, .. } => {
+                    Some((requirements.to_owned(), None))
+                }
+                _ => None,
+            })
+            .unwrap_or((None, None))
+    }
+
+    /// 任务需要的核心总数，未指定时按 1 核计算
+    fn required_cores(requirements: Option<&Requirements>) -> u64 {
+        requirements.map(|el| el.required_cores()).unwrap_or(1)
+    }
+
+    /// 任务需要的内存总量（字节），未指定时为 0
+    fn required_memory(requirements: Option<&Requirements>) -> u64 {
+        requirements.map(|el| el.required_memory()).unwrap_or_default()
+    }
+
+    /// 对候选集群排序，排在最前面的是最合适的集群
+    fn rank(candidates: &mut [Candidate], cores: u64, memory: u64) {
+        candidates.sort_by(|a, b| {
+            b.fits(cores, memory)
+                .cmp(&a.fits(cores, memory))
+                .then(b.preferred.cmp(&a.preferred))
+                .then_with(|| match (&a.resource, &b.resource) {
+                    (Some(a), Some(b)) => a
+                        .queuing_task_count
+                        .cmp(&b.queuing_task_count)
+                        .then(b.free_cores().cmp(&a.free_cores()))
+                        .then(b.free_memory().cmp(&a.free_memory())),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+
+    fn cluster(
+        id: Uuid,
+        enabled: bool,
+        resource: Option<(u64, u64)>,
+    ) -> (Cluster, Option<ClusterResource>) {
+        (
+            Cluster {
+                id: id.to_string(),
+                name: id.to_string(),
+                enabled,
+                ..Default::default()
+            },
+            resource.map(|(core_number, queuing_task_count)| ClusterResource {
+                cluster_id: id,
+                memory: 1 << 30,
+                core_number,
+                queuing_task_count,
+                ..Default::default()
+            }),
+        )
+    }
+
+    fn service(
+        clusters: Vec<(Cluster, Option<ClusterResource>)>,
+        flow_strategy: SchedulingStrategy,
+    ) -> ClusterSelectionService {
+        service_without_software(clusters, flow_strategy, vec![])
+    }
+
+    fn service_without_software(
+        clusters: Vec<(Cluster, Option<ClusterResource>)>,
+        flow_strategy: SchedulingStrategy,
+        without_software: Vec<Uuid>,
+    ) -> ClusterSelectionService {
+        let mut cluster_repository = MockClusterRepository::new();
+        cluster_repository
+            .expect_get_all_clusters_with_resource()
+            .returning(move || Ok(clusters.clone()));
+        let mut installed_software_repository = MockInstalledSoftwareRepository::new();
+        installed_software_repository
+            .expect_is_software_installed_on_cluster()
+            .returning(move |cluster_id, _, _| Ok(!without_software.contains(&cluster_id)));
+        let mut workflow_instance_repository = MockWorkflowInstanceRepository::new();
+        workflow_instance_repository.expect_get_by_node_id().returning(move |_| {
+            let mut workflow_instance = WorkflowInstance::default();
+            workflow_instance.spec.scheduling_strategy = flow_strategy.clone();
+            Ok(workflow_instance)
+        });
+        ClusterSelectionServiceBuilder::default()
+            .cluster_repository(Arc::new(cluster_repository))
+            .installed_software_repository(Arc::new(installed_software_repository))
+            .workflow_instance_repository(Arc::new(workflow_instance_repository))
+            .build()
+            .unwrap()
+    }
+
+    fn task(cpu_cores: usize) -> Task {
+        task_with_memory(cpu_cores, None)
+    }
+
+    fn task_with_memory(cpu_cores: usize, memory_per_node: Option<usize>) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            command: TaskCommand::Start,
+            body: vec![TaskBody::UsecaseExecution {
+                name: "run".to_string(),
+                facility_kind: FacilityKind::Spack {
+                    name: "gromacs".to_string(),
+                    argument_list: vec![],
+                },
+                arguments: vec![],
+                environments: Default::default(),
+                std_in: StdInKind::None,
+                files: vec![],
+                requirements: Some(Requirements {
+                    cpu_cores: Some(cpu_cores),
+                    memory_per_node,
+                    ..Default::default()
+                }),
+                launcher: Launcher::default(),
+                std_out_validator: None,
+                std_err_validator: None,
+                validators: Default::default(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auto_selects_least_loaded_enabled_cluster() {
+        let (busy, idle, disabled) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let service = service(
+            vec![
+                cluster(busy, true, Some((64, 10))),
+                cluster(idle, true, Some((64, 0))),
+                cluster(disabled, false, Some((1024, 0))),
+            ],
+            SchedulingStrategy::Auto,
+        );
+        let decision = service.select_cluster(&NodeSpec::default(), &task(8)).await.unwrap();
+        assert_eq!(decision.cluster_id, idle);
+        assert!(decision.reason.contains("1 disabled clusters skipped"));
+    }
+
+    #[tokio::test]
+    async fn test_manual_restricts_candidates() {
+        let (small, large) = (Uuid::new_v4(), Uuid::new_v4());
+        let service = service(
+            vec![
+                cluster(small, true, Some((4, 0))),
+                cluster(large, true, Some((128, 0))),
+            ],
+            SchedulingStrategy::Manual {
+                clusters: vec![small],
+            },
+        );
+        let decision = service.select_cluster(&NodeSpec::default(), &task(8)).await.unwrap();
+        assert_eq!(decision.cluster_id, small);
+        assert!(decision.reason.contains("task may queue"));
+    }
+
+    #[tokio::test]
+    async fn test_prefer_falls_back_when_preferred_cluster_is_full() {
+        let (preferred, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let clusters = vec![
+            cluster(preferred, true, Some((4, 0))),
+            cluster(other, true, None),
+        ];
+        let node_spec = NodeSpec {
+            scheduling_strategy: SchedulingStrategy::Prefer {
+                clusters: vec![preferred],
+            },
+            ..Default::default()
+        };
+
+        let decision = service(clusters.clone(), SchedulingStrategy::Auto)
+            .select_cluster(&node_spec, &task(2))
+            .await
+            .unwrap();
+        assert_eq!(decision.cluster_id, preferred);
+
+        let clusters = vec![clusters[0].clone(), cluster(other, true, Some((32, 0)))];
+        let decision = service(clusters, SchedulingStrategy::Auto)
+            .select_cluster(&node_spec, &task(8))
+            .await
+            .unwrap();
+        assert_eq!(decision.cluster_id, other);
+    }
+
+    #[tokio::test]
+    async fn test_skips_clusters_without_software() {
+        let (idle, busy) = (Uuid::new_v4(), Uuid::new_v4());
+        let clusters = vec![
+            cluster(idle, true, Some((64, 0))),
+            cluster(busy, true, Some((64, 10))),
+        ];
+
+        let decision =
+            service_without_software(clusters.clone(), SchedulingStrategy::Auto, vec![idle])
+                .select_cluster(&NodeSpec::default(), &task(8))
+                .await
+                .unwrap();
+        assert_eq!(decision.cluster_id, busy);
+        assert!(decision.reason.contains("1 clusters without it skipped"));
+
+        let decision =
+            service_without_software(clusters, SchedulingStrategy::Auto, vec![idle, busy])
+                .select_cluster(&NodeSpec::default(), &task(8))
+                .await
+                .unwrap();
+        assert_eq!(decision.cluster_id, idle);
+        assert!(decision.reason.contains("will be deployed before execution"));
+    }
+
+    #[tokio::test]
+    async fn test_skips_clusters_without_enough_memory() {
+        let (small, large) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut large_cluster = cluster(large, true, Some((64, 5)));
+        large_cluster.1.as_mut().unwrap().memory = 4 << 30;
+        let clusters = vec![cluster(small, true, Some((64, 0))), large_cluster];
+
+        // 需要 2 GiB 内存，small 集群只有 1 GiB，即使它没有排队任务也不应选中
+        let decision = service(clusters, SchedulingStrategy::Auto)
+            .select_cluster(&NodeSpec::default(), &task_with_memory(8, Some(2048)))
+            .await
+            .unwrap();
+        assert_eq!(decision.cluster_id, large);
+        assert!(!decision.reason.contains("task may queue"));
+    }
+
+    #[tokio::test]
+    async fn test_no_enabled_cluster() {
+        let service = service(
+            vec![cluster(Uuid::new_v4(), false, None)],
+            SchedulingStrategy::Auto,
+        );
+        assert!(service.select_cluster(&NodeSpec::default(), &task(1)).await.is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use crate::prelude::*;
+use alice_architecture::repository::IReadOnlyRepository;
+use chrono::Utc;
+
+/// 自定义节点服务
+#[derive(Builder)]
+pub struct CustomNodeService {
+    custom_node_repository: Arc<dyn ICustomNodeRepository + Send + Sync>,
+    workflow_draft_repository: Arc<dyn IReadOnlyRepository<WorkflowDraft> + Send + Sync>,
+}
+
+impl CustomNodeService {
+    async fn get_user_custom_node(&self, user_id: Uuid, id: Uuid) -> anyhow::Result<CustomNode> {
+        let custom_node = self.custom_node_repository.get_by_id(&id.to_string()).await?;
+        if custom_node.user_id != user_id {
+            bail!("Custom node {id} doesn't belong to user {user_id}.");
+        }
+        Ok(custom_node)
+    }
+}
+
+#[async_trait]
+impl ICustomNodeService for CustomNodeService {
+    async fn package(
+        &self,
+        user_id: Uuid,
+        command: PackageCustomNodeCommand,
+    ) -> anyhow::Result<CustomNode> {
+        let draft = self.workflow_draft_repository.get_by_id(&command.draft_id.to_string()).await?;
+        let spec = CustomNode::package(
+            &draft.spec,
+            &command.node_ids,
+            command.input_slots,
+            command.output_slots,
+        )?;
+        let custom_node = CustomNode {
+            id: Uuid::new_v4(),
+            name: command.name,
+            description: command.description,
+            user_id,
+            spec,
+            created_time: Utc::now(),
+        };
+        let custom_node = self.custom_node_repository.insert(custom_node).await?;
+        self.custom_node_repository.save_changed().await?;
+        Ok(custom_node)
+    }
+
+    async fn get_custom_nodes(&self, user_id: Uuid) -> anyhow::Result<Vec<CustomNode>> {
+        self.custom_node_repository.get_all_by_user_id(user_id).await
+    }
+
+    async fn get_node_draft(&self, user_id: Uuid, id: Uuid) -> anyhow::Result<NodeDraft> {
+        self.get_user_custom_node(user_id, id).await?.node_draft()
+    }
+
+    async fn remove(&self, user_id: Uuid, id: Uuid) -> anyhow::Result<()> {
+        let custom_node = self.get_user_custom_node(user_id, id).await?;
+        self.custom_node_repository
+            .delete_by_id(&id.to_string(), Some(custom_node))
+            .await?;
+        self.custom_node_repository.save_changed().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+    use std::collections::HashMap;
+
+    fn node_draft(name: &str) -> NodeDraft {
+        NodeDraft {
+            kind: NodeKind::NoAction,
+            external_id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: String::default(),
+            batch_strategies: None,
+            input_slots: vec![NodeInputSlot {
+                kind: NodeInputSlotKind::Text {
+                    contents: None,
+                    rule: TextInputSlotRule::default(),
+                },
+                optional: false,
+                descriptor: "in".to_string(),
+                description: None,
+            }],
+            output_slots: vec![NodeDraftOutputSlot {
+                kind: NodeDraftOutputSlotKind::Text,
+                descriptor: "out".to_string(),
+                description: None,
+                optional: false,
+            }],
+            scheduling_strategy: SchedulingStrategy::default(),
+            requirements: None,
+            retry_policy: None,
+            additional_datas: None,
+        }
+    }
+
+    fn relation(from_id: Uuid, from_slot: &str, to_id: Uuid, to_slot: &str) -> NodeRelation {
+        NodeRelation {
+            from_id,
+            to_id,
+            slot_relations: vec![SlotRelation {
+                from_slot: from_slot.to_string(),
+                to_slot: to_slot.to_string(),
+                transfer_strategy: TransferStrategy::default(),
+            }],
+            branch: None,
+        }
+    }
+
+    fn exposed(descriptor: &str, node_external_id: Uuid, inner_descriptor: &str) -> ExposedSlot {
+        ExposedSlot {
+            descriptor: descriptor.to_string(),
+            description: None,
+            node_external_id,
+            inner_descriptor: inner_descriptor.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_package_and_expand() {
+        let (a, b, c) = (node_draft("a"), node_draft("b"), node_draft("c"));
+        let (a_id, b_id) = (a.external_id, b.external_id);
+        let draft = WorkflowDraft {
+            id: Uuid::new_v4(),
+            spec: WorkflowDraftSpec {
+                node_drafts: vec![a, b, c.to_owned()],
+                node_relations: vec![
+                    relation(a_id, "out", b_id, "in"),
+                    relation(b_id, "out", c.external_id, "in"),
+                ],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let returned = draft.to_owned();
+        let mut workflow_draft_repository = MockWorkflowDraftRepository::new();
+        workflow_draft_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(returned.to_owned()));
+        let mut custom_node_repository = MockCustomNodeRepository::new();
+        custom_node_repository.expect_insert().returning(Ok);
+        custom_node_repository.expect_save_changed().returning(|| Ok(true));
+        let service = CustomNodeServiceBuilder::default()
+            .custom_node_repository(Arc::new(custom_node_repository))
+            .workflow_draft_repository(Arc::new(workflow_draft_repository))
+            .build()
+            .unwrap();
+        let user_id = Uuid::new_v4();
+        let custom_node = service
+            .package(
+                user_id,
+                PackageCustomNodeCommand {
+                    draft_id: draft.id,
+                    node_ids: vec![a_id, b_id],
+                    name: "pair".to_string(),
+                    description: String::default(),
+                    input_slots: vec![exposed("x", a_id, "in")],
+                    output_slots: vec![exposed("y", b_id, "out")],
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(custom_node.spec.workflow.node_drafts.len(), 2);
+        assert_eq!(custom_node.spec.workflow.node_relations.len(), 1);
+
+        let shell = custom_node.node_draft().unwrap();
+        assert_eq!(shell.input_slots[0].descriptor, "x");
+        assert_eq!(shell.output_slots[0].descriptor, "y");
+        let (p, q) = (node_draft("p"), node_draft("q"));
+        let (p_id, q_id, shell_id) = (p.external_id, q.external_id, shell.external_id);
+        let mut spec = WorkflowDraftSpec {
+            node_drafts: vec![p, shell, q],
+            node_relations: vec![
+                relation(p_id, "out", shell_id, "x"),
+                relation(shell_id, "y", q_id, "in"),
+            ],
+            ..Default::default()
+        };
+        let custom_nodes = HashMap::from([(custom_node.id, custom_node.to_owned())]);
+        spec.expand_custom_nodes(&custom_nodes).unwrap();
+        assert!(spec.custom_node_ids().is_empty());
+        assert_eq!(spec.node_drafts.len(), 4);
+        let inner_id = |name: &str| {
+            spec.node_drafts
+                .iter()
+                .find(|el| el.name == format!("pair/{name}"))
+                .unwrap()
+                .external_id
+        };
+        let (inner_a, inner_b) = (inner_id("a"), inner_id("b"));
+        assert!(inner_a != a_id && inner_b != b_id);
+        let edges = spec
+            .node_relations
+            .iter()
+            .map(|el| {
+                let slot = &el.slot_relations[0];
+                (
+                    el.from_id,
+                    slot.from_slot.as_str(),
+                    el.to_id,
+                    slot.to_slot.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(edges.len(), 3);
+        assert!(edges.contains(&(p_id, "out", inner_a, "in")));
+        assert!(edges.contains(&(inner_a, "out", inner_b, "in")));
+        assert!(edges.contains(&(inner_b, "out", q_id, "in")));
+        assert!(spec.analyze_graph().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expand_rejects_unexposed_slot() {
+        let inner = node_draft("inner");
+        let custom_node = CustomNode {
+            id: Uuid::new_v4(),
+            spec: CustomNodeSpec {
+                input_slots: vec![exposed("x", inner.external_id, "in")],
+                workflow: WorkflowDraftSpec {
+                    node_drafts: vec![inner],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let shell = custom_node.node_draft().unwrap();
+        let p = node_draft("p");
+        let mut spec = WorkflowDraftSpec {
+            node_relations: vec![relation(p.external_id, "out", shell.external_id, "in")],
+            node_drafts: vec![p, shell],
+            ..Default::default()
+        };
+        let custom_nodes = HashMap::from([(custom_node.id, custom_node)]);
+        assert!(spec.expand_custom_nodes(&custom_nodes).is_err());
+    }
+}
@@ -1,9 +1,21 @@
+pub mod budget;
+pub mod cluster_selection;
+pub mod custom_node;
+pub mod quota;
 pub mod schedule;
 pub mod status_receiver;
+pub mod task_dispatch;
 pub mod workflow;
+pub mod workflow_template;
 
 pub mod prelude {
+    pub use super::budget::*;
+    pub use super::cluster_selection::*;
+    pub use super::custom_node::*;
+    pub use super::quota::*;
     pub use super::schedule::*;
     pub use super::status_receiver::*;
+    pub use super::task_dispatch::*;
     pub use super::workflow::*;
+    pub use super::workflow_template::*;
 }
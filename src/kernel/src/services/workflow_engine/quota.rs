@@ -0,0 +1,402 @@
+use crate::prelude::*;
+use alice_architecture::IMessageQueueProducerTemplate;
+use std::sync::Arc;
+
+/// 用户配额服务
+#[derive(Builder)]
+pub struct QuotaService {
+    user_resource_repository: Arc<dyn IUserResourceRepository + Send + Sync>,
+    workflow_instance_repository: Arc<dyn IWorkflowInstanceRepository + Send + Sync>,
+    mq_producer: Arc<dyn IMessageQueueProducerTemplate<QuotaAlert> + Send + Sync>,
+    /// 配额告警主题
+    alert_topic: String,
+    /// 配额不足时的处理策略
+    policy: QuotaPolicy,
+}
+
+#[async_trait]
+impl IQuotaService for QuotaService {
+    async fn acquire(
+        &self,
+        node_instance: &NodeInstance,
+        cluster_id: Uuid,
+        task: &Task,
+    ) -> anyhow::Result<QuotaDecision> {
+        let user_id = self.user_id(node_instance).await?;
+        let Some(user_resource) = self
+            .user_resource_repository
+            .get_by_user_and_cluster(user_id, cluster_id)
+            .await?
+        else {
+            return Ok(QuotaDecision::Granted(None));
+        };
+        let reservation = QuotaReservation {
+            cores: task.requirements().map(|el| el.required_cores()).unwrap_or(1),
+            memory: task.requirements().map(|el| el.required_memory()).unwrap_or_default(),
+        };
+
+        // 存储空间不会因任务结束而释放，核心数与内存超出上限的任务等待也无法满足
+        let mut unsatisfiable = vec![];
+        if user_resource.storage_capacity_max != 0
+            && user_resource.storage_capacity >= user_resource.storage_capacity_max
+        {
+            unsatisfiable.push(format!(
+                "storage capacity {} bytes reached the limit {} bytes",
+                user_resource.storage_capacity, user_resource.storage_capacity_max
+            ));
+        }
+        if exceeds(0, reservation.cores, user_resource.core_number_max) {
+            unsatisfiable.push(format!(
+                "{} cores requested but the limit is {}",
+                reservation.cores, user_resource.core_number_max
+            ));
+        }
+        if exceeds(0, reservation.memory, user_resource.memory_max) {
+            unsatisfiable.push(format!(
+                "{} bytes of memory requested but the limit is {} bytes",
+                reservation.memory, user_resource.memory_max
+            ));
+        }
+        if !unsatisfiable.is_empty() {
+            return Ok(QuotaDecision::Rejected(format!(
+                "Quota on cluster {cluster_id} can not be satisfied: {}.",
+                unsatisfiable.join("; ")
+            )));
+        }
+
+        // 检查与占用在同一条语句中完成，并发提交的任务不会同时越过上限
+        if let Some(after) = self
+            .user_resource_repository
+            .try_reserve(user_resource.id, &reservation)
+            .await?
+        {
+            let before = UserResource {
+                core_number: after.core_number.saturating_sub(reservation.cores),
+                memory: after.memory.saturating_sub(reservation.memory),
+                ..after.to_owned()
+            };
+            self.send_alerts(&before, &after).await?;
+            return Ok(QuotaDecision::Granted(Some(reservation)));
+        }
+
+        let user_resource = self
+            .user_resource_repository
+            .get_by_user_and_cluster(user_id, cluster_id)
+            .await?
+            .unwrap_or(user_resource);
+        let mut exceeded = vec![];
+        if exceeds(
+            user_resource.core_number,
+            reservation.cores,
+            user_resource.core_number_max,
+        ) {
+            exceeded.push(format!(
+                "{} of {} cores in use, {} requested",
+                user_resource.core_number, user_resource.core_number_max, reservation.cores
+            ));
+        }
+        if exceeds(
+            user_resource.memory,
+            reservation.memory,
+            user_resource.memory_max,
+        ) {
+            exceeded.push(format!(
+                "{} of {} bytes of memory in use, {} requested",
+                user_resource.memory, user_resource.memory_max, reservation.memory
+            ));
+        }
+        if exceeded.is_empty() {
+            exceeded.push("quota was taken by concurrently submitted tasks".to_string());
+        }
+        let reason = format!(
+            "Quota on cluster {cluster_id} exceeded: {}.",
+            exceeded.join("; ")
+        );
+        Ok(match self.policy {
+            QuotaPolicy::Queue => QuotaDecision::Queued(reason),
+            QuotaPolicy::Reject => QuotaDecision::Rejected(reason),
+        })
+    }
+
+    async fn release(
+        &self,
+        node_instance: &NodeInstance,
+        reservation: &QuotaReservation,
+        used_storage: u64,
+    ) -> anyhow::Result<()> {
+        let Some(cluster_id) = node_instance.cluster_id else {
+            return Ok(());
+        };
+        let user_id = self.user_id(node_instance).await?;
+        let Some(user_resource) = self
+            .user_resource_repository
+            .get_by_user_and_cluster(user_id, cluster_id)
+            .await?
+        else {
+            return Ok(());
+        };
+        let after = self
+            .user_resource_repository
+            .release_reserved(user_resource.id, reservation, used_storage)
+            .await?;
+        let before = UserResource {
+            storage_capacity: after.storage_capacity.saturating_sub(used_storage),
+            ..after.to_owned()
+        };
+        self.send_alerts(&before, &after).await
+    }
+}
+
+impl QuotaService {
+    /// 节点实例所属用户的 id
+    async fn user_id(&self, node_instance: &NodeInstance) -> anyhow::Result<Uuid> {
+        Ok(self
+            .workflow_instance_repository
+            .get_by_id(&node_instance.flow_instance_id.to_string())
+            .await?
+            .user_id)
+    }
+
+    /// 使用量从告警阈值以下升至阈值及以上时发送告警
+    ///
+    /// # 参数
+    ///
+    /// * `before` - 变更前的配额
+    /// * `after` - 变更后的配额
+    async fn send_alerts(&self, before: &UserResource, after: &UserResource) -> anyhow::Result<()> {
+        let usages = [
+            (
+                QuotaResourceKind::CoreNumber,
+                before.core_number,
+                after.core_number,
+                after.core_number_alert,
+                after.core_number_max,
+            ),
+            (
+                QuotaResourceKind::Memory,
+                before.memory,
+                after.memory,
+                after.memory_alert,
+                after.memory_max,
+            ),
+            (
+                QuotaResourceKind::StorageCapacity,
+                before.storage_capacity,
+                after.storage_capacity,
+                after.storage_capacity_alert,
+                after.storage_capacity_max,
+            ),
+        ];
+        for (kind, before, used, alert, max) in usages {
+            if alert == 0 || before >= alert || used < alert {
+                continue;
+            }
+            self.mq_producer
+                .send_object(
+                    &QuotaAlert {
+                        user_id: after.user_id,
+                        cluster_id: after.cluster_id,
+                        kind,
+                        used,
+                        alert,
+                        max,
+                    },
+                    Some(&self.alert_topic),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// 在已使用 `used` 的基础上再使用 `requested` 是否超出上限，上限为 0 时不限制
+fn exceeds(used: u64, requested: u64, max: u64) -> bool {
+    max != 0 && used + requested > max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct AlertCollector {
+        alerts: Mutex<Vec<QuotaAlert>>,
+    }
+
+    #[async_trait]
+    impl IMessageQueueProducerTemplate<QuotaAlert> for AlertCollector {
+        async fn send_object(&self, alert: &QuotaAlert, _: Option<&str>) -> anyhow::Result<()> {
+            self.alerts.lock().unwrap().push(alert.to_owned());
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        service: QuotaService,
+        updated: Arc<Mutex<Vec<UserResource>>>,
+        alerts: Arc<AlertCollector>,
+    }
+
+    fn fixture(user_resource: UserResource, policy: QuotaPolicy) -> Fixture {
+        let updated = Arc::new(Mutex::new(vec![]));
+        let mut user_resource_repository = MockUserResourceRepository::new();
+        user_resource_repository.expect_get_by_user_and_cluster().returning({
+            let user_resource = user_resource.to_owned();
+            move |_, _| Ok(Some(user_resource.to_owned()))
+        });
+        let reserved = user_resource.to_owned();
+        let updated_clone = updated.clone();
+        user_resource_repository.expect_try_reserve().returning(move |_, reservation| {
+            if exceeds(
+                reserved.core_number,
+                reservation.cores,
+                reserved.core_number_max,
+            ) || exceeds(reserved.memory, reservation.memory, reserved.memory_max)
+            {
+                return Ok(None);
+            }
+            let after = UserResource {
+                core_number: reserved.core_number + reservation.cores,
+                memory: reserved.memory + reservation.memory,
+                ..reserved.to_owned()
+            };
+            updated_clone.lock().unwrap().push(after.to_owned());
+            Ok(Some(after))
+        });
+        let released = user_resource.to_owned();
+        let updated_clone = updated.clone();
+        user_resource_repository.expect_release_reserved().returning(
+            move |_, reservation, used_storage| {
+                let after = UserResource {
+                    core_number: released.core_number.saturating_sub(reservation.cores),
+                    memory: released.memory.saturating_sub(reservation.memory),
+                    storage_capacity: released.storage_capacity + used_storage,
+                    ..released.to_owned()
+                };
+                updated_clone.lock().unwrap().push(after.to_owned());
+                Ok(after)
+            },
+        );
+        let mut workflow_instance_repository = MockWorkflowInstanceRepository::new();
+        workflow_instance_repository
+            .expect_get_by_id()
+            .returning(|_| Ok(WorkflowInstance::default()));
+        let alerts = Arc::new(AlertCollector::default());
+        let service = QuotaServiceBuilder::default()
+            .user_resource_repository(Arc::new(user_resource_repository))
+            .workflow_instance_repository(Arc::new(workflow_instance_repository))
+            .mq_producer(alerts.clone())
+            .alert_topic("quota-alert".to_string())
+            .policy(policy)
+            .build()
+            .unwrap();
+        Fixture {
+            service,
+            updated,
+            alerts,
+        }
+    }
+
+    fn task(cpu_cores: usize) -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            command: TaskCommand::Start,
+            body: vec![TaskBody::ExecuteScript {
+                kind: ScriptKind::Python {
+                    environment: PythonEnvironment::System,
+                },
+                origin: ScriptOriginKind::Edit {
+                    content: String::default(),
+                },
+                files: vec![],
+                validators: Default::default(),
+                requirements: Some(Requirements {
+                    cpu_cores: Some(cpu_cores),
+                    memory_per_node: Some(1024),
+                    ..Default::default()
+                }),
+            }],
+        }
+    }
+
+    fn user_resource(core_number: u64) -> UserResource {
+        UserResource {
+            core_number,
+            core_number_max: 8,
+            core_number_alert: 6,
+            memory_max: 16 << 30,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire_reserves_quota() {
+        let fixture = fixture(user_resource(2), QuotaPolicy::Queue);
+        let decision = fixture
+            .service
+            .acquire(&NodeInstance::default(), Uuid::new_v4(), &task(4))
+            .await
+            .unwrap();
+        let QuotaDecision::Granted(Some(reservation)) = decision else {
+            panic!("Quota should be granted, got {decision:?}");
+        };
+        assert_eq!(reservation.cores, 4);
+        assert_eq!(reservation.memory, 1 << 30);
+        let updated = fixture.updated.lock().unwrap();
+        assert_eq!(updated[0].core_number, 6);
+        assert_eq!(updated[0].memory, 1 << 30);
+        let alerts = fixture.alerts.alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, QuotaResourceKind::CoreNumber);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_exceeded_quota_follows_policy() {
+        let queue = fixture(user_resource(6), QuotaPolicy::Queue);
+        let decision = queue
+            .service
+            .acquire(&NodeInstance::default(), Uuid::new_v4(), &task(4))
+            .await
+            .unwrap();
+        assert!(matches!(decision, QuotaDecision::Queued(_)));
+        assert!(queue.updated.lock().unwrap().is_empty());
+
+        let reject = fixture(user_resource(6), QuotaPolicy::Reject);
+        let decision = reject
+            .service
+            .acquire(&NodeInstance::default(), Uuid::new_v4(), &task(4))
+            .await
+            .unwrap();
+        assert!(matches!(decision, QuotaDecision::Rejected(_)));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_rejects_unsatisfiable_requirements() {
+        let fixture = fixture(user_resource(0), QuotaPolicy::Queue);
+        let decision = fixture
+            .service
+            .acquire(&NodeInstance::default(), Uuid::new_v4(), &task(16))
+            .await
+            .unwrap();
+        assert!(matches!(decision, QuotaDecision::Rejected(_)));
+    }
+
+    #[tokio::test]
+    async fn test_release() {
+        let fixture = fixture(user_resource(6), QuotaPolicy::Queue);
+        let node_instance = NodeInstance {
+            cluster_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let reservation = QuotaReservation {
+            cores: 4,
+            memory: 0,
+        };
+        fixture.service.release(&node_instance, &reservation, 1024).await.unwrap();
+        let updated = fixture.updated.lock().unwrap();
+        assert_eq!(updated[0].core_number, 2);
+        assert_eq!(updated[0].storage_capacity, 1024);
+    }
+}
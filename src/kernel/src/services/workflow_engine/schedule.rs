@@ -1,7 +1,10 @@
 use crate::prelude::*;
 use alice_architecture::repository::IDBRepository;
 use chrono::Utc;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 #[derive(Builder)]
 pub struct WorkflowScheduleService {
@@ -17,87 +20,167 @@ pub struct WorkflowScheduleService {
 impl IWorkflowScheduleService for WorkflowScheduleService {
     /// （√）
     async fn schedule_next_nodes(&self, mode: ScheduleMode) -> anyhow::Result<()> {
-        let (mut workflow_instance, entry_node_ids) = match mode {
-            ScheduleMode::WorkflowInstanceId(id) => {
-                let mut workflow_instance =
-                    self.workflow_instance_repository.get_by_id(&id.to_string()).await?;
-                workflow_instance.status = WorkflowInstanceStatus::Running;
-                self.workflow_instance_repository.update(workflow_instance.to_owned()).await?;
-                self.workflow_instance_repository.save_changed().await?;
-                // 找到入度为零的节点
-                let node_specs = &workflow_instance.spec.node_specs;
-                let node_id_dependencies: Vec<(Uuid, Uuid)> = workflow_instance
-                    .spec
-                    .node_relations
-                    .iter()
-                    .map(|el| (el.from_id.to_owned(), el.to_id.to_owned()))
-                    .collect();
-                let node_ids = node_specs.iter().map(|el| el.id.to_owned()).collect::<Vec<_>>();
-                let entry_node_ids =
-                    Self::find_entry_nodes_ids(&node_ids, &node_id_dependencies).await;
-
-                // 把入度不为零的节点置为 StandBy
-                for node_spec in node_specs.iter() {
-                    if !entry_node_ids.contains(&node_spec.id) {
-                        // 非入口节点状态更新为待命中
-                        let mut stand_by_node_instance = self
-                            .node_instance_repository
-                            .get_by_id(&node_spec.id.to_string())
-                            .await?;
-                        stand_by_node_instance.status = NodeInstanceStatus::Standby;
-                        // 更新批量根节点
-                        self.node_instance_repository.update(stand_by_node_instance).await?;
-                        if !node_spec.batch_strategies.is_empty() {
-                            let mut stand_by_sub_node_instances = self
+        let (mut workflow_instance, entry_node_ids, condition_results, skipped_node_ids) =
+            match mode {
+                ScheduleMode::WorkflowInstanceId(id) => {
+                    let mut workflow_instance =
+                        self.workflow_instance_repository.get_by_id(&id.to_string()).await?;
+                    workflow_instance.status = WorkflowInstanceStatus::Running;
+                    self.workflow_instance_repository.update(workflow_instance.to_owned()).await?;
+                    self.workflow_instance_repository.save_changed().await?;
+                    // 找到入度为零的节点
+                    let node_specs = &workflow_instance.spec.node_specs;
+                    let node_id_dependencies: Vec<(Uuid, Uuid)> = workflow_instance
+                        .spec
+                        .node_relations
+                        .iter()
+                        .map(|el| (el.from_id.to_owned(), el.to_id.to_owned()))
+                        .collect();
+                    let node_ids = node_specs.iter().map(|el| el.id.to_owned()).collect::<Vec<_>>();
+                    let entry_node_ids =
+                        Self::find_entry_nodes_ids(&node_ids, &node_id_dependencies).await;
+
+                    // 把入度不为零的节点置为 StandBy
+                    for node_spec in node_specs.iter() {
+                        if !entry_node_ids.contains(&node_spec.id) {
+                            // 非入口节点状态更新为待命中
+                            let mut stand_by_node_instance = self
                                 .node_instance_repository
-                                .get_node_sub_node_instances(node_spec.id)
+                                .get_by_id(&node_spec.id.to_string())
                                 .await?;
-                            for el in stand_by_sub_node_instances.iter_mut() {
-                                el.status = NodeInstanceStatus::Standby;
-                                // 更新批量子节点
-                                self.node_instance_repository.update(el.to_owned()).await?;
+                            stand_by_node_instance.status = NodeInstanceStatus::Standby;
+                            // 更新批量根节点
+                            self.node_instance_repository.update(stand_by_node_instance).await?;
+                            if !node_spec.batch_strategies.is_empty() {
+                                let mut stand_by_sub_node_instances = self
+                                    .node_instance_repository
+                                    .get_node_sub_node_instances(node_spec.id)
+                                    .await?;
+                                for el in stand_by_sub_node_instances.iter_mut() {
+                                    el.status = NodeInstanceStatus::Standby;
+                                    // 更新批量子节点
+                                    self.node_instance_repository.update(el.to_owned()).await?;
+                                }
                             }
                         }
                     }
+                    (
+                        workflow_instance,
+                        entry_node_ids,
+                        HashMap::new(),
+                        HashSet::new(),
+                    )
                 }
-                (workflow_instance, entry_node_ids)
-            }
-            ScheduleMode::NodeInstanceId(node_id) => {
-                if self
-                    .node_instance_repository
-                    .is_all_same_entryment_nodes_success(node_id)
-                    .await?
-                {
-                    let workflow_instance =
-                        self.workflow_instance_repository.get_by_node_id(node_id).await?;
-
-                    let stand_by_nodes = self
+                ScheduleMode::NodeInstanceId(node_id) => {
+                    if self
                         .node_instance_repository
-                        .get_all_workflow_instance_stand_by_nodes(workflow_instance.id)
-                        .await?;
+                        .is_all_same_entryment_nodes_success(node_id)
+                        .await?
+                    {
+                        let mut workflow_instance =
+                            self.workflow_instance_repository.get_by_node_id(node_id).await?;
 
-                    let stand_by_node_ids =
-                        stand_by_nodes.iter().map(|el| el.id.to_owned()).collect::<Vec<_>>();
+                        let mut node_instances = self
+                            .node_instance_repository
+                            .get_all_workflow_instance_nodes(workflow_instance.id)
+                            .await?;
+                        // 循环体的一次迭代完成后，未满足终止条件时实例化下一次迭代
+                        let iteration_node_instances =
+                            self.iterate_loops(&mut workflow_instance, &node_instances).await?;
+                        if !iteration_node_instances.is_empty() {
+                            for el in iteration_node_instances.iter() {
+                                self.node_instance_repository.insert(el.to_owned()).await?;
+                            }
+                            self.node_instance_repository.save_changed().await?;
+                            self.workflow_instance_repository
+                                .update(workflow_instance.to_owned())
+                                .await?;
+                            self.workflow_instance_repository.save_changed().await?;
+                            node_instances.extend(iteration_node_instances);
+                        }
+                        let condition_results =
+                            self.condition_results(&workflow_instance, &node_instances).await?;
+                        let mut skipped_node_ids = node_instances
+                            .iter()
+                            .filter(|el| {
+                                el.batch_parent_id.is_none()
+                                    && matches!(el.status, NodeInstanceStatus::Skipped)
+                            })
+                            .map(|el| el.id.to_owned())
+                            .collect::<HashSet<_>>();
+                        let mut stand_by_node_ids = node_instances
+                            .iter()
+                            .filter(|el| {
+                                el.batch_parent_id.is_none()
+                                    && matches!(el.status, NodeInstanceStatus::Standby)
+                            })
+                            .map(|el| el.id.to_owned())
+                            .collect::<Vec<_>>();
 
-                    let node_dependencies: Vec<(Uuid, Uuid)> = workflow_instance
-                        .spec
-                        .node_relations
-                        .iter()
-                        .filter(|el| el.from_id.ne(&node_id) && el.to_id.ne(&node_id))
-                        .map(|el| (el.from_id.to_owned(), el.to_id.to_owned()))
-                        .collect();
+                        // 所有依赖关系都不生效的节点被跳过，并继续影响其下游节点
+                        let mut newly_skipped_node_ids = vec![];
+                        loop {
+                            let skipped = stand_by_node_ids
+                                .iter()
+                                .filter(|el| {
+                                    let node_relations =
+                                        workflow_instance.node_dependency_relations(**el);
+                                    !node_relations.is_empty()
+                                        && node_relations.iter().all(|el| {
+                                            !Self::is_relation_active(
+                                                el,
+                                                &condition_results,
+                                                &skipped_node_ids,
+                                            )
+                                        })
+                                })
+                                .cloned()
+                                .collect::<Vec<_>>();
+                            if skipped.is_empty() {
+                                break;
+                            }
+                            stand_by_node_ids.retain(|el| !skipped.contains(el));
+                            skipped_node_ids.extend(skipped.iter().cloned());
+                            newly_skipped_node_ids.extend(skipped);
+                        }
+                        for skipped_node_id in newly_skipped_node_ids {
+                            let mut skipped_node_instances = self
+                                .node_instance_repository
+                                .get_node_sub_node_instances(skipped_node_id)
+                                .await?;
+                            skipped_node_instances.push(
+                                self.node_instance_repository
+                                    .get_by_id(&skipped_node_id.to_string())
+                                    .await?,
+                            );
+                            for mut el in skipped_node_instances.into_iter() {
+                                el.status = NodeInstanceStatus::Skipped;
+                                el.log = Some(
+                                    "Skipped because none of its upstream branches is taken."
+                                        .to_string(),
+                                );
+                                self.node_instance_repository.update(el).await?;
+                            }
+                        }
+                        self.node_instance_repository.save_changed().await?;
 
-                    let entry_node_ids =
-                        Self::find_entry_nodes_ids(&stand_by_node_ids, &node_dependencies).await;
+                        let entry_node_ids =
+                            Self::stand_by_entry_node_ids(&workflow_instance, &stand_by_node_ids)
+                                .await?;
 
-                    (workflow_instance, entry_node_ids)
-                } else {
-                    anyhow::bail!(
+                        (
+                            workflow_instance,
+                            entry_node_ids,
+                            condition_results,
+                            skipped_node_ids,
+                        )
+                    } else {
+                        anyhow::bail!(
                         "Nodes in the same batch of node with id: {node_id} has not been fully scheduled!"
                     );
+                    }
                 }
-            }
-        };
+            };
 
         // 如果没有入口节点，立即返回
         if entry_node_ids.is_empty() {
@@ -108,6 +191,8 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
         }
 
         let mut task_node_specs = vec![];
+        // 批量父节点只记录状态，由其子节点执行任务
+        let mut batch_parent_ids = vec![];
         let static_workflow_instance = workflow_instance.clone();
         let entry_nodes = workflow_instance
             .spec
@@ -119,7 +204,12 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
         // 遍历入口节点列表
         for entry_node in entry_nodes.into_iter() {
             // 获取包含该入口节点的依赖关系数据
-            let node_relations = static_workflow_instance.node_dependency_relations(entry_node.id);
+            // 未选中的条件分支与被跳过的节点不提供输入
+            let node_relations = static_workflow_instance
+                .node_dependency_relations(entry_node.id)
+                .into_iter()
+                .filter(|el| Self::is_relation_active(el, &condition_results, &skipped_node_ids))
+                .collect::<Vec<_>>();
 
             // 判断该入口节点是否为批量节点
             if entry_node.batch_strategies.is_empty() {
@@ -144,23 +234,34 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
                             // 更新依赖输出的输入插槽
                             match &mut to_slot.kind {
                                 NodeInputSlotKind::Text { contents, .. } => {
-                                    *contents = Some(vec![
-                                        from_slot
-                                            .all_tasks_text_outputs()?
-                                            .get(0)
-                                            .unwrap()
-                                            .to_owned();
-                                        1
-                                    ]);
+                                    let outputs = self
+                                        .successful_outputs(
+                                            from_node.id,
+                                            from_slot.all_tasks_text_outputs()?,
+                                        )
+                                        .await?;
+                                    let output = outputs.first().ok_or(anyhow::anyhow!(
+                                        "Node {} has no successful output on slot {}",
+                                        from_node.id,
+                                        from_slot.descriptor
+                                    ))?;
+                                    *contents = Some(vec![output.to_owned(); 1]);
                                 }
                                 NodeInputSlotKind::File { contents, .. } => {
+                                    let outputs = self
+                                        .successful_outputs(
+                                            from_node.id,
+                                            from_slot.all_tasks_file_outputs()?,
+                                        )
+                                        .await?;
+                                    let output = outputs.first().ok_or(anyhow::anyhow!(
+                                        "Node {} has no successful output on slot {}",
+                                        from_node.id,
+                                        from_slot.descriptor
+                                    ))?;
                                     *contents = Some(vec![
                                         FileInput {
-                                            file_metadata_id: from_slot
-                                                .all_tasks_file_outputs()?
-                                                .get(0)
-                                                .unwrap()
-                                                .to_owned(),
+                                            file_metadata_id: output.to_owned(),
                                             ..Default::default()
                                         };
                                         1
@@ -176,11 +277,24 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
                 // 对于批量节点
                 let entry_node2 = entry_node.to_owned();
                 task_node_specs.push(entry_node2.to_owned());
+                batch_parent_ids.push(entry_node2.id);
                 let entry_node_output_slots = &mut entry_node.output_slots;
-                // 对于分批后的每个批量节点
-                for (i, task_node) in
-                    self.debatch(&node_relations, &entry_node2).await?.iter().enumerate()
+                let task_nodes = self.debatch(&node_relations, &entry_node2).await?;
+                // 上游批量子任务失败时，多出的子节点不会执行
+                for mut sub_node_instance in self
+                    .node_instance_repository
+                    .get_node_sub_node_instances(entry_node2.id)
+                    .await?
+                    .into_iter()
+                    .filter(|el| !task_nodes.iter().any(|el2| el2.id.eq(&el.id)))
                 {
+                    sub_node_instance.status = NodeInstanceStatus::Stopped;
+                    sub_node_instance.log =
+                        Some("Skipped because the upstream sub task failed.".to_string());
+                    self.node_instance_repository.update(sub_node_instance).await?;
+                }
+                // 对于分批后的每个批量节点
+                for (i, task_node) in task_nodes.iter().enumerate() {
                     for entry_node_output_slot in entry_node_output_slots.iter_mut() {
                         let task_node_output_slot =
                             task_node.output_slot(&entry_node_output_slot.descriptor);
@@ -234,7 +348,8 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
         // 先 save_changed 所有改动的节点，再调任务分发
         self.node_instance_repository.save_changed().await?;
 
-        for task_node_spec in task_node_specs.iter() {
+        for task_node_spec in task_node_specs.iter().filter(|el| !batch_parent_ids.contains(&el.id))
+        {
             // 发送节点信息
             self.usecase_select_service.send_usecase(task_node_spec.to_owned()).await?;
         }
@@ -279,10 +394,16 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
         let mut workflow_instance =
             self.workflow_instance_repository.get_by_id(&id.to_string()).await?;
         workflow_instance.status = WorkflowInstanceStatus::Running;
-        let mut paused_node_instances = self
+        let node_instances = self
             .node_instance_repository
             .get_all_workflow_instance_nodes(workflow_instance.id)
-            .await?
+            .await?;
+        let queued_node_instance_ids = node_instances
+            .iter()
+            .filter(|el| matches!(el.status, NodeInstanceStatus::Queued))
+            .map(|el| el.id)
+            .collect::<Vec<_>>();
+        let mut paused_node_instances = node_instances
             .into_iter()
             .filter(|el| matches!(el.status, NodeInstanceStatus::Paused))
             .collect::<Vec<_>>();
@@ -301,6 +422,10 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
         }
         self.workflow_instance_repository.update(workflow_instance).await?;
         self.workflow_instance_repository.save_changed().await?;
+        // 暂停期间排队的节点不会被重新提交，恢复时重新检查配额
+        for queued_node_instance_id in queued_node_instance_ids {
+            self.retry_node(queued_node_instance_id).await?;
+        }
 
         Ok(())
     }
@@ -309,10 +434,20 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
         let mut workflow_instance =
             self.workflow_instance_repository.get_by_id(&id.to_string()).await?;
         workflow_instance.status = WorkflowInstanceStatus::Stopped;
-        let mut running_node_instances = self
+        let node_instances = self
             .node_instance_repository
             .get_all_workflow_instance_nodes(workflow_instance.id)
-            .await?
+            .await?;
+        // 排队中的节点还没有分发到集群，直接终止
+        for mut queued_node_instance in node_instances
+            .iter()
+            .filter(|el| matches!(el.status, NodeInstanceStatus::Queued))
+            .cloned()
+        {
+            queued_node_instance.status = NodeInstanceStatus::Stopped;
+            self.node_instance_repository.update(queued_node_instance).await?;
+        }
+        let mut running_node_instances = node_instances
             .into_iter()
             .filter(|el| {
                 if let NodeInstanceStatus::Running = el.status {
@@ -341,6 +476,83 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
         Ok(())
     }
 
+    async fn retry_node(&self, node_instance_id: Uuid) -> anyhow::Result<()> {
+        let mut node_instance =
+            self.node_instance_repository.get_by_id(&node_instance_id.to_string()).await?;
+        let mut workflow_instance = self
+            .workflow_instance_repository
+            .get_by_id(&node_instance.flow_instance_id.to_string())
+            .await?;
+        // 等待重试或排队期间工作流可能已被暂停或终止
+        if !matches!(workflow_instance.status, WorkflowInstanceStatus::Running)
+            || !matches!(
+                node_instance.status,
+                NodeInstanceStatus::Pending | NodeInstanceStatus::Queued
+            )
+        {
+            return Ok(());
+        }
+
+        let node_spec = match node_instance.batch_parent_id {
+            None => workflow_instance.spec.node(node_instance.id).to_owned(),
+            Some(parent_id) => {
+                // 批量子节点的 spec 不保存在工作流实例中，需要重新分批得到
+                let parent = workflow_instance.spec.node(parent_id);
+                let index = node_instance.batch_index.ok_or(anyhow::anyhow!(
+                    "Node instance {} is not a sub node of {parent_id}",
+                    node_instance.id
+                ))?;
+                let node_relations = workflow_instance.node_dependency_relations(parent_id);
+                let mut sub_node = self
+                    .debatch(&node_relations, parent)
+                    .await?
+                    .into_iter()
+                    .find(|el| el.id.eq(&node_instance.id))
+                    .ok_or(anyhow::anyhow!(
+                        "Unable to debatch sub node {} from {parent_id}",
+                        node_instance.id
+                    ))?;
+                sub_node.restore_sub_node_output_slots(parent, index)?;
+                sub_node
+            }
+        };
+
+        node_instance.status = NodeInstanceStatus::Running;
+        node_instance.retry_at = None;
+        self.node_instance_repository.update(node_instance.to_owned()).await?;
+        self.node_instance_repository.save_changed().await?;
+
+        if let Err(e) = self.usecase_select_service.send_usecase(node_spec).await {
+            node_instance.status = NodeInstanceStatus::Error;
+            node_instance.log = Some(format!("Failed to resubmit node instance: {e}"));
+            self.node_instance_repository.update(node_instance).await?;
+            self.node_instance_repository.save_changed().await?;
+            workflow_instance.status = WorkflowInstanceStatus::Error;
+            self.workflow_instance_repository.update(workflow_instance).await?;
+            self.workflow_instance_repository.save_changed().await?;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    async fn retry_due_nodes(&self) -> anyhow::Result<()> {
+        let node_instances = self
+            .node_instance_repository
+            .get_all_due_retry_node_instances(Utc::now())
+            .await?;
+        let mut errors = vec![];
+        for node_instance in node_instances {
+            if let Err(e) = self.retry_node(node_instance.id).await {
+                errors.push(format!("{}: {e}", node_instance.id));
+            }
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("Failed to retry node instances: {}", errors.join("; "));
+        }
+        Ok(())
+    }
+
     /// （√）
     async fn debatch(
         &self,
@@ -368,6 +580,194 @@ impl IWorkflowScheduleService for WorkflowScheduleService {
 }
 
 impl WorkflowScheduleService {
+    /// 获取节点输出插槽上成功执行的任务的输出，普通节点直接返回所有输出
+    ///
+    /// # 参数
+    ///
+    /// * `node_id` - 提供输出的节点 id
+    /// * `outputs` - 输出插槽上所有任务的输出
+    async fn successful_outputs(
+        &self,
+        node_id: Uuid,
+        outputs: &[Uuid],
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let sub_node_instances =
+            self.node_instance_repository.get_node_sub_node_instances(node_id).await?;
+        if sub_node_instances.is_empty() {
+            return Ok(outputs.to_vec());
+        }
+        // 按子任务次序而不是查询结果的次序对应输出
+        let finished = sub_node_instances
+            .iter()
+            .filter(|el| matches!(el.status, NodeInstanceStatus::Finished))
+            .filter_map(|el| el.batch_index)
+            .collect::<HashSet<_>>();
+        Ok(outputs
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| finished.contains(index))
+            .map(|(_, output)| output.to_owned())
+            .collect())
+    }
+
+    /// 检查所有未结束的循环，当前迭代的节点全部完成时求值终止条件
+    /// 未满足终止条件且未达到最大迭代次数时实例化下一次迭代，返回新迭代的节点实例
+    async fn iterate_loops(
+        &self,
+        workflow_instance: &mut WorkflowInstance,
+        node_instances: &[NodeInstance],
+    ) -> anyhow::Result<Vec<NodeInstance>> {
+        let mut iteration_node_instances = vec![];
+        for loop_index in 0..workflow_instance.spec.loops.len() {
+            let node_loop = &workflow_instance.spec.loops[loop_index];
+            if node_loop.completion.is_some() {
+                continue;
+            }
+            let iteration_completed = node_loop.current_iteration().iter().all(|el| {
+                node_instances.iter().any(|el2| {
+                    el2.id.eq(el)
+                        && matches!(
+                            el2.status,
+                            NodeInstanceStatus::Finished | NodeInstanceStatus::Skipped
+                        )
+                })
+            });
+            if !iteration_completed {
+                continue;
+            }
+
+            let until = &node_loop.until;
+            let until_node_id = node_loop.current_node_id(until.node_id).ok_or(anyhow::anyhow!(
+                "Loop condition node {} is out of the loop body.",
+                until.node_id
+            ))?;
+            let key = workflow_instance
+                .spec
+                .node(until_node_id)
+                .output_slot(&until.slot)
+                .all_tasks_text_outputs()?
+                .first()
+                .ok_or(anyhow::anyhow!(
+                    "Node {until_node_id} has no text output on slot {}",
+                    until.slot
+                ))?
+                .to_owned();
+            let text = self.text_storage_repository.get_by_id(&key.to_string()).await?.value;
+            let converged =
+                until.condition.evaluate(&HashMap::from([(until.slot.to_owned(), text)]))?;
+
+            if converged {
+                workflow_instance.spec.loops[loop_index].completion =
+                    Some(LoopCompletion::Converged);
+            } else if node_loop.current_iteration_count() >= node_loop.max_iterations {
+                workflow_instance.spec.loops[loop_index].completion =
+                    Some(LoopCompletion::MaxIterationsReached);
+            } else {
+                iteration_node_instances
+                    .extend(workflow_instance.instantiate_loop_iteration(loop_index)?);
+            }
+        }
+        Ok(iteration_node_instances)
+    }
+
+    /// 获取已完成的条件节点的求值结果，结果从条件节点的结果输出插槽读取
+    async fn condition_results(
+        &self,
+        workflow_instance: &WorkflowInstance,
+        node_instances: &[NodeInstance],
+    ) -> anyhow::Result<HashMap<Uuid, bool>> {
+        let mut condition_results = HashMap::new();
+        for node_spec in workflow_instance
+            .spec
+            .node_specs
+            .iter()
+            .filter(|el| matches!(el.kind, NodeKind::Condition { .. }))
+        {
+            let is_finished = node_instances.iter().any(|el| {
+                el.id.eq(&node_spec.id) && matches!(el.status, NodeInstanceStatus::Finished)
+            });
+            if !is_finished {
+                continue;
+            }
+            let key = node_spec
+                .output_slots
+                .iter()
+                .find(|el| el.descriptor.eq(Condition::RESULT_SLOT))
+                .ok_or(anyhow::anyhow!(
+                    "Condition node {} has no output slot {}.",
+                    node_spec.id,
+                    Condition::RESULT_SLOT
+                ))?
+                .all_tasks_text_outputs()?
+                .first()
+                .ok_or(anyhow::anyhow!(
+                    "Condition node {} has no text output on slot {}.",
+                    node_spec.id,
+                    Condition::RESULT_SLOT
+                ))?
+                .to_owned();
+            let text = self.text_storage_repository.get_by_id(&key.to_string()).await?.value;
+            let result = text.trim().parse::<bool>().map_err(|e| {
+                anyhow::anyhow!(
+                    "Condition node {} has invalid result {text}: {e}",
+                    node_spec.id
+                )
+            })?;
+            condition_results.insert(node_spec.id, result);
+        }
+        Ok(condition_results)
+    }
+
+    /// 依赖关系是否生效
+    /// 出节点被跳过，或出节点为条件节点且结果与关系的分支不同时，该关系不生效
+    fn is_relation_active(
+        node_relation: &NodeRelation,
+        condition_results: &HashMap<Uuid, bool>,
+        skipped_node_ids: &HashSet<Uuid>,
+    ) -> bool {
+        if skipped_node_ids.contains(&node_relation.from_id) {
+            return false;
+        }
+        match (
+            condition_results.get(&node_relation.from_id),
+            node_relation.branch,
+        ) {
+            (Some(result), Some(branch)) => result.eq(&branch),
+            _ => true,
+        }
+    }
+
+    /// 待命中的节点里可以调度的节点
+    ///
+    /// 只有仍在待命中的上游节点会阻塞节点执行；
+    /// 循环体外部依赖循环体节点的节点要等循环结束后才能调度，否则会读到中间迭代的输出
+    async fn stand_by_entry_node_ids(
+        workflow_instance: &WorkflowInstance,
+        stand_by_node_ids: &[Uuid],
+    ) -> anyhow::Result<Vec<Uuid>> {
+        let node_dependencies: Vec<(Uuid, Uuid)> = workflow_instance
+            .spec
+            .node_relations
+            .iter()
+            .filter(|el| {
+                stand_by_node_ids.contains(&el.from_id) && stand_by_node_ids.contains(&el.to_id)
+            })
+            .map(|el| (el.from_id.to_owned(), el.to_id.to_owned()))
+            .collect();
+        let (waiting_node_ids, entry_node_ids): (Vec<_>, Vec<_>) =
+            Self::find_entry_nodes_ids(stand_by_node_ids, &node_dependencies)
+                .await
+                .into_iter()
+                .partition(|el| workflow_instance.waits_for_unfinished_loop(*el));
+        // 此时没有运行中的节点，若只剩等待循环的节点，循环已无法继续迭代
+        if entry_node_ids.is_empty() && !waiting_node_ids.is_empty() {
+            anyhow::bail!(
+                "Nodes {waiting_node_ids:?} are waiting for loops that can't make progress."
+            );
+        }
+        Ok(entry_node_ids)
+    }
+
     /// 传入节点 id 集合、节点依赖关系 id 集合，获得一批入口节点 id
     async fn find_entry_nodes_ids(
         node_ids: &[Uuid],
@@ -566,12 +966,17 @@ impl WorkflowScheduleService {
                     self.workflow_instance_repository.get_by_node_id(in_node_id).await?;
                 let in_node_spec = workflow_instance.spec.node(in_node_id);
                 let output_slot = in_node_spec.output_slot(&from_slot_descriptor);
+                // 只使用成功的子任务的输出
                 match &output_slot.kind {
                     NodeSpecOutputSlotKind::File {
                         all_tasks_prepared_content_ids,
                         ..
                     } => {
-                        for tasks_prepared_content_id in all_tasks_prepared_content_ids.iter() {
+                        for tasks_prepared_content_id in self
+                            .successful_outputs(in_node_id, all_tasks_prepared_content_ids)
+                            .await?
+                            .iter()
+                        {
                             result.push(Input::File(FileInput {
                                 file_metadata_id: tasks_prepared_content_id.to_owned(),
                                 file_metadata_name: String::default(),
@@ -584,7 +989,11 @@ impl WorkflowScheduleService {
                         all_tasks_prepared_text_keys,
                         ..
                     } => {
-                        for task_prepared_text_key in all_tasks_prepared_text_keys.iter() {
+                        for task_prepared_text_key in self
+                            .successful_outputs(in_node_id, all_tasks_prepared_text_keys)
+                            .await?
+                            .iter()
+                        {
                             result.push(Input::Text(task_prepared_text_key.to_owned()))
                         }
                     }
@@ -684,4 +1093,296 @@ mod tests {
             .await
             .unwrap();
     }
+
+    #[tokio::test]
+    async fn test_inactive_condition_branch() {
+        let (condition_id, taken_id, not_taken_id) =
+            (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut workflow_instance = WorkflowInstance::default();
+        workflow_instance.spec.node_specs = vec![NodeSpec {
+            id: condition_id,
+            kind: NodeKind::Condition {
+                data: Condition {
+                    expression: "energy < 0".to_string(),
+                },
+            },
+            output_slots: vec![NodeSpecOutputSlot {
+                kind: NodeSpecOutputSlotKind::Text {
+                    all_tasks_prepared_text_keys: vec![Uuid::new_v4()],
+                },
+                descriptor: Condition::RESULT_SLOT.to_string(),
+                description: None,
+                optional: true,
+            }],
+            ..Default::default()
+        }];
+        let node_instances = vec![NodeInstance {
+            id: condition_id,
+            status: NodeInstanceStatus::Finished,
+            // 日志不作为求值结果
+            log: Some("true".to_string()),
+            ..Default::default()
+        }];
+        let condition_results = loop_schedule_service("false")
+            .condition_results(&workflow_instance, &node_instances)
+            .await
+            .unwrap();
+        assert_eq!(condition_results.get(&condition_id), Some(&false));
+
+        let relation = |to_id, branch| NodeRelation {
+            from_id: condition_id,
+            to_id,
+            branch,
+            ..Default::default()
+        };
+        let skipped_node_ids = HashSet::new();
+        assert!(WorkflowScheduleService::is_relation_active(
+            &relation(taken_id, Some(false)),
+            &condition_results,
+            &skipped_node_ids
+        ));
+        assert!(!WorkflowScheduleService::is_relation_active(
+            &relation(not_taken_id, Some(true)),
+            &condition_results,
+            &skipped_node_ids
+        ));
+        assert!(WorkflowScheduleService::is_relation_active(
+            &relation(not_taken_id, None),
+            &condition_results,
+            &skipped_node_ids
+        ));
+        assert!(!WorkflowScheduleService::is_relation_active(
+            &relation(taken_id, Some(false)),
+            &condition_results,
+            &HashSet::from([condition_id])
+        ));
+    }
+
+    fn loop_schedule_service(residual: &'static str) -> WorkflowScheduleService {
+        let mut text_storage_repository = MockTextStorageRepository::new();
+        text_storage_repository.expect_get_by_id().returning(move |key| {
+            Ok(TextStorage {
+                key: Some(Uuid::from_str(key).unwrap()),
+                value: residual.to_string(),
+            })
+        });
+        WorkflowScheduleServiceBuilder::default()
+            .text_storage_repository(Arc::new(text_storage_repository))
+            .node_instance_repository(Arc::new(MockNodeInstanceRepository::new()))
+            .workflow_instance_repository(Arc::new(MockWorkflowInstanceRepository::new()))
+            .usecase_select_service(Arc::new(MockUsecaseSelectService::new()))
+            .file_move_service(Arc::new(MockFileMoveService::new()))
+            .download_service(Arc::new(MockStorageServerDownloadDispatcherService::new()))
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_loop_iterates_until_converged() {
+        let (upstream_id, solver_id, downstream_id) =
+            (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let text_relation = |from_id, to_id, from_slot: &str, to_slot: &str| NodeRelation {
+            from_id,
+            to_id,
+            slot_relations: vec![SlotRelation {
+                from_slot: from_slot.to_string(),
+                to_slot: to_slot.to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let node = |id, name: &str| NodeSpec {
+            id,
+            name: name.to_string(),
+            output_slots: vec![NodeSpecOutputSlot {
+                kind: NodeSpecOutputSlotKind::Text {
+                    all_tasks_prepared_text_keys: vec![Uuid::new_v4()],
+                },
+                descriptor: "residual".to_string(),
+                description: None,
+                optional: false,
+            }],
+            ..Default::default()
+        };
+        let spec = WorkflowInstanceSpec {
+            node_specs: vec![
+                node(upstream_id, "upstream"),
+                node(solver_id, "solver"),
+                node(downstream_id, "downstream"),
+            ],
+            node_relations: vec![
+                text_relation(upstream_id, solver_id, "residual", "guess"),
+                text_relation(solver_id, downstream_id, "residual", "residual"),
+            ],
+            loops: vec![NodeLoop {
+                node_ids: vec![solver_id],
+                carried_relations: vec![text_relation(solver_id, solver_id, "residual", "guess")],
+                until: LoopCondition {
+                    node_id: solver_id,
+                    slot: "residual".to_string(),
+                    condition: Condition {
+                        expression: "residual < 0.01".to_string(),
+                    },
+                },
+                max_iterations: 3,
+                iterations: vec![vec![solver_id]],
+                completion: None,
+            }],
+            ..Default::default()
+        };
+        let mut workflow_instance = WorkflowInstance {
+            spec,
+            ..Default::default()
+        };
+        let finished = |id| NodeInstance {
+            id,
+            status: NodeInstanceStatus::Finished,
+            ..Default::default()
+        };
+
+        let node_instances = loop_schedule_service("0.5")
+            .iterate_loops(
+                &mut workflow_instance,
+                &[finished(upstream_id), finished(solver_id)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(node_instances.len(), 1);
+        let second_id = node_instances[0].id;
+        assert_ne!(second_id, solver_id);
+        assert_eq!(node_instances[0].name, "solver_iteration_2");
+        assert_eq!(node_instances[0].status, NodeInstanceStatus::Standby);
+        assert_eq!(
+            workflow_instance.spec.loops[0].iterations,
+            vec![vec![solver_id], vec![second_id]]
+        );
+        let from_ids = workflow_instance
+            .node_dependency_relations(second_id)
+            .iter()
+            .map(|el| el.from_id)
+            .collect::<Vec<_>>();
+        assert_eq!(from_ids, vec![solver_id]);
+        let from_ids = workflow_instance
+            .node_dependency_relations(downstream_id)
+            .iter()
+            .map(|el| el.from_id)
+            .collect::<Vec<_>>();
+        assert_eq!(from_ids, vec![second_id]);
+
+        let node_instances = loop_schedule_service("0.001")
+            .iterate_loops(
+                &mut workflow_instance,
+                &[
+                    finished(upstream_id),
+                    finished(solver_id),
+                    finished(second_id),
+                ],
+            )
+            .await
+            .unwrap();
+        assert!(node_instances.is_empty());
+        assert_eq!(
+            workflow_instance.spec.loops[0].completion,
+            Some(LoopCompletion::Converged)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_loop_body_fan_out_waits_for_convergence() {
+        let (prepare_id, solve_id, report_id, summary_id) = (
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+        );
+        let relation = |from_id, to_id| NodeRelation {
+            from_id,
+            to_id,
+            ..Default::default()
+        };
+        let mut workflow_instance = WorkflowInstance {
+            spec: WorkflowInstanceSpec {
+                node_relations: vec![
+                    relation(prepare_id, solve_id),
+                    // 循环体外部的节点依赖循环体中非最后一个节点
+                    relation(prepare_id, report_id),
+                    relation(solve_id, summary_id),
+                ],
+                loops: vec![NodeLoop {
+                    node_ids: vec![prepare_id, solve_id],
+                    max_iterations: 3,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // 第一次迭代的 prepare 完成后只能调度 solve
+        let entry_node_ids = WorkflowScheduleService::stand_by_entry_node_ids(
+            &workflow_instance,
+            &[solve_id, report_id, summary_id],
+        )
+        .await
+        .unwrap();
+        assert_eq!(entry_node_ids, vec![solve_id]);
+
+        // 循环无法继续时不能把只剩等待循环的节点当作工作流结束
+        assert!(WorkflowScheduleService::stand_by_entry_node_ids(
+            &workflow_instance,
+            &[report_id, summary_id],
+        )
+        .await
+        .is_err());
+
+        workflow_instance.spec.loops[0].completion = Some(LoopCompletion::Converged);
+        let mut entry_node_ids = WorkflowScheduleService::stand_by_entry_node_ids(
+            &workflow_instance,
+            &[report_id, summary_id],
+        )
+        .await
+        .unwrap();
+        entry_node_ids.sort();
+        let mut expected = vec![report_id, summary_id];
+        expected.sort();
+        assert_eq!(entry_node_ids, expected);
+    }
+
+    #[tokio::test]
+    async fn test_successful_outputs_follow_batch_index() {
+        let parent_id = Uuid::new_v4();
+        let sub_node = move |batch_index, status| NodeInstance {
+            id: Uuid::new_v4(),
+            batch_parent_id: Some(parent_id),
+            batch_index: Some(batch_index),
+            status,
+            ..Default::default()
+        };
+        let mut node_instance_repository = MockNodeInstanceRepository::new();
+        node_instance_repository
+            .expect_get_node_sub_node_instances()
+            .returning(move |_| {
+                // 查询结果的次序与子任务次序不同
+                Ok(vec![
+                    sub_node(2, NodeInstanceStatus::Finished),
+                    sub_node(0, NodeInstanceStatus::Error),
+                    sub_node(1, NodeInstanceStatus::Finished),
+                ])
+            });
+        let service = WorkflowScheduleServiceBuilder::default()
+            .text_storage_repository(Arc::new(MockTextStorageRepository::new()))
+            .node_instance_repository(Arc::new(node_instance_repository))
+            .workflow_instance_repository(Arc::new(MockWorkflowInstanceRepository::new()))
+            .usecase_select_service(Arc::new(MockUsecaseSelectService::new()))
+            .file_move_service(Arc::new(MockFileMoveService::new()))
+            .download_service(Arc::new(MockStorageServerDownloadDispatcherService::new()))
+            .build()
+            .unwrap();
+
+        let outputs = [Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        assert_eq!(
+            service.successful_outputs(parent_id, &outputs).await.unwrap(),
+            vec![outputs[1], outputs[2]]
+        );
+    }
 }
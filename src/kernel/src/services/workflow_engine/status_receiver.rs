@@ -1,5 +1,6 @@
 use crate::prelude::*;
 use alice_architecture::IMessageQueueProducerTemplate;
+use chrono::Utc;
 use std::sync::Arc;
 
 #[derive(Builder)]
@@ -7,6 +8,7 @@ pub struct WorkflowStatusReceiverService {
     node_instance_repository: Arc<dyn INodeInstanceRepository + Send + Sync>,
     workflow_instance_repository: Arc<dyn IWorkflowInstanceRepository + Send + Sync>,
     schedule_service: Arc<dyn IWorkflowScheduleService + Send + Sync>,
+    quota_service: Arc<dyn IQuotaService + Send + Sync>,
     mq_producer: Arc<dyn IMessageQueueProducerTemplate<NodeInstanceId> + Send + Sync>,
     bill_topic: String,
 }
@@ -22,6 +24,10 @@ impl IWorkflowStatusReceiverService for WorkflowStatusReceiverService {
     async fn receive_node_status(&self, result: TaskResult) -> anyhow::Result<()> {
         let mut node_instance =
             self.node_instance_repository.get_by_id(&result.id.to_string()).await?;
+        let failure_kind = match result.status {
+            TaskResultStatus::Failed => Some(TaskFailureKind::from_message(&result.message)),
+            _ => None,
+        };
         node_instance.log = Some(result.message);
         node_instance.resource_meter = result.used_resources;
         node_instance.status = match result.status {
@@ -31,13 +37,60 @@ impl IWorkflowStatusReceiverService for WorkflowStatusReceiverService {
             TaskResultStatus::Failed => NodeInstanceStatus::Error,
             TaskResultStatus::Deleted => NodeInstanceStatus::Stopped,
         };
+        if let TaskResultStatus::Success | TaskResultStatus::Failed = result.status {
+            node_instance.attempts.push(NodeAttempt {
+                attempt: node_instance.attempts.len() + 1,
+                cluster_id: node_instance.cluster_id,
+                status: node_instance.status.to_owned(),
+                failure_kind: failure_kind.to_owned(),
+                log: node_instance.log.to_owned(),
+                resource_meter: node_instance.resource_meter.to_owned(),
+                finished_time: Utc::now(),
+            });
+        }
+        // 节点结束后释放占用的配额，有配额被释放时排队的节点可能可以重新提交
+        // 配额由仓储原子地取出，重复或并发到达的结束消息只会释放一次
+        let quota_released = if let TaskResultStatus::Success
+        | TaskResultStatus::Failed
+        | TaskResultStatus::Deleted = result.status
+        {
+            node_instance.quota_reservation = None;
+            match self.node_instance_repository.take_quota_reservation(result.id).await? {
+                Some(reservation) => {
+                    let used_storage =
+                        node_instance.resource_meter.as_ref().map(|el| el.storage).unwrap_or(0);
+                    self.quota_service.release(&node_instance, &reservation, used_storage).await?;
+                    true
+                }
+                None => false,
+            }
+        } else {
+            false
+        };
+        let retry_backoff = match &failure_kind {
+            Some(kind) => self.retry_backoff(&node_instance, kind).await?,
+            None => None,
+        };
+        if let Some(seconds) = retry_backoff {
+            // 等待重新提交，重试时间保存在节点实例上，服务重启后仍会由调度服务重新提交
+            node_instance.status = NodeInstanceStatus::Pending;
+            if seconds > 0 {
+                node_instance.retry_at =
+                    Some(Utc::now() + chrono::Duration::seconds(seconds as i64));
+            }
+        }
         self.node_instance_repository.update(node_instance.to_owned()).await?;
         self.node_instance_repository.save_changed().await?;
 
         if let TaskResultStatus::Success = result.status {
-            self.schedule_service
-                .schedule_next_nodes(ScheduleMode::NodeInstanceId(result.id))
-                .await?;
+            match node_instance.batch_parent_id {
+                Some(parent_id) => self.settle_batch(parent_id).await?,
+                None => {
+                    self.schedule_service
+                        .schedule_next_nodes(ScheduleMode::NodeInstanceId(result.id))
+                        .await?
+                }
+            }
             self.mq_producer
                 .send_object(
                     &NodeInstanceId {
@@ -46,13 +99,23 @@ impl IWorkflowStatusReceiverService for WorkflowStatusReceiverService {
                     Some(&self.bill_topic),
                 )
                 .await?;
+        } else if let (TaskResultStatus::Failed, Some(seconds)) = (&result.status, retry_backoff) {
+            // 有退避时间的节点由 retry_due_nodes 在到达重试时间后重新提交
+            if seconds == 0 {
+                self.schedule_service.retry_node(result.id).await?;
+            }
         } else if let TaskResultStatus::Failed = result.status {
-            let mut workflow_instance = self
-                .workflow_instance_repository
-                .get_by_id(&node_instance.flow_instance_id.to_string())
-                .await?;
-            workflow_instance.status = WorkflowInstanceStatus::Error;
-            self.workflow_instance_repository.update(workflow_instance).await?;
+            match node_instance.batch_parent_id {
+                Some(parent_id) => self.settle_batch(parent_id).await?,
+                None => {
+                    let mut workflow_instance = self
+                        .workflow_instance_repository
+                        .get_by_id(&node_instance.flow_instance_id.to_string())
+                        .await?;
+                    workflow_instance.status = WorkflowInstanceStatus::Error;
+                    self.workflow_instance_repository.update(workflow_instance).await?;
+                }
+            }
         } else if let TaskResultStatus::Paused = result.status {
             let mut workflow_instance = self
                 .workflow_instance_repository
@@ -90,8 +153,125 @@ impl IWorkflowStatusReceiverService for WorkflowStatusReceiverService {
         }
 
         self.workflow_instance_repository.save_changed().await?;
+        if quota_released {
+            self.resubmit_queued_nodes(node_instance.flow_instance_id).await?;
+        }
+        Ok(())
+    }
+}
+
+impl WorkflowStatusReceiverService {
+    /// 重新提交同一用户因配额不足排队的节点，仍然不足的节点会继续排队
+    ///
+    /// # 参数
+    ///
+    /// * `workflow_instance_id` - 释放配额的节点所在的工作流实例 id
+    async fn resubmit_queued_nodes(&self, workflow_instance_id: Uuid) -> anyhow::Result<()> {
+        let user_id = self
+            .workflow_instance_repository
+            .get_by_id(&workflow_instance_id.to_string())
+            .await?
+            .user_id;
+        let queued_node_instances =
+            self.node_instance_repository.get_user_queued_node_instances(user_id).await?;
+        for queued_node_instance in queued_node_instances {
+            // 重新提交失败时 retry_node 会将节点与工作流置为出错
+            let _ = self.schedule_service.retry_node(queued_node_instance.id).await;
+        }
         Ok(())
     }
+
+    /// 批量子节点结束后，按批量失败策略结算批量父节点
+    /// 失败超出容忍范围时父节点与工作流出错，整批结束且失败在容忍范围内时父节点完成并调度下一组节点
+    ///
+    /// # 参数
+    ///
+    /// * `parent_id` - 批量父节点 id
+    async fn settle_batch(&self, parent_id: Uuid) -> anyhow::Result<()> {
+        let mut parent = self.node_instance_repository.get_by_id(&parent_id.to_string()).await?;
+        // 父节点已经结算过
+        if !matches!(parent.status, NodeInstanceStatus::Running) {
+            return Ok(());
+        }
+        let mut workflow_instance = self
+            .workflow_instance_repository
+            .get_by_id(&parent.flow_instance_id.to_string())
+            .await?;
+        let sub_node_instances =
+            self.node_instance_repository.get_node_sub_node_instances(parent_id).await?;
+        let failed = sub_node_instances
+            .iter()
+            .filter(|el| matches!(el.status, NodeInstanceStatus::Error))
+            .collect::<Vec<_>>();
+        let tolerated = workflow_instance
+            .spec
+            .node(parent_id)
+            .tolerates_batch_failures(failed.len(), sub_node_instances.len());
+        let unsettled = sub_node_instances.iter().any(|el| {
+            !matches!(
+                el.status,
+                NodeInstanceStatus::Finished
+                    | NodeInstanceStatus::Error
+                    | NodeInstanceStatus::Stopped
+            )
+        });
+        if tolerated && unsettled {
+            return Ok(());
+        }
+
+        let mut summary = vec![format!(
+            "{} of {} sub tasks failed.",
+            failed.len(),
+            sub_node_instances.len()
+        )];
+        summary.extend(failed.iter().map(|el| format!("{} ({})", el.name, el.id)));
+        parent.log = Some(summary.join("\n"));
+        parent.status = if tolerated {
+            NodeInstanceStatus::Finished
+        } else {
+            NodeInstanceStatus::Error
+        };
+        self.node_instance_repository.update(parent).await?;
+        self.node_instance_repository.save_changed().await?;
+
+        if tolerated {
+            self.schedule_service
+                .schedule_next_nodes(ScheduleMode::NodeInstanceId(parent_id))
+                .await?;
+        } else {
+            workflow_instance.status = WorkflowInstanceStatus::Error;
+            self.workflow_instance_repository.update(workflow_instance).await?;
+        }
+        Ok(())
+    }
+
+    /// 节点失败后需要重试时，返回重新提交前需要等待的秒数
+    ///
+    /// # 参数
+    ///
+    /// * `node_instance` - 已记录本次执行的节点实例
+    /// * `kind` - 本次执行的失败种类
+    async fn retry_backoff(
+        &self,
+        node_instance: &NodeInstance,
+        kind: &TaskFailureKind,
+    ) -> anyhow::Result<Option<u64>> {
+        let workflow_instance = self
+            .workflow_instance_repository
+            .get_by_id(&node_instance.flow_instance_id.to_string())
+            .await?;
+        // 批量子节点使用父节点的重试策略
+        let node_id = node_instance.batch_parent_id.unwrap_or(node_instance.id);
+        let attempt = node_instance.attempts.len();
+        Ok(workflow_instance
+            .spec
+            .node_specs
+            .iter()
+            .find(|el| el.id.eq(&node_id))
+            .and_then(|el| el.retry_policy.as_ref())
+            .filter(|el| el.should_retry(attempt, kind))
+            .map(|el| el.backoff_seconds(attempt)))
+    }
 }
 
 #[cfg(test)]
@@ -146,6 +326,7 @@ mod tests {
                 .node_instance_repository(json_repository.clone())
                 .workflow_instance_repository(json_repository)
                 .schedule_service(schedule_service)
+                .quota_service(Arc::new(quota_service()))
                 .build()
                 .unwrap(),
         )
@@ -177,4 +358,336 @@ mod tests {
             .await
             .unwrap()
     }
+
+    fn quota_service() -> MockQuotaService {
+        let mut quota_service = MockQuotaService::new();
+        quota_service.expect_release().returning(|_, _, _| Ok(()));
+        quota_service
+    }
+
+    struct NoopProducer;
+
+    #[async_trait]
+    impl IMessageQueueProducerTemplate<NodeInstanceId> for NoopProducer {
+        async fn send_object(&self, _: &NodeInstanceId, _: Option<&str>) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn retry_receiver(
+        attempts: usize,
+        backoff: RetryBackoff,
+        expect_retry: bool,
+    ) -> (WorkflowStatusReceiverService, TaskResult) {
+        let delayed = !matches!(backoff, RetryBackoff::Immediate);
+        let node_id = Uuid::new_v4();
+        let mut workflow_instance = WorkflowInstance {
+            status: WorkflowInstanceStatus::Running,
+            ..Default::default()
+        };
+        workflow_instance.spec.node_specs.push(NodeSpec {
+            id: node_id,
+            retry_policy: Some(RetryPolicy {
+                max_attempts: 2,
+                backoff,
+                retry_on: vec![TaskFailureKind::NodeFail],
+            }),
+            ..Default::default()
+        });
+        let node_instance = NodeInstance {
+            id: node_id,
+            flow_instance_id: workflow_instance.id,
+            status: NodeInstanceStatus::Running,
+            attempts: vec![
+                NodeAttempt {
+                    attempt: 1,
+                    cluster_id: None,
+                    status: NodeInstanceStatus::Error,
+                    failure_kind: Some(TaskFailureKind::NodeFail),
+                    log: None,
+                    resource_meter: None,
+                    finished_time: Utc::now(),
+                };
+                attempts
+            ],
+            ..Default::default()
+        };
+
+        let mut node_instance_repository = MockNodeInstanceRepository::new();
+        node_instance_repository.expect_take_quota_reservation().returning(|_| Ok(None));
+        node_instance_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(node_instance.clone()));
+        node_instance_repository
+            .expect_update()
+            .withf(move |el| {
+                el.attempts.len() == attempts + 1
+                    && el.retry_at.is_some() == (expect_retry && delayed)
+                    && el.status.eq(&if expect_retry {
+                        NodeInstanceStatus::Pending
+                    } else {
+                        NodeInstanceStatus::Error
+                    })
+            })
+            .times(1)
+            .returning(Ok);
+        node_instance_repository.expect_save_changed().returning(|| Ok(true));
+        let mut workflow_instance_repository = MockWorkflowInstanceRepository::new();
+        workflow_instance_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(workflow_instance.clone()));
+        workflow_instance_repository
+            .expect_update()
+            .withf(|el| matches!(el.status, WorkflowInstanceStatus::Error))
+            .times(usize::from(!expect_retry))
+            .returning(Ok);
+        workflow_instance_repository.expect_save_changed().returning(|| Ok(true));
+        let mut schedule_service = MockWorkflowScheduleService::new();
+        schedule_service
+            .expect_retry_node()
+            .withf(move |el| el.eq(&node_id))
+            .times(usize::from(expect_retry && !delayed))
+            .returning(|_| Ok(()));
+
+        let receiver = WorkflowStatusReceiverServiceBuilder::default()
+            .node_instance_repository(Arc::new(node_instance_repository))
+            .workflow_instance_repository(Arc::new(workflow_instance_repository))
+            .schedule_service(Arc::new(schedule_service))
+            .quota_service(Arc::new(quota_service()))
+            .mq_producer(Arc::new(NoopProducer))
+            .bill_topic(String::default())
+            .build()
+            .unwrap();
+        let result = TaskResult {
+            id: node_id,
+            status: TaskResultStatus::Failed,
+            message: format!("{}NODE_FAIL", TaskFailureKind::SCHEDULER_STATE_PREFIX),
+            used_resources: None,
+        };
+        (receiver, result)
+    }
+
+    #[tokio::test]
+    async fn test_failed_node_is_retried() {
+        let (receiver, result) = retry_receiver(0, RetryBackoff::Immediate, true);
+        receiver.receive_node_status(result).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_node_waits_for_retry_at() {
+        let (receiver, result) = retry_receiver(0, RetryBackoff::Fixed { seconds: 60 }, true);
+        receiver.receive_node_status(result).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_node_errors_when_retries_exhausted() {
+        let (receiver, result) = retry_receiver(1, RetryBackoff::Immediate, false);
+        receiver.receive_node_status(result).await.unwrap();
+    }
+
+    fn batch_receiver(
+        failure_policy: BatchFailurePolicy,
+        expect_tolerated: bool,
+    ) -> (WorkflowStatusReceiverService, TaskResult) {
+        let parent_id = Uuid::new_v4();
+        let mut workflow_instance = WorkflowInstance {
+            status: WorkflowInstanceStatus::Running,
+            ..Default::default()
+        };
+        workflow_instance.spec.node_specs.push(NodeSpec {
+            id: parent_id,
+            batch_strategies: vec![BatchStrategy {
+                failure_policy,
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let parent = NodeInstance {
+            id: parent_id,
+            name: "parent".to_string(),
+            is_parent: true,
+            flow_instance_id: workflow_instance.id,
+            status: NodeInstanceStatus::Running,
+            ..Default::default()
+        };
+        let sub_node = |i: usize, status: NodeInstanceStatus| NodeInstance {
+            id: Uuid::new_v4(),
+            name: format!("parent_sub_task_{i}"),
+            batch_parent_id: Some(parent_id),
+            batch_index: Some(i),
+            status,
+            ..parent.clone()
+        };
+        let finished = sub_node(0, NodeInstanceStatus::Finished);
+        let failed = sub_node(1, NodeInstanceStatus::Running);
+        let failed_id = failed.id;
+
+        let mut node_instance_repository = MockNodeInstanceRepository::new();
+        node_instance_repository.expect_take_quota_reservation().returning(|_| Ok(None));
+        let node_instances = [parent, finished.clone(), failed.clone()];
+        node_instance_repository.expect_get_by_id().returning(move |id| {
+            Ok(node_instances.iter().find(|el| el.id.to_string().eq(id)).unwrap().clone())
+        });
+        node_instance_repository
+            .expect_get_node_sub_node_instances()
+            .returning(move |_| {
+                Ok(vec![
+                    finished.clone(),
+                    NodeInstance {
+                        status: NodeInstanceStatus::Error,
+                        ..failed.clone()
+                    },
+                ])
+            });
+        node_instance_repository
+            .expect_update()
+            .withf(move |el| el.id.ne(&parent_id))
+            .times(1)
+            .returning(Ok);
+        node_instance_repository
+            .expect_update()
+            .withf(move |el| {
+                el.id.eq(&parent_id)
+                    && el.log.as_ref().unwrap().contains("1 of 2 sub tasks failed.")
+                    && el.status.eq(&if expect_tolerated {
+                        NodeInstanceStatus::Finished
+                    } else {
+                        NodeInstanceStatus::Error
+                    })
+            })
+            .times(1)
+            .returning(Ok);
+        node_instance_repository.expect_save_changed().returning(|| Ok(true));
+        let mut workflow_instance_repository = MockWorkflowInstanceRepository::new();
+        workflow_instance_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(workflow_instance.clone()));
+        workflow_instance_repository
+            .expect_update()
+            .withf(|el| matches!(el.status, WorkflowInstanceStatus::Error))
+            .times(usize::from(!expect_tolerated))
+            .returning(Ok);
+        workflow_instance_repository.expect_save_changed().returning(|| Ok(true));
+        let mut schedule_service = MockWorkflowScheduleService::new();
+        schedule_service
+            .expect_schedule_next_nodes()
+            .withf(move |el| matches!(el, ScheduleMode::NodeInstanceId(id) if id.eq(&parent_id)))
+            .times(usize::from(expect_tolerated))
+            .returning(|_| Ok(()));
+
+        let receiver = WorkflowStatusReceiverServiceBuilder::default()
+            .node_instance_repository(Arc::new(node_instance_repository))
+            .workflow_instance_repository(Arc::new(workflow_instance_repository))
+            .schedule_service(Arc::new(schedule_service))
+            .quota_service(Arc::new(quota_service()))
+            .mq_producer(Arc::new(NoopProducer))
+            .bill_topic(String::default())
+            .build()
+            .unwrap();
+        let result = TaskResult {
+            id: failed_id,
+            status: TaskResultStatus::Failed,
+            message: "Job exit with 1".to_string(),
+            used_resources: None,
+        };
+        (receiver, result)
+    }
+
+    #[tokio::test]
+    async fn test_batch_failure_within_tolerance() {
+        let (receiver, result) =
+            batch_receiver(BatchFailurePolicy::TolerateCount { count: 1 }, true);
+        receiver.receive_node_status(result).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_batch_failure_fails_fast() {
+        let (receiver, result) = batch_receiver(BatchFailurePolicy::FailFast, false);
+        receiver.receive_node_status(result).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_finished_node_resubmits_queued_nodes() {
+        let workflow_instance = WorkflowInstance {
+            status: WorkflowInstanceStatus::Running,
+            ..Default::default()
+        };
+        let node_instance = NodeInstance {
+            id: Uuid::new_v4(),
+            flow_instance_id: workflow_instance.id,
+            status: NodeInstanceStatus::Running,
+            quota_reservation: Some(QuotaReservation {
+                cores: 4,
+                memory: 0,
+            }),
+            ..Default::default()
+        };
+        let queued_id = Uuid::new_v4();
+
+        let mut node_instance_repository = MockNodeInstanceRepository::new();
+        let node_instance_clone = node_instance.clone();
+        node_instance_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(node_instance_clone.clone()));
+        node_instance_repository
+            .expect_update()
+            .withf(|el| el.quota_reservation.is_none())
+            .times(1)
+            .returning(Ok);
+        node_instance_repository.expect_save_changed().returning(|| Ok(true));
+        let reservation = node_instance.quota_reservation.clone();
+        node_instance_repository
+            .expect_take_quota_reservation()
+            .times(1)
+            .returning(move |_| Ok(reservation.clone()));
+        node_instance_repository
+            .expect_get_user_queued_node_instances()
+            .returning(move |_| {
+                Ok(vec![NodeInstance {
+                    id: queued_id,
+                    status: NodeInstanceStatus::Queued,
+                    ..Default::default()
+                }])
+            });
+        let mut workflow_instance_repository = MockWorkflowInstanceRepository::new();
+        workflow_instance_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(workflow_instance.clone()));
+        workflow_instance_repository.expect_save_changed().returning(|| Ok(true));
+        let mut schedule_service = MockWorkflowScheduleService::new();
+        schedule_service.expect_schedule_next_nodes().returning(|_| Ok(()));
+        schedule_service
+            .expect_retry_node()
+            .withf(move |el| el.eq(&queued_id))
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut quota_service = MockQuotaService::new();
+        quota_service
+            .expect_release()
+            .withf(|_, reservation, used_storage| reservation.cores == 4 && *used_storage == 1024)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let receiver = WorkflowStatusReceiverServiceBuilder::default()
+            .node_instance_repository(Arc::new(node_instance_repository))
+            .workflow_instance_repository(Arc::new(workflow_instance_repository))
+            .schedule_service(Arc::new(schedule_service))
+            .quota_service(Arc::new(quota_service))
+            .mq_producer(Arc::new(NoopProducer))
+            .bill_topic(String::default())
+            .build()
+            .unwrap();
+        receiver
+            .receive_node_status(TaskResult {
+                id: node_instance.id,
+                status: TaskResultStatus::Success,
+                message: String::default(),
+                used_resources: Some(TaskUsedResource {
+                    storage: 1024,
+                    ..Default::default()
+                }),
+            })
+            .await
+            .unwrap();
+    }
 }
@@ -0,0 +1,168 @@
+use crate::prelude::*;
+
+/// 任务派发服务
+#[derive(Builder)]
+pub struct TaskDispatchService {
+    /// 集群选择服务
+    cluster_selection_service: Arc<dyn IClusterSelectionService + Send + Sync>,
+    /// 预算服务
+    budget_service: Arc<dyn IBudgetService + Send + Sync>,
+    /// 用户配额服务
+    quota_service: Arc<dyn IQuotaService + Send + Sync>,
+    /// 任务分发服务
+    task_distribution_service: Arc<dyn ITaskDistributionService + Send + Sync>,
+    /// 节点实例仓储
+    node_instance_repository: Arc<dyn INodeInstanceRepository + Send + Sync>,
+    /// 工作流实例仓储
+    workflow_instance_repository: Arc<dyn IWorkflowInstanceRepository + Send + Sync>,
+}
+
+#[async_trait]
+impl ITaskDispatchService for TaskDispatchService {
+    async fn dispatch(&self, node_spec: &NodeSpec, task: &Task) -> anyhow::Result<()> {
+        let decision = self.cluster_selection_service.select_cluster(node_spec, task).await?;
+        let mut node_instance =
+            self.node_instance_repository.get_by_id(&task.id.to_string()).await?;
+        node_instance.cluster_id = Some(decision.cluster_id);
+        node_instance.schedule_reason = Some(decision.reason);
+        match self.budget_service.check(&node_instance, decision.cluster_id, task).await? {
+            BudgetDecision::Allowed => {}
+            BudgetDecision::Paused(reason) => {
+                node_instance.status = NodeInstanceStatus::Queued;
+                node_instance.log = Some(reason);
+                let mut workflow_instance = self
+                    .workflow_instance_repository
+                    .get_by_id(&node_instance.flow_instance_id.to_string())
+                    .await?;
+                workflow_instance.status = WorkflowInstanceStatus::Paused;
+                self.node_instance_repository.update(node_instance).await?;
+                self.workflow_instance_repository.update(workflow_instance).await?;
+                self.node_instance_repository.save_changed().await?;
+                return self.workflow_instance_repository.save_changed().await.map(|_| ());
+            }
+            BudgetDecision::Refused(reason) => {
+                node_instance.status = NodeInstanceStatus::Error;
+                node_instance.log = Some(reason.to_owned());
+                self.node_instance_repository.update(node_instance).await?;
+                self.node_instance_repository.save_changed().await?;
+                anyhow::bail!(reason);
+            }
+        }
+        match self.quota_service.acquire(&node_instance, decision.cluster_id, task).await? {
+            QuotaDecision::Granted(reservation) => node_instance.quota_reservation = reservation,
+            QuotaDecision::Queued(reason) => {
+                node_instance.status = NodeInstanceStatus::Queued;
+                node_instance.log = Some(reason);
+                self.node_instance_repository.update(node_instance).await?;
+                return self.node_instance_repository.save_changed().await.map(|_| ());
+            }
+            QuotaDecision::Rejected(reason) => {
+                node_instance.status = NodeInstanceStatus::Error;
+                node_instance.log = Some(reason.to_owned());
+                self.node_instance_repository.update(node_instance).await?;
+                self.node_instance_repository.save_changed().await?;
+                anyhow::bail!(reason);
+            }
+        }
+        self.node_instance_repository.update(node_instance.to_owned()).await?;
+        self.node_instance_repository.save_changed().await?;
+        if let Err(e) = self.task_distribution_service.send_task(task, decision.cluster_id).await {
+            // 任务没有下发，不会收到结束消息，需要在此释放占用的配额
+            if let Some(reservation) =
+                self.node_instance_repository.take_quota_reservation(task.id).await?
+            {
+                self.quota_service.release(&node_instance, &reservation, 0).await?;
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+
+    fn service(
+        quota_decision: QuotaDecision,
+        send_succeeds: bool,
+        quota_service: MockQuotaService,
+    ) -> TaskDispatchService {
+        let mut cluster_selection_service = MockClusterSelectionService::new();
+        cluster_selection_service.expect_select_cluster().returning(|_, _| {
+            Ok(ClusterDecision {
+                cluster_id: Uuid::new_v4(),
+                reason: String::default(),
+            })
+        });
+        let mut budget_service = MockBudgetService::new();
+        budget_service.expect_check().returning(|_, _, _| Ok(BudgetDecision::Allowed));
+        let mut quota_service = quota_service;
+        quota_service
+            .expect_acquire()
+            .returning(move |_, _, _| Ok(quota_decision.clone()));
+        let mut task_distribution_service = MockTaskDistributionService::new();
+        task_distribution_service.expect_send_task().returning(move |_, _| {
+            if send_succeeds {
+                Ok(())
+            } else {
+                anyhow::bail!("broker unavailable")
+            }
+        });
+        let mut node_instance_repository = MockNodeInstanceRepository::new();
+        node_instance_repository
+            .expect_get_by_id()
+            .returning(|_| Ok(NodeInstance::default()));
+        node_instance_repository.expect_update().returning(Ok);
+        node_instance_repository.expect_save_changed().returning(|| Ok(true));
+        node_instance_repository
+            .expect_take_quota_reservation()
+            .returning(|_| Ok(Some(QuotaReservation::default())));
+        TaskDispatchServiceBuilder::default()
+            .cluster_selection_service(Arc::new(cluster_selection_service))
+            .budget_service(Arc::new(budget_service))
+            .quota_service(Arc::new(quota_service))
+            .task_distribution_service(Arc::new(task_distribution_service))
+            .node_instance_repository(Arc::new(node_instance_repository))
+            .workflow_instance_repository(Arc::new(MockWorkflowInstanceRepository::new()))
+            .build()
+            .unwrap()
+    }
+
+    fn task() -> Task {
+        Task {
+            id: Uuid::new_v4(),
+            command: TaskCommand::Start,
+            body: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_failure_releases_quota() {
+        let reservation = QuotaReservation {
+            cores: 4,
+            memory: 0,
+        };
+        let mut quota_service = MockQuotaService::new();
+        quota_service.expect_release().times(1).returning(|_, _, _| Ok(()));
+        let service = service(
+            QuotaDecision::Granted(Some(reservation)),
+            false,
+            quota_service,
+        );
+        assert!(service.dispatch(&NodeSpec::default(), &task()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queued_task_is_not_sent() {
+        let mut quota_service = MockQuotaService::new();
+        quota_service.expect_release().never();
+        let service = service(
+            QuotaDecision::Queued("quota exhausted".to_string()),
+            false,
+            quota_service,
+        );
+        service.dispatch(&NodeSpec::default(), &task()).await.unwrap();
+    }
+}
@@ -1,8 +1,12 @@
 use crate::prelude::*;
 use alice_architecture::exceptions::GenericError;
 use alice_architecture::repository::{IDBRepository, IReadOnlyRepository};
+use std::collections::{hash_map::Entry, HashMap};
 type Exception = GenericError<WorkflowDraftException>;
 
+/// 自定义节点的最大嵌套层数
+const MAX_CUSTOM_NODE_DEPTH: usize = 8;
+
 #[derive(Builder)]
 pub struct WorkflowService {
     workflow_draft_repository: Arc<dyn IReadOnlyRepository<WorkflowDraft> + Send + Sync>,
@@ -10,14 +14,16 @@ pub struct WorkflowService {
     node_instance_repository: Arc<dyn INodeInstanceRepository + Send + Sync>,
     file_metadata_repository: Arc<dyn IReadOnlyRepository<FileMeta> + Send + Sync>,
     workflow_schedule_service: Arc<dyn IWorkflowScheduleService + Send + Sync>,
+    cluster_repository: Arc<dyn IClusterRepository + Send + Sync>,
+    custom_node_repository: Arc<dyn IReadOnlyRepository<CustomNode> + Send + Sync>,
 }
 
 #[async_trait]
 impl IWorkflowService for WorkflowService {
     async fn submit_workflow(&self, id: Uuid) -> anyhow::Result<Uuid> {
-        let workflow_draft = self.workflow_draft_repository.get_by_id(&id.to_string()).await?;
-        let spec = &workflow_draft.spec;
-        self.validate_workflow_draft(spec).await?;
+        let mut workflow_draft = self.workflow_draft_repository.get_by_id(&id.to_string()).await?;
+        self.expand_custom_nodes(&mut workflow_draft.spec).await?;
+        self.validate_workflow_draft(&workflow_draft.spec).await?;
         let workflow_instance = WorkflowInstance::from(workflow_draft);
         let node_instances = workflow_instance.parse_node_instances().await?;
         self.workflow_instance_repository.insert(workflow_instance.clone()).await?;
@@ -29,7 +35,8 @@ impl IWorkflowService for WorkflowService {
     }
 
     async fn validate(&self, id: Uuid) -> anyhow::Result<()> {
-        let workflow_draft = self.workflow_draft_repository.get_by_id(&id.to_string()).await?;
+        let mut workflow_draft = self.workflow_draft_repository.get_by_id(&id.to_string()).await?;
+        self.expand_custom_nodes(&mut workflow_draft.spec).await?;
         self.validate_workflow_draft(&workflow_draft.spec).await
     }
 
@@ -77,6 +84,27 @@ impl IWorkflowService for WorkflowService {
 }
 
 impl WorkflowService {
+    /// 逐层展开工作流草稿中引用的自定义节点，直到不再含有自定义节点
+    async fn expand_custom_nodes(&self, data: &mut WorkflowDraftSpec) -> anyhow::Result<()> {
+        let mut custom_nodes = HashMap::new();
+        for _ in 0..MAX_CUSTOM_NODE_DEPTH {
+            let custom_node_ids = data.custom_node_ids();
+            if custom_node_ids.is_empty() {
+                return Ok(());
+            }
+            for id in custom_node_ids {
+                if let Entry::Vacant(entry) = custom_nodes.entry(id) {
+                    entry.insert(self.custom_node_repository.get_by_id(&id.to_string()).await?);
+                }
+            }
+            data.expand_custom_nodes(&custom_nodes)?;
+        }
+        if !data.custom_node_ids().is_empty() {
+            bail!("Custom nodes are nested more than {MAX_CUSTOM_NODE_DEPTH} levels.");
+        }
+        Ok(())
+    }
+
     /// 验证工作流草稿逻辑
     ///
     /// 须同时满足以下条件：
@@ -86,7 +114,18 @@ impl WorkflowService {
     /// 5. MatchRegex 类型批量输入必须等于 1
     /// 6. 调度策略 Manual 和 Prefer 至少选一个集群
     /// 7. 所有输入文件必须在 FileMeta 表中存在
+    ///
+    /// 在此之前先对依赖图做静态分析，一次返回发现的所有问题：
+    /// 节点 id 重复、依赖成环、节点不可达、必需输出未被使用、资源需求没有集群能满足
     async fn validate_workflow_draft(&self, data: &WorkflowDraftSpec) -> anyhow::Result<()> {
+        let clusters = self.cluster_repository.get_all_clusters_with_resource().await?;
+        let mut problems = data.analyze_graph();
+        problems.extend(data.validate_requirements(&clusters));
+        if !problems.is_empty() {
+            return Err(
+                Exception::Specific(WorkflowDraftException::from_problems(problems)).into(),
+            );
+        }
         let relied_input_slots =
             data.validate_related_nodes().await.map_err(Exception::Specific)?;
         data.validate_per_node(relied_input_slots, self.file_metadata_repository.to_owned())
@@ -112,6 +151,10 @@ mod tests {
             .expect_schedule_next_nodes()
             .returning(|_| anyhow::Ok(()));
         let workflow_schedule_service = Arc::new(workflow_schedule_service);
+        let mut cluster_repository = MockClusterRepository::new();
+        cluster_repository
+            .expect_get_all_clusters_with_resource()
+            .returning(|| Ok(vec![]));
         let workflow_service = Arc::new(
             WorkflowServiceBuilder::default()
                 .workflow_draft_repository(json_repository.clone())
@@ -119,6 +162,8 @@ mod tests {
                 .node_instance_repository(json_repository.clone())
                 .file_metadata_repository(json_repository)
                 .workflow_schedule_service(workflow_schedule_service)
+                .cluster_repository(Arc::new(cluster_repository))
+                .custom_node_repository(Arc::new(MockCustomNodeRepository::new()))
                 .build()
                 .unwrap(),
         );
@@ -148,4 +193,145 @@ mod tests {
             .to_owned();
         workflow_service.start_workflow(workflow_instance_id).await.unwrap();
     }
+
+    mockall::mock! {
+        FileMetadataRepository {}
+        #[async_trait]
+        impl IReadOnlyRepository<FileMeta> for FileMetadataRepository {
+            async fn get_by_id(&self, uuid: &str) -> anyhow::Result<FileMeta>;
+            async fn get_all(&self) -> anyhow::Result<Vec<FileMeta>>;
+        }
+    }
+
+    fn node_draft(id: Uuid, requirements: Option<Requirements>) -> NodeDraft {
+        NodeDraft {
+            kind: NodeKind::NoAction,
+            external_id: id,
+            name: id.to_string(),
+            description: String::new(),
+            batch_strategies: None,
+            input_slots: vec![],
+            output_slots: vec![NodeDraftOutputSlot {
+                kind: NodeDraftOutputSlotKind::Text,
+                descriptor: "out".to_string(),
+                description: None,
+                optional: false,
+            }],
+            scheduling_strategy: SchedulingStrategy::Auto,
+            requirements,
+            retry_policy: None,
+            additional_datas: None,
+        }
+    }
+
+    fn relation(from_id: Uuid, to_id: Uuid) -> NodeRelation {
+        NodeRelation {
+            from_id,
+            to_id,
+            ..Default::default()
+        }
+    }
+
+    async fn validate(spec: WorkflowDraftSpec) -> WorkflowDraftException {
+        let mut workflow_draft_repository = MockWorkflowDraftRepository::new();
+        workflow_draft_repository.expect_get_by_id().returning(move |_| {
+            Ok(WorkflowDraft {
+                spec: spec.clone(),
+                ..Default::default()
+            })
+        });
+        let mut cluster_repository = MockClusterRepository::new();
+        cluster_repository.expect_get_all_clusters_with_resource().returning(|| {
+            let id = Uuid::new_v4();
+            Ok(vec![(
+                Cluster {
+                    id: id.to_string(),
+                    enabled: true,
+                    ..Default::default()
+                },
+                Some(ClusterResource {
+                    cluster_id: id,
+                    core_number: 64,
+                    ..Default::default()
+                }),
+            )])
+        });
+        let workflow_service = WorkflowServiceBuilder::default()
+            .workflow_draft_repository(Arc::new(workflow_draft_repository))
+            .workflow_instance_repository(Arc::new(MockWorkflowInstanceRepository::new()))
+            .node_instance_repository(Arc::new(MockNodeInstanceRepository::new()))
+            .file_metadata_repository(Arc::new(MockFileMetadataRepository::new()))
+            .workflow_schedule_service(Arc::new(MockWorkflowScheduleService::new()))
+            .cluster_repository(Arc::new(cluster_repository))
+            .custom_node_repository(Arc::new(MockCustomNodeRepository::new()))
+            .build()
+            .unwrap();
+        match workflow_service
+            .validate(Uuid::new_v4())
+            .await
+            .unwrap_err()
+            .downcast::<Exception>()
+        {
+            Ok(GenericError::Specific(e)) => e,
+            _ => panic!("Expected a workflow draft exception."),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_cycle_and_unreachable_nodes() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let spec = WorkflowDraftSpec {
+            node_drafts: vec![
+                node_draft(a, None),
+                node_draft(b, None),
+                node_draft(c, None),
+            ],
+            node_relations: vec![relation(a, b), relation(b, a), relation(b, c)],
+            ..Default::default()
+        };
+        match validate(spec).await {
+            WorkflowDraftException::Problems { problems } => {
+                assert!(matches!(
+                    &problems[0],
+                    WorkflowDraftException::CyclicDependency { node_ids } if node_ids.eq(&vec![a, b])
+                ));
+                assert!(problems.iter().any(|el| matches!(
+                    el,
+                    WorkflowDraftException::UnreachableNode { node_id } if node_id.eq(&c)
+                )));
+                assert!(problems.iter().any(|el| matches!(
+                    el,
+                    WorkflowDraftException::UnconsumedOutputSlot { node_id, .. } if node_id.eq(&a)
+                )));
+            }
+            e => panic!("Unexpected exception: {e}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_reports_duplicated_ids_and_requirements() {
+        let a = Uuid::new_v4();
+        let requirements = Requirements {
+            cpu_cores: Some(32),
+            node_count: Some(4),
+            ..Default::default()
+        };
+        let spec = WorkflowDraftSpec {
+            node_drafts: vec![node_draft(a, None), node_draft(a, Some(requirements))],
+            ..Default::default()
+        };
+        match validate(spec).await {
+            WorkflowDraftException::Problems { problems } => {
+                assert!(matches!(
+                    problems[0],
+                    WorkflowDraftException::DuplicatedNodeId { id } if id.eq(&a)
+                ));
+                assert!(matches!(
+                    problems[1],
+                    WorkflowDraftException::UnsatisfiableRequirements { cpu_cores: 128, .. }
+                ));
+            }
+            e => panic!("Unexpected exception: {e}"),
+        }
+    }
 }
@@ -0,0 +1,274 @@
+use crate::prelude::*;
+use alice_architecture::repository::IDBRepository;
+use std::collections::{HashMap, HashSet};
+
+/// 工作流模板服务
+#[derive(Builder)]
+pub struct WorkflowTemplateService {
+    workflow_template_repository: Arc<dyn IWorkflowTemplateRepository + Send + Sync>,
+    workflow_draft_repository: Arc<dyn IDBRepository<WorkflowDraft> + Send + Sync>,
+}
+
+#[async_trait]
+impl IWorkflowTemplateService for WorkflowTemplateService {
+    async fn publish(
+        &self,
+        user_id: Uuid,
+        command: PublishTemplateCommand,
+    ) -> anyhow::Result<WorkflowTemplate> {
+        let draft = self.workflow_draft_repository.get_by_id(&command.draft_id.to_string()).await?;
+        if let TemplateVisibility::Group { name } = &command.visibility {
+            let groups = self.workflow_template_repository.get_user_groups(user_id).await?;
+            if !groups.contains(name) {
+                bail!("User {user_id} can't share templates within group {name}.");
+            }
+        }
+        let previous = match command.family_id {
+            Some(family_id) => {
+                let versions =
+                    self.workflow_template_repository.get_all_by_family_id(family_id).await?;
+                let latest = versions
+                    .into_iter()
+                    .max_by_key(|el| el.version)
+                    .ok_or(anyhow!("There is no template with family id {family_id}."))?;
+                if latest.user_id != user_id {
+                    bail!("Template {family_id} isn't published by user {user_id}.");
+                }
+                Some(latest)
+            }
+            None => None,
+        };
+        let template = WorkflowTemplate::publish(
+            draft,
+            user_id,
+            command.visibility,
+            command.parameters,
+            previous.as_ref(),
+        )?;
+        let template = self.workflow_template_repository.insert(template).await?;
+        self.workflow_template_repository.save_changed().await?;
+        Ok(template)
+    }
+
+    async fn search(
+        &self,
+        user_id: Uuid,
+        keyword: Option<String>,
+    ) -> anyhow::Result<Vec<WorkflowTemplate>> {
+        let groups = self.workflow_template_repository.get_user_groups(user_id).await?;
+        let mut templates =
+            self.workflow_template_repository.search(user_id, groups, keyword).await?;
+        templates.sort_by_key(|el| std::cmp::Reverse(el.version));
+        let mut families = HashSet::new();
+        templates.retain(|el| families.insert(el.family_id));
+        templates.sort_by_key(|el| std::cmp::Reverse(el.created_time));
+        Ok(templates)
+    }
+
+    async fn get_template(&self, user_id: Uuid, id: Uuid) -> anyhow::Result<WorkflowTemplate> {
+        let template = self.workflow_template_repository.get_by_id(&id.to_string()).await?;
+        let groups = self.workflow_template_repository.get_user_groups(user_id).await?;
+        if !template.visible_to(user_id, &groups) {
+            bail!("Template {id} isn't visible to user {user_id}.");
+        }
+        Ok(template)
+    }
+
+    async fn get_versions(
+        &self,
+        user_id: Uuid,
+        family_id: Uuid,
+    ) -> anyhow::Result<Vec<WorkflowTemplate>> {
+        let groups = self.workflow_template_repository.get_user_groups(user_id).await?;
+        let mut versions =
+            self.workflow_template_repository.get_all_by_family_id(family_id).await?;
+        versions.retain(|el| el.visible_to(user_id, &groups));
+        Ok(versions)
+    }
+
+    async fn instantiate(
+        &self,
+        user_id: Uuid,
+        id: Uuid,
+        name: Option<String>,
+        arguments: HashMap<String, TemplateArgument>,
+    ) -> anyhow::Result<Uuid> {
+        let template = self.get_template(user_id, id).await?;
+        let draft = template.instantiate(name, arguments)?;
+        let draft = self.workflow_draft_repository.insert(draft).await?;
+        self.workflow_draft_repository.save_changed().await?;
+        Ok(draft.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::prelude::*;
+    use std::sync::Mutex;
+
+    fn draft() -> WorkflowDraft {
+        let slot = |descriptor: &str| NodeInputSlot {
+            kind: NodeInputSlotKind::Text {
+                contents: Some(vec![Uuid::new_v4()]),
+                rule: TextInputSlotRule::default(),
+            },
+            optional: false,
+            descriptor: descriptor.to_string(),
+            description: None,
+        };
+        WorkflowDraft {
+            id: Uuid::new_v4(),
+            name: "draft".to_string(),
+            description: String::default(),
+            logo: None,
+            spec: WorkflowDraftSpec {
+                node_drafts: vec![NodeDraft {
+                    kind: NodeKind::NoAction,
+                    external_id: Uuid::new_v4(),
+                    name: "node".to_string(),
+                    description: String::default(),
+                    batch_strategies: None,
+                    input_slots: vec![slot("input"), slot("config")],
+                    output_slots: vec![],
+                    scheduling_strategy: SchedulingStrategy::default(),
+                    requirements: None,
+                    retry_policy: None,
+                    additional_datas: None,
+                }],
+                ..Default::default()
+            },
+        }
+    }
+
+    fn parameter(node_external_id: Uuid, descriptor: &str, optional: bool) -> TemplateParameter {
+        TemplateParameter {
+            name: descriptor.to_string(),
+            description: None,
+            node_external_id,
+            descriptor: descriptor.to_string(),
+            optional,
+        }
+    }
+
+    fn text_contents(spec: &WorkflowDraftSpec, descriptor: &str) -> Option<Vec<Uuid>> {
+        match &spec.node_drafts[0].input_slot(descriptor).kind {
+            NodeInputSlotKind::Text { contents, .. } => contents.to_owned(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publish_and_instantiate() {
+        let owner = Uuid::new_v4();
+        let draft = draft();
+        let node_id = draft.spec.node_drafts[0].external_id;
+
+        let mut workflow_draft_repository = MockWorkflowDraftRepository::new();
+        let returned = draft.to_owned();
+        workflow_draft_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(returned.to_owned()));
+        let inserted = Arc::new(Mutex::new(vec![]));
+        let inserted_clone = inserted.clone();
+        workflow_draft_repository.expect_insert().returning(move |el| {
+            inserted_clone.lock().unwrap().push(el.to_owned());
+            Ok(el)
+        });
+        workflow_draft_repository.expect_save_changed().returning(|| Ok(true));
+        let mut workflow_template_repository = MockWorkflowTemplateRepository::new();
+        workflow_template_repository.expect_get_user_groups().returning(|_| Ok(vec![]));
+        workflow_template_repository.expect_insert().returning(Ok);
+        workflow_template_repository.expect_save_changed().returning(|| Ok(true));
+        let service = WorkflowTemplateServiceBuilder::default()
+            .workflow_template_repository(Arc::new(workflow_template_repository))
+            .workflow_draft_repository(Arc::new(workflow_draft_repository))
+            .build()
+            .unwrap();
+
+        let template = service
+            .publish(
+                owner,
+                PublishTemplateCommand {
+                    draft_id: draft.id,
+                    family_id: None,
+                    visibility: TemplateVisibility::Public,
+                    parameters: vec![
+                        parameter(node_id, "input", false),
+                        parameter(node_id, "config", true),
+                    ],
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(template.version, 1);
+        assert_eq!(template.family_id, template.id);
+        assert_eq!(text_contents(&template.spec, "input"), None);
+        assert_eq!(text_contents(&template.spec, "config"), None);
+        assert!(template.visible_to(Uuid::new_v4(), &[]));
+
+        assert!(template.instantiate(None, HashMap::new()).is_err());
+        let text_id = Uuid::new_v4();
+        let arguments = HashMap::from([(
+            "input".to_string(),
+            TemplateArgument::Text {
+                contents: vec![text_id],
+            },
+        )]);
+        let draft = template.instantiate(Some("mine".to_string()), arguments).unwrap();
+        assert_eq!(draft.name, "mine");
+        assert_eq!(text_contents(&draft.spec, "input"), Some(vec![text_id]));
+        assert_eq!(text_contents(&draft.spec, "config"), None);
+        let config_id = Uuid::new_v4();
+        let arguments = HashMap::from([
+            (
+                "input".to_string(),
+                TemplateArgument::Text {
+                    contents: vec![text_id],
+                },
+            ),
+            (
+                "config".to_string(),
+                TemplateArgument::Text {
+                    contents: vec![config_id],
+                },
+            ),
+        ]);
+        let draft = template.instantiate(None, arguments).unwrap();
+        assert_eq!(text_contents(&draft.spec, "config"), Some(vec![config_id]));
+
+        let file_argument = HashMap::from([(
+            "input".to_string(),
+            TemplateArgument::File { contents: vec![] },
+        )]);
+        assert!(template.instantiate(None, file_argument).is_err());
+        assert!(inserted.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_private_template_is_hidden() {
+        let template = WorkflowTemplate {
+            user_id: Uuid::new_v4(),
+            visibility: TemplateVisibility::Group {
+                name: "lab".to_string(),
+            },
+            ..Default::default()
+        };
+        let returned = template.to_owned();
+        let mut workflow_template_repository = MockWorkflowTemplateRepository::new();
+        workflow_template_repository
+            .expect_get_by_id()
+            .returning(move |_| Ok(returned.to_owned()));
+        workflow_template_repository
+            .expect_get_user_groups()
+            .returning(|_| Ok(vec!["other".to_string()]));
+        let service = WorkflowTemplateServiceBuilder::default()
+            .workflow_template_repository(Arc::new(workflow_template_repository))
+            .workflow_draft_repository(Arc::new(MockWorkflowDraftRepository::new()))
+            .build()
+            .unwrap();
+        assert!(service.get_template(Uuid::new_v4(), template.id).await.is_err());
+        assert!(service.get_template(template.user_id, template.id).await.is_ok());
+        assert!(template.visible_to(Uuid::new_v4(), &["other".to_string(), "lab".to_string()]));
+    }
+}
@@ -48,3 +48,22 @@ pub struct Requirements {
     /// 定时终止 (utc 0 时区 时间戳)
     pub stop_time: Option<usize>,
 }
+
+#[derive(JsonSchema, Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+/// 并行启动器
+pub enum Launcher {
+    /// 不使用启动器直接执行，适用于串行或仅使用 OpenMP 的程序
+    None,
+    /// mpirun
+    #[default]
+    Mpirun,
+    /// srun
+    Srun,
+    /// mpiexec
+    Mpiexec,
+    /// 自定义启动命令模板
+    /// 可使用 `{{ranks}}`（进程数）、`{{threads}}`（每进程线程数）与 `{{command}}`（用例命令）占位符，
+    /// 未包含 `{{command}}` 时用例命令追加在模板之后
+    Custom(String),
+}
@@ -31,6 +31,9 @@ pub struct UsecaseSpec {
     pub std_err_validator: Option<OutValidator>,
     /// 需要的物理资源
     pub requirements: Option<Requirements>,
+    /// 并行启动器，未指定时使用 mpirun
+    #[serde(default)]
+    pub launcher: Launcher,
     /// 提供描述的元数据
     #[serde(default)]
     pub metadata: Metadata,
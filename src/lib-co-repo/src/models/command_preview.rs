@@ -9,6 +9,8 @@ pub struct CommandPreview {
     pub software_facility: FacilityKind,
     /// 命令名称
     pub command_name: String,
+    /// 并行启动器
+    pub launcher: Launcher,
     /// 参数排序、参数内容及占位符对应的输入插槽或模板描述符
     pub argument_formats_sorts: BTreeMap<usize, FormatFillPreview>,
     /// 环境变量键名、参数内容及占位符对应的输入插槽或模板描述符
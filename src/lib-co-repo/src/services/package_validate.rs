@@ -667,9 +667,16 @@ impl IPackageValidateService for PackageValidateService {
                 .collect::<BTreeMap<usize, FormatFillPreview>>()
         }
 
+        if let Launcher::Custom(template) = &usecase_spec.launcher {
+            if template.trim().is_empty() {
+                anyhow::bail!("custom launcher template must not be empty.");
+            }
+        }
+
         Ok(CommandPreview {
             software_facility: FacilityKind::from(software_spec),
             command_name: usecase_spec.command_file,
+            launcher: usecase_spec.launcher,
             argument_formats_sorts: argument_formats_sorts2,
             environment_formats,
             templates_kv_map,
@@ -0,0 +1,76 @@
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+/// Hash algorithm.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase", tag = "hashAlgorithm", content = "hash")]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    /// Same as `sha256sum` and S3 SHA-256 checksums.
+    Sha256,
+    /// 64 bits XXH3, same as `xxh3sum`.
+    Xxh3,
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blake3 => write!(f, "blake3"),
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Xxh3 => write!(f, "xxh3"),
+        }
+    }
+}
+
+impl std::str::FromStr for HashAlgorithm {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "blake3" => Ok(Self::Blake3),
+            "sha256" => Ok(Self::Sha256),
+            "xxh3" => Ok(Self::Xxh3),
+            _ => bail!("{s} can't be transformed to HashAlgorithm"),
+        }
+    }
+}
+
+impl HashAlgorithm {
+    /// Create an incremental hasher of this algorithm.
+    pub fn hasher(&self) -> FileHasher {
+        match self {
+            Self::Blake3 => FileHasher::Blake3(Box::default()),
+            Self::Sha256 => FileHasher::Sha256(sha2::Sha256::new()),
+            Self::Xxh3 => FileHasher::Xxh3(Box::default()),
+        }
+    }
+}
+
+/// Incremental hasher, hashes are formatted as uppercase hex.
+pub enum FileHasher {
+    Blake3(Box<blake3::Hasher>),
+    Sha256(sha2::Sha256),
+    Xxh3(Box<xxhash_rust::xxh3::Xxh3>),
+}
+
+impl FileHasher {
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Xxh3(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> String {
+        match self {
+            Self::Blake3(hasher) => hasher.finalize().to_hex().to_uppercase(),
+            Self::Sha256(hasher) => format!("{:X}", hasher.finalize()),
+            Self::Xxh3(hasher) => format!("{:016X}", hasher.digest()),
+        }
+    }
+}